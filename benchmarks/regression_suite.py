@@ -0,0 +1,150 @@
+"""pytest-benchmark regression suite for rapsqlite.
+
+Unlike ``benchmark_suite.py`` (one-off comparisons with aiosqlite/sqlite3), these
+benchmarks are meant to be saved and compared across commits so that changes
+such as row-conversion refactors show up as regressions:
+
+    pytest benchmarks/regression_suite.py --benchmark-autosave
+    # ... make changes, rebuild ...
+    pytest benchmarks/regression_suite.py --benchmark-compare \
+        --benchmark-compare-fail=mean:10%
+
+Requires ``pip install pytest-benchmark``. The Rust-level counterparts live in
+``crates/rapsqlite-core/benches`` (``cargo bench -p rapsqlite-core``).
+"""
+
+import asyncio
+import os
+import sys
+import tempfile
+
+import pytest
+
+pytest.importorskip("pytest_benchmark")
+
+import rapsqlite  # noqa: E402
+
+ROWS = 10_000
+
+
+def cleanup_db(test_db: str) -> None:
+    """Helper to clean up database file."""
+    for suffix in ("", "-wal", "-shm"):
+        path = test_db + suffix
+        if os.path.exists(path):
+            try:
+                os.unlink(path)
+            except (PermissionError, OSError):
+                if sys.platform != "win32":
+                    raise
+
+
+@pytest.fixture
+def loop():
+    """A dedicated event loop; pytest-benchmark times synchronous callables."""
+    loop = asyncio.new_event_loop()
+    try:
+        yield loop
+    finally:
+        loop.close()
+
+
+@pytest.fixture
+def db_path():
+    with tempfile.NamedTemporaryFile(suffix=".db", delete=False) as f:
+        path = f.name
+    try:
+        yield path
+    finally:
+        cleanup_db(path)
+
+
+def _run(loop, method, *args):
+    """Run an async connection method to completion on ``loop``.
+
+    Native methods need a running loop when called, so the call happens inside
+    a coroutine.
+    """
+
+    async def call():
+        return await method(*args)
+
+    return loop.run_until_complete(call())
+
+
+def _rows(count):
+    return [(i, f"name-{i}", i * 0.5, bytes([i % 256]) * 32) for i in range(count)]
+
+
+async def _populate(conn, count):
+    await conn.execute(
+        "CREATE TABLE rows (id INTEGER PRIMARY KEY, name TEXT, score REAL, data BLOB)"
+    )
+    await conn.execute_many("INSERT INTO rows VALUES (?, ?, ?, ?)", _rows(count))
+
+
+def test_insert_throughput(benchmark, loop, db_path):
+    """execute_many of 1000 rows inside a transaction."""
+    conn = rapsqlite.Connection(db_path)
+    loop.run_until_complete(_populate(conn, 0))
+    batch = _rows(1000)
+
+    async def insert_batch():
+        async with conn.transaction():
+            await conn.execute("DELETE FROM rows")
+            await conn.execute_many("INSERT INTO rows VALUES (?, ?, ?, ?)", batch)
+
+    try:
+        benchmark(_run, loop, insert_batch)
+    finally:
+        _run(loop, conn.close)
+
+
+@pytest.mark.parametrize("rows", [10, 100, 1_000, 10_000])
+def test_fetch_all_latency(benchmark, loop, db_path, rows):
+    """fetch_all latency by result size (includes conversion to Python rows)."""
+    conn = rapsqlite.Connection(db_path)
+    loop.run_until_complete(_populate(conn, ROWS))
+    query = "SELECT id, name, score, data FROM rows LIMIT ?"
+
+    try:
+        result = benchmark(_run, loop, conn.fetch_all, query, [rows])
+        assert len(result) == rows
+    finally:
+        _run(loop, conn.close)
+
+
+@pytest.mark.parametrize("udf", [False, True], ids=["builtin", "python_udf"])
+def test_udf_overhead(benchmark, loop, db_path, udf):
+    """Calling a Python UDF per row compared with the equivalent builtin."""
+    conn = rapsqlite.Connection(db_path)
+    loop.run_until_complete(_populate(conn, 1_000))
+    if udf:
+        _run(loop, conn.create_function, "py_upper", 1, lambda s: s.upper())
+    query = f"SELECT {'py_upper' if udf else 'upper'}(name) FROM rows"
+
+    try:
+        result = benchmark(_run, loop, conn.fetch_all, query)
+        assert result[1] == ["NAME-1"]
+    finally:
+        _run(loop, conn.close)
+
+
+@pytest.mark.parametrize("pool_size", [1, 4])
+def test_pool_contention(benchmark, loop, db_path, pool_size):
+    """16 concurrent fetches sharing one connection's pool."""
+    setup = rapsqlite.Connection(db_path)
+    loop.run_until_complete(_populate(setup, ROWS))
+    _run(loop, setup.close)
+
+    conn = rapsqlite.Connection(db_path, pragmas={"journal_mode": "WAL"})
+    conn.pool_size = pool_size
+    query = "SELECT count(*) FROM rows WHERE score > ?"
+
+    async def contend():
+        await asyncio.gather(*(conn.fetch_one(query, [i * 100.0]) for i in range(16)))
+
+    try:
+        benchmark(_run, loop, contend)
+    finally:
+        _run(loop, conn.close)
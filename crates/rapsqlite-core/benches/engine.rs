@@ -0,0 +1,201 @@
+//! Engine benchmarks: insert throughput, fetch_all latency by result size and
+//! pool contention. Python-side costs (row conversion to Python objects, UDF
+//! calls) are covered by `benchmarks/regression_suite.py`.
+//!
+//! Run with `cargo bench -p rapsqlite-core`; compare against a saved baseline
+//! with `-- --save-baseline main` / `-- --baseline main`.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use rapsqlite_core::query::{
+    bind_and_execute, bind_and_execute_on_connection, bind_and_fetch_all, bind_and_fetch_one,
+};
+use rapsqlite_core::{open_pool, PoolOptions, SqliteParam};
+use sqlx::SqlitePool;
+use std::path::PathBuf;
+use tokio::runtime::Runtime;
+
+const ROWS: i64 = 10_000;
+
+struct BenchDb {
+    path: PathBuf,
+}
+
+impl BenchDb {
+    fn new(name: &str) -> Self {
+        let path =
+            std::env::temp_dir().join(format!("rapsqlite-bench-{name}-{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        std::fs::File::create(&path).expect("create benchmark database");
+        BenchDb { path }
+    }
+
+    async fn pool(&self, max_connections: usize) -> SqlitePool {
+        let options = PoolOptions {
+            max_connections: Some(max_connections),
+            pragmas: vec![("journal_mode".into(), "WAL".into())],
+            ..PoolOptions::default()
+        };
+        open_pool(self.path.to_str().unwrap(), &options)
+            .await
+            .expect("open pool")
+    }
+}
+
+impl Drop for BenchDb {
+    fn drop(&mut self) {
+        for suffix in ["", "-wal", "-shm"] {
+            let mut path = self.path.clone().into_os_string();
+            path.push(suffix);
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+fn row_params(i: i64) -> Vec<SqliteParam> {
+    vec![
+        SqliteParam::Int(i),
+        SqliteParam::Text(format!("name-{i}")),
+        SqliteParam::Real(i as f64 * 0.5),
+        SqliteParam::Blob(vec![(i % 256) as u8; 32]),
+    ]
+}
+
+async fn create_rows_table(pool: &SqlitePool, rows: i64) {
+    bind_and_execute(
+        "CREATE TABLE rows (id INTEGER PRIMARY KEY, name TEXT, score REAL, data BLOB)",
+        &[],
+        pool,
+    )
+    .await
+    .unwrap();
+    let mut conn = pool.acquire().await.unwrap();
+    bind_and_execute_on_connection("BEGIN", &[], &mut conn)
+        .await
+        .unwrap();
+    for i in 0..rows {
+        bind_and_execute_on_connection(
+            "INSERT INTO rows VALUES (?, ?, ?, ?)",
+            &row_params(i),
+            &mut conn,
+        )
+        .await
+        .unwrap();
+    }
+    bind_and_execute_on_connection("COMMIT", &[], &mut conn)
+        .await
+        .unwrap();
+}
+
+fn bench_insert(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+    let db = BenchDb::new("insert");
+    let pool = rt.block_on(async {
+        let pool = db.pool(1).await;
+        create_rows_table(&pool, 0).await;
+        pool
+    });
+
+    const BATCH: i64 = 1_000;
+    let mut group = c.benchmark_group("insert");
+    group.throughput(Throughput::Elements(BATCH as u64));
+    group.bench_function("transaction_1000_rows", |b| {
+        b.to_async(&rt).iter(|| async {
+            let mut conn = pool.acquire().await.unwrap();
+            // Clearing the table keeps every iteration inserting into the same size
+            bind_and_execute_on_connection("BEGIN", &[], &mut conn)
+                .await
+                .unwrap();
+            bind_and_execute_on_connection("DELETE FROM rows", &[], &mut conn)
+                .await
+                .unwrap();
+            for i in 0..BATCH {
+                bind_and_execute_on_connection(
+                    "INSERT INTO rows VALUES (?, ?, ?, ?)",
+                    &row_params(i),
+                    &mut conn,
+                )
+                .await
+                .unwrap();
+            }
+            bind_and_execute_on_connection("COMMIT", &[], &mut conn)
+                .await
+                .unwrap();
+        })
+    });
+    group.finish();
+}
+
+fn bench_fetch_all(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+    let db = BenchDb::new("fetch");
+    let pool = rt.block_on(async {
+        let pool = db.pool(1).await;
+        create_rows_table(&pool, ROWS).await;
+        pool
+    });
+
+    let mut group = c.benchmark_group("fetch_all");
+    for rows in [10_i64, 100, 1_000, 10_000] {
+        group.throughput(Throughput::Elements(rows as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(rows), &rows, |b, &rows| {
+            b.to_async(&rt).iter(|| async {
+                let result = bind_and_fetch_all(
+                    "SELECT id, name, score, data FROM rows LIMIT ?",
+                    &[SqliteParam::Int(rows)],
+                    &pool,
+                )
+                .await
+                .unwrap();
+                assert_eq!(result.len() as i64, rows);
+            })
+        });
+    }
+    group.finish();
+}
+
+fn bench_pool_contention(c: &mut Criterion) {
+    const TASKS: i64 = 16;
+    let rt = Runtime::new().unwrap();
+    let db = BenchDb::new("pool");
+    rt.block_on(async {
+        let pool = db.pool(1).await;
+        create_rows_table(&pool, ROWS).await;
+        pool.close().await;
+    });
+
+    let mut group = c.benchmark_group("pool_contention");
+    group.throughput(Throughput::Elements(TASKS as u64));
+    for size in [1_usize, 4] {
+        let pool = rt.block_on(db.pool(size));
+        group.bench_with_input(BenchmarkId::new("pool_size", size), &size, |b, _| {
+            b.to_async(&rt).iter(|| async {
+                let tasks: Vec<_> = (0..TASKS)
+                    .map(|i| {
+                        let pool = pool.clone();
+                        tokio::spawn(async move {
+                            bind_and_fetch_one(
+                                "SELECT count(*) FROM rows WHERE score > ?",
+                                &[SqliteParam::Real(i as f64 * 100.0)],
+                                &pool,
+                            )
+                            .await
+                            .unwrap()
+                        })
+                    })
+                    .collect();
+                for task in tasks {
+                    task.await.unwrap();
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_insert,
+    bench_fetch_all,
+    bench_pool_contention
+);
+criterion_main!(benches);
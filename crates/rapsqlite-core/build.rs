@@ -0,0 +1,32 @@
+//! Detects whether the bundled SQLite is built with the counters
+//! `profile::profile` reads.
+//!
+//! `libsqlite3-sys` only compiles `sqlite3_stmt_scanstatus_v2()` and the
+//! `bytecode()` table when `LIBSQLITE3_FLAGS` defines them. The workspace sets
+//! both in `.cargo/config.toml`; builds that don't get the `sqlite_profile`
+//! cfg left out, and profiling reports itself as unsupported instead of
+//! failing to link.
+
+use std::env;
+
+const REQUIRED: [&str; 2] = [
+    "SQLITE_ENABLE_STMT_SCANSTATUS",
+    "SQLITE_ENABLE_BYTECODE_VTAB",
+];
+
+fn main() {
+    println!("cargo::rustc-check-cfg=cfg(sqlite_profile)");
+    println!("cargo:rerun-if-env-changed=LIBSQLITE3_FLAGS");
+
+    let flags = env::var("LIBSQLITE3_FLAGS").unwrap_or_default();
+    // Same spellings libsqlite3-sys accepts: `-DNAME`, `-DNAME=1` or `NAME`
+    let defined = |name: &str| {
+        flags.split_whitespace().any(|flag| {
+            let flag = flag.strip_prefix("-D").unwrap_or(flag);
+            flag == name || flag.strip_prefix(name).is_some_and(|v| v.starts_with('='))
+        })
+    };
+    if REQUIRED.iter().all(|name| defined(name)) {
+        println!("cargo:rustc-cfg=sqlite_profile");
+    }
+}
@@ -0,0 +1,204 @@
+//! Statements running on pooled connections.
+//!
+//! [`install`] registers a `sqlite3_trace_v2` hook that records each
+//! statement in the pool's [`Activity`] when it starts running
+//! (`SQLITE_TRACE_STMT`) and removes it when it finishes or is reset
+//! (`SQLITE_TRACE_PROFILE`), so [`Activity::snapshot`] lists what every
+//! connection is busy with, e.g. when the pool stalls. A statement whose rows
+//! are only partly fetched counts as running until it is reset.
+//!
+//! SQLite has one trace hook per connection. Code that sets its own must
+//! forward the events to [`trace`] and put the hook back with [`restore`]
+//! when done.
+
+use libsqlite3_sys::{
+    sqlite3, sqlite3_db_handle, sqlite3_get_clientdata, sqlite3_set_clientdata, sqlite3_stmt,
+    sqlite3_trace_v2, SQLITE_TRACE_PROFILE, SQLITE_TRACE_STMT,
+};
+use std::collections::HashMap;
+use std::ffi::{c_char, c_int, c_uint, c_void, CStr};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+
+const ACTIVITY_KEY: &CStr = c"rapsqlite.activity";
+
+/// The trace events [`trace`] needs.
+pub const TRACE_MASK: c_uint = (SQLITE_TRACE_STMT | SQLITE_TRACE_PROFILE) as c_uint;
+
+/// A statement that started running and has not finished.
+#[derive(Debug, Clone)]
+pub struct ActiveStatement {
+    /// The [`lifecycle`](crate::lifecycle) id of the connection running it,
+    /// 0 when the pool has no events listener.
+    pub connection: u64,
+    /// The SQL text, without the bound parameter values.
+    pub sql: String,
+    /// When it started running.
+    pub started: SystemTime,
+    since: Instant,
+}
+
+impl ActiveStatement {
+    /// How long it has been running.
+    pub fn elapsed(&self) -> Duration {
+        self.since.elapsed()
+    }
+}
+
+/// The statements running on a pool's connections.
+#[derive(Debug, Default)]
+pub struct Activity {
+    /// By `sqlite3_stmt` address.
+    running: Mutex<HashMap<usize, ActiveStatement>>,
+}
+
+impl Activity {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The running statements, longest-running first.
+    pub fn snapshot(&self) -> Vec<ActiveStatement> {
+        let mut statements: Vec<_> = self.running.lock().unwrap().values().cloned().collect();
+        statements.sort_by_key(|statement| statement.since);
+        statements
+    }
+
+    fn start(&self, stmt: usize, connection: u64, sql: String) {
+        let statement = ActiveStatement {
+            connection,
+            sql,
+            started: SystemTime::now(),
+            since: Instant::now(),
+        };
+        self.running.lock().unwrap().insert(stmt, statement);
+    }
+
+    fn finish(&self, stmt: usize) {
+        self.running.lock().unwrap().remove(&stmt);
+    }
+}
+
+struct Tracer {
+    connection: u64,
+    activity: Arc<Activity>,
+}
+
+unsafe extern "C" fn drop_tracer(tracer: *mut c_void) {
+    let tracer = Box::from_raw(tracer as *mut Tracer);
+    // Statements are finalized before the connection closes; this only
+    // catches ones whose end was not traced.
+    let connection = tracer.connection;
+    tracer
+        .activity
+        .running
+        .lock()
+        .unwrap()
+        .retain(|_, statement| statement.connection != connection);
+}
+
+unsafe extern "C" fn trace_hook(
+    event: c_uint,
+    _ctx: *mut c_void,
+    p: *mut c_void,
+    x: *mut c_void,
+) -> c_int {
+    trace(event, p, x);
+    0
+}
+
+/// Record statements run on `db` in `activity`, as the connection with
+/// [`lifecycle`](crate::lifecycle) id `connection`.
+///
+/// # Safety
+///
+/// `db` must meet the [raw handle](crate#raw-handles) requirements.
+pub unsafe fn install(db: *mut sqlite3, activity: Arc<Activity>, connection: u64) {
+    let tracer = Box::into_raw(Box::new(Tracer {
+        connection,
+        activity,
+    })) as *mut c_void;
+    sqlite3_set_clientdata(db, ACTIVITY_KEY.as_ptr(), tracer, Some(drop_tracer));
+    restore(db);
+}
+
+/// Set the trace hook [`install`] registers back on `db`, or clear the trace
+/// hook when [`install`] was not called on it. Returns the SQLite result code.
+///
+/// # Safety
+///
+/// `db` must meet the [raw handle](crate#raw-handles) requirements.
+pub unsafe fn restore(db: *mut sqlite3) -> c_int {
+    if sqlite3_get_clientdata(db, ACTIVITY_KEY.as_ptr()).is_null() {
+        sqlite3_trace_v2(db, 0, None, std::ptr::null_mut())
+    } else {
+        sqlite3_trace_v2(db, TRACE_MASK, Some(trace_hook), std::ptr::null_mut())
+    }
+}
+
+/// Handle a `sqlite3_trace_v2` event for the connection's [`Activity`], if
+/// [`install`] was called on it. For trace hooks that replace the one
+/// [`install`] sets; they need at least the events in [`TRACE_MASK`].
+///
+/// # Safety
+///
+/// `event`, `p` and `x` must be the arguments SQLite passed to the trace hook.
+pub unsafe fn trace(event: c_uint, p: *mut c_void, x: *mut c_void) {
+    if event != SQLITE_TRACE_STMT as c_uint && event != SQLITE_TRACE_PROFILE as c_uint {
+        return;
+    }
+    let stmt = p as *mut sqlite3_stmt;
+    let tracer =
+        sqlite3_get_clientdata(sqlite3_db_handle(stmt), ACTIVITY_KEY.as_ptr()) as *const Tracer;
+    let Some(tracer) = tracer.as_ref() else {
+        return;
+    };
+    if event == SQLITE_TRACE_PROFILE as c_uint {
+        tracer.activity.finish(stmt as usize);
+        return;
+    }
+    let sql = CStr::from_ptr(x as *const c_char).to_string_lossy();
+    // Statements of triggers are reported as "-- TRIGGER name" while the
+    // statement that fired them keeps running
+    if !sql.starts_with("--") {
+        tracer
+            .activity
+            .start(stmt as usize, tracer.connection, sql.into_owned());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{open_pool, PoolOptions};
+    use futures_util::TryStreamExt;
+    use sqlx::Row;
+
+    #[tokio::test]
+    async fn test_running_statements() {
+        let activity = Arc::new(Activity::new());
+        let options = PoolOptions {
+            activity: Some(Arc::clone(&activity)),
+            ..PoolOptions::default()
+        };
+        let pool = open_pool(":memory:", &options).await.unwrap();
+        let mut conn = pool.acquire().await.unwrap();
+        sqlx::query("CREATE TABLE t (x); INSERT INTO t VALUES (1), (2)")
+            .execute(&mut *conn)
+            .await
+            .unwrap();
+        assert!(activity.snapshot().is_empty());
+
+        // Running until its rows are all fetched
+        let mut rows = sqlx::query("SELECT x FROM t").fetch(&mut *conn);
+        let first = rows.try_next().await.unwrap().unwrap();
+        assert_eq!(first.get::<i64, _>(0), 1);
+        let running = activity.snapshot();
+        assert_eq!(running.len(), 1);
+        assert_eq!(running[0].sql, "SELECT x FROM t");
+        assert!(running[0].elapsed() < Duration::from_secs(60));
+        while rows.try_next().await.unwrap().is_some() {}
+        drop(rows);
+        assert!(activity.snapshot().is_empty());
+    }
+}
@@ -0,0 +1,219 @@
+//! Hardened mode: an SQLite authorizer that denies ATTACH/DETACH, PRAGMA writes
+//! and schema changes unless explicitly allowed, for applications that execute
+//! semi-trusted SQL.
+//!
+//! [`install`] registers a [`StatementPolicy`] on a connection; the policy is
+//! owned by the connection (as SQLite client data) and freed when it closes.
+
+use libsqlite3_sys::{
+    sqlite3, sqlite3_get_clientdata, sqlite3_set_authorizer, sqlite3_set_clientdata,
+    SQLITE_ALTER_TABLE, SQLITE_ATTACH, SQLITE_CREATE_INDEX, SQLITE_CREATE_TABLE,
+    SQLITE_CREATE_TRIGGER, SQLITE_CREATE_VIEW, SQLITE_CREATE_VTABLE, SQLITE_DENY, SQLITE_DETACH,
+    SQLITE_DROP_INDEX, SQLITE_DROP_TABLE, SQLITE_DROP_TRIGGER, SQLITE_DROP_VIEW,
+    SQLITE_DROP_VTABLE, SQLITE_OK, SQLITE_PRAGMA,
+};
+use std::collections::HashSet;
+use std::ffi::{c_char, c_int, c_void, CStr};
+use std::sync::Arc;
+
+/// PRAGMAs that take an argument but only read (`PRAGMA table_info(t)`, or the
+/// `pragma_table_info('t')` table-valued function).
+const READ_ONLY_PRAGMAS: &[&str] = &[
+    "collation_list",
+    "database_list",
+    "foreign_key_check",
+    "foreign_key_list",
+    "function_list",
+    "index_info",
+    "index_list",
+    "index_xinfo",
+    "integrity_check",
+    "module_list",
+    "pragma_list",
+    "quick_check",
+    "table_info",
+    "table_list",
+    "table_xinfo",
+];
+
+/// What hardened mode permits beyond ordinary reads and writes.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StatementPolicy {
+    /// Allow ATTACH and DETACH.
+    pub attach: bool,
+    /// Allow CREATE/DROP/ALTER of tables, indexes, views, triggers and virtual
+    /// tables. TEMP objects are always allowed.
+    pub schema: bool,
+    /// Lowercased names of PRAGMAs that may be set.
+    pub pragmas: HashSet<String>,
+}
+
+impl StatementPolicy {
+    /// Parse allowlist entries: `"attach"`, `"schema"` or `"pragma:<name>"`.
+    pub fn from_allowlist<'a>(entries: impl IntoIterator<Item = &'a str>) -> Result<Self, String> {
+        let mut policy = StatementPolicy::default();
+        for entry in entries {
+            match entry.to_ascii_lowercase().as_str() {
+                "attach" => policy.attach = true,
+                "schema" => policy.schema = true,
+                other => match other.strip_prefix("pragma:") {
+                    Some(name) if !name.is_empty() => {
+                        policy.pragmas.insert(name.to_string());
+                    }
+                    _ => {
+                        return Err(format!(
+                            "unknown hardened allowlist entry {entry:?} \
+                             (expected \"attach\", \"schema\" or \"pragma:<name>\")"
+                        ))
+                    }
+                },
+            }
+        }
+        Ok(policy)
+    }
+
+    /// Whether the authorizer action `action` with SQLite's first two arguments
+    /// is permitted.
+    pub fn permits(&self, action: c_int, arg1: Option<&str>, arg2: Option<&str>) -> bool {
+        match action {
+            SQLITE_ATTACH | SQLITE_DETACH => self.attach,
+            SQLITE_PRAGMA => {
+                let name = arg1.unwrap_or_default().to_ascii_lowercase();
+                arg2.is_none()
+                    || READ_ONLY_PRAGMAS.contains(&name.as_str())
+                    || self.pragmas.contains(&name)
+            }
+            SQLITE_CREATE_INDEX
+            | SQLITE_CREATE_TABLE
+            | SQLITE_CREATE_TRIGGER
+            | SQLITE_CREATE_VIEW
+            | SQLITE_CREATE_VTABLE
+            | SQLITE_DROP_INDEX
+            | SQLITE_DROP_TABLE
+            | SQLITE_DROP_TRIGGER
+            | SQLITE_DROP_VIEW
+            | SQLITE_DROP_VTABLE
+            | SQLITE_ALTER_TABLE => self.schema,
+            _ => true,
+        }
+    }
+}
+
+const POLICY_KEY: &CStr = c"rapsqlite.statement_policy";
+
+unsafe extern "C" fn free_policy(policy: *mut c_void) {
+    drop(Box::from_raw(policy as *mut Arc<StatementPolicy>));
+}
+
+unsafe fn optional_str<'a>(ptr: *const c_char) -> Option<&'a str> {
+    if ptr.is_null() {
+        None
+    } else {
+        CStr::from_ptr(ptr).to_str().ok()
+    }
+}
+
+/// Authorizer callback for a `*const StatementPolicy` user data pointer.
+///
+/// # Safety
+///
+/// `policy` must point to a live `StatementPolicy`; the string arguments are
+/// null or NUL-terminated, as SQLite passes them.
+pub unsafe extern "C" fn policy_authorizer(
+    policy: *mut c_void,
+    action: c_int,
+    arg1: *const c_char,
+    arg2: *const c_char,
+    _database: *const c_char,
+    _trigger: *const c_char,
+) -> c_int {
+    let policy = &*(policy as *const StatementPolicy);
+    if policy.permits(action, optional_str(arg1), optional_str(arg2)) {
+        SQLITE_OK
+    } else {
+        SQLITE_DENY
+    }
+}
+
+/// Register `policy` as the authorizer of `db`, replacing any other authorizer.
+///
+/// # Safety
+///
+/// `db` must meet the [raw handle](crate#raw-handles) requirements.
+pub unsafe fn install(db: *mut sqlite3, policy: Arc<StatementPolicy>) {
+    let ptr = Arc::as_ptr(&policy) as *mut c_void;
+    // The connection owns the policy from now on; replacing it frees the old one
+    // only after the authorizer points at the new one.
+    sqlite3_set_authorizer(db, Some(policy_authorizer), ptr);
+    let data = Box::into_raw(Box::new(policy)) as *mut c_void;
+    sqlite3_set_clientdata(db, POLICY_KEY.as_ptr(), data, Some(free_policy));
+}
+
+/// The policy installed on `db`, if any.
+///
+/// # Safety
+///
+/// `db` must meet the [raw handle](crate#raw-handles) requirements.
+pub unsafe fn installed(db: *mut sqlite3) -> Option<Arc<StatementPolicy>> {
+    let data = sqlite3_get_clientdata(db, POLICY_KEY.as_ptr());
+    if data.is_null() {
+        None
+    } else {
+        Some(Arc::clone(&*(data as *const Arc<StatementPolicy>)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{open_pool, PoolOptions};
+
+    #[test]
+    fn test_allowlist_parsing() {
+        let policy = StatementPolicy::from_allowlist(["Attach", "pragma:journal_mode"]).unwrap();
+        assert!(policy.attach && !policy.schema);
+        assert!(policy.pragmas.contains("journal_mode"));
+        assert!(StatementPolicy::from_allowlist(["pragma:"]).is_err());
+        assert!(StatementPolicy::from_allowlist(["everything"]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_hardened_pool_denies_unlisted_statements() {
+        let options = PoolOptions {
+            max_connections: Some(2),
+            pragmas: vec![("user_version".into(), "3".into())],
+            policy: Some(Arc::new(StatementPolicy {
+                pragmas: ["user_version".to_string()].into(),
+                ..StatementPolicy::default()
+            })),
+            ..PoolOptions::default()
+        };
+        let pool = open_pool(":memory:", &options).await.unwrap();
+        let run = |sql: &'static str| {
+            let pool = pool.clone();
+            async move { sqlx::query(sql).execute(&pool).await }
+        };
+        assert!(run("SELECT * FROM pragma_table_info('sqlite_master')")
+            .await
+            .is_ok());
+        assert!(run("PRAGMA user_version = 4").await.is_ok());
+        assert!(run("PRAGMA journal_mode").await.is_ok());
+        assert!(run("CREATE TEMP TABLE scratch (x)").await.is_ok());
+        for denied in [
+            "CREATE TABLE t (x)",
+            "ATTACH ':memory:' AS other",
+            "PRAGMA writable_schema = ON",
+        ] {
+            let err = run(denied).await.unwrap_err();
+            assert!(
+                err.to_string().contains("not authorized"),
+                "{denied}: {err}"
+            );
+        }
+
+        let mut conn = pool.acquire().await.unwrap();
+        let mut handle = conn.lock_handle().await.unwrap();
+        let policy = unsafe { installed(handle.as_raw_handle().as_ptr()) }.unwrap();
+        assert!(!policy.schema);
+    }
+}
@@ -0,0 +1,279 @@
+//! Consistent snapshots for backups.
+//!
+//! A [`Snapshot`] is a page-for-page copy of a database made with the SQLite
+//! backup API into a temporary file, which [`s3`](crate::s3) uploads and
+//! [`encryption`](crate::encryption) seals. The file is removed when the
+//! snapshot is dropped. A [`MemorySnapshot`] is a copy kept in memory to reset
+//! the database to later, e.g. between tests. A [`BackupFile`] is a database
+//! file opened for a backup to write into.
+
+use libsqlite3_sys::{
+    sqlite3, sqlite3_backup_finish, sqlite3_backup_init, sqlite3_backup_step, sqlite3_close,
+    sqlite3_errcode, sqlite3_open_v2, SQLITE_DONE, SQLITE_OK, SQLITE_OPEN_CREATE,
+    SQLITE_OPEN_FULLMUTEX, SQLITE_OPEN_MEMORY, SQLITE_OPEN_READWRITE,
+};
+use std::ffi::CString;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::encryption::{self, KEY_SIZE};
+use crate::wal_archive;
+
+/// A database copy in a temporary file.
+#[derive(Debug)]
+pub struct Snapshot {
+    path: PathBuf,
+}
+
+impl Snapshot {
+    fn temp_path() -> PathBuf {
+        static NEXT: AtomicU64 = AtomicU64::new(0);
+        std::env::temp_dir().join(format!(
+            "rapsqlite-snapshot-{}-{}.db",
+            std::process::id(),
+            NEXT.fetch_add(1, Ordering::Relaxed)
+        ))
+    }
+
+    /// Copy the main database of `db`. SQLite cannot back up from a
+    /// connection in a write transaction; that fails with `SQLITE_BUSY`.
+    ///
+    /// # Safety
+    ///
+    /// `db` must meet the [raw handle](crate#raw-handles) requirements.
+    pub unsafe fn take(db: *mut sqlite3) -> io::Result<Self> {
+        let snapshot = Snapshot {
+            path: Self::temp_path(),
+        };
+        wal_archive::backup(db, &snapshot.path)?;
+        Ok(snapshot)
+    }
+
+    /// The snapshot [encrypted](crate::encryption) under `key`, in a new
+    /// temporary file.
+    pub fn encrypt(&self, key: &[u8; KEY_SIZE]) -> io::Result<Snapshot> {
+        let encrypted = Snapshot {
+            path: Self::temp_path(),
+        };
+        encryption::encrypt_file(&self.path, &encrypted.path, key)?;
+        Ok(encrypted)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for Snapshot {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Copy the main database of `src` into that of `dest` in one backup step.
+///
+/// # Safety
+///
+/// Both must meet the [raw handle](crate#raw-handles) requirements.
+unsafe fn copy_database(src: *mut sqlite3, dest: *mut sqlite3) -> io::Result<()> {
+    let main = c"main".as_ptr();
+    let backup = sqlite3_backup_init(dest, main, src, main);
+    let rc = if backup.is_null() {
+        sqlite3_errcode(dest)
+    } else {
+        let rc = sqlite3_backup_step(backup, -1);
+        // finish() reports errors of the step, e.g. SQLITE_BUSY
+        let finished = sqlite3_backup_finish(backup);
+        if rc == SQLITE_DONE {
+            finished
+        } else {
+            rc
+        }
+    };
+    if rc == SQLITE_OK {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!(
+            "SQLite backup failed with error code {rc}"
+        )))
+    }
+}
+
+/// A copy of a database in a private in-memory database.
+#[derive(Debug)]
+pub struct MemorySnapshot {
+    db: *mut sqlite3,
+}
+
+// Safety: the handle is opened in SQLite's serialized threading mode, whose
+// mutex guards concurrent use.
+unsafe impl Send for MemorySnapshot {}
+unsafe impl Sync for MemorySnapshot {}
+
+impl MemorySnapshot {
+    /// Copy the main database of `db`. As for [`Snapshot::take`], this fails
+    /// with `SQLITE_BUSY` while `db` is in a write transaction.
+    ///
+    /// # Safety
+    ///
+    /// `db` must meet the [raw handle](crate#raw-handles) requirements.
+    pub unsafe fn take(db: *mut sqlite3) -> io::Result<Self> {
+        let mut memory: *mut sqlite3 = std::ptr::null_mut();
+        let flags =
+            SQLITE_OPEN_READWRITE | SQLITE_OPEN_CREATE | SQLITE_OPEN_MEMORY | SQLITE_OPEN_FULLMUTEX;
+        let rc = sqlite3_open_v2(c":memory:".as_ptr(), &mut memory, flags, std::ptr::null());
+        let snapshot = MemorySnapshot { db: memory };
+        if rc != SQLITE_OK {
+            return Err(io::Error::other(format!(
+                "Failed to open in-memory database (error code {rc})"
+            )));
+        }
+        copy_database(db, snapshot.db)?;
+        Ok(snapshot)
+    }
+
+    /// Replace the main database of `db` with the snapshot. Other connections
+    /// see the restored contents once it returns.
+    ///
+    /// # Safety
+    ///
+    /// `db` must meet the [raw handle](crate#raw-handles) requirements.
+    pub unsafe fn restore(&self, db: *mut sqlite3) -> io::Result<()> {
+        copy_database(self.db, db)
+    }
+}
+
+impl Drop for MemorySnapshot {
+    fn drop(&mut self) {
+        // Safety: the handle is owned by the snapshot (closing NULL is a no-op).
+        unsafe { sqlite3_close(self.db) };
+    }
+}
+
+/// A connection of its own to a database file, created if missing, for a
+/// backup to copy into. It is closed when dropped.
+#[derive(Debug)]
+pub struct BackupFile {
+    db: *mut sqlite3,
+}
+
+// Safety: as for MemorySnapshot, the handle is opened in serialized mode.
+unsafe impl Send for BackupFile {}
+unsafe impl Sync for BackupFile {}
+
+impl BackupFile {
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let c_path = CString::new(path.as_os_str().as_encoded_bytes())
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        let mut db: *mut sqlite3 = std::ptr::null_mut();
+        let flags = SQLITE_OPEN_READWRITE | SQLITE_OPEN_CREATE | SQLITE_OPEN_FULLMUTEX;
+        // Safety: c_path is a valid C string; on failure the handle is still
+        // allocated (or NULL) and closed by the drop below.
+        let rc = unsafe { sqlite3_open_v2(c_path.as_ptr(), &mut db, flags, std::ptr::null()) };
+        let file = BackupFile { db };
+        if rc != SQLITE_OK {
+            return Err(io::Error::other(format!(
+                "Failed to open {} (error code {rc})",
+                path.display()
+            )));
+        }
+        Ok(file)
+    }
+
+    /// The `sqlite3*` to pass to `sqlite3_backup_init` as the destination.
+    pub fn handle(&self) -> *mut sqlite3 {
+        self.db
+    }
+}
+
+impl Drop for BackupFile {
+    fn drop(&mut self) {
+        // Safety: the handle is owned by the file (closing NULL is a no-op).
+        unsafe { sqlite3_close(self.db) };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    unsafe fn exec(db: *mut sqlite3, sql: &std::ffi::CStr) {
+        let rc = libsqlite3_sys::sqlite3_exec(
+            db,
+            sql.as_ptr(),
+            None,
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+        );
+        assert_eq!(rc, SQLITE_OK);
+    }
+
+    unsafe fn count(db: *mut sqlite3) -> i64 {
+        let mut stmt = std::ptr::null_mut();
+        let sql = c"SELECT count(*) FROM t";
+        libsqlite3_sys::sqlite3_prepare_v2(db, sql.as_ptr(), -1, &mut stmt, std::ptr::null_mut());
+        libsqlite3_sys::sqlite3_step(stmt);
+        let n = libsqlite3_sys::sqlite3_column_int64(stmt, 0);
+        libsqlite3_sys::sqlite3_finalize(stmt);
+        n
+    }
+
+    #[test]
+    fn test_memory_snapshot_restores_contents() {
+        let path = Snapshot::temp_path();
+        let c_path = std::ffi::CString::new(path.to_string_lossy().as_bytes()).unwrap();
+        unsafe {
+            let mut db = std::ptr::null_mut();
+            let flags = SQLITE_OPEN_READWRITE | SQLITE_OPEN_CREATE;
+            assert_eq!(
+                sqlite3_open_v2(c_path.as_ptr(), &mut db, flags, std::ptr::null()),
+                SQLITE_OK
+            );
+            exec(
+                db,
+                c"PRAGMA journal_mode = WAL; CREATE TABLE t (x); INSERT INTO t VALUES (1)",
+            );
+
+            let snapshot = MemorySnapshot::take(db).unwrap();
+            exec(db, c"INSERT INTO t VALUES (2); CREATE TABLE u (y)");
+            assert_eq!(count(db), 2);
+
+            snapshot.restore(db).unwrap();
+            assert_eq!(count(db), 1);
+            // Restoring twice gives the same contents
+            exec(db, c"DELETE FROM t");
+            snapshot.restore(db).unwrap();
+            assert_eq!(count(db), 1);
+            sqlite3_close(db);
+        }
+        let _ = fs::remove_file(&path);
+        let _ = fs::remove_file(format!("{}-wal", path.display()));
+        let _ = fs::remove_file(format!("{}-shm", path.display()));
+    }
+
+    #[test]
+    fn test_backup_file_receives_copy() {
+        let source = Snapshot::temp_path();
+        let dest = Snapshot::temp_path();
+        let c_source = std::ffi::CString::new(source.to_string_lossy().as_bytes()).unwrap();
+        unsafe {
+            let mut db = std::ptr::null_mut();
+            let flags = SQLITE_OPEN_READWRITE | SQLITE_OPEN_CREATE;
+            assert_eq!(
+                sqlite3_open_v2(c_source.as_ptr(), &mut db, flags, std::ptr::null()),
+                SQLITE_OK
+            );
+            exec(db, c"CREATE TABLE t (x); INSERT INTO t VALUES (1), (2)");
+
+            let file = BackupFile::open(&dest).unwrap();
+            copy_database(db, file.handle()).unwrap();
+            assert_eq!(count(file.handle()), 2);
+            sqlite3_close(db);
+        }
+        assert!(BackupFile::open(Path::new("/nonexistent-dir/x.db")).is_err());
+        let _ = fs::remove_file(&source);
+        let _ = fs::remove_file(&dest);
+    }
+}
@@ -0,0 +1,561 @@
+//! Query result cache with table-level invalidation.
+//!
+//! Entries are keyed by normalized query text and parameters. A write statement
+//! drops only the entries that read a table it may change, following view
+//! definitions, triggers and foreign key actions through [`TableDependencies`].
+//! Commits made by other connections are detected with `PRAGMA data_version`
+//! (see [`ResultCache::observe_data_version`]) and clear the whole cache.
+//!
+//! [`TtlCache`] is the simpler variant: entries expire after a fixed time.
+
+use sqlx::sqlite::{SqliteConnection, SqliteRow};
+use sqlx::Row;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::sql::{identifiers, normalize_query, quote_identifier, tokenize_sql, SqlToken};
+use crate::SqliteParam;
+
+/// How executing a statement affects cached results, ordered from least to most
+/// disruptive.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum StatementEffect {
+    /// Reads only.
+    Read,
+    /// Inserts, updates or deletes rows.
+    Write,
+    /// Ends a transaction: its writes become visible to other connections.
+    TransactionEnd,
+    /// CREATE/DROP/ALTER or ATTACH/DETACH.
+    SchemaChange,
+}
+
+impl StatementEffect {
+    /// Classify `sql` (one statement or a script) by its keywords.
+    pub fn of(sql: &str) -> Self {
+        let tokens = tokenize_sql(sql);
+        let mut effect = StatementEffect::Read;
+        for (i, token) in tokens.iter().enumerate() {
+            if token.0 != SqlToken::Word {
+                continue;
+            }
+            let statement_start = i == 0 || &sql[tokens[i - 1].1..tokens[i - 1].2] == ";";
+            let word = sql[token.1..token.2].to_ascii_lowercase();
+            let found = match word.as_str() {
+                "create" | "drop" | "alter" | "attach" | "detach" => StatementEffect::SchemaChange,
+                "commit" | "rollback" | "release" => StatementEffect::TransactionEnd,
+                "end" if statement_start => StatementEffect::TransactionEnd,
+                "insert" | "update" | "delete" => StatementEffect::Write,
+                // REPLACE INTO, not the replace() function
+                "replace" if tokens.get(i + 1).is_none_or(|t| t.0 != SqlToken::Open) => {
+                    StatementEffect::Write
+                }
+                _ => StatementEffect::Read,
+            };
+            effect = effect.max(found);
+        }
+        effect
+    }
+}
+
+/// Which tables and views may change when a given table is written.
+#[derive(Clone, Debug, Default)]
+pub struct TableDependencies {
+    /// Lowercased names of every table and view in all attached schemas.
+    names: HashSet<String>,
+    /// Written table -> objects whose contents may change as a consequence.
+    edges: HashMap<String, HashSet<String>>,
+}
+
+impl TableDependencies {
+    /// Read the schema of every attached database on `conn`.
+    pub async fn load(conn: &mut SqliteConnection) -> Result<Self, sqlx::Error> {
+        let schemas: Vec<String> = sqlx::query_scalar("SELECT name FROM pragma_database_list")
+            .fetch_all(&mut *conn)
+            .await?;
+        let mut objects = Vec::new();
+        let mut foreign_keys = Vec::new();
+        for schema in &schemas {
+            let quoted = quote_identifier(schema);
+            let rows = sqlx::query(&format!(
+                "SELECT type, name, tbl_name, sql FROM {quoted}.sqlite_master \
+                 WHERE type IN ('table', 'view', 'trigger')"
+            ))
+            .fetch_all(&mut *conn)
+            .await?;
+            for row in rows {
+                objects.push((
+                    row.try_get::<String, _>(0)?,
+                    row.try_get::<String, _>(1)?,
+                    row.try_get::<String, _>(2)?,
+                    row.try_get::<Option<String>, _>(3)?,
+                ));
+            }
+            let rows = sqlx::query(&format!(
+                "SELECT m.name, f.\"table\" FROM {quoted}.sqlite_master AS m, \
+                 pragma_foreign_key_list(m.name, ?) AS f WHERE m.type = 'table'"
+            ))
+            .bind(schema)
+            .fetch_all(&mut *conn)
+            .await?;
+            for row in rows {
+                foreign_keys.push((row.try_get::<String, _>(0)?, row.try_get::<String, _>(1)?));
+            }
+        }
+        Ok(Self::from_schema(&objects, &foreign_keys))
+    }
+
+    /// Build from `(type, name, tbl_name, sql)` rows of `sqlite_master` and
+    /// `(child table, parent table)` foreign keys.
+    pub fn from_schema(
+        objects: &[(String, String, String, Option<String>)],
+        foreign_keys: &[(String, String)],
+    ) -> Self {
+        let mut deps = TableDependencies::default();
+        for (kind, name, _, _) in objects {
+            if kind != "trigger" {
+                deps.names.insert(name.to_lowercase());
+            }
+        }
+        for (kind, name, table, sql) in objects {
+            let referenced = || {
+                identifiers(sql.as_deref().unwrap_or_default())
+                    .into_iter()
+                    .filter(|w| deps.names.contains(w))
+                    .collect::<Vec<_>>()
+            };
+            match kind.as_str() {
+                // Writing anything a view selects from changes the view
+                "view" => {
+                    let view = name.to_lowercase();
+                    for source in referenced().into_iter().filter(|s| *s != view) {
+                        deps.edges.entry(source).or_default().insert(view.clone());
+                    }
+                }
+                // A trigger body may write any table it names
+                "trigger" => {
+                    let targets = referenced();
+                    deps.edges
+                        .entry(table.to_lowercase())
+                        .or_default()
+                        .extend(targets);
+                }
+                _ => {}
+            }
+        }
+        // ON DELETE/UPDATE actions write the child table
+        for (child, parent) in foreign_keys {
+            deps.edges
+                .entry(parent.to_lowercase())
+                .or_default()
+                .insert(child.to_lowercase());
+        }
+        deps
+    }
+
+    /// Every table or view that may change when `sql` runs: the ones it names,
+    /// plus everything reachable from them through views, triggers and foreign keys.
+    pub fn affected_by(&self, sql: &str) -> HashSet<String> {
+        let mut affected = HashSet::new();
+        let mut pending: Vec<String> = self.tables_in(sql).into_iter().collect();
+        while let Some(name) = pending.pop() {
+            if affected.insert(name.clone()) {
+                if let Some(next) = self.edges.get(&name) {
+                    pending.extend(next.iter().cloned());
+                }
+            }
+        }
+        affected
+    }
+
+    /// The known tables and views named in `sql`.
+    pub fn tables_in(&self, sql: &str) -> HashSet<String> {
+        identifiers(sql)
+            .into_iter()
+            .filter(|w| self.names.contains(w))
+            .collect()
+    }
+}
+
+/// Counters reported by [`ResultCache::stats`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// Entries currently cached.
+    pub size: usize,
+    pub capacity: usize,
+}
+
+struct Entry {
+    rows: Arc<Vec<SqliteRow>>,
+    tables: HashSet<String>,
+    last_used: u64,
+}
+
+/// LRU cache of fetched rows.
+///
+/// Fill it in three steps so that a write racing with a fetch is never cached:
+/// take [`generation`](Self::generation) before running the query, then pass it
+/// to [`insert`](Self::insert), which ignores results from before an invalidation.
+pub struct ResultCache {
+    capacity: usize,
+    entries: HashMap<String, Entry>,
+    clock: u64,
+    generation: u64,
+    hits: u64,
+    misses: u64,
+    dependencies: Option<TableDependencies>,
+    data_versions: HashMap<u64, i64>,
+}
+
+impl ResultCache {
+    /// A cache holding at most `capacity` results.
+    pub fn new(capacity: usize) -> Self {
+        ResultCache {
+            capacity,
+            entries: HashMap::new(),
+            clock: 0,
+            generation: 0,
+            hits: 0,
+            misses: 0,
+            dependencies: None,
+            data_versions: HashMap::new(),
+        }
+    }
+
+    /// Cache key for a query and its bound parameters.
+    pub fn key(query: &str, params: &[SqliteParam]) -> String {
+        format!("{}\0{params:?}", normalize_query(query))
+    }
+
+    /// Look up `key`, counting a hit or a miss.
+    pub fn get(&mut self, key: &str) -> Option<Arc<Vec<SqliteRow>>> {
+        self.clock += 1;
+        match self.entries.get_mut(key) {
+            Some(entry) => {
+                entry.last_used = self.clock;
+                self.hits += 1;
+                Some(Arc::clone(&entry.rows))
+            }
+            None => {
+                self.misses += 1;
+                None
+            }
+        }
+    }
+
+    /// Changes on every invalidation.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// False until [`set_dependencies`](Self::set_dependencies) is called, and
+    /// again after a schema change.
+    pub fn has_dependencies(&self) -> bool {
+        self.dependencies.is_some()
+    }
+
+    /// Install the schema graph loaded at `generation`.
+    pub fn set_dependencies(&mut self, dependencies: TableDependencies, generation: u64) {
+        if generation == self.generation {
+            self.dependencies = Some(dependencies);
+        }
+    }
+
+    /// Cache `rows` for `key` unless the cache was invalidated since `generation`
+    /// or the schema graph is missing.
+    pub fn insert(&mut self, key: String, query: &str, rows: Arc<Vec<SqliteRow>>, generation: u64) {
+        let Some(dependencies) = &self.dependencies else {
+            return;
+        };
+        if generation != self.generation || self.capacity == 0 {
+            return;
+        }
+        if self.entries.len() >= self.capacity && !self.entries.contains_key(&key) {
+            let oldest = self
+                .entries
+                .iter()
+                .min_by_key(|(_, e)| e.last_used)
+                .map(|(k, _)| k.clone());
+            if let Some(oldest) = oldest {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.clock += 1;
+        let entry = Entry {
+            rows,
+            tables: dependencies.tables_in(query),
+            last_used: self.clock,
+        };
+        self.entries.insert(key, entry);
+    }
+
+    /// Drop the entries that `sql`, which this process just ran, may have made stale.
+    pub fn invalidate(&mut self, sql: &str) {
+        match StatementEffect::of(sql) {
+            StatementEffect::Read => return,
+            StatementEffect::Write => match &self.dependencies {
+                Some(dependencies) => {
+                    let affected = dependencies.affected_by(sql);
+                    self.entries
+                        .retain(|_, entry| entry.tables.is_disjoint(&affected));
+                }
+                None => self.entries.clear(),
+            },
+            StatementEffect::TransactionEnd => self.entries.clear(),
+            StatementEffect::SchemaChange => {
+                self.entries.clear();
+                self.dependencies = None;
+            }
+        }
+        self.generation += 1;
+    }
+
+    /// Record the `PRAGMA data_version` read on `connection_id` (an id unique to
+    /// one SQLite connection). A value that changed since the last read on that
+    /// connection means another connection committed, so everything is dropped;
+    /// so is a connection seen for the first time.
+    pub fn observe_data_version(&mut self, connection_id: u64, version: i64) {
+        if self.data_versions.insert(connection_id, version) != Some(version) {
+            self.invalidate_all();
+        }
+    }
+
+    /// Drop every entry and the schema graph, e.g. after the database file was
+    /// replaced.
+    pub fn invalidate_all(&mut self) {
+        self.entries.clear();
+        self.dependencies = None;
+        self.generation += 1;
+    }
+
+    /// Drop every entry and reset the hit/miss counters.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.hits = 0;
+        self.misses = 0;
+        self.generation += 1;
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits,
+            misses: self.misses,
+            size: self.entries.len(),
+            capacity: self.capacity,
+        }
+    }
+}
+
+/// Rows memoized for a limited time with no invalidation, for reference data that
+/// may be briefly stale. A lighter alternative to [`ResultCache`].
+#[derive(Default)]
+pub struct TtlCache {
+    /// key -> (fetched at, kept until, rows)
+    entries: HashMap<String, (Instant, Instant, Arc<Vec<SqliteRow>>)>,
+    hits: u64,
+    misses: u64,
+}
+
+impl TtlCache {
+    /// Look up `key`, counting a hit when it was fetched less than `ttl` before
+    /// `now` and a miss otherwise.
+    pub fn get(&mut self, key: &str, ttl: Duration, now: Instant) -> Option<Arc<Vec<SqliteRow>>> {
+        match self.entries.get(key) {
+            Some((fetched, _, rows)) if now.saturating_duration_since(*fetched) < ttl => {
+                self.hits += 1;
+                Some(Arc::clone(rows))
+            }
+            _ => {
+                self.misses += 1;
+                None
+            }
+        }
+    }
+
+    /// Store `rows` fetched at `now` for `ttl`, dropping entries past their own TTL.
+    pub fn insert(&mut self, key: String, rows: Arc<Vec<SqliteRow>>, ttl: Duration, now: Instant) {
+        self.entries.retain(|_, (_, until, _)| *until > now);
+        self.entries.insert(key, (now, now + ttl, rows));
+    }
+
+    /// Drop every entry and reset the hit/miss counters.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.hits = 0;
+        self.misses = 0;
+    }
+
+    /// `capacity` is 0: entries are bounded by their TTL rather than a size.
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits,
+            misses: self.misses,
+            size: self.entries.len(),
+            capacity: 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn object(
+        kind: &str,
+        name: &str,
+        table: &str,
+        sql: &str,
+    ) -> (String, String, String, Option<String>) {
+        (kind.into(), name.into(), table.into(), Some(sql.into()))
+    }
+
+    #[test]
+    fn test_statement_effect() {
+        use StatementEffect::*;
+        assert_eq!(
+            StatementEffect::of("SELECT replace(name, 'a', 'b') FROM t"),
+            Read
+        );
+        assert_eq!(
+            StatementEffect::of("SELECT CASE WHEN x THEN 1 END FROM t"),
+            Read
+        );
+        assert_eq!(StatementEffect::of("REPLACE INTO t VALUES (1)"), Write);
+        assert_eq!(
+            StatementEffect::of("WITH c AS (SELECT 1) DELETE FROM t"),
+            Write
+        );
+        assert_eq!(
+            StatementEffect::of("INSERT INTO t VALUES (1); END"),
+            TransactionEnd
+        );
+        assert_eq!(StatementEffect::of("ALTER TABLE t ADD c"), SchemaChange);
+    }
+
+    #[test]
+    fn test_affected_by_follows_views_triggers_and_foreign_keys() {
+        let deps = TableDependencies::from_schema(
+            &[
+                object("table", "parent", "parent", "CREATE TABLE parent (id)"),
+                object("table", "child", "child", "CREATE TABLE child (pid)"),
+                object("table", "audit", "audit", "CREATE TABLE audit (x)"),
+                object("table", "other", "other", "CREATE TABLE other (x)"),
+                object("view", "audit_view", "audit_view", "CREATE VIEW audit_view AS SELECT * FROM audit"),
+                object(
+                    "trigger",
+                    "log",
+                    "child",
+                    "CREATE TRIGGER log AFTER DELETE ON child BEGIN INSERT INTO \"Audit\" VALUES (1); END",
+                ),
+            ],
+            &[("child".into(), "parent".into())],
+        );
+        let affected = deps.affected_by("DELETE FROM parent WHERE id = 1");
+        let expected: HashSet<String> = ["parent", "child", "audit", "audit_view"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        assert_eq!(affected, expected);
+        assert_eq!(deps.affected_by("UPDATE other SET x = 1").len(), 1);
+    }
+
+    #[test]
+    fn test_ttl_cache_expiry() {
+        let mut cache = TtlCache::default();
+        let now = Instant::now();
+        let ttl = Duration::from_secs(10);
+        assert!(cache.get("q", ttl, now).is_none());
+        cache.insert("q".into(), Arc::new(Vec::new()), ttl, now);
+        assert!(cache.get("q", ttl, now).is_some());
+        assert!(cache.get("q", ttl, now + ttl).is_none());
+        assert!(cache.get("q", Duration::ZERO, now).is_none());
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses, stats.size), (1, 3, 1));
+        cache.clear();
+        assert_eq!(cache.stats(), CacheStats::default());
+    }
+
+    #[tokio::test]
+    async fn test_result_cache_invalidation() {
+        use sqlx::Connection;
+
+        let mut conn = SqliteConnection::connect("sqlite::memory:").await.unwrap();
+        for sql in [
+            "CREATE TABLE a (x)",
+            "CREATE TABLE b (x)",
+            "CREATE VIEW v AS SELECT x FROM a",
+            "INSERT INTO a VALUES (1)",
+        ] {
+            sqlx::query(sql).execute(&mut conn).await.unwrap();
+        }
+        let mut cache = ResultCache::new(2);
+        let generation = cache.generation();
+        cache.set_dependencies(
+            TableDependencies::load(&mut conn).await.unwrap(),
+            generation,
+        );
+
+        let fill = |cache: &mut ResultCache, query: &str| {
+            let key = ResultCache::key(query, &[]);
+            assert!(cache.get(&key).is_none());
+            cache.insert(key, query, Arc::new(Vec::new()), cache.generation());
+        };
+        fill(&mut cache, "SELECT * FROM v");
+        fill(&mut cache, "SELECT * FROM b");
+        assert!(cache
+            .get(&ResultCache::key("SELECT  *  FROM v", &[]))
+            .is_some());
+
+        // Writing `a` invalidates the view over it but not `b`
+        cache.invalidate("INSERT INTO a VALUES (2)");
+        assert!(cache
+            .get(&ResultCache::key("SELECT * FROM v", &[]))
+            .is_none());
+        assert!(cache
+            .get(&ResultCache::key("SELECT * FROM b", &[]))
+            .is_some());
+
+        // Results fetched before an invalidation are not cached
+        let stale = cache.generation();
+        cache.invalidate("DELETE FROM b");
+        cache.insert(
+            ResultCache::key("SELECT 1", &[]),
+            "SELECT 1",
+            Arc::new(Vec::new()),
+            stale,
+        );
+        assert_eq!(cache.stats().size, 0);
+
+        // LRU eviction
+        fill(&mut cache, "SELECT * FROM a");
+        fill(&mut cache, "SELECT * FROM b");
+        cache.get(&ResultCache::key("SELECT * FROM a", &[]));
+        fill(&mut cache, "SELECT * FROM v");
+        assert!(cache
+            .get(&ResultCache::key("SELECT * FROM b", &[]))
+            .is_none());
+        assert_eq!(cache.stats().size, 2);
+
+        // A data_version change on a known connection clears everything
+        cache.observe_data_version(1, 5);
+        fill(&mut cache, "SELECT * FROM a");
+        cache.observe_data_version(1, 5);
+        assert_eq!(
+            cache.stats().size,
+            0,
+            "no schema graph after the first observation"
+        );
+        cache.set_dependencies(
+            TableDependencies::load(&mut conn).await.unwrap(),
+            cache.generation(),
+        );
+        fill(&mut cache, "SELECT * FROM a");
+        cache.observe_data_version(1, 5);
+        assert_eq!(cache.stats().size, 1);
+        cache.observe_data_version(1, 6);
+        assert_eq!(cache.stats().size, 0);
+    }
+}
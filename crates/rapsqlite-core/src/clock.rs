@@ -0,0 +1,190 @@
+//! Fake clock for SQL date and time functions.
+//!
+//! SQLite asks the connection's VFS for the current time whenever a statement
+//! uses `'now'`: `datetime('now')`, `unixepoch()`, `CURRENT_TIMESTAMP` and the
+//! other date and time functions. Each [`FakeClock`] has a VFS that
+//! forwards everything to the default VFS except `xCurrentTime` and
+//! `xCurrentTimeInt64`, which read the clock instead, so tests exercising date
+//! logic in SQL get reproducible results. Open a pool with
+//! [`PoolOptions::vfs`](crate::PoolOptions::vfs) set to
+//! [`FakeClock::vfs_name`] to use it.
+//!
+//! SQLite reads the time once per statement, so every `'now'` in a statement
+//! sees the same value, as with the real clock. SQLite cannot free a VFS a
+//! connection may still use, so a dropped clock's VFS is kept and handed to
+//! the next clock created: there are never more VFSes registered than clocks
+//! alive at once. Keep the clock alive as long as the pools using it, or they
+//! read whichever clock gets its VFS next.
+
+use libsqlite3_sys::{sqlite3_int64, sqlite3_vfs, SQLITE_OK};
+use std::ffi::c_int;
+use std::fmt;
+use std::sync::atomic::{AtomicI64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::vfs::WrapperVfs;
+
+/// The Unix epoch as a Julian day number in milliseconds, SQLite's unit for
+/// `xCurrentTimeInt64`.
+const UNIX_EPOCH_JD_MS: i64 = 210_866_760_000_000;
+
+/// A clock's VFS, holding the time in milliseconds since the Unix epoch.
+type ClockVfs = WrapperVfs<AtomicI64>;
+
+/// VFSes of dropped clocks, waiting for the next clocks created.
+static IDLE: Mutex<Vec<&'static ClockVfs>> = Mutex::new(Vec::new());
+
+/// A VFS in use by a clock and its clones; goes back to [`IDLE`] on drop.
+struct InUse(&'static ClockVfs);
+
+impl Drop for InUse {
+    fn drop(&mut self) {
+        IDLE.lock().unwrap().push(self.0);
+    }
+}
+
+/// A settable clock read by SQLite instead of the system time, through a VFS
+/// of its own. Clones share the clock.
+#[derive(Clone)]
+pub struct FakeClock {
+    vfs: Arc<InUse>,
+}
+
+impl FakeClock {
+    /// Create a clock starting at `unix_ms`, milliseconds since the Unix
+    /// epoch, reusing the VFS of a dropped clock or registering a new one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if SQLite refuses the registration.
+    pub fn new(unix_ms: i64) -> FakeClock {
+        static NEXT_ID: AtomicUsize = AtomicUsize::new(1);
+        let idle = IDLE.lock().unwrap().pop();
+        let vfs = match idle {
+            Some(vfs) => {
+                vfs.state.store(unix_ms, Ordering::SeqCst);
+                vfs
+            }
+            None => {
+                let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+                WrapperVfs::register(
+                    format!("rapsqlite-clock-{id}"),
+                    None,
+                    AtomicI64::new(unix_ms),
+                    |vfs| {
+                        vfs.xCurrentTime = Some(x_current_time);
+                        vfs.xCurrentTimeInt64 = Some(x_current_time_int64);
+                    },
+                )
+            }
+        };
+        FakeClock {
+            vfs: Arc::new(InUse(vfs)),
+        }
+    }
+
+    /// Name of the clock's VFS, for [`PoolOptions::vfs`](crate::PoolOptions::vfs).
+    pub fn vfs_name(&self) -> &'static str {
+        self.vfs.0.name()
+    }
+
+    /// Current time of the clock, in milliseconds since the Unix epoch.
+    pub fn now_ms(&self) -> i64 {
+        self.vfs.0.state.load(Ordering::SeqCst)
+    }
+
+    /// Set the clock to `unix_ms`, milliseconds since the Unix epoch.
+    pub fn set_ms(&self, unix_ms: i64) {
+        self.vfs.0.state.store(unix_ms, Ordering::SeqCst);
+    }
+
+    /// Move the clock by `delta_ms` milliseconds (backwards when negative).
+    pub fn advance_ms(&self, delta_ms: i64) {
+        self.vfs.0.state.fetch_add(delta_ms, Ordering::SeqCst);
+    }
+}
+
+impl fmt::Debug for FakeClock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FakeClock")
+            .field("vfs_name", &self.vfs_name())
+            .field("now_ms", &self.now_ms())
+            .finish()
+    }
+}
+
+unsafe extern "C" fn x_current_time_int64(vfs: *mut sqlite3_vfs, out: *mut sqlite3_int64) -> c_int {
+    let now_ms = &WrapperVfs::<AtomicI64>::from_raw(vfs).state;
+    *out = now_ms.load(Ordering::SeqCst) + UNIX_EPOCH_JD_MS;
+    SQLITE_OK
+}
+
+unsafe extern "C" fn x_current_time(vfs: *mut sqlite3_vfs, out: *mut f64) -> c_int {
+    let mut jd_ms = 0;
+    x_current_time_int64(vfs, &mut jd_ms);
+    *out = jd_ms as f64 / 86_400_000.0;
+    SQLITE_OK
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{open_pool, PoolOptions};
+
+    #[tokio::test]
+    async fn test_fake_clock_drives_now() {
+        // 2024-01-02 03:04:05 UTC
+        let clock = FakeClock::new(1_704_164_645_000);
+        let path = std::env::temp_dir().join(format!("rapsqlite-clock-{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        std::fs::File::create(&path).unwrap();
+        let options = PoolOptions {
+            vfs: Some(clock.vfs_name().to_string()),
+            ..PoolOptions::default()
+        };
+        let pool = open_pool(path.to_str().unwrap(), &options).await.unwrap();
+
+        let (now, epoch): (String, i64) =
+            sqlx::query_as("SELECT CURRENT_TIMESTAMP, unixepoch('now')")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(now, "2024-01-02 03:04:05");
+        assert_eq!(epoch, 1_704_164_645);
+
+        clock.advance_ms(86_400_000);
+        let (date,): (String,) = sqlx::query_as("SELECT date('now')")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(date, "2024-01-03");
+
+        // Files still go through the default VFS
+        sqlx::query("CREATE TABLE t (x)")
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert!(std::fs::metadata(&path).unwrap().len() > 0);
+        pool.close().await;
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_dropped_clock_vfs_is_reused() {
+        let first = FakeClock::new(1_000);
+        let name = first.vfs_name();
+        let clone = first.clone();
+        drop(first);
+        // A clone still uses the VFS
+        let second = FakeClock::new(2_000);
+        assert_ne!(second.vfs_name(), name);
+        assert_eq!(clone.now_ms(), 1_000);
+
+        drop(clone);
+        // Other tests may be dropping clocks too, so only check it is idle
+        assert!(IDLE.lock().unwrap().iter().any(|vfs| vfs.name() == name));
+        let third = FakeClock::new(3_000);
+        assert_eq!(third.now_ms(), 3_000);
+        assert_eq!(second.now_ms(), 2_000);
+    }
+}
@@ -0,0 +1,62 @@
+//! CSV output for `Connection.export_csv()`, in the RFC 4180 dialect that
+//! Python's `csv` module reads and writes by default.
+
+use std::io::{self, Write};
+
+/// Write one record: fields separated by commas and terminated by CRLF. A
+/// field is quoted, with embedded quotes doubled, if it contains a comma,
+/// quote, CR or LF; `None` (SQL NULL) is written as an empty field. A record
+/// of one empty field is written as `""` so it doesn't read as a blank line.
+pub fn write_record<'a>(
+    writer: &mut impl Write,
+    fields: impl IntoIterator<Item = Option<&'a [u8]>>,
+) -> io::Result<()> {
+    let fields: Vec<&[u8]> = fields.into_iter().map(Option::unwrap_or_default).collect();
+    if let [b""] = fields[..] {
+        return writer.write_all(b"\"\"\r\n");
+    }
+    for (i, field) in fields.into_iter().enumerate() {
+        if i > 0 {
+            writer.write_all(b",")?;
+        }
+        if field
+            .iter()
+            .any(|b| matches!(b, b',' | b'"' | b'\r' | b'\n'))
+        {
+            writer.write_all(b"\"")?;
+            for (j, part) in field.split(|&b| b == b'"').enumerate() {
+                if j > 0 {
+                    writer.write_all(b"\"\"")?;
+                }
+                writer.write_all(part)?;
+            }
+            writer.write_all(b"\"")?;
+        } else {
+            writer.write_all(field)?;
+        }
+    }
+    writer.write_all(b"\r\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_record() {
+        let mut out = Vec::new();
+        let fields: [Option<&[u8]>; 5] = [
+            Some(b"plain"),
+            None,
+            Some(b"a,b"),
+            Some(b"say \"hi\""),
+            Some(b"two\nlines"),
+        ];
+        write_record(&mut out, fields).unwrap();
+        write_record(&mut out, [Some(&b""[..])]).unwrap();
+        assert_eq!(
+            out,
+            b"plain,,\"a,b\",\"say \"\"hi\"\"\",\"two\nlines\"\r\n\"\"\r\n".to_vec()
+        );
+    }
+}
@@ -0,0 +1,224 @@
+//! Deadlines shared by the statements a task runs.
+//!
+//! [`scope`] runs a future with a deadline. Pools opened with
+//! [`PoolOptions::deadlines`](crate::PoolOptions) give every connection a
+//! progress handler that interrupts the running statement (`SQLITE_INTERRUPT`)
+//! once the deadline it was marked with has passed. A connection is marked
+//! when it is acquired from the pool inside a scope, or when one of the
+//! `_on_connection` [`query`](crate::query) helpers runs on it there (for
+//! connections held across statements, such as a transaction's); the marks
+//! are cleared when the scope ends.
+//!
+//! The progress handler is checked every [`PROGRESS_OPS`] virtual machine
+//! instructions, so a statement shorter than that is never interrupted, and
+//! `sqlite3_progress_handler` replacing it on a connection turns the
+//! deadline off there until [`restore`] puts it back.
+
+use libsqlite3_sys::{
+    sqlite3, sqlite3_get_clientdata, sqlite3_progress_handler, sqlite3_set_clientdata,
+};
+use sqlx::sqlite::SqliteConnection;
+use std::ffi::{c_int, c_void, CStr};
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+const DEADLINE_KEY: &CStr = c"rapsqlite.deadline";
+
+/// Virtual machine instructions between two deadline checks.
+pub const PROGRESS_OPS: c_int = 1000;
+
+/// Connections marked with a deadline, so acquiring one outside a scope only
+/// needs to look at it when some may be.
+static MARKED: AtomicUsize = AtomicUsize::new(0);
+
+tokio::task_local! {
+    static SCOPE: Arc<Scope>;
+}
+
+struct Scope {
+    deadline: Instant,
+    /// The connections marked in the scope.
+    slots: Mutex<Vec<Arc<Slot>>>,
+}
+
+/// The deadline a connection is marked with.
+#[derive(Default)]
+struct Slot(Mutex<Option<Instant>>);
+
+impl Slot {
+    fn set(&self, deadline: Option<Instant>) {
+        let previous = std::mem::replace(&mut *self.0.lock().unwrap(), deadline);
+        match (previous.is_some(), deadline.is_some()) {
+            (false, true) => MARKED.fetch_add(1, Ordering::Relaxed),
+            (true, false) => MARKED.fetch_sub(1, Ordering::Relaxed),
+            _ => 0,
+        };
+    }
+
+    fn expired(&self) -> bool {
+        self.0
+            .lock()
+            .unwrap()
+            .is_some_and(|deadline| Instant::now() >= deadline)
+    }
+}
+
+/// Clears the marks of a scope when it ends, however it ends.
+struct ClearMarks(Arc<Scope>);
+
+impl Drop for ClearMarks {
+    fn drop(&mut self) {
+        for slot in self.0.slots.lock().unwrap().drain(..) {
+            slot.set(None);
+        }
+    }
+}
+
+/// Run `future` with `deadline` for the statements it runs on pooled
+/// connections. Scopes nest; the innermost one applies.
+pub async fn scope<F: Future>(deadline: Instant, future: F) -> F::Output {
+    let scope = Arc::new(Scope {
+        deadline,
+        slots: Mutex::new(Vec::new()),
+    });
+    let _clear = ClearMarks(Arc::clone(&scope));
+    SCOPE.scope(scope, future).await
+}
+
+/// The deadline of the current task, if it runs in a [`scope`].
+pub fn current() -> Option<Instant> {
+    SCOPE.try_with(|scope| scope.deadline).ok()
+}
+
+/// Whether the current task runs in a [`scope`] whose deadline has passed.
+pub fn expired() -> bool {
+    current().is_some_and(|deadline| Instant::now() >= deadline)
+}
+
+unsafe extern "C" fn drop_slot(slot: *mut c_void) {
+    let slot = Box::from_raw(slot as *mut Arc<Slot>);
+    slot.set(None);
+}
+
+unsafe extern "C" fn check(slot: *mut c_void) -> c_int {
+    c_int::from((*(slot as *const Slot)).expired())
+}
+
+/// Give `db` the progress handler that interrupts statements past the
+/// deadline it is marked with.
+///
+/// # Safety
+///
+/// `db` must meet the [raw handle](crate#raw-handles) requirements.
+pub unsafe fn install(db: *mut sqlite3) {
+    let slot = Box::new(Arc::new(Slot::default()));
+    sqlite3_set_clientdata(
+        db,
+        DEADLINE_KEY.as_ptr(),
+        Box::into_raw(slot) as *mut c_void,
+        Some(drop_slot),
+    );
+    restore(db);
+}
+
+/// Set the progress handler [`install`] registers back on `db`, or clear the
+/// progress handler when [`install`] was not called on it.
+///
+/// # Safety
+///
+/// `db` must meet the [raw handle](crate#raw-handles) requirements.
+pub unsafe fn restore(db: *mut sqlite3) {
+    match slot(db) {
+        Some(slot) => sqlite3_progress_handler(
+            db,
+            PROGRESS_OPS,
+            Some(check),
+            Arc::as_ptr(&slot) as *mut c_void,
+        ),
+        None => sqlite3_progress_handler(db, 0, None, std::ptr::null_mut()),
+    }
+}
+
+unsafe fn slot(db: *mut sqlite3) -> Option<Arc<Slot>> {
+    let slot = sqlite3_get_clientdata(db, DEADLINE_KEY.as_ptr()) as *const Arc<Slot>;
+    slot.as_ref().map(Arc::clone)
+}
+
+/// Mark `db` with the deadline of the current task, or clear its mark
+/// outside a [`scope`].
+///
+/// # Safety
+///
+/// `db` must meet the [raw handle](crate#raw-handles) requirements.
+pub unsafe fn mark(db: *mut sqlite3) {
+    let Some(slot) = slot(db) else {
+        return;
+    };
+    match SCOPE.try_with(Arc::clone) {
+        Ok(scope) => {
+            slot.set(Some(scope.deadline));
+            scope.slots.lock().unwrap().push(slot);
+        }
+        Err(_) => slot.set(None),
+    }
+}
+
+/// [`mark`] for a connection that is not locked yet.
+pub async fn enter(conn: &mut SqliteConnection) -> Result<(), sqlx::Error> {
+    if current().is_none() && MARKED.load(Ordering::Relaxed) == 0 {
+        return Ok(());
+    }
+    let mut handle = conn.lock_handle().await?;
+    // Safety: the handle lock gives exclusive use of the connection.
+    unsafe { mark(handle.as_raw_handle().as_ptr()) };
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{open_pool, query, PoolOptions};
+    use std::time::Duration;
+
+    fn count(rows: u64) -> String {
+        format!(
+            "WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < {rows}) \
+             SELECT count(*) FROM n"
+        )
+    }
+
+    #[tokio::test]
+    async fn test_deadline_interrupts_statements() {
+        let options = PoolOptions {
+            deadlines: true,
+            ..PoolOptions::default()
+        };
+        let pool = open_pool(":memory:", &options).await.unwrap();
+        let deadline = Instant::now() + Duration::from_millis(50);
+        let long = count(100_000_000);
+        let error = scope(deadline, query::bind_and_fetch_all(&long, &[], &pool))
+            .await
+            .err()
+            .unwrap();
+        assert!(error.to_string().contains("interrupt"), "{error}");
+        assert!(Instant::now() < deadline + Duration::from_secs(5));
+
+        // Held connections are marked by the `_on_connection` helpers
+        let mut conn = pool.acquire().await.unwrap();
+        let deadline = Instant::now() + Duration::from_millis(50);
+        let result = scope(
+            deadline,
+            query::bind_and_fetch_all_on_connection(&long, &[], &mut conn),
+        )
+        .await;
+        assert!(result.is_err());
+        drop(conn);
+
+        // Outside a scope the marks are gone
+        assert_eq!(MARKED.load(Ordering::Relaxed), 0);
+        let rows = query::bind_and_fetch_all(&count(100_000), &[], &pool).await;
+        assert_eq!(rows.unwrap().len(), 1);
+    }
+}
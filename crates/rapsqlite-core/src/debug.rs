@@ -0,0 +1,318 @@
+//! Debug-mode diagnostics.
+//!
+//! [`NPlusOneDetector`] spots the N+1 pattern: the same statement run over and
+//! over with a different single parameter (`SELECT * FROM users WHERE id = ?`
+//! once per row of an earlier result) where one query with `IN (...)` or a join
+//! would do.
+//!
+//! [`TaskAudit`] spots tasks that share a connection stepping on each other:
+//! one task changing a setting another task set and still relies on, running a
+//! statement inside another task's transaction, or ending that transaction.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::sql::normalize_query;
+use crate::SqliteParam;
+
+/// Scopes (tasks) tracked at once; the oldest is forgotten first.
+const MAX_SCOPES: usize = 256;
+
+/// A statement that crossed the threshold within one scope.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NPlusOneReport {
+    /// Normalized query text.
+    pub query: String,
+    /// Executions in the scope so far.
+    pub count: u64,
+    /// Distinct parameter values among them.
+    pub distinct: usize,
+}
+
+#[derive(Default)]
+struct QueryStats {
+    count: u64,
+    values: HashSet<String>,
+    reported: bool,
+}
+
+/// Counts single-parameter statements per scope and reports each one once, when
+/// it has run with `threshold` different parameter values in the same scope.
+pub struct NPlusOneDetector {
+    threshold: usize,
+    scopes: HashMap<u64, HashMap<String, QueryStats>>,
+    order: VecDeque<u64>,
+}
+
+impl NPlusOneDetector {
+    pub fn new(threshold: usize) -> Self {
+        NPlusOneDetector {
+            threshold: threshold.max(2),
+            scopes: HashMap::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    /// Record one execution of `query` in `scope` (e.g. an async task id).
+    /// Statements with other than exactly one parameter are ignored.
+    pub fn record(
+        &mut self,
+        scope: u64,
+        query: &str,
+        params: &[SqliteParam],
+    ) -> Option<NPlusOneReport> {
+        let [param] = params else {
+            return None;
+        };
+        if !self.scopes.contains_key(&scope) {
+            if self.order.len() >= MAX_SCOPES {
+                if let Some(oldest) = self.order.pop_front() {
+                    self.scopes.remove(&oldest);
+                }
+            }
+            self.order.push_back(scope);
+        }
+        let normalized = normalize_query(query);
+        let stats = self
+            .scopes
+            .entry(scope)
+            .or_default()
+            .entry(normalized.clone())
+            .or_default();
+        stats.count += 1;
+        if stats.reported {
+            return None;
+        }
+        stats.values.insert(format!("{param:?}"));
+        if stats.values.len() < self.threshold {
+            return None;
+        }
+        stats.reported = true;
+        Some(NPlusOneReport {
+            query: normalized,
+            count: stats.count,
+            distinct: stats.values.len(),
+        })
+    }
+}
+
+/// An async task as seen by [`TaskAudit`]: an id unique among running tasks
+/// and a name for messages.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TaskRef {
+    pub id: u64,
+    pub name: String,
+}
+
+/// Records which task last changed each connection setting and which task
+/// owns the open transaction, and reports the interleavings that break another
+/// task's expectations.
+#[derive(Default)]
+pub struct TaskAudit {
+    /// Setting name -> task that set it last.
+    settings: HashMap<String, TaskRef>,
+    /// Settings each task set and has not been told about losing.
+    expected: HashMap<u64, HashSet<String>>,
+    order: VecDeque<u64>,
+    transaction: Option<TaskRef>,
+    /// Tasks already reported for running statements in the open transaction.
+    intruders: HashSet<u64>,
+    /// Installed callback (e.g. `trace_callback`) -> task that installed it.
+    callbacks: HashMap<String, TaskRef>,
+    /// (callback, task) pairs already reported for running statements under
+    /// another task's callback.
+    callback_users: HashSet<(String, u64)>,
+}
+
+impl TaskAudit {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `task` changed `setting` (e.g. `row_factory` or `pragma cache_size`).
+    pub fn setting_changed(&mut self, task: &TaskRef, setting: &str) {
+        self.settings.insert(setting.to_string(), task.clone());
+        if !self.expected.contains_key(&task.id) {
+            if self.order.len() >= MAX_SCOPES {
+                if let Some(oldest) = self.order.pop_front() {
+                    self.expected.remove(&oldest);
+                }
+            }
+            self.order.push_back(task.id);
+        }
+        self.expected
+            .entry(task.id)
+            .or_default()
+            .insert(setting.to_string());
+    }
+
+    /// `task` is about to run a statement. Returns the settings it set that
+    /// another task has changed since, with the task that did (each reported
+    /// once), and the owner of the open transaction if that is another task
+    /// (once per transaction and task).
+    pub fn statement(&mut self, task: &TaskRef) -> (Vec<(String, TaskRef)>, Option<TaskRef>) {
+        let mut changed = Vec::new();
+        if let Some(expected) = self.expected.get_mut(&task.id) {
+            expected.retain(|setting| match self.settings.get(setting) {
+                Some(by) if by.id != task.id => {
+                    changed.push((setting.clone(), by.clone()));
+                    false
+                }
+                _ => true,
+            });
+            changed.sort_by(|a, b| a.0.cmp(&b.0));
+        }
+        let owner = self
+            .transaction
+            .as_ref()
+            .filter(|owner| owner.id != task.id && self.intruders.insert(task.id))
+            .cloned();
+        (changed, owner)
+    }
+
+    /// `task` installed (`installed`) or cleared `callback`, a hook such as
+    /// `trace_callback` that runs for every statement on the connection. Also
+    /// recorded as a change of the setting named `callback`.
+    pub fn callback_changed(&mut self, task: &TaskRef, callback: &str, installed: bool) {
+        self.setting_changed(task, callback);
+        self.callback_users.retain(|(name, _)| name != callback);
+        if installed {
+            self.callbacks.insert(callback.to_string(), task.clone());
+        } else {
+            self.callbacks.remove(callback);
+        }
+    }
+
+    /// `task` is about to run a statement. Returns the installed callbacks
+    /// another task set, which will run for it, with that task (once per
+    /// installation and task).
+    pub fn foreign_callbacks(&mut self, task: &TaskRef) -> Vec<(String, TaskRef)> {
+        let mut foreign: Vec<_> = self
+            .callbacks
+            .iter()
+            .filter(|(name, by)| {
+                by.id != task.id && self.callback_users.insert(((*name).clone(), task.id))
+            })
+            .map(|(name, by)| (name.clone(), by.clone()))
+            .collect();
+        foreign.sort_by(|a, b| a.0.cmp(&b.0));
+        foreign
+    }
+
+    /// `task` began a transaction.
+    pub fn transaction_begun(&mut self, task: TaskRef) {
+        self.transaction = Some(task);
+        self.intruders.clear();
+    }
+
+    /// `task` is about to commit or roll back the open transaction. Returns
+    /// its owner if that is another task.
+    pub fn transaction_ending(&self, task: &TaskRef) -> Option<TaskRef> {
+        self.transaction
+            .as_ref()
+            .filter(|owner| owner.id != task.id)
+            .cloned()
+    }
+
+    /// The open transaction was committed or rolled back.
+    pub fn transaction_ended(&mut self) {
+        self.transaction = None;
+        self.intruders.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_n_plus_one_detector() {
+        let mut detector = NPlusOneDetector::new(3);
+        let query = "SELECT * FROM users WHERE id = ?";
+        // Repeating the same value is not N+1
+        for _ in 0..5 {
+            assert!(detector.record(1, query, &[SqliteParam::Int(1)]).is_none());
+        }
+        assert!(detector.record(1, query, &[SqliteParam::Int(2)]).is_none());
+        let report = detector
+            .record(
+                1,
+                "SELECT *  FROM users WHERE id = ?",
+                &[SqliteParam::Int(3)],
+            )
+            .unwrap();
+        assert_eq!(report.query, query);
+        assert_eq!((report.count, report.distinct), (7, 3));
+        // Reported once per scope
+        assert!(detector.record(1, query, &[SqliteParam::Int(4)]).is_none());
+
+        // Other scopes and multi-parameter statements are counted separately
+        for v in 0..5 {
+            assert!(detector
+                .record(
+                    1,
+                    "SELECT ?, ?",
+                    &[SqliteParam::Int(v), SqliteParam::Int(v)]
+                )
+                .is_none());
+        }
+        assert!(detector.record(2, query, &[SqliteParam::Int(1)]).is_none());
+        assert!(detector.record(2, query, &[SqliteParam::Int(2)]).is_none());
+    }
+
+    #[test]
+    fn test_task_audit() {
+        let task = |id: u64| TaskRef {
+            id,
+            name: format!("Task-{id}"),
+        };
+        let (a, b) = (task(1), task(2));
+        let mut audit = TaskAudit::new();
+        audit.setting_changed(&a, "row_factory");
+        assert_eq!(audit.statement(&a), (vec![], None));
+        // A setting changed by the task that relies on it is not reported
+        audit.setting_changed(&b, "row_factory");
+        assert_eq!(audit.statement(&b), (vec![], None));
+        assert_eq!(
+            audit.statement(&a),
+            (vec![("row_factory".to_string(), b.clone())], None)
+        );
+        assert_eq!(audit.statement(&a), (vec![], None));
+
+        audit.transaction_begun(a.clone());
+        assert_eq!(audit.statement(&a), (vec![], None));
+        assert_eq!(audit.statement(&b), (vec![], Some(a.clone())));
+        assert_eq!(audit.statement(&b), (vec![], None));
+        assert_eq!(audit.transaction_ending(&a), None);
+        assert_eq!(audit.transaction_ending(&b), Some(a.clone()));
+        audit.transaction_ended();
+        assert_eq!(audit.statement(&b), (vec![], None));
+    }
+
+    #[test]
+    fn test_task_audit_callbacks() {
+        let task = |id: u64| TaskRef {
+            id,
+            name: format!("Task-{id}"),
+        };
+        let (a, b) = (task(1), task(2));
+        let mut audit = TaskAudit::new();
+        audit.callback_changed(&a, "trace_callback", true);
+        assert!(audit.foreign_callbacks(&a).is_empty());
+        assert_eq!(
+            audit.foreign_callbacks(&b),
+            vec![("trace_callback".to_string(), a.clone())]
+        );
+        // Reported once per installation
+        assert!(audit.foreign_callbacks(&b).is_empty());
+        audit.callback_changed(&a, "trace_callback", true);
+        assert_eq!(audit.foreign_callbacks(&b).len(), 1);
+
+        // Clearing another task's callback is a setting change it is told about
+        audit.callback_changed(&b, "trace_callback", false);
+        assert!(audit.foreign_callbacks(&a).is_empty());
+        assert_eq!(
+            audit.statement(&a),
+            (vec![("trace_callback".to_string(), b.clone())], None)
+        );
+    }
+}
@@ -0,0 +1,47 @@
+//! Error type for pool setup, backups and downloads.
+
+use std::fmt;
+
+/// Errors from [`open_pool`](crate::open_pool),
+/// [`upload_file`](crate::s3::upload_file) and [`fetch`](crate::remote::fetch). Query helpers return
+/// `sqlx::Error` directly, since the caller already knows the SQL it ran.
+#[derive(Debug)]
+pub enum Error {
+    /// Connecting to the database failed.
+    Connect { path: String, source: sqlx::Error },
+    /// A statement run while setting up the pool (e.g. a PRAGMA) failed.
+    Query { query: String, source: sqlx::Error },
+    /// Writing the base snapshot of a WAL archive failed.
+    Archive { dir: String, source: sqlx::Error },
+    /// Uploading a backup to object storage failed.
+    Backup { url: String, message: String },
+    /// Downloading a remote database into the cache failed.
+    Download { url: String, message: String },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Connect { path, source } => {
+                write!(f, "Failed to connect to database at {path}: {source}")
+            }
+            Error::Query { query, source } => write!(f, "Failed to execute {query}: {source}"),
+            Error::Archive { dir, source } => {
+                write!(f, "Failed to write WAL archive snapshot to {dir}: {source}")
+            }
+            Error::Backup { url, message } => write!(f, "Backup to {url} failed: {message}"),
+            Error::Download { url, message } => write!(f, "Download of {url} failed: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Connect { source, .. }
+            | Error::Query { source, .. }
+            | Error::Archive { source, .. } => Some(source),
+            Error::Backup { .. } | Error::Download { .. } => None,
+        }
+    }
+}
@@ -0,0 +1,481 @@
+//! Fault injection for testing retry and timeout handling.
+//!
+//! A [`FaultInjector`] registers a VFS that wraps the database, journal and
+//! WAL files it opens. On demand, their I/O methods fail with `SQLITE_BUSY`
+//! (when taking the write lock) or `SQLITE_IOERR_*` (reads, writes, syncs),
+//! or sleep before running, so application code can be tested against busy
+//! databases, failing disks and slow storage without racing real
+//! connections. Open a pool with [`PoolOptions::vfs`](crate::PoolOptions::vfs)
+//! set to [`FaultInjector::vfs_name`] to use it.
+//!
+//! The faults are process-wide: every injector shares them, and creating one
+//! resets them. One VFS is registered over each base VFS the first time it is
+//! asked for and reused after that, staying registered for the life of the
+//! process like [`clock`](crate::clock)'s.
+
+use libsqlite3_sys::{
+    sqlite3_file, sqlite3_filename, sqlite3_int64, sqlite3_io_methods, sqlite3_vfs, SQLITE_BUSY,
+    SQLITE_IOERR_FSYNC, SQLITE_IOERR_READ, SQLITE_IOERR_WRITE, SQLITE_LOCK_RESERVED, SQLITE_OK,
+    SQLITE_OPEN_MAIN_DB, SQLITE_OPEN_MAIN_JOURNAL, SQLITE_OPEN_WAL, SQLITE_SHM_EXCLUSIVE,
+    SQLITE_SHM_LOCK,
+};
+use std::ffi::{c_int, c_void};
+use std::fmt;
+use std::mem::size_of;
+use std::ptr;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::vfs::{self, WrapperVfs};
+
+/// A file operation faults can be injected into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FaultOp {
+    /// Reading a database, journal or WAL file; fails with `SQLITE_IOERR_READ`.
+    Read,
+    /// Writing one; fails with `SQLITE_IOERR_WRITE`.
+    Write,
+    /// Syncing one to disk; fails with `SQLITE_IOERR_FSYNC`.
+    Sync,
+    /// Taking the write lock (`RESERVED` or above in rollback-journal mode,
+    /// the WAL write lock in WAL mode); fails with `SQLITE_BUSY`, which
+    /// SQLite's busy handler retries until the busy timeout.
+    Lock,
+}
+
+impl FaultOp {
+    /// Every operation.
+    pub const ALL: [FaultOp; 4] = [FaultOp::Read, FaultOp::Write, FaultOp::Sync, FaultOp::Lock];
+
+    fn error_code(self) -> c_int {
+        match self {
+            FaultOp::Read => SQLITE_IOERR_READ,
+            FaultOp::Write => SQLITE_IOERR_WRITE,
+            FaultOp::Sync => SQLITE_IOERR_FSYNC,
+            FaultOp::Lock => SQLITE_BUSY,
+        }
+    }
+}
+
+/// Faults to inject, indexed by [`FaultOp`].
+struct Faults {
+    /// Operations left to fail.
+    pending: [AtomicU64; 4],
+    /// Delay before every operation, in microseconds.
+    latency_us: [AtomicU64; 4],
+    /// Failures injected so far.
+    injected: [AtomicU64; 4],
+    /// The VFS registered over each base VFS.
+    vfs: Mutex<Vec<(Option<String>, &'static str)>>,
+}
+
+/// The faults every injector shares.
+static FAULTS: Faults = Faults {
+    pending: [const { AtomicU64::new(0) }; 4],
+    latency_us: [const { AtomicU64::new(0) }; 4],
+    injected: [const { AtomicU64::new(0) }; 4],
+    vfs: Mutex::new(Vec::new()),
+};
+
+impl Faults {
+    /// Delay `op` by its latency and report whether it should fail.
+    fn check(&self, op: FaultOp) -> bool {
+        let i = op as usize;
+        let latency = self.latency_us[i].load(Ordering::SeqCst);
+        if latency > 0 {
+            std::thread::sleep(Duration::from_micros(latency));
+        }
+        let fail = self.pending[i]
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+            .is_ok();
+        if fail {
+            self.injected[i].fetch_add(1, Ordering::SeqCst);
+        }
+        fail
+    }
+}
+
+/// Injects failures and latency into the file operations of the connections
+/// using its VFS. All injectors share the same faults.
+#[derive(Clone, Copy)]
+pub struct FaultInjector {
+    faults: &'static Faults,
+}
+
+impl Default for FaultInjector {
+    fn default() -> Self {
+        FaultInjector::new()
+    }
+}
+
+impl FaultInjector {
+    /// Create an injector, clearing any faults set through another one.
+    pub fn new() -> FaultInjector {
+        let injector = FaultInjector { faults: &FAULTS };
+        injector.reset();
+        injector
+    }
+
+    /// Name of the injector's VFS over the VFS registered as `base` (the
+    /// default VFS when `None`, or e.g. a [`FakeClock`](crate::clock::FakeClock)'s),
+    /// registering it on first use.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `base` is not a registered VFS.
+    pub fn vfs_name(&self, base: Option<&str>) -> &'static str {
+        static NEXT_ID: AtomicUsize = AtomicUsize::new(1);
+        let mut registered = self.faults.vfs.lock().unwrap();
+        if let Some((_, name)) = registered.iter().find(|(b, _)| b.as_deref() == base) {
+            return name;
+        }
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        let wrapper =
+            WrapperVfs::register(format!("rapsqlite-faults-{id}"), base, self.faults, |vfs| {
+                // Wrapped files keep the base VFS's file right after ours
+                vfs.szOsFile += size_of::<FaultFile>() as c_int;
+                vfs.xOpen = Some(x_open);
+            });
+        registered.push((base.map(str::to_string), wrapper.name()));
+        wrapper.name()
+    }
+
+    /// Make the next `count` operations of kind `op` fail, replacing any
+    /// failures still pending for it.
+    pub fn fail_next(&self, op: FaultOp, count: u64) {
+        self.faults.pending[op as usize].store(count, Ordering::SeqCst);
+    }
+
+    /// Delay every operation of kind `op` by `latency` (zero to stop).
+    pub fn set_latency(&self, op: FaultOp, latency: Duration) {
+        let micros = u64::try_from(latency.as_micros()).unwrap_or(u64::MAX);
+        self.faults.latency_us[op as usize].store(micros, Ordering::SeqCst);
+    }
+
+    /// Number of failures injected into operations of kind `op` so far.
+    pub fn injected(&self, op: FaultOp) -> u64 {
+        self.faults.injected[op as usize].load(Ordering::SeqCst)
+    }
+
+    /// Clear pending failures, latencies and the injected counts.
+    pub fn reset(&self) {
+        for i in 0..FaultOp::ALL.len() {
+            self.faults.pending[i].store(0, Ordering::SeqCst);
+            self.faults.latency_us[i].store(0, Ordering::SeqCst);
+            self.faults.injected[i].store(0, Ordering::SeqCst);
+        }
+    }
+}
+
+impl fmt::Debug for FaultInjector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_struct("FaultInjector");
+        for op in FaultOp::ALL {
+            let i = op as usize;
+            debug.field(
+                &format!("{op:?}").to_lowercase(),
+                &(
+                    self.faults.pending[i].load(Ordering::SeqCst),
+                    self.faults.latency_us[i].load(Ordering::SeqCst),
+                ),
+            );
+        }
+        debug.finish()
+    }
+}
+
+/// A wrapped file. The base VFS's file follows it in the same allocation.
+#[repr(C)]
+struct FaultFile {
+    file: sqlite3_file,
+    faults: &'static Faults,
+}
+
+/// The base VFS's file inside a [`FaultFile`].
+///
+/// # Safety
+///
+/// `file` must have been opened by `x_open` with wrapped I/O methods.
+unsafe fn real(file: *mut sqlite3_file) -> (*mut sqlite3_file, &'static sqlite3_io_methods) {
+    let real = (file as *mut u8).add(size_of::<FaultFile>()) as *mut sqlite3_file;
+    (real, &*(*real).pMethods)
+}
+
+/// The faults of a [`FaultFile`].
+///
+/// # Safety
+///
+/// As for [`real`].
+unsafe fn faults(file: *mut sqlite3_file) -> &'static Faults {
+    (*(file as *mut FaultFile)).faults
+}
+
+unsafe extern "C" fn x_open(
+    vfs: *mut sqlite3_vfs,
+    name: sqlite3_filename,
+    file: *mut sqlite3_file,
+    flags: c_int,
+    out_flags: *mut c_int,
+) -> c_int {
+    if flags & (SQLITE_OPEN_MAIN_DB | SQLITE_OPEN_MAIN_JOURNAL | SQLITE_OPEN_WAL) == 0 {
+        // Temporary files are opened unwrapped
+        return vfs::x_open(vfs, name, file, flags, out_flags);
+    }
+    let real = (file as *mut u8).add(size_of::<FaultFile>()) as *mut sqlite3_file;
+    (*real).pMethods = ptr::null();
+    let rc = vfs::x_open(vfs, name, real, flags, out_flags);
+    let fault_file = file as *mut FaultFile;
+    (*fault_file).faults = WrapperVfs::<&'static Faults>::from_raw(vfs).state;
+    // SQLite closes the file whenever its methods are set, even if opening it
+    // failed
+    (*fault_file).file.pMethods = if (*real).pMethods.is_null() {
+        ptr::null()
+    } else {
+        &IO_METHODS
+    };
+    rc
+}
+
+static IO_METHODS: sqlite3_io_methods = sqlite3_io_methods {
+    iVersion: 3,
+    xClose: Some(x_close),
+    xRead: Some(x_read),
+    xWrite: Some(x_write),
+    xTruncate: Some(x_truncate),
+    xSync: Some(x_sync),
+    xFileSize: Some(x_file_size),
+    xLock: Some(x_lock),
+    xUnlock: Some(x_unlock),
+    xCheckReservedLock: Some(x_check_reserved_lock),
+    xFileControl: Some(x_file_control),
+    xSectorSize: Some(x_sector_size),
+    xDeviceCharacteristics: Some(x_device_characteristics),
+    xShmMap: Some(x_shm_map),
+    xShmLock: Some(x_shm_lock),
+    xShmBarrier: Some(x_shm_barrier),
+    xShmUnmap: Some(x_shm_unmap),
+    xFetch: Some(x_fetch),
+    xUnfetch: Some(x_unfetch),
+};
+
+unsafe extern "C" fn x_close(file: *mut sqlite3_file) -> c_int {
+    let (real, methods) = real(file);
+    methods.xClose.unwrap()(real)
+}
+
+unsafe extern "C" fn x_read(
+    file: *mut sqlite3_file,
+    buf: *mut c_void,
+    amount: c_int,
+    offset: sqlite3_int64,
+) -> c_int {
+    if faults(file).check(FaultOp::Read) {
+        return FaultOp::Read.error_code();
+    }
+    let (real, methods) = real(file);
+    methods.xRead.unwrap()(real, buf, amount, offset)
+}
+
+unsafe extern "C" fn x_write(
+    file: *mut sqlite3_file,
+    buf: *const c_void,
+    amount: c_int,
+    offset: sqlite3_int64,
+) -> c_int {
+    if faults(file).check(FaultOp::Write) {
+        return FaultOp::Write.error_code();
+    }
+    let (real, methods) = real(file);
+    methods.xWrite.unwrap()(real, buf, amount, offset)
+}
+
+unsafe extern "C" fn x_truncate(file: *mut sqlite3_file, size: sqlite3_int64) -> c_int {
+    let (real, methods) = real(file);
+    methods.xTruncate.unwrap()(real, size)
+}
+
+unsafe extern "C" fn x_sync(file: *mut sqlite3_file, flags: c_int) -> c_int {
+    if faults(file).check(FaultOp::Sync) {
+        return FaultOp::Sync.error_code();
+    }
+    let (real, methods) = real(file);
+    methods.xSync.unwrap()(real, flags)
+}
+
+unsafe extern "C" fn x_file_size(file: *mut sqlite3_file, size: *mut sqlite3_int64) -> c_int {
+    let (real, methods) = real(file);
+    methods.xFileSize.unwrap()(real, size)
+}
+
+unsafe extern "C" fn x_lock(file: *mut sqlite3_file, level: c_int) -> c_int {
+    if level >= SQLITE_LOCK_RESERVED && faults(file).check(FaultOp::Lock) {
+        return FaultOp::Lock.error_code();
+    }
+    let (real, methods) = real(file);
+    methods.xLock.unwrap()(real, level)
+}
+
+unsafe extern "C" fn x_unlock(file: *mut sqlite3_file, level: c_int) -> c_int {
+    let (real, methods) = real(file);
+    methods.xUnlock.unwrap()(real, level)
+}
+
+unsafe extern "C" fn x_check_reserved_lock(file: *mut sqlite3_file, res_out: *mut c_int) -> c_int {
+    let (real, methods) = real(file);
+    methods.xCheckReservedLock.unwrap()(real, res_out)
+}
+
+unsafe extern "C" fn x_file_control(file: *mut sqlite3_file, op: c_int, arg: *mut c_void) -> c_int {
+    let (real, methods) = real(file);
+    methods.xFileControl.unwrap()(real, op, arg)
+}
+
+unsafe extern "C" fn x_sector_size(file: *mut sqlite3_file) -> c_int {
+    let (real, methods) = real(file);
+    methods.xSectorSize.unwrap()(real)
+}
+
+unsafe extern "C" fn x_device_characteristics(file: *mut sqlite3_file) -> c_int {
+    let (real, methods) = real(file);
+    methods.xDeviceCharacteristics.unwrap()(real)
+}
+
+unsafe extern "C" fn x_shm_map(
+    file: *mut sqlite3_file,
+    page: c_int,
+    page_size: c_int,
+    extend: c_int,
+    out: *mut *mut c_void,
+) -> c_int {
+    let (real, methods) = real(file);
+    match methods.xShmMap {
+        Some(f) if methods.iVersion >= 2 => f(real, page, page_size, extend, out),
+        _ => libsqlite3_sys::SQLITE_IOERR_SHMMAP,
+    }
+}
+
+unsafe extern "C" fn x_shm_lock(
+    file: *mut sqlite3_file,
+    offset: c_int,
+    n: c_int,
+    flags: c_int,
+) -> c_int {
+    // Slot 0 is the WAL write lock
+    if offset == 0
+        && flags == SQLITE_SHM_LOCK | SQLITE_SHM_EXCLUSIVE
+        && faults(file).check(FaultOp::Lock)
+    {
+        return FaultOp::Lock.error_code();
+    }
+    let (real, methods) = real(file);
+    match methods.xShmLock {
+        Some(f) if methods.iVersion >= 2 => f(real, offset, n, flags),
+        _ => libsqlite3_sys::SQLITE_IOERR_SHMLOCK,
+    }
+}
+
+unsafe extern "C" fn x_shm_barrier(file: *mut sqlite3_file) {
+    let (real, methods) = real(file);
+    if let Some(f) = methods.xShmBarrier.filter(|_| methods.iVersion >= 2) {
+        f(real);
+    }
+}
+
+unsafe extern "C" fn x_shm_unmap(file: *mut sqlite3_file, delete: c_int) -> c_int {
+    let (real, methods) = real(file);
+    match methods.xShmUnmap {
+        Some(f) if methods.iVersion >= 2 => f(real, delete),
+        _ => SQLITE_OK,
+    }
+}
+
+unsafe extern "C" fn x_fetch(
+    file: *mut sqlite3_file,
+    offset: sqlite3_int64,
+    amount: c_int,
+    out: *mut *mut c_void,
+) -> c_int {
+    let (real, methods) = real(file);
+    match methods.xFetch {
+        Some(f) if methods.iVersion >= 3 => f(real, offset, amount, out),
+        _ => {
+            *out = ptr::null_mut();
+            SQLITE_OK
+        }
+    }
+}
+
+unsafe extern "C" fn x_unfetch(
+    file: *mut sqlite3_file,
+    offset: sqlite3_int64,
+    page: *mut c_void,
+) -> c_int {
+    let (real, methods) = real(file);
+    match methods.xUnfetch {
+        Some(f) if methods.iVersion >= 3 => f(real, offset, page),
+        _ => SQLITE_OK,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{open_pool, PoolOptions};
+
+    #[tokio::test]
+    async fn test_fault_injector_busy_and_io_errors() {
+        let path = std::env::temp_dir().join(format!("rapsqlite-faults-{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        std::fs::File::create(&path).unwrap();
+        let faults = FaultInjector::new();
+        let options = PoolOptions {
+            vfs: Some(faults.vfs_name(None).to_string()),
+            ..PoolOptions::default()
+        };
+        let pool = open_pool(path.to_str().unwrap(), &options).await.unwrap();
+        sqlx::query("CREATE TABLE t (x)")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        // The busy handler retries until the write lock is granted
+        faults.fail_next(FaultOp::Lock, 2);
+        sqlx::query("INSERT INTO t VALUES (1)")
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert_eq!(faults.injected(FaultOp::Lock), 2);
+
+        faults.fail_next(FaultOp::Write, 1);
+        let err = sqlx::query("INSERT INTO t VALUES (2)")
+            .execute(&pool)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("disk I/O error"), "{err}");
+
+        faults.reset();
+        assert_eq!(faults.injected(FaultOp::Write), 0);
+        sqlx::query("INSERT INTO t VALUES (3)")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let (values,): (String,) = sqlx::query_as("SELECT group_concat(x) FROM t")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(values, "1,3");
+        assert_eq!(faults.vfs_name(None), options.vfs.as_deref().unwrap());
+
+        // Injectors share the VFS and its faults; a new one starts clean
+        faults.fail_next(FaultOp::Read, 5);
+        let other = FaultInjector::new();
+        assert_eq!(other.vfs_name(None), options.vfs.as_deref().unwrap());
+        assert_eq!(format!("{faults:?}"), format!("{other:?}"));
+        sqlx::query("SELECT * FROM t").execute(&pool).await.unwrap();
+        assert_eq!(other.injected(FaultOp::Read), 0);
+
+        pool.close().await;
+        let _ = std::fs::remove_file(&path);
+    }
+}
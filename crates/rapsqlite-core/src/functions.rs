@@ -0,0 +1,241 @@
+//! Rust-native SQL functions: `uuid4()`, `sha256(X)`, `md5(X)` and
+//! `regexp_replace(X, pattern, replacement)`.
+//!
+//! [`install`] registers them on a connection. They run entirely in Rust, so
+//! unlike Python user-defined functions they need no GIL and work on every
+//! pooled connection.
+//!
+//! * `uuid4()` - a random version 4 UUID as lowercase hyphenated TEXT.
+//! * `sha256(X)`, `md5(X)` - lowercase hex digest of a TEXT or BLOB value
+//!   (numbers are hashed in their text form).
+//! * `regexp_replace(X, pattern, replacement)` - replace every match of
+//!   `pattern` in X; `$1` / `${name}` in `replacement` refer to capture groups.
+//!
+//! Each function returns NULL when any argument is NULL.
+
+use libsqlite3_sys::{
+    sqlite3, sqlite3_context, sqlite3_create_function_v2, sqlite3_get_auxdata, sqlite3_randomness,
+    sqlite3_result_error, sqlite3_result_null, sqlite3_result_text, sqlite3_set_auxdata,
+    sqlite3_value, sqlite3_value_blob, sqlite3_value_bytes, sqlite3_value_text, sqlite3_value_type,
+    SQLITE_DETERMINISTIC, SQLITE_INNOCUOUS, SQLITE_NULL, SQLITE_OK, SQLITE_TRANSIENT, SQLITE_UTF8,
+};
+use md5::Md5;
+use regex::bytes::Regex;
+use sha2::{Digest, Sha256};
+use std::ffi::{c_int, c_void, CString};
+
+type ScalarFn = unsafe extern "C" fn(*mut sqlite3_context, c_int, *mut *mut sqlite3_value);
+
+/// Register the native functions on `db`. Returns the first non-`SQLITE_OK`
+/// result code from `sqlite3_create_function_v2`.
+///
+/// # Safety
+///
+/// `db` must be a valid `sqlite3*` that the caller has exclusive use of.
+pub unsafe fn install(db: *mut sqlite3) -> c_int {
+    let deterministic = SQLITE_UTF8 | SQLITE_DETERMINISTIC | SQLITE_INNOCUOUS;
+    let functions: [(&str, c_int, c_int, ScalarFn); 4] = [
+        ("uuid4", 0, SQLITE_UTF8 | SQLITE_INNOCUOUS, uuid4),
+        ("sha256", 1, deterministic, sha256),
+        ("md5", 1, deterministic, md5),
+        ("regexp_replace", 3, deterministic, regexp_replace),
+    ];
+    for (name, n_arg, flags, func) in functions {
+        let name = CString::new(name).expect("function names contain no NUL");
+        let rc = sqlite3_create_function_v2(
+            db,
+            name.as_ptr(),
+            n_arg,
+            flags,
+            std::ptr::null_mut(),
+            Some(func),
+            None,
+            None,
+            None,
+        );
+        if rc != SQLITE_OK {
+            return rc;
+        }
+    }
+    SQLITE_OK
+}
+
+/// Bytes of a TEXT or BLOB argument (numbers in their text form), or None for NULL.
+unsafe fn arg_bytes<'a>(value: *mut sqlite3_value) -> Option<&'a [u8]> {
+    if sqlite3_value_type(value) == SQLITE_NULL {
+        return None;
+    }
+    // sqlite3_value_blob must be called before sqlite3_value_bytes
+    let ptr = sqlite3_value_blob(value) as *const u8;
+    let len = sqlite3_value_bytes(value) as usize;
+    Some(if ptr.is_null() || len == 0 {
+        &[]
+    } else {
+        std::slice::from_raw_parts(ptr, len)
+    })
+}
+
+unsafe fn result_text(ctx: *mut sqlite3_context, text: &[u8]) {
+    sqlite3_result_text(
+        ctx,
+        text.as_ptr() as *const _,
+        text.len() as c_int,
+        SQLITE_TRANSIENT(),
+    );
+}
+
+unsafe fn result_error(ctx: *mut sqlite3_context, message: &str) {
+    sqlite3_result_error(ctx, message.as_ptr() as *const _, message.len() as c_int);
+}
+
+fn hex(digest: &[u8]) -> String {
+    digest.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// Format 16 random bytes as a version 4, RFC 4122 variant UUID.
+fn format_uuid4(mut bytes: [u8; 16]) -> String {
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex = hex(&bytes);
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+unsafe extern "C" fn uuid4(
+    ctx: *mut sqlite3_context,
+    _argc: c_int,
+    _argv: *mut *mut sqlite3_value,
+) {
+    let mut bytes = [0u8; 16];
+    sqlite3_randomness(bytes.len() as c_int, bytes.as_mut_ptr() as *mut c_void);
+    result_text(ctx, format_uuid4(bytes).as_bytes());
+}
+
+unsafe extern "C" fn sha256(
+    ctx: *mut sqlite3_context,
+    _argc: c_int,
+    argv: *mut *mut sqlite3_value,
+) {
+    match arg_bytes(*argv) {
+        Some(data) => result_text(ctx, hex(&Sha256::digest(data)).as_bytes()),
+        None => sqlite3_result_null(ctx),
+    }
+}
+
+unsafe extern "C" fn md5(ctx: *mut sqlite3_context, _argc: c_int, argv: *mut *mut sqlite3_value) {
+    match arg_bytes(*argv) {
+        Some(data) => result_text(ctx, hex(&Md5::digest(data)).as_bytes()),
+        None => sqlite3_result_null(ctx),
+    }
+}
+
+unsafe extern "C" fn regexp_replace(
+    ctx: *mut sqlite3_context,
+    _argc: c_int,
+    argv: *mut *mut sqlite3_value,
+) {
+    let (Some(text), Some(replacement)) = (arg_bytes(*argv), arg_bytes(*argv.add(2))) else {
+        sqlite3_result_null(ctx);
+        return;
+    };
+    let pattern = *argv.add(1);
+    if sqlite3_value_type(pattern) == SQLITE_NULL {
+        sqlite3_result_null(ctx);
+        return;
+    }
+
+    // A constant pattern is compiled once per statement and kept as auxdata
+    let cached = sqlite3_get_auxdata(ctx, 1) as *const Regex;
+    if let Some(regex) = cached.as_ref() {
+        result_text(ctx, &regex.replace_all(text, replacement));
+        return;
+    }
+    let pattern = sqlite3_value_text(pattern);
+    let pattern = std::ffi::CStr::from_ptr(pattern as *const _).to_string_lossy();
+    let regex = match Regex::new(&pattern) {
+        Ok(regex) => regex,
+        Err(e) => {
+            result_error(ctx, &format!("regexp_replace: invalid pattern: {e}"));
+            return;
+        }
+    };
+    result_text(ctx, &regex.replace_all(text, replacement));
+    // SQLite may free the regex before this returns, so it is not used after
+    unsafe extern "C" fn free_regex(ptr: *mut c_void) {
+        drop(Box::from_raw(ptr as *mut Regex));
+    }
+    sqlite3_set_auxdata(
+        ctx,
+        1,
+        Box::into_raw(Box::new(regex)) as *mut c_void,
+        Some(free_regex),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{open_pool, PoolOptions};
+
+    #[test]
+    fn test_format_uuid4() {
+        assert_eq!(
+            format_uuid4([0xff; 16]),
+            "ffffffff-ffff-4fff-bfff-ffffffffffff"
+        );
+        assert_eq!(
+            format_uuid4([0; 16]),
+            "00000000-0000-4000-8000-000000000000"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_native_functions() {
+        let options = PoolOptions {
+            native_functions: true,
+            ..PoolOptions::default()
+        };
+        let pool = open_pool(":memory:", &options).await.unwrap();
+        let (sha, md5, replaced, null): (String, String, String, Option<String>) = sqlx::query_as(
+            "SELECT sha256('abc'), md5(X'616263'), \
+                 regexp_replace('a1b22', '([0-9]+)', '<$1>'), md5(NULL)",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(
+            sha,
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(md5, "900150983cd24fb0d6963f7d28e17f72");
+        assert_eq!(replaced, "a<1>b<22>");
+        assert_eq!(null, None);
+
+        let (uuid,): (String,) = sqlx::query_as("SELECT uuid4()")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(uuid.len(), 36);
+        assert_eq!(&uuid[14..15], "4");
+
+        let err = sqlx::query("SELECT regexp_replace('a', '(', '')")
+            .execute(&pool)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("invalid pattern"));
+
+        let pool = open_pool(":memory:", &PoolOptions::default())
+            .await
+            .unwrap();
+        assert!(sqlx::query("SELECT uuid4()")
+            .fetch_one(&pool)
+            .await
+            .is_err());
+    }
+}
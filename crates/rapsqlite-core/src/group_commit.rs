@@ -0,0 +1,249 @@
+//! Group commit: small writes submitted by concurrent callers within a short
+//! window are run in one transaction by a writer task, so the group pays for a
+//! single fsync. Each caller's future resolves once the group has committed.
+
+use sqlx::pool::PoolConnection;
+use sqlx::{Sqlite, SqlitePool};
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+use tokio::time::Instant;
+
+use crate::query::bind_and_execute_on_connection;
+use crate::SqliteParam;
+
+/// Settings for [`GroupCommitter::spawn`].
+#[derive(Clone, Copy, Debug)]
+pub struct GroupCommitOptions {
+    /// How long the writer waits for more statements after the first one of a group.
+    pub window: Duration,
+    /// Statements per transaction at most; a full group commits immediately.
+    pub max_batch: usize,
+}
+
+impl Default for GroupCommitOptions {
+    fn default() -> Self {
+        GroupCommitOptions {
+            window: Duration::from_millis(2),
+            max_batch: 1000,
+        }
+    }
+}
+
+/// Why a statement submitted to [`GroupCommitter::execute`] was not applied.
+#[derive(Debug)]
+pub enum GroupCommitError {
+    /// The statement itself failed; the rest of its group is unaffected.
+    Statement(sqlx::Error),
+    /// The group's transaction failed (acquiring a connection, BEGIN, COMMIT, or
+    /// an `ON CONFLICT ROLLBACK` in another statement), so nothing was applied.
+    Transaction(Arc<sqlx::Error>),
+    /// The writer task has stopped.
+    Closed,
+}
+
+impl fmt::Display for GroupCommitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GroupCommitError::Statement(e) => write!(f, "{e}"),
+            GroupCommitError::Transaction(e) => write!(f, "Group commit failed: {e}"),
+            GroupCommitError::Closed => write!(f, "Group commit writer has stopped"),
+        }
+    }
+}
+
+impl std::error::Error for GroupCommitError {}
+
+/// (rows affected, last insert rowid) of a committed statement.
+pub type GroupCommitResult = Result<(u64, i64), GroupCommitError>;
+
+type StatementResult = Result<(u64, i64), sqlx::Error>;
+
+struct Job {
+    query: String,
+    params: Vec<SqliteParam>,
+    reply: oneshot::Sender<GroupCommitResult>,
+}
+
+/// Handle to a writer task. Cloning shares the task; it exits once every handle
+/// is dropped and the queued statements have been committed.
+#[derive(Clone)]
+pub struct GroupCommitter {
+    sender: mpsc::UnboundedSender<Job>,
+}
+
+impl GroupCommitter {
+    /// Spawn a writer task for `pool` on the current Tokio runtime.
+    pub fn spawn(pool: SqlitePool, options: GroupCommitOptions) -> Self {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        tokio::spawn(run_writer(pool, options, receiver));
+        GroupCommitter { sender }
+    }
+
+    /// Queue a write and wait until the group containing it has committed.
+    pub async fn execute(&self, query: &str, params: &[SqliteParam]) -> GroupCommitResult {
+        let (reply, result) = oneshot::channel();
+        let job = Job {
+            query: query.to_string(),
+            params: params.to_vec(),
+            reply,
+        };
+        self.sender
+            .send(job)
+            .map_err(|_| GroupCommitError::Closed)?;
+        result.await.unwrap_or(Err(GroupCommitError::Closed))
+    }
+}
+
+async fn run_writer(
+    pool: SqlitePool,
+    options: GroupCommitOptions,
+    mut receiver: mpsc::UnboundedReceiver<Job>,
+) {
+    while let Some(first) = receiver.recv().await {
+        let mut batch = vec![first];
+        let deadline = Instant::now() + options.window;
+        while batch.len() < options.max_batch.max(1) {
+            match tokio::time::timeout_at(deadline, receiver.recv()).await {
+                Ok(Some(job)) => batch.push(job),
+                // Window elapsed, or every handle was dropped
+                _ => break,
+            }
+        }
+        commit_batch(&pool, batch).await;
+    }
+}
+
+/// Run `batch` in transactions on one connection and answer every job.
+async fn commit_batch(pool: &SqlitePool, batch: Vec<Job>) {
+    let mut conn = match pool.acquire().await {
+        Ok(conn) => conn,
+        Err(e) => return fail_all(batch, Arc::new(e)),
+    };
+    let mut jobs = batch.into_iter().peekable();
+    while jobs.peek().is_some() {
+        if let Err(e) = sqlx::query("BEGIN IMMEDIATE").execute(&mut *conn).await {
+            return fail_all(jobs.collect(), Arc::new(e));
+        }
+        // Answered only after COMMIT, so callers never observe uncommitted data
+        let mut pending: Vec<(Job, StatementResult)> = Vec::new();
+        let mut rolled_back = false;
+        for job in jobs.by_ref() {
+            let result = bind_and_execute_on_connection(&job.query, &job.params, &mut conn)
+                .await
+                .map(|r| (r.rows_affected(), r.last_insert_rowid()));
+            let failed = result.is_err();
+            pending.push((job, result));
+            // ON CONFLICT ROLLBACK ends the transaction: earlier statements are gone
+            // and the remaining jobs start a new group.
+            if failed && is_autocommit(&mut conn).await {
+                rolled_back = true;
+                break;
+            }
+        }
+
+        let failure = if rolled_back {
+            Some(sqlx::Error::Protocol(
+                "transaction rolled back by ON CONFLICT ROLLBACK in another statement".into(),
+            ))
+        } else {
+            match sqlx::query("COMMIT").execute(&mut *conn).await {
+                Ok(_) => None,
+                Err(e) => {
+                    let _ = sqlx::query("ROLLBACK").execute(&mut *conn).await;
+                    Some(e)
+                }
+            }
+        };
+        let cause = failure.map(Arc::new);
+        for (job, result) in pending {
+            let reply = match (result, &cause) {
+                (Err(e), _) => Err(GroupCommitError::Statement(e)),
+                (Ok(_), Some(cause)) => Err(GroupCommitError::Transaction(Arc::clone(cause))),
+                (Ok(done), None) => Ok(done),
+            };
+            let _ = job.reply.send(reply);
+        }
+    }
+}
+
+fn fail_all(jobs: Vec<Job>, cause: Arc<sqlx::Error>) {
+    for job in jobs {
+        let _ = job
+            .reply
+            .send(Err(GroupCommitError::Transaction(Arc::clone(&cause))));
+    }
+}
+
+/// True when no transaction is open on `conn`.
+async fn is_autocommit(conn: &mut PoolConnection<Sqlite>) -> bool {
+    match conn.lock_handle().await {
+        // Safety: the handle stays locked while the pointer is used.
+        Ok(mut handle) => unsafe {
+            libsqlite3_sys::sqlite3_get_autocommit(handle.as_raw_handle().as_ptr()) != 0
+        },
+        Err(_) => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{open_pool, PoolOptions};
+
+    #[tokio::test]
+    async fn test_group_commit_batches_and_isolates_failures() {
+        let path = std::env::temp_dir().join(format!("rapsqlite-group-{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        std::fs::File::create(&path).unwrap();
+        let options = PoolOptions {
+            max_connections: Some(2),
+            ..PoolOptions::default()
+        };
+        let pool = open_pool(path.to_str().unwrap(), &options).await.unwrap();
+        sqlx::query("CREATE TABLE t (id INTEGER PRIMARY KEY, v UNIQUE ON CONFLICT ROLLBACK)")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let committer = GroupCommitter::spawn(
+            pool.clone(),
+            GroupCommitOptions {
+                window: Duration::from_millis(50),
+                max_batch: 100,
+            },
+        );
+        let insert = |v: i64| {
+            let committer = committer.clone();
+            async move {
+                committer
+                    .execute("INSERT INTO t (v) VALUES (?)", &[SqliteParam::Int(v)])
+                    .await
+            }
+        };
+        let (a, b, c) = tokio::join!(insert(1), insert(2), insert(3));
+        assert!(a.is_ok() && b.is_ok() && c.is_ok());
+        let (count,): (i64,) = sqlx::query_as("SELECT count(*) FROM t")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(count, 3);
+
+        // The duplicate rolls back its group: the earlier insert is reported as
+        // failed and the later one commits in a new transaction.
+        let (a, b, c) = tokio::join!(insert(10), insert(1), insert(11));
+        assert!(matches!(a, Err(GroupCommitError::Transaction(_))));
+        assert!(matches!(b, Err(GroupCommitError::Statement(_))));
+        assert!(c.is_ok());
+        let values: Vec<i64> = sqlx::query_scalar("SELECT v FROM t ORDER BY v")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        assert_eq!(values, vec![1, 2, 3, 11]);
+
+        drop(committer);
+        pool.close().await;
+        let _ = std::fs::remove_file(&path);
+    }
+}
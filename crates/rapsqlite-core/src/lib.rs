@@ -0,0 +1,107 @@
+//! Async SQLite engine behind the `rapsqlite` Python package.
+//!
+//! This crate holds the parts of rapsqlite that don't depend on Python. The
+//! `rapsqlite` crate wraps it with PyO3 and maps [`Error`] / `sqlx::Error` to
+//! Python exceptions.
+//!
+//! - [`open_pool`] and [`query`]: pool creation, parameter binding and query
+//!   execution on top of sqlx.
+//! - [`sql`]: SQL text helpers for statement splitting and schema
+//!   introspection.
+//! - [`cache`]: the query result cache.
+//! - [`group_commit`]: the group-commit writer.
+//! - [`debug`]: debug-mode diagnostics.
+//! - [`authorizer`]: the hardened-mode authorizer.
+//! - [`functions`]: Rust-native SQL functions.
+//! - [`profile`]: statement profiling.
+//! - [`wal_archive`] and [`wal_hook`]: WAL archiving and replication events.
+//! - [`activity`]: the statements running on each pooled connection.
+//! - [`deadline`]: per-task statement deadlines.
+//! - [`queue`]: tasks waiting for a pooled connection.
+//! - [`lifecycle`]: lifecycle events of pooled connections.
+//! - [`backup`], [`compression`], [`encryption`] and [`csv`]: backups, dumps
+//!   and exports.
+//! - [`s3`]: backups to S3-compatible object storage.
+//! - [`remote`]: a read-through cache of remote databases.
+//! - [`clock`] and [`faults`]: a fake clock for SQL date and time functions
+//!   and fault injection, for tests.
+//!
+//! # Raw handles
+//!
+//! Functions taking a `*mut sqlite3` are `unsafe`: the handle must be valid and
+//! nothing else may use it until the function returns. A pool connection's
+//! handle qualifies while `lock_handle()` holds it.
+//!
+//! ```no_run
+//! use rapsqlite_core::{open_pool, query, PoolOptions, SqliteParam};
+//!
+//! # async fn demo() -> Result<(), Box<dyn std::error::Error>> {
+//! let options = PoolOptions {
+//!     pragmas: vec![("journal_mode".into(), "WAL".into())],
+//!     ..PoolOptions::default()
+//! };
+//! let pool = open_pool("app.db", &options).await?;
+//! query::bind_and_execute(
+//!     "INSERT INTO users (name) VALUES (?)",
+//!     &[SqliteParam::Text("ann".into())],
+//!     &pool,
+//! )
+//! .await?;
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod activity;
+
+pub mod authorizer;
+
+pub mod backup;
+
+pub mod cache;
+
+pub mod clock;
+
+pub mod compression;
+
+pub mod csv;
+
+pub mod debug;
+
+pub mod deadline;
+
+pub mod encryption;
+
+pub mod faults;
+
+mod error;
+pub use error::Error;
+
+pub mod functions;
+
+pub mod group_commit;
+
+pub mod lifecycle;
+
+mod param;
+pub use param::SqliteParam;
+
+mod pool;
+pub use pool::{open_pool, PoolOptions};
+
+pub mod profile;
+
+pub mod query;
+
+pub mod queue;
+
+pub mod remote;
+
+pub mod s3;
+
+pub mod sql;
+
+pub mod wal_archive;
+
+pub mod wal_hook;
+
+mod vfs;
@@ -0,0 +1,174 @@
+//! Lifecycle events of pooled connections.
+//!
+//! A [`PoolListener`] set as [`PoolOptions::events`](crate::PoolOptions) is
+//! told when each pooled connection is opened, handed out, returned to the
+//! pool and closed. [`install`] gives every new connection an id, kept as
+//! SQLite client data whose destructor reports the close, so a close is seen
+//! whatever made sqlx close the connection: the pool closing, the idle timeout,
+//! the maximum lifetime or a failed event.
+
+use futures_util::future::BoxFuture;
+use libsqlite3_sys::{sqlite3, sqlite3_get_clientdata, sqlite3_set_clientdata};
+use std::ffi::{c_void, CStr};
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+const EVENTS_KEY: &CStr = c"rapsqlite.lifecycle";
+
+/// Something that happened to a pooled connection while it is open.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PoolEvent {
+    /// The connection was opened. It is handed out right after, so an
+    /// [`PoolEvent::Acquire`] follows.
+    Connect,
+    /// The connection is about to be handed out by `acquire()`.
+    Acquire,
+    /// The connection was returned to the pool. Not reported for the first
+    /// connection, which opening the pool hands out and back internally, or
+    /// when the pool is closing.
+    Release,
+}
+
+/// A pooled connection reported to a [`PoolListener`].
+pub struct EventConnection {
+    /// Unique to the connection for the life of the process.
+    pub id: u64,
+    db: *mut sqlite3,
+}
+
+// Safety: the handle is only used while the pool keeps the connection locked
+// for the event (see `PoolListener::event`).
+unsafe impl Send for EventConnection {}
+
+impl EventConnection {
+    /// The connection's `sqlite3*`, locked until the event's future completes.
+    pub fn db(&self) -> *mut sqlite3 {
+        self.db
+    }
+}
+
+/// Receives the lifecycle events of a pool's connections.
+pub trait PoolListener: Send + Sync {
+    /// Whether `event` needs to be reported. Reporting locks the connection,
+    /// so events nobody listens to are skipped.
+    fn wants(&self, event: PoolEvent) -> bool;
+
+    /// Handle `event`. The connection stays locked, and may be used through
+    /// [`EventConnection::db`], until the returned future completes. On an
+    /// error the connection is closed: for [`PoolEvent::Connect`] the pool
+    /// then retries opening one until its acquire timeout; for the other
+    /// events it is not handed out or kept.
+    fn event(
+        &self,
+        event: PoolEvent,
+        conn: EventConnection,
+    ) -> BoxFuture<'static, Result<(), String>>;
+
+    /// The connection with this id was closed. Runs on the thread closing it,
+    /// so it should hand the event off rather than block.
+    fn closed(&self, id: u64);
+}
+
+impl fmt::Debug for dyn PoolListener {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("PoolListener")
+    }
+}
+
+struct Registration {
+    id: u64,
+    listener: Arc<dyn PoolListener>,
+}
+
+unsafe extern "C" fn closed(registration: *mut c_void) {
+    let registration = Box::from_raw(registration as *mut Registration);
+    registration.listener.closed(registration.id);
+}
+
+/// Give `db` a new id and report its close to `listener`. Returns the id.
+///
+/// # Safety
+///
+/// `db` must meet the [raw handle](crate#raw-handles) requirements.
+pub unsafe fn install(db: *mut sqlite3, listener: Arc<dyn PoolListener>) -> u64 {
+    static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    let data = Box::into_raw(Box::new(Registration { id, listener })) as *mut c_void;
+    sqlite3_set_clientdata(db, EVENTS_KEY.as_ptr(), data, Some(closed));
+    id
+}
+
+/// The connection `db` for an event, if [`install`] was called on it.
+///
+/// # Safety
+///
+/// `db` must be a valid `sqlite3*` that stays locked while the result is used.
+pub unsafe fn connection(db: *mut sqlite3) -> Option<EventConnection> {
+    let registration = sqlite3_get_clientdata(db, EVENTS_KEY.as_ptr()) as *const Registration;
+    registration.as_ref().map(|registration| EventConnection {
+        id: registration.id,
+        db,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{open_pool, PoolOptions};
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct Recorder {
+        events: Mutex<Vec<(&'static str, u64)>>,
+    }
+
+    impl PoolListener for Recorder {
+        fn wants(&self, _event: PoolEvent) -> bool {
+            true
+        }
+
+        fn event(
+            &self,
+            event: PoolEvent,
+            conn: EventConnection,
+        ) -> BoxFuture<'static, Result<(), String>> {
+            let name = match event {
+                PoolEvent::Connect => "connect",
+                PoolEvent::Acquire => "acquire",
+                PoolEvent::Release => "release",
+            };
+            self.events.lock().unwrap().push((name, conn.id));
+            Box::pin(async { Ok(()) })
+        }
+
+        fn closed(&self, id: u64) {
+            self.events.lock().unwrap().push(("close", id));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_pool_events() {
+        let recorder = Arc::new(Recorder::default());
+        let options = PoolOptions {
+            events: Some(Arc::clone(&recorder) as Arc<dyn PoolListener>),
+            ..PoolOptions::default()
+        };
+        let pool = open_pool(":memory:", &options).await.unwrap();
+        drop(pool.acquire().await.unwrap());
+        // Connections are returned to the pool by a spawned task
+        for _ in 0..100 {
+            if recorder.events.lock().unwrap().len() == 4 {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        pool.close().await;
+
+        let events = recorder.events.lock().unwrap();
+        let names: Vec<_> = events.iter().map(|e| e.0).collect();
+        // Opening the pool hands its first connection out and back internally
+        assert_eq!(names, ["connect", "acquire", "acquire", "release", "close"]);
+        assert!(events.iter().all(|e| e.1 == events[0].1));
+    }
+}
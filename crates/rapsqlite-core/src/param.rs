@@ -0,0 +1,11 @@
+//! Parameter values bound to SQL statements.
+
+/// A value bound to a statement parameter, one variant per SQLite storage class.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SqliteParam {
+    Null,
+    Int(i64),
+    Real(f64),
+    Text(String),
+    Blob(Vec<u8>),
+}
@@ -0,0 +1,311 @@
+//! Pool creation.
+
+use libsqlite3_sys::{SQLITE_OK, SQLITE_READONLY};
+use sqlx::sqlite::{SqliteConnectOptions, SqliteConnection, SqlitePoolOptions};
+use sqlx::SqlitePool;
+use std::ffi::c_int;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::activity::{self, Activity};
+use crate::authorizer::{self, StatementPolicy};
+use crate::deadline;
+use crate::lifecycle::{self, PoolEvent, PoolListener};
+use crate::wal_hook::{self, WalHook};
+use crate::{functions, profile, Error};
+
+/// Settings for [`open_pool`].
+#[derive(Clone, Debug, Default)]
+pub struct PoolOptions {
+    /// Maximum number of pooled connections (default and minimum 1).
+    pub max_connections: Option<usize>,
+    /// Seconds to wait when acquiring a connection (default 30).
+    pub acquire_timeout_secs: Option<u64>,
+    /// `PRAGMA name = value` pairs run once the pool is open.
+    pub pragmas: Vec<(String, String)>,
+    /// Hardened-mode authorizer installed on every pooled connection. The
+    /// PRAGMAs in `pragmas` must be allowed by it for the pool to open.
+    pub policy: Option<Arc<StatementPolicy>>,
+    /// Register the [`functions`](crate::functions) (`uuid4()`, `sha256()`,
+    /// `md5()`, `regexp_replace()`) on every pooled connection.
+    pub native_functions: bool,
+    /// Install this [`WalHook`] on every pooled connection. The pool switches
+    /// the database to WAL mode and, when the hook has an archive, writes a
+    /// base snapshot into it once it is open.
+    pub wal_hook: Option<Arc<WalHook>>,
+    /// Report the [`lifecycle`] events of every pooled connection to this
+    /// listener.
+    pub events: Option<Arc<dyn PoolListener>>,
+    /// Open every pooled connection with the VFS registered under this name,
+    /// such as a [`FakeClock`](crate::clock::FakeClock)'s.
+    pub vfs: Option<String>,
+    /// Open every pooled connection read-only (`mode=ro`), for the reader pool
+    /// next to a writer pool on the same file. PRAGMAs that would write the
+    /// file are left to the writer pool, and so is switching to WAL mode.
+    pub read_only: bool,
+    /// Record the statements running on every pooled connection in this
+    /// [`Activity`], with the connection ids [`lifecycle`] gives them.
+    pub activity: Option<Arc<Activity>>,
+    /// Interrupt statements on every pooled connection once the
+    /// [`deadline`] of the task running them has passed.
+    pub deadlines: bool,
+}
+
+/// Open a connection pool for the SQLite database at `path` and apply PRAGMAs.
+pub async fn open_pool(path: &str, options: &PoolOptions) -> Result<SqlitePool, Error> {
+    let max_conn = options.max_connections.unwrap_or(1).max(1) as u32;
+    let timeout = options.acquire_timeout_secs.unwrap_or(30);
+    let policy = options.policy.clone();
+    let native_functions = options.native_functions;
+    let wal_hook = options.wal_hook.clone();
+    let events = options.events.clone();
+    let activity = options.activity.clone();
+    let deadlines = options.deadlines;
+    let mut pool_options = SqlitePoolOptions::new()
+        .max_connections(max_conn)
+        .acquire_timeout(Duration::from_secs(timeout))
+        .after_connect(move |conn, _meta| {
+            let policy = policy.clone();
+            let wal_hook = wal_hook.clone();
+            let events = events.clone();
+            let activity = activity.clone();
+            Box::pin(async move {
+                let mut handle = conn.lock_handle().await?;
+                {
+                    let db = handle.as_raw_handle().as_ptr();
+                    // Safety: the handle lock gives exclusive use of the connection.
+                    unsafe { profile::set_scan_status(db, false) };
+                    if let Some(policy) = policy {
+                        // Safety: as above.
+                        unsafe { authorizer::install(db, policy) };
+                    }
+                    // Safety: as above.
+                    if native_functions && unsafe { functions::install(db) } != SQLITE_OK {
+                        return Err(sqlx::Error::Protocol(
+                            "failed to register native SQL functions".into(),
+                        ));
+                    }
+                    if let Some(wal_hook) = wal_hook {
+                        // Safety: as above.
+                        unsafe { wal_hook::install(db, wal_hook) };
+                    }
+                    if let Some(events) = &events {
+                        // Safety: as above.
+                        unsafe { lifecycle::install(db, Arc::clone(events)) };
+                    }
+                    if let Some(activity) = activity {
+                        // Safety: as above.
+                        let id = unsafe { lifecycle::connection(db) }.map_or(0, |conn| conn.id);
+                        // Safety: as above.
+                        unsafe { activity::install(db, activity, id) };
+                    }
+                    if deadlines {
+                        // Safety: as above.
+                        unsafe {
+                            deadline::install(db);
+                            deadline::mark(db);
+                        }
+                    }
+                }
+                // A new connection is only opened to be handed out
+                for event in [PoolEvent::Connect, PoolEvent::Acquire] {
+                    let Some(events) = events.as_ref().filter(|e| e.wants(event)) else {
+                        continue;
+                    };
+                    // Safety: the handle stays locked while the event is handled.
+                    let conn = unsafe { lifecycle::connection(handle.as_raw_handle().as_ptr()) };
+                    if let Some(conn) = conn {
+                        events
+                            .event(event, conn)
+                            .await
+                            .map_err(sqlx::Error::Protocol)?;
+                    }
+                }
+                Ok(())
+            })
+        });
+    if options.events.is_some() || deadlines {
+        let acquire_events = options.events.clone();
+        pool_options = pool_options.before_acquire(move |conn, _meta| {
+            let events = acquire_events.clone();
+            Box::pin(async move {
+                if deadlines {
+                    deadline::enter(conn).await?;
+                }
+                match events {
+                    Some(events) => report(&*events, PoolEvent::Acquire, conn).await,
+                    None => Ok(true),
+                }
+            })
+        });
+    }
+    if let Some(events) = &options.events {
+        let release_events = Arc::clone(events);
+        pool_options = pool_options.after_release(move |conn, _meta| {
+            let events = Arc::clone(&release_events);
+            Box::pin(async move { report(&*events, PoolEvent::Release, conn).await })
+        });
+    }
+    let connect_error = |source| Error::Connect {
+        path: path.to_string(),
+        source,
+    };
+    let mut connect_options =
+        SqliteConnectOptions::from_str(&format!("sqlite:{path}")).map_err(connect_error)?;
+    if let Some(vfs) = &options.vfs {
+        connect_options = connect_options.vfs(vfs.clone());
+    }
+    if options.read_only {
+        connect_options = connect_options.read_only(true);
+    }
+    let pool = pool_options
+        .connect_with(connect_options)
+        .await
+        .map_err(connect_error)?;
+
+    for (name, value) in &options.pragmas {
+        // Safety: PRAGMA names and values come from user input (via pragmas parameter or URI).
+        // SQLite's PRAGMA parser will reject invalid syntax, providing protection against
+        // SQL injection. PRAGMA names are identifiers (alphanumeric + underscore), and
+        // values are typically simple (strings, integers, keywords). While not perfect,
+        // SQLite's parser provides reasonable protection. For maximum security, applications
+        // should validate PRAGMA names against a whitelist.
+        let query = format!("PRAGMA {name} = {value}");
+        match sqlx::query(&query).execute(&pool).await {
+            Err(source) if !(options.read_only && is_readonly_error(&source)) => {
+                return Err(Error::Query { query, source });
+            }
+            _ => {}
+        }
+    }
+    if options.read_only {
+        return Ok(pool);
+    }
+
+    if options.wal_hook.is_some() {
+        let query = "PRAGMA journal_mode = WAL".to_string();
+        if let Err(source) = sqlx::query(&query).execute(&pool).await {
+            return Err(Error::Query { query, source });
+        }
+    }
+    if let Some(archive) = options.wal_hook.as_ref().and_then(|h| h.archive.as_ref()) {
+        if let Err(source) = archive.snapshot(&pool).await {
+            return Err(Error::Archive {
+                dir: archive.dir().display().to_string(),
+                source,
+            });
+        }
+    }
+
+    Ok(pool)
+}
+
+/// Whether `error` is SQLITE_READONLY (or one of its extended codes).
+fn is_readonly_error(error: &sqlx::Error) -> bool {
+    error
+        .as_database_error()
+        .and_then(|e| e.code())
+        .and_then(|code| code.parse::<c_int>().ok())
+        .is_some_and(|code| code & 0xff == SQLITE_READONLY)
+}
+
+/// Report `event` for a pooled connection, for `before_acquire` and
+/// `after_release`. A failed event closes the connection.
+async fn report(
+    events: &dyn PoolListener,
+    event: PoolEvent,
+    conn: &mut SqliteConnection,
+) -> Result<bool, sqlx::Error> {
+    if !events.wants(event) {
+        return Ok(true);
+    }
+    let mut handle = conn.lock_handle().await?;
+    let db = handle.as_raw_handle().as_ptr();
+    // Safety: the handle lock gives exclusive use of the connection until the
+    // event is handled.
+    let Some(conn) = (unsafe { lifecycle::connection(db) }) else {
+        return Ok(true);
+    };
+    Ok(events.event(event, conn).await.is_ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_open_pool_applies_pragmas() {
+        let options = PoolOptions {
+            pragmas: vec![("user_version".into(), "7".into())],
+            ..PoolOptions::default()
+        };
+        let pool = open_pool(":memory:", &options).await.unwrap();
+        let (version,): (i64,) = sqlx::query_as("PRAGMA user_version")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(version, 7);
+
+        let options = PoolOptions {
+            pragmas: vec![("user_version".into(), "'not a number'; DROP".into())],
+            ..PoolOptions::default()
+        };
+        assert!(matches!(
+            open_pool(":memory:", &options).await,
+            Err(Error::Query { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_read_only_pool() {
+        let path = std::env::temp_dir().join(format!("rapsqlite-ro-{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        std::fs::File::create(&path).unwrap();
+        let path = path.to_str().unwrap();
+        let pragmas = vec![
+            ("journal_mode".into(), "WAL".into()),
+            ("user_version".into(), "7".into()),
+        ];
+        let writer = open_pool(
+            path,
+            &PoolOptions {
+                pragmas: pragmas.clone(),
+                ..PoolOptions::default()
+            },
+        )
+        .await
+        .unwrap();
+        sqlx::query("CREATE TABLE t (x); INSERT INTO t VALUES (1)")
+            .execute(&writer)
+            .await
+            .unwrap();
+
+        // The writer already applied the PRAGMAs that write the file
+        let reader = open_pool(
+            path,
+            &PoolOptions {
+                pragmas,
+                read_only: true,
+                ..PoolOptions::default()
+            },
+        )
+        .await
+        .unwrap();
+        let (x, version): (i64, i64) =
+            sqlx::query_as("SELECT x, (SELECT user_version FROM pragma_user_version) FROM t")
+                .fetch_one(&reader)
+                .await
+                .unwrap();
+        assert_eq!((x, version), (1, 7));
+        let error = sqlx::query("INSERT INTO t VALUES (2)")
+            .execute(&reader)
+            .await
+            .unwrap_err();
+        assert!(is_readonly_error(&error), "{error}");
+
+        writer.close().await;
+        reader.close().await;
+        let _ = std::fs::remove_file(path);
+    }
+}
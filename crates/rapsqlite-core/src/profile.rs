@@ -0,0 +1,410 @@
+//! Per-plan-node and per-opcode counters for a single statement run.
+//!
+//! [`profile`] runs a statement with SQLite's statement scan status enabled
+//! and reads back two views of where the work went: one [`PlanNode`] per
+//! node of the `EXPLAIN QUERY PLAN` tree (from `sqlite3_stmt_scanstatus_v2`)
+//! and one [`OpcodeStats`] per instruction of the `EXPLAIN` program (from the
+//! `bytecode()` table-valued function). Both need a SQLite built with
+//! `SQLITE_ENABLE_STMT_SCANSTATUS` and `SQLITE_ENABLE_BYTECODE_VTAB`, as the
+//! workspace's `.cargo/config.toml` does; [`SUPPORTED`] tells whether this
+//! build has them.
+//!
+//! Collecting the counters costs a cycle-counter read per instruction, so
+//! [`open_pool`](crate::open_pool) switches it off on every connection and
+//! [`profile`] only enables it for the statement it runs.
+
+use libsqlite3_sys::{
+    sqlite3, sqlite3_bind_blob, sqlite3_bind_double, sqlite3_bind_int64, sqlite3_bind_null,
+    sqlite3_bind_pointer, sqlite3_bind_text, sqlite3_column_double, sqlite3_column_int64,
+    sqlite3_column_text, sqlite3_column_type, sqlite3_db_config, sqlite3_finalize,
+    sqlite3_prepare_v2, sqlite3_step, sqlite3_stmt, sqlite3_stmt_status,
+    SQLITE_DBCONFIG_STMT_SCANSTATUS, SQLITE_DONE, SQLITE_ERROR, SQLITE_NULL, SQLITE_OK, SQLITE_ROW,
+    SQLITE_STMTSTATUS_AUTOINDEX, SQLITE_STMTSTATUS_FULLSCAN_STEP, SQLITE_STMTSTATUS_SORT,
+    SQLITE_STMTSTATUS_VM_STEP, SQLITE_TRANSIENT,
+};
+#[cfg(sqlite_profile)]
+use libsqlite3_sys::{
+    sqlite3_stmt_scanstatus_v2, SQLITE_SCANSTAT_COMPLEX, SQLITE_SCANSTAT_EST,
+    SQLITE_SCANSTAT_EXPLAIN, SQLITE_SCANSTAT_NAME, SQLITE_SCANSTAT_NCYCLE, SQLITE_SCANSTAT_NLOOP,
+    SQLITE_SCANSTAT_NVISIT, SQLITE_SCANSTAT_PARENTID, SQLITE_SCANSTAT_SELECTID,
+};
+use std::ffi::{c_char, c_int, c_void, CStr};
+use std::ptr;
+use std::time::{Duration, Instant};
+
+use crate::SqliteParam;
+
+/// Whether the SQLite this crate links was built with the options [`profile`]
+/// needs. Without them `sqlite3_stmt_scanstatus_v2()` is not compiled in, so
+/// set `SQLITE_ENABLE_STMT_SCANSTATUS` and `SQLITE_ENABLE_BYTECODE_VTAB` in
+/// `LIBSQLITE3_FLAGS` when building outside this workspace.
+pub const SUPPORTED: bool = cfg!(sqlite_profile);
+
+/// Counters of one node of the query plan, as listed by `EXPLAIN QUERY PLAN`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlanNode {
+    /// The node's id; `parent` refers to it. Matches the `id` column of
+    /// `EXPLAIN QUERY PLAN`.
+    pub id: i32,
+    /// Id of the enclosing node, 0 at the top level.
+    pub parent: i32,
+    /// The `EXPLAIN QUERY PLAN` detail, e.g. `SEARCH t USING INDEX i (a=?)`.
+    pub detail: String,
+    /// Table or index the node reads, if any.
+    pub name: Option<String>,
+    /// Times the loop was started (0 for nodes that are not loops).
+    pub loops: i64,
+    /// Rows the loop visited over all its runs.
+    pub visits: i64,
+    /// The planner's estimate of rows per run.
+    pub estimated_rows: f64,
+    /// CPU cycles spent in the node's instructions.
+    pub cycles: i64,
+}
+
+/// Counters of one instruction of the program listed by `EXPLAIN`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OpcodeStats {
+    pub addr: i64,
+    pub opcode: String,
+    pub p1: i64,
+    pub p2: i64,
+    pub p3: i64,
+    pub p4: Option<String>,
+    pub p5: i64,
+    pub comment: Option<String>,
+    /// Trigger program the instruction belongs to; `None` for the statement's
+    /// own program.
+    pub subprog: Option<String>,
+    /// Times the instruction ran.
+    pub executions: i64,
+    /// CPU cycles spent running it.
+    pub cycles: i64,
+}
+
+/// What [`profile`] measured.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Profile {
+    /// Rows the statement returned.
+    pub rows: u64,
+    /// Wall-clock time spent stepping the statement.
+    pub elapsed: Duration,
+    /// Instructions run (`SQLITE_STMTSTATUS_VM_STEP`).
+    pub vm_steps: i64,
+    /// Rows stepped through by full table scans (`SQLITE_STMTSTATUS_FULLSCAN_STEP`).
+    pub fullscan_steps: i64,
+    /// Sorts run (`SQLITE_STMTSTATUS_SORT`).
+    pub sorts: i64,
+    /// Rows inserted into automatic indexes (`SQLITE_STMTSTATUS_AUTOINDEX`).
+    pub autoindex_rows: i64,
+    pub plan: Vec<PlanNode>,
+    pub opcodes: Vec<OpcodeStats>,
+}
+
+/// Run the first statement in `sql` to completion with `params` bound and
+/// return its counters. Rows are counted, not kept. On failure, returns the
+/// SQLite result code; `sqlite3_errmsg(db)` describes it. Returns
+/// `SQLITE_ERROR` without running anything unless [`SUPPORTED`].
+///
+/// # Safety
+///
+/// `db` must meet the [raw handle](crate#raw-handles) requirements.
+pub unsafe fn profile(
+    db: *mut sqlite3,
+    sql: &CStr,
+    params: &[SqliteParam],
+) -> Result<Profile, c_int> {
+    if !SUPPORTED {
+        return Err(SQLITE_ERROR);
+    }
+    // The flag must be set both when the statement is prepared and while it runs
+    set_scan_status(db, true);
+    let result = run(db, sql, params);
+    set_scan_status(db, false);
+    result
+}
+
+/// Switch the collection of scan status counters on `db` on or off.
+///
+/// # Safety
+///
+/// `db` must be a valid `sqlite3*`.
+pub(crate) unsafe fn set_scan_status(db: *mut sqlite3, enabled: bool) {
+    sqlite3_db_config(
+        db,
+        SQLITE_DBCONFIG_STMT_SCANSTATUS,
+        c_int::from(enabled),
+        ptr::null_mut::<c_int>(),
+    );
+}
+
+unsafe fn run(db: *mut sqlite3, sql: &CStr, params: &[SqliteParam]) -> Result<Profile, c_int> {
+    let mut stmt: *mut sqlite3_stmt = ptr::null_mut();
+    let rc = sqlite3_prepare_v2(db, sql.as_ptr(), -1, &mut stmt, ptr::null_mut());
+    if rc != SQLITE_OK {
+        return Err(rc);
+    }
+    if stmt.is_null() {
+        // Only whitespace or comments
+        return Ok(Profile {
+            rows: 0,
+            elapsed: Duration::ZERO,
+            vm_steps: 0,
+            fullscan_steps: 0,
+            sorts: 0,
+            autoindex_rows: 0,
+            plan: Vec::new(),
+            opcodes: Vec::new(),
+        });
+    }
+    let result = (|| {
+        bind(stmt, params)?;
+        let start = Instant::now();
+        let mut rows = 0;
+        loop {
+            match sqlite3_step(stmt) {
+                SQLITE_ROW => rows += 1,
+                SQLITE_DONE => break,
+                rc => return Err(rc),
+            }
+        }
+        let elapsed = start.elapsed();
+        Ok(Profile {
+            rows,
+            elapsed,
+            vm_steps: sqlite3_stmt_status(stmt, SQLITE_STMTSTATUS_VM_STEP, 0).into(),
+            fullscan_steps: sqlite3_stmt_status(stmt, SQLITE_STMTSTATUS_FULLSCAN_STEP, 0).into(),
+            sorts: sqlite3_stmt_status(stmt, SQLITE_STMTSTATUS_SORT, 0).into(),
+            autoindex_rows: sqlite3_stmt_status(stmt, SQLITE_STMTSTATUS_AUTOINDEX, 0).into(),
+            plan: plan_nodes(stmt),
+            opcodes: opcode_stats(db, stmt)?,
+        })
+    })();
+    sqlite3_finalize(stmt);
+    result
+}
+
+unsafe fn bind(stmt: *mut sqlite3_stmt, params: &[SqliteParam]) -> Result<(), c_int> {
+    for (i, param) in params.iter().enumerate() {
+        let index = i as c_int + 1;
+        let rc = match param {
+            SqliteParam::Null => sqlite3_bind_null(stmt, index),
+            SqliteParam::Int(v) => sqlite3_bind_int64(stmt, index, *v),
+            SqliteParam::Real(v) => sqlite3_bind_double(stmt, index, *v),
+            SqliteParam::Text(v) => sqlite3_bind_text(
+                stmt,
+                index,
+                v.as_ptr() as *const c_char,
+                v.len() as c_int,
+                SQLITE_TRANSIENT(),
+            ),
+            SqliteParam::Blob(v) => sqlite3_bind_blob(
+                stmt,
+                index,
+                v.as_ptr() as *const c_void,
+                v.len() as c_int,
+                SQLITE_TRANSIENT(),
+            ),
+        };
+        if rc != SQLITE_OK {
+            return Err(rc);
+        }
+    }
+    Ok(())
+}
+
+unsafe fn text(ptr: *const c_char) -> Option<String> {
+    (!ptr.is_null()).then(|| CStr::from_ptr(ptr).to_string_lossy().into_owned())
+}
+
+#[cfg(not(sqlite_profile))]
+unsafe fn plan_nodes(_stmt: *mut sqlite3_stmt) -> Vec<PlanNode> {
+    Vec::new()
+}
+
+#[cfg(sqlite_profile)]
+unsafe fn plan_nodes(stmt: *mut sqlite3_stmt) -> Vec<PlanNode> {
+    let mut nodes = Vec::new();
+    for idx in 0.. {
+        let mut loops = 0i64;
+        // Non-zero once `idx` is past the last node
+        if sqlite3_stmt_scanstatus_v2(
+            stmt,
+            idx,
+            SQLITE_SCANSTAT_NLOOP,
+            SQLITE_SCANSTAT_COMPLEX,
+            &mut loops as *mut i64 as *mut c_void,
+        ) != 0
+        {
+            break;
+        }
+        let mut visits = 0i64;
+        let mut estimated_rows = 0f64;
+        let mut name: *const c_char = ptr::null();
+        let mut detail: *const c_char = ptr::null();
+        let mut id: c_int = 0;
+        let mut parent: c_int = 0;
+        let mut cycles = 0i64;
+        let outputs: [(c_int, *mut c_void); 7] = [
+            (
+                SQLITE_SCANSTAT_NVISIT,
+                &mut visits as *mut i64 as *mut c_void,
+            ),
+            (
+                SQLITE_SCANSTAT_EST,
+                &mut estimated_rows as *mut f64 as *mut c_void,
+            ),
+            (
+                SQLITE_SCANSTAT_NAME,
+                &mut name as *mut *const c_char as *mut c_void,
+            ),
+            (
+                SQLITE_SCANSTAT_EXPLAIN,
+                &mut detail as *mut *const c_char as *mut c_void,
+            ),
+            (
+                SQLITE_SCANSTAT_SELECTID,
+                &mut id as *mut c_int as *mut c_void,
+            ),
+            (
+                SQLITE_SCANSTAT_PARENTID,
+                &mut parent as *mut c_int as *mut c_void,
+            ),
+            (
+                SQLITE_SCANSTAT_NCYCLE,
+                &mut cycles as *mut i64 as *mut c_void,
+            ),
+        ];
+        for (op, out) in outputs {
+            sqlite3_stmt_scanstatus_v2(stmt, idx, op, SQLITE_SCANSTAT_COMPLEX, out);
+        }
+        nodes.push(PlanNode {
+            id,
+            parent,
+            detail: text(detail).unwrap_or_default(),
+            name: text(name),
+            loops,
+            visits,
+            estimated_rows,
+            cycles,
+        });
+    }
+    nodes
+}
+
+unsafe fn opcode_stats(
+    db: *mut sqlite3,
+    stmt: *mut sqlite3_stmt,
+) -> Result<Vec<OpcodeStats>, c_int> {
+    let sql = c"SELECT addr, opcode, p1, p2, p3, p4, p5, comment, subprog, nexec, ncycle \
+                FROM bytecode(?1)";
+    let mut query: *mut sqlite3_stmt = ptr::null_mut();
+    let rc = sqlite3_prepare_v2(db, sql.as_ptr(), -1, &mut query, ptr::null_mut());
+    if rc != SQLITE_OK {
+        return Err(rc);
+    }
+    let result = (|| {
+        let rc = sqlite3_bind_pointer(
+            query,
+            1,
+            stmt as *mut c_void,
+            c"stmt-pointer".as_ptr(),
+            None,
+        );
+        if rc != SQLITE_OK {
+            return Err(rc);
+        }
+        let column_text = |i| {
+            if sqlite3_column_type(query, i) == SQLITE_NULL {
+                None
+            } else {
+                text(sqlite3_column_text(query, i) as *const c_char)
+            }
+        };
+        let mut opcodes = Vec::new();
+        loop {
+            match sqlite3_step(query) {
+                SQLITE_ROW => opcodes.push(OpcodeStats {
+                    addr: sqlite3_column_int64(query, 0),
+                    opcode: column_text(1).unwrap_or_default(),
+                    p1: sqlite3_column_int64(query, 2),
+                    p2: sqlite3_column_int64(query, 3),
+                    p3: sqlite3_column_int64(query, 4),
+                    p4: column_text(5),
+                    p5: sqlite3_column_int64(query, 6),
+                    comment: column_text(7),
+                    subprog: column_text(8),
+                    executions: sqlite3_column_int64(query, 9),
+                    cycles: sqlite3_column_double(query, 10) as i64,
+                }),
+                SQLITE_DONE => return Ok(opcodes),
+                rc => return Err(rc),
+            }
+        }
+    })();
+    sqlite3_finalize(query);
+    result
+}
+
+#[cfg(all(test, sqlite_profile))]
+mod tests {
+    use super::*;
+    use crate::{open_pool, PoolOptions};
+
+    #[tokio::test]
+    async fn test_profile_counts_loops_and_opcodes() {
+        let pool = open_pool(":memory:", &PoolOptions::default())
+            .await
+            .unwrap();
+        let mut conn = pool.acquire().await.unwrap();
+        sqlx::query(
+            "CREATE TABLE t (id INTEGER PRIMARY KEY, v INTEGER);
+             WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 100)
+             INSERT INTO t (v) SELECT i % 10 FROM n;",
+        )
+        .execute(&mut *conn)
+        .await
+        .unwrap();
+
+        let mut handle = conn.lock_handle().await.unwrap();
+        let db = handle.as_raw_handle().as_ptr();
+        // Safety: the handle lock gives exclusive use of the connection.
+        let profile =
+            unsafe { profile(db, c"SELECT * FROM t WHERE v = ?", &[SqliteParam::Int(3)]).unwrap() };
+        assert_eq!(profile.rows, 10);
+        assert_eq!(profile.fullscan_steps, 99);
+        let scan = profile.plan.iter().find(|n| n.detail == "SCAN t").unwrap();
+        assert_eq!((scan.loops, scan.visits), (1, 100));
+        let next = profile.opcodes.iter().find(|o| o.opcode == "Next").unwrap();
+        assert_eq!(next.executions, 100);
+        let result_row = profile
+            .opcodes
+            .iter()
+            .find(|o| o.opcode == "ResultRow")
+            .unwrap();
+        assert_eq!(result_row.executions, 10);
+
+        // Statements run outside profile() don't collect counters
+        let mut stmt = ptr::null_mut();
+        unsafe {
+            sqlite3_prepare_v2(
+                db,
+                c"SELECT count(*) FROM t".as_ptr(),
+                -1,
+                &mut stmt,
+                ptr::null_mut(),
+            );
+            sqlite3_step(stmt);
+            let mut loops = 0i64;
+            sqlite3_stmt_scanstatus_v2(
+                stmt,
+                0,
+                SQLITE_SCANSTAT_NLOOP,
+                SQLITE_SCANSTAT_COMPLEX,
+                &mut loops as *mut i64 as *mut c_void,
+            );
+            assert_eq!(loops, 0);
+            sqlite3_finalize(stmt);
+        }
+    }
+}
@@ -0,0 +1,556 @@
+//! Parameter binding and query execution on top of sqlx.
+//!
+//! Each helper comes in a pool flavour and an `_on_connection` flavour for a
+//! connection that is already checked out (e.g. one holding a transaction).
+//! The `_on_connection` helpers that run statements mark the connection with
+//! the task's [`deadline`], as acquiring it from the pool would.
+
+use futures_util::stream::BoxStream;
+use futures_util::TryStreamExt;
+use libsqlite3_sys::{
+    sqlite3, sqlite3_column_count, sqlite3_finalize, sqlite3_prepare_v2, sqlite3_stmt_isexplain,
+    sqlite3_stmt_readonly, SQLITE_OK,
+};
+use sqlx::pool::PoolConnection;
+use sqlx::sqlite::{SqliteArguments, SqliteConnection, SqliteRow};
+use sqlx::{Decode, Row, Sqlite, SqlitePool, TypeInfo, ValueRef};
+use std::ffi::{c_int, CStr, CString};
+use std::fmt;
+use std::ptr;
+
+use crate::deadline;
+use crate::sql::returns_rows_hint;
+use crate::SqliteParam;
+
+/// Bind parameters and execute a query.
+pub async fn bind_and_execute(
+    query: &str,
+    params: &[SqliteParam],
+    pool: &SqlitePool,
+) -> Result<sqlx::sqlite::SqliteQueryResult, sqlx::Error> {
+    bind_params(query, params).execute(pool).await
+}
+
+/// Bind parameters and execute a query on a specific connection.
+pub async fn bind_and_execute_on_connection(
+    query: &str,
+    params: &[SqliteParam],
+    conn: &mut PoolConnection<sqlx::Sqlite>,
+) -> Result<sqlx::sqlite::SqliteQueryResult, sqlx::Error> {
+    deadline::enter(conn).await?;
+    bind_params(query, params).execute(&mut **conn).await
+}
+
+/// Bind parameters and fetch all rows.
+pub async fn bind_and_fetch_all(
+    query: &str,
+    params: &[SqliteParam],
+    pool: &SqlitePool,
+) -> Result<Vec<sqlx::sqlite::SqliteRow>, sqlx::Error> {
+    bind_params(query, params).fetch_all(pool).await
+}
+
+/// Bind parameters and fetch exactly one row.
+pub async fn bind_and_fetch_one(
+    query: &str,
+    params: &[SqliteParam],
+    pool: &SqlitePool,
+) -> Result<sqlx::sqlite::SqliteRow, sqlx::Error> {
+    bind_params(query, params).fetch_one(pool).await
+}
+
+/// Bind parameters and fetch the first row, if any.
+pub async fn bind_and_fetch_optional(
+    query: &str,
+    params: &[SqliteParam],
+    pool: &SqlitePool,
+) -> Result<Option<sqlx::sqlite::SqliteRow>, sqlx::Error> {
+    bind_params(query, params).fetch_optional(pool).await
+}
+
+/// Bind parameters and fetch all rows on a specific connection.
+pub async fn bind_and_fetch_all_on_connection(
+    query: &str,
+    params: &[SqliteParam],
+    conn: &mut PoolConnection<sqlx::Sqlite>,
+) -> Result<Vec<sqlx::sqlite::SqliteRow>, sqlx::Error> {
+    deadline::enter(conn).await?;
+    bind_params(query, params).fetch_all(&mut **conn).await
+}
+
+/// Bind parameters and execute a query on a specific connection, collecting
+/// the rows it returns (e.g. `INSERT ... RETURNING`) along with its result.
+pub async fn bind_and_execute_returning_on_connection(
+    query: &str,
+    params: &[SqliteParam],
+    conn: &mut PoolConnection<sqlx::Sqlite>,
+) -> Result<(sqlx::sqlite::SqliteQueryResult, Vec<SqliteRow>), sqlx::Error> {
+    deadline::enter(conn).await?;
+    let mut results = sqlx::Executor::fetch_many(&mut **conn, bind_params(query, params));
+    let mut result = sqlx::sqlite::SqliteQueryResult::default();
+    let mut rows = Vec::new();
+    while let Some(item) = results.try_next().await? {
+        match item {
+            sqlx::Either::Left(done) => result.extend([done]),
+            sqlx::Either::Right(row) => rows.push(row),
+        }
+    }
+    Ok((result, rows))
+}
+
+/// Caps on the result of a fetch. Rows are counted while they are read, so an
+/// oversized result is abandoned rather than collected.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FetchLimits {
+    /// Maximum number of rows.
+    pub max_rows: Option<usize>,
+    /// Maximum total size of the fetched values: the length of TEXT and BLOB
+    /// values plus 8 bytes per INTEGER or REAL.
+    pub max_bytes: Option<usize>,
+}
+
+/// Error from [`bind_and_fetch_all_limited`] and
+/// [`bind_and_fetch_all_limited_on_connection`].
+#[derive(Debug)]
+pub enum FetchError {
+    /// Running the query failed.
+    Query(sqlx::Error),
+    /// The result has more than `max_rows` rows.
+    TooManyRows { max_rows: usize },
+    /// The result is larger than `max_bytes` bytes.
+    TooManyBytes { max_bytes: usize },
+}
+
+impl fmt::Display for FetchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FetchError::Query(source) => source.fmt(f),
+            FetchError::TooManyRows { max_rows } => {
+                write!(f, "query returned more than max_rows={max_rows} rows")
+            }
+            FetchError::TooManyBytes { max_bytes } => write!(
+                f,
+                "query result is larger than max_result_bytes={max_bytes} bytes"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for FetchError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            FetchError::Query(source) => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl From<sqlx::Error> for FetchError {
+    fn from(source: sqlx::Error) -> Self {
+        FetchError::Query(source)
+    }
+}
+
+/// Size of `row` as counted for [`FetchLimits::max_bytes`].
+fn row_size(row: &SqliteRow) -> usize {
+    (0..row.len())
+        .map(|i| match row.try_get_raw(i) {
+            Ok(value) if value.is_null() => 0,
+            Ok(value) => match value.type_info().name() {
+                "TEXT" | "BLOB" => <&[u8] as Decode<Sqlite>>::decode(value).map_or(0, <[u8]>::len),
+                _ => 8,
+            },
+            Err(_) => 0,
+        })
+        .sum()
+}
+
+impl FetchLimits {
+    /// Whether no limit is set.
+    pub fn is_unlimited(&self) -> bool {
+        self.max_rows.is_none() && self.max_bytes.is_none()
+    }
+
+    /// Check rows that were fetched earlier (e.g. a cached result).
+    pub fn check(&self, rows: &[SqliteRow]) -> Result<(), FetchError> {
+        let mut bytes = 0;
+        for (i, row) in rows.iter().enumerate() {
+            self.admit(i + 1, &mut bytes, row)?;
+        }
+        Ok(())
+    }
+
+    /// Account for the `count`-th row of a result.
+    fn admit(&self, count: usize, bytes: &mut usize, row: &SqliteRow) -> Result<(), FetchError> {
+        if let Some(max_rows) = self.max_rows.filter(|&max| count > max) {
+            return Err(FetchError::TooManyRows { max_rows });
+        }
+        if let Some(max_bytes) = self.max_bytes {
+            *bytes += row_size(row);
+            if *bytes > max_bytes {
+                return Err(FetchError::TooManyBytes { max_bytes });
+            }
+        }
+        Ok(())
+    }
+
+    async fn collect(
+        &self,
+        mut rows: BoxStream<'_, Result<SqliteRow, sqlx::Error>>,
+    ) -> Result<Vec<SqliteRow>, FetchError> {
+        let mut out = Vec::new();
+        let mut bytes = 0;
+        while let Some(row) = rows.try_next().await? {
+            self.admit(out.len() + 1, &mut bytes, &row)?;
+            out.push(row);
+        }
+        Ok(out)
+    }
+}
+
+/// Bind `params` to `query` in order. Any number of them can be bound, up to
+/// SQLite's limit on host parameters (32766), past which preparing the
+/// statement fails.
+fn bind_params<'q>(
+    query: &'q str,
+    params: &'q [SqliteParam],
+) -> sqlx::query::Query<'q, Sqlite, SqliteArguments<'q>> {
+    params
+        .iter()
+        .fold(sqlx::query(query), |query, param| match param {
+            SqliteParam::Null => query.bind(Option::<i64>::None),
+            SqliteParam::Int(v) => query.bind(*v),
+            SqliteParam::Real(v) => query.bind(*v),
+            SqliteParam::Text(v) => query.bind(v.as_str()),
+            SqliteParam::Blob(v) => query.bind(v.as_slice()),
+        })
+}
+
+/// [`bind_and_fetch_all`] that stops with an error once the result passes `limits`.
+pub async fn bind_and_fetch_all_limited(
+    query: &str,
+    params: &[SqliteParam],
+    pool: &SqlitePool,
+    limits: FetchLimits,
+) -> Result<Vec<SqliteRow>, FetchError> {
+    limits.collect(bind_params(query, params).fetch(pool)).await
+}
+
+/// [`bind_and_fetch_all_on_connection`] that stops with an error once the
+/// result passes `limits`.
+pub async fn bind_and_fetch_all_limited_on_connection(
+    query: &str,
+    params: &[SqliteParam],
+    conn: &mut PoolConnection<sqlx::Sqlite>,
+    limits: FetchLimits,
+) -> Result<Vec<SqliteRow>, FetchError> {
+    deadline::enter(conn).await?;
+    limits
+        .collect(bind_params(query, params).fetch(&mut **conn))
+        .await
+}
+
+/// Bind parameters and stream the result rows from a specific connection as
+/// SQLite produces them, instead of collecting them first.
+pub fn bind_and_stream_on_connection<'c>(
+    query: &'c str,
+    params: &'c [SqliteParam],
+    conn: &'c mut PoolConnection<sqlx::Sqlite>,
+) -> BoxStream<'c, Result<SqliteRow, sqlx::Error>> {
+    bind_params(query, params).fetch(&mut **conn)
+}
+
+/// Bind parameters and fetch exactly one row on a specific connection.
+pub async fn bind_and_fetch_one_on_connection(
+    query: &str,
+    params: &[SqliteParam],
+    conn: &mut PoolConnection<sqlx::Sqlite>,
+) -> Result<sqlx::sqlite::SqliteRow, sqlx::Error> {
+    deadline::enter(conn).await?;
+    bind_params(query, params).fetch_one(&mut **conn).await
+}
+
+/// Bind parameters and fetch the first row, if any, on a specific connection.
+pub async fn bind_and_fetch_optional_on_connection(
+    query: &str,
+    params: &[SqliteParam],
+    conn: &mut PoolConnection<sqlx::Sqlite>,
+) -> Result<Option<sqlx::sqlite::SqliteRow>, sqlx::Error> {
+    deadline::enter(conn).await?;
+    bind_params(query, params).fetch_optional(&mut **conn).await
+}
+
+/// Prepare `query` on a specific connection without running it.
+///
+/// sqlx keeps the statements a connection prepared in a cache keyed by their
+/// SQL text, so a later execution of the same text on this connection skips
+/// the prepare step.
+pub async fn prepare_on_connection(
+    query: &str,
+    conn: &mut PoolConnection<sqlx::Sqlite>,
+) -> Result<(), sqlx::Error> {
+    sqlx::Executor::prepare(&mut **conn, query)
+        .await
+        .map(|_| ())
+}
+
+/// Whether `query` returns rows to fetch rather than being run for its effect:
+/// SELECTs, and statements that prepare to a read-only statement with result
+/// columns (`WITH ... SELECT`, `VALUES`, `EXPLAIN`, `PRAGMA table_info(...)`).
+/// `WITH ... INSERT`, `INSERT ... RETURNING` and PRAGMAs that change a setting
+/// return false.
+///
+/// Only statements whose leading keyword doesn't settle it
+/// ([`returns_rows_hint`]) are prepared on `conn`. A statement that fails to
+/// prepare returns false, so running it reports the error.
+pub async fn returns_rows_on_connection(
+    query: &str,
+    conn: &mut SqliteConnection,
+) -> Result<bool, sqlx::Error> {
+    if let Some(returns_rows) = returns_rows_hint(query) {
+        return Ok(returns_rows);
+    }
+    let Ok(sql) = CString::new(query) else {
+        return Ok(false);
+    };
+    let mut handle = conn.lock_handle().await?;
+    let db = handle.as_raw_handle().as_ptr();
+    let mut stmt = ptr::null_mut();
+    // Safety: the handle lock gives exclusive use of the connection, and
+    // finalizing a null statement is a no-op.
+    unsafe {
+        let rc = sqlite3_prepare_v2(db, sql.as_ptr(), -1, &mut stmt, ptr::null_mut());
+        // EXPLAIN lists the program without running it, writes included
+        let returns_rows = rc == SQLITE_OK
+            && !stmt.is_null()
+            && sqlite3_column_count(stmt) > 0
+            && (sqlite3_stmt_readonly(stmt) != 0 || sqlite3_stmt_isexplain(stmt) != 0);
+        sqlite3_finalize(stmt);
+        Ok(returns_rows)
+    }
+}
+
+/// Like [`returns_rows_on_connection`], on a connection from `pool`.
+pub async fn returns_rows(query: &str, pool: &SqlitePool) -> Result<bool, sqlx::Error> {
+    if let Some(returns_rows) = returns_rows_hint(query) {
+        return Ok(returns_rows);
+    }
+    returns_rows_on_connection(query, &mut *pool.acquire().await?).await
+}
+
+/// Whether every statement in `sql` leaves the database unchanged, as
+/// `sqlite3_stmt_readonly` reports it: such SQL can run on a read-only
+/// connection. `EXPLAIN` counts as read-only whatever it explains, and so do
+/// transaction control statements (`BEGIN`, `COMMIT`, `SAVEPOINT`) and
+/// `ATTACH`. Statements are prepared, not run, stopping at the first one that
+/// writes. On failure, returns the SQLite result code; `sqlite3_errmsg(db)`
+/// describes it.
+///
+/// # Safety
+///
+/// `db` must meet the [raw handle](crate#raw-handles) requirements.
+pub unsafe fn is_readonly(db: *mut sqlite3, sql: &CStr) -> Result<bool, c_int> {
+    let mut rest = sql.as_ptr();
+    while *rest != 0 {
+        let mut stmt = ptr::null_mut();
+        let rc = sqlite3_prepare_v2(db, rest, -1, &mut stmt, &mut rest);
+        if rc != SQLITE_OK {
+            return Err(rc);
+        }
+        if stmt.is_null() {
+            // Only whitespace or comments left
+            break;
+        }
+        let readonly = sqlite3_stmt_readonly(stmt) != 0 || sqlite3_stmt_isexplain(stmt) != 0;
+        sqlite3_finalize(stmt);
+        if !readonly {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{open_pool, PoolOptions};
+
+    #[tokio::test]
+    async fn test_fetch_limits() {
+        let pool = open_pool(":memory:", &PoolOptions::default())
+            .await
+            .unwrap();
+        let query = "WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n) \
+                     SELECT i, 'abcd' FROM n LIMIT ?";
+        let fetch = |count: i64, limits: FetchLimits| {
+            let pool = pool.clone();
+            async move {
+                bind_and_fetch_all_limited(query, &[SqliteParam::Int(count)], &pool, limits).await
+            }
+        };
+
+        let rows = FetchLimits {
+            max_rows: Some(10),
+            ..FetchLimits::default()
+        };
+        assert_eq!(fetch(10, rows).await.unwrap().len(), 10);
+        assert!(matches!(
+            fetch(11, rows).await,
+            Err(FetchError::TooManyRows { max_rows: 10 })
+        ));
+
+        // 12 bytes per row: an INTEGER and 4 bytes of TEXT
+        let bytes = FetchLimits {
+            max_bytes: Some(120),
+            ..FetchLimits::default()
+        };
+        let fetched = fetch(10, bytes).await.unwrap();
+        assert!(bytes.check(&fetched).is_ok());
+        assert!(rows.check(&fetched).is_ok());
+        assert!(matches!(
+            fetch(11, bytes).await,
+            Err(FetchError::TooManyBytes { max_bytes: 120 })
+        ));
+        // An unbounded query is abandoned at the limit
+        assert!(fetch(i64::MAX, rows).await.is_err());
+        assert_eq!(
+            fetch(1_000, FetchLimits::default()).await.unwrap().len(),
+            1_000
+        );
+
+        let tight = FetchLimits {
+            max_rows: Some(9),
+            ..FetchLimits::default()
+        };
+        assert!(tight.check(&fetched).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_execute_returning() {
+        let pool = open_pool(":memory:", &PoolOptions::default())
+            .await
+            .unwrap();
+        let mut conn = pool.acquire().await.unwrap();
+        sqlx::query("CREATE TABLE t (id INTEGER PRIMARY KEY, x)")
+            .execute(&mut *conn)
+            .await
+            .unwrap();
+        let insert = "INSERT INTO t (x) VALUES (?), (?) RETURNING id";
+        let params = [SqliteParam::Int(7), SqliteParam::Int(8)];
+        let (result, rows) = bind_and_execute_returning_on_connection(insert, &params, &mut conn)
+            .await
+            .unwrap();
+        assert_eq!(result.rows_affected(), 2);
+        assert_eq!(result.last_insert_rowid(), 2);
+        let ids: Vec<i64> = rows.iter().map(|row| row.get(0)).collect();
+        assert_eq!(ids, [1, 2]);
+
+        // Statements without RETURNING give no rows
+        let (result, rows) =
+            bind_and_execute_returning_on_connection("DELETE FROM t", &[], &mut conn)
+                .await
+                .unwrap();
+        assert_eq!((result.rows_affected(), rows.len()), (2, 0));
+    }
+
+    #[tokio::test]
+    async fn test_binds_any_number_of_parameters() {
+        let pool = open_pool(":memory:", &PoolOptions::default())
+            .await
+            .unwrap();
+        let columns: Vec<String> = (0..500).map(|i| format!("c{i}")).collect();
+        sqlx::query(&format!("CREATE TABLE t ({})", columns.join(", ")))
+            .execute(&pool)
+            .await
+            .unwrap();
+        let values: Vec<SqliteParam> = (0..500).map(SqliteParam::Int).collect();
+        let insert = format!("INSERT INTO t VALUES ({})", vec!["?"; 500].join(", "));
+        bind_and_execute(&insert, &values, &pool).await.unwrap();
+
+        let select = format!(
+            "SELECT * FROM t WHERE c499 IN ({})",
+            vec!["?"; 2000].join(", ")
+        );
+        let keys: Vec<SqliteParam> = (0..2000).map(SqliteParam::Int).collect();
+        let row = bind_and_fetch_optional(&select, &keys, &pool)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(row.len(), 500);
+        assert_eq!(row.get::<i64, _>(499), 499);
+    }
+
+    #[tokio::test]
+    async fn test_returns_rows() {
+        let pool = open_pool(":memory:", &PoolOptions::default())
+            .await
+            .unwrap();
+        sqlx::query("CREATE TABLE t (x)")
+            .execute(&pool)
+            .await
+            .unwrap();
+        for (query, expected) in [
+            ("SELECT * FROM t", true),
+            ("WITH x AS (SELECT 1) SELECT * FROM x", true),
+            ("VALUES (1), (2)", true),
+            ("PRAGMA table_info(t)", true),
+            ("EXPLAIN INSERT INTO t VALUES (1)", true),
+            ("EXPLAIN QUERY PLAN SELECT * FROM t", true),
+            ("WITH x AS (SELECT 1) INSERT INTO t SELECT * FROM x", false),
+            (
+                "WITH x AS (SELECT 1) INSERT INTO t SELECT * FROM x RETURNING *",
+                false,
+            ),
+            ("PRAGMA user_version = 5", false),
+            ("PRAGMA journal_mode = WAL", false),
+            ("PRAGMA wal_checkpoint", false),
+            ("PRAGMA busy_timeout = 100", false),
+            ("PRAGMA integrity_check", true),
+            ("INSERT INTO t VALUES (1) RETURNING x", false),
+            ("WITH x AS (SELECT 1) SELECT * FROM missing", false),
+        ] {
+            assert_eq!(
+                returns_rows(query, &pool).await.unwrap(),
+                expected,
+                "{query}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_is_readonly() {
+        let pool = open_pool(":memory:", &PoolOptions::default())
+            .await
+            .unwrap();
+        sqlx::query("CREATE TABLE t (x)")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let mut conn = pool.acquire().await.unwrap();
+        let mut handle = conn.lock_handle().await.unwrap();
+        let db = handle.as_raw_handle().as_ptr();
+        for (query, expected) in [
+            ("SELECT * FROM t", true),
+            ("WITH x AS (SELECT 1) SELECT * FROM x", true),
+            ("PRAGMA table_info(t)", true),
+            ("EXPLAIN INSERT INTO t VALUES (1)", true),
+            ("BEGIN", true),
+            ("SELECT 1; SELECT 2; -- done", true),
+            ("", true),
+            ("INSERT INTO t VALUES (1) RETURNING x", false),
+            ("WITH x AS (SELECT 1) INSERT INTO t SELECT * FROM x", false),
+            ("CREATE TABLE u (y)", false),
+            ("SELECT 1; DELETE FROM t", false),
+            // Stops at the write, before preparing a statement needing it
+            ("CREATE TABLE u (y); SELECT * FROM u", false),
+        ] {
+            let sql = CString::new(query).unwrap();
+            // Safety: the handle lock gives exclusive use of the connection.
+            let readonly = unsafe { is_readonly(db, &sql) };
+            assert_eq!(readonly, Ok(expected), "{query}");
+        }
+        let sql = CString::new("SELECT * FROM missing").unwrap();
+        // Safety: as above.
+        assert!(unsafe { is_readonly(db, &sql) }.is_err());
+    }
+}
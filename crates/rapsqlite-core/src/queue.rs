@@ -0,0 +1,194 @@
+//! Waiting for a pooled connection.
+//!
+//! sqlx hands out connections first come, first served. [`AcquireQueue`]
+//! wraps `Pool::acquire` to count the tasks waiting in that queue and time
+//! each wait, and turns a task away at once when the pool is saturated and
+//! `max_queue` tasks are already waiting, so an overloaded pool fails fast
+//! with the queue depth and wait time instead of an opaque timeout.
+
+use sqlx::pool::PoolConnection;
+use sqlx::{Sqlite, SqlitePool};
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Counters of an [`AcquireQueue`].
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct QueueStats {
+    /// Tasks waiting for a connection now.
+    pub waiting: usize,
+    /// Connections handed out.
+    pub acquired: u64,
+    /// Waits that ended in [`AcquireError::Timeout`].
+    pub timed_out: u64,
+    /// Tasks turned away with [`AcquireError::QueueFull`].
+    pub rejected: u64,
+    /// Time spent waiting for the connections handed out.
+    pub total_wait: Duration,
+    /// The longest wait for a connection handed out.
+    pub max_wait: Duration,
+}
+
+/// Why [`AcquireQueue::acquire`] returned no connection.
+#[derive(Debug)]
+pub enum AcquireError {
+    /// The pool was saturated and `max_queue` tasks were already waiting.
+    QueueFull { depth: usize },
+    /// No connection was released within the pool's acquire timeout;
+    /// `depth` tasks, this one included, were waiting when it gave up.
+    Timeout { depth: usize, waited: Duration },
+    /// Opening a connection failed, or the pool is closed.
+    Pool(sqlx::Error),
+}
+
+impl fmt::Display for AcquireError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AcquireError::QueueFull { depth } => write!(
+                f,
+                "pool exhausted and {depth} tasks already waiting for a connection (max_queue)"
+            ),
+            AcquireError::Timeout { depth, waited } => write!(
+                f,
+                "pool timed out after waiting {:.3}s for a connection, with {depth} tasks waiting",
+                waited.as_secs_f64()
+            ),
+            AcquireError::Pool(e) => e.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for AcquireError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            AcquireError::Pool(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// The queue of tasks waiting for a pool's connections.
+#[derive(Debug, Default)]
+pub struct AcquireQueue {
+    max_queue: Mutex<Option<usize>>,
+    waiting: AtomicUsize,
+    stats: Mutex<QueueStats>,
+}
+
+/// Leaves the queue when the wait ends, however it ends.
+struct Waiting<'a>(&'a AtomicUsize);
+
+impl Drop for Waiting<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+impl AcquireQueue {
+    /// A queue turning tasks away once `max_queue` are waiting (None: never).
+    pub fn new(max_queue: Option<usize>) -> Self {
+        AcquireQueue {
+            max_queue: Mutex::new(max_queue),
+            ..AcquireQueue::default()
+        }
+    }
+
+    pub fn max_queue(&self) -> Option<usize> {
+        *self.max_queue.lock().unwrap()
+    }
+
+    pub fn set_max_queue(&self, max_queue: Option<usize>) {
+        *self.max_queue.lock().unwrap() = max_queue;
+    }
+
+    pub fn stats(&self) -> QueueStats {
+        QueueStats {
+            waiting: self.waiting.load(Ordering::Relaxed),
+            ..*self.stats.lock().unwrap()
+        }
+    }
+
+    /// Acquire a connection from `pool`, waiting in turn.
+    pub async fn acquire(&self, pool: &SqlitePool) -> Result<PoolConnection<Sqlite>, AcquireError> {
+        let ahead = self.waiting.fetch_add(1, Ordering::Relaxed);
+        let waiting = Waiting(&self.waiting);
+        let saturated =
+            || pool.num_idle() == 0 && pool.size() >= pool.options().get_max_connections();
+        if self.max_queue().is_some_and(|max| ahead >= max) && saturated() {
+            drop(waiting);
+            self.stats.lock().unwrap().rejected += 1;
+            return Err(AcquireError::QueueFull { depth: ahead });
+        }
+
+        let started = Instant::now();
+        let result = pool.acquire().await;
+        let waited = started.elapsed();
+        let depth = self.waiting.load(Ordering::Relaxed);
+        drop(waiting);
+
+        let mut stats = self.stats.lock().unwrap();
+        match result {
+            Ok(conn) => {
+                stats.acquired += 1;
+                stats.total_wait += waited;
+                stats.max_wait = stats.max_wait.max(waited);
+                Ok(conn)
+            }
+            Err(sqlx::Error::PoolTimedOut) => {
+                stats.timed_out += 1;
+                Err(AcquireError::Timeout { depth, waited })
+            }
+            Err(e) => Err(AcquireError::Pool(e)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{open_pool, PoolOptions};
+
+    #[tokio::test]
+    async fn test_queue_limits_and_times_waits() {
+        let options = PoolOptions {
+            acquire_timeout_secs: Some(1),
+            ..PoolOptions::default()
+        };
+        let pool = open_pool(":memory:", &options).await.unwrap();
+        let queue = AcquireQueue::new(Some(1));
+        let held = queue.acquire(&pool).await.unwrap();
+
+        // The first waiter queues and times out; a second one is turned away
+        let (first, second) = tokio::join!(queue.acquire(&pool), async {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            queue.acquire(&pool).await
+        });
+        match first {
+            Err(AcquireError::Timeout { depth, waited }) => {
+                assert_eq!(depth, 1);
+                assert!(waited >= Duration::from_secs(1));
+            }
+            other => panic!("expected a timeout, got {other:?}"),
+        }
+        assert!(matches!(second, Err(AcquireError::QueueFull { depth: 1 })));
+
+        // Waiters are served once the connection is released
+        let (conn, ()) = tokio::join!(queue.acquire(&pool), async {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            drop(held);
+        });
+        drop(conn.unwrap());
+        let stats = queue.stats();
+        assert_eq!(
+            (
+                stats.waiting,
+                stats.acquired,
+                stats.timed_out,
+                stats.rejected
+            ),
+            (0, 2, 1, 1)
+        );
+        assert!(stats.max_wait >= Duration::from_millis(50));
+    }
+}
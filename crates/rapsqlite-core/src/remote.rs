@@ -0,0 +1,234 @@
+//! Read-through cache of remote database files.
+//!
+//! [`fetch`] makes a local copy of a database published at an `http(s)://`
+//! or `s3://bucket/key` URL and returns its path, for opening read-only and
+//! immutable. Copies live in [`CacheOptions::cache_dir`]; a cached copy is
+//! revalidated with a conditional GET (`If-None-Match` / `If-Modified-Since`)
+//! and only downloaded again when the remote file changed.
+//!
+//! Each version is stored under its own name (`<url hash>-<content hash>.db`),
+//! so a refresh never modifies a file that open connections are reading; the
+//! versions it replaces are removed (on Windows, once no longer open).
+
+use reqwest::header::{HeaderMap, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use reqwest::StatusCode;
+use sha2::{Digest, Sha256};
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime};
+
+use crate::s3::{self, S3Options};
+use crate::Error;
+
+const SQLITE_HEADER: &[u8] = b"SQLite format 3\0";
+
+/// Settings for [`fetch`].
+#[derive(Clone, Debug)]
+pub struct CacheOptions {
+    /// Directory holding the cached copies (created if missing).
+    pub cache_dir: PathBuf,
+    /// Use a cached copy without revalidating it while it was checked less
+    /// than this long ago. `None` revalidates on every call.
+    pub max_age: Option<Duration>,
+    /// Region, endpoint and credentials for `s3://` URLs.
+    pub s3: S3Options,
+}
+
+/// What the cache knows about the copy of one URL, stored next to it as
+/// `<url hash>.meta`: the file name, then the `ETag` and `Last-Modified` the
+/// server sent with it, one per line.
+#[derive(Debug, Default, PartialEq)]
+struct Entry {
+    file: String,
+    etag: String,
+    last_modified: String,
+}
+
+impl Entry {
+    fn read(path: &Path) -> Option<Self> {
+        let text = fs::read_to_string(path).ok()?;
+        let mut lines = text.lines().map(str::to_string);
+        let entry = Entry {
+            file: lines.next()?,
+            etag: lines.next().unwrap_or_default(),
+            last_modified: lines.next().unwrap_or_default(),
+        };
+        (!entry.file.is_empty()).then_some(entry)
+    }
+
+    fn write(&self, path: &Path) -> io::Result<()> {
+        let tmp = temp_path(path);
+        fs::write(
+            &tmp,
+            format!("{}\n{}\n{}\n", self.file, self.etag, self.last_modified),
+        )?;
+        fs::rename(&tmp, path)
+    }
+}
+
+/// A unique sibling of `path` to write before renaming it into place.
+fn temp_path(path: &Path) -> PathBuf {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".{}-{n}.part", std::process::id()));
+    path.with_file_name(name)
+}
+
+fn hex_prefix(digest: &[u8]) -> String {
+    digest[..8].iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn header(headers: &HeaderMap, name: reqwest::header::HeaderName) -> String {
+    headers
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// Return the path of an up-to-date local copy of the database at `url`,
+/// downloading it into the cache if it is missing or changed. If the server
+/// can't be reached, an existing copy is returned as it is.
+pub async fn fetch(url: &str, options: &CacheOptions) -> Result<PathBuf, Error> {
+    let fail = |message: String| Error::Download {
+        url: url.to_string(),
+        message,
+    };
+    fs::create_dir_all(&options.cache_dir).map_err(|e| fail(e.to_string()))?;
+    let key = hex_prefix(&Sha256::digest(url.as_bytes()));
+    let meta_path = options.cache_dir.join(format!("{key}.meta"));
+    let cached =
+        Entry::read(&meta_path).filter(|entry| options.cache_dir.join(&entry.file).is_file());
+
+    if let (Some(entry), Some(max_age)) = (&cached, options.max_age) {
+        let fresh = fs::metadata(&meta_path)
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|checked| SystemTime::now().duration_since(checked).ok())
+            .is_some_and(|age| age < max_age);
+        if fresh {
+            return Ok(options.cache_dir.join(&entry.file));
+        }
+    }
+
+    let mut conditions = Vec::new();
+    if let Some(entry) = &cached {
+        if !entry.etag.is_empty() {
+            conditions.push((IF_NONE_MATCH.as_str(), entry.etag.as_str()));
+        }
+        if !entry.last_modified.is_empty() {
+            conditions.push((IF_MODIFIED_SINCE.as_str(), entry.last_modified.as_str()));
+        }
+    }
+    let response = if url.starts_with("s3://") {
+        s3::Client::new(url, &options.s3)
+            .map_err(fail)?
+            .get(&conditions)
+            .await
+    } else if url.starts_with("http://") || url.starts_with("https://") {
+        let mut request = reqwest::Client::new().get(url);
+        for (name, value) in &conditions {
+            request = request.header(*name, *value);
+        }
+        request.send().await
+    } else {
+        return Err(fail(
+            "expected an http://, https:// or s3://bucket/key URL".to_string(),
+        ));
+    };
+    let mut response = match (response, &cached) {
+        (Ok(response), _) => response,
+        (Err(_), Some(entry)) => return Ok(options.cache_dir.join(&entry.file)),
+        (Err(e), None) => return Err(fail(format!("GET request failed: {e}"))),
+    };
+
+    let status = response.status();
+    if status == StatusCode::NOT_MODIFIED {
+        if let Some(entry) = cached {
+            // Rewriting the entry restarts max_age
+            entry.write(&meta_path).map_err(|e| fail(e.to_string()))?;
+            return Ok(options.cache_dir.join(&entry.file));
+        }
+    }
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(fail(if url.starts_with("s3://") {
+            s3::s3_error(status, &body)
+        } else {
+            format!("HTTP {status}")
+        }));
+    }
+
+    let etag = header(response.headers(), ETAG);
+    let last_modified = header(response.headers(), LAST_MODIFIED);
+    let tmp = temp_path(&options.cache_dir.join(&key));
+    let downloaded = async {
+        let mut file = File::create(&tmp).map_err(|e| e.to_string())?;
+        let mut hasher = Sha256::new();
+        while let Some(chunk) = response
+            .chunk()
+            .await
+            .map_err(|e| format!("download interrupted: {e}"))?
+        {
+            hasher.update(&chunk);
+            file.write_all(&chunk).map_err(|e| e.to_string())?;
+        }
+        file.sync_all().map_err(|e| e.to_string())?;
+        let mut magic = [0u8; SQLITE_HEADER.len()];
+        let is_database = File::open(&tmp)
+            .and_then(|mut f| f.read_exact(&mut magic))
+            .is_ok()
+            && magic == SQLITE_HEADER;
+        if !is_database {
+            return Err("the response is not an SQLite database".to_string());
+        }
+        Ok(format!("{key}-{}.db", hex_prefix(&hasher.finalize())))
+    }
+    .await;
+    let file = match downloaded {
+        Ok(file) => file,
+        Err(message) => {
+            let _ = fs::remove_file(&tmp);
+            return Err(fail(message));
+        }
+    };
+    let entry = Entry {
+        file,
+        etag,
+        last_modified,
+    };
+    let path = options.cache_dir.join(&entry.file);
+    fs::rename(&tmp, &path)
+        .and_then(|()| entry.write(&meta_path))
+        .map_err(|e| fail(e.to_string()))?;
+    if let Some(old) = cached.filter(|old| old.file != entry.file) {
+        // Connections may still read the old version; on Unix they keep it
+        let _ = fs::remove_file(options.cache_dir.join(old.file));
+    }
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entry_roundtrip() {
+        let dir = std::env::temp_dir().join(format!("rapsqlite-remote-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("key.meta");
+        let entry = Entry {
+            file: "key-0123.db".into(),
+            etag: "\"abc\"".into(),
+            last_modified: String::new(),
+        };
+        entry.write(&path).unwrap();
+        assert_eq!(Entry::read(&path), Some(entry));
+        fs::write(&path, "").unwrap();
+        assert_eq!(Entry::read(&path), None);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,680 @@
+//! SQL text helpers: statement classification and splitting, identifier
+//! quoting, parsing of `CREATE TABLE` column clauses for introspection and of
+//! `rapsqlite.sql()` templates.
+
+use libsqlite3_sys::sqlite3_complete;
+use std::collections::{HashMap, HashSet};
+use std::ffi::CString;
+
+/// Detect if a query is a SELECT query (for determining execution strategy).
+pub fn is_select_query(query: &str) -> bool {
+    let trimmed = query.trim().to_uppercase();
+    trimmed.starts_with("SELECT") || trimmed.starts_with("WITH")
+}
+
+/// Whether `query` has a RETURNING clause, so running it for its effect
+/// (see [`returns_rows_hint`]) also gives rows.
+pub fn has_returning(query: &str) -> bool {
+    tokenize_sql(query).iter().any(|&(token, start, end)| {
+        token == SqlToken::Word && query[start..end].eq_ignore_ascii_case("returning")
+    })
+}
+
+/// PRAGMAs whose argument names what to inspect rather than a new setting.
+const INSPECTING_PRAGMAS: &[&str] = &[
+    "foreign_key_check",
+    "foreign_key_list",
+    "index_info",
+    "index_list",
+    "index_xinfo",
+    "integrity_check",
+    "quick_check",
+    "table_info",
+    "table_list",
+    "table_xinfo",
+];
+
+/// What the text of `query` tells about whether it returns rows to fetch:
+/// `Some(true)` for SELECT, `Some(false)` for statements run for their effect
+/// (INSERT, CREATE, BEGIN, PRAGMAs changing a setting, ...) and `None` for
+/// WITH, VALUES, EXPLAIN and the other PRAGMAs, which can go either way until
+/// the statement is prepared (see
+/// [`query::returns_rows_on_connection`](crate::query::returns_rows_on_connection)).
+pub fn returns_rows_hint(query: &str) -> Option<bool> {
+    let tokens = tokenize_sql(query);
+    let word = |i: usize| match tokens.get(i) {
+        Some(&(SqlToken::Word, start, end)) => Some(query[start..end].to_ascii_lowercase()),
+        _ => None,
+    };
+    let Some(keyword) = word(0) else {
+        return Some(false);
+    };
+    match keyword.as_str() {
+        "select" => Some(true),
+        "with" | "values" | "explain" => None,
+        "pragma" => {
+            // PRAGMA [schema.]name [= value | (value)]
+            let name_at = if tokens.get(2).is_some_and(|t| &query[t.1..t.2] == ".") {
+                3
+            } else {
+                1
+            };
+            let name = word(name_at)?;
+            let has_argument = tokens.len() > name_at + 1;
+            // Setting PRAGMAs return the new value, but run for their effect
+            if name == "optimize" || has_argument && !INSPECTING_PRAGMAS.contains(&name.as_str()) {
+                Some(false)
+            } else {
+                None
+            }
+        }
+        _ => Some(false),
+    }
+}
+
+/// Byte offset of the second statement in `query`, if there is one: where the
+/// tail `sqlite3_prepare` leaves after the first statement starts, ignoring
+/// whitespace, comments and empty statements. Statements are split as
+/// `sqlite3_complete()` does, so the `;` inside a `CREATE TRIGGER` body
+/// doesn't end it.
+pub fn second_statement(query: &str) -> Option<usize> {
+    let tokens = tokenize_sql(query);
+    let is_semicolon = |t: &(SqlToken, usize, usize)| &query[t.1..t.2] == ";";
+    let end = tokens.iter().filter(|t| is_semicolon(t)).find(|t| {
+        // Safety: a valid NUL-terminated string
+        CString::new(&query[..t.2]).is_ok_and(|sql| unsafe { sqlite3_complete(sql.as_ptr()) } != 0)
+    })?;
+    tokens
+        .iter()
+        .find(|t| t.1 >= end.2 && !is_semicolon(t))
+        .map(|t| t.1)
+}
+
+/// The statements of `query`, split as [`second_statement`] does, without
+/// their terminating `;` and surrounding whitespace.
+pub fn split_statements(query: &str) -> Vec<&str> {
+    let mut statements = Vec::new();
+    let mut rest = query;
+    loop {
+        let (statement, tail) = match second_statement(rest) {
+            Some(at) => (&rest[..at], Some(&rest[at..])),
+            None => (rest, None),
+        };
+        let statement = statement
+            .trim()
+            .trim_end_matches(|c: char| c == ';' || c.is_whitespace());
+        if !statement.is_empty() {
+            statements.push(statement);
+        }
+        match tail {
+            Some(tail) => rest = tail,
+            None => return statements,
+        }
+    }
+}
+
+/// Normalize a SQL query by removing extra whitespace and standardizing formatting.
+/// This helps improve prepared statement cache hit rates by ensuring queries with
+/// different whitespace are treated as identical.
+///
+/// **Prepared Statement Caching (Phase 2.13):**
+/// sqlx (the underlying database library) automatically caches prepared statements
+/// per connection. When the same query is executed multiple times on the same
+/// connection, sqlx reuses the prepared statement, providing significant performance
+/// benefits. This normalization function ensures that queries with only whitespace
+/// differences are treated as identical, maximizing cache hit rates.
+///
+/// The prepared statement cache is managed entirely by sqlx and does not require
+/// explicit configuration. Each connection in the pool maintains its own cache,
+/// and statements are automatically prepared on first use and reused for subsequent
+/// executions of the same query.
+pub fn normalize_query(query: &str) -> String {
+    // Remove leading/trailing whitespace
+    let trimmed = query.trim();
+    // Replace multiple whitespace characters with single space
+    let normalized: String = trimmed
+        .chars()
+        .fold((String::new(), false), |(acc, was_space), ch| {
+            let is_space = ch.is_whitespace();
+            if is_space && was_space {
+                // Skip multiple consecutive spaces
+                (acc, true)
+            } else if is_space {
+                // Replace any whitespace with single space
+                (acc + " ", true)
+            } else {
+                (acc + &ch.to_string(), false)
+            }
+        })
+        .0;
+    normalized
+}
+
+/// True if `s` is a decimal integer literal that does not fit in an i64, i.e. a
+/// value bound as TEXT under `large_int_policy = "text"`.
+pub fn is_large_int_literal(s: &str) -> bool {
+    let digits = s.strip_prefix(['+', '-']).unwrap_or(s);
+    !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit()) && s.parse::<i64>().is_err()
+}
+
+/// Quote an SQLite identifier (table or column name) with double quotes.
+pub fn quote_identifier(ident: &str) -> String {
+    format!("\"{}\"", ident.replace('"', "\"\""))
+}
+
+/// Name for the `hidden` value reported by `PRAGMA table_xinfo`: 1 marks a
+/// hidden virtual-table column, 2 and 3 mark VIRTUAL and STORED generated columns.
+pub fn hidden_column_kind(hidden: i64) -> &'static str {
+    match hidden {
+        0 => "normal",
+        1 => "hidden",
+        2 => "virtual",
+        3 => "stored",
+        _ => "unknown",
+    }
+}
+
+/// Column clauses recovered from a table's `CREATE TABLE` text, which PRAGMA
+/// table_info either omits (generation expressions) or normalizes (it drops the
+/// parentheses around `DEFAULT (expr)`).
+#[derive(Debug, Default, PartialEq)]
+pub struct ColumnClauses {
+    /// The DEFAULT term exactly as written, e.g. `(datetime('now'))` or `-1`.
+    pub default_expr: Option<String>,
+    /// The expression inside `[GENERATED ALWAYS] AS (...)`.
+    pub generated_expr: Option<String>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum SqlToken {
+    Word,
+    Quoted,
+    Literal,
+    Open,
+    Close,
+    Comma,
+    Other,
+}
+
+/// Split SQL into (kind, start, end) tokens, skipping whitespace and comments.
+pub(crate) fn tokenize_sql(sql: &str) -> Vec<(SqlToken, usize, usize)> {
+    let bytes = sql.as_bytes();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    let scan_quoted = |start: usize, close: u8| {
+        let mut j = start + 1;
+        while j < bytes.len() {
+            if bytes[j] == close {
+                // A doubled quote is an escaped quote, except for [...] identifiers
+                if close != b']' && bytes.get(j + 1) == Some(&close) {
+                    j += 2;
+                    continue;
+                }
+                return j + 1;
+            }
+            j += 1;
+        }
+        bytes.len()
+    };
+    while i < bytes.len() {
+        let b = bytes[i];
+        let start = i;
+        let kind = match b {
+            _ if b.is_ascii_whitespace() => {
+                i += 1;
+                continue;
+            }
+            b'-' if bytes.get(i + 1) == Some(&b'-') => {
+                while i < bytes.len() && bytes[i] != b'\n' {
+                    i += 1;
+                }
+                continue;
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i = sql[i + 2..]
+                    .find("*/")
+                    .map_or(bytes.len(), |p| i + 2 + p + 2);
+                continue;
+            }
+            b'\'' => {
+                i = scan_quoted(i, b'\'');
+                SqlToken::Literal
+            }
+            b'"' | b'`' => {
+                i = scan_quoted(i, b);
+                SqlToken::Quoted
+            }
+            b'[' => {
+                i = scan_quoted(i, b']');
+                SqlToken::Quoted
+            }
+            b'(' => {
+                i += 1;
+                SqlToken::Open
+            }
+            b')' => {
+                i += 1;
+                SqlToken::Close
+            }
+            b',' => {
+                i += 1;
+                SqlToken::Comma
+            }
+            b'x' | b'X' if bytes.get(i + 1) == Some(&b'\'') => {
+                i = scan_quoted(i + 1, b'\'');
+                SqlToken::Literal
+            }
+            _ if b.is_ascii_digit()
+                || (b == b'.' && bytes.get(i + 1).is_some_and(u8::is_ascii_digit)) =>
+            {
+                while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'.') {
+                    // Exponent sign, e.g. 1e-5
+                    if matches!(bytes[i], b'e' | b'E')
+                        && matches!(bytes.get(i + 1), Some(b'+' | b'-'))
+                    {
+                        i += 1;
+                    }
+                    i += 1;
+                }
+                SqlToken::Literal
+            }
+            _ if b.is_ascii_alphabetic() || b == b'_' || b >= 0x80 => {
+                while i < bytes.len()
+                    && (bytes[i].is_ascii_alphanumeric()
+                        || matches!(bytes[i], b'_' | b'$')
+                        || bytes[i] >= 0x80)
+                {
+                    i += 1;
+                }
+                SqlToken::Word
+            }
+            _ => {
+                i += 1;
+                SqlToken::Other
+            }
+        };
+        tokens.push((kind, start, i));
+    }
+    tokens
+}
+
+/// Remove SQL identifier quoting ("x", `x`, [x]) from a column name.
+pub(crate) fn unquote_identifier(ident: &str) -> String {
+    let inner = |q: &str| ident[1..ident.len() - 1].replace(&format!("{q}{q}"), q);
+    match ident.as_bytes().first() {
+        Some(b'"') if ident.len() >= 2 => inner("\""),
+        Some(b'`') if ident.len() >= 2 => inner("`"),
+        Some(b'\'') if ident.len() >= 2 => inner("'"),
+        Some(b'[') if ident.len() >= 2 => ident[1..ident.len() - 1].to_string(),
+        _ => ident.to_string(),
+    }
+}
+
+/// Extract each column's DEFAULT and generation expressions from a `CREATE TABLE`
+/// statement, keyed by lowercased column name. Table constraints and
+/// `CREATE TABLE ... AS SELECT` statements yield no entries.
+pub fn parse_column_clauses(create_sql: &str) -> HashMap<String, ColumnClauses> {
+    let tokens = tokenize_sql(create_sql);
+    let text = |t: &(SqlToken, usize, usize)| &create_sql[t.1..t.2];
+    let mut columns = HashMap::new();
+
+    let Some(open) = tokens.iter().position(|t| t.0 == SqlToken::Open) else {
+        return columns;
+    };
+    if tokens[..open]
+        .iter()
+        .any(|t| t.0 == SqlToken::Word && text(t).eq_ignore_ascii_case("AS"))
+    {
+        return columns;
+    }
+
+    // Index of the token closing the group opened at `idx`
+    let matching_close = |idx: usize| {
+        let mut depth = 0usize;
+        for (j, t) in tokens.iter().enumerate().skip(idx) {
+            match t.0 {
+                SqlToken::Open => depth += 1,
+                SqlToken::Close => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return j;
+                    }
+                }
+                _ => {}
+            }
+        }
+        tokens.len() - 1
+    };
+
+    // Split the column list on top-level commas
+    let close = matching_close(open);
+    let mut definitions = Vec::new();
+    let mut depth = 0usize;
+    let mut start = open + 1;
+    for (j, t) in tokens.iter().enumerate().take(close).skip(open + 1) {
+        match t.0 {
+            SqlToken::Open => depth += 1,
+            SqlToken::Close => depth = depth.saturating_sub(1),
+            SqlToken::Comma if depth == 0 => {
+                definitions.push(start..j);
+                start = j + 1;
+            }
+            _ => {}
+        }
+    }
+    definitions.push(start..close);
+
+    for def in definitions {
+        let Some(first) = tokens.get(def.start).filter(|_| !def.is_empty()) else {
+            continue;
+        };
+        let name = text(first);
+        if first.0 == SqlToken::Word
+            && ["CONSTRAINT", "PRIMARY", "UNIQUE", "CHECK", "FOREIGN"]
+                .iter()
+                .any(|kw| name.eq_ignore_ascii_case(kw))
+        {
+            continue;
+        }
+
+        let mut clauses = ColumnClauses::default();
+        let mut j = def.start + 1;
+        while j < def.end {
+            let t = &tokens[j];
+            if t.0 == SqlToken::Open {
+                j = matching_close(j) + 1;
+                continue;
+            }
+            if t.0 != SqlToken::Word {
+                j += 1;
+                continue;
+            }
+            let word = text(t);
+            if word.eq_ignore_ascii_case("DEFAULT") && j + 1 < def.end {
+                let next = &tokens[j + 1];
+                let end = match next.0 {
+                    SqlToken::Open => matching_close(j + 1),
+                    // Signed number, e.g. DEFAULT -1
+                    SqlToken::Other if j + 2 < def.end => j + 2,
+                    _ => j + 1,
+                };
+                clauses.default_expr = Some(create_sql[next.1..tokens[end].2].to_string());
+                j = end + 1;
+            } else if word.eq_ignore_ascii_case("AS")
+                && tokens.get(j + 1).is_some_and(|t| t.0 == SqlToken::Open)
+            {
+                let end = matching_close(j + 1);
+                let expr = &create_sql[tokens[j + 1].2..tokens[end].1];
+                clauses.generated_expr = Some(expr.trim().to_string());
+                j = end + 1;
+            } else {
+                j += 1;
+            }
+        }
+        columns.insert(unquote_identifier(name).to_lowercase(), clauses);
+    }
+    columns
+}
+
+/// Lowercased words and unquoted identifiers in `sql`, skipping literals and
+/// comments. A superset of the table and view names a statement refers to.
+pub fn identifiers(sql: &str) -> HashSet<String> {
+    tokenize_sql(sql)
+        .into_iter()
+        .filter(|t| matches!(t.0, SqlToken::Word | SqlToken::Quoted))
+        .map(|t| unquote_identifier(&sql[t.1..t.2]).to_lowercase())
+        .collect()
+}
+
+/// A piece of a parsed SQL template (see [`parse_template`]).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TemplatePart {
+    /// SQL text, copied as-is.
+    Text(String),
+    /// `:name`, `@name` or `$name`: a value (or a composed fragment).
+    Value(String),
+    /// `{name}`: an identifier, quoted when rendered.
+    Identifier(String),
+}
+
+/// Split a SQL template into text, `:name` / `@name` / `$name` value
+/// placeholders and `{name}` identifier placeholders. Placeholders inside
+/// string literals, quoted identifiers and comments are left alone. Fails for
+/// positional `?` placeholders and for a `{` not closing over a name.
+pub fn parse_template(template: &str) -> Result<Vec<TemplatePart>, String> {
+    let tokens = tokenize_sql(template);
+    let mut parts = Vec::new();
+    let mut copied = 0;
+    let mut i = 0;
+    let mut placeholder = |parts: &mut Vec<TemplatePart>, start: usize, end: usize, part| {
+        if copied < start {
+            parts.push(TemplatePart::Text(template[copied..start].to_string()));
+        }
+        parts.push(part);
+        copied = end;
+    };
+    while i < tokens.len() {
+        let (kind, start, end) = tokens[i];
+        let next = tokens
+            .get(i + 1)
+            .filter(|t| t.0 == SqlToken::Word && t.1 == end);
+        if kind != SqlToken::Other {
+            i += 1;
+            continue;
+        }
+        match (&template[start..end], next) {
+            (":" | "@" | "$", Some(&(_, _, name_end))) => {
+                let name = template[end..name_end].to_string();
+                placeholder(&mut parts, start, name_end, TemplatePart::Value(name));
+                i += 2;
+            }
+            ("{", Some(&(_, _, name_end))) => {
+                let close = tokens
+                    .get(i + 2)
+                    .filter(|t| t.1 == name_end && &template[t.1..t.2] == "}")
+                    .ok_or_else(|| format!("Unclosed '{{' at offset {start} in SQL template"))?;
+                let name = template[end..name_end].to_string();
+                placeholder(&mut parts, start, close.2, TemplatePart::Identifier(name));
+                i += 3;
+            }
+            ("{", None) => {
+                return Err(format!(
+                    "Expected an identifier name after '{{' at offset {start} in SQL template"
+                ))
+            }
+            ("?", _) => {
+                return Err(format!(
+                    "Positional placeholder '?' at offset {start} in SQL template; \
+                     use named placeholders (:name)"
+                ))
+            }
+            _ => i += 1,
+        }
+    }
+    if copied < template.len() {
+        parts.push(TemplatePart::Text(template[copied..].to_string()));
+    }
+    Ok(parts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_select_query_basic() {
+        assert!(is_select_query("SELECT 1"));
+        assert!(is_select_query(" select 1 "));
+        assert!(is_select_query("\n\tSELECT 1"));
+        assert!(is_select_query("WITH cte AS (SELECT 1) SELECT * FROM cte"));
+
+        assert!(!is_select_query("INSERT INTO t VALUES (1)"));
+        assert!(!is_select_query("UPDATE t SET x = 1"));
+        assert!(!is_select_query("DELETE FROM t"));
+        assert!(!is_select_query("PRAGMA foreign_keys = ON"));
+    }
+
+    #[test]
+    fn test_returns_rows_hint() {
+        assert_eq!(returns_rows_hint("SELECT 1"), Some(true));
+        assert_eq!(
+            returns_rows_hint("-- rows\n /* all */ select 1"),
+            Some(true)
+        );
+        assert_eq!(returns_rows_hint("INSERT INTO t VALUES (1)"), Some(false));
+        assert_eq!(returns_rows_hint("BEGIN"), Some(false));
+        assert_eq!(returns_rows_hint(""), Some(false));
+        for query in [
+            "WITH x AS (SELECT 1) INSERT INTO t SELECT * FROM x",
+            "values (1), (2)",
+            "PRAGMA table_info(t)",
+            "PRAGMA main.index_list = t",
+            "PRAGMA schema_version",
+            "EXPLAIN SELECT 1",
+        ] {
+            assert_eq!(returns_rows_hint(query), None, "{query}");
+        }
+        for query in [
+            "PRAGMA busy_timeout = 100",
+            "PRAGMA main.mmap_size(0)",
+            "PRAGMA optimize",
+        ] {
+            assert_eq!(returns_rows_hint(query), Some(false), "{query}");
+        }
+    }
+
+    #[test]
+    fn test_has_returning() {
+        assert!(has_returning("INSERT INTO t VALUES (1) RETURNING id"));
+        assert!(has_returning("delete from t returning *"));
+        assert!(!has_returning("INSERT INTO t VALUES ('returning')"));
+        assert!(!has_returning("SELECT \"returning\" FROM t"));
+    }
+
+    #[test]
+    fn test_second_statement() {
+        assert_eq!(second_statement("SELECT 1"), None);
+        assert_eq!(second_statement("SELECT 1;"), None);
+        assert_eq!(second_statement("SELECT 1; ; -- done\n"), None);
+        assert_eq!(second_statement("SELECT ';'"), None);
+        assert_eq!(
+            second_statement("INSERT INTO t VALUES (1); DROP TABLE t"),
+            Some(26)
+        );
+        assert_eq!(second_statement("SELECT 1;SELECT 2;"), Some(9));
+        let trigger = "CREATE TRIGGER tr AFTER INSERT ON t BEGIN \
+                       DELETE FROM u; INSERT INTO u VALUES (1); END";
+        assert_eq!(second_statement(trigger), None);
+        let then = format!("{trigger}; DROP TABLE t");
+        assert_eq!(second_statement(&then), Some(trigger.len() + 2));
+    }
+
+    #[test]
+    fn test_split_statements() {
+        assert!(split_statements("  ; ").is_empty());
+        assert_eq!(
+            split_statements("INSERT INTO t VALUES (';');\nDROP TABLE t;;"),
+            ["INSERT INTO t VALUES (';')", "DROP TABLE t"]
+        );
+        let trigger = "CREATE TRIGGER tr AFTER INSERT ON t BEGIN DELETE FROM u; END";
+        assert_eq!(
+            split_statements(&format!("{trigger}; SELECT 1")),
+            [trigger, "SELECT 1"]
+        );
+    }
+
+    #[test]
+    fn test_normalize_query_whitespace() {
+        assert_eq!(normalize_query("  SELECT   1  "), "SELECT 1");
+        assert_eq!(normalize_query("SELECT\t1"), "SELECT 1");
+        assert_eq!(normalize_query("SELECT\n1"), "SELECT 1");
+        assert_eq!(normalize_query("SELECT\r\n1"), "SELECT 1");
+        assert_eq!(normalize_query("SELECT  1   FROM   t"), "SELECT 1 FROM t");
+    }
+
+    #[test]
+    fn test_is_large_int_literal() {
+        assert!(is_large_int_literal("9223372036854775808"));
+        assert!(is_large_int_literal("-9223372036854775809"));
+        assert!(is_large_int_literal("123456789012345678901234567890"));
+
+        assert!(!is_large_int_literal("9223372036854775807"));
+        assert!(!is_large_int_literal("-9223372036854775808"));
+        assert!(!is_large_int_literal("42"));
+        assert!(!is_large_int_literal(""));
+        assert!(!is_large_int_literal("-"));
+        assert!(!is_large_int_literal("1e30"));
+        assert!(!is_large_int_literal("12345678901234567890 "));
+    }
+
+    #[test]
+    fn test_hidden_column_kind() {
+        assert_eq!(hidden_column_kind(0), "normal");
+        assert_eq!(hidden_column_kind(1), "hidden");
+        assert_eq!(hidden_column_kind(2), "virtual");
+        assert_eq!(hidden_column_kind(3), "stored");
+        assert_eq!(hidden_column_kind(7), "unknown");
+    }
+
+    #[test]
+    fn test_parse_column_clauses() {
+        let cols = parse_column_clauses(
+            "CREATE TABLE t (\n\
+             id INTEGER PRIMARY KEY, -- comment, with comma\n\
+             \"Created At\" TEXT DEFAULT (datetime('now')),\n\
+             n DECIMAL(10, 2) DEFAULT -1.5 NOT NULL,\n\
+             s TEXT DEFAULT 'a,b''c' CHECK (length(s) > 0),\n\
+             total INT GENERATED ALWAYS AS ( n * 2 ) STORED,\n\
+             [v] BLOB AS (x'00'),\n\
+             CONSTRAINT pk UNIQUE (s)\n\
+             )",
+        );
+        assert_eq!(cols.len(), 6);
+        assert_eq!(cols["id"], ColumnClauses::default());
+        assert_eq!(
+            cols["created at"].default_expr.as_deref(),
+            Some("(datetime('now'))")
+        );
+        assert_eq!(cols["n"].default_expr.as_deref(), Some("-1.5"));
+        assert_eq!(cols["s"].default_expr.as_deref(), Some("'a,b''c'"));
+        assert_eq!(cols["total"].generated_expr.as_deref(), Some("n * 2"));
+        assert_eq!(cols["v"].generated_expr.as_deref(), Some("x'00'"));
+        assert!(cols["v"].default_expr.is_none());
+
+        assert!(parse_column_clauses("CREATE TABLE t AS SELECT (1) AS a").is_empty());
+    }
+
+    #[test]
+    fn test_parse_template() {
+        use TemplatePart::*;
+        let parts = parse_template(
+            "SELECT {col} FROM {table} WHERE id IN :ids AND x = @x -- :not_this\n\
+             AND note <> ':literal' AND \"$quoted\" = $y",
+        )
+        .unwrap();
+        assert_eq!(
+            parts,
+            vec![
+                Text("SELECT ".into()),
+                Identifier("col".into()),
+                Text(" FROM ".into()),
+                Identifier("table".into()),
+                Text(" WHERE id IN ".into()),
+                Value("ids".into()),
+                Text(" AND x = ".into()),
+                Value("x".into()),
+                Text(" -- :not_this\nAND note <> ':literal' AND \"$quoted\" = ".into()),
+                Value("y".into()),
+            ]
+        );
+        assert_eq!(parse_template("").unwrap(), vec![]);
+        assert!(parse_template("SELECT ?").is_err());
+        assert!(parse_template("SELECT {a FROM t").is_err());
+        assert!(parse_template("SELECT { }").is_err());
+        // A `?` in a literal is not a placeholder
+        assert_eq!(
+            parse_template("SELECT '?'").unwrap(),
+            vec![Text("SELECT '?'".into())]
+        );
+    }
+}
@@ -0,0 +1,232 @@
+//! Wrapper VFSes: a `sqlite3_vfs` that forwards every method to another
+//! registered VFS. The [`clock`](crate::clock) and [`faults`](crate::faults)
+//! modules register one each, replacing the methods they change.
+
+use libsqlite3_sys::{
+    sqlite3_file, sqlite3_filename, sqlite3_vfs, sqlite3_vfs_find, sqlite3_vfs_register, SQLITE_OK,
+};
+use std::ffi::{c_char, c_int, c_void, CString};
+use std::ptr;
+
+/// A registered wrapper VFS holding `state`. `vfs` and `base` come first so
+/// SQLite's `sqlite3_vfs` pointer is also a pointer to this struct, whatever
+/// `T` is.
+#[repr(C)]
+pub(crate) struct WrapperVfs<T> {
+    vfs: sqlite3_vfs,
+    base: *mut sqlite3_vfs,
+    name: CString,
+    pub(crate) state: T,
+}
+
+// Safety: `base` points to a registered VFS, which SQLite never frees.
+unsafe impl<T: Sync> Send for WrapperVfs<T> {}
+unsafe impl<T: Sync> Sync for WrapperVfs<T> {}
+
+impl<T> WrapperVfs<T> {
+    /// Register a VFS named `name` forwarding to the VFS registered as `base`
+    /// (the default VFS when `None`), after `customize` replaced some of its
+    /// methods. The VFS is leaked: SQLite may use it for the life of the
+    /// process.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `base` is not a registered VFS or SQLite refuses the
+    /// registration.
+    pub(crate) fn register(
+        name: String,
+        base: Option<&str>,
+        state: T,
+        customize: impl FnOnce(&mut sqlite3_vfs),
+    ) -> &'static WrapperVfs<T> {
+        let base_name = base.map(|b| CString::new(b).unwrap());
+        // Safety: a null name finds the default VFS, initializing SQLite.
+        let base =
+            unsafe { sqlite3_vfs_find(base_name.as_ref().map_or(ptr::null(), |b| b.as_ptr())) };
+        assert!(!base.is_null(), "no such VFS: {base_name:?}");
+        // Safety: checked non-null above; registered VFSes are never freed.
+        let base_ref = unsafe { &*base };
+        let name = CString::new(name).unwrap();
+        let mut vfs = sqlite3_vfs {
+            // The version 3 system call methods are only used for testing
+            // SQLite itself
+            iVersion: base_ref.iVersion.min(2),
+            szOsFile: base_ref.szOsFile,
+            mxPathname: base_ref.mxPathname,
+            pNext: ptr::null_mut(),
+            zName: name.as_ptr(),
+            pAppData: ptr::null_mut(),
+            xOpen: Some(x_open),
+            xDelete: Some(x_delete),
+            xAccess: Some(x_access),
+            xFullPathname: Some(x_full_pathname),
+            xDlOpen: Some(x_dl_open),
+            xDlError: Some(x_dl_error),
+            xDlSym: Some(x_dl_sym),
+            xDlClose: Some(x_dl_close),
+            xRandomness: Some(x_randomness),
+            xSleep: Some(x_sleep),
+            xCurrentTime: Some(x_current_time),
+            xGetLastError: Some(x_get_last_error),
+            xCurrentTimeInt64: Some(x_current_time_int64),
+            xSetSystemCall: None,
+            xGetSystemCall: None,
+            xNextSystemCall: None,
+        };
+        customize(&mut vfs);
+        let wrapper = Box::leak(Box::new(WrapperVfs {
+            vfs,
+            base,
+            name,
+            state,
+        }));
+        // Safety: the VFS is leaked, so it outlives every connection using it.
+        let rc = unsafe { sqlite3_vfs_register(&mut wrapper.vfs, 0) };
+        assert_eq!(rc, SQLITE_OK, "failed to register VFS {:?}", wrapper.name);
+        wrapper
+    }
+
+    /// The wrapper a VFS method was called on.
+    ///
+    /// # Safety
+    ///
+    /// `vfs` must be the `sqlite3_vfs` of a `WrapperVfs<T>`.
+    pub(crate) unsafe fn from_raw(vfs: *mut sqlite3_vfs) -> &'static WrapperVfs<T> {
+        &*(vfs as *const WrapperVfs<T>)
+    }
+
+    /// Name the VFS is registered under.
+    pub(crate) fn name(&'static self) -> &'static str {
+        self.name.to_str().unwrap()
+    }
+}
+
+/// The VFS a wrapper forwards to.
+///
+/// # Safety
+///
+/// `vfs` must be the `sqlite3_vfs` of a [`WrapperVfs`].
+pub(crate) unsafe fn base(vfs: *mut sqlite3_vfs) -> &'static sqlite3_vfs {
+    // The layout of the fields read does not depend on `T`
+    &*WrapperVfs::<()>::from_raw(vfs).base
+}
+
+/// Forwards `xOpen`. Files are opened by the base VFS, so they use its I/O
+/// methods.
+///
+/// # Safety
+///
+/// As for any `xOpen`, with `vfs` the `sqlite3_vfs` of a [`WrapperVfs`].
+pub(crate) unsafe extern "C" fn x_open(
+    vfs: *mut sqlite3_vfs,
+    name: sqlite3_filename,
+    file: *mut sqlite3_file,
+    flags: c_int,
+    out_flags: *mut c_int,
+) -> c_int {
+    let base = base(vfs);
+    base.xOpen.unwrap()(base as *const _ as *mut _, name, file, flags, out_flags)
+}
+
+unsafe extern "C" fn x_delete(
+    vfs: *mut sqlite3_vfs,
+    name: *const c_char,
+    sync_dir: c_int,
+) -> c_int {
+    let base = base(vfs);
+    base.xDelete.unwrap()(base as *const _ as *mut _, name, sync_dir)
+}
+
+unsafe extern "C" fn x_access(
+    vfs: *mut sqlite3_vfs,
+    name: *const c_char,
+    flags: c_int,
+    res_out: *mut c_int,
+) -> c_int {
+    let base = base(vfs);
+    base.xAccess.unwrap()(base as *const _ as *mut _, name, flags, res_out)
+}
+
+unsafe extern "C" fn x_full_pathname(
+    vfs: *mut sqlite3_vfs,
+    name: *const c_char,
+    n_out: c_int,
+    out: *mut c_char,
+) -> c_int {
+    let base = base(vfs);
+    base.xFullPathname.unwrap()(base as *const _ as *mut _, name, n_out, out)
+}
+
+unsafe extern "C" fn x_dl_open(vfs: *mut sqlite3_vfs, filename: *const c_char) -> *mut c_void {
+    let base = base(vfs);
+    match base.xDlOpen {
+        Some(f) => f(base as *const _ as *mut _, filename),
+        None => ptr::null_mut(),
+    }
+}
+
+unsafe extern "C" fn x_dl_error(vfs: *mut sqlite3_vfs, n_byte: c_int, err_msg: *mut c_char) {
+    let base = base(vfs);
+    if let Some(f) = base.xDlError {
+        f(base as *const _ as *mut _, n_byte, err_msg);
+    }
+}
+
+type DlSym = unsafe extern "C" fn(*mut sqlite3_vfs, *mut c_void, *const c_char);
+
+unsafe extern "C" fn x_dl_sym(
+    vfs: *mut sqlite3_vfs,
+    handle: *mut c_void,
+    symbol: *const c_char,
+) -> Option<DlSym> {
+    let base = base(vfs);
+    base.xDlSym
+        .and_then(|f| f(base as *const _ as *mut _, handle, symbol))
+}
+
+unsafe extern "C" fn x_dl_close(vfs: *mut sqlite3_vfs, handle: *mut c_void) {
+    let base = base(vfs);
+    if let Some(f) = base.xDlClose {
+        f(base as *const _ as *mut _, handle);
+    }
+}
+
+unsafe extern "C" fn x_randomness(vfs: *mut sqlite3_vfs, n_byte: c_int, out: *mut c_char) -> c_int {
+    let base = base(vfs);
+    base.xRandomness.unwrap()(base as *const _ as *mut _, n_byte, out)
+}
+
+unsafe extern "C" fn x_sleep(vfs: *mut sqlite3_vfs, microseconds: c_int) -> c_int {
+    let base = base(vfs);
+    base.xSleep.unwrap()(base as *const _ as *mut _, microseconds)
+}
+
+unsafe extern "C" fn x_current_time(vfs: *mut sqlite3_vfs, out: *mut f64) -> c_int {
+    let base = base(vfs);
+    base.xCurrentTime.unwrap()(base as *const _ as *mut _, out)
+}
+
+unsafe extern "C" fn x_get_last_error(
+    vfs: *mut sqlite3_vfs,
+    n_byte: c_int,
+    out: *mut c_char,
+) -> c_int {
+    let base = base(vfs);
+    match base.xGetLastError {
+        Some(f) => f(base as *const _ as *mut _, n_byte, out),
+        None => 0,
+    }
+}
+
+unsafe extern "C" fn x_current_time_int64(vfs: *mut sqlite3_vfs, out: *mut i64) -> c_int {
+    let base = base(vfs);
+    match base.xCurrentTimeInt64 {
+        Some(f) if base.iVersion >= 2 => f(base as *const _ as *mut _, out),
+        _ => {
+            let mut days = 0.0;
+            let rc = base.xCurrentTime.unwrap()(base as *const _ as *mut _, &mut days);
+            *out = (days * 86_400_000.0) as i64;
+            rc
+        }
+    }
+}
@@ -0,0 +1,396 @@
+//! WAL archiving and point-in-time restore.
+//!
+//! A [`WalArchive`] is a directory holding base snapshots of one database and
+//! the WAL frames committed after them. The [`WalHook`](crate::wal_hook::WalHook)
+//! of a pool with an archive copies the frames of every commit into a new
+//! segment file. [`restore_to`] rebuilds the database as of an earlier time by
+//! copying the latest base snapshot taken before it and replaying the archived
+//! commits up to it.
+//!
+//! Archive layout (`seq` orders files; `nanos` is the Unix time in ns at which
+//! the file was written):
+//!
+//! * `base-<seq>-<nanos>.db` - page-for-page copy of the database; segments
+//!   from `seq` on are replayed on top of it.
+//! * `wal-<seq>-<nanos>.frames` - the 32-byte WAL header followed by the
+//!   frames (24-byte header + page) of one or more commits.
+//!
+//! Only commits made on connections with the hook installed are archived;
+//! writes from other processes leave a gap until the next base snapshot.
+
+use libsqlite3_sys::{
+    sqlite3, sqlite3_backup_finish, sqlite3_backup_init, sqlite3_backup_step, sqlite3_close,
+    sqlite3_errcode, sqlite3_open_v2, SQLITE_DONE, SQLITE_OK, SQLITE_OPEN_CREATE,
+    SQLITE_OPEN_READWRITE,
+};
+use sqlx::SqlitePool;
+use std::ffi::CString;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub(crate) const WAL_HEADER_SIZE: usize = 32;
+const FRAME_HEADER_SIZE: usize = 24;
+
+/// Where archiving has got to in the current WAL.
+#[derive(Debug, Default)]
+struct ArchiveState {
+    /// Salts of the WAL generation being archived; a checkpoint that restarts
+    /// the WAL changes them.
+    salts: Option<[u8; 8]>,
+    /// Frames of that generation already archived.
+    frames: u32,
+    next_seq: u64,
+}
+
+/// An archive directory for one database.
+#[derive(Debug)]
+pub struct WalArchive {
+    dir: PathBuf,
+    state: Mutex<ArchiveState>,
+}
+
+/// A base snapshot or segment file name, parsed.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct ArchiveFile {
+    seq: u64,
+    nanos: u128,
+    path: PathBuf,
+}
+
+fn parse_name(path: &Path, prefix: &str, suffix: &str) -> Option<ArchiveFile> {
+    let name = path.file_name()?.to_str()?;
+    let (seq, nanos) = name
+        .strip_prefix(prefix)?
+        .strip_suffix(suffix)?
+        .split_once('-')?;
+    Some(ArchiveFile {
+        seq: seq.parse().ok()?,
+        nanos: nanos.parse().ok()?,
+        path: path.to_path_buf(),
+    })
+}
+
+/// Base snapshots and segments in `dir`, each sorted by sequence number.
+fn list_archive(dir: &Path) -> io::Result<(Vec<ArchiveFile>, Vec<ArchiveFile>)> {
+    let mut bases = Vec::new();
+    let mut segments = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if let Some(base) = parse_name(&path, "base-", ".db") {
+            bases.push(base);
+        } else if let Some(segment) = parse_name(&path, "wal-", ".frames") {
+            segments.push(segment);
+        }
+    }
+    bases.sort();
+    segments.sort();
+    Ok((bases, segments))
+}
+
+fn now_nanos() -> u128 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos())
+}
+
+/// Write `data` to `path` via a temporary file, so a crash never leaves a
+/// partial archive file behind.
+fn write_atomically(
+    path: &Path,
+    write: impl FnOnce(&mut File) -> io::Result<()>,
+) -> io::Result<()> {
+    let tmp = path.with_extension("tmp");
+    let mut file = File::create(&tmp)?;
+    write(&mut file)?;
+    file.sync_all()?;
+    fs::rename(&tmp, path)
+}
+
+pub(crate) fn be_u32(bytes: &[u8]) -> u32 {
+    u32::from_be_bytes(bytes[..4].try_into().unwrap())
+}
+
+impl WalArchive {
+    /// Use `dir` (created if missing) as an archive; numbering continues after
+    /// the files already in it.
+    pub fn open(dir: impl Into<PathBuf>) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        let (bases, segments) = list_archive(&dir)?;
+        let next_seq = bases
+            .iter()
+            .chain(&segments)
+            .map(|f| f.seq + 1)
+            .max()
+            .unwrap_or(0);
+        Ok(WalArchive {
+            dir,
+            state: Mutex::new(ArchiveState {
+                next_seq,
+                ..ArchiveState::default()
+            }),
+        })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Copy the committed frames of `wal_path` not archived yet into a new
+    /// segment. `committed` is the number of frames SQLite reports in the WAL.
+    pub(crate) fn archive_commits(&self, wal_path: &Path, committed: u32) -> io::Result<()> {
+        let mut state = self.state.lock().unwrap();
+        let mut wal = File::open(wal_path)?;
+        let mut header = [0u8; WAL_HEADER_SIZE];
+        wal.read_exact(&mut header)?;
+        let page_size = be_u32(&header[8..]) as usize;
+        let salts: [u8; 8] = header[16..24].try_into().unwrap();
+        if state.salts != Some(salts) {
+            // A restarted WAL: its frames start over from the beginning
+            state.salts = Some(salts);
+            state.frames = 0;
+        }
+        if committed <= state.frames {
+            return Ok(());
+        }
+
+        let frame_size = FRAME_HEADER_SIZE + page_size;
+        let start = WAL_HEADER_SIZE + state.frames as usize * frame_size;
+        let mut frames = vec![0u8; (committed - state.frames) as usize * frame_size];
+        wal.seek(SeekFrom::Start(start as u64))?;
+        wal.read_exact(&mut frames)?;
+        // Only whole commits are archived: stop after the last commit frame
+        let mut end = 0;
+        for (i, frame) in frames.chunks_exact(frame_size).enumerate() {
+            if frame[8..16] != salts {
+                break;
+            }
+            if be_u32(&frame[4..]) != 0 {
+                end = i + 1;
+            }
+        }
+        if end == 0 {
+            return Ok(());
+        }
+
+        let path = self.dir.join(format!(
+            "wal-{:020}-{:020}.frames",
+            state.next_seq,
+            now_nanos()
+        ));
+        write_atomically(&path, |file| {
+            file.write_all(&header)?;
+            file.write_all(&frames[..end * frame_size])
+        })?;
+        state.next_seq += 1;
+        state.frames += end as u32;
+        Ok(())
+    }
+
+    /// Write a base snapshot of the database `pool` is connected to. Commits
+    /// archived from now on are replayed on top of it when restoring.
+    pub async fn snapshot(&self, pool: &SqlitePool) -> Result<(), sqlx::Error> {
+        let seq = {
+            let mut state = self.state.lock().unwrap();
+            state.next_seq += 1;
+            state.next_seq - 1
+        };
+        let tmp = self.dir.join(format!("base-{seq:020}.tmp"));
+        let mut conn = pool.acquire().await?;
+        let mut handle = conn.lock_handle().await?;
+        // Safety: the handle lock gives exclusive use of the connection.
+        unsafe { backup(handle.as_raw_handle().as_ptr(), &tmp) }.map_err(sqlx::Error::Io)?;
+        drop(handle);
+        let path = self
+            .dir
+            .join(format!("base-{seq:020}-{:020}.db", now_nanos()));
+        File::open(&tmp)
+            .and_then(|f| f.sync_all())
+            .and_then(|_| fs::rename(&tmp, &path))
+            .map_err(sqlx::Error::Io)
+    }
+}
+
+/// Copy the main database of `src` page for page into a new file at `dest`.
+///
+/// # Safety
+///
+/// `src` must meet the [raw handle](crate#raw-handles) requirements.
+pub(crate) unsafe fn backup(src: *mut sqlite3, dest: &Path) -> io::Result<()> {
+    let _ = fs::remove_file(dest);
+    let dest_path = CString::new(dest.to_string_lossy().as_bytes())
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    let mut dest_db: *mut sqlite3 = std::ptr::null_mut();
+    let flags = SQLITE_OPEN_READWRITE | SQLITE_OPEN_CREATE;
+    let mut rc = sqlite3_open_v2(dest_path.as_ptr(), &mut dest_db, flags, std::ptr::null());
+    if rc == SQLITE_OK {
+        let main = c"main".as_ptr();
+        let backup = sqlite3_backup_init(dest_db, main, src, main);
+        if backup.is_null() {
+            rc = sqlite3_errcode(dest_db);
+        } else {
+            // One step copies everything within a single read transaction
+            rc = sqlite3_backup_step(backup, -1);
+            sqlite3_backup_finish(backup);
+            if rc == SQLITE_DONE {
+                rc = SQLITE_OK;
+            }
+        }
+    }
+    sqlite3_close(dest_db);
+    if rc == SQLITE_OK {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!(
+            "SQLite backup failed with error code {rc}"
+        )))
+    }
+}
+
+/// Rebuild the database archived in `archive_dir` as of `until` (Unix time in
+/// nanoseconds; None for the latest archived commit) into the new file `dest`.
+///
+/// Starts from the latest base snapshot written at or before `until` and
+/// replays every archived commit after it up to `until`. Returns the number of
+/// segments replayed.
+pub fn restore_to(archive_dir: &Path, dest: &Path, until: Option<u128>) -> io::Result<usize> {
+    let until = until.unwrap_or(u128::MAX);
+    if dest.exists() || Path::new(&format!("{}-wal", dest.display())).exists() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} already exists", dest.display()),
+        ));
+    }
+    let (bases, segments) = list_archive(archive_dir)?;
+    let base = bases
+        .iter()
+        .rev()
+        .find(|b| b.nanos <= until)
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!(
+                    "no base snapshot in {} at or before the requested time",
+                    archive_dir.display()
+                ),
+            )
+        })?;
+    let segments: Vec<_> = segments
+        .iter()
+        .filter(|s| s.seq > base.seq && s.nanos <= until)
+        .collect();
+
+    fs::copy(&base.path, dest)?;
+    let result = replay(dest, &segments);
+    if result.is_err() {
+        let _ = fs::remove_file(dest);
+    }
+    result.map(|_| segments.len())
+}
+
+/// Apply the commits in `segments`, in order, to the database file `dest`.
+fn replay(dest: &Path, segments: &[&ArchiveFile]) -> io::Result<()> {
+    let mut db = OpenOptions::new().write(true).open(dest)?;
+    let mut final_size = None;
+    for segment in segments {
+        let data = fs::read(&segment.path)?;
+        if data.len() < WAL_HEADER_SIZE {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("truncated archive segment {}", segment.path.display()),
+            ));
+        }
+        let page_size = be_u32(&data[8..]) as u64;
+        let frame_size = FRAME_HEADER_SIZE + page_size as usize;
+        for frame in data[WAL_HEADER_SIZE..].chunks_exact(frame_size) {
+            let page = be_u32(frame) as u64;
+            db.seek(SeekFrom::Start((page - 1) * page_size))?;
+            db.write_all(&frame[FRAME_HEADER_SIZE..])?;
+            let size = be_u32(&frame[4..]) as u64;
+            if size != 0 {
+                final_size = Some(size * page_size);
+            }
+        }
+    }
+    if let Some(size) = final_size {
+        db.set_len(size)?;
+    }
+    db.sync_all()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wal_hook::WalHook;
+    use crate::{open_pool, PoolOptions};
+    use std::sync::Arc;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "rapsqlite-wal-archive-{name}-{}",
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    async fn count(path: &Path) -> i64 {
+        let pool = open_pool(path.to_str().unwrap(), &PoolOptions::default())
+            .await
+            .unwrap();
+        let (n,): (i64,) = sqlx::query_as("SELECT count(*) FROM t")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        pool.close().await;
+        n
+    }
+
+    #[tokio::test]
+    async fn test_archive_and_restore() {
+        let dir = temp_dir("restore");
+        let db = dir.join("app.db");
+        let archive_dir = dir.join("archive");
+        File::create(&db).unwrap();
+        let options = PoolOptions {
+            max_connections: Some(2),
+            wal_hook: Some(Arc::new(WalHook {
+                archive: Some(Arc::new(WalArchive::open(&archive_dir).unwrap())),
+                listener: None,
+            })),
+            ..PoolOptions::default()
+        };
+        let pool = open_pool(db.to_str().unwrap(), &options).await.unwrap();
+        sqlx::query("CREATE TABLE t (x BLOB)")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let mut marks = Vec::new();
+        for _ in 0..3 {
+            for _ in 0..400 {
+                sqlx::query("INSERT INTO t VALUES (randomblob(1000))")
+                    .execute(&pool)
+                    .await
+                    .unwrap();
+            }
+            marks.push(now_nanos());
+        }
+        pool.close().await;
+
+        for (i, mark) in marks.iter().enumerate() {
+            let dest = dir.join(format!("restored-{i}.db"));
+            restore_to(&archive_dir, &dest, Some(*mark)).unwrap();
+            assert_eq!(count(&dest).await, 400 * (i as i64 + 1));
+        }
+        let latest = dir.join("latest.db");
+        restore_to(&archive_dir, &latest, None).unwrap();
+        assert_eq!(count(&latest).await, 1200);
+        assert!(restore_to(&archive_dir, &latest, None).is_err());
+        assert!(restore_to(&archive_dir, &dir.join("early.db"), Some(0)).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,231 @@
+//! WAL hook: archiving and replication events.
+//!
+//! [`install`] registers a `sqlite3_wal_hook` that runs after every commit in
+//! WAL mode. It copies the commit into the pool's [`WalArchive`], if any,
+//! reports a [`WalEvent::FramesWritten`] to the listener, if any, and then
+//! runs the checkpoints SQLite's automatic checkpointing would have run (the
+//! hook replaces it), reporting each with [`WalEvent::CheckpointStart`] and
+//! [`WalEvent::CheckpointFinish`].
+//!
+//! Events carry the WAL *generation*: the two salts from the WAL header as one
+//! integer. SQLite picks new salts whenever it restarts the WAL from the
+//! beginning after a checkpoint, so a replication tool that sees the
+//! generation change knows the frames it shipped have been checkpointed into
+//! the database file and the WAL is being rewritten from frame 1.
+//!
+//! Only commits and checkpoints run by connections with the hook installed
+//! are reported; `PRAGMA wal_checkpoint` and other processes are not.
+
+use libsqlite3_sys::{
+    sqlite3, sqlite3_db_filename, sqlite3_set_clientdata, sqlite3_wal_checkpoint_v2,
+    sqlite3_wal_hook, SQLITE_CHECKPOINT_PASSIVE, SQLITE_IOERR, SQLITE_OK,
+};
+use std::ffi::{c_char, c_int, c_void, CStr};
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::wal_archive::{WalArchive, WAL_HEADER_SIZE};
+
+/// Frames in the WAL after which a commit runs a passive checkpoint, as
+/// SQLite's default `wal_autocheckpoint` does.
+const AUTOCHECKPOINT_FRAMES: c_int = 1000;
+
+const HOOK_KEY: &CStr = c"rapsqlite.wal_hook";
+
+/// Something the WAL hook did, reported to [`WalHook::listener`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WalEvent<'a> {
+    /// A commit appended frames to the WAL; `frames` is the number of frames
+    /// now in it.
+    FramesWritten {
+        generation: u64,
+        frames: u32,
+        wal_path: &'a Path,
+    },
+    /// A checkpoint of the `frames` frames in the WAL is about to run.
+    CheckpointStart { generation: u64, frames: u32 },
+    /// A checkpoint finished; `checkpointed` of the `frames` frames in the
+    /// WAL are now in the database file. `result` is the SQLite result code.
+    CheckpointFinish {
+        generation: u64,
+        frames: u32,
+        checkpointed: u32,
+        result: c_int,
+    },
+}
+
+/// Receives [`WalEvent`]s on the thread that committed. It runs while the
+/// connection is still busy, so it should hand events off rather than block.
+pub type WalListener = dyn Fn(&WalEvent<'_>) + Send + Sync;
+
+/// What the WAL hook does on each commit.
+#[derive(Default)]
+pub struct WalHook {
+    /// Archive every commit into this [`WalArchive`].
+    pub archive: Option<Arc<WalArchive>>,
+    /// Report every commit and checkpoint to this callback.
+    pub listener: Option<Box<WalListener>>,
+}
+
+impl fmt::Debug for WalHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WalHook")
+            .field("archive", &self.archive)
+            .field("listener", &self.listener.as_ref().map(|_| ".."))
+            .finish()
+    }
+}
+
+/// The generation (salts) of the WAL at `wal_path`.
+fn read_generation(wal_path: &Path) -> io::Result<u64> {
+    let mut header = [0u8; WAL_HEADER_SIZE];
+    File::open(wal_path)?.read_exact(&mut header)?;
+    Ok(u64::from_be_bytes(header[16..24].try_into().unwrap()))
+}
+
+unsafe extern "C" fn free_hook(hook: *mut c_void) {
+    drop(Box::from_raw(hook as *mut Arc<WalHook>));
+}
+
+unsafe extern "C" fn wal_hook(
+    hook: *mut c_void,
+    db: *mut sqlite3,
+    name: *const c_char,
+    frames: c_int,
+) -> c_int {
+    let hook = &*(hook as *const WalHook);
+    if CStr::from_ptr(name).to_bytes() != b"main" {
+        return SQLITE_OK;
+    }
+    let filename = sqlite3_db_filename(db, name);
+    if filename.is_null() {
+        return SQLITE_OK;
+    }
+    let mut wal_path = CStr::from_ptr(filename).to_string_lossy().into_owned();
+    wal_path.push_str("-wal");
+    let wal_path = Path::new(&wal_path);
+    let frames = frames.max(0) as u32;
+    if let Some(archive) = &hook.archive {
+        // The commit has happened either way; an error fails the statement so
+        // the caller learns the archive is missing it
+        if archive.archive_commits(wal_path, frames).is_err() {
+            return SQLITE_IOERR;
+        }
+    }
+    // A WAL whose header cannot be read has no generation to report
+    let listener = hook
+        .listener
+        .as_deref()
+        .and_then(|listener| Some((listener, read_generation(wal_path).ok()?)));
+    if let Some((listener, generation)) = listener {
+        listener(&WalEvent::FramesWritten {
+            generation,
+            frames,
+            wal_path,
+        });
+    }
+    if frames >= AUTOCHECKPOINT_FRAMES as u32 {
+        if let Some((listener, generation)) = listener {
+            listener(&WalEvent::CheckpointStart { generation, frames });
+        }
+        let mut log_frames = 0;
+        let mut checkpointed = 0;
+        let result = sqlite3_wal_checkpoint_v2(
+            db,
+            name,
+            SQLITE_CHECKPOINT_PASSIVE,
+            &mut log_frames,
+            &mut checkpointed,
+        );
+        if let Some((listener, generation)) = listener {
+            listener(&WalEvent::CheckpointFinish {
+                generation,
+                frames: log_frames.max(0) as u32,
+                checkpointed: checkpointed.max(0) as u32,
+                result,
+            });
+        }
+    }
+    SQLITE_OK
+}
+
+/// Run `hook` after every commit on `db`, replacing its WAL hook (and so
+/// SQLite's automatic checkpointing, which the hook takes over).
+///
+/// # Safety
+///
+/// `db` must meet the [raw handle](crate#raw-handles) requirements.
+pub unsafe fn install(db: *mut sqlite3, hook: Arc<WalHook>) {
+    let ptr = Arc::as_ptr(&hook) as *mut c_void;
+    sqlite3_wal_hook(db, Some(wal_hook), ptr);
+    let data = Box::into_raw(Box::new(hook)) as *mut c_void;
+    sqlite3_set_clientdata(db, HOOK_KEY.as_ptr(), data, Some(free_hook));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{open_pool, PoolOptions};
+    use std::sync::Mutex;
+
+    #[tokio::test]
+    async fn test_listener_events() {
+        let dir = std::env::temp_dir().join(format!("rapsqlite-wal-hook-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let db = dir.join("app.db");
+        File::create(&db).unwrap();
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&events);
+        let options = PoolOptions {
+            wal_hook: Some(Arc::new(WalHook {
+                archive: None,
+                listener: Some(Box::new(move |event| {
+                    let event = match *event {
+                        WalEvent::FramesWritten {
+                            generation, frames, ..
+                        } => ("frames", generation, frames),
+                        WalEvent::CheckpointStart { generation, frames } => {
+                            ("start", generation, frames)
+                        }
+                        WalEvent::CheckpointFinish {
+                            generation,
+                            checkpointed,
+                            ..
+                        } => ("finish", generation, checkpointed),
+                    };
+                    sink.lock().unwrap().push(event);
+                })),
+            })),
+            ..PoolOptions::default()
+        };
+        let pool = open_pool(db.to_str().unwrap(), &options).await.unwrap();
+        sqlx::query("CREATE TABLE t (x BLOB)")
+            .execute(&pool)
+            .await
+            .unwrap();
+        for _ in 0..300 {
+            sqlx::query("INSERT INTO t VALUES (randomblob(10000))")
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+        pool.close().await;
+
+        let events = events.lock().unwrap();
+        let frames: Vec<_> = events.iter().filter(|e| e.0 == "frames").collect();
+        assert_eq!(frames.len(), 301);
+        let start = events.iter().position(|e| e.0 == "start").unwrap();
+        assert!(events[start].2 >= AUTOCHECKPOINT_FRAMES as u32);
+        assert_eq!(events[start + 1].0, "finish");
+        assert_eq!(events[start + 1].2, events[start].2);
+        // The WAL restarts after a complete checkpoint, with new salts
+        let generations: std::collections::HashSet<_> = frames.iter().map(|e| e.1).collect();
+        assert!(generations.len() > 1);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -47,9 +47,17 @@ Example:
                 await conn.rollback()
 """
 
-from typing import Any, List, Optional
+from typing import Any, AsyncIterator, Callable, Dict, Iterator, List, Optional, Tuple
 
+import asyncio as _asyncio
 import builtins as _builtins
+import csv as _csv
+import enum as _enum
+import io as _io
+import os as _os
+import socket as _socket
+import time as _time
+import uuid as _uuid
 
 try:
     # Preferred: import extension from the local module name used when installed.
@@ -71,6 +79,57 @@ DatabaseError = _ext.DatabaseError
 OperationalError = _ext.OperationalError
 ProgrammingError = _ext.ProgrammingError
 IntegrityError = _ext.IntegrityError
+NotSupportedError = _ext.NotSupportedError
+QueryAborted = _ext.QueryAborted
+DeadlineExceeded = _ext.DeadlineExceeded
+ResultTooLarge = _ext.ResultTooLarge
+PoolTimeout = _ext.PoolTimeout
+NPlusOneWarning = _ext.NPlusOneWarning
+TaskSafetyWarning = _ext.TaskSafetyWarning
+configure_runtime = _ext.configure_runtime
+register_converter = _ext.register_converter
+restore_to = _ext.restore_to
+decrypt_file = _ext.decrypt_file
+write_encrypted = _ext.write_encrypted
+write_compressed = _ext.write_compressed
+read_compressed = _ext.read_compressed
+fetch_cached = _ext.fetch_cached
+sql = _ext.sql
+uri_filename = _ext.uri_filename
+parse_uri = _ext.parse_uri
+deadline = _ext.deadline
+Deadline = _ext.Deadline
+Sql = _ext.Sql
+PooledConnection = _ext.PooledConnection
+FakeClock = _ext.FakeClock
+FaultInjector = _ext.FaultInjector
+Pointer = _ext.Pointer
+PARSE_DECLTYPES: int = _ext.PARSE_DECLTYPES
+PARSE_COLNAMES: int = _ext.PARSE_COLNAMES
+
+# SQLite constants (SQLITE_OK, SQLITE_DENY, authorizer actions, open flags and
+# limit categories) as module attributes, and grouped into enums.
+_constant_groups = {name: (prefix, members) for name, prefix, members in _ext._constant_groups}
+_constant_names: List[str] = []
+for _prefix, _members in _constant_groups.values():
+    globals().update(_members)
+    _constant_names.extend(name for name in _members if name not in _constant_names)
+
+
+def _constant_enum(name: str, kind: Any = _enum.IntEnum) -> Any:
+    prefix, members = _constant_groups[name]
+    return kind(
+        name,
+        {constant[len(prefix) :]: value for constant, value in members.items()},
+        module=__name__,
+    )
+
+
+ResultCode = _constant_enum("ResultCode")
+AuthorizerResult = _constant_enum("AuthorizerResult")
+AuthorizerAction = _constant_enum("AuthorizerAction")
+OpenFlag = _constant_enum("OpenFlag", _enum.IntFlag)
+Limit = _constant_enum("Limit")
 try:
     ValueError = _ext.ValueError
 except AttributeError:  # pragma: no cover - compatibility with older wheels
@@ -95,18 +154,242 @@ __all__: List[str] = [
     "Cursor",
     "Row",
     "connect",
+    "register",
+    "unregister",
+    "ConnectionSpec",
+    "diff_data",
+    "FileLock",
+    "with_write_lease",
+    "Router",
+    "configure_runtime",
+    "register_converter",
+    "restore_to",
+    "decrypt_file",
+    "write_encrypted",
+    "write_compressed",
+    "read_compressed",
+    "open_http",
+    "fetch_cached",
+    "sql",
+    "uri_filename",
+    "parse_uri",
+    "deadline",
+    "Deadline",
+    "Sql",
+    "PooledConnection",
+    "FakeClock",
+    "FaultInjector",
+    "Pointer",
+    "PARSE_DECLTYPES",
+    "PARSE_COLNAMES",
+    "ResultCode",
+    "AuthorizerResult",
+    "AuthorizerAction",
+    "OpenFlag",
+    "Limit",
+    *_constant_names,
     "Error",
     "Warning",
     "DatabaseError",
     "OperationalError",
     "ProgrammingError",
     "IntegrityError",
+    "NotSupportedError",
+    "QueryAborted",
+    "DeadlineExceeded",
+    "ResultTooLarge",
+    "PoolTimeout",
     "ValueError",
+    "NPlusOneWarning",
+    "TaskSafetyWarning",
 ]
 
 
+# -----------------------------------------------------------------------------
+# Named connections
+# -----------------------------------------------------------------------------
+
+_NAMED_PREFIX = "name://"
+# Default of connect()'s arguments, so registered options only fill in the
+# ones the caller left out
+_UNSET: Any = object()
+_CONNECT_OPTIONS = (
+    "pragmas",
+    "timeout",
+    "strict",
+    "cache",
+    "cache_size",
+    "group_commit_window",
+    "debug",
+    "n_plus_one_threshold",
+    "hardened",
+    "hardened_allow",
+    "max_rows",
+    "max_result_bytes",
+    "shared_pool",
+    "dedicated_runtime",
+    "detect_types",
+    "native_functions",
+    "wal_archive",
+    "replication_hook",
+    "auto_analyze",
+    "init_hook",
+    "init_hook_scope",
+    "clock",
+    "faults",
+    "read_pool_size",
+    "write_pool_size",
+    "max_queue",
+)
+_registry: Dict[str, Tuple[str, Dict[str, Any]]] = {}
+
+
+def register(name: str, path: str, **options: Any) -> None:
+    """Register a named connection for ``connect("name://<name>")``.
+
+    Centralizes connection configuration so modules can connect by name and
+    the file can be swapped per environment in one place. Registering an
+    existing name replaces it.
+
+    Args:
+        name: Name to register (non-empty, without ``://``).
+        path: Database path passed to :class:`Connection`.
+        **options: Default :func:`connect` options for this name
+            (``pragmas``, ``timeout``, ``strict``, ``cache``, ``cache_size``,
+            ``group_commit_window``, ``debug``, ``n_plus_one_threshold``,
+            ``hardened``, ``hardened_allow``, ``max_rows``,
+            ``max_result_bytes``, ``shared_pool``, ``dedicated_runtime``,
+            ``detect_types``, ``native_functions``, ``wal_archive``,
+            ``replication_hook``, ``auto_analyze``, ``init_hook``,
+            ``init_hook_scope``, ``clock``, ``faults``, ``read_pool_size``,
+            ``write_pool_size``, ``max_queue``).
+
+    Raises:
+        ValueError: If ``name`` is empty or contains ``://``.
+        TypeError: If an option is not a :func:`connect` option.
+
+    Example:
+        ::
+
+            rapsqlite.register("analytics", "/data/analytics.db",
+                               pragmas={"journal_mode": "WAL"}, timeout=30.0)
+
+            async with rapsqlite.connect("name://analytics") as conn:
+                ...
+    """
+    if not name or "://" in name:
+        raise ValueError(f"Invalid connection name: {name!r}")
+    unknown = sorted(set(options) - set(_CONNECT_OPTIONS))
+    if unknown:
+        raise TypeError(
+            f"Unknown connect() option(s) for {name!r}: {', '.join(unknown)}"
+        )
+    _registry[name] = (path, dict(options))
+
+
+def unregister(name: str) -> None:
+    """Remove a named connection registered with :func:`register`.
+
+    Raises:
+        ValueError: If no connection is registered under ``name``.
+    """
+    if _registry.pop(name, None) is None:
+        raise ValueError(f"No connection registered under name {name!r}")
+
+
+def _resolve_named(path: str) -> Tuple[str, Dict[str, Any]]:
+    name = path[len(_NAMED_PREFIX) :]
+    try:
+        registered_path, options = _registry[name]
+    except KeyError:
+        raise ValueError(f"No connection registered under name {name!r}") from None
+    return registered_path, dict(options)
+
+
+class ConnectionSpec:
+    """Picklable description of a connection: a path and :func:`connect` options.
+
+    A :class:`Connection` owns a connection pool bound to its process and
+    cannot be pickled. Pass a ``ConnectionSpec`` to worker processes
+    (``multiprocessing``, ``concurrent.futures.ProcessPoolExecutor``) instead,
+    and open a connection from it in the worker.
+
+    Args:
+        path: Database path passed to :func:`connect` (``"name://<name>"``
+            paths are resolved in the process that connects).
+        options: :func:`connect` options (the ones :func:`register`
+            accepts). Values must be picklable themselves.
+
+    Raises:
+        TypeError: If an option is not a :func:`connect` option.
+
+    Example:
+        ::
+
+            spec = rapsqlite.ConnectionSpec("app.db", {"timeout": 30.0})
+
+            def work(spec, job_id):
+                async def run():
+                    async with spec.connect() as conn:
+                        return await conn.fetch_one(
+                            "SELECT * FROM jobs WHERE id = ?", [job_id]
+                        )
+                return asyncio.run(run())
+
+            with ProcessPoolExecutor() as pool:
+                rows = list(pool.map(work, [spec] * 3, [1, 2, 3]))
+    """
+
+    def __init__(self, path: str, options: Optional[Dict[str, Any]] = None) -> None:
+        options = dict(options or {})
+        unknown = sorted(set(options) - set(_CONNECT_OPTIONS))
+        if unknown:
+            raise TypeError(f"Unknown connect() option(s): {', '.join(unknown)}")
+        self.path = path
+        self.options = options
+
+    def connect(self) -> "Connection":  # type: ignore[valid-type]
+        """Open a connection with :func:`connect` from this spec."""
+        return connect(self.path, **self.options)
+
+    def __eq__(self, other: object) -> bool:
+        if not isinstance(other, ConnectionSpec):
+            return NotImplemented
+        return (self.path, self.options) == (other.path, other.options)
+
+    def __repr__(self) -> str:
+        return f"ConnectionSpec({self.path!r}, {self.options!r})"
+
+
 def connect(
-    path: str, *, pragmas: Any = None, timeout: float = 5.0, **kwargs: Any
+    path: str,
+    *,
+    pragmas: Any = _UNSET,
+    timeout: float = _UNSET,
+    strict: bool = _UNSET,
+    cache: Optional[str] = _UNSET,
+    cache_size: int = _UNSET,
+    group_commit_window: Optional[float] = _UNSET,
+    debug: bool = _UNSET,
+    n_plus_one_threshold: int = _UNSET,
+    hardened: bool = _UNSET,
+    hardened_allow: Optional[List[str]] = _UNSET,
+    max_rows: Optional[int] = _UNSET,
+    max_result_bytes: Optional[int] = _UNSET,
+    shared_pool: bool = _UNSET,
+    dedicated_runtime: bool = _UNSET,
+    detect_types: int = _UNSET,
+    native_functions: bool = _UNSET,
+    wal_archive: Optional[str] = _UNSET,
+    replication_hook: Optional[Callable[[Dict[str, Any]], None]] = _UNSET,
+    auto_analyze: bool = _UNSET,
+    init_hook: Optional[Callable[[Any], Any]] = _UNSET,
+    init_hook_scope: str = _UNSET,
+    clock: Any = _UNSET,
+    faults: Any = _UNSET,
+    read_pool_size: Optional[int] = _UNSET,
+    write_pool_size: Optional[int] = _UNSET,
+    max_queue: Optional[int] = _UNSET,
 ) -> "Connection":  # type: ignore[valid-type]
     """Connect to a SQLite database.
 
@@ -127,12 +410,100 @@ def connect(
             another process/thread before raising an error. Default: 5.0 seconds.
             This sets SQLite's busy_timeout PRAGMA. Set to 0.0 to disable timeout.
             This matches aiosqlite and sqlite3's timeout parameter.
-        **kwargs: Additional arguments (currently ignored, reserved for future use)
+        strict: Enable strict typing mode. Parameter binding refuses lossy
+            conversions (NaN floats, types without a SQLite equivalent), fetch
+            decodes by declared column type and raises DatabaseError on
+            mismatched values, and :meth:`Connection.create_table` emits STRICT
+            tables by default. Default: False.
+        cache: ``"lru"`` to cache :meth:`Connection.fetch_all` results for
+            read-heavy workloads. Writes made through rapsqlite invalidate the
+            results that read an affected table; commits by other connections
+            clear the cache. Default: None (no caching).
+        cache_size: Maximum number of cached results. Default: 128.
+        group_commit_window: Seconds (e.g. ``0.002``) during which concurrent
+            :meth:`Connection.execute` writes outside a transaction are
+            coalesced into one transaction, trading a little latency for much
+            higher write throughput. Each call returns once its group has
+            committed, and a failing statement only fails its own call.
+            Default: None (every write commits on its own).
+        debug: Enable debug-mode diagnostics. N+1 detection: a statement run
+            within one asyncio task with ``n_plus_one_threshold`` different
+            values for its single parameter emits :class:`NPlusOneWarning`.
+            Task-safety audit: :class:`TaskSafetyWarning` when tasks sharing
+            the connection interfere, e.g. one task commits another task's
+            transaction or changes a ``row_factory`` another task set.
+            Default: False.
+        n_plus_one_threshold: Distinct parameter values that trigger the
+            N+1 warning. Default: 10.
+        hardened: Deny ATTACH/DETACH, setting PRAGMAs and schema changes
+            (TEMP objects excepted) on every connection, as a tripwire for
+            applications that run semi-trusted SQL. Denied statements raise
+            :class:`DatabaseError`. PRAGMAs given in ``pragmas`` stay allowed.
+            Default: False.
+        hardened_allow: Exceptions for hardened mode: ``"attach"``,
+            ``"schema"`` and ``"pragma:<name>"`` entries. Default: None.
+        max_rows: Default row limit for :meth:`Connection.fetch_all`: a query
+            returning more rows is abandoned with :class:`ResultTooLarge`
+            instead of being read into memory. Default: None (no limit).
+        max_result_bytes: Default size limit for :meth:`Connection.fetch_all`,
+            counting the length of TEXT and BLOB values plus 8 bytes per
+            number. Default: None (no limit).
+        shared_pool: Share one connection pool with every other connection
+            opened with ``shared_pool=True`` on the same file and settings, so
+            ``pool_size`` caps their OS connections together. Default: False.
+        dedicated_runtime: Run this connection's work on a Tokio runtime of
+            its own instead of the process-wide one (see
+            :func:`configure_runtime`). Default: False.
+        detect_types: :data:`PARSE_DECLTYPES` and/or :data:`PARSE_COLNAMES`
+            (combined with ``|``) to pass fetched values through the
+            converters registered with :func:`register_converter`, selected
+            by declared column type or by a ``[type]`` suffix on the column
+            name, as in sqlite3. Default: 0 (no conversion).
+        native_functions: Register the Rust-implemented SQL functions
+            ``uuid4()``, ``sha256(X)``, ``md5(X)`` and
+            ``regexp_replace(X, pattern, replacement)`` on every pooled
+            connection. Default: False.
+        wal_archive: Directory to archive every commit into, for
+            point-in-time recovery with :func:`restore_to`. Switches the
+            database to WAL mode. Default: None.
+        replication_hook: Callable receiving a dict for each WAL event
+            (``"frames_written"`` after every commit, ``"checkpoint_start"``
+            and ``"checkpoint_finish"`` around checkpoints), each with the
+            WAL ``generation``, so replication tools need not poll the file
+            system. Runs on a database thread; keep it short. Switches the
+            database to WAL mode. Default: None.
+        auto_analyze: Run ``PRAGMA optimize`` when the pool opens, so tables
+            whose statistics are missing (new tables and indexes) or stale
+            are analyzed, with ``PRAGMA analysis_limit`` (400 unless set in
+            ``pragmas``) bounding the work. Default: False.
+        init_hook: Async callable run with the connection before its first
+            operation, as in :class:`Connection`. Default: None.
+        init_hook_scope: ``"once"`` to run ``init_hook`` once, or
+            ``"connection"`` to run it each time the pool opens a connection.
+            Default: ``"once"``.
+        clock: A :class:`FakeClock` read by the SQL date and time functions
+            instead of the system time, for reproducible tests. Default: None.
+        faults: A :class:`FaultInjector` whose busy errors, I/O errors and
+            latency apply to this connection's files, for testing retry logic
+            and timeout handling. Default: None.
+        read_pool_size: Open this many read-only connections next to the
+            connection pool, which becomes the writer. Queries run by the
+            ``fetch_*`` methods and :meth:`Connection.stream` that only read
+            (see :meth:`Connection.is_readonly_query`) use them, so under WAL
+            reads don't queue behind writes. Default: None (one pool).
+        write_pool_size: Size of the writer pool when ``read_pool_size`` is
+            set. Default: 1.
+        max_queue: When every pooled connection is in use and this many tasks
+            already wait for one, raise :class:`PoolTimeout` at once instead of
+            queuing another. Waiting tasks are served first come, first
+            served, and :meth:`Connection.queue_stats` reports how long they
+            wait. Default: None (no limit).
 
     Returns:
         Connection: An async SQLite connection object that can be used as an
-            async context manager. The connection uses lazy initialization -
-            the actual database connection pool is created on first use.
+            async context manager or awaited. The connection uses lazy initialization -
+            the actual database connection pool is created on first use, or
+            when the connection is awaited.
 
     Example:
         With timeout (aiosqlite compatibility)::
@@ -140,8 +511,16 @@ def connect(
             async with connect("example.db", timeout=10.0) as conn:
                 await conn.execute("CREATE TABLE test (id INTEGER PRIMARY KEY)")
 
+    Named connections:
+        ``path`` may be ``"name://<name>"`` to use a connection registered with
+        :func:`register`. The registered options apply to any argument not
+        passed; arguments passed explicitly take precedence, even when equal
+        to the default.
+
     Raises:
         ValueError: If the database path is invalid (empty or contains null bytes)
+            or names an unregistered connection
+        TypeError: If an argument is not one listed above
         OperationalError: If the database connection cannot be established
             (e.g., permission denied, disk full, etc.)
 
@@ -174,6 +553,15 @@ def connect(
             async with connect("file:example.db?mode=rwc") as conn:
                 await conn.execute("CREATE TABLE test (id INTEGER)")
 
+        Awaited, as with aiosqlite (the pool is opened right away, so a
+        database that cannot be opened fails here)::
+
+            conn = await connect("example.db")
+            try:
+                await conn.execute("CREATE TABLE test (id INTEGER)")
+            finally:
+                await conn.close()
+
     Note:
         The connection object supports async context manager protocol. It's
         recommended to use ``async with`` to ensure proper resource cleanup.
@@ -181,10 +569,81 @@ def connect(
         async performance.
 
     See Also:
-        :class:`Connection`: For more advanced connection options including
-        initialization hooks.
+        :class:`Connection`: The connection class these arguments are passed
+        to.
+    """
+    # Only arguments the caller passed; Connection supplies the defaults
+    arguments = {
+        name: value
+        for name, value in locals().items()
+        if name != "path" and value is not _UNSET
+    }
+    if path.startswith(_NAMED_PREFIX):
+        path, options = _resolve_named(path)
+        arguments = {**options, **arguments}
+    return Connection(path, **arguments)  # type: ignore[no-any-return]
+
+
+def _default_cache_dir() -> str:
+    base = _os.environ.get("XDG_CACHE_HOME") or _os.path.join(
+        _os.path.expanduser("~"), ".cache"
+    )
+    return _os.path.join(base, "rapsqlite")
+
+
+async def open_http(
+    url: str,
+    cache_dir: Optional[str] = None,
+    *,
+    max_age: Optional[float] = None,
+    region: Optional[str] = None,
+    endpoint_url: Optional[str] = None,
+    **options: Any,
+) -> "Connection":  # type: ignore[valid-type]
+    """Open a database published at a URL, read-only, through a local cache.
+
+    Lets a SQLite file served over HTTP(S) or from S3 be queried like an API:
+    the file is downloaded into ``cache_dir`` by :func:`fetch_cached` (only
+    again when it changed, checked with a conditional GET) and opened
+    read-only and immutable, so SQLite skips locking and change detection.
+
+    Args:
+        url: ``http://``, ``https://`` or ``s3://bucket/key`` URL of the
+            database. ``s3://`` requests are signed with the AWS credentials
+            from the environment.
+        cache_dir: Directory for cached copies. Default:
+            ``$XDG_CACHE_HOME/rapsqlite`` (``~/.cache/rapsqlite``).
+        max_age: Seconds during which a cached copy is used without asking
+            the server whether it changed. Default: None (always ask).
+        region: Bucket region for ``s3://`` URLs.
+        endpoint_url: S3-compatible endpoint for ``s3://`` URLs.
+        **options: Passed on to :func:`connect`, e.g. ``cache`` or ``max_rows``.
+
+    Returns:
+        A read-only :class:`Connection` on the cached copy. Call
+        ``open_http`` again to pick up a newer version; an open connection
+        keeps reading the version it was opened on.
+
+    Raises:
+        OperationalError: If the URL is not supported, the download fails and
+            nothing is cached, or the response is not an SQLite database.
+
+    Example::
+
+        conn = await rapsqlite.open_http("https://example.com/data/cities.db")
+        async with conn:
+            rows = await conn.fetch_all("SELECT name FROM cities LIMIT 10")
     """
-    return Connection(path, pragmas=pragmas, timeout=timeout)  # type: ignore[no-any-return]
+    path = await fetch_cached(
+        url,
+        cache_dir if cache_dir is not None else _default_cache_dir(),
+        max_age=max_age,
+        region=region,
+        endpoint_url=endpoint_url,
+    )
+    # Read by sqlx when the pool opens: never write, and trust that the file
+    # doesn't change (each cached version has its own name)
+    return connect(path + "?mode=ro&immutable=true", **options)
 
 
 # -----------------------------------------------------------------------------
@@ -258,6 +717,176 @@ Connection._iterdump_raw = _raw_iterdump  # type: ignore[attr-defined]
 Connection.iterdump = _iterdump  # type: ignore[assignment]
 
 
+async def _dump_encrypted(
+    self: "Connection",  # type: ignore[valid-type]
+    path: str,
+    key: bytes,
+) -> None:
+    """Write the SQL dump of the database to ``path``, encrypted under ``key``.
+
+    The dump is what :meth:`iterdump` yields, one statement per line, sealed
+    with XChaCha20-Poly1305 as :meth:`Connection.backup_encrypted` does.
+    Restore it with :func:`decrypt_file` and ``Cursor.executescript``.
+
+    Args:
+        self: The connection to dump.
+        path: File to write (replaced if it exists).
+        key: 32-byte symmetric key, e.g. from ``os.urandom(32)``.
+
+    Raises:
+        ValueError: If the key is not 32 bytes.
+        OperationalError: If dumping or writing fails.
+    """
+    lines = await _raw_iterdump(self)  # type: ignore[arg-type]
+    await write_encrypted(path, ("\n".join(lines) + "\n").encode(), key)
+
+
+Connection.dump_encrypted = _dump_encrypted  # type: ignore[attr-defined]
+
+
+async def _dump(
+    self: "Connection",  # type: ignore[valid-type]
+    path: str,
+    *,
+    compression: Optional[str] = None,
+) -> int:
+    """Write the SQL dump of the database to ``path``, optionally compressed.
+
+    The dump is what :meth:`iterdump` yields, one statement per line; restore
+    it with :meth:`import_dump`. Compression runs in Rust.
+
+    Args:
+        self: The connection to dump.
+        path: File to write (replaced if it exists).
+        compression: ``"gzip"``, ``"zstd"``, or None for plain SQL.
+
+    Returns:
+        The size of the written file in bytes.
+
+    Raises:
+        ValueError: If ``compression`` is not ``"gzip"``, ``"zstd"`` or None.
+        OperationalError: If dumping or writing fails.
+    """
+    lines = await _raw_iterdump(self)  # type: ignore[arg-type]
+    data = ("\n".join(lines) + "\n").encode()
+    return await write_compressed(path, data, compression)  # type: ignore[no-any-return]
+
+
+async def _atomically(
+    conn: "Connection",  # type: ignore[valid-type]
+    run: Callable[[], Any],
+) -> Any:
+    """Await ``run()`` in a transaction of its own, or in the caller's if one is open."""
+    if await conn.in_transaction():
+        return await run()
+    await conn.begin()
+    try:
+        result = await run()
+    except BaseException:
+        await conn.rollback()
+        raise
+    await conn.commit()
+    return result
+
+
+async def _import_dump(
+    self: "Connection",  # type: ignore[valid-type]
+    path: str,
+    *,
+    compression: Optional[str] = None,
+) -> None:
+    """Run the SQL script at ``path``, such as one written by :meth:`dump`.
+
+    The script runs in one transaction (the caller's, if one is open), so a
+    failing statement leaves the database unchanged. The ``BEGIN TRANSACTION;``
+    and ``COMMIT;`` lines that :meth:`iterdump` adds are skipped.
+
+    Args:
+        self: The connection to import into.
+        path: Script to run, plain or compressed.
+        compression: ``"gzip"`` or ``"zstd"`` to require that format, or None
+            to detect it from the file.
+
+    Raises:
+        ValueError: If ``compression`` is not ``"gzip"``, ``"zstd"`` or None.
+        OperationalError: If the file can't be read or a statement fails.
+    """
+    data = await read_compressed(path, compression)
+    lines = data.decode("utf-8").split("\n")
+    if lines and lines[0] == "BEGIN TRANSACTION;":
+        lines.pop(0)
+    while lines and lines[-1] in ("", "COMMIT;"):
+        lines.pop()
+    script = "\n".join(lines)
+    if script.strip():
+        await _atomically(self, lambda: self.execute_raw(script))  # type: ignore[attr-defined]
+
+
+async def _import_csv(
+    self: "Connection",  # type: ignore[valid-type]
+    path: str,
+    table: str,
+    *,
+    header: bool = True,
+    compression: Optional[str] = None,
+) -> int:
+    """Insert the records of a CSV file into an existing table.
+
+    Reads what :meth:`export_csv` writes (RFC 4180, as Python's ``csv``
+    module does). Values are inserted as text and converted by the columns'
+    type affinity; empty fields are inserted as empty strings. All rows are
+    inserted in one transaction (the caller's, if one is open).
+
+    Args:
+        self: The connection to import into.
+        path: CSV file, plain or compressed.
+        table: Table to insert into.
+        header: The first record names the columns to fill; without it, each
+            record fills the table's columns in order.
+        compression: ``"gzip"`` or ``"zstd"`` to require that format, or None
+            to detect it from the file.
+
+    Returns:
+        The number of rows inserted.
+
+    Raises:
+        ValueError: If ``compression`` is unknown or a record has the wrong
+            number of fields.
+        OperationalError: If the file can't be read or an insert fails.
+    """
+    data = await read_compressed(path, compression)
+    records = [
+        record
+        for record in _csv.reader(_io.StringIO(data.decode("utf-8"), newline=""))
+        if record
+    ]
+    if header and records:
+        columns: Optional[List[str]] = records.pop(0)
+    else:
+        columns = None
+    if not records:
+        return 0
+    width = len(columns) if columns is not None else len(records[0])
+    for number, record in enumerate(records, start=2 if header else 1):
+        if len(record) != width:
+            raise ValueError(
+                f"CSV record {number} has {len(record)} fields, expected {width}"
+            )
+    sql = f"INSERT INTO {_quote_identifier(table)} "
+    if columns is not None:
+        sql += "(" + ", ".join(_quote_identifier(c) for c in columns) + ") "
+    sql += "VALUES (" + ", ".join("?" * width) + ")"
+    await _atomically(
+        self, lambda: self.execute_many(sql, records)  # type: ignore[attr-defined]
+    )
+    return len(records)
+
+
+Connection.dump = _dump  # type: ignore[attr-defined]
+Connection.import_dump = _import_dump  # type: ignore[attr-defined]
+Connection.import_csv = _import_csv  # type: ignore[attr-defined]
+
+
 async def _backup(
     self: "Connection",  # type: ignore[valid-type]
     target: Any,
@@ -353,3 +982,1506 @@ async def _backup(
 
 Connection._backup_raw = _raw_backup  # type: ignore[attr-defined]
 Connection.backup = _backup  # type: ignore[assignment]
+
+
+# -----------------------------------------------------------------------------
+# Schema helpers
+# -----------------------------------------------------------------------------
+
+
+def _quote_identifier(name: str) -> str:
+    return '"' + name.replace('"', '""') + '"'
+
+
+async def _create_table(
+    self: "Connection",  # type: ignore[valid-type]
+    name: str,
+    columns: Dict[str, str],
+    *,
+    strict: Optional[bool] = None,
+    if_not_exists: bool = False,
+) -> None:
+    """Create a table from a mapping of column names to column definitions.
+
+    Args:
+        name: Table name (quoted automatically).
+        columns: Mapping of column name to its definition, e.g.
+            ``{"id": "INTEGER PRIMARY KEY", "name": "TEXT NOT NULL"}``.
+        strict: Emit a ``STRICT`` table (SQLite 3.37+). Defaults to the
+            connection's ``strict`` setting.
+        if_not_exists: Add ``IF NOT EXISTS`` to the statement.
+
+    Raises:
+        ValueError: If ``columns`` is empty.
+    """
+    if not columns:
+        raise ValueError("create_table requires at least one column")
+    if strict is None:
+        strict = self.strict  # type: ignore[attr-defined]
+    column_sql = ", ".join(
+        f"{_quote_identifier(col)} {definition}".rstrip()
+        for col, definition in columns.items()
+    )
+    sql = "CREATE TABLE "
+    if if_not_exists:
+        sql += "IF NOT EXISTS "
+    sql += f"{_quote_identifier(name)} ({column_sql})"
+    if strict:
+        sql += " STRICT"
+    await self.execute(sql)  # type: ignore[attr-defined]
+
+
+Connection.create_table = _create_table  # type: ignore[attr-defined]
+
+
+def _index_sql(part: Any, what: str) -> str:
+    """SQL text of an index column or ``where`` expression (``Sql`` or str)."""
+    if isinstance(part, Sql):
+        text, values = part.render()
+        if values:
+            raise ValueError(
+                f"index {what} cannot have bound values; write literals inline"
+            )
+        return str(text)
+    return str(part)
+
+
+async def _create_index(
+    self: "Connection",  # type: ignore[valid-type]
+    table: str,
+    columns_or_expressions: List[Any],
+    *,
+    name: Optional[str] = None,
+    unique: bool = False,
+    where: Any = None,
+    if_not_exists: bool = True,
+) -> str:
+    """Create an index, optionally on expressions or partial.
+
+    Args:
+        table: Table to index (quoted automatically).
+        columns_or_expressions: What to index, in order. A ``str`` is a column
+            name (quoted automatically); a :func:`sql` object is an
+            expression, e.g. ``sql("lower({col})").bind(col="email")`` or
+            ``sql('"created_at" DESC')``.
+        name: Index name (quoted automatically). Defaults to ``idx_<table>_``
+            followed by the indexed column names.
+        unique: Create a ``UNIQUE`` index.
+        where: Condition making a partial index, as SQL text or a
+            :func:`sql` object.
+        if_not_exists: Add ``IF NOT EXISTS`` to the statement.
+
+    Returns:
+        The index name.
+
+    Raises:
+        ValueError: If ``columns_or_expressions`` is empty, or an expression
+            has bound values (SQLite can't bind parameters in a schema).
+
+    Example:
+        ::
+
+            await conn.create_index(
+                "users", [sql("lower(email)")], unique=True, where="deleted = 0"
+            )
+    """
+    if isinstance(columns_or_expressions, (str, Sql)):
+        columns_or_expressions = [columns_or_expressions]
+    if not columns_or_expressions:
+        raise ValueError("create_index requires at least one column or expression")
+    parts = []
+    for part in columns_or_expressions:
+        if isinstance(part, str):
+            parts.append(_quote_identifier(part))
+        else:
+            parts.append(_index_sql(part, "expressions"))
+    if name is None:
+        words = [
+            "".join(c if c.isalnum() else "_" for c in _index_sql(part, "expressions"))
+            for part in columns_or_expressions
+        ]
+        name = "_".join(["idx", table, *(w.strip("_") for w in words)])
+    sql_text = "CREATE UNIQUE INDEX " if unique else "CREATE INDEX "
+    if if_not_exists:
+        sql_text += "IF NOT EXISTS "
+    sql_text += (
+        f"{_quote_identifier(name)} ON {_quote_identifier(table)} ({', '.join(parts)})"
+    )
+    if where is not None:
+        sql_text += f" WHERE {_index_sql(where, 'conditions')}"
+    await self.execute(sql_text)  # type: ignore[attr-defined]
+    return name
+
+
+async def _drop_index(
+    self: "Connection",  # type: ignore[valid-type]
+    name: str,
+    *,
+    if_exists: bool = True,
+) -> None:
+    """Drop the index ``name`` (quoted automatically).
+
+    Args:
+        name: Index name, as returned by :meth:`create_index`.
+        if_exists: Add ``IF EXISTS`` to the statement.
+    """
+    sql_text = "DROP INDEX IF EXISTS " if if_exists else "DROP INDEX "
+    await self.execute(sql_text + _quote_identifier(name))  # type: ignore[attr-defined]
+
+
+Connection.create_index = _create_index  # type: ignore[attr-defined]
+Connection.drop_index = _drop_index  # type: ignore[attr-defined]
+
+
+# Words that end a column's type name in a column definition
+_COLUMN_CONSTRAINT_WORDS = frozenset(
+    (
+        "CONSTRAINT",
+        "PRIMARY",
+        "NOT",
+        "NULL",
+        "UNIQUE",
+        "CHECK",
+        "DEFAULT",
+        "COLLATE",
+        "REFERENCES",
+        "GENERATED",
+        "AS",
+    )
+)
+_TABLE_CONSTRAINT_WORDS = frozenset(
+    ("CONSTRAINT", "PRIMARY", "UNIQUE", "CHECK", "FOREIGN")
+)
+
+
+def _is_word_char(c: str) -> bool:
+    return c.isalnum() or c in "_$" or ord(c) > 127
+
+
+def _sql_token_end(text: str, i: int) -> int:
+    """End of the SQL token starting at ``text[i]``; a parenthesized group,
+    a quoted name or string, or a comment is one token."""
+    c = text[i]
+    if text.startswith("--", i):
+        end = text.find("\n", i)
+        return len(text) if end < 0 else end
+    if text.startswith("/*", i):
+        end = text.find("*/", i + 2)
+        return len(text) if end < 0 else end + 2
+    if c in "'\"`[":
+        close = "]" if c == "[" else c
+        j = i + 1
+        while j < len(text):
+            if text[j] == close:
+                if close != "]" and text[j + 1 : j + 2] == close:
+                    j += 2
+                    continue
+                return j + 1
+            j += 1
+        return j
+    if c == "(":
+        j = i + 1
+        while j < len(text) and text[j] != ")":
+            j = j + 1 if text[j].isspace() else _sql_token_end(text, j)
+        return j + 1
+    if _is_word_char(c):
+        j = i
+        while j < len(text) and _is_word_char(text[j]):
+            j += 1
+        return j
+    return i + 1
+
+
+def _sql_tokens(text: str) -> List[Tuple[int, int]]:
+    """``(start, end)`` of the top-level tokens of ``text``, without comments."""
+    tokens = []
+    i = 0
+    while i < len(text):
+        if text[i].isspace():
+            i += 1
+            continue
+        end = _sql_token_end(text, i)
+        if not text.startswith(("--", "/*"), i):
+            tokens.append((i, end))
+        i = end
+    return tokens
+
+
+def _unquote_identifier(token: str) -> str:
+    if token[:1] in ("'", '"', "`"):
+        return token[1:-1].replace(token[0] * 2, token[0])
+    if token[:1] == "[":
+        return token[1:-1]
+    return token
+
+
+def _rebuilt_table_sql(
+    create_sql: str,
+    new_name: str,
+    add: List[str],
+    drop: List[str],
+    retype: Dict[str, str],
+) -> Tuple[str, List[str]]:
+    """``CREATE TABLE`` statement for ``new_name`` with the columns changed,
+    and the names of the columns to copy into it."""
+    tokens = _sql_tokens(create_sql)
+    body = next(
+        ((start, end) for start, end in tokens if create_sql[start] == "("), None
+    )
+    if body is None:
+        raise OperationalError("alter_table could not parse the table definition")
+    text = create_sql[body[0] + 1 : body[1] - 1]
+
+    # Split the body at top-level commas
+    elements: List[str] = []
+    start = 0
+    for token_start, token_end in _sql_tokens(text):
+        if text[token_start:token_end] == ",":
+            elements.append(text[start:token_start].strip())
+            start = token_end
+    elements.append(text[start:].strip())
+
+    columns: List[str] = []
+    constraints: List[str] = []
+    copied: List[str] = []
+    drop_names = {name.lower() for name in drop}
+    retypes = {name.lower(): type_ for name, type_ in retype.items()}
+    found = set()
+    for element in elements:
+        element_tokens = _sql_tokens(element)
+        words = [element[s:e] for s, e in element_tokens]
+        if words[0].upper() in _TABLE_CONSTRAINT_WORDS:
+            constraints.append(element)
+            continue
+        name = _unquote_identifier(words[0])
+        found.add(name.lower())
+        if name.lower() in drop_names:
+            continue
+        if name.lower() in retypes:
+            # The type name is the words (and size in parentheses) after the name
+            type_end = element_tokens[0][1]
+            for (s, e), word in zip(element_tokens[1:], words[1:]):
+                if word.upper() in _COLUMN_CONSTRAINT_WORDS:
+                    break
+                type_end = e
+                if word.startswith("("):
+                    break
+            element = (
+                f"{element[: element_tokens[0][1]]} {retypes[name.lower()]}"
+                f"{element[type_end:]}"
+            )
+        # Generated columns can't be inserted into
+        if not any(word.upper() in ("GENERATED", "AS") for word in words[1:]):
+            copied.append(name)
+        columns.append(element)
+
+    missing = [name for name in [*drop, *retype] if name.lower() not in found]
+    if missing:
+        raise OperationalError(f"no such column: {missing[0]}")
+    columns.extend(add)
+    definition = ",\n  ".join(columns + constraints)
+    return (
+        f"CREATE TABLE {_quote_identifier(new_name)} (\n  {definition}\n)"
+        f"{create_sql[body[1]:]}",
+        copied,
+    )
+
+
+async def _alter_table(
+    self: "Connection",  # type: ignore[valid-type]
+    table: str,
+    *,
+    add: Optional[List[str]] = None,
+    drop: Optional[List[str]] = None,
+    rename: Optional[Dict[str, str]] = None,
+    retype: Optional[Dict[str, str]] = None,
+) -> None:
+    """Change the columns of ``table`` in one transaction, beyond what
+    ``ALTER TABLE`` supports.
+
+    Columns are renamed with ``ALTER TABLE ... RENAME COLUMN``, which also
+    updates the indexes, triggers and views using them. Adding, dropping and
+    retyping columns follow SQLite's 12-step procedure for other schema
+    changes: foreign key enforcement is switched off, a new table is created
+    from the table's definition with the changes applied (keeping its
+    constraints and table options), the rows are copied, the old table is
+    dropped and the new one renamed in its place, then the table's indexes
+    and triggers are recreated and ``PRAGMA foreign_key_check`` verifies the
+    result. Foreign keys of other tables referencing ``table`` keep working.
+
+    Any failure, e.g. an index or constraint using a dropped column, rolls
+    everything back.
+
+    Args:
+        table: Table to change (in the ``main`` schema).
+        add: Column definitions to add, e.g. ``["age INTEGER DEFAULT 0"]``.
+        drop: Names of the columns to drop.
+        rename: Mapping of old column names to new ones.
+        retype: Mapping of column names to their new type, e.g.
+            ``{"price": "REAL"}``. Values are converted by the new column's
+            affinity when copied.
+
+    Raises:
+        OperationalError: If a transaction is active, or ``table`` or a
+            column to drop or retype does not exist.
+        IntegrityError: If the changed table violates a foreign key.
+
+    Example:
+        ::
+
+            await conn.alter_table(
+                "users",
+                add=["created_at TEXT"],
+                drop=["legacy_flag"],
+                rename={"mail": "email"},
+                retype={"age": "INTEGER"},
+            )
+    """
+    conn = self
+    if await conn.in_transaction():  # type: ignore[attr-defined]
+        raise OperationalError("alter_table cannot be used inside a transaction")
+    add, drop = list(add or []), list(drop or [])
+    rename, retype = dict(rename or {}), dict(retype or {})
+    rebuild = bool(add or drop or retype)
+
+    # Takes effect only outside a transaction, so switch it off before BEGIN
+    restore_foreign_keys = False
+    enforced = await conn.fetch_one("PRAGMA foreign_keys")  # type: ignore[attr-defined]
+    if rebuild and enforced[0]:
+        await conn.set_pragma("foreign_keys", 0)  # type: ignore[attr-defined]
+        restore_foreign_keys = True
+    try:
+        await conn.begin()  # type: ignore[attr-defined]
+        try:
+            row = await conn.fetch_optional(  # type: ignore[attr-defined]
+                "SELECT name FROM sqlite_master WHERE type = 'table' AND name = ? "
+                "COLLATE NOCASE",
+                [table],
+            )
+            if row is None:
+                raise OperationalError(f"no such table: {table}")
+            table = str(row[0])
+            for old, new in rename.items():
+                await conn.execute(  # type: ignore[attr-defined]
+                    f"ALTER TABLE {_quote_identifier(table)} RENAME COLUMN "
+                    f"{_quote_identifier(old)} TO {_quote_identifier(new)}"
+                )
+            if rebuild:
+                await _rebuild_table(
+                    conn, table, add, drop, retype, restore_foreign_keys
+                )
+        except BaseException:
+            await conn.rollback()  # type: ignore[attr-defined]
+            raise
+        await conn.commit()  # type: ignore[attr-defined]
+    finally:
+        if restore_foreign_keys:
+            await conn.set_pragma("foreign_keys", 1)  # type: ignore[attr-defined]
+
+
+async def _rebuild_table(
+    conn: "Connection",  # type: ignore[valid-type]
+    table: str,
+    add: List[str],
+    drop: List[str],
+    retype: Dict[str, str],
+    check_foreign_keys: bool,
+) -> None:
+    """Steps 4-10 of the 12-step procedure, inside the caller's transaction."""
+    create_sql = (
+        await conn.fetch_one(  # type: ignore[attr-defined]
+            "SELECT sql FROM sqlite_master WHERE type = 'table' AND name = ?", [table]
+        )
+    )[0]
+    schema = await conn.fetch_all(  # type: ignore[attr-defined]
+        "SELECT sql FROM sqlite_master WHERE tbl_name = ? "
+        "AND type IN ('index', 'trigger') AND sql IS NOT NULL",
+        [table],
+    )
+    new_table = f"_rapsqlite_alter_{table}"
+    new_sql, copied = _rebuilt_table_sql(create_sql, new_table, add, drop, retype)
+    await conn.execute(new_sql)  # type: ignore[attr-defined]
+    column_sql = ", ".join(_quote_identifier(name) for name in copied)
+    await conn.execute(  # type: ignore[attr-defined]
+        f"INSERT INTO {_quote_identifier(new_table)} ({column_sql}) "
+        f"SELECT {column_sql} FROM {_quote_identifier(table)}"
+    )
+    await conn.execute(  # type: ignore[attr-defined]
+        f"DROP TABLE {_quote_identifier(table)}"
+    )
+    # Without it, the rename would check (and fail on) views using the table
+    await conn.execute("PRAGMA legacy_alter_table = ON")  # type: ignore[attr-defined]
+    try:
+        await conn.execute(  # type: ignore[attr-defined]
+            f"ALTER TABLE {_quote_identifier(new_table)} RENAME TO "
+            f"{_quote_identifier(table)}"
+        )
+    finally:
+        await conn.execute(  # type: ignore[attr-defined]
+            "PRAGMA legacy_alter_table = OFF"
+        )
+    for (sql_text,) in schema:
+        await conn.execute(sql_text)  # type: ignore[attr-defined]
+    if check_foreign_keys:
+        violations = await conn.fetch_all(  # type: ignore[attr-defined]
+            "SELECT \"table\", rowid, parent FROM pragma_foreign_key_check"
+        )
+        if violations:
+            child, rowid, parent = violations[0][0], violations[0][1], violations[0][2]
+            raise IntegrityError(
+                f"alter_table left {len(violations)} foreign key violation(s); "
+                f"first: {child!r} rowid {rowid} references missing {parent!r} row"
+            )
+
+
+Connection.alter_table = _alter_table  # type: ignore[attr-defined]
+
+
+class _BulkLoad:
+    """Async context manager returned by :meth:`Connection.bulk_load`."""
+
+    def __init__(
+        self,
+        conn: "Connection",  # type: ignore[valid-type]
+        table: str,
+    ) -> None:
+        self._conn = conn
+        self._table = table
+        self._indexes: List[Tuple[str, str]] = []
+        self._restore_foreign_keys = False
+
+    async def __aenter__(self) -> "Connection":  # type: ignore[valid-type]
+        conn = self._conn
+        if await conn.in_transaction():
+            raise OperationalError("bulk_load cannot be used inside a transaction")
+        exists = await conn.fetch_optional(
+            "SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ? "
+            "COLLATE NOCASE",
+            [self._table],
+        )
+        if exists is None:
+            raise OperationalError(f"no such table: {self._table}")
+
+        # Takes effect only outside a transaction, so switch it off before BEGIN
+        if (await conn.fetch_one("PRAGMA foreign_keys"))[0]:
+            await conn.set_pragma("foreign_keys", 0)
+            self._restore_foreign_keys = True
+        try:
+            await conn.begin()
+            # Covers a pooled connection that still enforces foreign keys
+            await conn.execute("PRAGMA defer_foreign_keys = ON")
+            # Indexes without SQL back UNIQUE/PRIMARY KEY constraints and stay
+            rows = await conn.fetch_all(
+                "SELECT name, sql FROM sqlite_master WHERE type = 'index' "
+                "AND tbl_name = ? COLLATE NOCASE AND sql IS NOT NULL",
+                [self._table],
+            )
+            self._indexes = [(str(row[0]), str(row[1])) for row in rows]
+            for name, _ in self._indexes:
+                await conn.execute(f"DROP INDEX {_quote_identifier(name)}")
+        except BaseException:
+            await self._finish(commit=False)
+            raise
+        return conn
+
+    async def __aexit__(
+        self,
+        exc_type: Optional[type],
+        exc_val: Optional[BaseException],
+        exc_tb: Any,
+    ) -> bool:
+        if exc_type is not None:
+            await self._finish(commit=False)
+            return False
+        conn = self._conn
+        try:
+            for _, sql in self._indexes:
+                await conn.execute(sql)
+            violations = await conn.fetch_all(
+                'SELECT rowid, parent FROM pragma_foreign_key_check(?)',
+                [self._table],
+            )
+            if violations:
+                rowid, parent = violations[0][0], violations[0][1]
+                raise IntegrityError(
+                    f"bulk_load left {len(violations)} foreign key violation(s) in "
+                    f"{self._table!r}; first: rowid {rowid} references missing "
+                    f"{parent!r} row"
+                )
+        except BaseException:
+            await self._finish(commit=False)
+            raise
+        await self._finish(commit=True)
+        return False
+
+    async def _finish(self, commit: bool) -> None:
+        conn = self._conn
+        try:
+            if await conn.in_transaction():
+                await (conn.commit() if commit else conn.rollback())
+        finally:
+            if self._restore_foreign_keys:
+                self._restore_foreign_keys = False
+                await conn.set_pragma("foreign_keys", 1)
+
+
+def _bulk_load(
+    self: "Connection",  # type: ignore[valid-type]
+    table: str,
+) -> _BulkLoad:
+    """Load rows into ``table`` without per-row index and foreign key work.
+
+    Automates the classic fast-load recipe: on entry the table's secondary
+    indexes are dropped (their DDL is recorded) and foreign key enforcement is
+    switched off; on exit the indexes are rebuilt from the recorded DDL,
+    ``PRAGMA foreign_key_check`` verifies the loaded rows and enforcement is
+    restored. Everything happens in a single transaction, so an exception, a
+    failed verification or a crash rolls back the load *and* the index drops:
+    the table is never left without its indexes.
+
+    Indexes backing ``UNIQUE`` and ``PRIMARY KEY`` constraints cannot be
+    dropped and are maintained as usual.
+
+    Args:
+        table: Table to load (in the ``main`` schema).
+
+    Returns:
+        An async context manager yielding the connection; run the inserts
+        inside it.
+
+    Raises:
+        OperationalError: On entry, if ``table`` does not exist or a
+            transaction is already active.
+        IntegrityError: On exit, if the loaded rows violate a ``UNIQUE``
+            index or a foreign key. The load is rolled back.
+
+    Example:
+        ::
+
+            async with conn.bulk_load("events"):
+                await conn.execute_many(
+                    "INSERT INTO events (user_id, kind) VALUES (?, ?)", rows
+                )
+    """
+    return _BulkLoad(self, table)
+
+
+Connection.bulk_load = _bulk_load  # type: ignore[attr-defined]
+
+
+async def _delete_cascade(
+    self: "Connection",  # type: ignore[valid-type]
+    table: str,
+    where: str,
+    params: Any = None,
+) -> Dict[str, int]:
+    """Delete the rows of ``table`` matching ``where`` and, children first,
+    every row that references them through foreign keys.
+
+    For schemas where ``ON DELETE CASCADE`` is missing or foreign key
+    enforcement is off. The tables referencing ``table`` (directly or through
+    other tables) are found with :meth:`get_foreign_keys`, and the dependent
+    rows are deleted deepest first, so enforced ``NO ACTION``/``RESTRICT``
+    keys are satisfied. Everything runs in one transaction: the enclosing one
+    if a transaction is active, otherwise a new one that is committed, or
+    rolled back on error.
+
+    Foreign keys declared ``ON DELETE SET NULL`` or ``SET DEFAULT`` are left
+    to SQLite: the rows referencing through them are kept.
+
+    Args:
+        table: Table to delete from.
+        where: SQL condition selecting the rows of ``table`` to delete.
+        params: Parameters for ``where`` (positional or named).
+
+    Returns:
+        A dict mapping each table to the number of rows deleted from it, in
+        deletion order (``table`` last).
+
+    Raises:
+        OperationalError: If the foreign keys form a cycle (including a table
+            referencing itself), which has no children-first order.
+
+    Example:
+        ::
+
+            deleted = await conn.delete_cascade("users", "id = ?", [42])
+            # {"comments": 7, "posts": 3, "users": 1}
+    """
+    children: Dict[str, List[Tuple[str, List[str], List[str]]]] = {}
+    for child in await self.get_tables():  # type: ignore[attr-defined]
+        keys: Dict[int, List[Dict[str, Any]]] = {}
+        for fk in await self.get_foreign_keys(child):  # type: ignore[attr-defined]
+            keys.setdefault(fk["id"], []).append(fk)
+        for columns in keys.values():
+            columns.sort(key=lambda fk: fk["seq"])
+            if columns[0].get("on_delete", "").upper() in ("SET NULL", "SET DEFAULT"):
+                continue
+            parent = columns[0]["table"]
+            to_columns = [fk.get("to") or "" for fk in columns]
+            if not all(to_columns):
+                # A key without columns references the parent's primary key
+                rows = await self.fetch_all(  # type: ignore[attr-defined]
+                    "SELECT name FROM pragma_table_info(?) WHERE pk > 0 ORDER BY pk",
+                    [parent],
+                )
+                to_columns = [str(row[0]) for row in rows]
+            from_columns = [fk["from"] for fk in columns]
+            children.setdefault(parent.lower(), []).append(
+                (child, from_columns, to_columns)
+            )
+
+    # (table, condition) pairs, deepest dependents first
+    plan: List[Tuple[str, str]] = []
+
+    def visit(name: str, condition: str, path: List[str]) -> None:
+        for child, from_columns, to_columns in children.get(name.lower(), []):
+            if child.lower() in path:
+                cycle = " -> ".join([*path, child.lower()])
+                raise OperationalError(
+                    f"delete_cascade cannot order cyclic foreign keys: {cycle}"
+                )
+            from_sql = ", ".join(_quote_identifier(col) for col in from_columns)
+            to_sql = ", ".join(_quote_identifier(col) for col in to_columns)
+            child_condition = (
+                f"({from_sql}) IN (SELECT {to_sql} FROM "
+                f"{_quote_identifier(name)} WHERE {condition})"
+            )
+            visit(child, child_condition, [*path, child.lower()])
+        plan.append((name, condition))
+
+    visit(table, where, [table.lower()])
+
+    own_transaction = not await self.in_transaction()  # type: ignore[attr-defined]
+    if own_transaction:
+        await self.begin()  # type: ignore[attr-defined]
+    deleted: Dict[str, int] = {}
+    try:
+        for name, condition in plan:
+            cursor = await self.execute(  # type: ignore[attr-defined]
+                f"DELETE FROM {_quote_identifier(name)} WHERE {condition}", params
+            )
+            deleted[name] = deleted.get(name, 0) + cursor.rowcount
+    except BaseException:
+        if own_transaction:
+            await self.rollback()  # type: ignore[attr-defined]
+        raise
+    if own_transaction:
+        await self.commit()  # type: ignore[attr-defined]
+    return deleted
+
+
+Connection.delete_cascade = _delete_cascade  # type: ignore[attr-defined]
+
+
+async def _load_fixtures(
+    self: "Connection",  # type: ignore[valid-type]
+    fixtures: Dict[str, List[Dict[str, Any]]],
+    *,
+    truncate: bool = False,
+) -> Dict[str, int]:
+    """Insert fixture rows, e.g. to set up a database for a test.
+
+    Tables are loaded parents first, following their foreign keys (found with
+    :meth:`get_foreign_keys`), and foreign key checks are deferred to the end
+    (see :meth:`defer_foreign_keys`), so rows may reference rows of the same
+    table or of a later one. Rows with the same columns are inserted with one
+    :meth:`execute_many` call. Everything runs in one transaction: the
+    enclosing one if a transaction is active, otherwise a new one that is
+    committed, or rolled back on error.
+
+    Args:
+        fixtures: Mapping of table names to rows, each a dict of column names
+            (quoted automatically) to values; columns left out get their
+            defaults.
+        truncate: First delete every row of the fixture tables (children
+            first) and reset their ``AUTOINCREMENT`` counters.
+
+    Returns:
+        A dict mapping each table to the number of rows inserted, in load
+        order.
+
+    Raises:
+        IntegrityError: If the rows violate a constraint or leave a foreign
+            key violation.
+
+    Example:
+        ::
+
+            await conn.load_fixtures(
+                {
+                    "users": [{"id": 1, "name": "alice"}],
+                    "posts": [{"user_id": 1, "title": "hello"}],
+                },
+                truncate=True,
+            )
+    """
+    # Parents of each fixture table among the fixture tables
+    names = {name.lower(): name for name in fixtures}
+    parents: Dict[str, List[str]] = {}
+    for name in fixtures:
+        parents[name] = []
+        for fk in await self.get_foreign_keys(name):  # type: ignore[attr-defined]
+            parent = names.get(fk["table"].lower())
+            if parent is not None and parent != name and parent not in parents[name]:
+                parents[name].append(parent)
+
+    # Parents first, in the given order otherwise; cycles keep the given order
+    order: List[str] = []
+    visiting: List[str] = []
+
+    def visit(name: str) -> None:
+        if name in order or name in visiting:
+            return
+        visiting.append(name)
+        for parent in parents[name]:
+            visit(parent)
+        visiting.remove(name)
+        order.append(name)
+
+    for name in fixtures:
+        visit(name)
+
+    async def load() -> Dict[str, int]:
+        if truncate:
+            for name in reversed(order):
+                await self.execute(  # type: ignore[attr-defined]
+                    f"DELETE FROM {_quote_identifier(name)}"
+                )
+            has_sequence = await self.fetch_optional(  # type: ignore[attr-defined]
+                "SELECT 1 FROM sqlite_master WHERE name = 'sqlite_sequence'"
+            )
+            if has_sequence is not None:
+                for name in order:
+                    await self.execute(  # type: ignore[attr-defined]
+                        "DELETE FROM sqlite_sequence WHERE name = ?", [name]
+                    )
+        inserted: Dict[str, int] = {}
+        for name in order:
+            groups: Dict[Tuple[str, ...], List[List[Any]]] = {}
+            for row in fixtures[name]:
+                groups.setdefault(tuple(row), []).append(list(row.values()))
+            for columns, values in groups.items():
+                if columns:
+                    column_sql = ", ".join(_quote_identifier(col) for col in columns)
+                    placeholders = ", ".join("?" for _ in columns)
+                    query = (
+                        f"INSERT INTO {_quote_identifier(name)} ({column_sql}) "
+                        f"VALUES ({placeholders})"
+                    )
+                    await self.execute_many(query, values)  # type: ignore[attr-defined]
+                else:
+                    for _ in values:
+                        await self.execute(  # type: ignore[attr-defined]
+                            f"INSERT INTO {_quote_identifier(name)} DEFAULT VALUES"
+                        )
+            inserted[name] = len(fixtures[name])
+        return inserted
+
+    if await self.in_transaction():  # type: ignore[attr-defined]
+        async with self.defer_foreign_keys():  # type: ignore[attr-defined]
+            return await load()
+    async with self.transaction():  # type: ignore[attr-defined]
+        async with self.defer_foreign_keys():  # type: ignore[attr-defined]
+            return await load()
+
+
+Connection.load_fixtures = _load_fixtures  # type: ignore[attr-defined]
+
+
+# -----------------------------------------------------------------------------
+# Query plan helpers
+# -----------------------------------------------------------------------------
+
+
+def _is_full_scan(detail: str) -> bool:
+    # "SCAN t" is a full table scan; "SCAN t USING [COVERING] INDEX i" walks an index
+    # and "SCAN CONSTANT ROW" (SELECT without FROM) reads nothing.
+    return (
+        detail.startswith("SCAN ")
+        and " USING " not in detail
+        and detail != "SCAN CONSTANT ROW"
+    )
+
+
+async def _explain_query_plan(
+    self: "Connection",  # type: ignore[valid-type]
+    query: str,
+    parameters: Any = None,
+) -> List[str]:
+    """Return the ``detail`` column of ``EXPLAIN QUERY PLAN`` for a query.
+
+    Args:
+        query: The query to explain (not executed).
+        parameters: Parameters for the query, as for :meth:`Connection.fetch_all`.
+
+    Returns:
+        One string per plan step, e.g.
+        ``["SEARCH users USING INDEX idx_email (email=?)"]``.
+    """
+    rows = await self.fetch_all(  # type: ignore[attr-defined]
+        "EXPLAIN QUERY PLAN " + query, parameters
+    )
+    return [str(row[-1]) for row in rows]
+
+
+async def _assert_index_used(
+    self: "Connection",  # type: ignore[valid-type]
+    query: str,
+    parameters: Any = None,
+    index_name: Optional[str] = None,
+) -> List[str]:
+    """Fail fast when a query's plan contains a full table scan.
+
+    Intended for performance tests: if a schema change drops an index the query
+    relies on, the plan degrades to ``SCAN <table>`` and this raises.
+
+    Args:
+        query: The query to check (not executed).
+        parameters: Parameters for the query, as for :meth:`Connection.fetch_all`.
+        index_name: If given, additionally require the plan to use this index.
+
+    Returns:
+        The plan steps (see :meth:`Connection.explain_query_plan`).
+
+    Raises:
+        AssertionError: If any step is a full table scan, or ``index_name`` is
+            given and no step uses it. The message includes the plan.
+    """
+    plan = await _explain_query_plan(self, query, parameters)
+    rendered = "\n".join("  " + detail for detail in plan)
+    scans = [detail for detail in plan if _is_full_scan(detail)]
+    if scans:
+        raise AssertionError(
+            f"Query plan contains a full table scan ({scans[0]}):\n{rendered}"
+        )
+    if index_name is not None and not any(
+        detail.endswith(f"INDEX {index_name}") or f"INDEX {index_name} (" in detail
+        for detail in plan
+    ):
+        raise AssertionError(
+            f"Query plan does not use index {index_name!r}:\n{rendered}"
+        )
+    return plan
+
+
+Connection.explain_query_plan = _explain_query_plan  # type: ignore[attr-defined]
+Connection.assert_index_used = _assert_index_used  # type: ignore[attr-defined]
+
+
+# -----------------------------------------------------------------------------
+# Row count helper
+# -----------------------------------------------------------------------------
+
+
+def _sql_words(sql: str) -> Iterator[Tuple[int, str, int, bool]]:
+    """Yield (position, word, paren depth, quoted) for identifiers and keywords.
+
+    String literals and comments are skipped; ``"x"``, `` `x` `` and ``[x]``
+    identifiers are yielded unquoted with ``quoted=True``.
+    """
+    depth = 0
+    i = 0
+    n = len(sql)
+    while i < n:
+        ch = sql[i]
+        if ch in "'\"`[":
+            close = "]" if ch == "[" else ch
+            start = i
+            i += 1
+            while i < n:
+                if sql[i] == close:
+                    # Doubled quote is an escaped quote inside the literal
+                    if close != "]" and i + 1 < n and sql[i + 1] == close:
+                        i += 2
+                        continue
+                    break
+                i += 1
+            i += 1
+            if ch != "'":
+                word = sql[start + 1 : i - 1]
+                if close != "]":
+                    word = word.replace(close * 2, close)
+                yield start, word, depth, True
+        elif sql.startswith("--", i):
+            end = sql.find("\n", i)
+            i = n if end == -1 else end + 1
+        elif sql.startswith("/*", i):
+            end = sql.find("*/", i + 2)
+            i = n if end == -1 else end + 2
+        elif ch == "(":
+            depth += 1
+            i += 1
+        elif ch == ")":
+            depth -= 1
+            i += 1
+        elif ch.isalpha() or ch == "_":
+            start = i
+            while i < n and (sql[i].isalnum() or sql[i] == "_"):
+                i += 1
+            yield start, sql[start:i], depth, False
+        else:
+            i += 1
+
+
+def _top_level_keywords(sql: str) -> List[Any]:
+    """Return (position, KEYWORD) for words outside parentheses, quotes and comments."""
+    return [
+        (pos, word.upper())
+        for pos, word, depth, quoted in _sql_words(sql)
+        if depth == 0 and not quoted
+    ]
+
+
+def _strip_order_by(sql: str) -> str:
+    """Drop a top-level ORDER BY clause, which cannot change a row count.
+
+    LIMIT/OFFSET after it are kept. The clause is left alone if it contains a
+    parameter placeholder, since removing it would shift parameter positions.
+    """
+    words = _top_level_keywords(sql)
+    for idx in range(len(words) - 1, 0, -1):
+        if words[idx - 1][1] == "ORDER" and words[idx][1] == "BY":
+            start = words[idx - 1][0]
+            end = next(
+                (pos for pos, word in words[idx + 1 :] if word == "LIMIT"), len(sql)
+            )
+            clause = sql[start:end]
+            if any(marker in clause for marker in ("?", ":", "@", "$")):
+                return sql
+            return sql[:start] + sql[end:]
+    return sql
+
+
+async def _count(
+    self: "Connection",  # type: ignore[valid-type]
+    query: str,
+    parameters: Any = None,
+) -> int:
+    """Count the rows a query would return without fetching them.
+
+    The query is wrapped as ``SELECT COUNT(*) FROM (<query>)`` and evaluated by
+    SQLite, so paginated UIs can fetch totals without transferring rows. A
+    top-level ``ORDER BY`` is stripped first since it only costs time; any
+    ``LIMIT``/``OFFSET`` is kept and bounds the count.
+
+    Args:
+        query: A SELECT (or VALUES / WITH ... SELECT) statement.
+        parameters: Parameters for the query, as for :meth:`Connection.fetch_one`.
+
+    Returns:
+        The number of rows the query returns.
+
+    Example:
+        ::
+
+            total = await conn.count(
+                "SELECT * FROM posts WHERE author = ? ORDER BY created", ["ann"]
+            )
+    """
+    inner = _strip_order_by(query.strip().rstrip(";").rstrip())
+    row = await self.fetch_one(  # type: ignore[attr-defined]
+        # Newlines keep a trailing "--" comment from swallowing the parenthesis
+        f"SELECT COUNT(*) FROM (\n{inner}\n)",
+        parameters,
+    )
+    return int(row[0])
+
+
+Connection.count = _count  # type: ignore[attr-defined]
+
+
+# -----------------------------------------------------------------------------
+# Schema dependency graph
+# -----------------------------------------------------------------------------
+
+
+async def _get_dependencies(
+    self: "Connection",  # type: ignore[valid-type]
+) -> Dict[str, Dict[str, Any]]:
+    """Map each schema object to the tables and views it references.
+
+    Indexes and triggers depend on the table they are attached to, views and
+    triggers on every table or view named in their SQL, and tables on the tables
+    their foreign keys point at. References are found by name, so a column or
+    alias that shares a table's name adds an edge; extra edges only make the
+    implied ordering more conservative.
+
+    Create objects after everything in their ``depends_on`` list and drop them
+    before it.
+
+    Returns:
+        ``{name: {"type": "table" | "view" | "index" | "trigger",
+        "depends_on": [names...]}}`` for every object in ``sqlite_master``
+        except SQLite's internal ``sqlite_*`` objects.
+    """
+    rows = await self.fetch_all(  # type: ignore[attr-defined]
+        "SELECT type, name, tbl_name, sql FROM sqlite_master "
+        "WHERE substr(name, 1, 7) <> 'sqlite_' ORDER BY rowid"
+    )
+    foreign_keys = await self.fetch_all(  # type: ignore[attr-defined]
+        'SELECT m.name, f."table" FROM sqlite_master AS m '
+        "JOIN pragma_foreign_key_list(m.name) AS f WHERE m.type = 'table'"
+    )
+    # SQLite resolves names case-insensitively
+    relations = {
+        str(row[1]).lower(): str(row[1]) for row in rows if row[0] in ("table", "view")
+    }
+
+    deps: Dict[str, set] = {}
+    types: Dict[str, str] = {}
+    for row in rows:
+        obj_type, name, table, sql = str(row[0]), str(row[1]), str(row[2]), row[3]
+        types[name] = obj_type
+        refs = deps.setdefault(name, set())
+        if obj_type in ("index", "trigger"):
+            refs.add(relations.get(table.lower(), table))
+        if obj_type in ("view", "trigger") and sql:
+            for _, word, _, _ in _sql_words(str(sql)):
+                if word.lower() in relations:
+                    refs.add(relations[word.lower()])
+    for row in foreign_keys:
+        target = relations.get(str(row[1]).lower())
+        if target is not None:
+            deps[str(row[0])].add(target)
+
+    return {
+        name: {"type": types[name], "depends_on": sorted(refs - {name})}
+        for name, refs in deps.items()
+    }
+
+
+Connection.get_dependencies = _get_dependencies  # type: ignore[attr-defined]
+
+
+# -----------------------------------------------------------------------------
+# Data diff
+# -----------------------------------------------------------------------------
+
+
+def _sqlite_order(key: Tuple[Any, ...]) -> Tuple[Any, ...]:
+    # SQLite sorts NULL < numbers < text < blobs; text under BINARY collation
+    # compares like Python str (code point order == UTF-8 byte order).
+    def rank(value: Any) -> Tuple[int, Any]:
+        if value is None:
+            return (0, 0)
+        if isinstance(value, (int, float)):
+            return (1, value)
+        if isinstance(value, str):
+            return (2, value)
+        return (3, bytes(value))
+
+    return tuple(rank(value) for value in key)
+
+
+async def _keyset_rows(
+    conn: "Connection",  # type: ignore[valid-type]
+    table: str,
+    keys: List[str],
+    batch_size: int,
+) -> AsyncIterator[Tuple[Tuple[Any, ...], List[Any]]]:
+    """Yield (key, row) in key order, fetching ``batch_size`` rows at a time."""
+    key_list = ", ".join(_quote_identifier(k) for k in keys)
+    select = f"SELECT {key_list}, * FROM {_quote_identifier(table)}"
+    order = f" ORDER BY {key_list} LIMIT {int(batch_size)}"
+    after = f" WHERE ({key_list}) > ({', '.join('?' * len(keys))})"
+    last: Optional[Tuple[Any, ...]] = None
+    while True:
+        if last is None:
+            rows = await conn.fetch_all(select + order)  # type: ignore[attr-defined]
+        else:
+            rows = await conn.fetch_all(  # type: ignore[attr-defined]
+                select + after + order, list(last)
+            )
+        for row in rows:
+            values = list(row)
+            last = tuple(values[: len(keys)])
+            if None in last:
+                raise ValueError(f"NULL key in {table!r}: {dict(zip(keys, last))}")
+            yield last, values[len(keys) :]
+        if len(rows) < batch_size:
+            return
+
+
+async def _anext_or_none(rows: AsyncIterator[Any]) -> Any:
+    try:
+        return await rows.__anext__()
+    except StopAsyncIteration:
+        return None
+
+
+async def diff_data(
+    conn_a: Connection,
+    conn_b: Connection,
+    table: str,
+    key_cols: Any,
+    *,
+    batch_size: int = 1000,
+) -> AsyncIterator[Tuple[str, Any]]:
+    """Compare a table across two databases, like ``sqldiff``.
+
+    Both sides are read in key order a page at a time (keyset pagination) and
+    merged, so memory use is bounded by ``batch_size`` regardless of table size.
+    Changes describe how to turn the table in ``conn_a`` into the one in
+    ``conn_b``. Rows are equal when every value and its storage class match.
+
+    Args:
+        conn_a: Connection to the original database.
+        conn_b: Connection to the database to compare against.
+        table: Table present in both databases with the same columns.
+        key_cols: Column name, or list of names, uniquely identifying a row
+            (typically the primary key). Keys must be non-NULL and use the
+            default BINARY collation.
+        batch_size: Rows fetched per query on each side.
+
+    Yields:
+        ``(change, key)`` tuples in key order, where ``change`` is
+        ``"inserted"`` (only in ``conn_b``), ``"updated"`` or ``"deleted"``
+        (only in ``conn_a``). ``key`` is a scalar when ``key_cols`` is a
+        string and a tuple otherwise.
+
+    Raises:
+        ValueError: If the table is missing or its columns differ between the
+            databases, ``key_cols`` is empty, or a key is NULL.
+
+    Example:
+        ::
+
+            async for change, key in rapsqlite.diff_data(old, new, "users", "id"):
+                print(change, key)
+    """
+    keys = [key_cols] if isinstance(key_cols, str) else list(key_cols)
+    if not keys:
+        raise ValueError("key_cols must name at least one column")
+    if batch_size < 1:
+        raise ValueError("batch_size must be positive")
+
+    columns_a = [c["name"] for c in await conn_a.get_table_info(table)]
+    columns_b = [c["name"] for c in await conn_b.get_table_info(table)]
+    if not columns_a or not columns_b:
+        raise ValueError(f"Table {table!r} does not exist in both databases")
+    if columns_a != columns_b:
+        raise ValueError(f"Columns of {table!r} differ: {columns_a} != {columns_b}")
+
+    def public(key: Tuple[Any, ...]) -> Any:
+        return key[0] if isinstance(key_cols, str) else key
+
+    def typed(row: List[Any]) -> List[Tuple[type, Any]]:
+        return [(type(value), value) for value in row]
+
+    rows_a = _keyset_rows(conn_a, table, keys, batch_size)
+    rows_b = _keyset_rows(conn_b, table, keys, batch_size)
+    a = await _anext_or_none(rows_a)
+    b = await _anext_or_none(rows_b)
+    while a is not None or b is not None:
+        a_first = a is not None and (
+            b is None or _sqlite_order(a[0]) < _sqlite_order(b[0])
+        )
+        if a_first:
+            yield "deleted", public(a[0])
+            a = await _anext_or_none(rows_a)
+        elif a is None or _sqlite_order(b[0]) < _sqlite_order(a[0]):
+            yield "inserted", public(b[0])
+            b = await _anext_or_none(rows_b)
+        else:
+            if typed(a[1]) != typed(b[1]):
+                yield "updated", public(a[0])
+            a = await _anext_or_none(rows_a)
+            b = await _anext_or_none(rows_b)
+
+
+# -----------------------------------------------------------------------------
+# Cross-process advisory locks
+# -----------------------------------------------------------------------------
+
+_LOCK_TABLE = "_rapsqlite_locks"
+
+
+class FileLock:
+    """Advisory lock shared by every process that opens the same database file.
+
+    Lets worker processes coordinate exclusive maintenance windows (VACUUM,
+    migrations) without an external lock service. The lock is a lease row in
+    the ``_rapsqlite_locks`` table, claimed with a single atomic upsert. While
+    held, a background task renews the lease; if the holder crashes, the lease
+    expires and the next process takes over.
+
+    The lock is advisory: it only excludes other ``FileLock`` holders with the
+    same ``name``, not ordinary readers and writers.
+
+    Args:
+        path: Database file to coordinate on.
+        name: Lock name; different names are independent locks.
+        lease: Seconds a lease stays valid without renewal. Pick a value longer
+            than any single statement that can block the renewal (a VACUUM holds
+            an exclusive lock on the file). Default: 60.0.
+        timeout: Seconds :meth:`acquire` waits before raising
+            ``OperationalError``; None waits forever, 0 tries once.
+        poll_interval: Seconds between attempts while waiting. Default: 0.1.
+
+    Example:
+        ::
+
+            async with FileLock("app.db", "vacuum", timeout=0):
+                async with connect("app.db") as conn:
+                    await conn.execute("VACUUM")
+    """
+
+    def __init__(
+        self,
+        path: str,
+        name: str = "default",
+        *,
+        lease: float = 60.0,
+        timeout: Optional[float] = None,
+        poll_interval: float = 0.1,
+    ) -> None:
+        if not lease > 0:
+            raise ValueError("lease must be > 0")
+        if timeout is not None and timeout < 0:
+            raise ValueError("timeout must be >= 0 or None")
+        self.path = path
+        self.name = name
+        self.lease = lease
+        self.timeout = timeout
+        self.poll_interval = poll_interval
+        self.owner = (
+            f"{_socket.gethostname()}:{_os.getpid()}:{_uuid.uuid4().hex[:12]}"
+        )
+        self._conn: Optional["Connection"] = None  # type: ignore[valid-type]
+        self._heartbeat: Optional["_asyncio.Task[None]"] = None
+
+    @property
+    def locked(self) -> bool:
+        """True while this instance holds the lock."""
+        return self._conn is not None
+
+    async def _claim(self, conn: "Connection") -> bool:  # type: ignore[valid-type]
+        now = _time.time()
+        cursor = await conn.execute(
+            f"INSERT INTO {_LOCK_TABLE} (name, owner, expires_at) VALUES (?, ?, ?) "
+            "ON CONFLICT(name) DO UPDATE SET owner = excluded.owner, "
+            "expires_at = excluded.expires_at WHERE expires_at < ?",
+            [self.name, self.owner, now + self.lease, now],
+        )
+        return bool(cursor.rowcount)
+
+    async def acquire(self) -> None:
+        """Wait for the lock and take it.
+
+        Raises:
+            OperationalError: If ``timeout`` elapses first, or this instance
+                already holds the lock.
+        """
+        if self._conn is not None:
+            raise OperationalError(f"Lock {self.name!r} is already held")
+        conn = Connection(self.path)
+        deadline = None if self.timeout is None else _time.monotonic() + self.timeout
+        try:
+            await conn.execute(
+                f"CREATE TABLE IF NOT EXISTS {_LOCK_TABLE} "
+                "(name TEXT PRIMARY KEY, owner TEXT NOT NULL, expires_at REAL NOT NULL)"
+            )
+            while not await self._claim(conn):
+                if deadline is not None and _time.monotonic() >= deadline:
+                    raise OperationalError(
+                        f"Timed out waiting for lock {self.name!r} on {self.path}"
+                    )
+                await _asyncio.sleep(self.poll_interval)
+        except BaseException:
+            await conn.close()
+            raise
+        self._conn = conn
+        self._heartbeat = _asyncio.create_task(self._renew(conn))
+
+    async def _renew(self, conn: "Connection") -> None:  # type: ignore[valid-type]
+        while True:
+            await _asyncio.sleep(self.lease / 3)
+            try:
+                await conn.execute(
+                    f"UPDATE {_LOCK_TABLE} SET expires_at = ? "
+                    "WHERE name = ? AND owner = ?",
+                    [_time.time() + self.lease, self.name, self.owner],
+                )
+            except OperationalError:
+                # Busy (e.g. during VACUUM); retry on the next beat
+                pass
+
+    async def release(self) -> None:
+        """Release the lock. Does nothing if it is not held."""
+        conn, self._conn = self._conn, None
+        if conn is None:
+            return
+        if self._heartbeat is not None:
+            self._heartbeat.cancel()
+            self._heartbeat = None
+        try:
+            await conn.execute(
+                f"DELETE FROM {_LOCK_TABLE} WHERE name = ? AND owner = ?",
+                [self.name, self.owner],
+            )
+        finally:
+            await conn.close()
+
+    async def __aenter__(self) -> "FileLock":
+        await self.acquire()
+        return self
+
+    async def __aexit__(self, exc_type: Any, exc_val: Any, exc_tb: Any) -> bool:
+        await self.release()
+        return False
+
+
+def with_write_lease(path: str, name: str = "write", **kwargs: Any) -> FileLock:
+    """Return a :class:`FileLock` for an exclusive maintenance window on ``path``.
+
+    Shorthand for ``FileLock(path, name, **kwargs)``; use it with ``async with``.
+    """
+    return FileLock(path, name, **kwargs)
+
+
+# -----------------------------------------------------------------------------
+# Multi-tenant routing
+# -----------------------------------------------------------------------------
+
+
+class _TenantConnection:
+    """Async context manager returned by :meth:`Router.for_tenant`."""
+
+    def __init__(self, router: "Router", path: str) -> None:
+        self._router = router
+        self._path = path
+
+    async def __aenter__(self) -> "Connection":  # type: ignore[valid-type]
+        conn = self._router._checkout(self._path)
+        await self._router._evict_idle()
+        return conn
+
+    async def __aexit__(self, exc_type: Any, exc_val: Any, exc_tb: Any) -> bool:
+        self._router._checkin(self._path)
+        await self._router._evict_idle()
+        return False
+
+
+class Router:
+    """Lazily opened connections to one SQLite file per tenant.
+
+    Centralizes the many-small-databases pattern: each tenant's file lives under
+    ``base_dir`` and its :class:`Connection` is opened on first use. At most
+    ``max_open`` connections stay open; past that, the least recently used
+    connections that are not in use are closed. A connection is in use while a
+    :meth:`for_tenant` block holds it, so busy tenants are never evicted (the
+    limit may be exceeded until they are released).
+
+    Args:
+        base_dir: Directory holding the tenant databases. Directories and
+            files for new tenants are created on first use.
+        naming: Maps a tenant id to a file name relative to ``base_dir``: a
+            format string with a ``{tenant}`` field, or a callable. Paths that
+            resolve outside ``base_dir`` are refused. Default: ``"{tenant}.db"``.
+        max_open: Number of connections kept open before idle ones are
+            closed. Default: 64.
+        **options: :func:`connect` options used for every tenant connection.
+
+    Example:
+        ::
+
+            router = Router("/data/tenants", pragmas={"journal_mode": "WAL"})
+
+            async with router.for_tenant(tenant_id) as conn:
+                rows = await conn.fetch_all("SELECT * FROM invoices")
+
+            await router.close()
+    """
+
+    def __init__(
+        self,
+        base_dir: str,
+        naming: Any = "{tenant}.db",
+        *,
+        max_open: int = 64,
+        **options: Any,
+    ) -> None:
+        if max_open < 1:
+            raise ValueError("max_open must be >= 1")
+        unknown = sorted(set(options) - set(_CONNECT_OPTIONS))
+        if unknown:
+            raise TypeError(f"Unknown connect() option(s): {', '.join(unknown)}")
+        self.base_dir = _os.path.abspath(base_dir)
+        self.naming = naming
+        self.max_open = max_open
+        self._options = options
+        # path -> [connection, active for_tenant blocks], least recently used first
+        self._open: Dict[str, List[Any]] = {}
+
+    def path_for(self, tenant_id: Any) -> str:
+        """Return the database path for ``tenant_id``.
+
+        Raises:
+            ValueError: If the name resolves outside ``base_dir``.
+        """
+        if callable(self.naming):
+            name = self.naming(tenant_id)
+        else:
+            name = self.naming.format(tenant=tenant_id)
+        path = _os.path.abspath(_os.path.join(self.base_dir, name))
+        inside = _os.path.commonpath([self.base_dir, path]) == self.base_dir
+        if not inside or path == self.base_dir:
+            raise ValueError(
+                f"Tenant {tenant_id!r} maps outside {self.base_dir}: {path}"
+            )
+        return path
+
+    def for_tenant(self, tenant_id: Any) -> _TenantConnection:
+        """Return an async context manager yielding ``tenant_id``'s connection.
+
+        Example:
+            ::
+
+                async with router.for_tenant("acme") as conn:
+                    await conn.execute("INSERT INTO events VALUES (?)", ["login"])
+        """
+        return _TenantConnection(self, self.path_for(tenant_id))
+
+    @property
+    def open_count(self) -> int:
+        """Number of tenant connections currently open."""
+        return len(self._open)
+
+    def _checkout(self, path: str) -> "Connection":  # type: ignore[valid-type]
+        entry = self._open.pop(path, None)
+        if entry is None:
+            # New tenants start with an empty file, which SQLite opens as an
+            # empty database (the pool does not create missing files)
+            _os.makedirs(_os.path.dirname(path), exist_ok=True)
+            open(path, "ab").close()
+            entry = [connect(path, **self._options), 0]
+        entry[1] += 1
+        self._open[path] = entry
+        return entry[0]
+
+    def _checkin(self, path: str) -> None:
+        entry = self._open.get(path)
+        if entry is not None:
+            entry[1] -= 1
+
+    async def _evict_idle(self) -> None:
+        excess = len(self._open) - self.max_open
+        idle = [path for path, (_, users) in self._open.items() if users == 0]
+        evicted = [self._open.pop(path)[0] for path in idle[: max(excess, 0)]]
+        for conn in evicted:
+            await conn.close()
+
+    async def close(self) -> None:
+        """Close every open tenant connection."""
+        conns = [conn for conn, _ in self._open.values()]
+        self._open.clear()
+        for conn in conns:
+            await conn.close()
+
+    async def __aenter__(self) -> "Router":
+        return self
+
+    async def __aexit__(self, exc_type: Any, exc_val: Any, exc_tb: Any) -> bool:
+        await self.close()
+        return False
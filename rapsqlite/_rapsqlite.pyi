@@ -3,7 +3,28 @@
 from __future__ import annotations
 
 import builtins
-from typing import Any, Callable, Coroutine, Dict, Iterator, List, Optional, Protocol, Type, TypeVar
+import datetime
+import os
+import sqlite3
+from typing import (
+    Any,
+    AsyncContextManager,
+    Awaitable,
+    Callable,
+    Coroutine,
+    Dict,
+    Iterable,
+    Iterator,
+    List,
+    Literal,
+    Mapping,
+    Optional,
+    Protocol,
+    Tuple,
+    Type,
+    TypeVar,
+    Union,
+)
 
 # Type alias for init_hook callback
 InitHook = Callable[["Connection"], Coroutine[Any, Any, None]]
@@ -32,10 +53,298 @@ class IntegrityError(DatabaseError):
     """Exception raised for integrity constraint violations."""
     def __init__(self, message: str) -> None: ...
 
+class NotSupportedError(DatabaseError):
+    """Exception raised when a feature needs SQLite compile options this build lacks."""
+    def __init__(self, message: str) -> None: ...
+
+class QueryAborted(OperationalError):
+    """Exception raised when a query is deliberately interrupted, e.g. by a progress handler returning False."""
+    def __init__(self, message: str) -> None: ...
+
+class DeadlineExceeded(QueryAborted):
+    """Exception raised when a statement runs past the deadline of a rapsqlite.deadline() block."""
+    def __init__(self, message: str) -> None: ...
+
+class ResultTooLarge(OperationalError):
+    """Exception raised when a fetch returns more than max_rows rows or max_result_bytes bytes."""
+    def __init__(self, message: str) -> None: ...
+
+class PoolTimeout(OperationalError):
+    """Exception raised when no pooled connection frees up within connection_timeout,
+    or max_queue tasks are already waiting for one."""
+
+    queue_depth: int
+    """Tasks waiting for a connection when the acquire failed."""
+    wait_time: float
+    """Seconds this task waited (0.0 when turned away by max_queue)."""
+    def __init__(self, message: str) -> None: ...
+
 class ValueError(builtins.ValueError):
     """Exception raised for invalid argument values."""
     def __init__(self, message: str) -> None: ...
 
+class NPlusOneWarning(UserWarning):
+    """Warning emitted in debug mode when a statement looks like an N+1 query pattern."""
+
+class TaskSafetyWarning(UserWarning):
+    """Warning emitted in debug mode when tasks sharing a Connection interfere."""
+
+def configure_runtime(
+    *,
+    worker_threads: Optional[int] = None,
+    thread_name_prefix: Optional[str] = None,
+    max_blocking_threads: Optional[int] = None,
+) -> None:
+    """Configure the Tokio runtime; must be called before the first Connection operation."""
+    ...
+
+PARSE_DECLTYPES: int
+PARSE_COLNAMES: int
+
+# SQLite result codes, authorizer results and actions, open flags, limit categories
+SQLITE_OK: int
+SQLITE_ERROR: int
+SQLITE_INTERNAL: int
+SQLITE_PERM: int
+SQLITE_ABORT: int
+SQLITE_BUSY: int
+SQLITE_LOCKED: int
+SQLITE_NOMEM: int
+SQLITE_READONLY: int
+SQLITE_INTERRUPT: int
+SQLITE_IOERR: int
+SQLITE_CORRUPT: int
+SQLITE_NOTFOUND: int
+SQLITE_FULL: int
+SQLITE_CANTOPEN: int
+SQLITE_PROTOCOL: int
+SQLITE_EMPTY: int
+SQLITE_SCHEMA: int
+SQLITE_TOOBIG: int
+SQLITE_CONSTRAINT: int
+SQLITE_MISMATCH: int
+SQLITE_MISUSE: int
+SQLITE_NOLFS: int
+SQLITE_AUTH: int
+SQLITE_FORMAT: int
+SQLITE_RANGE: int
+SQLITE_NOTADB: int
+SQLITE_NOTICE: int
+SQLITE_WARNING: int
+SQLITE_ROW: int
+SQLITE_DONE: int
+SQLITE_DENY: int
+SQLITE_IGNORE: int
+SQLITE_CREATE_INDEX: int
+SQLITE_CREATE_TABLE: int
+SQLITE_CREATE_TEMP_INDEX: int
+SQLITE_CREATE_TEMP_TABLE: int
+SQLITE_CREATE_TEMP_TRIGGER: int
+SQLITE_CREATE_TEMP_VIEW: int
+SQLITE_CREATE_TRIGGER: int
+SQLITE_CREATE_VIEW: int
+SQLITE_DELETE: int
+SQLITE_DROP_INDEX: int
+SQLITE_DROP_TABLE: int
+SQLITE_DROP_TEMP_INDEX: int
+SQLITE_DROP_TEMP_TABLE: int
+SQLITE_DROP_TEMP_TRIGGER: int
+SQLITE_DROP_TEMP_VIEW: int
+SQLITE_DROP_TRIGGER: int
+SQLITE_DROP_VIEW: int
+SQLITE_INSERT: int
+SQLITE_PRAGMA: int
+SQLITE_READ: int
+SQLITE_SELECT: int
+SQLITE_TRANSACTION: int
+SQLITE_UPDATE: int
+SQLITE_ATTACH: int
+SQLITE_DETACH: int
+SQLITE_ALTER_TABLE: int
+SQLITE_REINDEX: int
+SQLITE_ANALYZE: int
+SQLITE_CREATE_VTABLE: int
+SQLITE_DROP_VTABLE: int
+SQLITE_FUNCTION: int
+SQLITE_SAVEPOINT: int
+SQLITE_RECURSIVE: int
+SQLITE_OPEN_READONLY: int
+SQLITE_OPEN_READWRITE: int
+SQLITE_OPEN_CREATE: int
+SQLITE_OPEN_URI: int
+SQLITE_OPEN_MEMORY: int
+SQLITE_OPEN_NOMUTEX: int
+SQLITE_OPEN_FULLMUTEX: int
+SQLITE_OPEN_SHAREDCACHE: int
+SQLITE_OPEN_PRIVATECACHE: int
+SQLITE_OPEN_NOFOLLOW: int
+SQLITE_OPEN_EXRESCODE: int
+SQLITE_LIMIT_LENGTH: int
+SQLITE_LIMIT_SQL_LENGTH: int
+SQLITE_LIMIT_COLUMN: int
+SQLITE_LIMIT_EXPR_DEPTH: int
+SQLITE_LIMIT_COMPOUND_SELECT: int
+SQLITE_LIMIT_VDBE_OP: int
+SQLITE_LIMIT_FUNCTION_ARG: int
+SQLITE_LIMIT_ATTACHED: int
+SQLITE_LIMIT_LIKE_PATTERN_LENGTH: int
+SQLITE_LIMIT_VARIABLE_NUMBER: int
+SQLITE_LIMIT_TRIGGER_DEPTH: int
+SQLITE_LIMIT_WORKER_THREADS: int
+
+def register_converter(typename: str, converter: Callable[[bytes], Any]) -> None:
+    """Register a converter (bytes -> value) for a column type, used with detect_types."""
+    ...
+
+def restore_to(
+    path: str,
+    archive_dir: str,
+    timestamp: Optional[Union[float, datetime.datetime]] = None,
+) -> Coroutine[Any, Any, None]:
+    """Rebuild the database archived by Connection(wal_archive=archive_dir) at path as of timestamp."""
+    ...
+
+def write_encrypted(path: str, data: bytes, key: bytes) -> Coroutine[Any, Any, None]:
+    """Encrypt data under a 32-byte key into path, in the format of Connection.backup_encrypted()."""
+    ...
+
+def decrypt_file(path: str, dest: str, key: bytes) -> Coroutine[Any, Any, None]:
+    """Decrypt an encrypted backup or dump at path into dest; raises on a wrong key or tampering."""
+    ...
+
+def write_compressed(
+    path: str, data: bytes, compression: Optional[str] = None
+) -> Coroutine[Any, Any, int]:
+    """Write data to path compressed with "gzip" or "zstd" (as-is for None); returns the file size."""
+    ...
+
+def read_compressed(
+    path: str, compression: Optional[str] = None
+) -> Coroutine[Any, Any, bytes]:
+    """Read and decompress path; compression=None detects gzip/zstd from the file."""
+    ...
+
+def fetch_cached(
+    url: str,
+    cache_dir: str,
+    *,
+    max_age: Optional[float] = None,
+    region: Optional[str] = None,
+    endpoint_url: Optional[str] = None,
+) -> Coroutine[Any, Any, str]:
+    """Download the database at an http(s):// or s3:// URL into cache_dir (revalidated) and return its path."""
+    ...
+
+class Sql:
+    """SQL template with bound parameters, created by sql(). Pass it as the query to execute/fetch_*."""
+
+    def bind(self, **params: Any) -> "Sql":
+        """Copy with params bound: values for :name, identifiers for {name}."""
+        ...
+    def join(self, fragments: Iterable["Sql"]) -> "Sql":
+        """Join fragments with this template as the separator."""
+        ...
+    def render(self) -> Tuple[str, List[Any]]:
+        """SQL text with ? placeholders and the values to bind."""
+        ...
+    def __add__(self, other: "Sql") -> "Sql": ...
+
+def sql(template: str) -> Sql:
+    """Create a composable SQL template (:name values, lists expand for IN; {name} quoted identifiers)."""
+    ...
+
+def uri_filename(path: Union[str, os.PathLike[str]], **params: Any) -> str:
+    """Build an escaped file: URI, e.g. uri_filename(path, mode="ro", immutable=True)."""
+    ...
+
+def parse_uri(uri: str) -> Tuple[str, Dict[str, str]]:
+    """Split a database name into (path, query parameters), decoding percent-escapes."""
+    ...
+
+def deadline(seconds: float) -> "Deadline":
+    """Share a budget of ``seconds`` among the statements run inside ``async with``."""
+    ...
+
+class Deadline:
+    """Async context manager returned by rapsqlite.deadline()."""
+
+    @property
+    def remaining(self) -> float:
+        """Seconds left in the budget (0.0 once spent)."""
+        ...
+    def __aenter__(self) -> Coroutine[Any, Any, "Deadline"]: ...
+    def __aexit__(
+        self,
+        exc_type: Optional[Type[BaseException]],
+        exc_val: Optional[BaseException],
+        exc_tb: Optional[Any],
+    ) -> Coroutine[Any, Any, bool]: ...
+
+class PooledConnection:
+    """Pooled connection passed to a Connection.set_event_hook() hook."""
+
+    @property
+    def id(self) -> int:
+        """Identifies the pooled connection for the life of the process."""
+        ...
+    def execute(self, sql: str) -> Coroutine[Any, Any, int]:
+        """Run sql on this connection while the hook runs; returns rows changed."""
+        ...
+
+class FakeClock:
+    """Settable clock for the SQL date and time functions of Connection(clock=...)."""
+
+    def __init__(self, start: Union[float, datetime.datetime]) -> None:
+        """Start at start: seconds since the Unix epoch or a datetime (naive = UTC)."""
+        ...
+    @property
+    def now(self) -> float:
+        """Current time of the clock, in seconds since the Unix epoch."""
+        ...
+    def set(self, when: Union[float, datetime.datetime]) -> None:
+        """Set the clock to when (seconds since the Unix epoch or a datetime)."""
+        ...
+    def advance(self, seconds: float) -> None:
+        """Move the clock forward by seconds (backwards when negative)."""
+        ...
+
+class FaultInjector:
+    """On-demand SQLITE_BUSY, I/O errors and latency for Connection(faults=...).
+
+    Injectors share one set of faults; creating one clears them.
+    """
+
+    def __init__(self) -> None: ...
+    def busy(self, count: int = 1) -> None:
+        """Fail the next count write-lock attempts with SQLITE_BUSY (retried until busy_timeout)."""
+        ...
+    def io_error(self, op: Literal["read", "write", "sync"], count: int = 1) -> None:
+        """Fail the next count op operations with an I/O error (DatabaseError)."""
+        ...
+    def latency(
+        self,
+        seconds: float,
+        op: Optional[Literal["read", "write", "sync", "lock"]] = None,
+    ) -> None:
+        """Delay every op operation (all when None) by seconds until reset()."""
+        ...
+    def reset(self) -> None:
+        """Clear pending faults, latencies and the injected counts."""
+        ...
+    @property
+    def injected(self) -> Dict[str, int]:
+        """Failures injected so far by op ("read", "write", "sync", "lock")."""
+        ...
+
+class Pointer:
+    """Passes obj from a user-defined function to other functions as a pointer value."""
+
+    def __init__(self, obj: Any) -> None: ...
+    @property
+    def obj(self) -> Any:
+        """The wrapped object."""
+        ...
+
 _T_co = TypeVar("_T_co", covariant=True)
 
 class _AwaitableAsyncIterator(Protocol[_T_co]):
@@ -50,6 +359,21 @@ class _AwaitableAsyncIterator(Protocol[_T_co]):
     def __anext__(self) -> Coroutine[Any, Any, _T_co]: ...
     def __await__(self) -> Iterator[Any]: ...
 
+class _ExecuteResult(Protocol):
+    """Returned by Connection.execute(): await it or use `async with` for the
+    Cursor, or iterate the rows with `async for`."""
+
+    def __await__(self) -> Iterator[Any]: ...
+    def __aenter__(self) -> Coroutine[Any, Any, "Cursor"]: ...
+    def __aexit__(
+        self,
+        exc_type: Optional[Type[BaseException]],
+        exc_val: Optional[BaseException],
+        exc_tb: Optional[Any],
+    ) -> Coroutine[Any, Any, bool]: ...
+    def __aiter__(self) -> "_ExecuteResult": ...
+    def __anext__(self) -> Coroutine[Any, Any, Any]: ...
+
 class Connection:
     """Async SQLite connection."""
 
@@ -60,6 +384,29 @@ class Connection:
         pragmas: Optional[Dict[str, Any]] = None,
         init_hook: Optional[InitHook] = None,
         timeout: float = 5.0,
+        strict: bool = False,
+        cache: Optional[str] = None,
+        cache_size: int = 128,
+        group_commit_window: Optional[float] = None,
+        debug: bool = False,
+        n_plus_one_threshold: int = 10,
+        hardened: bool = False,
+        hardened_allow: Optional[List[str]] = None,
+        max_rows: Optional[int] = None,
+        max_result_bytes: Optional[int] = None,
+        shared_pool: bool = False,
+        dedicated_runtime: bool = False,
+        detect_types: int = 0,
+        native_functions: bool = False,
+        wal_archive: Optional[str] = None,
+        replication_hook: Optional[Callable[[Dict[str, Any]], None]] = None,
+        auto_analyze: bool = False,
+        init_hook_scope: Literal["once", "connection"] = "once",
+        clock: Optional["FakeClock"] = None,
+        faults: Optional["FaultInjector"] = None,
+        read_pool_size: Optional[int] = None,
+        write_pool_size: Optional[int] = None,
+        max_queue: Optional[int] = None,
     ) -> "Connection":
         """Create a new async SQLite connection.
         
@@ -73,6 +420,67 @@ class Connection:
                 process/thread before raising an error. Default: 5.0 seconds.
                 This sets SQLite's busy_timeout PRAGMA. Set to 0.0 to disable timeout.
                 This matches aiosqlite and sqlite3's timeout parameter.
+            strict: Enable strict typing mode: binding refuses lossy conversions and
+                fetch decodes by declared column type. Default: False.
+            cache: "lru" to cache fetch_all results. Writes through rapsqlite invalidate
+                results that read an affected table (including via views, triggers and
+                foreign key actions); commits by other connections clear the cache.
+                Queries inside a transaction bypass it. Default: None.
+            cache_size: Maximum number of cached results. Default: 128.
+            group_commit_window: Seconds (e.g. 0.002) during which concurrent execute()
+                writes outside a transaction are coalesced into one transaction by a
+                background writer. Each call returns once its group has committed; a
+                failing statement only fails its own call. Default: None.
+            debug: Enable debug-mode diagnostics (N+1 detection: NPlusOneWarning when a
+                statement runs in one task with n_plus_one_threshold different values
+                for its single parameter; task-safety audit: TaskSafetyWarning when
+                tasks sharing the connection interfere, including running statements
+                under a callback another task installed). Default: False.
+            n_plus_one_threshold: Distinct parameter values that trigger the N+1
+                warning. Default: 10.
+            hardened: Deny ATTACH/DETACH, setting PRAGMAs and schema changes (TEMP
+                objects excepted) unless allowed; denied statements raise DatabaseError.
+                PRAGMAs given in pragmas stay allowed. Default: False.
+            hardened_allow: Hardened-mode exceptions: "attach", "schema" and
+                "pragma:<name>". Default: None.
+            max_rows: Default row limit for fetch_all; a larger result raises
+                ResultTooLarge. Default: None.
+            max_result_bytes: Default result size limit for fetch_all (TEXT/BLOB
+                length plus 8 bytes per number). Default: None.
+            shared_pool: Share one pool (and pool_size cap) with other
+                shared_pool=True connections to the same file and settings.
+                Default: False.
+            dedicated_runtime: Run this connection's work on its own Tokio runtime
+                (one worker thread unless configure_runtime() sets worker_threads).
+                Default: False.
+            detect_types: PARSE_DECLTYPES and/or PARSE_COLNAMES to apply converters
+                registered with register_converter(), by declared column type or by a
+                "[type]" suffix on the column name. Default: 0.
+            native_functions: Register uuid4(), sha256(), md5() and regexp_replace(),
+                implemented in Rust, on every pooled connection. Default: False.
+            wal_archive: Directory to archive every commit into for restore_to();
+                switches the database to WAL mode. Default: None.
+            replication_hook: Callable receiving a dict per WAL event ("frames_written",
+                "checkpoint_start", "checkpoint_finish", each with the WAL "generation")
+                on a database thread; switches the database to WAL mode. Default: None.
+            auto_analyze: Run PRAGMA optimize when the pool opens, analyzing tables whose
+                statistics are missing or stale, bounded by PRAGMA analysis_limit (400
+                unless set in pragmas). Default: False.
+            init_hook_scope: "once" runs init_hook once per Connection; "connection" runs it
+                on every new pooled connection, passing a PooledConnection, for
+                per-connection setup (temp tables, PRAGMAs). Default: "once".
+            clock: FakeClock read by datetime('now'), unixepoch(), CURRENT_TIMESTAMP
+                and the other date and time functions instead of the system time.
+                Default: None.
+            faults: FaultInjector whose SQLITE_BUSY, I/O errors and latency apply to
+                this connection's database, journal and WAL files. Default: None.
+            read_pool_size: Open this many read-only connections next to the pool,
+                which becomes the writer; fetch_all/fetch_one/fetch_optional/
+                cached_fetch_all/stream queries that only read run on them.
+                Default: None.
+            write_pool_size: Writer pool size when read_pool_size is set. Default: 1.
+            max_queue: Raise PoolTimeout at once when every pooled connection is in use
+                and this many tasks already wait for one. Default: None (no limit).
                 
         Note:
             init_hook is a rapsqlite-specific enhancement and is not available in aiosqlite.
@@ -80,6 +488,9 @@ class Connection:
             standard aiosqlite functionality.
         """
         ...
+    def __await__(self) -> Iterator[Any]:
+        """``conn = await connect(path)``: open the pool and resolve to the connection."""
+        ...
     def __aenter__(self) -> "Connection": ...
     def __aexit__(
         self,
@@ -92,28 +503,147 @@ class Connection:
     def commit(self) -> Coroutine[Any, Any, None]: ...
     def rollback(self) -> Coroutine[Any, Any, None]: ...
     def execute(
-        self, query: str, parameters: Optional[Any] = None
-    ) -> Coroutine[Any, Any, "Cursor"]: ...
+        self,
+        query: Union[str, "Sql"],
+        parameters: Optional[Any] = None,
+        *,
+        multi: bool = False,
+    ) -> _ExecuteResult:
+        """Run one statement; several raise ProgrammingError unless multi=True."""
+        ...
     def execute_many(
         self, query: str, parameters: List[List[Any]]
     ) -> Coroutine[Any, Any, None]: ...
     def fetch_all(
-        self, query: str, parameters: Optional[Any] = None
+        self,
+        query: Union[str, "Sql"],
+        parameters: Optional[Any] = None,
+        *,
+        max_rows: Optional[int] = None,
+        max_result_bytes: Optional[int] = None,
     ) -> Coroutine[Any, Any, List[Any]]: ...
     def fetch_one(
-        self, query: str, parameters: Optional[Any] = None
-    ) -> Coroutine[Any, Any, Any]: ...
+        self, query: Union[str, "Sql"], parameters: Optional[Any] = None
+    ) -> Coroutine[Any, Any, Any]:
+        """Exactly one row; raises ProgrammingError if the query returns more."""
+        ...
     def fetch_optional(
-        self, query: str, parameters: Optional[Any] = None
-    ) -> Coroutine[Any, Any, Optional[Any]]: ...
+        self, query: Union[str, "Sql"], parameters: Optional[Any] = None
+    ) -> Coroutine[Any, Any, Optional[Any]]:
+        """One row or None; raises ProgrammingError if the query returns more."""
+        ...
+    def fetch_first(
+        self, query: Union[str, "Sql"], parameters: Optional[Any] = None
+    ) -> Coroutine[Any, Any, Optional[Any]]:
+        """First row or None; further rows are ignored (aiosqlite semantics)."""
+        ...
+    def cached_fetch_all(
+        self,
+        query: Union[str, "Sql"],
+        parameters: Optional[Any] = None,
+        ttl: float = 5.0,
+        *,
+        max_rows: Optional[int] = None,
+        max_result_bytes: Optional[int] = None,
+    ) -> Coroutine[Any, Any, List[Any]]:
+        """fetch_all memoized per query and parameters for ``ttl`` seconds (no invalidation)."""
+        ...
+    def stream(
+        self,
+        query: Union[str, "Sql"],
+        parameters: Optional[Any] = None,
+        *,
+        prefetch: int = 128,
+    ) -> "RowStream":
+        """Iterate over query rows with at most ``prefetch`` rows buffered ahead."""
+        ...
+    def fetch_stream(
+        self,
+        query: Union[str, "Sql"],
+        parameters: Optional[Any] = None,
+        *,
+        chunk_size: int = 1000,
+    ) -> "RowStream":
+        """Iterate over query rows in lists of up to ``chunk_size`` rows, one chunk buffered ahead."""
+        ...
+    def ttl_cache_info(self) -> Dict[str, Any]:
+        """cached_fetch_all statistics: hits, misses, hit_rate, currsize."""
+        ...
+    def ttl_cache_clear(self) -> None:
+        """Drop every cached_fetch_all result and reset its counters."""
+        ...
+    def cache_info(self) -> Optional[Dict[str, int]]:
+        """Result cache statistics: hits, misses, maxsize, currsize (None if disabled)."""
+        ...
+    def cache_clear(self) -> None:
+        """Drop every cached result and reset the hit/miss counters."""
+        ...
     def last_insert_rowid(self) -> Coroutine[Any, Any, int]: ...
     def changes(self) -> Coroutine[Any, Any, int]: ...
+    def apply_ddl(
+        self,
+        statements: List[str],
+        *,
+        dry_run: bool = False,
+        transaction: bool = True,
+    ) -> Coroutine[Any, Any, int]:
+        """Validate (``dry_run=True``, prepare only) or atomically apply DDL statements.
+
+        Returns the number of statements validated or applied.
+        """
+        ...
+    def table_checksum(
+        self, table: str, *, columns: Optional[List[str]] = None
+    ) -> Coroutine[Any, Any, str]:
+        """Order-independent hex checksum of a table's rows (streamed, hashed in Rust)."""
+        ...
+    def execute_raw(self, sql: str) -> Coroutine[Any, Any, int]:
+        """Execute SQL with sqlite3_exec (no prepared statement cache, no parameters).
+
+        Accepts several ``;``-separated statements and returns the number of rows they changed.
+        """
+        ...
+    def executescript(self, script: str) -> Coroutine[Any, Any, None]:
+        """Execute ``;``-separated statements with sqlite3_exec on one pinned connection.
+
+        Runs inside the ``begin()`` transaction when there is one.
+        """
+        ...
     def total_changes(self) -> Coroutine[Any, Any, int]: ...
     """Get the total number of database changes since connection was opened."""
     def in_transaction(self) -> Coroutine[Any, Any, bool]: ...
     """Check if connection is currently in a transaction."""
     def cursor(self) -> "Cursor": ...
     def transaction(self) -> "TransactionContextManager": ...
+    def defer_foreign_keys(self) -> "DeferForeignKeysContextManager":
+        """Defer foreign key enforcement in the active transaction; checks for violations on exit."""
+        ...
+    def savepoint(self, name: Optional[str] = None) -> "SavepointContextManager":
+        """Run a block in a savepoint of the active transaction; rolled back to if it raises."""
+        ...
+    def options(
+        self,
+        *,
+        row_factory: Optional[Any] = ...,
+        text_factory: Optional[Callable[[bytes], Any]] = ...,
+    ) -> "OptionsContextManager":
+        """Override row_factory/text_factory for the current task inside the block."""
+        ...
+    def set_default_params(self, params: Optional[Mapping[str, Any]]) -> None:
+        """Set named parameters merged into every named-parameter execution (None clears)."""
+        ...
+    def set_event_hook(
+        self,
+        event: Literal["connect", "acquire", "release", "close"],
+        hook: Optional[Callable[[PooledConnection], Awaitable[None]]],
+    ) -> None:
+        """Set an async hook for a pooled-connection lifecycle event (None removes it)."""
+        ...
+    def set_pragma(
+        self, name: str, value: Union[bool, int, float, str, None]
+    ) -> Coroutine[Any, Any, Any]:
+        """Set a PRAGMA (bools as 1/0) and resolve to its effective value."""
+        ...
     @property
     def row_factory(self) -> Any: ...
     @row_factory.setter
@@ -127,10 +657,54 @@ class Connection:
         """Set the text factory for decoding TEXT columns."""
         ...
     @property
+    def null_floats_as_nan(self) -> bool:
+        """Get whether NULL in REAL columns is fetched as float("nan"). Default: False."""
+        ...
+    @null_floats_as_nan.setter
+    def null_floats_as_nan(self, value: bool) -> None:
+        """Set whether NULL in REAL columns is fetched as float("nan") (NaN is stored as NULL)."""
+        ...
+    @property
+    def large_int_policy(self) -> str:
+        """Get how ints outside the 64-bit range are bound or returned from UDFs: "error" (default), "text" or "blob"."""
+        ...
+    @large_int_policy.setter
+    def large_int_policy(self, value: str) -> None:
+        """Set how ints outside the 64-bit range are bound ("error" raises OverflowError)."""
+        ...
+    @property
+    def decode_large_ints(self) -> bool:
+        """Get whether TEXT holding an integer outside the 64-bit range is fetched as int. Default: False."""
+        ...
+    @decode_large_ints.setter
+    def decode_large_ints(self, value: bool) -> None:
+        """Set whether TEXT holding an integer outside the 64-bit range is fetched as int."""
+        ...
+    @property
+    def invalid_utf8_policy(self) -> str:
+        """Get how TEXT that is not valid UTF-8 is returned (fetches and UDF arguments): "bytes" (default), "replace" or "error"."""
+        ...
+    @invalid_utf8_policy.setter
+    def invalid_utf8_policy(self, value: str) -> None:
+        """Set how TEXT that is not valid UTF-8 is returned ("error" raises)."""
+        ...
+    @property
+    def strict(self) -> bool:
+        """Whether the connection was opened with strict typing mode. Read-only."""
+        ...
+    @property
+    def detect_types(self) -> int:
+        """The detect_types flags the connection was opened with. Read-only."""
+        ...
+    @property
     def pool_size(self) -> Optional[int]: ...
     @pool_size.setter
     def pool_size(self, value: Optional[int]) -> None: ...
     @property
+    def max_queue(self) -> Optional[int]: ...
+    @max_queue.setter
+    def max_queue(self, value: Optional[int]) -> None: ...
+    @property
     def connection_timeout(self) -> Optional[int]: ...
     @connection_timeout.setter
     def connection_timeout(self, value: Optional[int]) -> None: ...
@@ -142,12 +716,97 @@ class Connection:
     def timeout(self, value: float) -> None:
         """Set the SQLite busy_timeout value (in seconds). Must be >= 0.0."""
         ...
+    @property
+    def callback_mode(self) -> str:
+        """Get how Python callbacks are executed: "inline" (default) or "thread"."""
+        ...
+    @callback_mode.setter
+    def callback_mode(self, value: str) -> None:
+        """Set how Python callbacks (UDFs, progress handler, trace) are executed."""
+        ...
+    @property
+    def callback_timeout(self) -> float:
+        """Get the time (in seconds) a statement waits for a callback in "thread" mode or an async UDF. Default: 5.0."""
+        ...
+    @callback_timeout.setter
+    def callback_timeout(self, value: float) -> None:
+        """Set the time (in seconds) a statement waits for a callback in "thread" mode or an async UDF. Must be > 0.0."""
+        ...
     def enable_load_extension(self, enabled: bool) -> Coroutine[Any, Any, None]: ...
     def load_extension(self, name: str) -> Coroutine[Any, Any, None]: ...
     """Load a SQLite extension from the specified file. Extension loading must be enabled first."""
     def create_function(
-        self, name: str, nargs: int, func: Optional[Any]
-    ) -> Coroutine[Any, Any, None]: ...
+        self,
+        name: str,
+        nargs: int,
+        func: Optional[Any],
+        *,
+        deterministic: bool = False,
+    ) -> Coroutine[Any, Any, None]:
+        """Create or remove a user-defined SQL function.
+
+        ``func`` may be an ``async def``; its coroutine runs on the event loop that
+        registered it, and the calling statement waits up to ``callback_timeout``.
+        If ``func`` raises, the calling query re-raises the original exception with
+        the underlying ``DatabaseError`` as its ``__cause__``.
+        ``deterministic=True`` sets ``SQLITE_DETERMINISTIC``, allowing the function
+        in indexes, generated columns and partial-index ``WHERE`` clauses.
+        """
+        ...
+    def preheat(self, queries: List[str]) -> Coroutine[Any, Any, int]:
+        """Prepare (without executing) ``queries`` on every pooled connection.
+
+        Returns the number of connections prepared.
+        """
+        ...
+    def freeze_statements(self, statements: Optional[List[str]] = None) -> None:
+        """Only permit the statements run so far (or ``statements``); others raise ProgrammingError.
+
+        Statements are compared as written after collapsing whitespace. SQL issued
+        internally (``begin()``, schema introspection) is not affected.
+        """
+        ...
+    def active_statements(self) -> List[Dict[str, Any]]:
+        """The statements running now on the pool's connections, longest-running first.
+
+        Each is a dict with ``connection`` (id), ``sql``, ``started`` (Unix
+        timestamp) and ``elapsed`` (seconds).
+        """
+        ...
+    def queue_stats(self) -> Dict[str, Any]:
+        """Counters of the queue tasks wait in for pooled connections.
+
+        A dict with ``waiting``, ``acquired``, ``timed_out``, ``rejected``,
+        ``total_wait`` and ``max_wait`` (seconds).
+        """
+        ...
+    def debug_info(self) -> Dict[str, Any]:
+        """Snapshot of the connection's state for logs and debugging.
+
+        A dict with ``path``, ``pool_open``, ``pool_size``, ``pool_idle``,
+        ``pool_max_size``, ``transaction``, ``row_factory``, ``text_factory``,
+        ``open_cursors``, ``shared_pool``, ``read_pool``, ``dedicated_runtime``
+        and ``queue`` (``queue_stats()``).
+        """
+        ...
+    @property
+    def redact_path(self) -> bool:
+        """Whether repr() and debug_info() show ``<redacted>`` instead of the path."""
+        ...
+    @redact_path.setter
+    def redact_path(self, value: bool) -> None: ...
+    def set_statement_logger(
+        self,
+        callback: Optional[Callable[[str, Any, float, int], None]],
+        redact: Optional[List[Union[str, int]]] = None,
+    ) -> None:
+        """Call ``callback(sql, params, duration, rows)`` after each successful statement.
+
+        ``redact`` lists parameter names (mapping parameters) and 0-based positions
+        (sequence parameters) whose values are logged as ``"<redacted>"``. Pass
+        ``None`` as the callback to remove the logger.
+        """
+        ...
     def set_trace_callback(
         self, callback: Optional[Any]
     ) -> Coroutine[Any, Any, None]: ...
@@ -155,10 +814,106 @@ class Connection:
     def set_progress_handler(
         self, n: int, callback: Optional[Any]
     ) -> Coroutine[Any, Any, None]: ...
+    def set_update_hook(
+        self, callback: Optional[Callable[[int, str, str, int], Any]]
+    ) -> Coroutine[Any, Any, None]:
+        """Call ``callback(operation, database, table, rowid)`` for each changed row."""
+        ...
     def iterdump(self) -> _AwaitableAsyncIterator[str]: ...
+    def count(
+        self, query: str, parameters: Optional[Any] = None
+    ) -> Coroutine[Any, Any, int]:
+        """Count the rows ``query`` returns via ``SELECT COUNT(*) FROM (query)``."""
+        ...
+    def explain_query_plan(
+        self, query: str, parameters: Optional[Any] = None
+    ) -> Coroutine[Any, Any, List[str]]:
+        """Return the ``detail`` column of ``EXPLAIN QUERY PLAN`` for ``query``."""
+        ...
+    def assert_index_used(
+        self,
+        query: str,
+        parameters: Optional[Any] = None,
+        index_name: Optional[str] = None,
+    ) -> Coroutine[Any, Any, List[str]]:
+        """Raise AssertionError if the plan has a full table scan (or doesn't use ``index_name``)."""
+        ...
+    def get_dependencies(self) -> Coroutine[Any, Any, Dict[str, Dict[str, Any]]]:
+        """Map each schema object to ``{"type", "depends_on"}`` (tables/views it references)."""
+        ...
+    def create_table(
+        self,
+        name: str,
+        columns: Dict[str, str],
+        *,
+        strict: Optional[bool] = None,
+        if_not_exists: bool = False,
+    ) -> Coroutine[Any, Any, None]:
+        """Create a table from a mapping of column names to definitions.
+
+        Emits a STRICT table when ``strict`` is True (defaults to ``Connection.strict``).
+        """
+        ...
+    def create_index(
+        self,
+        table: str,
+        columns_or_expressions: Union[str, "Sql", List[Union[str, "Sql"]]],
+        *,
+        name: Optional[str] = None,
+        unique: bool = False,
+        where: Union[str, "Sql", None] = None,
+        if_not_exists: bool = True,
+    ) -> Coroutine[Any, Any, str]:
+        """Create an index and return its name.
+
+        Strings are column names (quoted); sql() objects are expressions. ``where``
+        makes a partial index. The name defaults to ``idx_<table>_<columns>``.
+        """
+        ...
+    def drop_index(self, name: str, *, if_exists: bool = True) -> Coroutine[Any, Any, None]:
+        """Drop an index (name quoted automatically)."""
+        ...
+    def alter_table(
+        self,
+        table: str,
+        *,
+        add: Optional[List[str]] = None,
+        drop: Optional[List[str]] = None,
+        rename: Optional[Dict[str, str]] = None,
+        retype: Optional[Dict[str, str]] = None,
+    ) -> Coroutine[Any, Any, None]:
+        """Add, drop, rename and retype columns in one transaction.
+
+        Renames use RENAME COLUMN; other changes rebuild the table with SQLite's
+        12-step procedure, keeping constraints, indexes, triggers and foreign keys.
+        """
+        ...
+    def bulk_load(self, table: str) -> AsyncContextManager["Connection"]:
+        """Fast-load ``table``: drop its secondary indexes and defer foreign keys.
+
+        On exit the indexes are rebuilt, ``PRAGMA foreign_key_check`` verifies the
+        rows and the transaction commits; any failure rolls everything back.
+        """
+        ...
+    def delete_cascade(
+        self, table: str, where: str, params: Optional[Any] = None
+    ) -> Coroutine[Any, Any, Dict[str, int]]:
+        """Delete matching rows of ``table`` and, children first, the rows referencing them.
+
+        Runs in one transaction; returns rows deleted per table in deletion order.
+        """
+        ...
+    def load_fixtures(
+        self, fixtures: Dict[str, List[Dict[str, Any]]], *, truncate: bool = False
+    ) -> Coroutine[Any, Any, Dict[str, int]]:
+        """Insert fixture rows per table, parents first, in one transaction.
+
+        ``truncate`` first empties the tables. Returns rows inserted per table.
+        """
+        ...
     def backup(
         self,
-        target: Any,
+        target: Union[Connection, sqlite3.Connection, str, os.PathLike[str]],
         *,
         pages: int = 0,
         progress: Optional[Callable[[int, int, int], None]] = None,
@@ -168,8 +923,9 @@ class Connection:
         """Make a backup of the current database to a target database.
         
         Args:
-            target: Target connection for backup. Can be a rapsqlite.Connection or
-                sqlite3.Connection. For sqlite3.Connection targets, only file-backed
+            target: Target for backup: a rapsqlite.Connection, a sqlite3.Connection,
+                or the path of a database file (created if missing, overwritten
+                otherwise). For sqlite3.Connection targets, only file-backed
                 databases are supported (not :memory: or non-file URIs).
             pages: Number of pages to copy per step (0 = all pages). Default: 0
             progress: Optional progress callback function receiving (remaining, page_count, pages_copied).
@@ -188,6 +944,199 @@ class Connection:
         """
         ...
     
+    def backup_to_url(
+        self,
+        url: str,
+        *,
+        region: Optional[str] = None,
+        endpoint_url: Optional[str] = None,
+        part_size: int = 8 * 1024 * 1024,
+        concurrency: int = 4,
+        encryption_key: Optional[bytes] = None,
+    ) -> Coroutine[Any, Any, int]:
+        """Upload a consistent snapshot of the committed database to S3-compatible storage.
+        
+        Args:
+            url: Destination object, "s3://bucket/key".
+            region: Bucket region. Default: AWS_REGION / AWS_DEFAULT_REGION, else us-east-1.
+            endpoint_url: S3-compatible endpoint (e.g. MinIO); objects are addressed
+                path-style. Default: AWS_ENDPOINT_URL, else AWS.
+            part_size: Bytes per multipart upload part (at least 5 MiB); smaller
+                databases are uploaded with a single PUT. Default: 8 MiB
+            concurrency: Parts uploaded at the same time. Default: 4
+            encryption_key: 32-byte key to encrypt the snapshot with before
+                uploading, as backup_encrypted() does. Default: None
+        
+        Returns:
+            Number of bytes uploaded.
+        
+        Raises:
+            ValueError: If part_size is below 5 MiB, concurrency is 0 or the
+                key is not 32 bytes
+            OperationalError: Inside a transaction, if the URL is malformed,
+                AWS_ACCESS_KEY_ID / AWS_SECRET_ACCESS_KEY are not set, or the
+                snapshot or upload fails
+        """
+        ...
+    
+    def backup_encrypted(self, path: str, key: bytes) -> Coroutine[Any, Any, None]:
+        """Write a consistent snapshot of the committed database to path, encrypted.
+        
+        The snapshot is sealed with XChaCha20-Poly1305 under key; restore it
+        with rapsqlite.decrypt_file().
+        
+        Args:
+            path: File to write (replaced if it exists).
+            key: 32-byte symmetric key, e.g. from os.urandom(32).
+        
+        Raises:
+            ValueError: If the key is not 32 bytes
+            OperationalError: Inside a transaction or if the snapshot or write fails
+        """
+        ...
+    
+    def begin_test_snapshot(self) -> Coroutine[Any, Any, None]:
+        """Keep an in-memory copy of the database for rollback_test_snapshot().
+        
+        Raises:
+            OperationalError: Inside a transaction or if the copy fails
+        """
+        ...
+    
+    def rollback_test_snapshot(self) -> Coroutine[Any, Any, None]:
+        """Reset the database to the begin_test_snapshot() copy (kept for reuse).
+        
+        Raises:
+            OperationalError: Without a snapshot, inside a transaction or if the
+                database is locked
+        """
+        ...
+    
+    def dump_encrypted(self, path: str, key: bytes) -> Coroutine[Any, Any, None]:
+        """Write the SQL dump of the database (as iterdump()) to path, encrypted.
+        
+        Restore it with rapsqlite.decrypt_file() and Cursor.executescript().
+        
+        Raises:
+            ValueError: If the key is not 32 bytes
+            OperationalError: If dumping or writing fails
+        """
+        ...
+    
+    def dump(
+        self, path: str, *, compression: Optional[str] = None
+    ) -> Coroutine[Any, Any, int]:
+        """Write the SQL dump of the database (as iterdump()) to path.
+        
+        Args:
+            path: File to write (replaced if it exists).
+            compression: "gzip", "zstd", or None for plain SQL. Default: None
+        
+        Returns:
+            Size of the written file in bytes.
+        """
+        ...
+    
+    def import_dump(
+        self, path: str, *, compression: Optional[str] = None
+    ) -> Coroutine[Any, Any, None]:
+        """Run the SQL script at path (e.g. from dump()) in one transaction.
+        
+        Args:
+            path: Script to run, plain or compressed.
+            compression: "gzip" or "zstd" to require that format; None detects it.
+        """
+        ...
+    
+    def export_csv(
+        self,
+        path: str,
+        query: Union[str, "Sql"],
+        parameters: Optional[Any] = None,
+        *,
+        header: bool = True,
+        compression: Optional[str] = None,
+    ) -> Coroutine[Any, Any, int]:
+        """Stream the rows of query to a CSV file, optionally compressed.
+        
+        Args:
+            path: File to write (replaced if it exists).
+            query: SELECT to export.
+            parameters: Parameters for query, as for execute().
+            header: Write the column names as the first record. Default: True
+            compression: "gzip", "zstd", or None for plain CSV. Default: None
+        
+        Returns:
+            Number of rows exported.
+        """
+        ...
+    
+    def profile(
+        self,
+        query: Union[str, "Sql"],
+        parameters: Optional[Any] = None,
+    ) -> Coroutine[Any, Any, Dict[str, Any]]:
+        """Run a statement with SQLite's profiling counters enabled.
+        
+        The statement runs to completion (rows are counted, not returned).
+        
+        Args:
+            query: Statement to profile.
+            parameters: Parameters for query, as for execute().
+        
+        Returns:
+            Dict with rows, elapsed (seconds), vm_steps, fullscan_steps, sorts
+            and autoindex_rows; "plan", one dict per EXPLAIN QUERY PLAN node
+            (id, parent, detail, name, loops, visits, estimated_rows, cycles);
+            and "opcodes", one dict per EXPLAIN row (addr, opcode, p1-p5,
+            comment, subprog, executions, cycles).
+        
+        Raises:
+            NotSupportedError: rapsqlite was built without
+                SQLITE_ENABLE_STMT_SCANSTATUS and SQLITE_ENABLE_BYTECODE_VTAB.
+        """
+        ...
+    
+    def is_readonly_query(
+        self, query: Union[str, "Sql"]
+    ) -> Coroutine[Any, Any, bool]:
+        """Whether every statement in query leaves the database unchanged.
+        
+        The statements are prepared, not run, so parameters need not be
+        bound. EXPLAIN, BEGIN, COMMIT and ATTACH count as read-only.
+        
+        Args:
+            query: SQL to classify.
+        
+        Returns:
+            True if the SQL only reads.
+        
+        Raises:
+            DatabaseError: If the SQL fails to prepare (e.g. a missing table).
+        """
+        ...
+    
+    def import_csv(
+        self,
+        path: str,
+        table: str,
+        *,
+        header: bool = True,
+        compression: Optional[str] = None,
+    ) -> Coroutine[Any, Any, int]:
+        """Insert the records of a CSV file (e.g. from export_csv()) into table.
+        
+        Args:
+            path: CSV file, plain or compressed.
+            table: Existing table to insert into.
+            header: The first record names the columns to fill. Default: True
+            compression: "gzip" or "zstd" to require that format; None detects it.
+        
+        Returns:
+            Number of rows inserted.
+        """
+        ...
+    
     def get_tables(
         self, name: Optional[str] = None
     ) -> Coroutine[Any, Any, List[str]]:
@@ -217,6 +1166,9 @@ class Connection:
             - notnull: Not null constraint (0 or 1)
             - dflt_value: Default value (can be None)
             - pk: Primary key (0 or 1)
+            - generated: "virtual" or "stored" for generated columns, else None
+            - default_expr: DEFAULT expression as written in CREATE TABLE (or None)
+            - generated_expr: Generation expression for generated columns (or None)
         
         Raises:
             OperationalError: If table does not exist.
@@ -343,6 +1295,7 @@ class Connection:
             - dflt_value: Default value (can be None)
             - pk: Primary key (0 or 1)
             - hidden: Hidden column flag (0=normal, 1=hidden, 2=virtual, 3=stored)
+            - kind: ``hidden`` as a name ("normal", "hidden", "virtual", "stored")
         """
         ...
 
@@ -357,6 +1310,58 @@ class TransactionContextManager:
         exc_tb: Optional[Any],
     ) -> Coroutine[Any, Any, None]: ...
 
+class OptionsContextManager:
+    """Async context manager for per-task option overrides. Returned by Connection.options()."""
+
+    def __aenter__(self) -> Coroutine[Any, Any, "Connection"]: ...
+    def __aexit__(
+        self,
+        exc_type: Optional[Type[BaseException]],
+        exc_val: Optional[BaseException],
+        exc_tb: Optional[Any],
+    ) -> Coroutine[Any, Any, bool]: ...
+
+class DeferForeignKeysContextManager:
+    """Async context manager deferring foreign keys. Returned by Connection.defer_foreign_keys()."""
+
+    def __aenter__(self) -> Coroutine[Any, Any, "Connection"]: ...
+    def __aexit__(
+        self,
+        exc_type: Optional[Type[BaseException]],
+        exc_val: Optional[BaseException],
+        exc_tb: Optional[Any],
+    ) -> Coroutine[Any, Any, bool]: ...
+
+class SavepointContextManager:
+    """Async context manager for a savepoint. Returned by Connection.savepoint()."""
+
+    @property
+    def name(self) -> str: ...
+    def __aenter__(self) -> Coroutine[Any, Any, "Connection"]: ...
+    def __aexit__(
+        self,
+        exc_type: Optional[Type[BaseException]],
+        exc_val: Optional[BaseException],
+        exc_tb: Optional[Any],
+    ) -> Coroutine[Any, Any, bool]: ...
+
+class RowStream:
+    """Async iterator over query rows. Returned by Connection.stream() and, yielding
+    lists of rows, Connection.fetch_stream()."""
+
+    def __aiter__(self) -> "RowStream": ...
+    def __anext__(self) -> Coroutine[Any, Any, Any]: ...
+    def aclose(self) -> Coroutine[Any, Any, None]:
+        """Stop the query and release its connection."""
+        ...
+    def __aenter__(self) -> Coroutine[Any, Any, "RowStream"]: ...
+    def __aexit__(
+        self,
+        exc_type: Optional[Type[BaseException]],
+        exc_val: Optional[BaseException],
+        exc_tb: Optional[Any],
+    ) -> Coroutine[Any, Any, None]: ...
+
 class Cursor:
     """Cursor for executing queries."""
 
@@ -368,22 +1373,60 @@ class Cursor:
         exc_tb: Optional[Any],
     ) -> Coroutine[Any, Any, Optional[bool]]: ...
     def execute(
-        self, query: str, parameters: Optional[Any] = None
+        self,
+        query: Union[str, "Sql"],
+        parameters: Optional[Any] = None,
+        *,
+        multi: bool = False,
     ) -> Coroutine[Any, Any, None]: ...
+    @property
+    def rowcount(self) -> int:
+        """Rows changed by the executed statement; -1 for SELECT or before execution."""
+        ...
+    @property
+    def rows_affected(self) -> int:
+        """Alias of ``rowcount``."""
+        ...
+    @property
+    def lastrowid(self) -> Optional[int]:
+        """Last inserted row ID of the executed statement; None for SELECT or before execution."""
+        ...
+    @property
+    def arraysize(self) -> int:
+        """Rows fetchmany() returns when called without a size (default 1)."""
+        ...
+    @arraysize.setter
+    def arraysize(self, value: int) -> None: ...
     def executemany(
         self, query: str, parameters: List[List[Any]]
     ) -> Coroutine[Any, Any, None]: ...
     def fetchone(self) -> Coroutine[Any, Any, Optional[Any]]: ...
-    def fetchall(self) -> Coroutine[Any, Any, List[Any]]: ...
+    def fetchall(
+        self, *, copy: bool = True
+    ) -> Coroutine[Any, Any, Union[List[Any], "BufferedRowIterator"]]: ...
+    """Fetch the remaining rows; copy=False iterates the buffered rows in place."""
     def fetchmany(
         self, size: Optional[int] = None
     ) -> Coroutine[Any, Any, List[Any]]: ...
+    def scroll(
+        self, value: int, mode: str = "relative"
+    ) -> Coroutine[Any, Any, None]: ...
+    """Move the result position ("relative" or "absolute"); IndexError past the ends."""
     def executescript(self, script: str) -> Coroutine[Any, Any, None]: ...
     """Execute a script containing multiple SQL statements separated by semicolons."""
+    def close(self) -> Coroutine[Any, Any, None]: ...
+    """Close the cursor; closing the connection closes its cursors too."""
     def __aiter__(self) -> "Cursor": ...
     """Async iterator entry point."""
     def __anext__(self) -> Coroutine[Any, Any, Any]: ...
-    """Async iterator next item."""
+    """Async iterator next item; runs the query on first use."""
+
+class BufferedRowIterator:
+    """Iterator over a cursor's buffered rows. Returned by Cursor.fetchall(copy=False)."""
+
+    def __iter__(self) -> "BufferedRowIterator": ...
+    def __next__(self) -> Any: ...
+    def __length_hint__(self) -> int: ...
 
 class RapRow:
     """Row class for dict-like access to query results (similar to aiosqlite.Row)."""
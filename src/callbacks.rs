@@ -0,0 +1,364 @@
+//! Dispatching of Python callbacks (UDFs, progress handler, trace) invoked by SQLite.
+//!
+//! By default ("inline" mode) SQLite trampolines call into Python directly on the
+//! thread executing the statement. In "thread" mode calls are marshalled to a
+//! dedicated dispatcher thread through a bounded queue, and the statement thread
+//! waits at most `callback_timeout` seconds for the result, so a slow Python callback
+//! can only delay its own statement instead of stalling the runtime.
+
+use pyo3::prelude::*;
+use pyo3::{PyTraverseError, PyVisit};
+use std::ffi::c_void;
+use std::future::Future;
+use std::sync::mpsc::{sync_channel, RecvTimeoutError, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::Duration;
+
+use crate::types::{InvalidUtf8Policy, LargeIntPolicy};
+use crate::{DatabaseError, OperationalError, ProgrammingError};
+
+/// Maximum number of callback invocations waiting for the dispatcher thread.
+/// When the queue is full, new invocations fail fast instead of blocking.
+const CALLBACK_QUEUE_SIZE: usize = 64;
+
+type Job = Box<dyn FnOnce() + Send + 'static>;
+
+/// Shared dispatcher slot: `None` means callbacks run inline.
+pub(crate) type CallbackDispatcherSlot = Arc<StdMutex<Option<Arc<CallbackDispatcher>>>>;
+
+/// Per-connection slot holding the Python exception raised by the last failing UDF.
+pub(crate) type UdfErrorSlot = Arc<StdMutex<Option<PyErr>>>;
+
+/// Prefix of the SQLite error message set when a UDF raises.
+pub(crate) const UDF_ERROR_PREFIX: &str = "Python function error";
+
+/// Re-raise the Python exception of a failing UDF from the statement that called it.
+///
+/// If `err` is the database error produced by a raising UDF, the original exception
+/// stashed in `slot` is returned instead, with the database error as its `__cause__`
+/// (like the stdlib sqlite3 module with callback tracebacks). Otherwise `err` is
+/// returned unchanged. Either way the slot is cleared.
+pub(crate) fn propagate_udf_error(slot: &UdfErrorSlot, err: PyErr) -> PyErr {
+    let Some(original) = slot.lock().unwrap().take() else {
+        return err;
+    };
+    Python::attach(|py| {
+        let caused_by_udf = err.is_instance_of::<DatabaseError>(py)
+            && err
+                .value(py)
+                .str()
+                .map(|msg| msg.to_string_lossy().contains(UDF_ERROR_PREFIX))
+                .unwrap_or(false);
+        if !caused_by_udf {
+            return err;
+        }
+        original.set_cause(py, Some(err));
+        original
+    })
+}
+
+/// Stash an exception raised by a UDF so the calling statement can re-raise it.
+pub(crate) fn stash_udf_error(slot: Option<&UdfErrorSlot>, py: Python<'_>, err: &PyErr) {
+    if let Some(slot) = slot {
+        *slot.lock().unwrap() = Some(err.clone_ref(py));
+    }
+}
+
+/// Await `future`, re-raising UDF exceptions via [`propagate_udf_error`].
+pub(crate) async fn with_udf_errors<T>(
+    slot: UdfErrorSlot,
+    future: impl Future<Output = PyResult<T>>,
+) -> PyResult<T> {
+    future.await.map_err(|e| propagate_udf_error(&slot, e))
+}
+
+/// User data handed to SQLite callback trampolines.
+///
+/// The callback is kept behind an `Arc` so trampolines can hand it to the
+/// dispatcher thread without holding the GIL.
+pub(crate) struct CallbackContext {
+    pub(crate) callback: Arc<Py<PyAny>>,
+    dispatcher: CallbackDispatcherSlot,
+    /// Set for `async def` UDFs: where their coroutines are run.
+    pub(crate) async_bridge: Option<Arc<AsyncBridge>>,
+    /// Set for UDFs: where a raised Python exception is stashed for the caller.
+    pub(crate) udf_error: Option<UdfErrorSlot>,
+    /// Set for UDFs: the connection's `invalid_utf8_policy` for TEXT arguments.
+    invalid_utf8: Option<Arc<StdMutex<InvalidUtf8Policy>>>,
+    /// Set for UDFs: the connection's `large_int_policy` for int results.
+    large_int: Option<Arc<StdMutex<LargeIntPolicy>>>,
+}
+
+impl CallbackContext {
+    /// `callback` is shared with the connection holding it (e.g. its
+    /// `user_functions`), which reports it to the garbage collector.
+    pub(crate) fn new(callback: Arc<Py<PyAny>>, dispatcher: &CallbackDispatcherSlot) -> Self {
+        CallbackContext {
+            callback,
+            dispatcher: Arc::clone(dispatcher),
+            async_bridge: None,
+            udf_error: None,
+            invalid_utf8: None,
+            large_int: None,
+        }
+    }
+
+    pub(crate) fn with_udf_error_slot(mut self, udf_error: &UdfErrorSlot) -> Self {
+        self.udf_error = Some(Arc::clone(udf_error));
+        self
+    }
+
+    pub(crate) fn with_async_bridge(mut self, async_bridge: Option<AsyncBridge>) -> Self {
+        self.async_bridge = async_bridge.map(Arc::new);
+        self
+    }
+
+    pub(crate) fn with_invalid_utf8_policy(
+        mut self,
+        invalid_utf8: &Arc<StdMutex<InvalidUtf8Policy>>,
+    ) -> Self {
+        self.invalid_utf8 = Some(Arc::clone(invalid_utf8));
+        self
+    }
+
+    pub(crate) fn with_large_int_policy(
+        mut self,
+        large_int: &Arc<StdMutex<LargeIntPolicy>>,
+    ) -> Self {
+        self.large_int = Some(Arc::clone(large_int));
+        self
+    }
+
+    /// How TEXT arguments that are not valid UTF-8 are passed to the callback.
+    pub(crate) fn invalid_utf8_policy(&self) -> InvalidUtf8Policy {
+        self.invalid_utf8
+            .as_ref()
+            .map(|policy| *policy.lock().unwrap())
+            .unwrap_or_default()
+    }
+
+    /// How int results outside the i64 range are returned to SQLite.
+    pub(crate) fn large_int_policy(&self) -> LargeIntPolicy {
+        self.large_int
+            .as_ref()
+            .map(|policy| *policy.lock().unwrap())
+            .unwrap_or_default()
+    }
+
+    /// Dispatcher to use for this invocation, or `None` to call inline.
+    pub(crate) fn dispatcher(&self) -> Option<Arc<CallbackDispatcher>> {
+        self.dispatcher.lock().unwrap().clone()
+    }
+}
+
+/// Context pointer registered with a SQLite hook that takes no destructor
+/// (trace, authorizer, progress handler, update hook).
+///
+/// SQLite never frees these pointers, so the connection keeps the registered one
+/// here and drops it once SQLite no longer references it, i.e. after the hook has
+/// been replaced or cleared.
+pub(crate) struct HookContext {
+    ptr: *mut c_void,
+    destroy: unsafe fn(*mut c_void),
+    /// The Python callback the boxed value holds, for garbage collection.
+    callback: Option<Arc<Py<PyAny>>>,
+}
+
+// Safety: a HookContext is only built from `Send` values (see `HookContext::new`)
+// and owns the boxed value exclusively.
+unsafe impl Send for HookContext {}
+
+impl HookContext {
+    pub(crate) fn new<T: Send + 'static>(value: T) -> Self {
+        unsafe fn destroy<T>(ptr: *mut c_void) {
+            // Safety: ptr was created by Box::into_raw in `HookContext::new::<T>`
+            unsafe { drop(Box::from_raw(ptr as *mut T)) }
+        }
+        HookContext {
+            ptr: Box::into_raw(Box::new(value)) as *mut c_void,
+            destroy: destroy::<T>,
+            callback: None,
+        }
+    }
+
+    /// Record the callback the boxed value holds (sharing its `Arc`), so the
+    /// connection can report it to the garbage collector.
+    pub(crate) fn holding(mut self, callback: &Arc<Py<PyAny>>) -> Self {
+        self.callback = Some(Arc::clone(callback));
+        self
+    }
+
+    /// Pointer to pass to SQLite as the hook's user data.
+    pub(crate) fn as_ptr(&self) -> *mut c_void {
+        self.ptr
+    }
+}
+
+impl Drop for HookContext {
+    fn drop(&mut self) {
+        // Safety: ptr and destroy were paired in `HookContext::new`, and drop runs once
+        unsafe { (self.destroy)(self.ptr) }
+    }
+}
+
+/// Hook contexts currently registered on the callback connection.
+#[derive(Default)]
+pub(crate) struct HookContexts {
+    pub(crate) trace: Option<HookContext>,
+    pub(crate) authorizer: Option<HookContext>,
+    pub(crate) progress: Option<HookContext>,
+    pub(crate) update: Option<HookContext>,
+}
+
+impl HookContexts {
+    /// Visit the callbacks the registered contexts hold.
+    pub(crate) fn traverse(&self, visit: &PyVisit<'_>) -> Result<(), PyTraverseError> {
+        for context in [&self.trace, &self.authorizer, &self.progress, &self.update]
+            .into_iter()
+            .flatten()
+        {
+            visit.call(context.callback.as_deref())?;
+        }
+        Ok(())
+    }
+}
+
+/// Shared slot owning the per-connection [`HookContexts`].
+pub(crate) type HookContextsSlot = Arc<StdMutex<HookContexts>>;
+
+/// Runs coroutines returned by `async def` UDFs on the event loop they were
+/// registered from, blocking only the statement that called the UDF.
+pub(crate) struct AsyncBridge {
+    event_loop: Py<PyAny>,
+    timeout: Arc<StdMutex<f64>>,
+}
+
+impl AsyncBridge {
+    /// Capture the running event loop if `func` is a coroutine function.
+    pub(crate) fn for_function(
+        py: Python<'_>,
+        func: &Bound<'_, PyAny>,
+        timeout: &Arc<StdMutex<f64>>,
+    ) -> PyResult<Option<Self>> {
+        let is_async = py
+            .import("inspect")?
+            .call_method1("iscoroutinefunction", (func,))?
+            .is_truthy()?;
+        if !is_async {
+            return Ok(None);
+        }
+        let event_loop = py
+            .import("asyncio")?
+            .call_method0("get_running_loop")
+            .map_err(|_| {
+                ProgrammingError::new_err(
+                    "async functions can only be registered while an event loop is running",
+                )
+            })?;
+        Ok(Some(AsyncBridge {
+            event_loop: event_loop.unbind(),
+            timeout: Arc::clone(timeout),
+        }))
+    }
+
+    /// Wait for `result` if it is a coroutine; other values are returned unchanged.
+    ///
+    /// Must not be called from the event loop thread itself. SQLite runs statements
+    /// on sqlx worker threads, so the loop stays free to drive the coroutine.
+    pub(crate) fn resolve<'py>(
+        &self,
+        py: Python<'py>,
+        result: Bound<'py, PyAny>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let asyncio = py.import("asyncio")?;
+        if !asyncio
+            .call_method1("iscoroutine", (&result,))?
+            .is_truthy()?
+        {
+            return Ok(result);
+        }
+        let future = asyncio.call_method1(
+            "run_coroutine_threadsafe",
+            (&result, self.event_loop.bind(py)),
+        )?;
+        let timeout = *self.timeout.lock().unwrap();
+        // Future.result() releases the GIL while waiting
+        future.call_method1("result", (timeout,)).map_err(|e| {
+            let _ = future.call_method0("cancel");
+            let timeout_error = py
+                .import("concurrent.futures")
+                .and_then(|m| m.getattr("TimeoutError"));
+            match timeout_error {
+                Ok(cls) if e.matches(py, &cls).unwrap_or(false) => OperationalError::new_err(
+                    format!("async function timed out after {timeout:.3}s"),
+                ),
+                _ => e,
+            }
+        })
+    }
+}
+
+/// Dedicated thread executing Python callbacks with a bounded queue.
+pub(crate) struct CallbackDispatcher {
+    sender: SyncSender<Job>,
+    timeout: Duration,
+}
+
+impl CallbackDispatcher {
+    /// Spawn a dispatcher thread. The thread exits once every handle is dropped.
+    pub(crate) fn new(timeout_secs: f64) -> PyResult<Self> {
+        let (sender, receiver) = sync_channel::<Job>(CALLBACK_QUEUE_SIZE);
+        std::thread::Builder::new()
+            .name("rapsqlite-callbacks".to_string())
+            .spawn(move || {
+                for job in receiver {
+                    job();
+                }
+            })
+            .map_err(|e| {
+                OperationalError::new_err(format!("Failed to start callback thread: {e}"))
+            })?;
+        Ok(CallbackDispatcher {
+            sender,
+            timeout: Duration::from_secs_f64(timeout_secs),
+        })
+    }
+
+    /// Run `job` on the dispatcher thread and wait for its result.
+    ///
+    /// Returns an error message if the queue is full, the dispatcher thread is gone,
+    /// or the result does not arrive within the timeout.
+    pub(crate) fn call<T, F>(&self, job: F) -> Result<T, String>
+    where
+        T: Send + 'static,
+        F: FnOnce() -> T + Send + 'static,
+    {
+        let (result_tx, result_rx) = std::sync::mpsc::channel();
+        self.post_job(Box::new(move || {
+            // The caller may have timed out and dropped the receiver
+            let _ = result_tx.send(job());
+        }))?;
+        result_rx.recv_timeout(self.timeout).map_err(|e| match e {
+            RecvTimeoutError::Timeout => format!(
+                "Python callback timed out after {:.3}s",
+                self.timeout.as_secs_f64()
+            ),
+            RecvTimeoutError::Disconnected => "Python callback did not complete".to_string(),
+        })
+    }
+
+    /// Queue `job` on the dispatcher thread without waiting for it.
+    pub(crate) fn post<F>(&self, job: F) -> Result<(), String>
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        self.post_job(Box::new(job))
+    }
+
+    fn post_job(&self, job: Job) -> Result<(), String> {
+        self.sender.try_send(job).map_err(|e| match e {
+            TrySendError::Full(_) => "Python callback queue is full".to_string(),
+            TrySendError::Disconnected(_) => "Python callback thread is not running".to_string(),
+        })
+    }
+}
@@ -0,0 +1,84 @@
+//! `rapsqlite.FakeClock`: a settable clock for SQL date and time functions,
+//! passed to `Connection(clock=...)`. The clock's VFS lives in
+//! `rapsqlite_core::clock`.
+
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use rapsqlite_core::clock::FakeClock as CoreClock;
+
+use crate::ValueError;
+
+/// A fake clock for the SQL date and time functions of the Connections opened
+/// with `clock=`: `datetime('now')`, `unixepoch()`, `CURRENT_TIMESTAMP` and
+/// the like read it instead of the system time, so tests exercising date logic
+/// in SQL get reproducible results. The clock only moves when `set()` or
+/// `advance()` is called.
+///
+/// Times are seconds since the Unix epoch or `datetime` objects; naive
+/// datetimes are taken as UTC, as SQLite does.
+#[pyclass(frozen)]
+pub(crate) struct FakeClock {
+    pub(crate) clock: CoreClock,
+}
+
+/// Milliseconds since the Unix epoch for `when`.
+fn unix_ms(when: &Bound<'_, PyAny>) -> PyResult<i64> {
+    let seconds: f64 = if when.hasattr("timestamp")? {
+        let when = if when.getattr("tzinfo")?.is_none() {
+            let utc = when
+                .py()
+                .import("datetime")?
+                .getattr("timezone")?
+                .getattr("utc")?;
+            let kwargs = PyDict::new(when.py());
+            kwargs.set_item("tzinfo", utc)?;
+            when.call_method("replace", (), Some(&kwargs))?
+        } else {
+            when.clone()
+        };
+        when.call_method0("timestamp")?.extract()?
+    } else {
+        when.extract()?
+    };
+    if !seconds.is_finite() {
+        return Err(ValueError::new_err("clock time must be finite"));
+    }
+    Ok((seconds * 1000.0).round() as i64)
+}
+
+#[pymethods]
+impl FakeClock {
+    /// Create a clock set to `start` (seconds since the Unix epoch or a
+    /// `datetime`).
+    #[new]
+    fn new(start: &Bound<'_, PyAny>) -> PyResult<Self> {
+        Ok(FakeClock {
+            clock: CoreClock::new(unix_ms(start)?),
+        })
+    }
+
+    /// Current time of the clock, in seconds since the Unix epoch.
+    #[getter]
+    fn now(&self) -> f64 {
+        self.clock.now_ms() as f64 / 1000.0
+    }
+
+    /// Set the clock to `when` (seconds since the Unix epoch or a `datetime`).
+    fn set(&self, when: &Bound<'_, PyAny>) -> PyResult<()> {
+        self.clock.set_ms(unix_ms(when)?);
+        Ok(())
+    }
+
+    /// Move the clock forward by `seconds` (backwards when negative).
+    fn advance(&self, seconds: f64) -> PyResult<()> {
+        if !seconds.is_finite() {
+            return Err(ValueError::new_err("seconds must be finite"));
+        }
+        self.clock.advance_ms((seconds * 1000.0).round() as i64);
+        Ok(())
+    }
+
+    fn __repr__(&self) -> String {
+        format!("FakeClock(now={})", self.now())
+    }
+}
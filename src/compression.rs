@@ -0,0 +1,88 @@
+//! Compressed dumps and exports: the `compression=` argument of
+//! `Connection.dump()`, `Connection.export_csv()` and the import methods, and
+//! `write_compressed()` / `read_compressed()` behind them. The codecs live in
+//! `rapsqlite_core::compression`.
+
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use rapsqlite_core::compression::{self, Compression};
+use std::path::PathBuf;
+
+use crate::{runtime, OperationalError, ValueError};
+
+/// Parse a `compression=` argument: "gzip", "zstd" or None.
+pub(crate) fn parse_compression(compression: Option<&str>) -> PyResult<Option<Compression>> {
+    compression
+        .map(|name| name.parse().map_err(ValueError::new_err))
+        .transpose()
+}
+
+/// Write `data` to a new file at `path`, compressed with gzip or zstd.
+///
+/// # Arguments
+///
+/// * `path` - File to write (replaced if it exists).
+/// * `data` - Bytes to write.
+/// * `compression` - "gzip", "zstd", or None to write `data` as-is.
+///
+/// # Returns
+///
+/// Returns an awaitable resolving to the size of the written file.
+///
+/// # Errors
+///
+/// Raises ValueError for an unknown `compression` and OperationalError if
+/// writing fails.
+#[pyfunction]
+#[pyo3(signature = (path, data, compression = None))]
+pub(crate) fn write_compressed(
+    py: Python<'_>,
+    path: PathBuf,
+    data: Vec<u8>,
+    compression: Option<&str>,
+) -> PyResult<Py<PyAny>> {
+    let compression = parse_compression(compression)?;
+    let future = async move {
+        tokio::task::spawn_blocking(move || compression::write_file(&path, &data, compression))
+            .await
+            .map_err(|e| OperationalError::new_err(format!("write_compressed failed: {e}")))?
+            .map_err(|e| OperationalError::new_err(format!("write_compressed failed: {e}")))
+    };
+    runtime::future_into_py(py, None, future).map(|bound| bound.unbind())
+}
+
+/// Read a file written by `write_compressed()`, `Connection.dump()` or
+/// `Connection.export_csv()`, decompressing it.
+///
+/// # Arguments
+///
+/// * `path` - File to read.
+/// * `compression` - "gzip" or "zstd" to require that format, or None to
+///   detect it from the file's magic bytes (an uncompressed file is read
+///   as-is).
+///
+/// # Returns
+///
+/// Returns an awaitable resolving to the decompressed bytes.
+///
+/// # Errors
+///
+/// Raises ValueError for an unknown `compression` and OperationalError if the
+/// file can't be read, is not in the required format or is corrupt.
+#[pyfunction]
+#[pyo3(signature = (path, compression = None))]
+pub(crate) fn read_compressed(
+    py: Python<'_>,
+    path: PathBuf,
+    compression: Option<&str>,
+) -> PyResult<Py<PyAny>> {
+    let compression = parse_compression(compression)?;
+    let future = async move {
+        let data = tokio::task::spawn_blocking(move || compression::read_file(&path, compression))
+            .await
+            .map_err(|e| OperationalError::new_err(format!("read_compressed failed: {e}")))?
+            .map_err(|e| OperationalError::new_err(format!("read_compressed failed: {e}")))?;
+        Ok(Python::attach(|py| PyBytes::new(py, &data).unbind()))
+    };
+    runtime::future_into_py(py, None, future).map(|bound| bound.unbind())
+}
@@ -2,52 +2,107 @@
 
 #![allow(non_local_definitions)] // False positive from pyo3 macros
 
+use futures_util::TryStreamExt;
 use pyo3::prelude::*;
 use pyo3::types::{PyDict, PyFloat, PyInt, PyList, PyString, PyTuple};
-use pyo3_async_runtimes::tokio::future_into_py;
+use pyo3::{PyTraverseError, PyVisit};
+use rapsqlite_core::activity;
+use rapsqlite_core::authorizer::{self, StatementPolicy};
+use rapsqlite_core::backup::{BackupFile, MemorySnapshot, Snapshot};
+use rapsqlite_core::cache::{ResultCache, TtlCache};
+use rapsqlite_core::clock::FakeClock as CoreClock;
+use rapsqlite_core::compression;
+use rapsqlite_core::debug::{NPlusOneDetector, TaskAudit};
+use rapsqlite_core::profile;
+use rapsqlite_core::query::{bind_and_stream_on_connection, FetchLimits};
+use rapsqlite_core::s3::{self, S3Options};
+use rapsqlite_core::wal_hook::WalHook;
+use sha2::{Digest, Sha256};
 use sqlx::pool::PoolConnection;
 use sqlx::sqlite::SqliteConnection;
 use sqlx::{Column, Row, SqlitePool};
-use std::collections::HashMap;
-use std::ffi::CString;
-use std::sync::{Arc, Mutex as StdMutex};
-use std::time::Duration;
-use tokio::sync::Mutex;
+use std::collections::{HashMap, HashSet};
+use std::ffi::{c_char, c_int, c_void, CStr, CString};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex as StdMutex, OnceLock};
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, Mutex};
 
 // libsqlite3-sys for raw SQLite C API access
 use libsqlite3_sys::{
     sqlite3, sqlite3_backup_finish, sqlite3_backup_init, sqlite3_backup_pagecount,
-    sqlite3_backup_remaining, sqlite3_backup_step, sqlite3_context, sqlite3_create_function_v2,
-    sqlite3_enable_load_extension, sqlite3_errcode, sqlite3_errmsg, sqlite3_free,
-    sqlite3_get_autocommit, sqlite3_libversion, sqlite3_load_extension, sqlite3_progress_handler,
-    sqlite3_result_null, sqlite3_set_authorizer, sqlite3_total_changes, sqlite3_trace_v2,
-    sqlite3_user_data, sqlite3_value, SQLITE_BUSY, SQLITE_DENY, SQLITE_DONE, SQLITE_LOCKED,
-    SQLITE_OK, SQLITE_TRACE_STMT, SQLITE_UTF8,
+    sqlite3_backup_remaining, sqlite3_backup_step, sqlite3_bind_blob, sqlite3_bind_double,
+    sqlite3_bind_int64, sqlite3_bind_null, sqlite3_bind_text, sqlite3_column_blob,
+    sqlite3_column_bytes, sqlite3_column_count, sqlite3_column_double, sqlite3_column_int64,
+    sqlite3_column_name, sqlite3_column_text, sqlite3_column_type, sqlite3_context,
+    sqlite3_create_function_v2, sqlite3_enable_load_extension, sqlite3_errcode, sqlite3_errmsg,
+    sqlite3_exec, sqlite3_finalize, sqlite3_free, sqlite3_get_autocommit, sqlite3_int64,
+    sqlite3_last_insert_rowid, sqlite3_libversion, sqlite3_load_extension, sqlite3_prepare_v2,
+    sqlite3_progress_handler, sqlite3_result_null, sqlite3_set_authorizer, sqlite3_step,
+    sqlite3_stmt, sqlite3_total_changes, sqlite3_trace_v2, sqlite3_update_hook, sqlite3_user_data,
+    sqlite3_value, SQLITE_BLOB, SQLITE_BUSY, SQLITE_DENY, SQLITE_DETERMINISTIC, SQLITE_DONE,
+    SQLITE_FLOAT, SQLITE_INTEGER, SQLITE_LOCKED, SQLITE_NULL, SQLITE_OK, SQLITE_ROW, SQLITE_TEXT,
+    SQLITE_TRACE_STMT, SQLITE_TRANSIENT, SQLITE_UTF8,
 };
 
-use crate::conversion::{py_to_sqlite_c_result, row_to_py_with_factory, sqlite_c_value_to_py};
-use crate::errors::map_sqlx_error;
-use crate::parameters::{process_named_parameters, process_positional_parameters};
+use crate::callbacks::{
+    stash_udf_error, with_udf_errors, AsyncBridge, CallbackContext, CallbackDispatcher,
+    CallbackDispatcherSlot, HookContext, HookContextsSlot, UdfErrorSlot, UDF_ERROR_PREFIX,
+};
+use crate::clock::FakeClock;
+use crate::conversion::{
+    convert_in_chunks, py_to_udf_result, sqlite_c_value_to_py, sqlite_value_to_py, udf_c_result,
+    DecodeOptions, RowFactory, UdfArg,
+};
+use crate::converters::{declared_types, declared_types_in_pool, ColumnConverters};
+use crate::cursor::{CursorRegistry, CursorResults};
+use crate::encryption;
+use crate::errors::{map_fetch_error, map_sqlite_error, map_sqlx_error};
+use crate::faults::FaultInjector;
+use crate::lifecycle::EventHooks;
+use crate::parameters::{
+    materialize_parameters, merge_default_parameters, process_parameters,
+    rewrite_named_placeholders,
+};
 use crate::pool::{
     ensure_callback_connection, execute_init_hook_if_needed, get_or_create_pool, has_callbacks,
-    pool_acquisition_error,
+    pool_acquisition_error, ReadPool, SharedPool,
 };
 use crate::query::{
     bind_and_execute, bind_and_execute_on_connection, bind_and_fetch_all,
-    bind_and_fetch_all_on_connection, bind_and_fetch_one, bind_and_fetch_one_on_connection,
-    bind_and_fetch_optional, bind_and_fetch_optional_on_connection,
+    bind_and_fetch_all_limited, bind_and_fetch_all_limited_on_connection,
+    bind_and_fetch_all_on_connection, bind_and_fetch_optional,
+    bind_and_fetch_optional_on_connection, bind_and_fetch_single,
+    bind_and_fetch_single_on_connection, prepare_on_connection,
+};
+use crate::replication;
+use crate::result_cache::{self, fetch_all_cached, SharedResultCache};
+use crate::runtime::{self, DedicatedRuntime};
+use crate::statement_log::{self, StatementLogger, StatementLoggerSlot};
+use crate::stream::RowStream;
+use crate::template::Query;
+use crate::types::{
+    BindOptions, ExecuteResult, GroupCommitSlot, InvalidUtf8Policy, LargeIntPolicy,
+    ProgressHandler, SqliteParam, SqliteParamExt, TransactionState, UserFunctions,
 };
-use crate::types::{ProgressHandler, SqliteParam, TransactionState, UserFunctions};
 use crate::utils::{
-    cstr_from_i8_ptr, is_select_query, parse_connection_string, track_query_usage, validate_path,
+    audit_callback, audit_setting, audit_statement, audit_transaction_begin, audit_transaction_end,
+    check_n_plus_one, cstr_from_i8_ptr, hidden_column_kind, normalize_query, parse_column_clauses,
+    parse_connection_string, pragma_literal, quote_identifier, returns_rows_hint, second_statement,
+    split_statements, track_query_usage, validate_path,
 };
 use crate::OperationalError;
 use crate::{
-    Cursor, ExecuteContextManager, ProgrammingError, TransactionContextManager, ValueError,
+    Cursor, DeferForeignKeysContextManager, ExecuteContextManager, NotSupportedError,
+    OptionsContextManager, ProgrammingError, SavepointContextManager, TransactionContextManager,
+    ValueError,
 };
 
+/// How long `preheat()` waits for each pooled connection after the first.
+const PREHEAT_ACQUIRE_WAIT: Duration = Duration::from_millis(250);
+
 /// Async SQLite connection.
-#[pyclass]
+#[pyclass(weakref)]
 pub(crate) struct Connection {
     path: String,
     pool: Arc<Mutex<Option<SqlitePool>>>,
@@ -64,6 +119,15 @@ pub(crate) struct Connection {
     connection_timeout_secs: Arc<StdMutex<Option<u64>>>, // Connection timeout in seconds
     row_factory: Arc<StdMutex<Option<Py<PyAny>>>>, // None | "dict" | "tuple" | callable
     text_factory: Arc<StdMutex<Option<Py<PyAny>>>>, // Callable(bytes) -> str, or None for default UTF-8
+    option_overrides: Py<PyAny>, // ContextVar: dict of options() overrides for the current task
+    default_params: Arc<StdMutex<Option<Py<PyDict>>>>, // set_default_params(): merged into named parameters
+    test_snapshot: Arc<StdMutex<Option<Arc<MemorySnapshot>>>>, // begin_test_snapshot() copy
+    null_floats_as_nan: Arc<StdMutex<bool>>,           // Fetch NULL in REAL columns as float("nan")
+    large_int_policy: Arc<StdMutex<LargeIntPolicy>>,   // Binding of ints outside the i64 range
+    decode_large_ints: Arc<StdMutex<bool>>,            // Fetch oversized integer TEXT as int
+    invalid_utf8: Arc<StdMutex<InvalidUtf8Policy>>,    // Handling of TEXT that is not valid UTF-8
+    strict: bool,      // Strict typing: no lossy binding, decode by declared column type
+    detect_types: u32, // PARSE_DECLTYPES | PARSE_COLNAMES: apply registered converters
     // Prepared statement cache tracking (Phase 2.13)
     // Tracks normalized query strings and usage counts for analytics/optimization.
     // This is separate from sqlx's internal prepared statement cache, which automatically
@@ -71,27 +135,54 @@ pub(crate) struct Connection {
     // for analytics and optimization insights, while sqlx handles the actual statement
     // caching and reuse for performance.
     query_cache: Arc<StdMutex<HashMap<String, u64>>>, // normalized_query -> usage_count
+    frozen_statements: StdMutex<Option<HashSet<String>>>, // Some after freeze_statements()
     // Callback infrastructure (Phase 2.7)
     callback_connection: Arc<Mutex<Option<PoolConnection<sqlx::Sqlite>>>>, // Dedicated connection for callbacks
     load_extension_enabled: Arc<StdMutex<bool>>, // Track load_extension state
-    user_functions: UserFunctions,               // name -> (nargs, callback)
+    user_functions: UserFunctions,               // (name, nargs) -> callback
     trace_callback: Arc<StdMutex<Option<Py<PyAny>>>>, // Trace callback
     authorizer_callback: Arc<StdMutex<Option<Py<PyAny>>>>, // Authorizer callback
     progress_handler: ProgressHandler,           // (n, callback)
+    update_hook: Arc<StdMutex<Option<Py<PyAny>>>>, // Update hook callback
+    hook_contexts: HookContextsSlot, // Contexts registered with trace/authorizer/progress/update hooks
+    udf_error: UdfErrorSlot,         // Python exception raised by the last failing UDF
+    callback_dispatcher: CallbackDispatcherSlot, // None = call Python callbacks inline
+    callback_timeout: Arc<StdMutex<f64>>, // Seconds to wait for a dispatched callback
     // Error message security: control whether query strings are included in errors
     include_query_in_errors: Arc<StdMutex<bool>>, // If false, exclude query strings from error messages
+    redact_path: Arc<StdMutex<bool>>,             // If true, repr() and debug_info() hide the path
     // SQLite busy_timeout (aiosqlite compatibility) - timeout in seconds for database locks
     timeout: Arc<StdMutex<f64>>, // Default: 5.0 seconds (matches sqlite3 default)
+    result_cache: Option<SharedResultCache>, // fetch_all result cache (cache="lru")
+    ttl_cache: Arc<StdMutex<TtlCache>>, // cached_fetch_all results
+    group_commit_window: Option<Duration>, // None = execute() writes commit individually
+    group_committer: GroupCommitSlot,
+    n_plus_one: Option<Arc<StdMutex<NPlusOneDetector>>>, // Some when debug=True
+    task_audit: Option<Arc<StdMutex<TaskAudit>>>,        // Some when debug=True
+    statement_logger: StatementLoggerSlot,
+    statement_policy: Option<Arc<StatementPolicy>>, // Some when hardened=True
+    native_functions: bool, // Register the Rust-native SQL functions on pooled connections
+    wal_hook: Option<Arc<WalHook>>,
+    vfs: Option<String>,                    // Name of the clock= FakeClock's VFS
+    _clock: Option<CoreClock>,              // Keeps that VFS from going to another clock
+    event_hooks: Arc<EventHooks>,           // set_event_hook() hooks, shared with a shared pool
+    fetch_limits: FetchLimits,              // Default max_rows / max_result_bytes for fetch_all
+    shared_pool: Option<Arc<SharedPool>>, // Some when shared_pool=True; owns pool/pool_size/timeout
+    read_pool: Option<Arc<ReadPool>>,     // Some when read_pool_size is set
+    runtime: Option<Arc<DedicatedRuntime>>, // Some when dedicated_runtime=True
+    cursors: CursorRegistry,              // Cursors handed out, closed by close()
 }
 
-// Note: We do not implement Drop for Connection because:
+// Connection's Drop only releases its handles on the held pool connections;
+// it does not roll back or close anything because:
 // 1. PyO3 pyclass cleanup happens in Python's GC, which may not have Tokio context
 // 2. Async cleanup (transaction rollback, connection release) requires async context
 // 3. The close() method handles all cleanup properly
 //
 // Resource cleanup behavior:
 // - Arc references will be automatically dropped when Connection is dropped
-// - Pool connections will be returned to pool when Arc<Mutex<Option<PoolConnection>>> is dropped
+// - Pool connections will be returned to pool when the last
+//   Arc<Mutex<Option<PoolConnection>>> is dropped, which needs a Tokio context
 // - However, active transactions will NOT be rolled back automatically
 // - Callback connections will be returned to pool when Arc is dropped
 //
@@ -99,6 +190,24 @@ pub(crate) struct Connection {
 // - Use async context managers: `async with rapsqlite.connect(...) as db:`
 // - Or call close() explicitly: `await db.close()`
 
+impl Drop for Connection {
+    fn drop(&mut self) {
+        // A pooled connection returns itself to the pool from a Tokio task,
+        // and a connection collected by the GC is dropped outside any runtime
+        let held = [
+            std::mem::take(&mut self.transaction_connection),
+            std::mem::take(&mut self.callback_connection),
+        ];
+        // Only enter the runtime when there is a connection to release, so an
+        // unused Connection does not start it
+        let holds = held
+            .iter()
+            .any(|conn| conn.try_lock().map_or(true, |conn| conn.is_some()));
+        let _guard = holds.then(|| pyo3_async_runtimes::tokio::get_runtime().enter());
+        drop(held);
+    }
+}
+
 #[pymethods]
 impl Connection {
     /// Create a new async SQLite connection.
@@ -120,6 +229,125 @@ impl Connection {
     ///   object and runs initialization code. Called once when the connection
     ///   pool is first used. This is a rapsqlite-specific enhancement for
     ///   automatic database initialization (schema setup, data seeding, etc.).
+    /// * `init_hook_scope` - `"once"` (default) to run `init_hook` once, as
+    ///   above, or `"connection"` to run it each time the pool opens a
+    ///   connection, for per-connection setup (temporary tables, PRAGMAs,
+    ///   ...). It then receives a `PooledConnection` (see `set_event_hook()`)
+    ///   whose statements run on the new connection, and runs before the
+    ///   "connect" event hook; if it raises, the connection is discarded.
+    /// * `timeout` - How long to wait (in seconds) when the database is locked.
+    /// * `strict` - Strict typing mode matching SQLite STRICT tables. Parameters
+    ///   that would need a lossy conversion (NaN, Decimal, arbitrary objects) are
+    ///   refused, and fetched values are decoded by declared column type instead
+    ///   of probing, raising DatabaseError when a stored value does not match it.
+    /// * `cache` - `"lru"` to cache `fetch_all` results, keyed by normalized query
+    ///   text and parameters. Writes made through rapsqlite invalidate the entries
+    ///   that read an affected table (following views, triggers and foreign key
+    ///   actions); commits by other connections or processes clear the whole
+    ///   cache. Queries inside a transaction are not cached. Default None.
+    /// * `cache_size` - Maximum number of cached results (default 128).
+    /// * `group_commit_window` - Seconds (e.g. 0.002) during which concurrent
+    ///   `execute()` writes (INSERT/UPDATE/DELETE outside a transaction) are
+    ///   coalesced into one transaction by a background writer, so the group pays
+    ///   for a single fsync. Each call returns once its group has committed; a
+    ///   failing statement only fails its own call. Not used while callbacks are
+    ///   registered. Default None (every write commits on its own).
+    /// * `debug` - Enable debug-mode diagnostics. N+1 detection: a statement
+    ///   run within one asyncio task with `n_plus_one_threshold` different
+    ///   values for its single parameter emits `NPlusOneWarning` (once per
+    ///   statement and task). Task-safety audit: `TaskSafetyWarning` when a
+    ///   task runs a statement after another task changed a `row_factory`,
+    ///   `text_factory`, `set_pragma()` or callback setting it had set, runs a
+    ///   statement inside another task's transaction or while a trace callback,
+    ///   authorizer, progress handler or update hook another task installed is
+    ///   set, or commits or rolls back another task's transaction. Default False.
+    /// * `n_plus_one_threshold` - Distinct parameter values that trigger the
+    ///   warning (default 10, minimum 2).
+    /// * `hardened` - Install an authorizer on every pooled connection that
+    ///   denies ATTACH/DETACH, setting PRAGMAs and schema changes (CREATE, DROP,
+    ///   ALTER; TEMP objects excepted), for applications that run semi-trusted
+    ///   SQL. Denied statements raise DatabaseError ("not authorized"). PRAGMAs
+    ///   given in `pragmas` stay allowed. Default False.
+    /// * `hardened_allow` - Exceptions for hardened mode: `"attach"`, `"schema"`
+    ///   and `"pragma:<name>"` entries.
+    /// * `max_rows` - Default row limit for `fetch_all` and `cached_fetch_all`: a
+    ///   query returning more rows is abandoned with `ResultTooLarge` instead of
+    ///   being read into memory. Default None (no limit).
+    /// * `max_result_bytes` - Default size limit for the same methods, counting
+    ///   the length of TEXT and BLOB values plus 8 bytes per number. Default None.
+    /// * `shared_pool` - Share one connection pool with every other Connection
+    ///   opened with `shared_pool=True` on the same file, PRAGMAs and hardened
+    ///   settings, instead of opening a pool per Connection. `pool_size` and
+    ///   `connection_timeout` are then shared too, so `pool_size` caps the OS
+    ///   connections of all of them. `close()` leaves a shared pool open for the
+    ///   other Connections; it closes once none of them is left. Not supported
+    ///   for ":memory:". Default False.
+    /// * `dedicated_runtime` - Run this Connection's work on a Tokio runtime of
+    ///   its own (one worker thread unless `configure_runtime()` sets
+    ///   `worker_threads`) instead of the process-wide one, isolating it from
+    ///   other Connections. The runtime stops when the Connection is garbage
+    ///   collected. Default False.
+    /// * `detect_types` - Pass fetched values through the converters registered
+    ///   with `register_converter()`, as sqlite3 does: `PARSE_DECLTYPES` selects
+    ///   the converter by the first word of the column's declared type,
+    ///   `PARSE_COLNAMES` by a `[type]` suffix on the column name (`SELECT d AS
+    ///   "d [date]"`), which is then left out of the name used for dict and Row
+    ///   keys. Combine both with `|`; a column name type takes precedence.
+    ///   Default 0 (no conversion).
+    /// * `native_functions` - Register SQL functions implemented in Rust on
+    ///   every pooled connection: `uuid4()` (random UUID text), `sha256(X)` and
+    ///   `md5(X)` (lowercase hex digest of TEXT or BLOB) and
+    ///   `regexp_replace(X, pattern, replacement)` (Rust regex syntax, `$1` for
+    ///   groups). Unlike `create_function()` they run without the GIL and do
+    ///   not route statements to a single callback connection. Default False.
+    /// * `wal_archive` - Directory to archive the database's commits into for
+    ///   `restore_to()`. The database is switched to WAL mode, a base snapshot
+    ///   is written each time the pool opens, and every commit made through the
+    ///   pool copies its WAL frames into the directory; rapsqlite then runs the
+    ///   WAL checkpoints itself (`wal_autocheckpoint` cannot be set). Commits by
+    ///   other processes are not archived. Not supported for ":memory:".
+    ///   Default None.
+    /// * `replication_hook` - Callable receiving a dict for each WAL event, so
+    ///   a replication tool can ship changes without polling the file system:
+    ///   `{"event": "frames_written", "generation", "frames", "wal_path"}`
+    ///   after every commit (`frames` is the number of frames now in the WAL),
+    ///   and `"checkpoint_start"` / `"checkpoint_finish"` (with `"checkpointed"`
+    ///   and `"ok"`) around each checkpoint. `generation` identifies the WAL's
+    ///   contents: it changes when the WAL restarts from frame 1 after a
+    ///   checkpoint. The callback runs on a database thread right after the
+    ///   commit, so it should only hand the event off (e.g. with
+    ///   `loop.call_soon_threadsafe`); its exceptions go to
+    ///   `sys.unraisablehook`. Implies WAL mode and rapsqlite-run checkpoints,
+    ///   as for `wal_archive`. Not supported for ":memory:" or with
+    ///   `shared_pool`. Default None.
+    /// * `auto_analyze` - Refresh the query planner's statistics when the pool
+    ///   opens: `PRAGMA optimize` checks every table and runs ANALYZE on those
+    ///   whose `sqlite_stat1` entries are missing (new tables and indexes) or
+    ///   stale because their row count changed a lot since. Each index scan is
+    ///   capped by `PRAGMA analysis_limit` (400 rows unless `pragmas` sets it),
+    ///   so opening a large database stays fast. Default False.
+    /// * `clock` - A `FakeClock` read by the SQL date and time functions
+    ///   (`datetime('now')`, `unixepoch()`, `CURRENT_TIMESTAMP`, ...) instead
+    ///   of the system time, for reproducible tests. Several Connections can
+    ///   share one clock. Default None.
+    /// * `faults` - A `FaultInjector` whose SQLITE_BUSY, I/O errors and
+    ///   latency apply to this Connection's database, journal and WAL files,
+    ///   for testing retry logic and timeout handling. Default None.
+    /// * `read_pool_size` - Open a second pool of this many read-only
+    ///   connections (`mode=ro`) next to the Connection's own pool, which then
+    ///   becomes the writer. `fetch_all()`, `fetch_one()`, `fetch_optional()`,
+    ///   `cached_fetch_all()` and `stream()` run their query on a reader when
+    ///   every statement in it only reads (see `is_readonly_query()`); all other
+    ///   statements, and everything inside a transaction or while callbacks are
+    ///   registered, use the writer. With `journal_mode` WAL the readers run
+    ///   alongside the writer's transactions. Not supported for ":memory:" or
+    ///   with `shared_pool`. Default None (one pool).
+    /// * `write_pool_size` - Size of the writer pool when `read_pool_size` is
+    ///   set (sets `pool_size`). Default 1, so writes are serialized.
+    /// * `max_queue` - When every pooled connection is in use and this many
+    ///   tasks already wait for one, raise `PoolTimeout` at once instead of
+    ///   queuing another (0: never wait on a saturated pool). Waiting tasks are
+    ///   served first come, first served. Default None (no limit).
     ///
     /// # Returns
     ///
@@ -157,17 +385,96 @@ impl Connection {
     ///         # Database is already initialized
     ///         pass
     #[new]
-    #[pyo3(signature = (path, *, pragmas = None, init_hook = None, timeout = 5.0))]
+    #[pyo3(signature = (
+        path,
+        *,
+        pragmas = None,
+        init_hook = None,
+        timeout = 5.0,
+        strict = false,
+        cache = None,
+        cache_size = 128,
+        group_commit_window = None,
+        debug = false,
+        n_plus_one_threshold = 10,
+        hardened = false,
+        hardened_allow = None,
+        max_rows = None,
+        max_result_bytes = None,
+        shared_pool = false,
+        dedicated_runtime = false,
+        detect_types = 0,
+        native_functions = false,
+        wal_archive = None,
+        replication_hook = None,
+        auto_analyze = false,
+        init_hook_scope = "once",
+        clock = None,
+        faults = None,
+        read_pool_size = None,
+        write_pool_size = None,
+        max_queue = None
+    ))]
+    #[allow(clippy::too_many_arguments)]
     fn new(
+        py: Python<'_>,
         path: String,
         pragmas: Option<&Bound<'_, pyo3::types::PyDict>>,
         init_hook: Option<Py<PyAny>>,
         timeout: f64,
+        strict: bool,
+        cache: Option<&str>,
+        cache_size: usize,
+        group_commit_window: Option<f64>,
+        debug: bool,
+        n_plus_one_threshold: usize,
+        hardened: bool,
+        hardened_allow: Option<Vec<String>>,
+        max_rows: Option<usize>,
+        max_result_bytes: Option<usize>,
+        shared_pool: bool,
+        dedicated_runtime: bool,
+        detect_types: u32,
+        native_functions: bool,
+        wal_archive: Option<String>,
+        replication_hook: Option<Py<PyAny>>,
+        auto_analyze: bool,
+        init_hook_scope: &str,
+        clock: Option<PyRef<'_, FakeClock>>,
+        faults: Option<PyRef<'_, FaultInjector>>,
+        read_pool_size: Option<usize>,
+        write_pool_size: Option<usize>,
+        max_queue: Option<usize>,
     ) -> PyResult<Self> {
         // Validate timeout (must be non-negative)
         if timeout < 0.0 {
             return Err(ValueError::new_err("timeout must be >= 0.0"));
         }
+        let per_connection_init = match init_hook_scope {
+            "once" => false,
+            "connection" => true,
+            _ => {
+                return Err(ValueError::new_err(format!(
+                    "init_hook_scope must be 'once' or 'connection', got {init_hook_scope:?}"
+                )))
+            }
+        };
+        let group_commit_window = group_commit_window
+            .map(|window| match Duration::try_from_secs_f64(window) {
+                Ok(window) if !window.is_zero() => Ok(window),
+                _ => Err(ValueError::new_err(
+                    "group_commit_window must be a finite number > 0",
+                )),
+            })
+            .transpose()?;
+        match cache {
+            None | Some("lru") => {}
+            Some(other) => {
+                return Err(ValueError::new_err(format!(
+                    "cache must be None or 'lru', got {other:?}"
+                )))
+            }
+        }
         // Parse connection string if it's a URI
         let (db_path, uri_params) = parse_connection_string(&path)?;
         validate_path(&db_path)?;
@@ -185,27 +492,191 @@ impl Connection {
             for item in pragmas_dict.iter() {
                 let (key, value) = item; // iter() returns tuples directly in pyo3 0.27
                 let key_str = key.extract::<String>()?;
-                let value_str = value.to_string();
+                // Strings stay bare so keywords like WAL can be compared below
+                let value_str = match value.extract::<String>() {
+                    Ok(keyword) => keyword,
+                    Err(_) => pragma_literal(&value)?,
+                };
                 all_pragmas.push((key_str, value_str));
             }
         }
+        if auto_analyze {
+            // 0x10000 checks all tables, not only those this connection has
+            // queried; 0x02 runs ANALYZE where the statistics need it
+            if !all_pragmas
+                .iter()
+                .any(|(name, _)| name.eq_ignore_ascii_case("analysis_limit"))
+            {
+                all_pragmas.push(("analysis_limit".to_string(), "400".to_string()));
+            }
+            all_pragmas.push(("optimize".to_string(), "0x10002".to_string()));
+        }
+
+        let statement_policy = if hardened {
+            let mut policy = StatementPolicy::from_allowlist(
+                hardened_allow.iter().flatten().map(String::as_str),
+            )
+            .map_err(ValueError::new_err)?;
+            // Configured PRAGMAs are applied when the pool opens; busy_timeout is
+            // set internally when transactions begin
+            policy.pragmas.extend(
+                all_pragmas
+                    .iter()
+                    .map(|(name, _)| name.to_ascii_lowercase())
+                    .chain(["busy_timeout".to_string()]),
+            );
+            Some(Arc::new(policy))
+        } else if hardened_allow.is_some() {
+            return Err(ValueError::new_err("hardened_allow requires hardened=True"));
+        } else {
+            None
+        };
+
+        let result_cache = cache.map(|_| result_cache::register(&db_path, cache_size));
+
+        if shared_pool && db_path == ":memory:" {
+            return Err(ValueError::new_err(
+                "shared_pool is not supported for in-memory databases",
+            ));
+        }
+        let uses_wal_hook = wal_archive.is_some() || replication_hook.is_some();
+        if uses_wal_hook && db_path == ":memory:" {
+            return Err(ValueError::new_err(
+                "wal_archive and replication_hook are not supported for in-memory databases",
+            ));
+        }
+        if uses_wal_hook
+            && all_pragmas.iter().any(|(name, value)| {
+                name.eq_ignore_ascii_case("wal_autocheckpoint")
+                    || (name.eq_ignore_ascii_case("journal_mode")
+                        && !value.eq_ignore_ascii_case("wal"))
+            })
+        {
+            return Err(ValueError::new_err(
+                "wal_archive and replication_hook require journal_mode WAL and manage wal_autocheckpoint themselves",
+            ));
+        }
+        if replication_hook.is_some() && shared_pool {
+            return Err(ValueError::new_err(
+                "replication_hook is not supported with shared_pool",
+            ));
+        }
+        match (read_pool_size, write_pool_size) {
+            (None, Some(_)) => {
+                return Err(ValueError::new_err(
+                    "write_pool_size requires read_pool_size; use pool_size",
+                ))
+            }
+            (Some(0), _) | (_, Some(0)) => {
+                return Err(ValueError::new_err(
+                    "read_pool_size and write_pool_size must be >= 1",
+                ))
+            }
+            (Some(_), _) if db_path == ":memory:" || shared_pool => {
+                return Err(ValueError::new_err(
+                    "read_pool_size is not supported for in-memory databases or with shared_pool",
+                ))
+            }
+            _ => {}
+        }
+        let archive = wal_archive
+            .map(|dir| crate::pool::wal_archive(&dir))
+            .transpose()?;
+        let wal_hook = uses_wal_hook.then(|| {
+            Arc::new(WalHook {
+                archive,
+                listener: replication_hook.map(replication::listener),
+            })
+        });
+        let clock = clock.map(|c| c.clock.clone());
+        let clock_vfs = clock.as_ref().map(CoreClock::vfs_name);
+        let vfs = match faults {
+            Some(faults) => Some(faults.injector.vfs_name(clock_vfs)),
+            None => clock_vfs,
+        }
+        .map(str::to_string);
+        let shared_pool = shared_pool.then(|| {
+            crate::pool::shared_pool(
+                &db_path,
+                &all_pragmas,
+                statement_policy.as_deref(),
+                native_functions,
+                wal_hook.as_ref().and_then(|h| h.archive.as_deref()),
+                vfs.as_deref(),
+            )
+        });
+
+        let event_hooks = shared_pool.as_ref().map_or_else(
+            || Arc::new(EventHooks::new(&db_path)),
+            |s| Arc::clone(&s.event_hooks),
+        );
+        if max_queue.is_some() {
+            event_hooks.queue().set_max_queue(max_queue);
+        }
+        let init_hook = match init_hook {
+            Some(init_hook) if per_connection_init => {
+                event_hooks.set_init_hook(init_hook);
+                None
+            }
+            init_hook => init_hook,
+        };
+        let pragmas = Arc::new(StdMutex::new(all_pragmas));
+        let connection_timeout_secs = shared_pool.as_ref().map_or_else(
+            || Arc::new(StdMutex::new(None)),
+            |s| Arc::clone(&s.connection_timeout_secs),
+        );
+        let read_pool = read_pool_size.map(|size| {
+            Arc::new(ReadPool::new(
+                size,
+                &pragmas,
+                &connection_timeout_secs,
+                &statement_policy,
+                native_functions,
+                &vfs,
+                &event_hooks,
+            ))
+        });
 
         Ok(Connection {
             path: db_path,
-            pool: Arc::new(Mutex::new(None)),
+            pool: shared_pool
+                .as_ref()
+                .map_or_else(|| Arc::new(Mutex::new(None)), |s| Arc::clone(&s.pool)),
             transaction_state: Arc::new(Mutex::new(TransactionState::None)),
             transaction_connection: Arc::new(Mutex::new(None)),
             last_rowid: Arc::new(Mutex::new(0)),
             last_changes: Arc::new(Mutex::new(0)),
-            pragmas: Arc::new(StdMutex::new(all_pragmas)),
+            pragmas,
             init_hook: Arc::new(StdMutex::new(init_hook)),
             init_hook_called: Arc::new(StdMutex::new(false)),
-            pool_size: Arc::new(StdMutex::new(None)),
-            connection_timeout_secs: Arc::new(StdMutex::new(None)),
+            pool_size: shared_pool.as_ref().map_or_else(
+                // A read pool leaves a single writer unless told otherwise
+                || {
+                    Arc::new(StdMutex::new(
+                        read_pool_size.map(|_| write_pool_size.unwrap_or(1)),
+                    ))
+                },
+                |s| Arc::clone(&s.pool_size),
+            ),
+            connection_timeout_secs,
             row_factory: Arc::new(StdMutex::new(None)),
             text_factory: Arc::new(StdMutex::new(None)),
+            option_overrides: py
+                .import("contextvars")?
+                .getattr("ContextVar")?
+                .call1(("rapsqlite_options",))?
+                .unbind(),
+            default_params: Arc::new(StdMutex::new(None)),
+            test_snapshot: Arc::new(StdMutex::new(None)),
+            null_floats_as_nan: Arc::new(StdMutex::new(false)),
+            large_int_policy: Arc::new(StdMutex::new(LargeIntPolicy::default())),
+            decode_large_ints: Arc::new(StdMutex::new(false)),
+            invalid_utf8: Arc::new(StdMutex::new(InvalidUtf8Policy::default())),
+            strict,
+            detect_types,
             // Prepared statement cache tracking (Phase 2.13)
             query_cache: Arc::new(StdMutex::new(HashMap::new())),
+            frozen_statements: StdMutex::new(None),
             // Callback infrastructure (Phase 2.7)
             callback_connection: Arc::new(Mutex::new(None)),
             load_extension_enabled: Arc::new(StdMutex::new(false)),
@@ -213,13 +684,48 @@ impl Connection {
             trace_callback: Arc::new(StdMutex::new(None)),
             authorizer_callback: Arc::new(StdMutex::new(None)),
             progress_handler: Arc::new(StdMutex::new(None)),
+            update_hook: Arc::new(StdMutex::new(None)),
+            hook_contexts: Arc::new(StdMutex::new(Default::default())),
+            udf_error: Arc::new(StdMutex::new(None)),
+            callback_dispatcher: Arc::new(StdMutex::new(None)),
+            callback_timeout: Arc::new(StdMutex::new(5.0)),
             include_query_in_errors: Arc::new(StdMutex::new(true)), // Default: include queries for debugging
+            redact_path: Arc::new(StdMutex::new(false)),
             timeout: Arc::new(StdMutex::new(timeout)), // SQLite busy_timeout in seconds (aiosqlite compatibility)
+            result_cache,
+            ttl_cache: Arc::new(StdMutex::new(TtlCache::default())),
+            group_commit_window,
+            group_committer: Arc::new(StdMutex::new(None)),
+            n_plus_one: debug
+                .then(|| Arc::new(StdMutex::new(NPlusOneDetector::new(n_plus_one_threshold)))),
+            task_audit: debug.then(|| Arc::new(StdMutex::new(TaskAudit::new()))),
+            statement_logger: Arc::new(StdMutex::new(None)),
+            statement_policy,
+            native_functions,
+            wal_hook,
+            vfs,
+            _clock: clock,
+            event_hooks,
+            fetch_limits: FetchLimits {
+                max_rows,
+                max_bytes: max_result_bytes,
+            },
+            shared_pool,
+            read_pool,
+            runtime: if dedicated_runtime {
+                Some(Arc::new(DedicatedRuntime::new()?))
+            } else {
+                None
+            },
+            cursors: CursorRegistry::default(),
         })
     }
 
     #[getter(row_factory)]
     fn row_factory(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
+        if let Some(value) = self.option_override(py, "row_factory")? {
+            return Ok(value.unwrap_or_else(|| py.None()));
+        }
         let guard = self.row_factory.lock().unwrap();
         Ok(match guard.as_ref() {
             Some(f) => f.clone_ref(py),
@@ -229,6 +735,7 @@ impl Connection {
 
     #[setter(row_factory)]
     fn set_row_factory(&self, value: &Bound<'_, PyAny>) -> PyResult<()> {
+        audit_setting(&self.task_audit, "row_factory")?;
         let mut guard = self.row_factory.lock().unwrap();
         *guard = if value.is_none() {
             None
@@ -269,6 +776,11 @@ impl Connection {
         let pragmas = Arc::clone(&self.pragmas);
         let pool_size = Arc::clone(&self.pool_size);
         let connection_timeout_secs = Arc::clone(&self.connection_timeout_secs);
+        let statement_policy = self.statement_policy.clone();
+        let native_functions = self.native_functions;
+        let wal_hook = self.wal_hook.clone();
+        let vfs = self.vfs.clone();
+        let event_hooks = Arc::clone(&self.event_hooks);
         let transaction_state = Arc::clone(&self.transaction_state);
         let transaction_connection = Arc::clone(&self.transaction_connection);
         let load_extension_enabled = Arc::clone(&self.load_extension_enabled);
@@ -276,6 +788,7 @@ impl Connection {
         let trace_callback = Arc::clone(&self.trace_callback);
         let authorizer_callback = Arc::clone(&self.authorizer_callback);
         let progress_handler = Arc::clone(&self.progress_handler);
+        let update_hook = Arc::clone(&self.update_hook);
 
         Python::attach(|py| {
             let future = async move {
@@ -304,6 +817,7 @@ impl Connection {
                         &trace_callback,
                         &authorizer_callback,
                         &progress_handler,
+                        &update_hook,
                     );
 
                     if has_callbacks_flag {
@@ -315,6 +829,11 @@ impl Connection {
                             &pragmas,
                             &pool_size,
                             &connection_timeout_secs,
+                            &statement_policy,
+                            native_functions,
+                            &wal_hook,
+                            &vfs,
+                            &event_hooks,
                         )
                         .await?;
 
@@ -335,6 +854,11 @@ impl Connection {
                             &pragmas,
                             &pool_size,
                             &connection_timeout_secs,
+                            &statement_policy,
+                            native_functions,
+                            &wal_hook,
+                            &vfs,
+                            &event_hooks,
                         )
                         .await?;
                         let pool_size_val = {
@@ -378,7 +902,7 @@ impl Connection {
 
                 Ok(total as u64)
             };
-            future_into_py(py, future).map(|bound| bound.unbind())
+            runtime::future_into_py(py, self.runtime.as_deref(), future).map(|bound| bound.unbind())
         })
     }
 
@@ -415,12 +939,15 @@ impl Connection {
                 let trans_guard = transaction_state.lock().await;
                 Ok(*trans_guard == TransactionState::Active)
             };
-            future_into_py(py, future).map(|bound| bound.unbind())
+            runtime::future_into_py(py, self.runtime.as_deref(), future).map(|bound| bound.unbind())
         })
     }
 
     #[getter(text_factory)]
     fn text_factory(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
+        if let Some(value) = self.option_override(py, "text_factory")? {
+            return Ok(value.unwrap_or_else(|| py.None()));
+        }
         let guard = self.text_factory.lock().unwrap();
         Ok(match guard.as_ref() {
             Some(f) => f.clone_ref(py),
@@ -430,6 +957,7 @@ impl Connection {
 
     #[setter(text_factory)]
     fn set_text_factory(&self, value: &Bound<'_, PyAny>) -> PyResult<()> {
+        audit_setting(&self.task_audit, "text_factory")?;
         let mut guard = self.text_factory.lock().unwrap();
         *guard = if value.is_none() {
             None
@@ -439,6 +967,123 @@ impl Connection {
         Ok(())
     }
 
+    /// Get whether NULL values in REAL columns are fetched as `float("nan")`.
+    ///
+    /// When False (default), NULL is fetched as None. SQLite stores NaN as NULL, so
+    /// setting this to True also makes NaN round-trip through REAL columns, which is
+    /// convenient for dataframe-style output. Infinite values always round-trip.
+    #[getter(null_floats_as_nan)]
+    fn null_floats_as_nan(&self) -> PyResult<bool> {
+        let guard = self.null_floats_as_nan.lock().unwrap();
+        Ok(*guard)
+    }
+
+    /// Set whether NULL values in REAL columns are fetched as `float("nan")`.
+    #[setter(null_floats_as_nan)]
+    fn set_null_floats_as_nan(&self, value: bool) -> PyResult<()> {
+        let mut guard = self.null_floats_as_nan.lock().unwrap();
+        *guard = value;
+        Ok(())
+    }
+
+    /// Get how Python ints outside the signed 64-bit range are bound.
+    ///
+    /// "error" (default) raises OverflowError. "text" binds the decimal string and
+    /// "blob" binds the big-endian two's complement bytes
+    /// (`int.from_bytes(b, "big", signed=True)` reverses it). Note that SQLite
+    /// converts oversized integer TEXT to REAL in INTEGER/NUMERIC columns, so store
+    /// such values in TEXT, BLOB or untyped columns. The policy also applies to ints
+    /// returned by `create_function()` callbacks.
+    #[getter(large_int_policy)]
+    fn large_int_policy(&self) -> PyResult<&'static str> {
+        let guard = self.large_int_policy.lock().unwrap();
+        Ok(guard.as_str())
+    }
+
+    /// Set how Python ints outside the signed 64-bit range are bound.
+    #[setter(large_int_policy)]
+    fn set_large_int_policy(&self, value: &str) -> PyResult<()> {
+        let policy = LargeIntPolicy::parse(value).ok_or_else(|| {
+            ValueError::new_err(format!(
+                "Invalid large_int_policy: {value:?}. Expected \"error\", \"text\" or \"blob\"."
+            ))
+        })?;
+        let mut guard = self.large_int_policy.lock().unwrap();
+        *guard = policy;
+        Ok(())
+    }
+
+    /// Get whether TEXT values holding an integer outside the i64 range are fetched as int.
+    ///
+    /// When False (default), such values are fetched as str. Set to True to read back
+    /// values stored with `large_int_policy = "text"`.
+    #[getter(decode_large_ints)]
+    fn decode_large_ints(&self) -> PyResult<bool> {
+        let guard = self.decode_large_ints.lock().unwrap();
+        Ok(*guard)
+    }
+
+    /// Set whether TEXT values holding an integer outside the i64 range are fetched as int.
+    #[setter(decode_large_ints)]
+    fn set_decode_large_ints(&self, value: bool) -> PyResult<()> {
+        let mut guard = self.decode_large_ints.lock().unwrap();
+        *guard = value;
+        Ok(())
+    }
+
+    /// Get how TEXT values that are not valid UTF-8 are returned.
+    ///
+    /// Applies to fetched values and to user-defined function arguments alike.
+    /// "bytes" (default) returns the raw bytes, "replace" decodes them with
+    /// U+FFFD replacement characters and "error" raises (OperationalError on
+    /// fetch; the function call fails for UDF arguments). A `text_factory`
+    /// receives such values as bytes regardless of the policy.
+    #[getter(invalid_utf8_policy)]
+    fn invalid_utf8_policy(&self) -> PyResult<&'static str> {
+        let guard = self.invalid_utf8.lock().unwrap();
+        Ok(guard.as_str())
+    }
+
+    /// Set how TEXT values that are not valid UTF-8 are returned.
+    #[setter(invalid_utf8_policy)]
+    fn set_invalid_utf8_policy(&self, value: &str) -> PyResult<()> {
+        let policy = InvalidUtf8Policy::parse(value).ok_or_else(|| {
+            ValueError::new_err(format!(
+                "Invalid invalid_utf8_policy: {value:?}. Expected \"error\", \"replace\" or \"bytes\"."
+            ))
+        })?;
+        let mut guard = self.invalid_utf8.lock().unwrap();
+        *guard = policy;
+        Ok(())
+    }
+
+    /// Whether the connection was opened with `strict=True`.
+    ///
+    /// In strict mode binding refuses lossy conversions (NaN, non-SQLite types) and
+    /// fetch decodes by declared column type, raising on mismatched values.
+    #[getter(strict)]
+    fn strict(&self) -> PyResult<bool> {
+        Ok(self.strict)
+    }
+
+    /// The `detect_types` flags the connection was opened with.
+    #[getter(detect_types)]
+    fn detect_types(&self) -> PyResult<u32> {
+        Ok(self.detect_types)
+    }
+
+    /// Tasks that may wait for a connection of a saturated pool before
+    /// `PoolTimeout` is raised at once (None: no limit).
+    #[getter(max_queue)]
+    fn max_queue(&self) -> Option<usize> {
+        self.event_hooks.queue().max_queue()
+    }
+
+    #[setter(max_queue)]
+    fn set_max_queue(&self, value: Option<usize>) {
+        self.event_hooks.queue().set_max_queue(value);
+    }
+
     #[getter(pool_size)]
     fn pool_size(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
         let guard = self.pool_size.lock().unwrap();
@@ -506,6 +1151,180 @@ impl Connection {
         Ok(())
     }
 
+    /// Whether `repr()`, `str()` and `debug_info()` of the connection and its
+    /// cursors show `<redacted>` instead of the database path, so they can be
+    /// logged without revealing it. Default False.
+    #[getter(redact_path)]
+    fn redact_path(&self) -> bool {
+        *self.redact_path.lock().unwrap()
+    }
+
+    #[setter(redact_path)]
+    fn set_redact_path(&self, value: bool) {
+        *self.redact_path.lock().unwrap() = value;
+    }
+
+    /// A snapshot of the connection's state for logs and debugging sessions.
+    ///
+    /// A dict with `path` (`"<redacted>"` under `redact_path`), `pool_open`,
+    /// `pool_size` and `pool_idle` (connections open and idle now),
+    /// `pool_max_size`, `transaction` ("none", "starting" or "active"),
+    /// `row_factory` and `text_factory` (as in effect for the current task),
+    /// `open_cursors`, `shared_pool`, `read_pool` and `dedicated_runtime`
+    /// (whether those options are used), and `queue` (`queue_stats()`).
+    /// Pool and transaction values are None while another task is opening or
+    /// closing the pool or changing the transaction.
+    fn debug_info<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let dict = PyDict::new(py);
+        dict.set_item("path", self.display_path())?;
+        match self.pool_summary() {
+            Some(pool) => {
+                let (size, idle, max_size) = pool.unwrap_or_default();
+                dict.set_item("pool_open", pool.is_some())?;
+                dict.set_item("pool_size", size)?;
+                dict.set_item("pool_idle", idle)?;
+                dict.set_item("pool_max_size", pool.map(|_| max_size))?;
+            }
+            None => {
+                for key in ["pool_open", "pool_size", "pool_idle", "pool_max_size"] {
+                    dict.set_item(key, py.None())?;
+                }
+            }
+        }
+        dict.set_item("transaction", self.transaction_label())?;
+        dict.set_item("row_factory", self.row_factory(py)?)?;
+        dict.set_item("text_factory", self.text_factory(py)?)?;
+        dict.set_item("open_cursors", self.cursors.open_count())?;
+        dict.set_item("shared_pool", self.shared_pool.is_some())?;
+        dict.set_item("read_pool", self.read_pool.is_some())?;
+        dict.set_item("dedicated_runtime", self.runtime.is_some())?;
+        dict.set_item("queue", self.queue_stats(py)?)?;
+        Ok(dict)
+    }
+
+    /// One-line summary: path, pool, transaction and the factories set.
+    fn __str__(&self, py: Python<'_>) -> PyResult<String> {
+        let pool = match self.pool_summary() {
+            Some(Some((size, idle, max_size))) => format!("{size}/{max_size} idle={idle}"),
+            Some(None) => "closed".to_string(),
+            None => "?".to_string(),
+        };
+        let mut summary = format!(
+            "<Connection path={} pool={pool} transaction={}",
+            self.path_repr(py)?,
+            self.transaction_label().unwrap_or("?")
+        );
+        for (name, factory) in [
+            ("row_factory", self.row_factory(py)?),
+            ("text_factory", self.text_factory(py)?),
+        ] {
+            let factory = factory.bind(py);
+            if !factory.is_none() {
+                summary.push_str(&format!(" {name}={}", factory.repr()?));
+            }
+        }
+        summary.push('>');
+        Ok(summary)
+    }
+
+    fn __repr__(&self, py: Python<'_>) -> PyResult<String> {
+        self.__str__(py)
+    }
+
+    /// Connections own a connection pool and OS handles bound to this process,
+    /// so pickling one (e.g. passing it to a multiprocessing worker) raises a
+    /// TypeError pointing to `ConnectionSpec`, which workers can connect from.
+    fn __reduce__(&self) -> PyResult<Py<PyAny>> {
+        Err(pyo3::exceptions::PyTypeError::new_err(
+            "rapsqlite.Connection objects cannot be pickled: they own a connection pool \
+             bound to this process. Pass rapsqlite.ConnectionSpec(path, options) to the \
+             worker instead and open a connection there with `spec.connect()`",
+        ))
+    }
+
+    /// Report the Python objects the connection holds to the garbage
+    /// collector, so reference cycles through them (e.g. a UDF or hook closure
+    /// capturing the connection) are collected. Slots locked by a running
+    /// operation are skipped, which at worst keeps a cycle alive until the
+    /// next collection.
+    fn __traverse__(&self, visit: PyVisit<'_>) -> Result<(), PyTraverseError> {
+        visit.call(&self.option_overrides)?;
+        for slot in [
+            &self.row_factory,
+            &self.text_factory,
+            &self.init_hook,
+            &self.trace_callback,
+            &self.authorizer_callback,
+            &self.update_hook,
+        ] {
+            if let Ok(value) = slot.try_lock() {
+                visit.call(value.as_ref())?;
+            }
+        }
+        if let Ok(params) = self.default_params.try_lock() {
+            visit.call(params.as_ref())?;
+        }
+        if let Ok(handler) = self.progress_handler.try_lock() {
+            visit.call(handler.as_ref().map(|(_, callback)| callback))?;
+        }
+        if let Ok(functions) = self.user_functions.try_lock() {
+            for function in functions.values() {
+                visit.call(&**function)?;
+            }
+        }
+        if let Ok(logger) = self.statement_logger.try_lock() {
+            if let Some(logger) = logger.as_ref() {
+                logger.traverse(&visit)?;
+            }
+        }
+        if let Ok(contexts) = self.hook_contexts.try_lock() {
+            contexts.traverse(&visit)?;
+        }
+        // Hooks of a shared pool are reported by none of its Connections
+        if self.shared_pool.is_none() {
+            self.event_hooks.traverse(&visit)?;
+        }
+        Ok(())
+    }
+
+    /// Drop the callbacks and factories held by a connection the garbage
+    /// collector found unreachable. Contexts registered with SQLite are kept
+    /// until the connection itself is dropped.
+    fn __clear__(&mut self) {
+        // Values are dropped once their locks are released: dropping them may
+        // run Python code using the connection
+        let mut dropped: Vec<Py<PyAny>> = Vec::new();
+        for slot in [
+            &self.row_factory,
+            &self.text_factory,
+            &self.init_hook,
+            &self.trace_callback,
+            &self.authorizer_callback,
+            &self.update_hook,
+        ] {
+            if let Ok(mut value) = slot.try_lock() {
+                dropped.extend(value.take());
+            }
+        }
+        if let Ok(mut params) = self.default_params.try_lock() {
+            dropped.extend(params.take().map(Py::into_any));
+        }
+        if let Ok(mut handler) = self.progress_handler.try_lock() {
+            dropped.extend(handler.take().map(|(_, callback)| callback));
+        }
+        let functions: Vec<_> = self
+            .user_functions
+            .try_lock()
+            .map(|mut functions| functions.drain().collect())
+            .unwrap_or_default();
+        let logger = self
+            .statement_logger
+            .try_lock()
+            .ok()
+            .and_then(|mut logger| logger.take());
+        drop((dropped, functions, logger));
+    }
+
     /// Get the SQLite busy_timeout value (in seconds).
     ///
     /// This controls how long SQLite will wait when the database is locked by another
@@ -535,6 +1354,67 @@ impl Connection {
         Ok(())
     }
 
+    /// Get how Python callbacks (UDFs, progress handler, trace) are executed.
+    ///
+    /// "inline" (default) calls Python directly on the thread running the statement.
+    /// "thread" marshals calls to a dedicated callback thread through a bounded queue;
+    /// the statement waits at most `callback_timeout` seconds for each call.
+    #[getter(callback_mode)]
+    fn callback_mode(&self) -> PyResult<&'static str> {
+        let guard = self.callback_dispatcher.lock().unwrap();
+        Ok(if guard.is_some() { "thread" } else { "inline" })
+    }
+
+    /// Set how Python callbacks (UDFs, progress handler, trace) are executed.
+    ///
+    /// In "thread" mode a UDF that times out or finds the queue full fails its
+    /// statement, a progress handler that times out lets the statement continue,
+    /// and trace callbacks are queued without waiting. The mode applies immediately,
+    /// including to callbacks that are already registered.
+    #[setter(callback_mode)]
+    fn set_callback_mode(&self, value: &str) -> PyResult<()> {
+        let mut guard = self.callback_dispatcher.lock().unwrap();
+        match value {
+            "inline" => *guard = None,
+            "thread" => {
+                if guard.is_none() {
+                    let timeout = *self.callback_timeout.lock().unwrap();
+                    *guard = Some(Arc::new(CallbackDispatcher::new(timeout)?));
+                }
+            }
+            _ => {
+                return Err(ValueError::new_err(format!(
+                    "Invalid callback_mode: {value:?}. Expected \"inline\" or \"thread\"."
+                )))
+            }
+        }
+        Ok(())
+    }
+
+    /// Get the maximum time (in seconds) a statement waits for a Python callback
+    /// in "thread" callback mode, or for an `async def` UDF. Default: 5.0 seconds.
+    #[getter(callback_timeout)]
+    fn callback_timeout(&self) -> PyResult<f64> {
+        let guard = self.callback_timeout.lock().unwrap();
+        Ok(*guard)
+    }
+
+    /// Set the maximum time (in seconds) a statement waits for a Python callback
+    /// in "thread" callback mode, or for an `async def` UDF. Must be > 0.0.
+    #[setter(callback_timeout)]
+    fn set_callback_timeout(&self, value: f64) -> PyResult<()> {
+        if !(value > 0.0 && value.is_finite()) {
+            return Err(ValueError::new_err("callback_timeout must be > 0.0"));
+        }
+        *self.callback_timeout.lock().unwrap() = value;
+        // Restart the dispatcher so the new timeout takes effect
+        let mut guard = self.callback_dispatcher.lock().unwrap();
+        if guard.is_some() {
+            *guard = Some(Arc::new(CallbackDispatcher::new(value)?));
+        }
+        Ok(())
+    }
+
     #[setter(connection_timeout)]
     fn set_connection_timeout(&self, value: &Bound<'_, PyAny>) -> PyResult<()> {
         let mut guard = self.connection_timeout_secs.lock().unwrap();
@@ -564,10 +1444,51 @@ impl Connection {
 
     /// Async context manager entry.
     fn __aenter__(slf: PyRef<Self>) -> PyResult<Py<PyAny>> {
+        let dedicated_runtime = slf.runtime.clone();
         let slf: Py<Self> = slf.into();
         Python::attach(|py| {
             let future = async move { Ok(slf) };
-            future_into_py(py, future).map(|bound| bound.unbind())
+            runtime::future_into_py(py, dedicated_runtime.as_deref(), future)
+                .map(|bound| bound.unbind())
+        })
+    }
+
+    /// `conn = await connect(path)`, as in aiosqlite: opens the connection pool,
+    /// so a database that cannot be opened fails here rather than on the first
+    /// query, and resolves to the connection. Close it with `close()`.
+    fn __await__(slf: PyRef<Self>) -> PyResult<Py<PyAny>> {
+        let path = slf.path.clone();
+        let pool = Arc::clone(&slf.pool);
+        let pragmas = Arc::clone(&slf.pragmas);
+        let pool_size = Arc::clone(&slf.pool_size);
+        let connection_timeout_secs = Arc::clone(&slf.connection_timeout_secs);
+        let statement_policy = slf.statement_policy.clone();
+        let native_functions = slf.native_functions;
+        let wal_hook = slf.wal_hook.clone();
+        let vfs = slf.vfs.clone();
+        let event_hooks = Arc::clone(&slf.event_hooks);
+        let dedicated_runtime = slf.runtime.clone();
+        let slf: Py<Self> = slf.into();
+        Python::attach(|py| {
+            let future = async move {
+                get_or_create_pool(
+                    &path,
+                    &pool,
+                    &pragmas,
+                    &pool_size,
+                    &connection_timeout_secs,
+                    &statement_policy,
+                    native_functions,
+                    &wal_hook,
+                    &vfs,
+                    &event_hooks,
+                )
+                .await?;
+                Ok(slf)
+            };
+            runtime::future_into_py(py, dedicated_runtime.as_deref(), future)?
+                .call_method0("__await__")
+                .map(|bound| bound.unbind())
         })
     }
 
@@ -578,7 +1499,11 @@ impl Connection {
         _exc_val: &Bound<'_, PyAny>,
         _exc_tb: &Bound<'_, PyAny>,
     ) -> PyResult<Py<PyAny>> {
+        self.cursors.close_all();
         let pool = Arc::clone(&self.pool);
+        let shared = self.shared_pool.is_some();
+        let read_pool = self.read_pool.clone();
+        let group_committer = Arc::clone(&self.group_committer);
         let transaction_state = Arc::clone(&self.transaction_state);
         let transaction_connection = Arc::clone(&self.transaction_connection);
         let callback_connection = Arc::clone(&self.callback_connection);
@@ -586,6 +1511,7 @@ impl Connection {
         let trace_callback = Arc::clone(&self.trace_callback);
         let authorizer_callback = Arc::clone(&self.authorizer_callback);
         let progress_handler = Arc::clone(&self.progress_handler);
+        let update_hook = Arc::clone(&self.update_hook);
         Python::attach(|py| {
             let future = async move {
                 // Clear all callbacks before closing
@@ -613,6 +1539,12 @@ impl Connection {
                     *progress_guard = None;
                 }
 
+                // Clear update hook
+                {
+                    let mut update_guard = update_hook.lock().unwrap();
+                    *update_guard = None;
+                }
+
                 // Clear callback connection (callbacks are cleared, connection returns to pool)
                 {
                     let mut callback_guard = callback_connection.lock().await;
@@ -633,21 +1565,34 @@ impl Connection {
                     *trans_guard = TransactionState::None;
                 }
 
-                // Close pool
-                let mut pool_guard = pool.lock().await;
-                if let Some(p) = pool_guard.take() {
-                    p.close().await;
+                // Stop the group-commit writer; queued writes still commit
+                group_committer.lock().unwrap().take();
+
+                // Close pool, unless other Connections share it
+                if !shared {
+                    let mut pool_guard = pool.lock().await;
+                    if let Some(p) = pool_guard.take() {
+                        p.close().await;
+                    }
+                }
+                if let Some(read_pool) = read_pool {
+                    read_pool.close().await;
                 }
 
                 Ok(())
             };
-            future_into_py(py, future).map(|bound| bound.unbind())
+            runtime::future_into_py(py, self.runtime.as_deref(), future).map(|bound| bound.unbind())
         })
     }
 
     /// Close the connection.
     fn close(&self) -> PyResult<Py<PyAny>> {
+        // Cursors handed out so far must not serve rows of the closed connection
+        self.cursors.close_all();
         let pool = Arc::clone(&self.pool);
+        let shared = self.shared_pool.is_some();
+        let read_pool = self.read_pool.clone();
+        let group_committer = Arc::clone(&self.group_committer);
         let transaction_state = Arc::clone(&self.transaction_state);
         let transaction_connection = Arc::clone(&self.transaction_connection);
         let callback_connection = Arc::clone(&self.callback_connection);
@@ -655,6 +1600,7 @@ impl Connection {
         let trace_callback = Arc::clone(&self.trace_callback);
         let authorizer_callback = Arc::clone(&self.authorizer_callback);
         let progress_handler = Arc::clone(&self.progress_handler);
+        let update_hook = Arc::clone(&self.update_hook);
         Python::attach(|py| {
             let future = async move {
                 // Clear all callbacks before closing
@@ -674,6 +1620,10 @@ impl Connection {
                     let mut progress_guard = progress_handler.lock().unwrap();
                     *progress_guard = None;
                 }
+                {
+                    let mut update_guard = update_hook.lock().unwrap();
+                    *update_guard = None;
+                }
                 {
                     let mut callback_guard = callback_connection.lock().await;
                     callback_guard.take();
@@ -693,15 +1643,23 @@ impl Connection {
                     *trans_guard = TransactionState::None;
                 }
 
-                // Close pool
-                let mut pool_guard = pool.lock().await;
-                if let Some(p) = pool_guard.take() {
-                    p.close().await;
+                // Stop the group-commit writer; queued writes still commit
+                group_committer.lock().unwrap().take();
+
+                // Close pool, unless other Connections share it
+                if !shared {
+                    let mut pool_guard = pool.lock().await;
+                    if let Some(p) = pool_guard.take() {
+                        p.close().await;
+                    }
+                }
+                if let Some(read_pool) = read_pool {
+                    read_pool.close().await;
                 }
 
                 Ok(())
             };
-            future_into_py(py, future).map(|bound| bound.unbind())
+            runtime::future_into_py(py, self.runtime.as_deref(), future).map(|bound| bound.unbind())
         })
     }
 
@@ -712,6 +1670,11 @@ impl Connection {
         let pragmas = Arc::clone(&self_.pragmas);
         let pool_size = Arc::clone(&self_.pool_size);
         let connection_timeout_secs = Arc::clone(&self_.connection_timeout_secs);
+        let statement_policy = self_.statement_policy.clone();
+        let native_functions = self_.native_functions;
+        let wal_hook = self_.wal_hook.clone();
+        let vfs = self_.vfs.clone();
+        let event_hooks = Arc::clone(&self_.event_hooks);
         let transaction_state = Arc::clone(&self_.transaction_state);
         let transaction_connection = Arc::clone(&self_.transaction_connection);
         // Callback infrastructure (Phase 2.7)
@@ -721,10 +1684,13 @@ impl Connection {
         let trace_callback = Arc::clone(&self_.trace_callback);
         let authorizer_callback = Arc::clone(&self_.authorizer_callback);
         let progress_handler = Arc::clone(&self_.progress_handler);
+        let update_hook = Arc::clone(&self_.update_hook);
         // Init hook infrastructure (Phase 2.11)
         let init_hook = Arc::clone(&self_.init_hook);
         let init_hook_called = Arc::clone(&self_.init_hook_called);
         let timeout = Arc::clone(&self_.timeout);
+        let dedicated_runtime = self_.runtime.clone();
+        let audit_owner = audit_transaction_begin(&self_.task_audit)?;
         let connection_self = self_.into();
         Python::attach(|py| {
             let future = async move {
@@ -747,6 +1713,11 @@ impl Connection {
                         &pragmas,
                         &pool_size,
                         &connection_timeout_secs,
+                        &statement_policy,
+                        native_functions,
+                        &wal_hook,
+                        &vfs,
+                        &event_hooks,
                     )
                     .await?;
 
@@ -773,6 +1744,7 @@ impl Connection {
                         &trace_callback,
                         &authorizer_callback,
                         &progress_handler,
+                        &update_hook,
                     );
 
                     if has_callbacks_flag {
@@ -784,6 +1756,11 @@ impl Connection {
                             &pragmas,
                             &pool_size,
                             &connection_timeout_secs,
+                            &statement_policy,
+                            native_functions,
+                            &wal_hook,
+                            &vfs,
+                            &event_hooks,
                         )
                         .await?;
                         let mut conn_guard = callback_connection.lock().await;
@@ -840,6 +1817,9 @@ impl Connection {
                         let mut trans_guard = transaction_state.lock().await;
                         *trans_guard = TransactionState::Active;
                     }
+                    if let Some((audit, task)) = audit_owner {
+                        audit.lock().unwrap().transaction_begun(task);
+                    }
                     Ok(())
                 }
                 .await;
@@ -865,12 +1845,15 @@ impl Connection {
 
                 result
             };
-            future_into_py(py, future).map(|bound| bound.unbind())
+            runtime::future_into_py(py, dedicated_runtime.as_deref(), future)
+                .map(|bound| bound.unbind())
         })
     }
 
     /// Commit the current transaction.
     fn commit(&self) -> PyResult<Py<PyAny>> {
+        audit_transaction_end(&self.task_audit, "commit")?;
+        let task_audit = self.task_audit.clone();
         let path = self.path.clone();
         let transaction_state = Arc::clone(&self.transaction_state);
         let transaction_connection = Arc::clone(&self.transaction_connection);
@@ -881,6 +1864,7 @@ impl Connection {
         let trace_callback = Arc::clone(&self.trace_callback);
         let authorizer_callback = Arc::clone(&self.authorizer_callback);
         let progress_handler = Arc::clone(&self.progress_handler);
+        let update_hook = Arc::clone(&self.update_hook);
         Python::attach(|py| {
             let future = async move {
                 let mut trans_guard = transaction_state.lock().await;
@@ -895,6 +1879,7 @@ impl Connection {
                     &trace_callback,
                     &authorizer_callback,
                     &progress_handler,
+                    &update_hook,
                 );
 
                 // Retrieve the stored transaction connection
@@ -908,6 +1893,7 @@ impl Connection {
                     .execute(&mut *conn)
                     .await
                     .map_err(|e| map_sqlx_error(e, &path, "COMMIT"))?;
+                result_cache::note_statement(&path, "COMMIT");
 
                 // If callbacks are set, return connection to callback_connection; otherwise it goes back to pool
                 if has_callbacks_flag {
@@ -919,14 +1905,19 @@ impl Connection {
                 }
 
                 *trans_guard = TransactionState::None;
+                if let Some(audit) = &task_audit {
+                    audit.lock().unwrap().transaction_ended();
+                }
                 Ok(())
             };
-            future_into_py(py, future).map(|bound| bound.unbind())
+            runtime::future_into_py(py, self.runtime.as_deref(), future).map(|bound| bound.unbind())
         })
     }
 
     /// Rollback the current transaction.
     fn rollback(&self) -> PyResult<Py<PyAny>> {
+        audit_transaction_end(&self.task_audit, "roll back")?;
+        let task_audit = self.task_audit.clone();
         let path = self.path.clone();
         let transaction_state = Arc::clone(&self.transaction_state);
         let transaction_connection = Arc::clone(&self.transaction_connection);
@@ -937,6 +1928,7 @@ impl Connection {
         let trace_callback = Arc::clone(&self.trace_callback);
         let authorizer_callback = Arc::clone(&self.authorizer_callback);
         let progress_handler = Arc::clone(&self.progress_handler);
+        let update_hook = Arc::clone(&self.update_hook);
         Python::attach(|py| {
             let future = async move {
                 let mut trans_guard = transaction_state.lock().await;
@@ -951,6 +1943,7 @@ impl Connection {
                     &trace_callback,
                     &authorizer_callback,
                     &progress_handler,
+                    &update_hook,
                 );
 
                 // Retrieve the stored transaction connection
@@ -975,9 +1968,12 @@ impl Connection {
                 }
 
                 *trans_guard = TransactionState::None;
+                if let Some(audit) = &task_audit {
+                    audit.lock().unwrap().transaction_ended();
+                }
                 Ok(())
             };
-            future_into_py(py, future).map(|bound| bound.unbind())
+            runtime::future_into_py(py, self.runtime.as_deref(), future).map(|bound| bound.unbind())
         })
     }
 
@@ -993,22 +1989,34 @@ impl Connection {
     /// * `query` - SQL query string to execute. Can contain parameter placeholders:
     ///   - Named parameters: `:name`, `@name`, `$name`
     ///   - Positional parameters: `?`, `?1`, `?2`
+    ///
+    ///   Or a `rapsqlite.sql()` template (also accepted by `fetch_*()`,
+    ///   `stream()`, `export_csv()` and `Cursor.execute()`), whose bound values
+    ///   are used; `parameters` must then be None.
     /// * `parameters` - Optional parameters for the query. Can be:
     ///   - A dictionary for named parameters: `{"name": "value", ...}`
     ///   - A list/tuple for positional parameters: `[value1, value2, ...]`
     ///   - A single value (treated as single positional parameter)
     ///   - None (no parameters)
+    /// * `multi` - Run every `;`-separated statement in `query`, in order, when
+    ///   awaited. Positional parameters are consumed statement by statement and
+    ///   no rows are returned. Default False: a query holding more than one
+    ///   statement raises ProgrammingError.
     ///
     /// # Returns
     ///
     /// Returns an ExecuteContextManager that can be used as:
-    /// - `await conn.execute(...)` - Execute and return None
+    /// - `await conn.execute(...)` - Execute and return the cursor
     /// - `async with conn.execute(...) as cursor:` - Execute and get cursor
     ///
+    /// For non-SELECT statements the cursor's `rowcount` (alias `rows_affected`) and
+    /// `lastrowid` hold the statement's result, so no second await to `changes()` is needed.
+    ///
     /// # Errors
     ///
     /// Raises OperationalError if the query execution fails (e.g., database
-    /// locked, disk full). Raises ProgrammingError for SQL syntax errors.
+    /// locked, disk full). Raises ProgrammingError for SQL syntax errors, and
+    /// for a query holding several statements unless `multi=True`.
     /// Raises IntegrityError for constraint violations.
     ///
     /// # Example
@@ -1030,17 +2038,34 @@ impl Connection {
     ///     # Using as context manager (returns cursor)
     ///     async with conn.execute("SELECT * FROM users") as cursor:
     ///         rows = await cursor.fetchall()
-    #[pyo3(signature = (query, parameters = None))]
+    #[pyo3(signature = (query, parameters = None, *, multi = false))]
     fn execute(
         self_: PyRef<Self>,
-        query: String,
+        query: Query,
         parameters: Option<&Bound<'_, PyAny>>,
+        multi: bool,
     ) -> PyResult<Py<PyAny>> {
+        let (query, parameters) = self_.statement(self_.py(), query, parameters)?;
+        let parameters = parameters.as_ref();
+        if !multi && second_statement(&query).is_some() {
+            return Err(ProgrammingError::new_err(
+                "execute() runs a single statement but the query holds several; \
+                 pass multi=True to run them all",
+            ));
+        }
+        self_.admit_statement(&query)?;
         let path = self_.path.clone();
         let pool = Arc::clone(&self_.pool);
         let pragmas = Arc::clone(&self_.pragmas);
         let pool_size = Arc::clone(&self_.pool_size);
         let connection_timeout_secs = Arc::clone(&self_.connection_timeout_secs);
+        let statement_policy = self_.statement_policy.clone();
+        let native_functions = self_.native_functions;
+        let wal_hook = self_.wal_hook.clone();
+        let vfs = self_.vfs.clone();
+        let event_hooks = Arc::clone(&self_.event_hooks);
+        let runtime = self_.runtime.clone();
+        let detect_types = self_.detect_types;
         let last_rowid = Arc::clone(&self_.last_rowid);
         let last_changes = Arc::clone(&self_.last_changes);
         let transaction_state = Arc::clone(&self_.transaction_state);
@@ -1052,13 +2077,25 @@ impl Connection {
         let trace_callback = Arc::clone(&self_.trace_callback);
         let authorizer_callback = Arc::clone(&self_.authorizer_callback);
         let progress_handler = Arc::clone(&self_.progress_handler);
-        // Prepared statement cache tracking (Phase 2.13)
-        let query_cache = Arc::clone(&self_.query_cache);
+        let update_hook = Arc::clone(&self_.update_hook);
+        let udf_error = Arc::clone(&self_.udf_error);
         // Init hook infrastructure (Phase 2.11)
         let init_hook = Arc::clone(&self_.init_hook);
         let init_hook_called = Arc::clone(&self_.init_hook_called);
-        let row_factory = Arc::clone(&self_.row_factory);
-        let text_factory = Arc::clone(&self_.text_factory);
+        let row_factory = self_.factory(self_.py(), "row_factory", &self_.row_factory)?;
+        let text_factory = self_.factory(self_.py(), "text_factory", &self_.text_factory)?;
+        let null_floats_as_nan = Arc::clone(&self_.null_floats_as_nan);
+        let decode_large_ints = Arc::clone(&self_.decode_large_ints);
+        let invalid_utf8 = Arc::clone(&self_.invalid_utf8);
+        let n_plus_one = self_.n_plus_one.clone();
+        let task_audit = self_.task_audit.clone();
+        let statement_logger = Arc::clone(&self_.statement_logger);
+        let bind_options = BindOptions {
+            large_int_policy: *self_.large_int_policy.lock().unwrap(),
+            strict: self_.strict,
+        };
+        let group_commit_window = self_.group_commit_window;
+        let group_committer = Arc::clone(&self_.group_committer);
         let connection_self: Py<Connection> = self_.into();
 
         // Clone query before processing (it may be moved)
@@ -1073,29 +2110,27 @@ impl Connection {
                 return Ok((query, Vec::new()));
             };
 
-            let params = params.as_borrowed();
-
-            // Check if it's a dict (named parameters)
-            if let Ok(dict) = params.cast::<pyo3::types::PyDict>() {
-                return process_named_parameters(&query, &dict);
-            }
-
-            // Check if it's a list or tuple (positional parameters)
-            if let Ok(list) = params.cast::<PyList>() {
-                let params_vec = process_positional_parameters(&list)?;
-                return Ok((query, params_vec));
-            }
-
-            // Single value (treat as single positional parameter)
-            let param = SqliteParam::from_py(&params)?;
-            Ok((query, vec![param]))
+            process_parameters(query, params, &bind_options)
         })?;
 
-        // Track query usage for prepared statement cache analytics (Phase 2.13)
-        track_query_usage(&query_cache, &processed_query);
+        check_n_plus_one(&n_plus_one, &processed_query, &param_values)?;
+        audit_statement(&task_audit)?;
 
-        // Check if this is a SELECT query (for lazy execution)
-        let is_select = is_select_query(&processed_query);
+        // Statements returning rows run lazily, on the first fetch; when the
+        // text doesn't tell, __aenter__ prepares the statement to find out
+        let returns_rows = if multi {
+            Some(false)
+        } else {
+            returns_rows_hint(&processed_query)
+        };
+        let statement_log = if returns_rows == Some(true) {
+            None
+        } else {
+            statement_log::start(&statement_logger, &original_query, parameters)?
+        };
+
+        // Shared by the cursor and the context manager that runs the statement
+        let execute_result: ExecuteResult = Arc::new(StdMutex::new(None));
 
         // Store original parameters for cursor (preserve original format)
         let params_for_cursor = parameters.map(|params| params.clone().unbind());
@@ -1118,11 +2153,14 @@ impl Connection {
         // The deprecation warning is acceptable as this is a sync context.
         #[allow(deprecated)]
         let cursor = Python::with_gil(|py| -> PyResult<Py<Cursor>> {
+            let results: CursorResults = Arc::new(StdMutex::new(None));
             let cursor = Cursor {
                 connection: connection_self.clone_ref(py),
                 query: original_query.clone(), // Store ORIGINAL query (with :value) for cursor processing
-                results: Arc::new(StdMutex::new(None)),
+                results: Arc::clone(&results),
+                closed: connection_self.borrow(py).cursors.track(&results),
                 current_index: Arc::new(StdMutex::new(0)),
+                arraysize: 1,
                 parameters: Arc::new(StdMutex::new(params_for_cursor)), // Store original params
                 processed_query: Some(processed_query.clone()), // Store processed query to avoid re-processing
                 processed_params: Some(param_values.clone()), // Store processed parameters to avoid re-processing
@@ -1131,8 +2169,21 @@ impl Connection {
                 connection_pragmas: Arc::clone(&pragmas),
                 pool_size: Arc::clone(&pool_size),
                 connection_timeout_secs: Arc::clone(&connection_timeout_secs),
+                statement_policy: statement_policy.clone(),
+                native_functions,
+                wal_hook: wal_hook.clone(),
+                vfs: vfs.clone(),
+                event_hooks: Arc::clone(&event_hooks),
+                runtime: runtime.clone(),
                 row_factory: Arc::clone(&row_factory),
                 text_factory: Arc::clone(&text_factory),
+                null_floats_as_nan: Arc::clone(&null_floats_as_nan),
+                large_int_policy: Arc::new(StdMutex::new(bind_options.large_int_policy)),
+                decode_large_ints: Arc::clone(&decode_large_ints),
+                invalid_utf8: Arc::clone(&invalid_utf8),
+                strict: bind_options.strict,
+                detect_types,
+                execute_result: Arc::clone(&execute_result),
                 transaction_state: Arc::clone(&transaction_state),
                 transaction_connection: Arc::clone(&transaction_connection),
                 callback_connection: Arc::clone(&callback_connection),
@@ -1141,6 +2192,8 @@ impl Connection {
                 trace_callback: Arc::clone(&trace_callback),
                 authorizer_callback: Arc::clone(&authorizer_callback),
                 progress_handler: Arc::clone(&progress_handler),
+                update_hook: Arc::clone(&update_hook),
+                udf_error: Arc::clone(&udf_error),
             };
             Py::new(py, cursor)
         })?;
@@ -1166,12 +2219,18 @@ impl Connection {
                 cursor: cursor.clone_ref(py),
                 query: processed_query,
                 param_values,
-                is_select,
+                returns_rows,
                 path,
                 pool: Arc::clone(&pool),
                 pragmas: Arc::clone(&pragmas),
                 pool_size: Arc::clone(&pool_size),
                 connection_timeout_secs: Arc::clone(&connection_timeout_secs),
+                statement_policy: statement_policy.clone(),
+                native_functions,
+                wal_hook: wal_hook.clone(),
+                vfs: vfs.clone(),
+                event_hooks: Arc::clone(&event_hooks),
+                runtime: runtime.clone(),
                 transaction_state: Arc::clone(&transaction_state),
                 transaction_connection: Arc::clone(&transaction_connection),
                 callback_connection: Arc::clone(&callback_connection),
@@ -1180,10 +2239,17 @@ impl Connection {
                 trace_callback: Arc::clone(&trace_callback),
                 authorizer_callback: Arc::clone(&authorizer_callback),
                 progress_handler: Arc::clone(&progress_handler),
+                update_hook: Arc::clone(&update_hook),
+                udf_error: Arc::clone(&udf_error),
                 init_hook: Arc::clone(&init_hook),
                 init_hook_called: Arc::clone(&init_hook_called),
                 last_rowid: Arc::clone(&last_rowid),
                 last_changes: Arc::clone(&last_changes),
+                execute_result: Arc::clone(&execute_result),
+                group_commit_window,
+                group_committer,
+                statement_log: StdMutex::new(statement_log),
+                iterating: StdMutex::new(false),
                 connection: connection_self.clone_ref(py),
             };
             Py::new(py, ctx_mgr).map(|c| c.into())
@@ -1196,11 +2262,18 @@ impl Connection {
         query: String,
         parameters: Vec<Vec<Py<PyAny>>>,
     ) -> PyResult<Py<PyAny>> {
+        self_.admit_statement(&query)?;
+        audit_statement(&self_.task_audit)?;
         let path = self_.path.clone();
         let pool = Arc::clone(&self_.pool);
         let pragmas = Arc::clone(&self_.pragmas);
         let pool_size = Arc::clone(&self_.pool_size);
         let connection_timeout_secs = Arc::clone(&self_.connection_timeout_secs);
+        let statement_policy = self_.statement_policy.clone();
+        let native_functions = self_.native_functions;
+        let wal_hook = self_.wal_hook.clone();
+        let vfs = self_.vfs.clone();
+        let event_hooks = Arc::clone(&self_.event_hooks);
         let last_rowid = Arc::clone(&self_.last_rowid);
         let last_changes = Arc::clone(&self_.last_changes);
         let transaction_state = Arc::clone(&self_.transaction_state);
@@ -1212,9 +2285,18 @@ impl Connection {
         let trace_callback = Arc::clone(&self_.trace_callback);
         let authorizer_callback = Arc::clone(&self_.authorizer_callback);
         let progress_handler = Arc::clone(&self_.progress_handler);
+        let update_hook = Arc::clone(&self_.update_hook);
+        let udf_error = Arc::clone(&self_.udf_error);
+        let bind_options = BindOptions {
+            large_int_policy: *self_.large_int_policy.lock().unwrap(),
+            strict: self_.strict,
+        };
         // Init hook infrastructure (Phase 2.11)
         let init_hook = Arc::clone(&self_.init_hook);
         let init_hook_called = Arc::clone(&self_.init_hook_called);
+        let statement_log =
+            statement_log::start_many(&self_.statement_logger, &query, &parameters)?;
+        let dedicated_runtime = self_.runtime.clone();
         let connection_self = self_.into();
 
         // Process all parameter sets
@@ -1229,7 +2311,7 @@ impl Connection {
                 let mut params_vec = Vec::new();
                 for param in param_set {
                     let bound_param = param.bind(py);
-                    let sqlx_param = SqliteParam::from_py(bound_param)?;
+                    let sqlx_param = SqliteParam::from_py(bound_param, &bind_options)?;
                     params_vec.push(sqlx_param);
                 }
                 result.push(params_vec);
@@ -1256,6 +2338,11 @@ impl Connection {
                         &pragmas,
                         &pool_size,
                         &connection_timeout_secs,
+                        &statement_policy,
+                        native_functions,
+                        &wal_hook,
+                        &vfs,
+                        &event_hooks,
                     )
                     .await?;
                 }
@@ -1269,6 +2356,7 @@ impl Connection {
                     &trace_callback,
                     &authorizer_callback,
                     &progress_handler,
+                    &update_hook,
                 );
 
                 let mut total_changes = 0u64;
@@ -1298,6 +2386,11 @@ impl Connection {
                         &pragmas,
                         &pool_size,
                         &connection_timeout_secs,
+                        &statement_policy,
+                        native_functions,
+                        &wal_hook,
+                        &vfs,
+                        &event_hooks,
                     )
                     .await?;
 
@@ -1322,6 +2415,11 @@ impl Connection {
                         &pragmas,
                         &pool_size,
                         &connection_timeout_secs,
+                        &statement_policy,
+                        native_functions,
+                        &wal_hook,
+                        &vfs,
+                        &event_hooks,
                     )
                     .await?;
                     for param_values in processed_params {
@@ -1334,10 +2432,18 @@ impl Connection {
 
                 *last_rowid.lock().await = last_row_id;
                 *last_changes.lock().await = total_changes;
+                if let Some(log) = statement_log {
+                    log.finish(total_changes as i64);
+                }
 
                 Ok(())
             };
-            future_into_py(py, future).map(|bound| bound.unbind())
+            runtime::future_into_py(
+                py,
+                dedicated_runtime.as_deref(),
+                with_udf_errors(udf_error, future),
+            )
+            .map(|bound| bound.unbind())
         })
     }
 
@@ -1350,6 +2456,11 @@ impl Connection {
     ///
     /// * `query` - SELECT query string. Can contain parameter placeholders.
     /// * `parameters` - Optional parameters (same format as `execute()`).
+    /// * `max_rows` - Abandon the query once it returns more rows than this.
+    ///   Overrides the connection's `max_rows`.
+    /// * `max_result_bytes` - Abandon the query once the fetched values exceed
+    ///   this size (TEXT/BLOB length plus 8 bytes per number). Overrides the
+    ///   connection's `max_result_bytes`.
     ///
     /// # Returns
     ///
@@ -1364,7 +2475,8 @@ impl Connection {
     /// # Errors
     ///
     /// Raises ProgrammingError for SQL syntax errors or if query is not a SELECT.
-    /// Raises OperationalError for database errors.
+    /// Raises OperationalError for database errors, and its subclass
+    /// ResultTooLarge when a limit is exceeded.
     ///
     /// # Example
     ///
@@ -1381,151 +2493,178 @@ impl Connection {
     ///
     ///     # With parameters
     ///     rows = await conn.fetch_all("SELECT * FROM users WHERE id > ?", [5])
-    #[pyo3(signature = (query, parameters = None))]
+    ///
+    ///     # Refuse to read more than 1000 rows
+    ///     rows = await conn.fetch_all("SELECT * FROM events", max_rows=1000)
+    #[pyo3(signature = (query, parameters = None, *, max_rows = None, max_result_bytes = None))]
     fn fetch_all(
         self_: PyRef<Self>,
-        query: String,
+        query: Query,
         parameters: Option<&Bound<'_, PyAny>>,
+        max_rows: Option<usize>,
+        max_result_bytes: Option<usize>,
     ) -> PyResult<Py<PyAny>> {
-        let path = self_.path.clone();
-        let pool = Arc::clone(&self_.pool);
-        let pragmas = Arc::clone(&self_.pragmas);
-        let pool_size = Arc::clone(&self_.pool_size);
-        let connection_timeout_secs = Arc::clone(&self_.connection_timeout_secs);
-        let transaction_state = Arc::clone(&self_.transaction_state);
-        let transaction_connection = Arc::clone(&self_.transaction_connection);
-        let row_factory = Arc::clone(&self_.row_factory);
-        let text_factory = Arc::clone(&self_.text_factory);
-        // Callback infrastructure (Phase 2.7)
-        let callback_connection = Arc::clone(&self_.callback_connection);
-        let load_extension_enabled = Arc::clone(&self_.load_extension_enabled);
-        let user_functions = Arc::clone(&self_.user_functions);
-        let trace_callback = Arc::clone(&self_.trace_callback);
-        let authorizer_callback = Arc::clone(&self_.authorizer_callback);
-        let progress_handler = Arc::clone(&self_.progress_handler);
-        // Prepared statement cache tracking (Phase 2.13)
-        let query_cache = Arc::clone(&self_.query_cache);
-        // Init hook infrastructure (Phase 2.11)
-        let init_hook = Arc::clone(&self_.init_hook);
-        let init_hook_called = Arc::clone(&self_.init_hook_called);
-        let connection_self = self_.into();
-
-        // Process parameters
-        // Note: Python::with_gil is used here for sync parameter processing before async execution.
-        // The deprecation warning is acceptable as this is a sync context.
-        #[allow(deprecated)]
-        let (processed_query, param_values) = Python::with_gil(|_py| -> PyResult<_> {
-            let Some(params) = parameters else {
-                return Ok((query, Vec::new()));
-            };
-
-            let params = params.as_borrowed();
-
-            // Check if it's a dict (named parameters)
-            if let Ok(dict) = params.cast::<pyo3::types::PyDict>() {
-                return process_named_parameters(&query, &dict);
-            }
-
-            // Check if it's a list or tuple (positional parameters)
-            if let Ok(list) = params.cast::<PyList>() {
-                let params_vec = process_positional_parameters(&list)?;
-                return Ok((query, params_vec));
-            }
-
-            // Single value (treat as single positional parameter)
-            let param = SqliteParam::from_py(&params)?;
-            Ok((query, vec![param]))
-        })?;
-
-        // Track query usage for prepared statement cache analytics (Phase 2.13)
-        track_query_usage(&query_cache, &processed_query);
+        let limits = self_.fetch_limits_for(max_rows, max_result_bytes);
+        Self::fetch_all_rows(self_, query, parameters, None, limits)
+    }
 
-        Python::attach(|py| {
-            let future = async move {
-                // Priority: transaction > callbacks > pool
-                let in_transaction = {
-                    let g = transaction_state.lock().await;
-                    g.is_active()
-                };
+    /// Fetch all rows, reusing the result of an identical call (same query text
+    /// and parameters) made less than `ttl` seconds ago.
+    ///
+    /// Meant for hot reference data that may be briefly stale: unlike
+    /// `Connection(cache="lru")`, entries are not invalidated by writes and simply
+    /// expire. Calls inside a transaction always query the database. Hit rates
+    /// are reported by `ttl_cache_info()`.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - SELECT query string. Can contain parameter placeholders.
+    /// * `parameters` - Optional parameters (same format as `execute()`).
+    /// * `ttl` - Seconds a result stays valid (default 5.0).
+    /// * `max_rows`, `max_result_bytes` - As for `fetch_all()`; also checked
+    ///   against results served from the cache.
+    ///
+    /// # Errors
+    ///
+    /// Raises ValueError if `ttl` is negative or not finite; otherwise the same
+    /// errors as `fetch_all()`.
+    ///
+    /// # Example
+    ///
+    /// .. code-block:: python
+    ///
+    ///     countries = await conn.cached_fetch_all("SELECT * FROM countries", ttl=60)
+    #[pyo3(signature = (
+        query,
+        parameters = None,
+        ttl = 5.0,
+        *,
+        max_rows = None,
+        max_result_bytes = None
+    ))]
+    fn cached_fetch_all(
+        self_: PyRef<Self>,
+        query: Query,
+        parameters: Option<&Bound<'_, PyAny>>,
+        ttl: f64,
+        max_rows: Option<usize>,
+        max_result_bytes: Option<usize>,
+    ) -> PyResult<Py<PyAny>> {
+        let ttl = Duration::try_from_secs_f64(ttl)
+            .map_err(|_| ValueError::new_err("ttl must be a finite number >= 0"))?;
+        let limits = self_.fetch_limits_for(max_rows, max_result_bytes);
+        Self::fetch_all_rows(self_, query, parameters, Some(ttl), limits)
+    }
 
-                // Ensure pool exists before calling init_hook (init_hook needs pool to execute queries)
-                // Skip if in transaction (transaction has its own connection)
-                if !in_transaction {
-                    get_or_create_pool(
-                        &path,
-                        &pool,
-                        &pragmas,
-                        &pool_size,
-                        &connection_timeout_secs,
-                    )
-                    .await?;
-                }
-
-                // Execute init_hook if needed (before any operations)
-                execute_init_hook_if_needed(&init_hook, &init_hook_called, connection_self).await?;
-
-                let has_callbacks_flag = has_callbacks(
-                    &load_extension_enabled,
-                    &user_functions,
-                    &trace_callback,
-                    &authorizer_callback,
-                    &progress_handler,
-                );
+    /// Iterate over the rows of a SELECT query as SQLite produces them.
+    ///
+    /// The query runs on its own pool connection in the background and at most
+    /// `prefetch` rows are buffered ahead of the consumer: a slow consumer pauses
+    /// the query instead of growing memory, and a fast one finds rows waiting.
+    /// Rows are formatted according to `row_factory`. Leaving the `async with`
+    /// block (or calling `aclose()`) stops the query and releases the
+    /// connection; results are not cached.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - SELECT query string. Can contain parameter placeholders.
+    /// * `parameters` - Optional parameters (same format as `execute()`).
+    /// * `prefetch` - Number of rows buffered ahead of the consumer (default 128).
+    ///
+    /// # Errors
+    ///
+    /// Raises ValueError if `prefetch` is 0. Iteration raises ProgrammingError
+    /// inside a transaction or while callbacks are registered (both need the
+    /// connection the stream would hold), and the errors of `fetch_all()`
+    /// otherwise.
+    ///
+    /// # Example
+    ///
+    /// .. code-block:: python
+    ///
+    ///     async with conn.stream("SELECT * FROM events", prefetch=1000) as rows:
+    ///         async for row in rows:
+    ///             await handle(row)
+    #[pyo3(signature = (query, parameters = None, *, prefetch = 128))]
+    fn stream(
+        self_: PyRef<Self>,
+        py: Python<'_>,
+        query: Query,
+        parameters: Option<&Bound<'_, PyAny>>,
+        prefetch: usize,
+    ) -> PyResult<RowStream> {
+        if prefetch == 0 {
+            return Err(ValueError::new_err("prefetch must be >= 1"));
+        }
+        Self::open_stream(self_, py, query, parameters, prefetch, None)
+    }
 
-                let rows = if in_transaction {
-                    let mut conn_guard = transaction_connection.lock().await;
-                    let conn = conn_guard.as_mut().ok_or_else(|| {
-                        OperationalError::new_err("Transaction connection not available")
-                    })?;
-                    bind_and_fetch_all_on_connection(&processed_query, &param_values, conn, &path)
-                        .await?
-                } else if has_callbacks_flag {
-                    // Ensure callback connection exists
-                    ensure_callback_connection(
-                        &path,
-                        &pool,
-                        &callback_connection,
-                        &pragmas,
-                        &pool_size,
-                        &connection_timeout_secs,
-                    )
-                    .await?;
+    /// Iterate over the rows of a SELECT query in lists of up to `chunk_size`
+    /// rows, without holding the whole result set in memory.
+    ///
+    /// Like `stream()`, the query runs on its own pool connection in the
+    /// background, reading at most one chunk ahead of the consumer; each chunk
+    /// is converted to Python objects at once. Every chunk but the last holds
+    /// `chunk_size` rows, and a query without rows yields no chunk. Rows are
+    /// formatted according to `row_factory`.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - SELECT query string. Can contain parameter placeholders.
+    /// * `parameters` - Optional parameters (same format as `execute()`).
+    /// * `chunk_size` - Maximum number of rows per chunk (default 1000).
+    ///
+    /// # Errors
+    ///
+    /// Raises ValueError if `chunk_size` is 0; iteration raises the errors of
+    /// `stream()`.
+    ///
+    /// # Example
+    ///
+    /// .. code-block:: python
+    ///
+    ///     async with conn.fetch_stream("SELECT * FROM events", chunk_size=5000) as chunks:
+    ///         async for rows in chunks:
+    ///             await export(rows)
+    #[pyo3(signature = (query, parameters = None, *, chunk_size = 1000))]
+    fn fetch_stream(
+        self_: PyRef<Self>,
+        py: Python<'_>,
+        query: Query,
+        parameters: Option<&Bound<'_, PyAny>>,
+        chunk_size: usize,
+    ) -> PyResult<RowStream> {
+        if chunk_size == 0 {
+            return Err(ValueError::new_err("chunk_size must be >= 1"));
+        }
+        Self::open_stream(self_, py, query, parameters, chunk_size, Some(chunk_size))
+    }
 
-                    // Use callback connection
-                    let mut conn_guard = callback_connection.lock().await;
-                    let conn = conn_guard.as_mut().ok_or_else(|| {
-                        OperationalError::new_err("Callback connection not available")
-                    })?;
-                    bind_and_fetch_all_on_connection(&processed_query, &param_values, conn, &path)
-                        .await?
-                } else {
-                    let pool_clone = get_or_create_pool(
-                        &path,
-                        &pool,
-                        &pragmas,
-                        &pool_size,
-                        &connection_timeout_secs,
-                    )
-                    .await?;
-                    bind_and_fetch_all(&processed_query, &param_values, &pool_clone, &path).await?
-                };
+    /// Hit/miss counters for `cached_fetch_all()`.
+    ///
+    /// Returns a dict with `hits`, `misses`, `hit_rate` (0.0 before any call) and
+    /// `currsize`.
+    fn ttl_cache_info<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let stats = self.ttl_cache.lock().unwrap().stats();
+        let lookups = stats.hits + stats.misses;
+        let info = PyDict::new(py);
+        info.set_item("hits", stats.hits)?;
+        info.set_item("misses", stats.misses)?;
+        info.set_item(
+            "hit_rate",
+            if lookups == 0 {
+                0.0
+            } else {
+                stats.hits as f64 / lookups as f64
+            },
+        )?;
+        info.set_item("currsize", stats.size)?;
+        Ok(info)
+    }
 
-                // Convert rows using row_factory
-                Python::attach(|py| -> PyResult<Py<PyAny>> {
-                    let guard = row_factory.lock().unwrap();
-                    let factory_opt = guard.as_ref();
-                    let tf_guard = text_factory.lock().unwrap();
-                    let tf_opt = tf_guard.as_ref();
-                    let result_list = PyList::empty(py);
-                    for row in rows.iter() {
-                        let out = row_to_py_with_factory(py, row, factory_opt, tf_opt)?;
-                        result_list.append(out)?;
-                    }
-                    Ok(result_list.into())
-                })
-            };
-            future_into_py(py, future).map(|bound| bound.unbind())
-        })
+    /// Drop every `cached_fetch_all()` result and reset its counters.
+    fn ttl_cache_clear(&self) {
+        self.ttl_cache.lock().unwrap().clear();
     }
 
     /// Fetch a single row from a SELECT query.
@@ -1545,8 +2684,9 @@ impl Connection {
     ///
     /// # Errors
     ///
-    /// Raises ProgrammingError if no rows are found or if more than one row
-    /// is returned. Raises OperationalError for database errors.
+    /// Raises DatabaseError if no rows are found and ProgrammingError if more
+    /// than one row is returned (use `fetch_first()` to take the first row of
+    /// several). Raises OperationalError for database errors.
     ///
     /// # Example
     ///
@@ -1564,18 +2704,37 @@ impl Connection {
     #[pyo3(signature = (query, parameters = None))]
     fn fetch_one(
         self_: PyRef<Self>,
-        query: String,
+        query: Query,
         parameters: Option<&Bound<'_, PyAny>>,
     ) -> PyResult<Py<PyAny>> {
+        let (query, parameters) = self_.statement(self_.py(), query, parameters)?;
+        let parameters = parameters.as_ref();
+        self_.admit_statement(&query)?;
         let path = self_.path.clone();
+        let read_pool = self_.read_pool.clone();
         let pool = Arc::clone(&self_.pool);
         let pragmas = Arc::clone(&self_.pragmas);
         let pool_size = Arc::clone(&self_.pool_size);
         let connection_timeout_secs = Arc::clone(&self_.connection_timeout_secs);
+        let statement_policy = self_.statement_policy.clone();
+        let native_functions = self_.native_functions;
+        let wal_hook = self_.wal_hook.clone();
+        let vfs = self_.vfs.clone();
+        let event_hooks = Arc::clone(&self_.event_hooks);
         let transaction_state = Arc::clone(&self_.transaction_state);
         let transaction_connection = Arc::clone(&self_.transaction_connection);
-        let row_factory = Arc::clone(&self_.row_factory);
-        let text_factory = Arc::clone(&self_.text_factory);
+        let row_factory = self_.factory(self_.py(), "row_factory", &self_.row_factory)?;
+        let text_factory = self_.factory(self_.py(), "text_factory", &self_.text_factory)?;
+        let null_floats_as_nan = Arc::clone(&self_.null_floats_as_nan);
+        let decode_large_ints = Arc::clone(&self_.decode_large_ints);
+        let invalid_utf8 = Arc::clone(&self_.invalid_utf8);
+        let n_plus_one = self_.n_plus_one.clone();
+        let task_audit = self_.task_audit.clone();
+        let statement_logger = Arc::clone(&self_.statement_logger);
+        let bind_options = BindOptions {
+            large_int_policy: *self_.large_int_policy.lock().unwrap(),
+            strict: self_.strict,
+        };
         // Callback infrastructure (Phase 2.7)
         let callback_connection = Arc::clone(&self_.callback_connection);
         let load_extension_enabled = Arc::clone(&self_.load_extension_enabled);
@@ -1583,9 +2742,13 @@ impl Connection {
         let trace_callback = Arc::clone(&self_.trace_callback);
         let authorizer_callback = Arc::clone(&self_.authorizer_callback);
         let progress_handler = Arc::clone(&self_.progress_handler);
+        let update_hook = Arc::clone(&self_.update_hook);
+        let udf_error = Arc::clone(&self_.udf_error);
         // Init hook infrastructure (Phase 2.11)
         let init_hook = Arc::clone(&self_.init_hook);
         let init_hook_called = Arc::clone(&self_.init_hook_called);
+        let detect_types = self_.detect_types;
+        let dedicated_runtime = self_.runtime.clone();
         let connection_self = self_.into();
 
         // Process parameters
@@ -1597,19 +2760,12 @@ impl Connection {
                 return Ok((query, Vec::new()));
             };
 
-            let params = params.as_borrowed();
-
-            if let Ok(dict) = params.cast::<pyo3::types::PyDict>() {
-                return process_named_parameters(&query, &dict);
-            }
-            if let Ok(list) = params.cast::<PyList>() {
-                let params_vec = process_positional_parameters(&list)?;
-                return Ok((query, params_vec));
-            }
-            let param = SqliteParam::from_py(&params)?;
-            Ok((query, vec![param]))
+            process_parameters(query, params, &bind_options)
         })?;
 
+        check_n_plus_one(&n_plus_one, &processed_query, &param_values)?;
+        audit_statement(&task_audit)?;
+        let statement_log = statement_log::start(&statement_logger, &processed_query, parameters)?;
         Python::attach(|py| {
             let future = async move {
                 // Priority: transaction > callbacks > pool
@@ -1627,6 +2783,11 @@ impl Connection {
                         &pragmas,
                         &pool_size,
                         &connection_timeout_secs,
+                        &statement_policy,
+                        native_functions,
+                        &wal_hook,
+                        &vfs,
+                        &event_hooks,
                     )
                     .await?;
                 }
@@ -1640,15 +2801,23 @@ impl Connection {
                     &trace_callback,
                     &authorizer_callback,
                     &progress_handler,
+                    &update_hook,
                 );
 
+                let decltypes;
                 let row = if in_transaction {
                     let mut conn_guard = transaction_connection.lock().await;
                     let conn = conn_guard.as_mut().ok_or_else(|| {
                         OperationalError::new_err("Transaction connection not available")
                     })?;
-                    bind_and_fetch_one_on_connection(&processed_query, &param_values, conn, &path)
-                        .await?
+                    decltypes = declared_types(detect_types, conn, &processed_query).await?;
+                    bind_and_fetch_single_on_connection(
+                        &processed_query,
+                        &param_values,
+                        conn,
+                        &path,
+                    )
+                    .await?
                 } else if has_callbacks_flag {
                     // Ensure callback connection exists
                     ensure_callback_connection(
@@ -1658,6 +2827,11 @@ impl Connection {
                         &pragmas,
                         &pool_size,
                         &connection_timeout_secs,
+                        &statement_policy,
+                        native_functions,
+                        &wal_hook,
+                        &vfs,
+                        &event_hooks,
                     )
                     .await?;
 
@@ -1666,8 +2840,14 @@ impl Connection {
                     let conn = conn_guard.as_mut().ok_or_else(|| {
                         OperationalError::new_err("Callback connection not available")
                     })?;
-                    bind_and_fetch_one_on_connection(&processed_query, &param_values, conn, &path)
-                        .await?
+                    decltypes = declared_types(detect_types, conn, &processed_query).await?;
+                    bind_and_fetch_single_on_connection(
+                        &processed_query,
+                        &param_values,
+                        conn,
+                        &path,
+                    )
+                    .await?
                 } else {
                     let pool_clone = get_or_create_pool(
                         &path,
@@ -1675,21 +2855,53 @@ impl Connection {
                         &pragmas,
                         &pool_size,
                         &connection_timeout_secs,
+                        &statement_policy,
+                        native_functions,
+                        &wal_hook,
+                        &vfs,
+                        &event_hooks,
                     )
                     .await?;
-                    bind_and_fetch_one(&processed_query, &param_values, &pool_clone, &path).await?
+                    let pool_clone = match &read_pool {
+                        Some(read_pool) => {
+                            read_pool.route(&path, pool_clone, &processed_query).await?
+                        }
+                        None => pool_clone,
+                    };
+                    decltypes =
+                        declared_types_in_pool(detect_types, &pool_clone, &processed_query, &path)
+                            .await?;
+                    bind_and_fetch_single(&processed_query, &param_values, &pool_clone, &path)
+                        .await?
                 };
+                let row = row.ok_or_else(|| {
+                    map_sqlx_error(sqlx::Error::RowNotFound, &path, &processed_query)
+                })?;
 
                 Python::attach(|py| -> PyResult<Py<PyAny>> {
                     let guard = row_factory.lock().unwrap();
-                    let factory_opt = guard.as_ref();
+                    let factory = RowFactory::resolve(py, guard.as_ref())?;
                     let tf_guard = text_factory.lock().unwrap();
-                    let tf_opt = tf_guard.as_ref();
-                    let out = row_to_py_with_factory(py, &row, factory_opt, tf_opt)?;
+                    let converters =
+                        ColumnConverters::resolve(py, detect_types, &row, decltypes.as_deref());
+                    let decode = DecodeOptions {
+                        text_factory: tf_guard.as_ref(),
+                        null_floats_as_nan: *null_floats_as_nan.lock().unwrap(),
+                        decode_large_ints: *decode_large_ints.lock().unwrap(),
+                        invalid_utf8: *invalid_utf8.lock().unwrap(),
+                        strict: bind_options.strict,
+                        converters: converters.as_ref(),
+                    };
+                    let out = factory.convert(py, &row, &decode)?;
                     Ok(out.unbind())
                 })
             };
-            future_into_py(py, future).map(|bound| bound.unbind())
+            runtime::future_into_py(
+                py,
+                dedicated_runtime.as_deref(),
+                statement_log::logged(statement_log, with_udf_errors(udf_error, future), |_, _| 1),
+            )
+            .map(|bound| bound.unbind())
         })
     }
 
@@ -1711,7 +2923,8 @@ impl Connection {
     ///
     /// # Errors
     ///
-    /// Raises ProgrammingError if more than one row is returned. Raises
+    /// Raises ProgrammingError if more than one row is returned (use
+    /// `fetch_first()` to take the first row of several). Raises
     /// OperationalError for database errors.
     ///
     /// # Example
@@ -1733,164 +2946,632 @@ impl Connection {
     #[pyo3(signature = (query, parameters = None))]
     fn fetch_optional(
         self_: PyRef<Self>,
-        query: String,
+        query: Query,
         parameters: Option<&Bound<'_, PyAny>>,
     ) -> PyResult<Py<PyAny>> {
-        let path = self_.path.clone();
-        let pool = Arc::clone(&self_.pool);
-        let pragmas = Arc::clone(&self_.pragmas);
-        let pool_size = Arc::clone(&self_.pool_size);
-        let connection_timeout_secs = Arc::clone(&self_.connection_timeout_secs);
-        let transaction_state = Arc::clone(&self_.transaction_state);
-        let transaction_connection = Arc::clone(&self_.transaction_connection);
-        let row_factory = Arc::clone(&self_.row_factory);
-        let text_factory = Arc::clone(&self_.text_factory);
-        // Callback infrastructure (Phase 2.7)
-        let callback_connection = Arc::clone(&self_.callback_connection);
-        let load_extension_enabled = Arc::clone(&self_.load_extension_enabled);
-        let user_functions = Arc::clone(&self_.user_functions);
-        let trace_callback = Arc::clone(&self_.trace_callback);
-        let authorizer_callback = Arc::clone(&self_.authorizer_callback);
-        let progress_handler = Arc::clone(&self_.progress_handler);
-        // Init hook infrastructure (Phase 2.11)
-        let init_hook = Arc::clone(&self_.init_hook);
-        let init_hook_called = Arc::clone(&self_.init_hook_called);
-        let connection_self = self_.into();
+        Self::fetch_optional_row(self_, query, parameters, false)
+    }
 
-        // Process parameters
-        // Note: Python::with_gil is used here for sync parameter processing before async execution.
-        // The deprecation warning is acceptable as this is a sync context.
-        #[allow(deprecated)]
-        let (processed_query, param_values) = Python::with_gil(|_py| -> PyResult<_> {
-            let Some(params) = parameters else {
-                return Ok((query, Vec::new()));
-            };
+    /// Fetch the first row of a query, returning None if there are no rows.
+    ///
+    /// aiosqlite-style counterpart of `fetch_optional()`: further rows are
+    /// ignored instead of raising, and only the first one is read.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - SELECT query string.
+    /// * `parameters` - Optional parameters (same format as `execute()`).
+    ///
+    /// # Returns
+    ///
+    /// Returns an awaitable that resolves to the first row (format depends on
+    /// `row_factory`) or None.
+    ///
+    /// # Example
+    ///
+    /// .. code-block:: python
+    ///
+    ///     newest = await conn.fetch_first("SELECT * FROM events ORDER BY ts DESC")
+    #[pyo3(signature = (query, parameters = None))]
+    fn fetch_first(
+        self_: PyRef<Self>,
+        query: Query,
+        parameters: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<Py<PyAny>> {
+        Self::fetch_optional_row(self_, query, parameters, true)
+    }
 
-            let params = params.as_borrowed();
+    /// Result cache statistics, or None when the connection was opened without
+    /// `cache="lru"`.
+    ///
+    /// Returns a dict with `hits`, `misses`, `maxsize` and `currsize`, like
+    /// `functools.lru_cache`'s `cache_info()`.
+    fn cache_info<'py>(&self, py: Python<'py>) -> PyResult<Option<Bound<'py, PyDict>>> {
+        let Some(cache) = &self.result_cache else {
+            return Ok(None);
+        };
+        let stats = cache.lock().unwrap().stats();
+        let info = PyDict::new(py);
+        info.set_item("hits", stats.hits)?;
+        info.set_item("misses", stats.misses)?;
+        info.set_item("maxsize", stats.capacity)?;
+        info.set_item("currsize", stats.size)?;
+        Ok(Some(info))
+    }
 
-            if let Ok(dict) = params.cast::<pyo3::types::PyDict>() {
-                return process_named_parameters(&query, &dict);
-            }
-            if let Ok(list) = params.cast::<PyList>() {
-                let params_vec = process_positional_parameters(&list)?;
-                return Ok((query, params_vec));
-            }
-            let param = SqliteParam::from_py(&params)?;
-            Ok((query, vec![param]))
-        })?;
+    /// Drop every cached result and reset the hit/miss counters.
+    fn cache_clear(&self) {
+        if let Some(cache) = &self.result_cache {
+            cache.lock().unwrap().clear();
+        }
+    }
 
+    /// Get the last insert row ID.
+    fn last_insert_rowid(&self) -> PyResult<Py<PyAny>> {
+        let last_rowid = Arc::clone(&self.last_rowid);
         Python::attach(|py| {
-            let future = async move {
-                // Priority: transaction > callbacks > pool
-                let in_transaction = {
-                    let g = transaction_state.lock().await;
-                    g.is_active()
-                };
+            let future = async move { Ok(*last_rowid.lock().await) };
+            runtime::future_into_py(py, self.runtime.as_deref(), future).map(|bound| bound.unbind())
+        })
+    }
 
-                // Ensure pool exists before calling init_hook (init_hook needs pool to execute queries)
-                // Skip if in transaction (transaction has its own connection)
-                if !in_transaction {
-                    get_or_create_pool(
-                        &path,
-                        &pool,
-                        &pragmas,
-                        &pool_size,
-                        &connection_timeout_secs,
-                    )
-                    .await?;
+    /// Get the number of rows affected by the last statement.
+    fn changes(&self) -> PyResult<Py<PyAny>> {
+        let last_changes = Arc::clone(&self.last_changes);
+        Python::attach(|py| {
+            let future = async move { Ok(*last_changes.lock().await) };
+            runtime::future_into_py(py, self.runtime.as_deref(), future).map(|bound| bound.unbind())
+        })
+    }
+
+    /// Execute SQL without preparing it through the statement cache.
+    ///
+    /// Runs `sql` with `sqlite3_exec` on a single pinned connection (the transaction
+    /// connection, the callback connection, or one pool connection held for the call).
+    /// Use it for statements that can't go through `execute()`, such as several
+    /// `;`-separated statements or PRAGMAs that must not be cached. Parameters are
+    /// not supported and any rows produced are discarded.
+    ///
+    /// # Returns
+    ///
+    /// Returns an awaitable resolving to the number of rows changed by all statements.
+    /// `last_insert_rowid()` and `changes()` are updated as after `execute()`.
+    ///
+    /// # Note
+    ///
+    /// Statements run on a pooled connection only affect that connection, so
+    /// connection-level PRAGMAs are only reliable inside a transaction or once
+    /// callbacks pin a connection. Don't use it for BEGIN/COMMIT; use `begin()`,
+    /// `commit()` and `rollback()` so transaction state stays in sync.
+    ///
+    /// # Example
+    ///
+    /// .. code-block:: python
+    ///
+    ///     changed = await conn.execute_raw(
+    ///         "INSERT INTO t VALUES (1); INSERT INTO t VALUES (2);"
+    ///     )  # 2
+    fn execute_raw(self_: PyRef<Self>, sql: String) -> PyResult<Py<PyAny>> {
+        self_.admit_statement(&sql)?;
+        let route = RawHandleRoute::new(&self_);
+        let init_hook = Arc::clone(&self_.init_hook);
+        let init_hook_called = Arc::clone(&self_.init_hook_called);
+        let last_rowid = Arc::clone(&self_.last_rowid);
+        let last_changes = Arc::clone(&self_.last_changes);
+        let dedicated_runtime = self_.runtime.clone();
+        let connection_self: Py<Connection> = self_.into();
+
+        let sql_cstr = CString::new(sql)
+            .map_err(|e| ValueError::new_err(format!("Invalid SQL string: {e}")))?;
+
+        Python::attach(|py| {
+            let future = async move {
+                if !route.in_transaction().await {
+                    execute_init_hook_if_needed(&init_hook, &init_hook_called, connection_self)
+                        .await?;
                 }
 
-                // Execute init_hook if needed (before any operations)
-                execute_init_hook_if_needed(&init_hook, &init_hook_called, connection_self).await?;
+                // Safety: see `RawHandleRoute::run`.
+                let (changes, rowid) = route
+                    .run(|db| unsafe { exec_unprepared(db, &sql_cstr, &route.path) })
+                    .await?;
 
-                let has_callbacks_flag = has_callbacks(
-                    &load_extension_enabled,
-                    &user_functions,
-                    &trace_callback,
-                    &authorizer_callback,
-                    &progress_handler,
-                );
+                *last_rowid.lock().await = rowid;
+                *last_changes.lock().await = changes;
+                Ok(changes)
+            };
+            runtime::future_into_py(py, dedicated_runtime.as_deref(), future)
+                .map(|bound| bound.unbind())
+        })
+    }
 
-                let opt = if in_transaction {
-                    let mut conn_guard = transaction_connection.lock().await;
-                    let conn = conn_guard.as_mut().ok_or_else(|| {
-                        OperationalError::new_err("Transaction connection not available")
-                    })?;
-                    bind_and_fetch_optional_on_connection(
-                        &processed_query,
-                        &param_values,
-                        conn,
-                        &path,
-                    )
-                    .await?
-                } else if has_callbacks_flag {
-                    // Ensure callback connection exists
-                    ensure_callback_connection(
-                        &path,
-                        &pool,
-                        &callback_connection,
-                        &pragmas,
-                        &pool_size,
-                        &connection_timeout_secs,
-                    )
+    /// Execute a script of `;`-separated SQL statements.
+    ///
+    /// The script runs with `sqlite3_exec` on a single pinned connection, like
+    /// `execute_raw()`, so it runs inside the transaction opened by `begin()` when
+    /// there is one. Semicolons inside string literals, comments and trigger bodies
+    /// don't split statements. Each statement is checked against
+    /// `freeze_statements()` before any of them runs.
+    ///
+    /// # Returns
+    ///
+    /// Returns an awaitable resolving to None. `last_insert_rowid()` and
+    /// `changes()` are updated as after `execute_raw()`.
+    ///
+    /// # Errors
+    ///
+    /// Raises the usual DatabaseError subclass for the first failing statement;
+    /// the statements before it stay applied unless the script runs in a
+    /// transaction. Outside `begin()`, a transaction the script leaves open (a
+    /// BEGIN without COMMIT, or a failure between them) is rolled back, and a
+    /// script that would otherwise succeed raises ProgrammingError.
+    ///
+    /// # Example
+    ///
+    /// .. code-block:: python
+    ///
+    ///     await conn.executescript("""
+    ///         BEGIN;
+    ///         CREATE TABLE notes (id INTEGER PRIMARY KEY, body TEXT);
+    ///         INSERT INTO notes (body) VALUES ('first; of many');
+    ///         COMMIT;
+    ///     """)
+    pub(crate) fn executescript(self_: PyRef<Self>, script: String) -> PyResult<Py<PyAny>> {
+        for statement in split_statements(&script) {
+            self_.admit_statement(statement)?;
+        }
+        let route = RawHandleRoute::new(&self_);
+        let init_hook = Arc::clone(&self_.init_hook);
+        let init_hook_called = Arc::clone(&self_.init_hook_called);
+        let last_rowid = Arc::clone(&self_.last_rowid);
+        let last_changes = Arc::clone(&self_.last_changes);
+        let udf_error = Arc::clone(&self_.udf_error);
+        let dedicated_runtime = self_.runtime.clone();
+        let connection_self: Py<Connection> = self_.into();
+
+        let script = CString::new(script)
+            .map_err(|e| ValueError::new_err(format!("Invalid SQL string: {e}")))?;
+
+        Python::attach(|py| {
+            let future = async move {
+                let in_transaction = route.in_transaction().await;
+                if !in_transaction {
+                    execute_init_hook_if_needed(&init_hook, &init_hook_called, connection_self)
+                        .await?;
+                }
+
+                // Safety: see `RawHandleRoute::run`.
+                let (changes, rowid) = route
+                    .run(|db| unsafe {
+                        let result = exec_unprepared(db, &script, &route.path);
+                        if in_transaction || sqlite3_get_autocommit(db) != 0 {
+                            return result;
+                        }
+                        // Don't leave the connection in a transaction begin() doesn't track
+                        sqlite3_exec(
+                            db,
+                            c"ROLLBACK".as_ptr(),
+                            None,
+                            std::ptr::null_mut(),
+                            std::ptr::null_mut(),
+                        );
+                        result?;
+                        Err(ProgrammingError::new_err(
+                            "executescript() left a transaction open, so it was rolled back; \
+                             end it with COMMIT in the script or use begin()",
+                        ))
+                    })
                     .await?;
 
-                    // Use callback connection
-                    let mut conn_guard = callback_connection.lock().await;
-                    let conn = conn_guard.as_mut().ok_or_else(|| {
-                        OperationalError::new_err("Callback connection not available")
-                    })?;
-                    bind_and_fetch_optional_on_connection(
-                        &processed_query,
-                        &param_values,
-                        conn,
-                        &path,
-                    )
-                    .await?
-                } else {
-                    let pool_clone = get_or_create_pool(
-                        &path,
-                        &pool,
-                        &pragmas,
-                        &pool_size,
-                        &connection_timeout_secs,
-                    )
+                *last_rowid.lock().await = rowid;
+                *last_changes.lock().await = changes;
+                Ok(())
+            };
+            runtime::future_into_py(
+                py,
+                dedicated_runtime.as_deref(),
+                with_udf_errors(udf_error, future),
+            )
+            .map(|bound| bound.unbind())
+        })
+    }
+
+    /// Validate or apply a batch of DDL statements.
+    ///
+    /// # Arguments
+    ///
+    /// * `statements` - SQL statements to run in order. Each entry may itself hold
+    ///   several `;`-separated statements.
+    /// * `dry_run` - If True, only compile each statement with `sqlite3_prepare_v2`
+    ///   and never execute it. Statements that depend on objects created earlier in
+    ///   the same batch (e.g. an index on a new table) fail validation in this mode.
+    /// * `transaction` - If True (default), apply all statements atomically inside a
+    ///   savepoint that is rolled back on the first failure. Works both outside and
+    ///   inside a `begin()`/`transaction()` block.
+    ///
+    /// # Returns
+    ///
+    /// Returns an awaitable resolving to the number of statements validated or applied.
+    ///
+    /// # Errors
+    ///
+    /// Raises the usual DatabaseError subclass for the first failing statement; the
+    /// message names the statement's index in `statements`.
+    ///
+    /// # Example
+    ///
+    /// .. code-block:: python
+    ///
+    ///     ddl = [
+    ///         "CREATE TABLE users (id INTEGER PRIMARY KEY, email TEXT)",
+    ///         "CREATE UNIQUE INDEX idx_users_email ON users(email)",
+    ///     ]
+    ///     await conn.apply_ddl(ddl[:1], dry_run=True)  # validate only
+    ///     await conn.apply_ddl(ddl)                    # all or nothing
+    #[pyo3(signature = (statements, *, dry_run = false, transaction = true))]
+    fn apply_ddl(
+        self_: PyRef<Self>,
+        statements: Vec<String>,
+        dry_run: bool,
+        transaction: bool,
+    ) -> PyResult<Py<PyAny>> {
+        for statement in &statements {
+            self_.admit_statement(statement)?;
+        }
+        let route = RawHandleRoute::new(&self_);
+        let init_hook = Arc::clone(&self_.init_hook);
+        let init_hook_called = Arc::clone(&self_.init_hook_called);
+        let dedicated_runtime = self_.runtime.clone();
+        let connection_self: Py<Connection> = self_.into();
+
+        let statements = statements
+            .into_iter()
+            .map(CString::new)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| ValueError::new_err(format!("Invalid SQL string: {e}")))?;
+
+        Python::attach(|py| {
+            let future = async move {
+                if !route.in_transaction().await {
+                    execute_init_hook_if_needed(&init_hook, &init_hook_called, connection_self)
+                        .await?;
+                }
+
+                let path = route.path.clone();
+                // Safety: see `RawHandleRoute::run`.
+                route
+                    .run(|db| unsafe {
+                        apply_ddl_statements(db, &statements, dry_run, transaction, &path)
+                    })
                     .await?;
-                    bind_and_fetch_optional(&processed_query, &param_values, &pool_clone, &path)
-                        .await?
-                };
+                Ok(statements.len())
+            };
+            runtime::future_into_py(py, dedicated_runtime.as_deref(), future)
+                .map(|bound| bound.unbind())
+        })
+    }
 
-                match opt {
-                    Some(row) => Python::attach(|py| -> PyResult<Py<PyAny>> {
-                        let guard = row_factory.lock().unwrap();
-                        let factory_opt = guard.as_ref();
-                        let tf_guard = text_factory.lock().unwrap();
-                        let tf_opt = tf_guard.as_ref();
-                        let out = row_to_py_with_factory(py, &row, factory_opt, tf_opt)?;
-                        Ok(out.unbind())
-                    }),
-                    None => Python::attach(|py| -> PyResult<Py<PyAny>> { Ok(py.None()) }),
+    /// Compute an order-independent checksum of a table's rows.
+    ///
+    /// Rows are streamed one at a time through a prepared statement, each row is
+    /// hashed with SHA-256 (values are tagged with their storage class, so `1` and
+    /// `1.0` differ), and the row hashes are summed, so the result does not depend
+    /// on row order or require holding the table in memory. Use it to check that a
+    /// replica or backup holds the same data without dumping contents.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - Table (or view) name.
+    /// * `columns` - Columns to include, in order. Defaults to every column.
+    ///
+    /// # Returns
+    ///
+    /// Returns an awaitable resolving to a 32-character hex string.
+    ///
+    /// # Example
+    ///
+    /// .. code-block:: python
+    ///
+    ///     same = await primary.table_checksum("users") == await replica.table_checksum(
+    ///         "users"
+    ///     )
+    #[pyo3(signature = (table, *, columns = None))]
+    fn table_checksum(
+        self_: PyRef<Self>,
+        table: String,
+        columns: Option<Vec<String>>,
+    ) -> PyResult<Py<PyAny>> {
+        let route = RawHandleRoute::new(&self_);
+        let init_hook = Arc::clone(&self_.init_hook);
+        let init_hook_called = Arc::clone(&self_.init_hook_called);
+        let dedicated_runtime = self_.runtime.clone();
+        let connection_self: Py<Connection> = self_.into();
+
+        let column_list = match columns {
+            Some(cols) if cols.is_empty() => {
+                return Err(ValueError::new_err("columns must not be empty"));
+            }
+            Some(cols) => cols
+                .iter()
+                .map(|c| quote_identifier(c))
+                .collect::<Vec<_>>()
+                .join(", "),
+            None => "*".to_string(),
+        };
+        let sql = CString::new(format!(
+            "SELECT {column_list} FROM {}",
+            quote_identifier(&table)
+        ))
+        .map_err(|e| ValueError::new_err(format!("Invalid identifier: {e}")))?;
+
+        Python::attach(|py| {
+            let future = async move {
+                if !route.in_transaction().await {
+                    execute_init_hook_if_needed(&init_hook, &init_hook_called, connection_self)
+                        .await?;
                 }
+
+                // Safety: see `RawHandleRoute::run`.
+                let sum = route
+                    .run(|db| unsafe { checksum_rows(db, &sql, &route.path) })
+                    .await?;
+                Ok(format!("{sum:032x}"))
             };
-            future_into_py(py, future).map(|bound| bound.unbind())
+            runtime::future_into_py(py, dedicated_runtime.as_deref(), future)
+                .map(|bound| bound.unbind())
         })
     }
 
-    /// Get the last insert row ID.
-    fn last_insert_rowid(&self) -> PyResult<Py<PyAny>> {
-        let last_rowid = Arc::clone(&self.last_rowid);
+    /// Export the rows of a query to a CSV file, optionally compressed.
+    ///
+    /// Rows are stepped one at a time through a prepared statement and written
+    /// straight through the gzip or zstd encoder, so exports of any size run in
+    /// constant memory. The output is RFC 4180 CSV as Python's `csv` module
+    /// writes it: comma-separated, CRLF line endings, fields quoted only when
+    /// needed. NULL is written as an empty field, numbers as SQLite renders
+    /// them, and BLOBs as their raw bytes.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - File to write (replaced if it exists; removed again if the
+    ///   export fails).
+    /// * `query` - SELECT to export.
+    /// * `parameters` - Parameters for `query`, as for `execute()`.
+    /// * `header` - Write the column names as the first record. Default True.
+    /// * `compression` - "gzip", "zstd", or None for plain CSV.
+    ///
+    /// # Returns
+    ///
+    /// Returns an awaitable resolving to the number of rows exported.
+    ///
+    /// # Example
+    ///
+    /// .. code-block:: python
+    ///
+    ///     rows = await conn.export_csv(
+    ///         "events.csv.zst", "SELECT * FROM events WHERE day = ?", ["2026-10-17"],
+    ///         compression="zstd",
+    ///     )
+    #[pyo3(signature = (path, query, parameters = None, *, header = true, compression = None))]
+    fn export_csv(
+        self_: PyRef<Self>,
+        path: PathBuf,
+        query: Query,
+        parameters: Option<&Bound<'_, PyAny>>,
+        header: bool,
+        compression: Option<&str>,
+    ) -> PyResult<Py<PyAny>> {
+        let (query, parameters) = self_.statement(self_.py(), query, parameters)?;
+        let parameters = parameters.as_ref();
+        let compression = crate::compression::parse_compression(compression)?;
+        self_.admit_statement(&query)?;
+        let bind_options = BindOptions {
+            large_int_policy: *self_.large_int_policy.lock().unwrap(),
+            strict: self_.strict,
+        };
+        let (query, param_values) = match parameters {
+            None => (query, Vec::new()),
+            Some(params) => process_parameters(query, params, &bind_options)?,
+        };
+        let sql =
+            CString::new(query).map_err(|e| ValueError::new_err(format!("Invalid query: {e}")))?;
+
+        let route = RawHandleRoute::new(&self_);
+        let init_hook = Arc::clone(&self_.init_hook);
+        let init_hook_called = Arc::clone(&self_.init_hook_called);
+        let dedicated_runtime = self_.runtime.clone();
+        let connection_self: Py<Connection> = self_.into();
+
         Python::attach(|py| {
-            let future = async move { Ok(*last_rowid.lock().await) };
-            future_into_py(py, future).map(|bound| bound.unbind())
+            let future = async move {
+                if !route.in_transaction().await {
+                    execute_init_hook_if_needed(&init_hook, &init_hook_called, connection_self)
+                        .await?;
+                }
+
+                route
+                    .run(|db| {
+                        let mut writer =
+                            compression::Writer::create(&path, compression).map_err(|e| {
+                                OperationalError::new_err(format!("export_csv failed: {e}"))
+                            })?;
+                        // Safety: see `RawHandleRoute::run`.
+                        let result = unsafe {
+                            export_csv_rows(
+                                db,
+                                &sql,
+                                &param_values,
+                                header,
+                                &mut writer,
+                                &route.path,
+                            )
+                        };
+                        match result {
+                            Ok(rows) => writer.finish().map(|_| rows).map_err(|e| {
+                                let _ = std::fs::remove_file(&path);
+                                OperationalError::new_err(format!("export_csv failed: {e}"))
+                            }),
+                            Err(e) => {
+                                writer.discard();
+                                Err(e)
+                            }
+                        }
+                    })
+                    .await
+            };
+            runtime::future_into_py(py, dedicated_runtime.as_deref(), future)
+                .map(|bound| bound.unbind())
         })
     }
 
-    /// Get the number of rows affected by the last statement.
-    fn changes(&self) -> PyResult<Py<PyAny>> {
-        let last_changes = Arc::clone(&self.last_changes);
+    /// Run a statement with SQLite's profiling counters enabled and report where
+    /// the time went, for tuning queries beyond what `EXPLAIN QUERY PLAN` shows.
+    ///
+    /// The statement runs to completion (rows are counted, not returned), so
+    /// writes take effect. Counters come from `sqlite3_stmt_scanstatus_v2()`
+    /// for each node of the query plan and from the `bytecode()` table for each
+    /// instruction of the program; collection is only enabled for this call.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - Statement to profile.
+    /// * `parameters` - Parameters for `query`, as for `execute()`.
+    ///
+    /// # Returns
+    ///
+    /// Returns an awaitable resolving to a dict with:
+    ///
+    /// * `rows`, `elapsed` (seconds), `vm_steps`, `fullscan_steps`, `sorts` and
+    ///   `autoindex_rows` for the whole statement.
+    /// * `plan` - One dict per `EXPLAIN QUERY PLAN` node: `id`, `parent` and
+    ///   `detail` as `EXPLAIN QUERY PLAN` lists them, plus `name` (table or
+    ///   index read), `loops`, `visits` (rows visited over all loops),
+    ///   `estimated_rows` (per loop) and `cycles`.
+    /// * `opcodes` - One dict per `EXPLAIN` row: `addr`, `opcode`, `p1` to
+    ///   `p5` and `comment` as `EXPLAIN` lists them, plus `subprog` (trigger
+    ///   program, None for the statement itself), `executions` and `cycles`.
+    ///
+    /// # Example
+    ///
+    /// .. code-block:: python
+    ///
+    ///     profile = await conn.profile("SELECT * FROM t WHERE v = ?", [3])
+    ///     for node in profile["plan"]:
+    ///         print(node["detail"], node["loops"], node["visits"])
+    ///
+    /// Raises `NotSupportedError` when rapsqlite was built without
+    /// `SQLITE_ENABLE_STMT_SCANSTATUS` and `SQLITE_ENABLE_BYTECODE_VTAB` in
+    /// `LIBSQLITE3_FLAGS`.
+    #[pyo3(signature = (query, parameters = None))]
+    fn profile(
+        self_: PyRef<Self>,
+        query: Query,
+        parameters: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<Py<PyAny>> {
+        if !profile::SUPPORTED {
+            return Err(NotSupportedError::new_err(
+                "profile() needs SQLite built with SQLITE_ENABLE_STMT_SCANSTATUS and \
+                 SQLITE_ENABLE_BYTECODE_VTAB; set them in LIBSQLITE3_FLAGS when building rapsqlite",
+            ));
+        }
+        let (query, parameters) = self_.statement(self_.py(), query, parameters)?;
+        let parameters = parameters.as_ref();
+        self_.admit_statement(&query)?;
+        let bind_options = BindOptions {
+            large_int_policy: *self_.large_int_policy.lock().unwrap(),
+            strict: self_.strict,
+        };
+        let (query, param_values) = match parameters {
+            None => (query, Vec::new()),
+            Some(params) => process_parameters(query, params, &bind_options)?,
+        };
+        let sql =
+            CString::new(query).map_err(|e| ValueError::new_err(format!("Invalid query: {e}")))?;
+
+        let route = RawHandleRoute::new(&self_);
+        let init_hook = Arc::clone(&self_.init_hook);
+        let init_hook_called = Arc::clone(&self_.init_hook_called);
+        let dedicated_runtime = self_.runtime.clone();
+        let connection_self: Py<Connection> = self_.into();
+
         Python::attach(|py| {
-            let future = async move { Ok(*last_changes.lock().await) };
-            future_into_py(py, future).map(|bound| bound.unbind())
+            let future = async move {
+                if !route.in_transaction().await {
+                    execute_init_hook_if_needed(&init_hook, &init_hook_called, connection_self)
+                        .await?;
+                }
+
+                let profile = route
+                    .run(|db| {
+                        // Safety: see `RawHandleRoute::run`.
+                        let result = unsafe { profile::profile(db, &sql, &param_values) };
+                        result_cache::note_statement(&route.path, &sql.to_string_lossy());
+                        result.map_err(|rc| {
+                            // Safety: as above.
+                            let msg = unsafe { cstr_from_i8_ptr(sqlite3_errmsg(db)) }
+                                .to_string_lossy()
+                                .into_owned();
+                            map_sqlite_error(rc, &msg, &route.path)
+                        })
+                    })
+                    .await?;
+                Python::attach(|py| profile_to_dict(py, &profile))
+            };
+            runtime::future_into_py(py, dedicated_runtime.as_deref(), future)
+                .map(|bound| bound.unbind())
+        })
+    }
+
+    /// Whether `query` only reads: every statement in it prepares to one that
+    /// leaves the database unchanged (`sqlite3_stmt_readonly`). Frameworks can
+    /// use it to send reads to a pool of readers and writes to the one
+    /// writer.
+    ///
+    /// Nothing is run and parameters need not be bound. `EXPLAIN`, `BEGIN`,
+    /// `COMMIT` and `ATTACH` count as read-only; `INSERT ... RETURNING` and
+    /// `WITH ... INSERT` don't. SQL that fails to prepare, e.g. because a
+    /// table is missing, raises the error running it would.
+    ///
+    /// Returns an awaitable resolving to a bool.
+    ///
+    /// # Example
+    ///
+    /// .. code-block:: python
+    ///
+    ///     target = reader if await conn.is_readonly_query(sql) else writer
+    fn is_readonly_query(self_: PyRef<Self>, query: Query) -> PyResult<Py<PyAny>> {
+        let (query, _) = self_.statement(self_.py(), query, None)?;
+        let sql =
+            CString::new(query).map_err(|e| ValueError::new_err(format!("Invalid query: {e}")))?;
+
+        let route = RawHandleRoute::new(&self_);
+        let init_hook = Arc::clone(&self_.init_hook);
+        let init_hook_called = Arc::clone(&self_.init_hook_called);
+        let dedicated_runtime = self_.runtime.clone();
+        let connection_self: Py<Connection> = self_.into();
+
+        Python::attach(|py| {
+            let future = async move {
+                if !route.in_transaction().await {
+                    execute_init_hook_if_needed(&init_hook, &init_hook_called, connection_self)
+                        .await?;
+                }
+
+                route
+                    .run(|db| {
+                        // Safety: see `RawHandleRoute::run`.
+                        unsafe { rapsqlite_core::query::is_readonly(db, &sql) }.map_err(|rc| {
+                            // Safety: as above.
+                            let msg = unsafe { cstr_from_i8_ptr(sqlite3_errmsg(db)) }
+                                .to_string_lossy()
+                                .into_owned();
+                            map_sqlite_error(rc, &msg, &route.path)
+                        })
+                    })
+                    .await
+            };
+            runtime::future_into_py(py, dedicated_runtime.as_deref(), future)
+                .map(|bound| bound.unbind())
         })
     }
 
@@ -1901,8 +3582,20 @@ impl Connection {
         let pragmas = Arc::clone(&slf.pragmas);
         let pool_size = Arc::clone(&slf.pool_size);
         let connection_timeout_secs = Arc::clone(&slf.connection_timeout_secs);
-        let row_factory = Arc::clone(&slf.row_factory);
-        let text_factory = Arc::clone(&slf.text_factory);
+        let statement_policy = slf.statement_policy.clone();
+        let native_functions = slf.native_functions;
+        let wal_hook = slf.wal_hook.clone();
+        let vfs = slf.vfs.clone();
+        let event_hooks = Arc::clone(&slf.event_hooks);
+        let runtime = slf.runtime.clone();
+        let row_factory = slf.factory(slf.py(), "row_factory", &slf.row_factory)?;
+        let text_factory = slf.factory(slf.py(), "text_factory", &slf.text_factory)?;
+        let null_floats_as_nan = Arc::clone(&slf.null_floats_as_nan);
+        let large_int_policy = Arc::clone(&slf.large_int_policy);
+        let decode_large_ints = Arc::clone(&slf.decode_large_ints);
+        let invalid_utf8 = Arc::clone(&slf.invalid_utf8);
+        let strict = slf.strict;
+        let detect_types = slf.detect_types;
         let transaction_state = Arc::clone(&slf.transaction_state);
         let transaction_connection = Arc::clone(&slf.transaction_connection);
         let callback_connection = Arc::clone(&slf.callback_connection);
@@ -1911,11 +3604,17 @@ impl Connection {
         let trace_callback = Arc::clone(&slf.trace_callback);
         let authorizer_callback = Arc::clone(&slf.authorizer_callback);
         let progress_handler = Arc::clone(&slf.progress_handler);
+        let update_hook = Arc::clone(&slf.update_hook);
+        let udf_error = Arc::clone(&slf.udf_error);
+        let results: CursorResults = Arc::new(StdMutex::new(None));
+        let closed = slf.cursors.track(&results);
         Ok(Cursor {
             connection: slf.into(),
             query: String::new(),
-            results: Arc::new(StdMutex::new(None)),
+            results,
+            closed,
             current_index: Arc::new(StdMutex::new(0)),
+            arraysize: 1,
             parameters: Arc::new(StdMutex::new(None)),
             processed_query: None,  // No processed query for cursor() method
             processed_params: None, // No processed params for cursor() method
@@ -1924,8 +3623,21 @@ impl Connection {
             connection_pragmas: pragmas,
             pool_size,
             connection_timeout_secs,
+            statement_policy,
+            native_functions,
+            wal_hook,
+            vfs,
+            event_hooks,
+            runtime,
             row_factory,
             text_factory,
+            null_floats_as_nan,
+            large_int_policy,
+            decode_large_ints,
+            invalid_utf8,
+            strict,
+            detect_types,
+            execute_result: Arc::new(StdMutex::new(None)),
             transaction_state,
             transaction_connection,
             callback_connection,
@@ -1934,6 +3646,8 @@ impl Connection {
             trace_callback,
             authorizer_callback,
             progress_handler,
+            update_hook,
+            udf_error,
         })
     }
 
@@ -1944,13 +3658,29 @@ impl Connection {
         query: String,
         parameters: Option<Py<PyAny>>,
     ) -> PyResult<Cursor> {
+        // The cursor processes its parameters on each fetch
+        let parameters = parameters
+            .map(|params| materialize_parameters(params.bind(slf.py())).map(Bound::unbind))
+            .transpose()?;
         let path = slf.path.clone();
         let pool = Arc::clone(&slf.pool);
         let pragmas = Arc::clone(&slf.pragmas);
         let pool_size = Arc::clone(&slf.pool_size);
         let connection_timeout_secs = Arc::clone(&slf.connection_timeout_secs);
-        let row_factory = Arc::clone(&slf.row_factory);
-        let text_factory = Arc::clone(&slf.text_factory);
+        let statement_policy = slf.statement_policy.clone();
+        let native_functions = slf.native_functions;
+        let wal_hook = slf.wal_hook.clone();
+        let vfs = slf.vfs.clone();
+        let event_hooks = Arc::clone(&slf.event_hooks);
+        let runtime = slf.runtime.clone();
+        let row_factory = slf.factory(slf.py(), "row_factory", &slf.row_factory)?;
+        let text_factory = slf.factory(slf.py(), "text_factory", &slf.text_factory)?;
+        let null_floats_as_nan = Arc::clone(&slf.null_floats_as_nan);
+        let large_int_policy = Arc::clone(&slf.large_int_policy);
+        let decode_large_ints = Arc::clone(&slf.decode_large_ints);
+        let invalid_utf8 = Arc::clone(&slf.invalid_utf8);
+        let strict = slf.strict;
+        let detect_types = slf.detect_types;
         let transaction_state = Arc::clone(&slf.transaction_state);
         let transaction_connection = Arc::clone(&slf.transaction_connection);
         let callback_connection = Arc::clone(&slf.callback_connection);
@@ -1959,11 +3689,17 @@ impl Connection {
         let trace_callback = Arc::clone(&slf.trace_callback);
         let authorizer_callback = Arc::clone(&slf.authorizer_callback);
         let progress_handler = Arc::clone(&slf.progress_handler);
+        let update_hook = Arc::clone(&slf.update_hook);
+        let udf_error = Arc::clone(&slf.udf_error);
+        let results: CursorResults = Arc::new(StdMutex::new(None));
+        let closed = slf.cursors.track(&results);
         Ok(Cursor {
             connection: slf.into(),
             query,
-            results: Arc::new(StdMutex::new(None)),
+            results,
+            closed,
             current_index: Arc::new(StdMutex::new(0)),
+            arraysize: 1,
             parameters: Arc::new(StdMutex::new(parameters)),
             processed_query: None, // No processed query for create_cursor_with_query() method
             processed_params: None, // No processed params for create_cursor_with_query() method
@@ -1972,8 +3708,21 @@ impl Connection {
             connection_pragmas: pragmas,
             pool_size,
             connection_timeout_secs,
+            statement_policy,
+            native_functions,
+            wal_hook,
+            vfs,
+            event_hooks,
+            runtime,
             row_factory,
             text_factory,
+            null_floats_as_nan,
+            large_int_policy,
+            decode_large_ints,
+            invalid_utf8,
+            strict,
+            detect_types,
+            execute_result: Arc::new(StdMutex::new(None)),
             transaction_state,
             transaction_connection,
             callback_connection,
@@ -1982,6 +3731,8 @@ impl Connection {
             trace_callback,
             authorizer_callback,
             progress_handler,
+            update_hook,
+            udf_error,
         })
     }
 
@@ -1993,11 +3744,18 @@ impl Connection {
         let pragmas = Arc::clone(&slf.pragmas);
         let pool_size = Arc::clone(&slf.pool_size);
         let connection_timeout_secs = Arc::clone(&slf.connection_timeout_secs);
+        let statement_policy = slf.statement_policy.clone();
+        let native_functions = slf.native_functions;
+        let wal_hook = slf.wal_hook.clone();
+        let vfs = slf.vfs.clone();
+        let event_hooks = Arc::clone(&slf.event_hooks);
+        let runtime = slf.runtime.clone();
         let transaction_state = Arc::clone(&slf.transaction_state);
         let transaction_connection = Arc::clone(&slf.transaction_connection);
         let init_hook = Arc::clone(&slf.init_hook);
         let init_hook_called = Arc::clone(&slf.init_hook_called);
         let timeout = Arc::clone(&slf.timeout);
+        let task_audit = slf.task_audit.clone();
         let connection: Py<Connection> = slf.into();
         Ok(TransactionContextManager {
             path,
@@ -2005,46 +3763,282 @@ impl Connection {
             pragmas,
             pool_size,
             connection_timeout_secs,
+            statement_policy,
+            native_functions,
+            wal_hook,
+            vfs,
+            event_hooks,
+            runtime,
             transaction_state,
             transaction_connection,
             connection,
             init_hook,
             init_hook_called,
             timeout,
+            task_audit,
         })
     }
 
-    /// Set a PRAGMA value on the database connection.
-    fn set_pragma(
-        self_: PyRef<Self>,
-        name: String,
-        value: &Bound<'_, PyAny>,
-    ) -> PyResult<Py<PyAny>> {
-        let path = self_.path.clone();
-        let pool = Arc::clone(&self_.pool);
-        let pragmas = Arc::clone(&self_.pragmas);
-        let pool_size = Arc::clone(&self_.pool_size);
-        let connection_timeout_secs = Arc::clone(&self_.connection_timeout_secs);
-        // Init hook infrastructure (Phase 2.11)
+    /// Return an async context manager that defers foreign key enforcement
+    /// in the enclosing transaction until the block exits, so rows can be
+    /// reparented in any order (e.g. replacing a parent key and then its
+    /// children's references).
+    ///
+    /// Entering runs `PRAGMA defer_foreign_keys = ON` on the transaction's
+    /// connection. When the block exits without an exception,
+    /// `PRAGMA foreign_key_check` must find no violation left anywhere in the
+    /// database, and enforcement is immediate again for the rest of the
+    /// transaction.
+    ///
+    /// # Example
+    ///
+    /// ```python
+    /// async with conn.transaction():
+    ///     async with conn.defer_foreign_keys():
+    ///         await conn.execute("UPDATE parent SET id = 2 WHERE id = 1")
+    ///         await conn.execute("UPDATE child SET parent_id = 2 WHERE parent_id = 1")
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Entering raises OperationalError when no transaction is active. Exiting
+    /// raises IntegrityError listing the violations found; the transaction is
+    /// left open (`transaction()` then rolls it back).
+    fn defer_foreign_keys(slf: PyRef<Self>) -> DeferForeignKeysContextManager {
+        DeferForeignKeysContextManager {
+            path: slf.path.clone(),
+            runtime: slf.runtime.clone(),
+            transaction_state: Arc::clone(&slf.transaction_state),
+            transaction_connection: Arc::clone(&slf.transaction_connection),
+            connection: slf.into(),
+        }
+    }
+
+    /// Return an async context manager running its block in a savepoint of the
+    /// active transaction, so blocks can nest: the savepoint is released when
+    /// the block exits, and rolled back to when it raises, undoing only the
+    /// block's changes. Entering resolves to the connection.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Savepoint name; a unique one is generated when omitted.
+    ///
+    /// # Example
+    ///
+    /// ```python
+    /// async with conn.transaction():
+    ///     await conn.execute("INSERT INTO orders (id) VALUES (1)")
+    ///     try:
+    ///         async with conn.savepoint():
+    ///             await conn.execute("INSERT INTO audit (order_id) VALUES (1)")
+    ///             raise RuntimeError("audit failed")
+    ///     except RuntimeError:
+    ///         pass  # the order is kept, the audit row is not
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Entering raises OperationalError when no transaction is active.
+    #[pyo3(signature = (name = None))]
+    fn savepoint(slf: PyRef<Self>, name: Option<String>) -> SavepointContextManager {
+        SavepointContextManager {
+            path: slf.path.clone(),
+            runtime: slf.runtime.clone(),
+            transaction_state: Arc::clone(&slf.transaction_state),
+            transaction_connection: Arc::clone(&slf.transaction_connection),
+            name: name.unwrap_or_else(SavepointContextManager::generated_name),
+            connection: slf.into(),
+        }
+    }
+
+    /// Return an async context manager that overrides `row_factory` and/or
+    /// `text_factory` for the statements run inside its block.
+    ///
+    /// The overrides are held in a context variable, so they apply only to the
+    /// task that entered the block (and tasks it creates) and leave the
+    /// connection's own settings untouched: concurrent tasks sharing one
+    /// Connection can each use their own row format without racing on the
+    /// attributes. Blocks nest; the innermost override wins. Inside the block
+    /// the `row_factory` / `text_factory` attributes report the override, and
+    /// cursors created there keep it after the block exits.
+    ///
+    /// # Arguments
+    ///
+    /// * `row_factory` - None, "dict", "tuple" or a callable, as for the
+    ///   attribute.
+    /// * `text_factory` - None or a callable(bytes), as for the attribute.
+    ///
+    /// # Errors
+    ///
+    /// Raises TypeError for any other keyword argument.
+    ///
+    /// # Example
+    ///
+    /// ```python
+    /// async with conn.options(row_factory="dict"):
+    ///     user = await conn.fetch_one("SELECT * FROM users WHERE id = 1")
+    ///     # {"id": 1, "name": "Alice"}; conn.row_factory is unchanged outside
+    /// ```
+    #[pyo3(signature = (**overrides))]
+    fn options(
+        slf: PyRef<Self>,
+        overrides: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<OptionsContextManager> {
+        let py = slf.py();
+        let overrides = match overrides {
+            Some(overrides) => overrides.copy()?,
+            None => PyDict::new(py),
+        };
+        for key in overrides.keys() {
+            let key: String = key.extract()?;
+            if key != "row_factory" && key != "text_factory" {
+                return Err(pyo3::exceptions::PyTypeError::new_err(format!(
+                    "options() got an unexpected keyword argument '{key}': expected \
+                     'row_factory' or 'text_factory'"
+                )));
+            }
+        }
+        Ok(OptionsContextManager {
+            overrides: overrides.unbind(),
+            variable: slf.option_overrides.clone_ref(py),
+            tokens: StdMutex::new(Vec::new()),
+            connection: slf.into(),
+        })
+    }
+
+    /// Set named parameters merged into every statement run with named
+    /// parameters, e.g. a tenant id used by multi-tenant filters, so it need
+    /// not be passed at every call site.
+    ///
+    /// A mapping given to `execute()`, `fetch_*()`, `stream()`, `export_csv()`
+    /// or `Cursor.execute()` gets the defaults it doesn't set itself (its own
+    /// values win), and a statement run without parameters binds its named
+    /// placeholders from the defaults. Positional parameters are not affected.
+    ///
+    /// # Arguments
+    ///
+    /// * `params` - Mapping of parameter names (without the `:`, `@` or `$`
+    ///   prefix) to values, copied when set; None removes the defaults.
+    ///
+    /// # Example
+    ///
+    /// ```python
+    /// conn.set_default_params({"tenant_id": 42})
+    /// rows = await conn.fetch_all("SELECT * FROM orders WHERE tenant_id = :tenant_id")
+    /// ```
+    #[pyo3(signature = (params))]
+    fn set_default_params(&self, params: Option<&Bound<'_, PyAny>>) -> PyResult<()> {
+        let defaults = match params {
+            None => None,
+            Some(params) => {
+                let mapping = params.cast::<pyo3::types::PyMapping>().map_err(|_| {
+                    pyo3::exceptions::PyTypeError::new_err(
+                        "set_default_params() expects a mapping of parameter names or None",
+                    )
+                })?;
+                let defaults = PyDict::new(params.py());
+                defaults.update(mapping)?;
+                for key in defaults.keys() {
+                    if !key.is_instance_of::<PyString>() {
+                        return Err(pyo3::exceptions::PyTypeError::new_err(
+                            "set_default_params() parameter names must be strings",
+                        ));
+                    }
+                }
+                Some(defaults.unbind())
+            }
+        };
+        *self.default_params.lock().unwrap() = defaults;
+        Ok(())
+    }
+
+    /// Set an async hook called on a lifecycle event of the pooled
+    /// connections, e.g. to apply per-connection settings or log pool churn.
+    /// Unlike `init_hook`, which runs once per Connection, hooks run for every
+    /// pooled connection (and, with `shared_pool`, are shared by all the
+    /// Connections of the pool).
+    ///
+    /// Hooks receive a `PooledConnection` with an `id` and an
+    /// `execute(sql)` method running statements on that connection while the
+    /// hook runs. They run on the event loop running when they were set.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - "connect" (a connection was opened), "acquire" (it is
+    ///   about to be used), "release" (it was returned to the pool) or
+    ///   "close" (it was closed; `execute()` is not available).
+    /// * `hook` - `async def hook(conn)`, or None to remove the hook.
+    ///
+    /// When a "connect" hook fails, the connection is discarded and opening
+    /// one is retried until `connection_timeout`; a failing "acquire" or
+    /// "release" hook closes the connection instead of using or keeping it.
+    /// Errors of "close" hooks are reported with `sys.unraisablehook`.
+    ///
+    /// # Example
+    ///
+    /// ```python
+    /// async def on_connect(conn):
+    ///     await conn.execute("PRAGMA cache_size = -20000")
+    ///
+    /// conn.set_event_hook("connect", on_connect)
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Raises ValueError for an unknown event, TypeError if `hook` is not an
+    /// async function, and ProgrammingError when no event loop is running.
+    #[pyo3(signature = (event, hook))]
+    fn set_event_hook(&self, py: Python<'_>, event: &str, hook: Option<Py<PyAny>>) -> PyResult<()> {
+        self.event_hooks.set(py, event, hook)
+    }
+
+    /// Set a PRAGMA value on the database connection.
+    ///
+    /// The value is also applied to connections the pool opens later.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - PRAGMA name, e.g. "journal_mode".
+    /// * `value` - bool (set as 1/0), int, float, str or None.
+    ///
+    /// # Returns
+    ///
+    /// The effective value SQLite reports for the PRAGMA after setting it
+    /// (e.g. "wal" for journal_mode, 1 for foreign_keys), or None if the
+    /// PRAGMA reports nothing.
+    ///
+    /// # Example
+    ///
+    /// ```python
+    /// assert await conn.set_pragma("foreign_keys", True) == 1
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Raises TypeError for a value of another type.
+    fn set_pragma(
+        self_: PyRef<Self>,
+        name: String,
+        value: &Bound<'_, PyAny>,
+    ) -> PyResult<Py<PyAny>> {
+        audit_setting(&self_.task_audit, &format!("pragma {name}"))?;
+        let path = self_.path.clone();
+        let pool = Arc::clone(&self_.pool);
+        let pragmas = Arc::clone(&self_.pragmas);
+        let pool_size = Arc::clone(&self_.pool_size);
+        let connection_timeout_secs = Arc::clone(&self_.connection_timeout_secs);
+        let statement_policy = self_.statement_policy.clone();
+        let native_functions = self_.native_functions;
+        let wal_hook = self_.wal_hook.clone();
+        let vfs = self_.vfs.clone();
+        let event_hooks = Arc::clone(&self_.event_hooks);
+        // Init hook infrastructure (Phase 2.11)
         let init_hook = Arc::clone(&self_.init_hook);
         let init_hook_called = Arc::clone(&self_.init_hook_called);
+        let dedicated_runtime = self_.runtime.clone();
         let connection_self = self_.into();
 
-        // Convert value to string for PRAGMA
-        // Note: Python::with_gil is used here for sync PRAGMA value conversion before async execution.
-        // The deprecation warning is acceptable as this is a sync context.
-        #[allow(deprecated)]
-        let pragma_value = Python::with_gil(|_py| -> PyResult<String> {
-            if value.is_none() {
-                Ok("NULL".to_string())
-            } else if let Ok(int_val) = value.extract::<i64>() {
-                Ok(int_val.to_string())
-            } else if let Ok(str_val) = value.extract::<String>() {
-                Ok(format!("'{}'", str_val.replace("'", "''"))) // Escape single quotes
-            } else {
-                Ok(format!("'{}'", value.to_string().replace("'", "''")))
-            }
-        })?;
+        let pragma_value = pragma_literal(value)?;
 
         // Store PRAGMA for future connections
         {
@@ -2080,20 +4074,40 @@ impl Connection {
                     &pragmas,
                     &pool_size,
                     &connection_timeout_secs,
+                    &statement_policy,
+                    native_functions,
+                    &wal_hook,
+                    &vfs,
+                    &event_hooks,
                 )
                 .await?;
 
                 // Execute init_hook if needed (before setting PRAGMA)
                 execute_init_hook_if_needed(&init_hook, &init_hook_called, connection_self).await?;
 
-                sqlx::query(&pragma_query)
-                    .execute(&pool_clone)
+                // Read the value back on the same connection: PRAGMAs such as
+                // journal_mode report it from the assignment itself, the others
+                // from a plain `PRAGMA name`.
+                let mut conn = pool_clone.acquire_for(&path).await?;
+                let mut row = sqlx::query(&pragma_query)
+                    .fetch_optional(&mut *conn)
                     .await
                     .map_err(|e| map_sqlx_error(e, &path, &pragma_query))?;
+                if row.is_none() {
+                    let read_query = format!("PRAGMA {name}");
+                    row = sqlx::query(&read_query)
+                        .fetch_optional(&mut *conn)
+                        .await
+                        .map_err(|e| map_sqlx_error(e, &path, &read_query))?;
+                }
 
-                Ok(())
+                Python::attach(|py| match row.filter(|row| !row.is_empty()) {
+                    Some(row) => sqlite_value_to_py(py, &row, 0, &DecodeOptions::default()),
+                    None => Ok(py.None()),
+                })
             };
-            future_into_py(py, future).map(|bound| bound.unbind())
+            runtime::future_into_py(py, dedicated_runtime.as_deref(), future)
+                .map(|bound| bound.unbind())
         })
     }
 
@@ -2105,6 +4119,11 @@ impl Connection {
         let pragmas = Arc::clone(&self.pragmas);
         let pool_size = Arc::clone(&self.pool_size);
         let connection_timeout_secs = Arc::clone(&self.connection_timeout_secs);
+        let statement_policy = self.statement_policy.clone();
+        let native_functions = self.native_functions;
+        let wal_hook = self.wal_hook.clone();
+        let vfs = self.vfs.clone();
+        let event_hooks = Arc::clone(&self.event_hooks);
         let load_extension_enabled = Arc::clone(&self.load_extension_enabled);
 
         Python::attach(|py| {
@@ -2117,6 +4136,11 @@ impl Connection {
                     &pragmas,
                     &pool_size,
                     &connection_timeout_secs,
+                    &statement_policy,
+                    native_functions,
+                    &wal_hook,
+                    &vfs,
+                    &event_hooks,
                 )
                 .await?;
 
@@ -2157,7 +4181,7 @@ impl Connection {
 
                 Ok(())
             };
-            future_into_py(py, future).map(|bound| bound.unbind())
+            runtime::future_into_py(py, self.runtime.as_deref(), future).map(|bound| bound.unbind())
         })
     }
 
@@ -2170,6 +4194,11 @@ impl Connection {
         let pragmas = Arc::clone(&self.pragmas);
         let pool_size = Arc::clone(&self.pool_size);
         let connection_timeout_secs = Arc::clone(&self.connection_timeout_secs);
+        let statement_policy = self.statement_policy.clone();
+        let native_functions = self.native_functions;
+        let wal_hook = self.wal_hook.clone();
+        let vfs = self.vfs.clone();
+        let event_hooks = Arc::clone(&self.event_hooks);
         let load_extension_enabled = Arc::clone(&self.load_extension_enabled);
 
         Python::attach(|py| {
@@ -2194,6 +4223,11 @@ impl Connection {
                     &pragmas,
                     &pool_size,
                     &connection_timeout_secs,
+                    &statement_policy,
+                    native_functions,
+                    &wal_hook,
+                    &vfs,
+                    &event_hooks,
                 )
                 .await?;
 
@@ -2255,17 +4289,32 @@ impl Connection {
 
                 Ok(())
             };
-            future_into_py(py, future).map(|bound| bound.unbind())
+            runtime::future_into_py(py, self.runtime.as_deref(), future).map(|bound| bound.unbind())
         })
     }
 
     /// Create or remove a user-defined SQL function.
     /// If func is None, the function is removed.
+    /// Like SQLite, each (name, nargs) pair is a separate overload: registering the same
+    /// name with another nargs adds an overload, nargs=-1 accepts any number of arguments
+    /// (up to SQLite's limit of 127 per call), passed to func as positional arguments,
+    /// and is used only when no exact-arity overload exists, and removing with func=None
+    /// only removes the overload with that nargs.
+    /// If func is an `async def`, it must be registered while the event loop is running;
+    /// each call schedules the coroutine on that loop and blocks only the calling statement
+    /// until it completes or `callback_timeout` expires.
+    /// If func raises, the query that called it re-raises the original exception,
+    /// with the underlying DatabaseError as its `__cause__`.
+    /// With `deterministic=True` the function is registered with SQLITE_DETERMINISTIC,
+    /// promising the same result for the same arguments, so it can be used in index
+    /// expressions, generated columns and partial-index WHERE clauses.
+    #[pyo3(signature = (name, nargs, func, *, deterministic = false))]
     fn create_function(
         &self,
         name: String,
         nargs: i32,
         func: Option<Py<PyAny>>,
+        deterministic: bool,
     ) -> PyResult<Py<PyAny>> {
         // SQLite supports nargs in [-1, 127]. (-1 means "any number of args".)
         if !(-1..=127).contains(&nargs) {
@@ -2274,22 +4323,41 @@ impl Connection {
             )));
         }
 
+        // SQLite function names are case-insensitive; each arity is its own overload
+        let function_key = (name.to_ascii_lowercase(), nargs);
+
         let path = self.path.clone();
         let pool = Arc::clone(&self.pool);
         let callback_connection = Arc::clone(&self.callback_connection);
         let pragmas = Arc::clone(&self.pragmas);
         let pool_size = Arc::clone(&self.pool_size);
         let connection_timeout_secs = Arc::clone(&self.connection_timeout_secs);
+        let statement_policy = self.statement_policy.clone();
+        let native_functions = self.native_functions;
+        let wal_hook = self.wal_hook.clone();
+        let vfs = self.vfs.clone();
+        let event_hooks = Arc::clone(&self.event_hooks);
         let user_functions = Arc::clone(&self.user_functions);
         // Need all callback fields to check if all are cleared
         let load_extension_enabled = Arc::clone(&self.load_extension_enabled);
         let trace_callback = Arc::clone(&self.trace_callback);
         let authorizer_callback = Arc::clone(&self.authorizer_callback);
         let progress_handler = Arc::clone(&self.progress_handler);
+        let update_hook = Arc::clone(&self.update_hook);
+        let udf_error = Arc::clone(&self.udf_error);
+        let invalid_utf8 = Arc::clone(&self.invalid_utf8);
+        let large_int_policy = Arc::clone(&self.large_int_policy);
+        let callback_dispatcher = Arc::clone(&self.callback_dispatcher);
+        let callback_timeout = Arc::clone(&self.callback_timeout);
 
         Python::attach(|py| {
             // Clone the callback with GIL to avoid Send issues
             let func_clone = func.as_ref().map(|f| f.clone_ref(py));
+            // `async def` functions run on the event loop that registered them
+            let async_bridge = match func.as_ref() {
+                Some(f) => AsyncBridge::for_function(py, f.bind(py), &callback_timeout)?,
+                None => None,
+            };
 
             let future = async move {
                 // Ensure callback connection exists (needed for both adding and removing functions)
@@ -2300,6 +4368,11 @@ impl Connection {
                     &pragmas,
                     &pool_size,
                     &connection_timeout_secs,
+                    &statement_policy,
+                    native_functions,
+                    &wal_hook,
+                    &vfs,
+                    &event_hooks,
                 )
                 .await?;
 
@@ -2315,194 +4388,125 @@ impl Connection {
                 })?;
                 let raw_db = handle.as_raw_handle().as_ptr();
 
-                if func_clone.is_none() {
-                    // Remove the function from user_functions
-                    {
-                        let mut funcs_guard = user_functions.lock().unwrap();
-                        funcs_guard.remove(&name);
-                    }
+                match func_clone {
+                    None => {
+                        // Remove this overload from user_functions (other arities stay registered)
+                        {
+                            let mut funcs_guard = user_functions.lock().unwrap();
+                            funcs_guard.remove(&function_key);
+                        }
 
-                    // Remove from SQLite by calling sqlite3_create_function_v2 with NULL callback
-                    let name_cstr = std::ffi::CString::new(name.clone()).map_err(|e| {
-                        OperationalError::new_err(format!("Function name contains null byte: {e}"))
-                    })?;
-                    // Safety: raw_db is a valid sqlite3* pointer obtained from
-                    // lock_handle().as_raw_handle().as_ptr() and is guaranteed to be valid
-                    // for the lifetime of the handle lock. name_cstr is a valid CString.
-                    // We pass NULL for all callbacks to remove the function, which is safe.
-                    let result = unsafe {
-                        sqlite3_create_function_v2(
-                            raw_db,
-                            name_cstr.as_ptr(),
-                            nargs,
-                            SQLITE_UTF8,
-                            std::ptr::null_mut(), // pApp (user data)
-                            None,                 // xFunc (scalar function callback)
-                            None,                 // xStep (aggregate step callback)
-                            None,                 // xFinal (aggregate final callback)
-                            None,                 // xDestroy (destructor)
-                        )
-                    };
+                        // Remove from SQLite by calling sqlite3_create_function_v2 with NULL callback
+                        let name_cstr = std::ffi::CString::new(name.clone()).map_err(|e| {
+                            OperationalError::new_err(format!(
+                                "Function name contains null byte: {e}"
+                            ))
+                        })?;
+                        // Safety: raw_db is a valid sqlite3* pointer obtained from
+                        // lock_handle().as_raw_handle().as_ptr() and is guaranteed to be valid
+                        // for the lifetime of the handle lock. name_cstr is a valid CString.
+                        // We pass NULL for all callbacks to remove the function, which is safe.
+                        let result = unsafe {
+                            sqlite3_create_function_v2(
+                                raw_db,
+                                name_cstr.as_ptr(),
+                                nargs,
+                                SQLITE_UTF8,
+                                std::ptr::null_mut(), // pApp (user data)
+                                None,                 // xFunc (scalar function callback)
+                                None,                 // xStep (aggregate step callback)
+                                None,                 // xFinal (aggregate final callback)
+                                None,                 // xDestroy (destructor)
+                            )
+                        };
 
-                    if result != SQLITE_OK {
-                        return Err(OperationalError::new_err(format!(
-                            "Failed to remove function '{name}': SQLite error code {result}"
-                        )));
-                    }
+                        if result != SQLITE_OK {
+                            return Err(OperationalError::new_err(format!(
+                                "Failed to remove function '{name}': SQLite error code {result}"
+                            )));
+                        }
 
-                    // After removing, check if all callbacks are now cleared
-                    let all_cleared = !has_callbacks(
-                        &load_extension_enabled,
-                        &user_functions,
-                        &trace_callback,
-                        &authorizer_callback,
-                        &progress_handler,
-                    );
-                    if all_cleared {
-                        // Release the callback connection
-                        drop(handle);
-                        drop(conn_guard);
-                        let mut callback_guard = callback_connection.lock().await;
-                        callback_guard.take();
-                        return Ok(());
-                    }
-                } else {
-                    // Store the function - need to clone the callback with GIL
-                    // Note: Python::with_gil is used here for sync callback storage in async context.
-                    // The deprecation warning is acceptable as this is a sync operation within async.
-                    #[allow(deprecated)]
-                    let callback_for_storage =
-                        Python::with_gil(|py| func_clone.as_ref().unwrap().clone_ref(py));
-                    {
-                        let mut funcs_guard = user_functions.lock().unwrap();
-                        funcs_guard.insert(name.clone(), (nargs, callback_for_storage));
+                        // After removing, check if all callbacks are now cleared
+                        let all_cleared = !has_callbacks(
+                            &load_extension_enabled,
+                            &user_functions,
+                            &trace_callback,
+                            &authorizer_callback,
+                            &progress_handler,
+                            &update_hook,
+                        );
+                        if all_cleared {
+                            // Release the callback connection
+                            drop(handle);
+                            drop(conn_guard);
+                            let mut callback_guard = callback_connection.lock().await;
+                            callback_guard.take();
+                            return Ok(());
+                        }
                     }
+                    Some(func) => {
+                        // Store the function. user_functions and the SQLite context share
+                        // one reference, which the connection reports to the garbage collector
+                        let callback = Arc::new(func);
+                        {
+                            let mut funcs_guard = user_functions.lock().unwrap();
+                            funcs_guard.insert(function_key.clone(), Arc::clone(&callback));
+                        }
 
-                    // Create a boxed callback pointer to pass as user data
-                    let name_cstr = std::ffi::CString::new(name.clone()).map_err(|e| {
-                        OperationalError::new_err(format!("Function name contains null byte: {e}"))
-                    })?;
-
-                    // Store the Python callback in a Box and pass it as user_data
-                    // Clone it with GIL
-                    // Note: Python::with_gil is used here for sync callback access in async context.
-                    // The deprecation warning is acceptable as this is a sync operation within async.
-                    #[allow(deprecated)]
-                    let callback =
-                        Python::with_gil(|py| func_clone.as_ref().unwrap().clone_ref(py));
-                    let callback_box: Box<Py<PyAny>> = Box::new(callback);
-                    let callback_ptr = Box::into_raw(callback_box) as *mut std::ffi::c_void;
-
-                    // Define the trampoline callback
-                    extern "C" fn udf_trampoline(
-                        ctx: *mut sqlite3_context,
-                        argc: std::ffi::c_int,
-                        argv: *mut *mut sqlite3_value,
-                    ) {
-                        // Safety: ctx is a valid sqlite3_context* pointer provided by SQLite
-                        // when calling the user-defined function. user_data was set when
-                        // registering the function and contains a Box<Py<PyAny>> pointer.
-                        // We check for null before dereferencing. The callback is called
-                        // synchronously from SQLite's execution context.
-                        unsafe {
-                            // Extract the Python callback from user_data
-                            let user_data = sqlite3_user_data(ctx);
-                            if user_data.is_null() {
-                                sqlite3_result_null(ctx);
-                                return;
-                            }
+                        // Create a boxed callback pointer to pass as user data
+                        let name_cstr = std::ffi::CString::new(name.clone()).map_err(|e| {
+                            OperationalError::new_err(format!(
+                                "Function name contains null byte: {e}"
+                            ))
+                        })?;
 
-                            // Get the callback from user_data
-                            // The callback is stored in a Box, we need to clone it to use it
-                            // We can't take ownership because the destructor will free it
-                            let callback_ptr = user_data as *mut Py<PyAny>;
-
-                            // Convert SQLite values to Python values
-                            // Note: Python::with_gil is used here for sync callback execution in async context.
-                            // The deprecation warning is acceptable as this is a sync operation within async.
-                            #[allow(deprecated)]
-                            // Note: Python::with_gil is used here for sync operation in async context.
-                            // The deprecation warning is acceptable as this is a sync operation within async.
-                            #[allow(deprecated)]
-                            Python::with_gil(|py| {
-                                // Clone the callback to use it (the original stays in the Box)
-                                let callback = (*callback_ptr).clone_ref(py);
-
-                                let mut py_args: Vec<Py<PyAny>> = Vec::new();
-                                for i in 0..argc {
-                                    let value_ptr = *argv.add(i as usize);
-                                    match sqlite_c_value_to_py(py, value_ptr) {
-                                        Ok(py_val) => {
-                                            py_args.push(py_val);
-                                        }
-                                        Err(e) => {
-                                            // On error, set SQLite error and return
-                                            let error_msg =
-                                                format!("Error converting argument {i}: {e}");
-                                            libsqlite3_sys::sqlite3_result_error(
-                                                ctx,
-                                                error_msg.as_ptr() as *const i8,
-                                                error_msg.len() as i32,
-                                            );
-                                            return;
-                                        }
-                                    }
+                        // Store the Python callback in a Box and pass it as user_data
+                        let callback_box = Box::new(
+                            CallbackContext::new(callback, &callback_dispatcher)
+                                .with_async_bridge(async_bridge)
+                                .with_udf_error_slot(&udf_error)
+                                .with_invalid_utf8_policy(&invalid_utf8)
+                                .with_large_int_policy(&large_int_policy),
+                        );
+                        let callback_ptr = Box::into_raw(callback_box) as *mut std::ffi::c_void;
+
+                        // Define the trampoline callback
+                        extern "C" fn udf_trampoline(
+                            ctx: *mut sqlite3_context,
+                            argc: std::ffi::c_int,
+                            argv: *mut *mut sqlite3_value,
+                        ) {
+                            // Safety: ctx is a valid sqlite3_context* pointer provided by SQLite
+                            // when calling the user-defined function. user_data was set when
+                            // registering the function and contains a Box<CallbackContext> pointer.
+                            // We check for null before dereferencing. The callback is called
+                            // synchronously from SQLite's execution context.
+                            unsafe {
+                                // Extract the Python callback from user_data
+                                let user_data = sqlite3_user_data(ctx);
+                                if user_data.is_null() {
+                                    sqlite3_result_null(ctx);
+                                    return;
                                 }
 
-                                // Call the Python callback with proper argument unpacking
-                                // PyO3's call1 with a tuple passes it as a single argument
-                                // We need to unpack based on argument count
-                                let result = match py_args.len() {
-                                    0 => callback.bind(py).call0(),
-                                    1 => {
-                                        // Single argument - pass directly
-                                        callback.bind(py).call1((py_args[0].clone_ref(py),))
-                                    }
-                                    2 => {
-                                        // Two arguments
-                                        callback.bind(py).call1((
-                                            py_args[0].clone_ref(py),
-                                            py_args[1].clone_ref(py),
-                                        ))
-                                    }
-                                    3 => {
-                                        // Three arguments
-                                        callback.bind(py).call1((
-                                            py_args[0].clone_ref(py),
-                                            py_args[1].clone_ref(py),
-                                            py_args[2].clone_ref(py),
-                                        ))
-                                    }
-                                    4 => {
-                                        // Four arguments
-                                        callback.bind(py).call1((
-                                            py_args[0].clone_ref(py),
-                                            py_args[1].clone_ref(py),
-                                            py_args[2].clone_ref(py),
-                                            py_args[3].clone_ref(py),
-                                        ))
-                                    }
-                                    5 => {
-                                        // Five arguments
-                                        callback.bind(py).call1((
-                                            py_args[0].clone_ref(py),
-                                            py_args[1].clone_ref(py),
-                                            py_args[2].clone_ref(py),
-                                            py_args[3].clone_ref(py),
-                                            py_args[4].clone_ref(py),
-                                        ))
-                                    }
-                                    _ => {
-                                        // For more than 5 arguments, use Python's unpacking
-                                        // Create a helper function that unpacks the tuple
-                                        let args_tuple = match PyTuple::new(
-                                            py,
-                                            py_args.iter().map(|arg: &Py<PyAny>| arg.clone_ref(py)),
+                                // Get the callback from user_data
+                                // The context is stored in a Box; we only borrow it because
+                                // the destructor will free it
+                                let context = &*(user_data as *const CallbackContext);
+
+                                // Thread mode: convert arguments without the GIL and run the
+                                // Python call on the callback thread
+                                if let Some(dispatcher) = context.dispatcher() {
+                                    let mut args = Vec::with_capacity(argc as usize);
+                                    for i in 0..argc {
+                                        match UdfArg::from_c_value(
+                                            *argv.add(i as usize),
+                                            context.invalid_utf8_policy(),
                                         ) {
-                                            Ok(t) => t,
+                                            Ok(param) => args.push(param),
                                             Err(e) => {
                                                 let error_msg =
-                                                    format!("Error creating argument tuple: {e}");
+                                                    format!("Error converting argument {i}: {e}");
                                                 libsqlite3_sys::sqlite3_result_error(
                                                     ctx,
                                                     error_msg.as_ptr() as *const i8,
@@ -2510,146 +4514,438 @@ impl Connection {
                                                 );
                                                 return;
                                             }
-                                        };
-                                        // Use Python code to unpack: lambda f, args: f(*args)
-                                        let code_str = match std::ffi::CString::new(
-                                            "lambda f, args: f(*args)",
-                                        ) {
-                                            Ok(s) => s,
-                                            Err(_) => {
-                                                libsqlite3_sys::sqlite3_result_error(
-                                                    ctx,
-                                                    c"Error creating CString".as_ptr(),
-                                                    22,
-                                                );
-                                                return;
-                                            }
-                                        };
-                                        let unpack_code =
-                                            match py.eval(code_str.as_c_str(), None, None) {
-                                                Ok(code) => code,
-                                                Err(e) => {
-                                                    let error_msg = format!(
-                                                        "Error creating unpack helper: {e}"
-                                                    );
-                                                    libsqlite3_sys::sqlite3_result_error(
-                                                        ctx,
-                                                        error_msg.as_ptr() as *const i8,
-                                                        error_msg.len() as i32,
-                                                    );
-                                                    return;
-                                                }
-                                            };
-                                        unpack_code.call1((callback.bind(py), args_tuple))
+                                        }
+                                    }
+                                    let callback = Arc::clone(&context.callback);
+                                    let async_bridge = context.async_bridge.clone();
+                                    let udf_error = context.udf_error.clone();
+                                    let large_int_policy = context.large_int_policy();
+                                    let outcome = dispatcher
+                                        .call(move || {
+                                            Python::attach(|py| {
+                                                let py_args = PyTuple::new(
+                                                    py,
+                                                    args.iter().map(|arg| arg.to_py(py)),
+                                                )
+                                                .map_err(|e| {
+                                                    format!("Error creating argument tuple: {e}")
+                                                })?;
+                                                callback
+                                                    .bind(py)
+                                                    .call1(py_args)
+                                                    .and_then(|result| match &async_bridge {
+                                                        Some(bridge) => bridge.resolve(py, result),
+                                                        None => Ok(result),
+                                                    })
+                                                    .and_then(|result| {
+                                                        py_to_udf_result(&result, large_int_policy)
+                                                    })
+                                                    .map_err(|e| {
+                                                        stash_udf_error(udf_error.as_ref(), py, &e);
+                                                        format!("{UDF_ERROR_PREFIX}: {e}")
+                                                    })
+                                            })
+                                        })
+                                        .and_then(|result| result);
+                                    match outcome {
+                                        Ok(result) => udf_c_result(ctx, result),
+                                        Err(error_msg) => libsqlite3_sys::sqlite3_result_error(
+                                            ctx,
+                                            error_msg.as_ptr() as *const i8,
+                                            error_msg.len() as i32,
+                                        ),
                                     }
-                                };
+                                    return;
+                                }
 
-                                match result {
-                                    Ok(result) => {
-                                        // Convert result back to SQLite
-                                        match py_to_sqlite_c_result(py, ctx, &result) {
-                                            Ok(_) => {}
+                                // Convert SQLite values to Python values
+                                // Note: Python::with_gil is used here for sync callback execution in async context.
+                                // The deprecation warning is acceptable as this is a sync operation within async.
+                                #[allow(deprecated)]
+                                Python::with_gil(|py| {
+                                    // Clone the callback to use it (the original stays in the Box)
+                                    let callback = context.callback.clone_ref(py);
+
+                                    let mut py_args: Vec<Py<PyAny>> =
+                                        Vec::with_capacity(argc as usize);
+                                    for i in 0..argc {
+                                        let value_ptr = *argv.add(i as usize);
+                                        match sqlite_c_value_to_py(
+                                            py,
+                                            value_ptr,
+                                            context.invalid_utf8_policy(),
+                                        ) {
+                                            Ok(py_val) => {
+                                                py_args.push(py_val);
+                                            }
                                             Err(e) => {
+                                                // On error, set SQLite error and return
                                                 let error_msg =
-                                                    format!("Error converting result: {e}");
+                                                    format!("Error converting argument {i}: {e}");
                                                 libsqlite3_sys::sqlite3_result_error(
                                                     ctx,
                                                     error_msg.as_ptr() as *const i8,
                                                     error_msg.len() as i32,
                                                 );
+                                                return;
                                             }
                                         }
                                     }
-                                    Err(e) => {
-                                        // Python exception - convert to SQLite error
-                                        let error_msg = format!("Python function error: {e}");
-                                        libsqlite3_sys::sqlite3_result_error(
-                                            ctx,
-                                            error_msg.as_ptr() as *const i8,
-                                            error_msg.len() as i32,
-                                        );
+
+                                    // Call the Python callback with the arguments unpacked from a
+                                    // tuple of any length (nargs=-1 functions receive every argument)
+                                    let args_tuple = match PyTuple::new(py, py_args) {
+                                        Ok(t) => t,
+                                        Err(e) => {
+                                            let error_msg =
+                                                format!("Error creating argument tuple: {e}");
+                                            libsqlite3_sys::sqlite3_result_error(
+                                                ctx,
+                                                error_msg.as_ptr() as *const i8,
+                                                error_msg.len() as i32,
+                                            );
+                                            return;
+                                        }
+                                    };
+                                    let result = callback.bind(py).call1(args_tuple);
+
+                                    // Wait for the coroutine of an `async def` function, then
+                                    // convert its result back to SQLite
+                                    let result = result
+                                        .and_then(|result| match &context.async_bridge {
+                                            Some(bridge) => bridge.resolve(py, result),
+                                            None => Ok(result),
+                                        })
+                                        .and_then(|result| {
+                                            py_to_udf_result(&result, context.large_int_policy())
+                                        });
+
+                                    match result {
+                                        Ok(result) => udf_c_result(ctx, result),
+                                        Err(e) => {
+                                            // Python exception (raised by the function or by
+                                            // converting its result) - stash it for the calling
+                                            // statement and convert to SQLite error
+                                            stash_udf_error(context.udf_error.as_ref(), py, &e);
+                                            let error_msg = format!("{UDF_ERROR_PREFIX}: {e}");
+                                            libsqlite3_sys::sqlite3_result_error(
+                                                ctx,
+                                                error_msg.as_ptr() as *const i8,
+                                                error_msg.len() as i32,
+                                            );
+                                        }
                                     }
-                                }
-                            });
+                                });
+                            }
                         }
-                    }
 
-                    // Destructor to clean up the callback pointer
-                    extern "C" fn udf_destructor(user_data: *mut std::ffi::c_void) {
-                        // Safety: user_data is a pointer to a Box<Py<PyAny>> that was
-                        // created with Box::into_raw when registering the function.
-                        // SQLite calls this destructor when the function is removed or
-                        // the database connection is closed. We check for null before
-                        // converting back to Box and dropping it.
-                        unsafe {
-                            if !user_data.is_null() {
-                                let _ = Box::from_raw(user_data as *mut Py<PyAny>);
+                        // Destructor to clean up the callback pointer
+                        extern "C" fn udf_destructor(user_data: *mut std::ffi::c_void) {
+                            // Safety: user_data is a pointer to a Box<CallbackContext> that was
+                            // created with Box::into_raw when registering the function.
+                            // SQLite calls this destructor when the function is removed or
+                            // the database connection is closed. We check for null before
+                            // converting back to Box and dropping it.
+                            unsafe {
+                                if !user_data.is_null() {
+                                    let _ = Box::from_raw(user_data as *mut CallbackContext);
+                                }
                             }
                         }
-                    }
 
-                    // Safety: raw_db is a valid sqlite3* pointer obtained from
-                    // lock_handle().as_raw_handle().as_ptr() and is guaranteed to be valid
-                    // for the lifetime of the handle lock. name_cstr is a valid CString.
-                    // callback_ptr is a pointer to Box<Py<PyAny>> created with Box::into_raw.
-                    // The trampoline and destructor functions handle the callback safely.
-                    let result = unsafe {
-                        sqlite3_create_function_v2(
-                            raw_db,
-                            name_cstr.as_ptr(),
-                            nargs,
-                            SQLITE_UTF8,
-                            callback_ptr, // pApp (user data - the Python callback)
-                            Some(udf_trampoline), // xFunc (scalar function callback)
-                            None,         // xStep (aggregate step callback)
-                            None,         // xFinal (aggregate final callback)
-                            Some(udf_destructor), // xDestroy (destructor)
-                        )
-                    };
+                        // Safety: raw_db is a valid sqlite3* pointer obtained from
+                        // lock_handle().as_raw_handle().as_ptr() and is guaranteed to be valid
+                        // for the lifetime of the handle lock. name_cstr is a valid CString.
+                        // callback_ptr is a pointer to Box<CallbackContext> created with Box::into_raw.
+                        // The trampoline and destructor functions handle the callback safely.
+                        let result = unsafe {
+                            sqlite3_create_function_v2(
+                                raw_db,
+                                name_cstr.as_ptr(),
+                                nargs,
+                                if deterministic {
+                                    SQLITE_UTF8 | SQLITE_DETERMINISTIC
+                                } else {
+                                    SQLITE_UTF8
+                                },
+                                callback_ptr, // pApp (user data - the Python callback)
+                                Some(udf_trampoline), // xFunc (scalar function callback)
+                                None,         // xStep (aggregate step callback)
+                                None,         // xFinal (aggregate final callback)
+                                Some(udf_destructor), // xDestroy (destructor)
+                            )
+                        };
 
-                    if result != SQLITE_OK {
-                        // Clean up the callback pointer on error
-                        // Safety: callback_ptr was created with Box::into_raw, so we can
-                        // safely convert it back to Box and drop it. This is safe because
-                        // the function registration failed, so SQLite won't call the destructor.
-                        unsafe {
-                            let _ = Box::from_raw(callback_ptr as *mut Py<PyAny>);
-                        }
-                        {
-                            let mut funcs_guard = user_functions.lock().unwrap();
-                            funcs_guard.remove(&name);
+                        if result != SQLITE_OK {
+                            // Clean up the callback pointer on error
+                            // Safety: callback_ptr was created with Box::into_raw, so we can
+                            // safely convert it back to Box and drop it. This is safe because
+                            // the function registration failed, so SQLite won't call the destructor.
+                            unsafe {
+                                let _ = Box::from_raw(callback_ptr as *mut CallbackContext);
+                            }
+                            {
+                                let mut funcs_guard = user_functions.lock().unwrap();
+                                funcs_guard.remove(&function_key);
+                            }
+                            return Err(OperationalError::new_err(format!(
+                                "Failed to create function '{name}': SQLite error code {result}"
+                            )));
                         }
-                        return Err(OperationalError::new_err(format!(
-                            "Failed to create function '{name}': SQLite error code {result}"
-                        )));
                     }
                 }
 
                 Ok(())
             };
-            future_into_py(py, future).map(|bound| bound.unbind())
+            runtime::future_into_py(py, self.runtime.as_deref(), future).map(|bound| bound.unbind())
         })
     }
 
-    /// Set or clear the trace callback.
-    /// The callback receives SQL strings as they are executed.
-    fn set_trace_callback(&self, callback: Option<Py<PyAny>>) -> PyResult<Py<PyAny>> {
-        let path = self.path.clone();
-        let pool = Arc::clone(&self.pool);
-        let callback_connection = Arc::clone(&self.callback_connection);
-        let pragmas = Arc::clone(&self.pragmas);
-        let pool_size = Arc::clone(&self.pool_size);
-        let connection_timeout_secs = Arc::clone(&self.connection_timeout_secs);
-        let trace_callback = Arc::clone(&self.trace_callback);
-        // Need all callback fields to check if all are cleared
-        let load_extension_enabled = Arc::clone(&self.load_extension_enabled);
-        let user_functions = Arc::clone(&self.user_functions);
-        let authorizer_callback = Arc::clone(&self.authorizer_callback);
-        let progress_handler = Arc::clone(&self.progress_handler);
-
-        Python::attach(|py| {
-            // Clone the callback with GIL
-            let callback_clone = callback.as_ref().map(|c| c.clone_ref(py));
+    /// Prepare statements ahead of time on every pooled connection, so the first
+    /// requests that run them don't pay the prepare cost.
+    ///
+    /// Each statement is compiled (not executed) on every connection the pool can
+    /// hand out, opening connections up to `pool_size`, and on the connection held
+    /// by an open transaction or by registered callbacks. Connections busy with
+    /// other work for longer than a moment are skipped. Prepared
+    /// statements are cached per connection under their SQL text, so pass queries
+    /// exactly as they will be run; named placeholders are rewritten the same way
+    /// `execute()` rewrites them. Each connection caches up to 100 statements.
+    ///
+    /// # Arguments
+    ///
+    /// * `queries` - SQL statements to prepare, e.g. read from a workload file.
+    ///
+    /// # Returns
+    ///
+    /// Returns an awaitable resolving to the number of connections prepared.
+    ///
+    /// # Errors
+    ///
+    /// Raises the usual DatabaseError subclass for the first statement that fails
+    /// to compile (for example one referring to a table that doesn't exist yet).
+    ///
+    /// # Example
+    ///
+    /// .. code-block:: python
+    ///
+    ///     with open("workload.sql") as f:
+    ///         await conn.preheat([line for line in f.read().splitlines() if line])
+    fn preheat(self_: PyRef<Self>, queries: Vec<String>) -> PyResult<Py<PyAny>> {
+        let queries: Vec<String> = queries
+            .iter()
+            .map(|query| rewrite_named_placeholders(query).0)
+            .collect();
+        let path = self_.path.clone();
+        let pool = Arc::clone(&self_.pool);
+        let pragmas = Arc::clone(&self_.pragmas);
+        let pool_size = Arc::clone(&self_.pool_size);
+        let connection_timeout_secs = Arc::clone(&self_.connection_timeout_secs);
+        let statement_policy = self_.statement_policy.clone();
+        let native_functions = self_.native_functions;
+        let wal_hook = self_.wal_hook.clone();
+        let vfs = self_.vfs.clone();
+        let event_hooks = Arc::clone(&self_.event_hooks);
+        let transaction_connection = Arc::clone(&self_.transaction_connection);
+        let callback_connection = Arc::clone(&self_.callback_connection);
+        let init_hook = Arc::clone(&self_.init_hook);
+        let init_hook_called = Arc::clone(&self_.init_hook_called);
+        let dedicated_runtime = self_.runtime.clone();
+        let connection_self: Py<Connection> = self_.into();
+
+        Python::attach(|py| {
+            let future = async move {
+                let pool_clone = get_or_create_pool(
+                    &path,
+                    &pool,
+                    &pragmas,
+                    &pool_size,
+                    &connection_timeout_secs,
+                    &statement_policy,
+                    native_functions,
+                    &wal_hook,
+                    &vfs,
+                    &event_hooks,
+                )
+                .await?;
+                execute_init_hook_if_needed(&init_hook, &init_hook_called, connection_self).await?;
+
+                let mut prepared = 0;
+                for held in [&transaction_connection, &callback_connection] {
+                    if let Some(conn) = held.lock().await.as_mut() {
+                        for query in &queries {
+                            prepare_on_connection(query, conn, &path).await?;
+                        }
+                        prepared += 1;
+                    }
+                }
+
+                // Hold every connection at once so each one is prepared. After the
+                // first, stop at the first one that doesn't free up shortly: it is
+                // held elsewhere (e.g. by an open transaction). The short wait
+                // covers connections sqlx is still returning to the pool.
+                let max = pool_clone.options().get_max_connections() as usize;
+                let mut conns = Vec::with_capacity(max);
+                let first = pool_clone.acquire().await.map_err(|e| {
+                    pool_acquisition_error(
+                        &path,
+                        &e,
+                        *pool_size.lock().unwrap(),
+                        *connection_timeout_secs.lock().unwrap(),
+                    )
+                })?;
+                conns.push(first);
+                while conns.len() < max {
+                    match tokio::time::timeout(PREHEAT_ACQUIRE_WAIT, pool_clone.acquire()).await {
+                        Ok(Ok(conn)) => conns.push(conn),
+                        _ => break,
+                    }
+                }
+                for conn in &mut conns {
+                    for query in &queries {
+                        prepare_on_connection(query, conn, &path).await?;
+                    }
+                }
+                Ok(prepared + conns.len())
+            };
+            runtime::future_into_py(py, dedicated_runtime.as_deref(), future)
+                .map(|bound| bound.unbind())
+        })
+    }
+
+    /// Only permit statements that are already known, for locked-down
+    /// (appliance-style) deployments.
+    ///
+    /// Without arguments the allowlist is every statement this connection has run
+    /// so far, so call it after a warm-up that exercises the application's queries.
+    /// Pass `statements` to allow exactly those instead. Afterwards any other SQL
+    /// given to `execute`, `execute_many`, `fetch_*`, `cached_fetch_all`,
+    /// `execute_raw`, `apply_ddl` or a cursor raises ProgrammingError before it
+    /// reaches SQLite. Statements are compared as written (placeholders included)
+    /// after collapsing whitespace. SQL that rapsqlite issues itself, such as
+    /// `begin()` or schema introspection, is not affected. Calling it again
+    /// replaces the allowlist.
+    ///
+    /// # Example
+    ///
+    /// .. code-block:: python
+    ///
+    ///     await warm_up(conn)
+    ///     conn.freeze_statements()
+    ///     await conn.execute("DROP TABLE users")  # ProgrammingError
+    #[pyo3(signature = (statements = None))]
+    fn freeze_statements(&self, statements: Option<Vec<String>>) {
+        let allowed = match statements {
+            Some(statements) => statements.iter().map(|s| normalize_query(s)).collect(),
+            None => self.query_cache.lock().unwrap().keys().cloned().collect(),
+        };
+        *self.frozen_statements.lock().unwrap() = Some(allowed);
+    }
+
+    /// The statements running now on the pool's connections, longest-running
+    /// first, to see what holds the connections when the pool stalls.
+    ///
+    /// Each is a dict with `connection` (the id `set_event_hook()` hooks see),
+    /// `sql` (the text as prepared, without parameter values), `started` (a
+    /// Unix timestamp) and `elapsed` (seconds). A query read with `stream()`
+    /// counts as running until the stream is exhausted or closed.
+    fn active_statements<'py>(&self, py: Python<'py>) -> PyResult<Vec<Bound<'py, PyDict>>> {
+        self.event_hooks
+            .activity()
+            .snapshot()
+            .into_iter()
+            .map(|statement| {
+                let started = statement
+                    .started
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap_or_default();
+                let dict = PyDict::new(py);
+                dict.set_item("connection", statement.connection)?;
+                dict.set_item("sql", &statement.sql)?;
+                dict.set_item("started", started.as_secs_f64())?;
+                dict.set_item("elapsed", statement.elapsed().as_secs_f64())?;
+                Ok(dict)
+            })
+            .collect()
+    }
+
+    /// Counters of the queue tasks wait in for the pool's connections, to see
+    /// how saturated the pool is.
+    ///
+    /// A dict with `waiting` (tasks waiting now), `acquired` (connections
+    /// handed out), `timed_out` and `rejected` (`PoolTimeout` raised after
+    /// `connection_timeout` or at once because of `max_queue`), and
+    /// `total_wait` and `max_wait` (seconds waited for the connections handed
+    /// out). Shared pools and the readers of `read_pool_size` count into the
+    /// same queue. Transactions and callbacks keep their connection, so their
+    /// statements don't wait in it.
+    fn queue_stats<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let stats = self.event_hooks.queue().stats();
+        let dict = PyDict::new(py);
+        dict.set_item("waiting", stats.waiting)?;
+        dict.set_item("acquired", stats.acquired)?;
+        dict.set_item("timed_out", stats.timed_out)?;
+        dict.set_item("rejected", stats.rejected)?;
+        dict.set_item("total_wait", stats.total_wait.as_secs_f64())?;
+        dict.set_item("max_wait", stats.max_wait.as_secs_f64())?;
+        Ok(dict)
+    }
+
+    /// Set or clear (`None`) the statement logger.
+    ///
+    /// After each statement run by `execute`, `execute_many`, `fetch_all`,
+    /// `fetch_one`, `fetch_optional` or `cached_fetch_all` succeeds, the logger is
+    /// called with `(sql, params, duration, rows)`: the parameters as passed (a list
+    /// of them for `execute_many`), the elapsed seconds, and the rows returned or,
+    /// for other statements, changed. A SELECT passed to `execute` runs when its
+    /// cursor is fetched and is not logged. Unlike `set_trace_callback` this needs
+    /// no dedicated connection.
+    ///
+    /// `redact` lists parameter names (for mapping parameters; a leading `:`, `@`
+    /// or `$` is ignored) and 0-based positions (for sequence parameters) whose
+    /// values are replaced by `"<redacted>"`. Exceptions raised by the logger go to
+    /// `sys.unraisablehook` and do not fail the statement.
+    #[pyo3(signature = (callback, redact = None))]
+    fn set_statement_logger(
+        &self,
+        callback: Option<Py<PyAny>>,
+        redact: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<()> {
+        let logger = callback
+            .map(|callback| StatementLogger::new(callback, redact))
+            .transpose()?;
+        *self.statement_logger.lock().unwrap() = logger.map(Arc::new);
+        Ok(())
+    }
+
+    /// Set or clear the trace callback.
+    /// The callback receives SQL strings as they are executed.
+    fn set_trace_callback(&self, callback: Option<Py<PyAny>>) -> PyResult<Py<PyAny>> {
+        audit_callback(&self.task_audit, "trace_callback", callback.is_some())?;
+        let path = self.path.clone();
+        let pool = Arc::clone(&self.pool);
+        let callback_connection = Arc::clone(&self.callback_connection);
+        let pragmas = Arc::clone(&self.pragmas);
+        let pool_size = Arc::clone(&self.pool_size);
+        let connection_timeout_secs = Arc::clone(&self.connection_timeout_secs);
+        let statement_policy = self.statement_policy.clone();
+        let native_functions = self.native_functions;
+        let wal_hook = self.wal_hook.clone();
+        let vfs = self.vfs.clone();
+        let event_hooks = Arc::clone(&self.event_hooks);
+        let trace_callback = Arc::clone(&self.trace_callback);
+        let callback_dispatcher = Arc::clone(&self.callback_dispatcher);
+        let hook_contexts = Arc::clone(&self.hook_contexts);
+        // Need all callback fields to check if all are cleared
+        let load_extension_enabled = Arc::clone(&self.load_extension_enabled);
+        let user_functions = Arc::clone(&self.user_functions);
+        let authorizer_callback = Arc::clone(&self.authorizer_callback);
+        let progress_handler = Arc::clone(&self.progress_handler);
+        let update_hook = Arc::clone(&self.update_hook);
+
+        Python::attach(|py| {
+            // Clone the callback with GIL
+            let callback_clone = callback.as_ref().map(|c| c.clone_ref(py));
 
             // Store the callback state
             {
@@ -2666,6 +4962,11 @@ impl Connection {
                     &pragmas,
                     &pool_size,
                     &connection_timeout_secs,
+                    &statement_policy,
+                    native_functions,
+                    &wal_hook,
+                    &vfs,
+                    &event_hooks,
                 )
                 .await?;
 
@@ -2683,16 +4984,22 @@ impl Connection {
 
                 // Define the trace callback trampoline
                 extern "C" fn trace_trampoline(
-                    _trace_type: std::ffi::c_uint,
+                    trace_type: std::ffi::c_uint,
                     ctx: *mut std::ffi::c_void,
-                    _p: *mut std::ffi::c_void,
+                    p: *mut std::ffi::c_void,
                     x: *mut std::ffi::c_void,
                 ) -> std::ffi::c_int {
-                    // Safety: ctx is a pointer to the Python callback (Box<Py<PyAny>>)
+                    // Safety: ctx is a pointer to the callback context (Box<CallbackContext>)
                     // that was set when registering the trace callback. x is a pointer to
                     // the SQL string provided by SQLite. We check for null before dereferencing.
                     // The callback is called synchronously from SQLite's execution context.
                     unsafe {
+                        // This hook replaces the one recording active_statements()
+                        activity::trace(trace_type, p, x);
+                        if trace_type != SQLITE_TRACE_STMT as std::ffi::c_uint {
+                            return 0;
+                        }
+
                         // x is a pointer to the SQL string (for SQLITE_TRACE_STMT)
                         if x.is_null() || ctx.is_null() {
                             return 0;
@@ -2705,13 +5012,25 @@ impl Connection {
                             cstr_from_i8_ptr(sql_cstr).to_string_lossy().into_owned();
 
                         // Get the Python callback from the context (pCtx)
-                        let callback_ptr = ctx as *mut Py<PyAny>;
+                        let context = &*(ctx as *const CallbackContext);
+
+                        // Thread mode: queue the call without waiting. Trace callbacks are
+                        // informational, so a full queue simply drops the event.
+                        if let Some(dispatcher) = context.dispatcher() {
+                            let callback = Arc::clone(&context.callback);
+                            let _ = dispatcher.post(move || {
+                                Python::attach(|py| {
+                                    let _ = callback.bind(py).call1((sql_str,));
+                                })
+                            });
+                            return 0;
+                        }
 
                         // Note: Python::with_gil is used here for sync operation in async context.
                         // The deprecation warning is acceptable as this is a sync operation within async.
                         #[allow(deprecated)]
                         Python::with_gil(|py| {
-                            let callback = (*callback_ptr).clone_ref(py);
+                            let callback = context.callback.clone_ref(py);
                             if let Err(e) = callback.bind(py).call1((sql_str,)) {
                                 // Trace callbacks are informational - log errors but continue
                                 // The error is silently ignored to prevent trace callback failures
@@ -2736,46 +5055,41 @@ impl Connection {
                     })
                 };
 
-                let callback_ptr = if let Some(cb) = callback_for_trace {
-                    let callback_box: Box<Py<PyAny>> = Box::new(cb);
-                    Box::into_raw(callback_box) as *mut std::ffi::c_void
-                } else {
-                    std::ptr::null_mut()
-                };
+                let context = callback_for_trace.map(|cb| {
+                    let callback = Arc::new(cb);
+                    HookContext::new(CallbackContext::new(
+                        Arc::clone(&callback),
+                        &callback_dispatcher,
+                    ))
+                    .holding(&callback)
+                });
+                let callback_ptr = context
+                    .as_ref()
+                    .map_or(std::ptr::null_mut(), HookContext::as_ptr);
 
                 // Set or clear the trace callback
                 // Safety: raw_db is a valid sqlite3* pointer obtained from
                 // lock_handle().as_raw_handle().as_ptr() and is guaranteed to be valid
                 // for the lifetime of the handle lock. callback_ptr is either null or
-                // a pointer to Box<Py<PyAny>> created with Box::into_raw. The trampoline
+                // a pointer to the CallbackContext owned by `context`. The trampoline
                 // function handles the callback safely.
+                // Clearing puts back the hook recording active_statements().
                 let result = unsafe {
-                    sqlite3_trace_v2(
-                        raw_db,
-                        if callback_ptr.is_null() {
-                            0
-                        } else {
-                            SQLITE_TRACE_STMT as u32
-                        }, // Trace mask
-                        if callback_ptr.is_null() {
-                            None
-                        } else {
-                            Some(trace_trampoline)
-                        },
-                        callback_ptr, // pCtx - the Python callback
-                    )
+                    if callback_ptr.is_null() {
+                        activity::restore(raw_db)
+                    } else {
+                        sqlite3_trace_v2(
+                            raw_db,
+                            activity::TRACE_MASK,
+                            Some(trace_trampoline),
+                            callback_ptr, // pCtx - the Python callback
+                        )
+                    }
                 };
 
                 if result != SQLITE_OK {
-                    // Clean up callback pointer on error
-                    // Safety: callback_ptr was created with Box::into_raw, so we can
-                    // safely convert it back to Box and drop it. This is safe because
-                    // the trace callback registration failed, so SQLite won't call the destructor.
-                    if !callback_ptr.is_null() {
-                        unsafe {
-                            let _ = Box::from_raw(callback_ptr as *mut Py<PyAny>);
-                        }
-                    }
+                    // Registration failed, so SQLite never saw the new context; dropping
+                    // `context` frees it
                     {
                         let mut trace_guard = trace_callback.lock().unwrap();
                         *trace_guard = None;
@@ -2785,6 +5099,12 @@ impl Connection {
                     )));
                 }
 
+                // SQLite now references the new context (if any); free the one it replaced
+                drop(std::mem::replace(
+                    &mut hook_contexts.lock().unwrap().trace,
+                    context,
+                ));
+
                 // After clearing, check if all callbacks are now cleared
                 if callback.is_none() {
                     let all_cleared = !has_callbacks(
@@ -2793,6 +5113,7 @@ impl Connection {
                         &trace_callback,
                         &authorizer_callback,
                         &progress_handler,
+                        &update_hook,
                     );
                     if all_cleared {
                         // Release the callback connection
@@ -2806,25 +5127,35 @@ impl Connection {
 
                 Ok(())
             };
-            future_into_py(py, future).map(|bound| bound.unbind())
+            runtime::future_into_py(py, self.runtime.as_deref(), future).map(|bound| bound.unbind())
         })
     }
 
     /// Set or clear the authorizer callback.
     /// The callback receives (action, arg1, arg2, arg3, arg4) and returns an int (SQLITE_OK, SQLITE_DENY, etc.).
+    /// The action codes and results are exported as `rapsqlite.SQLITE_*` constants
+    /// and the `AuthorizerAction`/`AuthorizerResult` enums.
     fn set_authorizer(&self, callback: Option<Py<PyAny>>) -> PyResult<Py<PyAny>> {
+        audit_callback(&self.task_audit, "authorizer", callback.is_some())?;
         let path = self.path.clone();
         let pool = Arc::clone(&self.pool);
         let callback_connection = Arc::clone(&self.callback_connection);
         let pragmas = Arc::clone(&self.pragmas);
         let pool_size = Arc::clone(&self.pool_size);
         let connection_timeout_secs = Arc::clone(&self.connection_timeout_secs);
+        let statement_policy = self.statement_policy.clone();
+        let native_functions = self.native_functions;
+        let wal_hook = self.wal_hook.clone();
+        let vfs = self.vfs.clone();
+        let event_hooks = Arc::clone(&self.event_hooks);
         let authorizer_callback = Arc::clone(&self.authorizer_callback);
+        let hook_contexts = Arc::clone(&self.hook_contexts);
         // Need all callback fields to check if all are cleared
         let load_extension_enabled = Arc::clone(&self.load_extension_enabled);
         let user_functions = Arc::clone(&self.user_functions);
         let trace_callback = Arc::clone(&self.trace_callback);
         let progress_handler = Arc::clone(&self.progress_handler);
+        let update_hook = Arc::clone(&self.update_hook);
 
         Python::attach(|py| {
             // Clone the callback with GIL
@@ -2845,12 +5176,12 @@ impl Connection {
                         &trace_callback,
                         &authorizer_callback,
                         &progress_handler,
+                        &update_hook,
                     );
-                    if all_cleared {
-                        // Release the callback connection
-                        let mut callback_guard = callback_connection.lock().await;
-                        callback_guard.take();
-                        // Clear the authorizer on SQLite side (already cleared in state)
+                    // Nothing to clear on the SQLite side if no callback connection exists;
+                    // otherwise fall through so the authorizer is removed before the
+                    // connection is returned to the pool
+                    if all_cleared && callback_connection.lock().await.is_none() {
                         return Ok(());
                     }
                 }
@@ -2863,6 +5194,11 @@ impl Connection {
                     &pragmas,
                     &pool_size,
                     &connection_timeout_secs,
+                    &statement_policy,
+                    native_functions,
+                    &wal_hook,
+                    &vfs,
+                    &event_hooks,
                 )
                 .await?;
 
@@ -2877,6 +5213,15 @@ impl Connection {
                     OperationalError::new_err(format!("Failed to lock handle: {e}"))
                 })?;
                 let raw_db = handle.as_raw_handle().as_ptr();
+                // Hardened mode's policy stays in force underneath the Python callback
+                // Safety: the handle lock gives exclusive use of raw_db
+                let policy = unsafe { authorizer::installed(raw_db) };
+
+                /// User data for `authorizer_trampoline`.
+                struct AuthorizerContext {
+                    callback: Arc<Py<PyAny>>,
+                    policy: Option<Arc<StatementPolicy>>,
+                }
 
                 // Define the authorizer callback trampoline
                 extern "C" fn authorizer_trampoline(
@@ -2887,7 +5232,7 @@ impl Connection {
                     arg3: *const i8,
                     arg4: *const i8,
                 ) -> std::ffi::c_int {
-                    // Safety: ctx is a pointer to the Python callback (Box<Py<PyAny>>)
+                    // Safety: ctx is a pointer to the AuthorizerContext owned by a HookContext
                     // that was set when registering the authorizer callback. The arg1-arg4
                     // pointers are C strings provided by SQLite; we check for null and
                     // safely convert them using cstr_from_i8_ptr. The callback is called
@@ -2919,14 +5264,18 @@ impl Connection {
                             Some(cstr_from_i8_ptr(arg4).to_string_lossy().into_owned())
                         };
 
-                        // Get the Python callback from the context
-                        let callback_ptr = ctx as *mut Py<PyAny>;
+                        let context = &*(ctx as *const AuthorizerContext);
+                        if let Some(policy) = &context.policy {
+                            if !policy.permits(action, arg1_str.as_deref(), arg2_str.as_deref()) {
+                                return SQLITE_DENY;
+                            }
+                        }
 
                         // Note: Python::with_gil is used here for sync operation in async context.
                         // The deprecation warning is acceptable as this is a sync operation within async.
                         #[allow(deprecated)]
                         Python::with_gil(|py| {
-                            let callback = (*callback_ptr).clone_ref(py);
+                            let callback = context.callback.clone_ref(py);
 
                             // Convert None strings to None in Python, otherwise pass the string
                             let py_arg1: Py<PyAny> = match arg1_str {
@@ -2978,31 +5327,47 @@ impl Connection {
                     })
                 };
 
-                let callback_ptr = if let Some(cb) = callback_for_auth {
-                    let callback_box: Box<Py<PyAny>> = Box::new(cb);
-                    Box::into_raw(callback_box) as *mut std::ffi::c_void
-                } else {
-                    std::ptr::null_mut()
-                };
+                let context = callback_for_auth.map(|callback| {
+                    let callback = Arc::new(callback);
+                    HookContext::new(AuthorizerContext {
+                        callback: Arc::clone(&callback),
+                        policy: policy.clone(),
+                    })
+                    .holding(&callback)
+                });
+                let callback_ptr = context
+                    .as_ref()
+                    .map_or(std::ptr::null_mut(), HookContext::as_ptr);
 
                 // Set or clear the authorizer callback
                 // Safety: raw_db is a valid sqlite3* pointer obtained from
                 // lock_handle().as_raw_handle().as_ptr() and is guaranteed to be valid
                 // for the lifetime of the handle lock. callback_ptr is either null or
-                // a pointer to Box<Py<PyAny>> created with Box::into_raw. The trampoline
+                // a pointer to the AuthorizerContext owned by `context`. The trampoline
                 // function handles the callback safely.
                 unsafe {
-                    sqlite3_set_authorizer(
-                        raw_db,
-                        if callback_ptr.is_null() {
-                            None
-                        } else {
-                            Some(authorizer_trampoline)
-                        },
-                        callback_ptr, // pUserData - the Python callback
-                    );
+                    match (callback_ptr.is_null(), policy) {
+                        // Clearing the callback restores the plain hardened-mode authorizer
+                        (true, Some(policy)) => authorizer::install(raw_db, policy),
+                        (true, None) => {
+                            sqlite3_set_authorizer(raw_db, None, std::ptr::null_mut());
+                        }
+                        (false, _) => {
+                            sqlite3_set_authorizer(
+                                raw_db,
+                                Some(authorizer_trampoline),
+                                callback_ptr, // pUserData - the Python callback
+                            );
+                        }
+                    }
                 }
 
+                // SQLite now references the new context (if any); free the one it replaced
+                drop(std::mem::replace(
+                    &mut hook_contexts.lock().unwrap().authorizer,
+                    context,
+                ));
+
                 // After clearing, check if all callbacks are now cleared
                 if callback.is_none() {
                     let all_cleared = !has_callbacks(
@@ -3011,6 +5376,7 @@ impl Connection {
                         &trace_callback,
                         &authorizer_callback,
                         &progress_handler,
+                        &update_hook,
                     );
                     if all_cleared {
                         // Release the callback connection
@@ -3024,20 +5390,29 @@ impl Connection {
 
                 Ok(())
             };
-            future_into_py(py, future).map(|bound| bound.unbind())
+            runtime::future_into_py(py, self.runtime.as_deref(), future).map(|bound| bound.unbind())
         })
     }
 
     /// Set or clear the progress handler callback.
     /// The callback is called every N VDBE operations and returns True to continue, False to abort.
     fn set_progress_handler(&self, n: i32, callback: Option<Py<PyAny>>) -> PyResult<Py<PyAny>> {
+        audit_callback(&self.task_audit, "progress_handler", callback.is_some())?;
         let path = self.path.clone();
         let pool = Arc::clone(&self.pool);
         let callback_connection = Arc::clone(&self.callback_connection);
         let pragmas = Arc::clone(&self.pragmas);
         let pool_size = Arc::clone(&self.pool_size);
         let connection_timeout_secs = Arc::clone(&self.connection_timeout_secs);
+        let statement_policy = self.statement_policy.clone();
+        let native_functions = self.native_functions;
+        let wal_hook = self.wal_hook.clone();
+        let vfs = self.vfs.clone();
+        let event_hooks = Arc::clone(&self.event_hooks);
         let progress_handler = Arc::clone(&self.progress_handler);
+        let update_hook = Arc::clone(&self.update_hook);
+        let callback_dispatcher = Arc::clone(&self.callback_dispatcher);
+        let hook_contexts = Arc::clone(&self.hook_contexts);
         // Need all callback fields to check if all are cleared
         let load_extension_enabled = Arc::clone(&self.load_extension_enabled);
         let user_functions = Arc::clone(&self.user_functions);
@@ -3063,12 +5438,12 @@ impl Connection {
                         &trace_callback,
                         &authorizer_callback,
                         &progress_handler,
+                        &update_hook,
                     );
-                    if all_cleared {
-                        // Release the callback connection
-                        let mut callback_guard = callback_connection.lock().await;
-                        callback_guard.take();
-                        // Clear the progress handler on SQLite side (already cleared in state)
+                    // Nothing to clear on the SQLite side if no callback connection exists;
+                    // otherwise fall through so the handler is removed before the
+                    // connection is returned to the pool
+                    if all_cleared && callback_connection.lock().await.is_none() {
                         return Ok(());
                     }
                 }
@@ -3081,6 +5456,11 @@ impl Connection {
                     &pragmas,
                     &pool_size,
                     &connection_timeout_secs,
+                    &statement_policy,
+                    native_functions,
+                    &wal_hook,
+                    &vfs,
+                    &event_hooks,
                 )
                 .await?;
 
@@ -3098,7 +5478,7 @@ impl Connection {
 
                 // Define the progress handler callback trampoline
                 extern "C" fn progress_trampoline(ctx: *mut std::ffi::c_void) -> std::ffi::c_int {
-                    // Safety: ctx is a pointer to the Python callback (Box<Py<PyAny>>)
+                    // Safety: ctx is a pointer to the callback context (Box<CallbackContext>)
                     // that was set when registering the progress handler. We check for
                     // null before dereferencing. The callback is called synchronously
                     // from SQLite's execution context during long-running operations.
@@ -3108,14 +5488,9 @@ impl Connection {
                         }
 
                         // Get the Python callback from the context
-                        let callback_ptr = ctx as *mut Py<PyAny>;
-
-                        // Note: Python::with_gil is used here for sync operation in async context.
-                        // The deprecation warning is acceptable as this is a sync operation within async.
-                        #[allow(deprecated)]
-                        Python::with_gil(|py| {
-                            let callback = (*callback_ptr).clone_ref(py);
+                        let context = &*(ctx as *const CallbackContext);
 
+                        fn call_progress(py: Python<'_>, callback: &Py<PyAny>) -> std::ffi::c_int {
                             match callback.bind(py).call0() {
                                 Ok(result) => {
                                     // Convert Python result to int (0 = continue, non-zero = abort)
@@ -3136,7 +5511,21 @@ impl Connection {
                                     0 // Continue on error
                                 }
                             }
-                        })
+                        }
+
+                        // Thread mode: a timed out or rejected call counts as "continue",
+                        // like a progress handler that raised
+                        if let Some(dispatcher) = context.dispatcher() {
+                            let callback = Arc::clone(&context.callback);
+                            return dispatcher
+                                .call(move || Python::attach(|py| call_progress(py, &callback)))
+                                .unwrap_or(0);
+                        }
+
+                        // Note: Python::with_gil is used here for sync operation in async context.
+                        // The deprecation warning is acceptable as this is a sync operation within async.
+                        #[allow(deprecated)]
+                        Python::with_gil(|py| call_progress(py, &context.callback))
                     }
                 }
 
@@ -3151,32 +5540,45 @@ impl Connection {
                     })
                 };
 
-                let callback_ptr = if let Some(cb) = callback_for_progress {
-                    let callback_box: Box<Py<PyAny>> = Box::new(cb);
-                    Box::into_raw(callback_box) as *mut std::ffi::c_void
-                } else {
-                    std::ptr::null_mut()
-                };
+                let context = callback_for_progress.map(|cb| {
+                    let callback = Arc::new(cb);
+                    HookContext::new(CallbackContext::new(
+                        Arc::clone(&callback),
+                        &callback_dispatcher,
+                    ))
+                    .holding(&callback)
+                });
+                let callback_ptr = context
+                    .as_ref()
+                    .map_or(std::ptr::null_mut(), HookContext::as_ptr);
 
                 // Set or clear the progress handler
                 // Safety: raw_db is a valid sqlite3* pointer obtained from
                 // lock_handle().as_raw_handle().as_ptr() and is guaranteed to be valid
                 // for the lifetime of the handle lock. callback_ptr is either null or
-                // a pointer to Box<Py<PyAny>> created with Box::into_raw. The trampoline
+                // a pointer to the CallbackContext owned by `context`. The trampoline
                 // function handles the callback safely.
+                // Clearing puts back the handler enforcing rapsqlite.deadline(),
+                // which a Python progress handler replaces on this connection.
                 unsafe {
-                    sqlite3_progress_handler(
-                        raw_db,
-                        if callback_ptr.is_null() { 0 } else { n },
-                        if callback_ptr.is_null() {
-                            None
-                        } else {
-                            Some(progress_trampoline)
-                        },
-                        callback_ptr, // pArg - the Python callback
-                    );
+                    if callback_ptr.is_null() {
+                        rapsqlite_core::deadline::restore(raw_db);
+                    } else {
+                        sqlite3_progress_handler(
+                            raw_db,
+                            n,
+                            Some(progress_trampoline),
+                            callback_ptr, // pArg - the Python callback
+                        );
+                    }
                 }
 
+                // SQLite now references the new context (if any); free the one it replaced
+                drop(std::mem::replace(
+                    &mut hook_contexts.lock().unwrap().progress,
+                    context,
+                ));
+
                 // After clearing, check if all callbacks are now cleared
                 if callback.is_none() {
                     let all_cleared = !has_callbacks(
@@ -3185,6 +5587,7 @@ impl Connection {
                         &trace_callback,
                         &authorizer_callback,
                         &progress_handler,
+                        &update_hook,
                     );
                     if all_cleared {
                         // Release the callback connection
@@ -3198,60 +5601,253 @@ impl Connection {
 
                 Ok(())
             };
-            future_into_py(py, future).map(|bound| bound.unbind())
+            runtime::future_into_py(py, self.runtime.as_deref(), future).map(|bound| bound.unbind())
         })
     }
 
-    /// Dump the database as a list of SQL statements.
-    /// Returns a list of SQL strings that can recreate the database.
-    fn iterdump(self_: PyRef<Self>) -> PyResult<Py<PyAny>> {
-        let path = self_.path.clone();
-        let pool = Arc::clone(&self_.pool);
-        let pragmas = Arc::clone(&self_.pragmas);
-        let pool_size = Arc::clone(&self_.pool_size);
-        let connection_timeout_secs = Arc::clone(&self_.connection_timeout_secs);
-        let transaction_state = Arc::clone(&self_.transaction_state);
-        let transaction_connection = Arc::clone(&self_.transaction_connection);
-        // Callback infrastructure (Phase 2.7)
-        let callback_connection = Arc::clone(&self_.callback_connection);
-        let load_extension_enabled = Arc::clone(&self_.load_extension_enabled);
-        let user_functions = Arc::clone(&self_.user_functions);
-        let trace_callback = Arc::clone(&self_.trace_callback);
-        let authorizer_callback = Arc::clone(&self_.authorizer_callback);
-        let progress_handler = Arc::clone(&self_.progress_handler);
+    /// Set or clear the update hook.
+    /// The callback receives `(operation, database, table, rowid)` for each row
+    /// inserted, updated or deleted in a rowid table, with `operation` one of
+    /// `rapsqlite.SQLITE_INSERT`, `SQLITE_UPDATE` or `SQLITE_DELETE`. It runs while
+    /// the statement is executing, so it must not use the connection; exceptions
+    /// it raises are ignored. Changes made by other connections, to WITHOUT ROWID
+    /// tables, and by truncating DELETEs without a WHERE clause are not reported.
+    fn set_update_hook(&self, callback: Option<Py<PyAny>>) -> PyResult<Py<PyAny>> {
+        audit_callback(&self.task_audit, "update_hook", callback.is_some())?;
+        let path = self.path.clone();
+        let pool = Arc::clone(&self.pool);
+        let callback_connection = Arc::clone(&self.callback_connection);
+        let pragmas = Arc::clone(&self.pragmas);
+        let pool_size = Arc::clone(&self.pool_size);
+        let connection_timeout_secs = Arc::clone(&self.connection_timeout_secs);
+        let statement_policy = self.statement_policy.clone();
+        let native_functions = self.native_functions;
+        let wal_hook = self.wal_hook.clone();
+        let vfs = self.vfs.clone();
+        let event_hooks = Arc::clone(&self.event_hooks);
+        let update_hook = Arc::clone(&self.update_hook);
+        let callback_dispatcher = Arc::clone(&self.callback_dispatcher);
+        let hook_contexts = Arc::clone(&self.hook_contexts);
+        // Need all callback fields to check if all are cleared
+        let load_extension_enabled = Arc::clone(&self.load_extension_enabled);
+        let user_functions = Arc::clone(&self.user_functions);
+        let trace_callback = Arc::clone(&self.trace_callback);
+        let authorizer_callback = Arc::clone(&self.authorizer_callback);
+        let progress_handler = Arc::clone(&self.progress_handler);
 
         Python::attach(|py| {
+            // Store the update hook state
+            {
+                let mut update_guard = update_hook.lock().unwrap();
+                *update_guard = callback.as_ref().map(|c| c.clone_ref(py));
+            }
+            let callback_for_update = callback.as_ref().map(|c| Arc::new(c.clone_ref(py)));
+
             let future = async move {
-                // Priority: transaction > callbacks > pool
-                let in_transaction = {
-                    let g = transaction_state.lock().await;
-                    g.is_active()
-                };
+                // If clearing the callback, check if all callbacks are now cleared
+                if callback.is_none() {
+                    let all_cleared = !has_callbacks(
+                        &load_extension_enabled,
+                        &user_functions,
+                        &trace_callback,
+                        &authorizer_callback,
+                        &progress_handler,
+                        &update_hook,
+                    );
+                    // Nothing to clear on the SQLite side if no callback connection exists
+                    if all_cleared && callback_connection.lock().await.is_none() {
+                        return Ok(());
+                    }
+                }
 
-                let has_callbacks_flag = has_callbacks(
-                    &load_extension_enabled,
-                    &user_functions,
-                    &trace_callback,
-                    &authorizer_callback,
-                    &progress_handler,
-                );
+                // Ensure callback connection exists
+                ensure_callback_connection(
+                    &path,
+                    &pool,
+                    &callback_connection,
+                    &pragmas,
+                    &pool_size,
+                    &connection_timeout_secs,
+                    &statement_policy,
+                    native_functions,
+                    &wal_hook,
+                    &vfs,
+                    &event_hooks,
+                )
+                .await?;
 
-                // Helper function to encode bytes as hex
-                fn bytes_to_hex(bytes: &[u8]) -> String {
-                    bytes.iter().map(|b| format!("{b:02x}")).collect()
-                }
+                // Get the callback connection and access raw handle
+                let mut conn_guard = callback_connection.lock().await;
+                let conn = conn_guard.as_mut().ok_or_else(|| {
+                    OperationalError::new_err("Callback connection not available")
+                })?;
 
-                // Get connection for queries
-                // We need to handle different connection types
-                let mut statements = Vec::new();
-                statements.push("BEGIN TRANSACTION;".to_string());
+                let sqlite_conn: &mut SqliteConnection = conn;
+                let mut handle = sqlite_conn.lock_handle().await.map_err(|e| {
+                    OperationalError::new_err(format!("Failed to lock handle: {e}"))
+                })?;
+                let raw_db = handle.as_raw_handle().as_ptr();
 
-                // Query sqlite_master - use appropriate connection
-                let schema_rows = if in_transaction {
-                    let mut conn_guard = transaction_connection.lock().await;
-                    let conn = conn_guard.as_mut().ok_or_else(|| {
-                        OperationalError::new_err("Transaction connection not available")
-                    })?;
+                // Define the update hook trampoline
+                extern "C" fn update_trampoline(
+                    ctx: *mut std::ffi::c_void,
+                    operation: std::ffi::c_int,
+                    database: *const std::ffi::c_char,
+                    table: *const std::ffi::c_char,
+                    rowid: sqlite3_int64,
+                ) {
+                    // Safety: ctx is a pointer to the callback context (Box<CallbackContext>)
+                    // that was set when registering the update hook. database and table are
+                    // NUL-terminated strings owned by SQLite for the duration of the call.
+                    // We check for null before dereferencing.
+                    unsafe {
+                        if ctx.is_null() || database.is_null() || table.is_null() {
+                            return;
+                        }
+                        let database = cstr_from_i8_ptr(database).to_string_lossy().into_owned();
+                        let table = cstr_from_i8_ptr(table).to_string_lossy().into_owned();
+                        let context = &*(ctx as *const CallbackContext);
+
+                        // Thread mode: queue the call without waiting. Like trace callbacks,
+                        // update hooks are informational, so a full queue drops the event.
+                        if let Some(dispatcher) = context.dispatcher() {
+                            let callback = Arc::clone(&context.callback);
+                            let _ = dispatcher.post(move || {
+                                Python::attach(|py| {
+                                    let _ = callback
+                                        .bind(py)
+                                        .call1((operation, database, table, rowid));
+                                })
+                            });
+                            return;
+                        }
+
+                        // Note: Python::with_gil is used here for sync operation in async context.
+                        // The deprecation warning is acceptable as this is a sync operation within async.
+                        #[allow(deprecated)]
+                        Python::with_gil(|py| {
+                            // Errors are ignored so a failing hook cannot fail the statement
+                            let _ = context
+                                .callback
+                                .bind(py)
+                                .call1((operation, database, table, rowid));
+                        });
+                    }
+                }
+
+                let context = callback_for_update.map(|callback| {
+                    HookContext::new(CallbackContext::new(
+                        Arc::clone(&callback),
+                        &callback_dispatcher,
+                    ))
+                    .holding(&callback)
+                });
+                let callback_ptr = context
+                    .as_ref()
+                    .map_or(std::ptr::null_mut(), HookContext::as_ptr);
+
+                // Set or clear the update hook
+                // Safety: raw_db is a valid sqlite3* pointer obtained from
+                // lock_handle().as_raw_handle().as_ptr() and is guaranteed to be valid
+                // for the lifetime of the handle lock. callback_ptr is either null or
+                // a pointer to the CallbackContext owned by `context`.
+                unsafe {
+                    sqlite3_update_hook(
+                        raw_db,
+                        (!callback_ptr.is_null()).then_some(update_trampoline as _),
+                        callback_ptr,
+                    );
+                }
+
+                // SQLite now references the new context (if any); free the one it replaced
+                drop(std::mem::replace(
+                    &mut hook_contexts.lock().unwrap().update,
+                    context,
+                ));
+
+                // After clearing, check if all callbacks are now cleared
+                if callback.is_none() {
+                    let all_cleared = !has_callbacks(
+                        &load_extension_enabled,
+                        &user_functions,
+                        &trace_callback,
+                        &authorizer_callback,
+                        &progress_handler,
+                        &update_hook,
+                    );
+                    if all_cleared {
+                        // Release the callback connection
+                        drop(handle);
+                        drop(conn_guard);
+                        let mut callback_guard = callback_connection.lock().await;
+                        callback_guard.take();
+                        return Ok(());
+                    }
+                }
+
+                Ok(())
+            };
+            runtime::future_into_py(py, self.runtime.as_deref(), future).map(|bound| bound.unbind())
+        })
+    }
+
+    /// Dump the database as a list of SQL statements.
+    /// Returns a list of SQL strings that can recreate the database.
+    fn iterdump(self_: PyRef<Self>) -> PyResult<Py<PyAny>> {
+        let path = self_.path.clone();
+        let pool = Arc::clone(&self_.pool);
+        let pragmas = Arc::clone(&self_.pragmas);
+        let pool_size = Arc::clone(&self_.pool_size);
+        let connection_timeout_secs = Arc::clone(&self_.connection_timeout_secs);
+        let statement_policy = self_.statement_policy.clone();
+        let native_functions = self_.native_functions;
+        let wal_hook = self_.wal_hook.clone();
+        let vfs = self_.vfs.clone();
+        let event_hooks = Arc::clone(&self_.event_hooks);
+        let transaction_state = Arc::clone(&self_.transaction_state);
+        let transaction_connection = Arc::clone(&self_.transaction_connection);
+        // Callback infrastructure (Phase 2.7)
+        let callback_connection = Arc::clone(&self_.callback_connection);
+        let load_extension_enabled = Arc::clone(&self_.load_extension_enabled);
+        let user_functions = Arc::clone(&self_.user_functions);
+        let trace_callback = Arc::clone(&self_.trace_callback);
+        let authorizer_callback = Arc::clone(&self_.authorizer_callback);
+        let progress_handler = Arc::clone(&self_.progress_handler);
+        let update_hook = Arc::clone(&self_.update_hook);
+
+        Python::attach(|py| {
+            let future = async move {
+                // Priority: transaction > callbacks > pool
+                let in_transaction = {
+                    let g = transaction_state.lock().await;
+                    g.is_active()
+                };
+
+                let has_callbacks_flag = has_callbacks(
+                    &load_extension_enabled,
+                    &user_functions,
+                    &trace_callback,
+                    &authorizer_callback,
+                    &progress_handler,
+                    &update_hook,
+                );
+
+                // Helper function to encode bytes as hex
+                fn bytes_to_hex(bytes: &[u8]) -> String {
+                    bytes.iter().map(|b| format!("{b:02x}")).collect()
+                }
+
+                // Get connection for queries
+                // We need to handle different connection types
+                let mut statements = Vec::new();
+                statements.push("BEGIN TRANSACTION;".to_string());
+
+                // Query sqlite_master - use appropriate connection
+                let schema_rows = if in_transaction {
+                    let mut conn_guard = transaction_connection.lock().await;
+                    let conn = conn_guard.as_mut().ok_or_else(|| {
+                        OperationalError::new_err("Transaction connection not available")
+                    })?;
                     sqlx::query("SELECT type, name, sql FROM sqlite_master WHERE sql IS NOT NULL ORDER BY type, name")
                         .fetch_all(&mut **conn)
                         .await
@@ -3264,6 +5860,11 @@ impl Connection {
                         &pragmas,
                         &pool_size,
                         &connection_timeout_secs,
+                        &statement_policy,
+                        native_functions,
+                        &wal_hook,
+                        &vfs,
+                        &event_hooks,
                     )
                     .await?;
                     let mut conn_guard = callback_connection.lock().await;
@@ -3281,10 +5882,15 @@ impl Connection {
                         &pragmas,
                         &pool_size,
                         &connection_timeout_secs,
+                        &statement_policy,
+                        native_functions,
+                        &wal_hook,
+                        &vfs,
+                        &event_hooks,
                     )
                     .await?;
                     sqlx::query("SELECT type, name, sql FROM sqlite_master WHERE sql IS NOT NULL ORDER BY type, name")
-                        .fetch_all(&pool_clone)
+                        .fetch_all(&*pool_clone)
                         .await
                         .map_err(|e| map_sqlx_error(e, &path, "SELECT FROM sqlite_master"))?
                 };
@@ -3307,11 +5913,9 @@ impl Connection {
                                 }
                                 statements.push(format!("{sql_stmt};"));
                             }
-                            "index" => {
-                                // Skip system indexes
-                                if !name.starts_with("sqlite_") {
-                                    statements.push(format!("{sql_stmt};"));
-                                }
+                            // Skip system indexes
+                            "index" if !name.starts_with("sqlite_") => {
+                                statements.push(format!("{sql_stmt};"));
                             }
                             "trigger" => {
                                 statements.push(format!("{sql_stmt};"));
@@ -3398,10 +6002,15 @@ impl Connection {
                             &pragmas,
                             &pool_size,
                             &connection_timeout_secs,
+                            &statement_policy,
+                            native_functions,
+                            &wal_hook,
+                            &vfs,
+                            &event_hooks,
                         )
                         .await?;
                         sqlx::query(&query)
-                            .fetch_all(&pool_clone)
+                            .fetch_all(&*pool_clone)
                             .await
                             .map_err(|e| map_sqlx_error(e, &path, &query))?
                     };
@@ -3452,7 +6061,8 @@ impl Connection {
                     Ok(list.into())
                 })
             };
-            future_into_py(py, future).map(|bound| bound.unbind())
+            runtime::future_into_py(py, self_.runtime.as_deref(), future)
+                .map(|bound| bound.unbind())
         })
     }
 
@@ -3464,6 +6074,11 @@ impl Connection {
         let pragmas = Arc::clone(&self_.pragmas);
         let pool_size = Arc::clone(&self_.pool_size);
         let connection_timeout_secs = Arc::clone(&self_.connection_timeout_secs);
+        let statement_policy = self_.statement_policy.clone();
+        let native_functions = self_.native_functions;
+        let wal_hook = self_.wal_hook.clone();
+        let vfs = self_.vfs.clone();
+        let event_hooks = Arc::clone(&self_.event_hooks);
         let transaction_state = Arc::clone(&self_.transaction_state);
         let transaction_connection = Arc::clone(&self_.transaction_connection);
         let callback_connection = Arc::clone(&self_.callback_connection);
@@ -3472,9 +6087,11 @@ impl Connection {
         let trace_callback = Arc::clone(&self_.trace_callback);
         let authorizer_callback = Arc::clone(&self_.authorizer_callback);
         let progress_handler = Arc::clone(&self_.progress_handler);
+        let update_hook = Arc::clone(&self_.update_hook);
         // Init hook infrastructure (Phase 2.11)
         let init_hook = Arc::clone(&self_.init_hook);
         let init_hook_called = Arc::clone(&self_.init_hook_called);
+        let dedicated_runtime = self_.runtime.clone();
         let connection_self = self_.into();
 
         Python::attach(|py| {
@@ -3493,6 +6110,11 @@ impl Connection {
                         &pragmas,
                         &pool_size,
                         &connection_timeout_secs,
+                        &statement_policy,
+                        native_functions,
+                        &wal_hook,
+                        &vfs,
+                        &event_hooks,
                     )
                     .await?;
                 }
@@ -3506,6 +6128,7 @@ impl Connection {
                     &trace_callback,
                     &authorizer_callback,
                     &progress_handler,
+                    &update_hook,
                 );
 
                 // Build query
@@ -3530,6 +6153,11 @@ impl Connection {
                         &pragmas,
                         &pool_size,
                         &connection_timeout_secs,
+                        &statement_policy,
+                        native_functions,
+                        &wal_hook,
+                        &vfs,
+                        &event_hooks,
                     )
                     .await?;
                     let mut conn_guard = callback_connection.lock().await;
@@ -3544,6 +6172,11 @@ impl Connection {
                         &pragmas,
                         &pool_size,
                         &connection_timeout_secs,
+                        &statement_policy,
+                        native_functions,
+                        &wal_hook,
+                        &vfs,
+                        &event_hooks,
                     )
                     .await?;
                     bind_and_fetch_all(&query, &[], &pool_clone, &path).await?
@@ -3566,17 +6199,25 @@ impl Connection {
                     Ok(result_list.into())
                 })
             };
-            future_into_py(py, future).map(|bound| bound.unbind())
+            runtime::future_into_py(py, dedicated_runtime.as_deref(), future)
+                .map(|bound| bound.unbind())
         })
     }
 
     /// Get table information (columns) for a specific table.
+    /// Generated columns are included and flagged via `generated`; `default_expr`
+    /// and `generated_expr` carry the expressions exactly as written in CREATE TABLE.
     fn get_table_info(self_: PyRef<Self>, table_name: String) -> PyResult<Py<PyAny>> {
         let path = self_.path.clone();
         let pool = Arc::clone(&self_.pool);
         let pragmas = Arc::clone(&self_.pragmas);
         let pool_size = Arc::clone(&self_.pool_size);
         let connection_timeout_secs = Arc::clone(&self_.connection_timeout_secs);
+        let statement_policy = self_.statement_policy.clone();
+        let native_functions = self_.native_functions;
+        let wal_hook = self_.wal_hook.clone();
+        let vfs = self_.vfs.clone();
+        let event_hooks = Arc::clone(&self_.event_hooks);
         let transaction_state = Arc::clone(&self_.transaction_state);
         let transaction_connection = Arc::clone(&self_.transaction_connection);
         let callback_connection = Arc::clone(&self_.callback_connection);
@@ -3585,9 +6226,11 @@ impl Connection {
         let trace_callback = Arc::clone(&self_.trace_callback);
         let authorizer_callback = Arc::clone(&self_.authorizer_callback);
         let progress_handler = Arc::clone(&self_.progress_handler);
+        let update_hook = Arc::clone(&self_.update_hook);
         // Init hook infrastructure (Phase 2.11)
         let init_hook = Arc::clone(&self_.init_hook);
         let init_hook_called = Arc::clone(&self_.init_hook_called);
+        let dedicated_runtime = self_.runtime.clone();
         let connection_self = self_.into();
 
         // Escape table name for SQL (string literal escaping)
@@ -3596,7 +6239,17 @@ impl Connection {
         // For better safety, we could use identifier quoting (double quotes), but string literals work
         // for PRAGMA table_info which accepts table names as string literals.
         let escaped_table_name = table_name.replace("'", "''");
-        let query = format!("PRAGMA table_info('{escaped_table_name}')");
+        // table_xinfo (minus virtual-table hidden columns) also lists generated
+        // columns; the CREATE statement is fetched alongside so DEFAULT and
+        // generation expressions can be reported as written.
+        let query = format!(
+            "SELECT cid, name, type, \"notnull\", dflt_value, pk, hidden, \
+             (SELECT sql FROM sqlite_temp_master WHERE type = 'table' AND name = '{escaped_table_name}' \
+              UNION ALL \
+              SELECT sql FROM sqlite_master WHERE type = 'table' AND name = '{escaped_table_name}' \
+              LIMIT 1) \
+             FROM pragma_table_xinfo('{escaped_table_name}') WHERE hidden <> 1"
+        );
 
         Python::attach(|py| {
             let future = async move {
@@ -3614,6 +6267,11 @@ impl Connection {
                         &pragmas,
                         &pool_size,
                         &connection_timeout_secs,
+                        &statement_policy,
+                        native_functions,
+                        &wal_hook,
+                        &vfs,
+                        &event_hooks,
                     )
                     .await?;
                 }
@@ -3627,6 +6285,7 @@ impl Connection {
                     &trace_callback,
                     &authorizer_callback,
                     &progress_handler,
+                    &update_hook,
                 );
 
                 let rows = if in_transaction {
@@ -3643,6 +6302,11 @@ impl Connection {
                         &pragmas,
                         &pool_size,
                         &connection_timeout_secs,
+                        &statement_policy,
+                        native_functions,
+                        &wal_hook,
+                        &vfs,
+                        &event_hooks,
                     )
                     .await?;
                     let mut conn_guard = callback_connection.lock().await;
@@ -3657,13 +6321,18 @@ impl Connection {
                         &pragmas,
                         &pool_size,
                         &connection_timeout_secs,
+                        &statement_policy,
+                        native_functions,
+                        &wal_hook,
+                        &vfs,
+                        &event_hooks,
                     )
                     .await?;
                     bind_and_fetch_all(&query, &[], &pool_clone, &path).await?
                 };
 
                 // Convert to list of dictionaries
-                // PRAGMA table_info returns: cid, name, type, notnull, dflt_value, pk
+                // Columns: cid, name, type, notnull, dflt_value, pk, hidden, create sql
                 // Note: Python::with_gil is used here for sync context manager creation before async execution.
                 // The deprecation warning is acceptable as this is a sync context.
                 #[allow(deprecated)]
@@ -3672,6 +6341,11 @@ impl Connection {
                 #[allow(deprecated)]
                 Python::with_gil(|py| -> PyResult<Py<PyAny>> {
                     let result_list = PyList::empty(py);
+                    let clauses = rows
+                        .first()
+                        .and_then(|row| row.try_get::<Option<String>, _>(7).ok().flatten())
+                        .map(|sql| parse_column_clauses(&sql))
+                        .unwrap_or_default();
                     for row in rows.iter() {
                         let dict = PyDict::new(py);
 
@@ -3713,12 +6387,32 @@ impl Connection {
                             dict.set_item("pk", PyInt::new(py, pk))?;
                         }
 
+                        // generated ("virtual"/"stored", None for ordinary columns)
+                        let hidden = row.try_get::<i64, _>(6).unwrap_or(0);
+                        let generated = matches!(hidden, 2 | 3).then(|| hidden_column_kind(hidden));
+                        dict.set_item("generated", generated)?;
+
+                        // DEFAULT / generation expressions verbatim from CREATE TABLE
+                        let column = row
+                            .try_get::<String, _>(1)
+                            .ok()
+                            .and_then(|name| clauses.get(&name.to_lowercase()));
+                        dict.set_item(
+                            "default_expr",
+                            column.and_then(|c| c.default_expr.as_deref()),
+                        )?;
+                        dict.set_item(
+                            "generated_expr",
+                            column.and_then(|c| c.generated_expr.as_deref()),
+                        )?;
+
                         result_list.append(dict)?;
                     }
                     Ok(result_list.into())
                 })
             };
-            future_into_py(py, future).map(|bound| bound.unbind())
+            runtime::future_into_py(py, dedicated_runtime.as_deref(), future)
+                .map(|bound| bound.unbind())
         })
     }
 
@@ -3730,6 +6424,11 @@ impl Connection {
         let pragmas = Arc::clone(&self_.pragmas);
         let pool_size = Arc::clone(&self_.pool_size);
         let connection_timeout_secs = Arc::clone(&self_.connection_timeout_secs);
+        let statement_policy = self_.statement_policy.clone();
+        let native_functions = self_.native_functions;
+        let wal_hook = self_.wal_hook.clone();
+        let vfs = self_.vfs.clone();
+        let event_hooks = Arc::clone(&self_.event_hooks);
         let transaction_state = Arc::clone(&self_.transaction_state);
         let transaction_connection = Arc::clone(&self_.transaction_connection);
         let callback_connection = Arc::clone(&self_.callback_connection);
@@ -3738,9 +6437,11 @@ impl Connection {
         let trace_callback = Arc::clone(&self_.trace_callback);
         let authorizer_callback = Arc::clone(&self_.authorizer_callback);
         let progress_handler = Arc::clone(&self_.progress_handler);
+        let update_hook = Arc::clone(&self_.update_hook);
         // Init hook infrastructure (Phase 2.11)
         let init_hook = Arc::clone(&self_.init_hook);
         let init_hook_called = Arc::clone(&self_.init_hook_called);
+        let dedicated_runtime = self_.runtime.clone();
         let connection_self = self_.into();
 
         // Build query
@@ -3769,6 +6470,11 @@ impl Connection {
                         &pragmas,
                         &pool_size,
                         &connection_timeout_secs,
+                        &statement_policy,
+                        native_functions,
+                        &wal_hook,
+                        &vfs,
+                        &event_hooks,
                     )
                     .await?;
                 }
@@ -3782,6 +6488,7 @@ impl Connection {
                     &trace_callback,
                     &authorizer_callback,
                     &progress_handler,
+                    &update_hook,
                 );
 
                 let rows = if in_transaction {
@@ -3798,6 +6505,11 @@ impl Connection {
                         &pragmas,
                         &pool_size,
                         &connection_timeout_secs,
+                        &statement_policy,
+                        native_functions,
+                        &wal_hook,
+                        &vfs,
+                        &event_hooks,
                     )
                     .await?;
                     let mut conn_guard = callback_connection.lock().await;
@@ -3812,6 +6524,11 @@ impl Connection {
                         &pragmas,
                         &pool_size,
                         &connection_timeout_secs,
+                        &statement_policy,
+                        native_functions,
+                        &wal_hook,
+                        &vfs,
+                        &event_hooks,
                     )
                     .await?;
                     bind_and_fetch_all(&query, &[], &pool_clone, &path).await?
@@ -3864,7 +6581,8 @@ impl Connection {
                     Ok(result_list.into())
                 })
             };
-            future_into_py(py, future).map(|bound| bound.unbind())
+            runtime::future_into_py(py, dedicated_runtime.as_deref(), future)
+                .map(|bound| bound.unbind())
         })
     }
 
@@ -3875,6 +6593,11 @@ impl Connection {
         let pragmas = Arc::clone(&self_.pragmas);
         let pool_size = Arc::clone(&self_.pool_size);
         let connection_timeout_secs = Arc::clone(&self_.connection_timeout_secs);
+        let statement_policy = self_.statement_policy.clone();
+        let native_functions = self_.native_functions;
+        let wal_hook = self_.wal_hook.clone();
+        let vfs = self_.vfs.clone();
+        let event_hooks = Arc::clone(&self_.event_hooks);
         let transaction_state = Arc::clone(&self_.transaction_state);
         let transaction_connection = Arc::clone(&self_.transaction_connection);
         let callback_connection = Arc::clone(&self_.callback_connection);
@@ -3883,9 +6606,11 @@ impl Connection {
         let trace_callback = Arc::clone(&self_.trace_callback);
         let authorizer_callback = Arc::clone(&self_.authorizer_callback);
         let progress_handler = Arc::clone(&self_.progress_handler);
+        let update_hook = Arc::clone(&self_.update_hook);
         // Init hook infrastructure (Phase 2.11)
         let init_hook = Arc::clone(&self_.init_hook);
         let init_hook_called = Arc::clone(&self_.init_hook_called);
+        let dedicated_runtime = self_.runtime.clone();
         let connection_self = self_.into();
 
         // Escape table name for SQL
@@ -3908,6 +6633,11 @@ impl Connection {
                         &pragmas,
                         &pool_size,
                         &connection_timeout_secs,
+                        &statement_policy,
+                        native_functions,
+                        &wal_hook,
+                        &vfs,
+                        &event_hooks,
                     )
                     .await?;
                 }
@@ -3921,6 +6651,7 @@ impl Connection {
                     &trace_callback,
                     &authorizer_callback,
                     &progress_handler,
+                    &update_hook,
                 );
 
                 let rows = if in_transaction {
@@ -3937,6 +6668,11 @@ impl Connection {
                         &pragmas,
                         &pool_size,
                         &connection_timeout_secs,
+                        &statement_policy,
+                        native_functions,
+                        &wal_hook,
+                        &vfs,
+                        &event_hooks,
                     )
                     .await?;
                     let mut conn_guard = callback_connection.lock().await;
@@ -3951,6 +6687,11 @@ impl Connection {
                         &pragmas,
                         &pool_size,
                         &connection_timeout_secs,
+                        &statement_policy,
+                        native_functions,
+                        &wal_hook,
+                        &vfs,
+                        &event_hooks,
                     )
                     .await?;
                     bind_and_fetch_all(&query, &[], &pool_clone, &path).await?
@@ -4014,7 +6755,8 @@ impl Connection {
                     Ok(result_list.into())
                 })
             };
-            future_into_py(py, future).map(|bound| bound.unbind())
+            runtime::future_into_py(py, dedicated_runtime.as_deref(), future)
+                .map(|bound| bound.unbind())
         })
     }
 
@@ -4026,6 +6768,11 @@ impl Connection {
         let pragmas = Arc::clone(&self_.pragmas);
         let pool_size = Arc::clone(&self_.pool_size);
         let connection_timeout_secs = Arc::clone(&self_.connection_timeout_secs);
+        let statement_policy = self_.statement_policy.clone();
+        let native_functions = self_.native_functions;
+        let wal_hook = self_.wal_hook.clone();
+        let vfs = self_.vfs.clone();
+        let event_hooks = Arc::clone(&self_.event_hooks);
         let transaction_state = Arc::clone(&self_.transaction_state);
         let transaction_connection = Arc::clone(&self_.transaction_connection);
         let callback_connection = Arc::clone(&self_.callback_connection);
@@ -4034,6 +6781,7 @@ impl Connection {
         let trace_callback = Arc::clone(&self_.trace_callback);
         let authorizer_callback = Arc::clone(&self_.authorizer_callback);
         let progress_handler = Arc::clone(&self_.progress_handler);
+        let update_hook = Arc::clone(&self_.update_hook);
 
         Python::attach(|py| {
             let future = async move {
@@ -4048,6 +6796,7 @@ impl Connection {
                     &trace_callback,
                     &authorizer_callback,
                     &progress_handler,
+                    &update_hook,
                 );
 
                 // Get tables
@@ -4071,6 +6820,11 @@ impl Connection {
                         &pragmas,
                         &pool_size,
                         &connection_timeout_secs,
+                        &statement_policy,
+                        native_functions,
+                        &wal_hook,
+                        &vfs,
+                        &event_hooks,
                     )
                     .await?;
                     let mut conn_guard = callback_connection.lock().await;
@@ -4085,6 +6839,11 @@ impl Connection {
                         &pragmas,
                         &pool_size,
                         &connection_timeout_secs,
+                        &statement_policy,
+                        native_functions,
+                        &wal_hook,
+                        &vfs,
+                        &event_hooks,
                     )
                     .await?;
                     bind_and_fetch_all(&tables_query, &[], &pool_clone, &path).await?
@@ -4123,6 +6882,11 @@ impl Connection {
                             &pragmas,
                             &pool_size,
                             &connection_timeout_secs,
+                            &statement_policy,
+                            native_functions,
+                            &wal_hook,
+                            &vfs,
+                            &event_hooks,
                         )
                         .await?;
                         bind_and_fetch_all(&info_query, &[], &pool_clone, &path).await?
@@ -4149,6 +6913,11 @@ impl Connection {
                             &pragmas,
                             &pool_size,
                             &connection_timeout_secs,
+                            &statement_policy,
+                            native_functions,
+                            &wal_hook,
+                            &vfs,
+                            &event_hooks,
                         )
                         .await?;
                         bind_and_fetch_all(&indexes_query, &[], &pool_clone, &path).await?
@@ -4176,6 +6945,11 @@ impl Connection {
                             &pragmas,
                             &pool_size,
                             &connection_timeout_secs,
+                            &statement_policy,
+                            native_functions,
+                            &wal_hook,
+                            &vfs,
+                            &event_hooks,
                         )
                         .await?;
                         bind_and_fetch_all(&fk_query, &[], &pool_clone, &path).await?
@@ -4308,7 +7082,8 @@ impl Connection {
                     Ok(schema_dict.into())
                 })
             };
-            future_into_py(py, future).map(|bound| bound.unbind())
+            runtime::future_into_py(py, self_.runtime.as_deref(), future)
+                .map(|bound| bound.unbind())
         })
     }
 
@@ -4320,6 +7095,11 @@ impl Connection {
         let pragmas = Arc::clone(&self_.pragmas);
         let pool_size = Arc::clone(&self_.pool_size);
         let connection_timeout_secs = Arc::clone(&self_.connection_timeout_secs);
+        let statement_policy = self_.statement_policy.clone();
+        let native_functions = self_.native_functions;
+        let wal_hook = self_.wal_hook.clone();
+        let vfs = self_.vfs.clone();
+        let event_hooks = Arc::clone(&self_.event_hooks);
         let transaction_state = Arc::clone(&self_.transaction_state);
         let transaction_connection = Arc::clone(&self_.transaction_connection);
         let callback_connection = Arc::clone(&self_.callback_connection);
@@ -4328,9 +7108,11 @@ impl Connection {
         let trace_callback = Arc::clone(&self_.trace_callback);
         let authorizer_callback = Arc::clone(&self_.authorizer_callback);
         let progress_handler = Arc::clone(&self_.progress_handler);
+        let update_hook = Arc::clone(&self_.update_hook);
         // Init hook infrastructure (Phase 2.11)
         let init_hook = Arc::clone(&self_.init_hook);
         let init_hook_called = Arc::clone(&self_.init_hook_called);
+        let dedicated_runtime = self_.runtime.clone();
         let connection_self = self_.into();
 
         Python::attach(|py| {
@@ -4349,6 +7131,11 @@ impl Connection {
                         &pragmas,
                         &pool_size,
                         &connection_timeout_secs,
+                        &statement_policy,
+                        native_functions,
+                        &wal_hook,
+                        &vfs,
+                        &event_hooks,
                     )
                     .await?;
                 }
@@ -4362,6 +7149,7 @@ impl Connection {
                     &trace_callback,
                     &authorizer_callback,
                     &progress_handler,
+                    &update_hook,
                 );
 
                 // Build query for views
@@ -4388,6 +7176,11 @@ impl Connection {
                         &pragmas,
                         &pool_size,
                         &connection_timeout_secs,
+                        &statement_policy,
+                        native_functions,
+                        &wal_hook,
+                        &vfs,
+                        &event_hooks,
                     )
                     .await?;
                     let mut conn_guard = callback_connection.lock().await;
@@ -4402,6 +7195,11 @@ impl Connection {
                         &pragmas,
                         &pool_size,
                         &connection_timeout_secs,
+                        &statement_policy,
+                        native_functions,
+                        &wal_hook,
+                        &vfs,
+                        &event_hooks,
                     )
                     .await?;
                     bind_and_fetch_all(&query, &[], &pool_clone, &path).await?
@@ -4424,7 +7222,8 @@ impl Connection {
                     Ok(result_list.into())
                 })
             };
-            future_into_py(py, future).map(|bound| bound.unbind())
+            runtime::future_into_py(py, dedicated_runtime.as_deref(), future)
+                .map(|bound| bound.unbind())
         })
     }
 
@@ -4435,6 +7234,11 @@ impl Connection {
         let pragmas = Arc::clone(&self_.pragmas);
         let pool_size = Arc::clone(&self_.pool_size);
         let connection_timeout_secs = Arc::clone(&self_.connection_timeout_secs);
+        let statement_policy = self_.statement_policy.clone();
+        let native_functions = self_.native_functions;
+        let wal_hook = self_.wal_hook.clone();
+        let vfs = self_.vfs.clone();
+        let event_hooks = Arc::clone(&self_.event_hooks);
         let transaction_state = Arc::clone(&self_.transaction_state);
         let transaction_connection = Arc::clone(&self_.transaction_connection);
         let callback_connection = Arc::clone(&self_.callback_connection);
@@ -4443,9 +7247,11 @@ impl Connection {
         let trace_callback = Arc::clone(&self_.trace_callback);
         let authorizer_callback = Arc::clone(&self_.authorizer_callback);
         let progress_handler = Arc::clone(&self_.progress_handler);
+        let update_hook = Arc::clone(&self_.update_hook);
         // Init hook infrastructure (Phase 2.11)
         let init_hook = Arc::clone(&self_.init_hook);
         let init_hook_called = Arc::clone(&self_.init_hook_called);
+        let dedicated_runtime = self_.runtime.clone();
         let connection_self = self_.into();
 
         // Escape table name for SQL
@@ -4468,6 +7274,11 @@ impl Connection {
                         &pragmas,
                         &pool_size,
                         &connection_timeout_secs,
+                        &statement_policy,
+                        native_functions,
+                        &wal_hook,
+                        &vfs,
+                        &event_hooks,
                     )
                     .await?;
                 }
@@ -4481,6 +7292,7 @@ impl Connection {
                     &trace_callback,
                     &authorizer_callback,
                     &progress_handler,
+                    &update_hook,
                 );
 
                 let rows = if in_transaction {
@@ -4497,6 +7309,11 @@ impl Connection {
                         &pragmas,
                         &pool_size,
                         &connection_timeout_secs,
+                        &statement_policy,
+                        native_functions,
+                        &wal_hook,
+                        &vfs,
+                        &event_hooks,
                     )
                     .await?;
                     let mut conn_guard = callback_connection.lock().await;
@@ -4511,6 +7328,11 @@ impl Connection {
                         &pragmas,
                         &pool_size,
                         &connection_timeout_secs,
+                        &statement_policy,
+                        native_functions,
+                        &wal_hook,
+                        &vfs,
+                        &event_hooks,
                     )
                     .await?;
                     bind_and_fetch_all(&query, &[], &pool_clone, &path).await?
@@ -4561,7 +7383,8 @@ impl Connection {
                     Ok(result_list.into())
                 })
             };
-            future_into_py(py, future).map(|bound| bound.unbind())
+            runtime::future_into_py(py, dedicated_runtime.as_deref(), future)
+                .map(|bound| bound.unbind())
         })
     }
 
@@ -4572,6 +7395,11 @@ impl Connection {
         let pragmas = Arc::clone(&self_.pragmas);
         let pool_size = Arc::clone(&self_.pool_size);
         let connection_timeout_secs = Arc::clone(&self_.connection_timeout_secs);
+        let statement_policy = self_.statement_policy.clone();
+        let native_functions = self_.native_functions;
+        let wal_hook = self_.wal_hook.clone();
+        let vfs = self_.vfs.clone();
+        let event_hooks = Arc::clone(&self_.event_hooks);
         let transaction_state = Arc::clone(&self_.transaction_state);
         let transaction_connection = Arc::clone(&self_.transaction_connection);
         let callback_connection = Arc::clone(&self_.callback_connection);
@@ -4580,9 +7408,11 @@ impl Connection {
         let trace_callback = Arc::clone(&self_.trace_callback);
         let authorizer_callback = Arc::clone(&self_.authorizer_callback);
         let progress_handler = Arc::clone(&self_.progress_handler);
+        let update_hook = Arc::clone(&self_.update_hook);
         // Init hook infrastructure (Phase 2.11)
         let init_hook = Arc::clone(&self_.init_hook);
         let init_hook_called = Arc::clone(&self_.init_hook_called);
+        let dedicated_runtime = self_.runtime.clone();
         let connection_self = self_.into();
 
         // Escape index name for SQL
@@ -4605,6 +7435,11 @@ impl Connection {
                         &pragmas,
                         &pool_size,
                         &connection_timeout_secs,
+                        &statement_policy,
+                        native_functions,
+                        &wal_hook,
+                        &vfs,
+                        &event_hooks,
                     )
                     .await?;
                 }
@@ -4618,6 +7453,7 @@ impl Connection {
                     &trace_callback,
                     &authorizer_callback,
                     &progress_handler,
+                    &update_hook,
                 );
 
                 let rows = if in_transaction {
@@ -4634,6 +7470,11 @@ impl Connection {
                         &pragmas,
                         &pool_size,
                         &connection_timeout_secs,
+                        &statement_policy,
+                        native_functions,
+                        &wal_hook,
+                        &vfs,
+                        &event_hooks,
                     )
                     .await?;
                     let mut conn_guard = callback_connection.lock().await;
@@ -4648,6 +7489,11 @@ impl Connection {
                         &pragmas,
                         &pool_size,
                         &connection_timeout_secs,
+                        &statement_policy,
+                        native_functions,
+                        &wal_hook,
+                        &vfs,
+                        &event_hooks,
                     )
                     .await?;
                     bind_and_fetch_all(&query, &[], &pool_clone, &path).await?
@@ -4686,18 +7532,26 @@ impl Connection {
                     Ok(result_list.into())
                 })
             };
-            future_into_py(py, future).map(|bound| bound.unbind())
+            runtime::future_into_py(py, dedicated_runtime.as_deref(), future)
+                .map(|bound| bound.unbind())
         })
     }
 
     /// Get extended table information using PRAGMA table_xinfo (SQLite 3.26.0+).
-    /// Returns additional information beyond table_info, including hidden columns.
+    /// Returns additional information beyond table_info, including hidden and
+    /// generated columns; each entry's `kind` is "normal", "hidden", "virtual"
+    /// or "stored".
     fn get_table_xinfo(self_: PyRef<Self>, table_name: String) -> PyResult<Py<PyAny>> {
         let path = self_.path.clone();
         let pool = Arc::clone(&self_.pool);
         let pragmas = Arc::clone(&self_.pragmas);
         let pool_size = Arc::clone(&self_.pool_size);
         let connection_timeout_secs = Arc::clone(&self_.connection_timeout_secs);
+        let statement_policy = self_.statement_policy.clone();
+        let native_functions = self_.native_functions;
+        let wal_hook = self_.wal_hook.clone();
+        let vfs = self_.vfs.clone();
+        let event_hooks = Arc::clone(&self_.event_hooks);
         let transaction_state = Arc::clone(&self_.transaction_state);
         let transaction_connection = Arc::clone(&self_.transaction_connection);
         let callback_connection = Arc::clone(&self_.callback_connection);
@@ -4706,9 +7560,11 @@ impl Connection {
         let trace_callback = Arc::clone(&self_.trace_callback);
         let authorizer_callback = Arc::clone(&self_.authorizer_callback);
         let progress_handler = Arc::clone(&self_.progress_handler);
+        let update_hook = Arc::clone(&self_.update_hook);
         // Init hook infrastructure (Phase 2.11)
         let init_hook = Arc::clone(&self_.init_hook);
         let init_hook_called = Arc::clone(&self_.init_hook_called);
+        let dedicated_runtime = self_.runtime.clone();
         let connection_self = self_.into();
 
         // Escape table name for SQL
@@ -4731,6 +7587,11 @@ impl Connection {
                         &pragmas,
                         &pool_size,
                         &connection_timeout_secs,
+                        &statement_policy,
+                        native_functions,
+                        &wal_hook,
+                        &vfs,
+                        &event_hooks,
                     )
                     .await?;
                 }
@@ -4744,6 +7605,7 @@ impl Connection {
                     &trace_callback,
                     &authorizer_callback,
                     &progress_handler,
+                    &update_hook,
                 );
 
                 let rows = if in_transaction {
@@ -4760,6 +7622,11 @@ impl Connection {
                         &pragmas,
                         &pool_size,
                         &connection_timeout_secs,
+                        &statement_policy,
+                        native_functions,
+                        &wal_hook,
+                        &vfs,
+                        &event_hooks,
                     )
                     .await?;
                     let mut conn_guard = callback_connection.lock().await;
@@ -4774,6 +7641,11 @@ impl Connection {
                         &pragmas,
                         &pool_size,
                         &connection_timeout_secs,
+                        &statement_policy,
+                        native_functions,
+                        &wal_hook,
+                        &vfs,
+                        &event_hooks,
                     )
                     .await?;
                     bind_and_fetch_all(&query, &[], &pool_clone, &path).await?
@@ -4833,6 +7705,7 @@ impl Connection {
                         // hidden (0=normal, 1=hidden, 2=virtual, 3=stored)
                         if let Ok(hidden) = row.try_get::<i64, _>(6) {
                             dict.set_item("hidden", PyInt::new(py, hidden))?;
+                            dict.set_item("kind", hidden_column_kind(hidden))?;
                         }
 
                         result_list.append(dict)?;
@@ -4840,11 +7713,26 @@ impl Connection {
                     Ok(result_list.into())
                 })
             };
-            future_into_py(py, future).map(|bound| bound.unbind())
+            runtime::future_into_py(py, dedicated_runtime.as_deref(), future)
+                .map(|bound| bound.unbind())
         })
     }
 
-    /// Backup database to another connection.
+    /// Back the database up to another connection or a database file.
+    ///
+    /// Copies `pages` pages per step (all of them when 0) with the SQLite
+    /// online backup API, sleeping `sleep` seconds between steps so other
+    /// connections can use the database meanwhile, and calls `progress` with
+    /// `(remaining, page_count, pages_copied)` after each step. `target` is a
+    /// rapsqlite.Connection, a sqlite3.Connection, or the path (str or
+    /// os.PathLike) of a database file, created if missing and overwritten
+    /// otherwise. Matches aiosqlite's `backup()`.
+    ///
+    /// # Example
+    ///
+    /// .. code-block:: python
+    ///
+    ///     await conn.backup("nightly.db", pages=256, progress=report)
     #[pyo3(signature = (target, *, pages = 0, progress = None, name = "main", sleep = 0.25))]
     fn backup(
         self_: PyRef<Self>,
@@ -4859,6 +7747,11 @@ impl Connection {
         let pragmas = Arc::clone(&self_.pragmas);
         let pool_size = Arc::clone(&self_.pool_size);
         let connection_timeout_secs = Arc::clone(&self_.connection_timeout_secs);
+        let statement_policy = self_.statement_policy.clone();
+        let native_functions = self_.native_functions;
+        let wal_hook = self_.wal_hook.clone();
+        let vfs = self_.vfs.clone();
+        let event_hooks = Arc::clone(&self_.event_hooks);
         let transaction_state = Arc::clone(&self_.transaction_state);
         let transaction_connection = Arc::clone(&self_.transaction_connection);
         let callback_connection = Arc::clone(&self_.callback_connection);
@@ -4867,6 +7760,7 @@ impl Connection {
         let trace_callback = Arc::clone(&self_.trace_callback);
         let authorizer_callback = Arc::clone(&self_.authorizer_callback);
         let progress_handler = Arc::clone(&self_.progress_handler);
+        let update_hook = Arc::clone(&self_.update_hook);
 
         let name = name.to_string();
         Python::attach(|py| {
@@ -4875,6 +7769,12 @@ impl Connection {
 
             // Check if target is rapsqlite Connection or sqlite3.Connection and extract info
             let target_is_rapsqlite = target.bind(py).is_instance_of::<Connection>();
+            // A str or os.PathLike names a database file to copy into
+            let target_file: Option<PathBuf> = if target_is_rapsqlite {
+                None
+            } else {
+                target.bind(py).extract().ok()
+            };
             let target_clone = target.clone_ref(py);
 
             // If rapsqlite, extract connection fields before async block
@@ -4884,6 +7784,11 @@ impl Connection {
                 target_pragmas_opt,
                 target_pool_size_opt,
                 target_connection_timeout_secs_opt,
+                target_statement_policy,
+                target_native_functions,
+                target_wal_hook,
+                target_vfs,
+                target_event_hooks_opt,
                 target_transaction_state_opt,
                 target_transaction_connection_opt,
                 target_callback_connection_opt,
@@ -4892,6 +7797,7 @@ impl Connection {
                 target_trace_callback_opt,
                 target_authorizer_callback_opt,
                 target_progress_handler_opt,
+                target_update_hook_opt,
             ) = if target_is_rapsqlite {
                 let target_conn = target_clone
                     .bind(py)
@@ -4904,6 +7810,11 @@ impl Connection {
                     Some(target_conn_borrowed.pragmas.clone()),
                     Some(target_conn_borrowed.pool_size.clone()),
                     Some(target_conn_borrowed.connection_timeout_secs.clone()),
+                    target_conn_borrowed.statement_policy.clone(),
+                    target_conn_borrowed.native_functions,
+                    target_conn_borrowed.wal_hook.clone(),
+                    target_conn_borrowed.vfs.clone(),
+                    Some(target_conn_borrowed.event_hooks.clone()),
                     Some(target_conn_borrowed.transaction_state.clone()),
                     Some(target_conn_borrowed.transaction_connection.clone()),
                     Some(target_conn_borrowed.callback_connection.clone()),
@@ -4912,10 +7823,12 @@ impl Connection {
                     Some(target_conn_borrowed.trace_callback.clone()),
                     Some(target_conn_borrowed.authorizer_callback.clone()),
                     Some(target_conn_borrowed.progress_handler.clone()),
+                    Some(target_conn_borrowed.update_hook.clone()),
                 )
             } else {
                 (
-                    None, None, None, None, None, None, None, None, None, None, None, None, None,
+                    None, None, None, None, None, None, false, None, None, None, None, None, None,
+                    None, None, None, None, None, None,
                 )
             };
 
@@ -4939,6 +7852,8 @@ impl Connection {
                 // the connection out of the slot and restore it afterwards.
                 let mut source_taken: Option<TakenConnection> = None;
                 let mut target_taken: Option<TakenConnection> = None;
+                // Open until the backup is finished
+                let mut target_file_db: Option<BackupFile> = None;
 
                 let result: Result<(), PyErr> = async {
                     // Determine source connection kind.
@@ -4952,6 +7867,7 @@ impl Connection {
                         &trace_callback,
                         &authorizer_callback,
                         &progress_handler,
+                        &update_hook,
                     );
 
                     // Acquire an exclusive source PoolConnection.
@@ -4970,6 +7886,11 @@ impl Connection {
                             &pragmas,
                             &pool_size,
                             &connection_timeout_secs,
+                            &statement_policy,
+                            native_functions,
+                            &wal_hook,
+                            &vfs,
+                            &event_hooks,
                         )
                         .await?;
                         let mut guard = callback_connection.lock().await;
@@ -4984,6 +7905,11 @@ impl Connection {
                             &pragmas,
                             &pool_size,
                             &connection_timeout_secs,
+                            &statement_policy,
+                            native_functions,
+                            &wal_hook,
+                            &vfs,
+                            &event_hooks,
                         )
                         .await?;
                         let pool_size_val = {
@@ -5035,6 +7961,10 @@ impl Connection {
                             target_authorizer_callback_opt.clone().unwrap();
                         let target_progress_handler: ProgressHandler =
                             target_progress_handler_opt.clone().unwrap();
+                        let target_update_hook: Arc<StdMutex<Option<Py<PyAny>>>> =
+                            target_update_hook_opt.clone().unwrap();
+                        let target_event_hooks: Arc<EventHooks> =
+                            target_event_hooks_opt.clone().unwrap();
 
                         let target_in_transaction = {
                             let g = target_transaction_state.lock().await;
@@ -5047,6 +7977,7 @@ impl Connection {
                             &target_trace_callback,
                             &target_authorizer_callback,
                             &target_progress_handler,
+                            &target_update_hook,
                         );
 
                         if target_in_transaction {
@@ -5063,6 +7994,11 @@ impl Connection {
                                 &target_pragmas,
                                 &target_pool_size,
                                 &target_connection_timeout_secs,
+                                &target_statement_policy,
+                                target_native_functions,
+                                &target_wal_hook,
+                                &target_vfs,
+                                &target_event_hooks,
                             )
                             .await?;
                             let mut guard = target_callback_connection.lock().await;
@@ -5077,6 +8013,11 @@ impl Connection {
                                 &target_pragmas,
                                 &target_pool_size,
                                 &target_connection_timeout_secs,
+                                &target_statement_policy,
+                                target_native_functions,
+                                &target_wal_hook,
+                                &target_vfs,
+                                &target_event_hooks,
                             )
                             .await?;
                             let target_pool_size_val = {
@@ -5103,6 +8044,15 @@ impl Connection {
                             OperationalError::new_err(format!("Failed to lock target handle: {e}"))
                         })?;
                         target_handle = SendPtr(handle.as_raw_handle().as_ptr());
+                    } else if let Some(target_file) = &target_file {
+                        let file = BackupFile::open(target_file).map_err(|e| {
+                            OperationalError::new_err(format!(
+                                "Cannot back up to {}: {e}",
+                                target_file.display()
+                            ))
+                        })?;
+                        target_handle = SendPtr(file.handle());
+                        target_file_db = Some(file);
                     } else {
                         // sqlite3.Connection - use Python helper to extract handle.
                         #[allow(deprecated)]
@@ -5311,10 +8261,1507 @@ impl Connection {
                     let mut g = slot.lock().await;
                     *g = Some(conn);
                 }
+                drop(target_file_db);
+                // Even a failed backup may have overwritten some of the target's pages
+                if let Some(target_path) = &target_path_opt {
+                    result_cache::note_replaced(target_path);
+                } else if let Some(target_file) = &target_file {
+                    result_cache::note_replaced(&target_file.to_string_lossy());
+                }
+
+                result
+            };
+            runtime::future_into_py(py, self_.runtime.as_deref(), future)
+                .map(|bound| bound.unbind())
+        })
+    }
+
+    /// Back the database up to S3-compatible object storage.
+    ///
+    /// Takes a consistent snapshot with the SQLite backup API and uploads it
+    /// to `url` (`s3://bucket/key`), as one PUT when it fits in `part_size`
+    /// and otherwise as a multipart upload sending `concurrency` parts at a
+    /// time. Only committed data is backed up. Requests are signed with AWS
+    /// Signature Version 4 using `AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY`
+    /// and `AWS_SESSION_TOKEN` from the environment.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - Destination object, `s3://bucket/key`.
+    /// * `region` - Bucket region (default `AWS_REGION`, then
+    ///   `AWS_DEFAULT_REGION`, then "us-east-1").
+    /// * `endpoint_url` - Endpoint of an S3-compatible store such as MinIO
+    ///   (default `AWS_ENDPOINT_URL`); objects are then addressed path-style.
+    /// * `part_size` - Bytes per upload part, at least 5 MiB. Default 8 MiB.
+    /// * `concurrency` - Parts uploaded at the same time. Default 4.
+    /// * `encryption_key` - 32-byte key to encrypt the snapshot with before
+    ///   it leaves the machine, as `backup_encrypted()` does. Default None.
+    ///
+    /// # Returns
+    ///
+    /// The number of bytes uploaded.
+    ///
+    /// # Errors
+    ///
+    /// Raises ValueError for a `part_size` below 5 MiB, a `concurrency` of 0
+    /// or a key that is not 32 bytes, and OperationalError inside a
+    /// transaction, if the URL is malformed, no credentials are set, or the
+    /// snapshot or upload fails (a failed multipart upload is aborted).
+    ///
+    /// # Example
+    ///
+    /// .. code-block:: python
+    ///
+    ///     async with rapsqlite.connect("app.db") as conn:
+    ///         size = await conn.backup_to_url("s3://backups/app/2026-10-17.db")
+    #[pyo3(signature = (
+        url,
+        *,
+        region = None,
+        endpoint_url = None,
+        part_size = rapsqlite_core::s3::DEFAULT_PART_SIZE,
+        concurrency = rapsqlite_core::s3::DEFAULT_CONCURRENCY,
+        encryption_key = None
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn backup_to_url(
+        self_: PyRef<Self>,
+        url: String,
+        region: Option<String>,
+        endpoint_url: Option<String>,
+        part_size: usize,
+        concurrency: usize,
+        encryption_key: Option<&[u8]>,
+    ) -> PyResult<Py<PyAny>> {
+        if part_size < rapsqlite_core::s3::MIN_PART_SIZE {
+            return Err(ValueError::new_err(
+                "part_size must be at least 5 MiB (5242880 bytes)",
+            ));
+        }
+        if concurrency == 0 {
+            return Err(ValueError::new_err("concurrency must be at least 1"));
+        }
+        let key = encryption_key.map(encryption::parse_key).transpose()?;
+        let options = S3Options {
+            region,
+            endpoint: endpoint_url,
+            credentials: None,
+            part_size,
+            concurrency,
+        };
+        let route = RawHandleRoute::new(&self_);
+        Python::attach(|py| {
+            let future = async move {
+                let mut snapshot = route.snapshot("backup_to_url").await?;
+                if let Some(key) = key {
+                    snapshot = tokio::task::spawn_blocking(move || snapshot.encrypt(&key))
+                        .await
+                        .map_err(|e| OperationalError::new_err(format!("Encryption failed: {e}")))?
+                        .map_err(|e| {
+                            OperationalError::new_err(format!("Encryption failed: {e}"))
+                        })?;
+                }
+                s3::upload_file(snapshot.path(), &url, &options)
+                    .await
+                    .map_err(|e| OperationalError::new_err(e.to_string()))
+            };
+            runtime::future_into_py(py, self_.runtime.as_deref(), future)
+                .map(|bound| bound.unbind())
+        })
+    }
+
+    /// Back the database up into an encrypted file.
+    ///
+    /// Takes a consistent snapshot with the SQLite backup API and encrypts it
+    /// with XChaCha20-Poly1305 under `key` while writing it to `path`, so the
+    /// backup can be kept on shared storage without SQLCipher. Restore it with
+    /// `rapsqlite.decrypt_file(path, dest, key)`, which also detects any
+    /// modification. Only committed data is backed up.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - File to write (replaced if it exists).
+    /// * `key` - 32-byte symmetric key, e.g. from `os.urandom(32)`. Keep it
+    ///   apart from the backups.
+    ///
+    /// # Errors
+    ///
+    /// Raises ValueError for a key that is not 32 bytes and OperationalError
+    /// inside a transaction or if the snapshot or write fails.
+    ///
+    /// # Example
+    ///
+    /// .. code-block:: python
+    ///
+    ///     key = os.urandom(32)
+    ///     await conn.backup_encrypted("/mnt/shared/app.db.enc", key)
+    ///     await rapsqlite.decrypt_file("/mnt/shared/app.db.enc", "restored.db", key)
+    fn backup_encrypted(self_: PyRef<Self>, path: PathBuf, key: &[u8]) -> PyResult<Py<PyAny>> {
+        let key = encryption::parse_key(key)?;
+        let route = RawHandleRoute::new(&self_);
+        Python::attach(|py| {
+            let future = async move {
+                let snapshot = route.snapshot("backup_encrypted").await?;
+                tokio::task::spawn_blocking(move || {
+                    rapsqlite_core::encryption::encrypt_file(snapshot.path(), &path, &key)
+                })
+                .await
+                .map_err(|e| OperationalError::new_err(format!("backup_encrypted failed: {e}")))?
+                .map_err(|e| OperationalError::new_err(format!("backup_encrypted failed: {e}")))?;
+                Ok(())
+            };
+            runtime::future_into_py(py, self_.runtime.as_deref(), future)
+                .map(|bound| bound.unbind())
+        })
+    }
+
+    /// Keep an in-memory copy of the database that `rollback_test_snapshot()`
+    /// resets it to, so a test suite can create its schema (and seed data)
+    /// once and undo each test's changes in microseconds instead of
+    /// recreating the database. Replaces any earlier snapshot.
+    ///
+    /// # Example
+    ///
+    /// ```python
+    /// @pytest.fixture
+    /// async def db(schema_db):
+    ///     yield schema_db
+    ///     await schema_db.rollback_test_snapshot()
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Raises OperationalError inside a transaction or if the copy fails.
+    fn begin_test_snapshot(self_: PyRef<Self>) -> PyResult<Py<PyAny>> {
+        let route = RawHandleRoute::new(&self_);
+        let test_snapshot = Arc::clone(&self_.test_snapshot);
+        Python::attach(|py| {
+            let future = async move {
+                if route.in_transaction().await {
+                    return Err(OperationalError::new_err(
+                        "begin_test_snapshot() cannot run inside a transaction; commit first",
+                    ));
+                }
+                let snapshot = route
+                    .run(|db| {
+                        // Safety: see `RawHandleRoute::run`.
+                        unsafe { MemorySnapshot::take(db) }.map_err(|e| {
+                            OperationalError::new_err(format!("begin_test_snapshot failed: {e}"))
+                        })
+                    })
+                    .await?;
+                *test_snapshot.lock().unwrap() = Some(Arc::new(snapshot));
+                Ok(())
+            };
+            runtime::future_into_py(py, self_.runtime.as_deref(), future)
+                .map(|bound| bound.unbind())
+        })
+    }
 
+    /// Reset the database to the copy taken by `begin_test_snapshot()`. The
+    /// copy is kept, so it can be restored after every test. Every connection
+    /// to the database sees the restored contents, and the result cache is
+    /// cleared.
+    ///
+    /// # Errors
+    ///
+    /// Raises OperationalError without a snapshot, inside a transaction, or if
+    /// the database is locked by another connection.
+    fn rollback_test_snapshot(self_: PyRef<Self>) -> PyResult<Py<PyAny>> {
+        let route = RawHandleRoute::new(&self_);
+        let snapshot = self_.test_snapshot.lock().unwrap().clone().ok_or_else(|| {
+            OperationalError::new_err("No test snapshot; call begin_test_snapshot() first")
+        })?;
+        Python::attach(|py| {
+            let future = async move {
+                if route.in_transaction().await {
+                    return Err(OperationalError::new_err(
+                        "rollback_test_snapshot() cannot run inside a transaction; roll back first",
+                    ));
+                }
+                let result = route
+                    .run(|db| {
+                        // Safety: see `RawHandleRoute::run`.
+                        unsafe { snapshot.restore(db) }.map_err(|e| {
+                            OperationalError::new_err(format!("rollback_test_snapshot failed: {e}"))
+                        })
+                    })
+                    .await;
+                result_cache::note_replaced(&route.path);
                 result
             };
-            future_into_py(py, future).map(|bound| bound.unbind())
+            runtime::future_into_py(py, self_.runtime.as_deref(), future)
+                .map(|bound| bound.unbind())
         })
     }
 }
+
+impl Connection {
+    /// The `RowStream` of `stream()` (`chunk_size` None) and `fetch_stream()`,
+    /// buffering up to `prefetch` rows.
+    fn open_stream(
+        self_: PyRef<Self>,
+        py: Python<'_>,
+        query: Query,
+        parameters: Option<&Bound<'_, PyAny>>,
+        prefetch: usize,
+        chunk_size: Option<usize>,
+    ) -> PyResult<RowStream> {
+        let method = if chunk_size.is_some() {
+            "fetch_stream()"
+        } else {
+            "stream()"
+        };
+        let (query, parameters) = self_.statement(self_.py(), query, parameters)?;
+        let parameters = parameters.as_ref();
+        self_.admit_statement(&query)?;
+        let bind_options = BindOptions {
+            large_int_policy: *self_.large_int_policy.lock().unwrap(),
+            strict: self_.strict,
+        };
+        let (query, param_values) = match parameters {
+            None => (query, Vec::new()),
+            Some(params) => process_parameters(query, params, &bind_options)?,
+        };
+
+        let path = self_.path.clone();
+        let read_pool = self_.read_pool.clone();
+        let pool = Arc::clone(&self_.pool);
+        let pragmas = Arc::clone(&self_.pragmas);
+        let pool_size = Arc::clone(&self_.pool_size);
+        let connection_timeout_secs = Arc::clone(&self_.connection_timeout_secs);
+        let statement_policy = self_.statement_policy.clone();
+        let native_functions = self_.native_functions;
+        let wal_hook = self_.wal_hook.clone();
+        let vfs = self_.vfs.clone();
+        let event_hooks = Arc::clone(&self_.event_hooks);
+        let transaction_state = Arc::clone(&self_.transaction_state);
+        let load_extension_enabled = Arc::clone(&self_.load_extension_enabled);
+        let user_functions = Arc::clone(&self_.user_functions);
+        let trace_callback = Arc::clone(&self_.trace_callback);
+        let authorizer_callback = Arc::clone(&self_.authorizer_callback);
+        let progress_handler = Arc::clone(&self_.progress_handler);
+        let update_hook = Arc::clone(&self_.update_hook);
+        let init_hook = Arc::clone(&self_.init_hook);
+        let init_hook_called = Arc::clone(&self_.init_hook_called);
+        let dedicated_runtime = self_.runtime.clone();
+        let row_factory = self_.factory(self_.py(), "row_factory", &self_.row_factory)?;
+        let text_factory = self_.factory(self_.py(), "text_factory", &self_.text_factory)?;
+        let null_floats_as_nan = Arc::clone(&self_.null_floats_as_nan);
+        let decode_large_ints = Arc::clone(&self_.decode_large_ints);
+        let invalid_utf8 = Arc::clone(&self_.invalid_utf8);
+        let strict = self_.strict;
+        let detect_types = self_.detect_types;
+        let decltypes = Arc::new(StdMutex::new(None));
+        let stream_decltypes = Arc::clone(&decltypes);
+        let connection_self: Py<Connection> = self_.into();
+
+        let (sender, receiver) = mpsc::channel(prefetch);
+        let producer = async move {
+            let result = async {
+                if transaction_state.lock().await.is_active() {
+                    return Err(ProgrammingError::new_err(format!(
+                        "{method} cannot be used inside a transaction; use fetch_all()"
+                    )));
+                }
+                if has_callbacks(
+                    &load_extension_enabled,
+                    &user_functions,
+                    &trace_callback,
+                    &authorizer_callback,
+                    &progress_handler,
+                    &update_hook,
+                ) {
+                    return Err(ProgrammingError::new_err(format!(
+                        "{method} cannot be used while callbacks are registered; use fetch_all()"
+                    )));
+                }
+                let pool_clone = get_or_create_pool(
+                    &path,
+                    &pool,
+                    &pragmas,
+                    &pool_size,
+                    &connection_timeout_secs,
+                    &statement_policy,
+                    native_functions,
+                    &wal_hook,
+                    &vfs,
+                    &event_hooks,
+                )
+                .await?;
+                execute_init_hook_if_needed(&init_hook, &init_hook_called, connection_self).await?;
+                let pool_clone = match &read_pool {
+                    Some(read_pool) => read_pool.route(&path, pool_clone, &query).await?,
+                    None => pool_clone,
+                };
+                let mut conn = pool_clone.acquire().await.map_err(|e| {
+                    pool_acquisition_error(
+                        &path,
+                        &e,
+                        *pool_size.lock().unwrap(),
+                        *connection_timeout_secs.lock().unwrap(),
+                    )
+                })?;
+                *stream_decltypes.lock().unwrap() =
+                    declared_types(detect_types, &mut conn, &query).await?;
+                let mut rows = bind_and_stream_on_connection(&query, &param_values, &mut conn);
+                while let Some(row) = rows
+                    .try_next()
+                    .await
+                    .map_err(|e| map_sqlx_error(e, &path, &query))?
+                {
+                    // The receiver is gone once the stream is closed or dropped
+                    if sender.send(Ok(row)).await.is_err() {
+                        break;
+                    }
+                }
+                Ok(())
+            }
+            .await;
+            result_cache::note_statement(&path, &query);
+            if let Err(e) = result {
+                let _ = sender.send(Err(e)).await;
+            }
+        };
+        let producer = runtime::spawn(py, dedicated_runtime.as_deref(), producer)?;
+
+        Ok(RowStream {
+            receiver: Arc::new(Mutex::new(receiver)),
+            producer,
+            runtime: dedicated_runtime,
+            row_factory,
+            text_factory,
+            null_floats_as_nan,
+            decode_large_ints,
+            invalid_utf8,
+            strict,
+            detect_types,
+            decltypes,
+            converters: Arc::new(OnceLock::new()),
+            chunk_size,
+        })
+    }
+
+    /// The SQL text and parameters to run for a `query` argument: a
+    /// `rapsqlite.sql()` template is rendered (parameters may not be given
+    /// separately), and the `set_default_params()` defaults are merged in.
+    pub(crate) fn statement<'py>(
+        &self,
+        py: Python<'py>,
+        query: Query,
+        parameters: Option<&Bound<'py, PyAny>>,
+    ) -> PyResult<(String, Option<Bound<'py, PyAny>>)> {
+        let defaults = self
+            .default_params
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(|defaults| defaults.bind(py).clone());
+        match query {
+            Query::Text(query) => {
+                let parameters = match &defaults {
+                    Some(defaults) => merge_default_parameters(parameters, defaults)?,
+                    None => parameters.cloned(),
+                };
+                Ok((query, parameters))
+            }
+            Query::Template(_) if parameters.is_some() => {
+                Err(pyo3::exceptions::PyTypeError::new_err(
+                    "parameters can't be passed with a rapsqlite.sql() query; bind() them instead",
+                ))
+            }
+            Query::Template(template) => {
+                let (query, values) = template.get().render_with(py, defaults.as_ref())?;
+                Ok((query, Some(values.into_any())))
+            }
+        }
+    }
+
+    /// The path as `repr()` shows it: quoted, or `<redacted>` under `redact_path`.
+    pub(crate) fn path_repr(&self, py: Python<'_>) -> PyResult<String> {
+        let path = self.display_path();
+        if *self.redact_path.lock().unwrap() {
+            return Ok(path.to_string());
+        }
+        Ok(PyString::new(py, path).repr()?.to_string())
+    }
+
+    fn display_path(&self) -> &str {
+        if *self.redact_path.lock().unwrap() {
+            "<redacted>"
+        } else {
+            &self.path
+        }
+    }
+
+    /// Open, idle and maximum connections of the pool, `Some(None)` when it is
+    /// not open, or None while it is being opened or closed.
+    fn pool_summary(&self) -> Option<Option<(u32, usize, u32)>> {
+        let pool = self.pool.try_lock().ok()?;
+        Some(pool.as_ref().filter(|pool| !pool.is_closed()).map(|pool| {
+            (
+                pool.size(),
+                pool.num_idle(),
+                pool.options().get_max_connections(),
+            )
+        }))
+    }
+
+    /// The transaction state's name, or None while it is being changed.
+    fn transaction_label(&self) -> Option<&'static str> {
+        let state = self.transaction_state.try_lock().ok()?;
+        Some(match *state {
+            TransactionState::None => "none",
+            TransactionState::Starting => "starting",
+            TransactionState::Active => "active",
+        })
+    }
+
+    /// The `options()` override of `name` in effect for the current task:
+    /// `Some(value)` (`None` for the default) or `None` when not overridden.
+    fn option_override(&self, py: Python<'_>, name: &str) -> PyResult<Option<Option<Py<PyAny>>>> {
+        let overrides = self
+            .option_overrides
+            .bind(py)
+            .call_method1("get", (py.None(),))?;
+        let Ok(overrides) = overrides.cast::<PyDict>() else {
+            return Ok(None);
+        };
+        Ok(overrides
+            .get_item(name)?
+            .map(|value| (!value.is_none()).then(|| value.unbind())))
+    }
+
+    /// `row_factory` or `text_factory` for a statement started now: the
+    /// connection's own setting, or a snapshot of an `options()` override.
+    fn factory(
+        &self,
+        py: Python<'_>,
+        name: &str,
+        setting: &Arc<StdMutex<Option<Py<PyAny>>>>,
+    ) -> PyResult<Arc<StdMutex<Option<Py<PyAny>>>>> {
+        Ok(match self.option_override(py, name)? {
+            Some(value) => Arc::new(StdMutex::new(value)),
+            None => Arc::clone(setting),
+        })
+    }
+
+    /// Shared implementation of `fetch_optional()` and `fetch_first()`: zero or
+    /// one row, or with `first_row` the first of any number of rows.
+    fn fetch_optional_row(
+        self_: PyRef<Self>,
+        query: Query,
+        parameters: Option<&Bound<'_, PyAny>>,
+        first_row: bool,
+    ) -> PyResult<Py<PyAny>> {
+        let (query, parameters) = self_.statement(self_.py(), query, parameters)?;
+        let parameters = parameters.as_ref();
+        self_.admit_statement(&query)?;
+        let path = self_.path.clone();
+        let read_pool = self_.read_pool.clone();
+        let pool = Arc::clone(&self_.pool);
+        let pragmas = Arc::clone(&self_.pragmas);
+        let pool_size = Arc::clone(&self_.pool_size);
+        let connection_timeout_secs = Arc::clone(&self_.connection_timeout_secs);
+        let statement_policy = self_.statement_policy.clone();
+        let native_functions = self_.native_functions;
+        let wal_hook = self_.wal_hook.clone();
+        let vfs = self_.vfs.clone();
+        let event_hooks = Arc::clone(&self_.event_hooks);
+        let transaction_state = Arc::clone(&self_.transaction_state);
+        let transaction_connection = Arc::clone(&self_.transaction_connection);
+        let row_factory = self_.factory(self_.py(), "row_factory", &self_.row_factory)?;
+        let text_factory = self_.factory(self_.py(), "text_factory", &self_.text_factory)?;
+        let null_floats_as_nan = Arc::clone(&self_.null_floats_as_nan);
+        let decode_large_ints = Arc::clone(&self_.decode_large_ints);
+        let invalid_utf8 = Arc::clone(&self_.invalid_utf8);
+        let n_plus_one = self_.n_plus_one.clone();
+        let task_audit = self_.task_audit.clone();
+        let statement_logger = Arc::clone(&self_.statement_logger);
+        let bind_options = BindOptions {
+            large_int_policy: *self_.large_int_policy.lock().unwrap(),
+            strict: self_.strict,
+        };
+        // Callback infrastructure (Phase 2.7)
+        let callback_connection = Arc::clone(&self_.callback_connection);
+        let load_extension_enabled = Arc::clone(&self_.load_extension_enabled);
+        let user_functions = Arc::clone(&self_.user_functions);
+        let trace_callback = Arc::clone(&self_.trace_callback);
+        let authorizer_callback = Arc::clone(&self_.authorizer_callback);
+        let progress_handler = Arc::clone(&self_.progress_handler);
+        let update_hook = Arc::clone(&self_.update_hook);
+        let udf_error = Arc::clone(&self_.udf_error);
+        // Init hook infrastructure (Phase 2.11)
+        let init_hook = Arc::clone(&self_.init_hook);
+        let init_hook_called = Arc::clone(&self_.init_hook_called);
+        let detect_types = self_.detect_types;
+        let dedicated_runtime = self_.runtime.clone();
+        let connection_self = self_.into();
+
+        // Process parameters
+        // Note: Python::with_gil is used here for sync parameter processing before async execution.
+        // The deprecation warning is acceptable as this is a sync context.
+        #[allow(deprecated)]
+        let (processed_query, param_values) = Python::with_gil(|_py| -> PyResult<_> {
+            let Some(params) = parameters else {
+                return Ok((query, Vec::new()));
+            };
+
+            process_parameters(query, params, &bind_options)
+        })?;
+
+        check_n_plus_one(&n_plus_one, &processed_query, &param_values)?;
+        audit_statement(&task_audit)?;
+        let statement_log = statement_log::start(&statement_logger, &processed_query, parameters)?;
+        Python::attach(|py| {
+            let future = async move {
+                // Priority: transaction > callbacks > pool
+                let in_transaction = {
+                    let g = transaction_state.lock().await;
+                    g.is_active()
+                };
+
+                // Ensure pool exists before calling init_hook (init_hook needs pool to execute queries)
+                // Skip if in transaction (transaction has its own connection)
+                if !in_transaction {
+                    get_or_create_pool(
+                        &path,
+                        &pool,
+                        &pragmas,
+                        &pool_size,
+                        &connection_timeout_secs,
+                        &statement_policy,
+                        native_functions,
+                        &wal_hook,
+                        &vfs,
+                        &event_hooks,
+                    )
+                    .await?;
+                }
+
+                // Execute init_hook if needed (before any operations)
+                execute_init_hook_if_needed(&init_hook, &init_hook_called, connection_self).await?;
+
+                let has_callbacks_flag = has_callbacks(
+                    &load_extension_enabled,
+                    &user_functions,
+                    &trace_callback,
+                    &authorizer_callback,
+                    &progress_handler,
+                    &update_hook,
+                );
+
+                let decltypes;
+                let opt = if in_transaction {
+                    let mut conn_guard = transaction_connection.lock().await;
+                    let conn = conn_guard.as_mut().ok_or_else(|| {
+                        OperationalError::new_err("Transaction connection not available")
+                    })?;
+                    decltypes = declared_types(detect_types, conn, &processed_query).await?;
+                    if first_row {
+                        bind_and_fetch_optional_on_connection(
+                            &processed_query,
+                            &param_values,
+                            conn,
+                            &path,
+                        )
+                        .await?
+                    } else {
+                        bind_and_fetch_single_on_connection(
+                            &processed_query,
+                            &param_values,
+                            conn,
+                            &path,
+                        )
+                        .await?
+                    }
+                } else if has_callbacks_flag {
+                    // Ensure callback connection exists
+                    ensure_callback_connection(
+                        &path,
+                        &pool,
+                        &callback_connection,
+                        &pragmas,
+                        &pool_size,
+                        &connection_timeout_secs,
+                        &statement_policy,
+                        native_functions,
+                        &wal_hook,
+                        &vfs,
+                        &event_hooks,
+                    )
+                    .await?;
+
+                    // Use callback connection
+                    let mut conn_guard = callback_connection.lock().await;
+                    let conn = conn_guard.as_mut().ok_or_else(|| {
+                        OperationalError::new_err("Callback connection not available")
+                    })?;
+                    decltypes = declared_types(detect_types, conn, &processed_query).await?;
+                    if first_row {
+                        bind_and_fetch_optional_on_connection(
+                            &processed_query,
+                            &param_values,
+                            conn,
+                            &path,
+                        )
+                        .await?
+                    } else {
+                        bind_and_fetch_single_on_connection(
+                            &processed_query,
+                            &param_values,
+                            conn,
+                            &path,
+                        )
+                        .await?
+                    }
+                } else {
+                    let pool_clone = get_or_create_pool(
+                        &path,
+                        &pool,
+                        &pragmas,
+                        &pool_size,
+                        &connection_timeout_secs,
+                        &statement_policy,
+                        native_functions,
+                        &wal_hook,
+                        &vfs,
+                        &event_hooks,
+                    )
+                    .await?;
+                    let pool_clone = match &read_pool {
+                        Some(read_pool) => {
+                            read_pool.route(&path, pool_clone, &processed_query).await?
+                        }
+                        None => pool_clone,
+                    };
+                    decltypes =
+                        declared_types_in_pool(detect_types, &pool_clone, &processed_query, &path)
+                            .await?;
+                    if first_row {
+                        bind_and_fetch_optional(&processed_query, &param_values, &pool_clone, &path)
+                            .await?
+                    } else {
+                        bind_and_fetch_single(&processed_query, &param_values, &pool_clone, &path)
+                            .await?
+                    }
+                };
+
+                match opt {
+                    Some(row) => Python::attach(|py| -> PyResult<Py<PyAny>> {
+                        let guard = row_factory.lock().unwrap();
+                        let factory = RowFactory::resolve(py, guard.as_ref())?;
+                        let tf_guard = text_factory.lock().unwrap();
+                        let converters =
+                            ColumnConverters::resolve(py, detect_types, &row, decltypes.as_deref());
+                        let decode = DecodeOptions {
+                            text_factory: tf_guard.as_ref(),
+                            null_floats_as_nan: *null_floats_as_nan.lock().unwrap(),
+                            decode_large_ints: *decode_large_ints.lock().unwrap(),
+                            invalid_utf8: *invalid_utf8.lock().unwrap(),
+                            strict: bind_options.strict,
+                            converters: converters.as_ref(),
+                        };
+                        let out = factory.convert(py, &row, &decode)?;
+                        Ok(out.unbind())
+                    }),
+                    None => Python::attach(|py| -> PyResult<Py<PyAny>> { Ok(py.None()) }),
+                }
+            };
+            runtime::future_into_py(
+                py,
+                dedicated_runtime.as_deref(),
+                statement_log::logged(
+                    statement_log,
+                    with_udf_errors(udf_error, future),
+                    |py, row| i64::from(!row.is_none(py)),
+                ),
+            )
+            .map(|bound| bound.unbind())
+        })
+    }
+
+    /// Record a statement passed in by the caller in `query_cache`, or refuse it
+    /// with ProgrammingError if statements are frozen and it is not allowed.
+    pub(crate) fn admit_statement(&self, query: &str) -> PyResult<()> {
+        if let Some(allowed) = self.frozen_statements.lock().unwrap().as_ref() {
+            let normalized = normalize_query(query);
+            if !allowed.contains(&normalized) {
+                return Err(ProgrammingError::new_err(format!(
+                    "Statement not allowed after freeze_statements(): {normalized}"
+                )));
+            }
+        }
+        track_query_usage(&self.query_cache, query);
+        Ok(())
+    }
+
+    /// The connection's fetch limits, with per-call overrides.
+    fn fetch_limits_for(
+        &self,
+        max_rows: Option<usize>,
+        max_result_bytes: Option<usize>,
+    ) -> FetchLimits {
+        FetchLimits {
+            max_rows: max_rows.or(self.fetch_limits.max_rows),
+            max_bytes: max_result_bytes.or(self.fetch_limits.max_bytes),
+        }
+    }
+
+    /// Shared body of `fetch_all` and `cached_fetch_all`; `ttl` enables the
+    /// TTL memo, `limits` caps the rows returned (cached ones included).
+    fn fetch_all_rows(
+        self_: PyRef<Self>,
+        query: Query,
+        parameters: Option<&Bound<'_, PyAny>>,
+        ttl: Option<Duration>,
+        limits: FetchLimits,
+    ) -> PyResult<Py<PyAny>> {
+        let (query, parameters) = self_.statement(self_.py(), query, parameters)?;
+        let parameters = parameters.as_ref();
+        self_.admit_statement(&query)?;
+        let path = self_.path.clone();
+        let read_pool = self_.read_pool.clone();
+        let pool = Arc::clone(&self_.pool);
+        let pragmas = Arc::clone(&self_.pragmas);
+        let pool_size = Arc::clone(&self_.pool_size);
+        let connection_timeout_secs = Arc::clone(&self_.connection_timeout_secs);
+        let statement_policy = self_.statement_policy.clone();
+        let native_functions = self_.native_functions;
+        let wal_hook = self_.wal_hook.clone();
+        let vfs = self_.vfs.clone();
+        let event_hooks = Arc::clone(&self_.event_hooks);
+        let transaction_state = Arc::clone(&self_.transaction_state);
+        let transaction_connection = Arc::clone(&self_.transaction_connection);
+        let row_factory = self_.factory(self_.py(), "row_factory", &self_.row_factory)?;
+        let text_factory = self_.factory(self_.py(), "text_factory", &self_.text_factory)?;
+        let null_floats_as_nan = Arc::clone(&self_.null_floats_as_nan);
+        let decode_large_ints = Arc::clone(&self_.decode_large_ints);
+        let invalid_utf8 = Arc::clone(&self_.invalid_utf8);
+        let n_plus_one = self_.n_plus_one.clone();
+        let task_audit = self_.task_audit.clone();
+        let statement_logger = Arc::clone(&self_.statement_logger);
+        let bind_options = BindOptions {
+            large_int_policy: *self_.large_int_policy.lock().unwrap(),
+            strict: self_.strict,
+        };
+        // Callback infrastructure (Phase 2.7)
+        let callback_connection = Arc::clone(&self_.callback_connection);
+        let load_extension_enabled = Arc::clone(&self_.load_extension_enabled);
+        let user_functions = Arc::clone(&self_.user_functions);
+        let trace_callback = Arc::clone(&self_.trace_callback);
+        let authorizer_callback = Arc::clone(&self_.authorizer_callback);
+        let progress_handler = Arc::clone(&self_.progress_handler);
+        let update_hook = Arc::clone(&self_.update_hook);
+        let udf_error = Arc::clone(&self_.udf_error);
+        let result_cache = self_.result_cache.clone();
+        let ttl_cache = Arc::clone(&self_.ttl_cache);
+        // Init hook infrastructure (Phase 2.11)
+        let init_hook = Arc::clone(&self_.init_hook);
+        let init_hook_called = Arc::clone(&self_.init_hook_called);
+        let detect_types = self_.detect_types;
+        let dedicated_runtime = self_.runtime.clone();
+        let connection_self = self_.into();
+
+        // Process parameters
+        // Note: Python::with_gil is used here for sync parameter processing before async execution.
+        // The deprecation warning is acceptable as this is a sync context.
+        #[allow(deprecated)]
+        let (processed_query, param_values) = Python::with_gil(|_py| -> PyResult<_> {
+            let Some(params) = parameters else {
+                return Ok((query, Vec::new()));
+            };
+
+            process_parameters(query, params, &bind_options)
+        })?;
+
+        check_n_plus_one(&n_plus_one, &processed_query, &param_values)?;
+        audit_statement(&task_audit)?;
+        let statement_log = statement_log::start(&statement_logger, &processed_query, parameters)?;
+        let ttl_key = ttl.map(|ttl| (ResultCache::key(&processed_query, &param_values), ttl));
+
+        Python::attach(|py| {
+            let future = async move {
+                // Priority: transaction > callbacks > pool
+                let in_transaction = {
+                    let g = transaction_state.lock().await;
+                    g.is_active()
+                };
+
+                // Ensure pool exists before calling init_hook (init_hook needs pool to execute queries)
+                // Skip if in transaction (transaction has its own connection)
+                if !in_transaction {
+                    get_or_create_pool(
+                        &path,
+                        &pool,
+                        &pragmas,
+                        &pool_size,
+                        &connection_timeout_secs,
+                        &statement_policy,
+                        native_functions,
+                        &wal_hook,
+                        &vfs,
+                        &event_hooks,
+                    )
+                    .await?;
+                }
+
+                // Execute init_hook if needed (before any operations)
+                execute_init_hook_if_needed(&init_hook, &init_hook_called, connection_self).await?;
+
+                let has_callbacks_flag = has_callbacks(
+                    &load_extension_enabled,
+                    &user_functions,
+                    &trace_callback,
+                    &authorizer_callback,
+                    &progress_handler,
+                    &update_hook,
+                );
+
+                let cached = match &ttl_key {
+                    Some((key, ttl)) if !in_transaction => {
+                        ttl_cache.lock().unwrap().get(key, *ttl, Instant::now())
+                    }
+                    _ => None,
+                };
+                let fetched = cached.is_none();
+
+                let decltypes;
+                let rows = if let Some(rows) = cached {
+                    limits
+                        .check(&rows)
+                        .map_err(|e| map_fetch_error(e, &path, &processed_query))?;
+                    let pool_clone = get_or_create_pool(
+                        &path,
+                        &pool,
+                        &pragmas,
+                        &pool_size,
+                        &connection_timeout_secs,
+                        &statement_policy,
+                        native_functions,
+                        &wal_hook,
+                        &vfs,
+                        &event_hooks,
+                    )
+                    .await?;
+                    decltypes =
+                        declared_types_in_pool(detect_types, &pool_clone, &processed_query, &path)
+                            .await?;
+                    rows
+                } else if in_transaction {
+                    let mut conn_guard = transaction_connection.lock().await;
+                    let conn = conn_guard.as_mut().ok_or_else(|| {
+                        OperationalError::new_err("Transaction connection not available")
+                    })?;
+                    decltypes = declared_types(detect_types, conn, &processed_query).await?;
+                    Arc::new(
+                        bind_and_fetch_all_limited_on_connection(
+                            &processed_query,
+                            &param_values,
+                            conn,
+                            &path,
+                            limits,
+                        )
+                        .await?,
+                    )
+                } else if has_callbacks_flag {
+                    // Ensure callback connection exists
+                    ensure_callback_connection(
+                        &path,
+                        &pool,
+                        &callback_connection,
+                        &pragmas,
+                        &pool_size,
+                        &connection_timeout_secs,
+                        &statement_policy,
+                        native_functions,
+                        &wal_hook,
+                        &vfs,
+                        &event_hooks,
+                    )
+                    .await?;
+
+                    // Use callback connection
+                    let mut conn_guard = callback_connection.lock().await;
+                    let conn = conn_guard.as_mut().ok_or_else(|| {
+                        OperationalError::new_err("Callback connection not available")
+                    })?;
+                    decltypes = declared_types(detect_types, conn, &processed_query).await?;
+                    match &result_cache {
+                        Some(cache) => {
+                            fetch_all_cached(
+                                cache,
+                                conn,
+                                &processed_query,
+                                &param_values,
+                                &path,
+                                limits,
+                            )
+                            .await?
+                        }
+                        None => Arc::new(
+                            bind_and_fetch_all_limited_on_connection(
+                                &processed_query,
+                                &param_values,
+                                conn,
+                                &path,
+                                limits,
+                            )
+                            .await?,
+                        ),
+                    }
+                } else {
+                    let pool_clone = get_or_create_pool(
+                        &path,
+                        &pool,
+                        &pragmas,
+                        &pool_size,
+                        &connection_timeout_secs,
+                        &statement_policy,
+                        native_functions,
+                        &wal_hook,
+                        &vfs,
+                        &event_hooks,
+                    )
+                    .await?;
+                    let pool_clone = match &read_pool {
+                        Some(read_pool) => {
+                            read_pool.route(&path, pool_clone, &processed_query).await?
+                        }
+                        None => pool_clone,
+                    };
+                    decltypes =
+                        declared_types_in_pool(detect_types, &pool_clone, &processed_query, &path)
+                            .await?;
+                    match &result_cache {
+                        // The data_version check and the fetch must use the same connection
+                        Some(cache) => {
+                            let mut conn = pool_clone.acquire().await.map_err(|e| {
+                                pool_acquisition_error(
+                                    &path,
+                                    &e,
+                                    *pool_size.lock().unwrap(),
+                                    *connection_timeout_secs.lock().unwrap(),
+                                )
+                            })?;
+                            fetch_all_cached(
+                                cache,
+                                &mut conn,
+                                &processed_query,
+                                &param_values,
+                                &path,
+                                limits,
+                            )
+                            .await?
+                        }
+                        None => Arc::new(
+                            bind_and_fetch_all_limited(
+                                &processed_query,
+                                &param_values,
+                                &pool_clone,
+                                &path,
+                                limits,
+                            )
+                            .await?,
+                        ),
+                    }
+                };
+
+                if let Some((key, ttl)) = ttl_key.filter(|_| fetched && !in_transaction) {
+                    ttl_cache
+                        .lock()
+                        .unwrap()
+                        .insert(key, Arc::clone(&rows), ttl, Instant::now());
+                }
+
+                // Convert rows using row_factory, in chunks for large results
+                convert_in_chunks(rows.len(), move |py, range, result_list| {
+                    let guard = row_factory.lock().unwrap();
+                    let factory = RowFactory::resolve(py, guard.as_ref())?;
+                    let tf_guard = text_factory.lock().unwrap();
+                    let converters = rows.first().and_then(|row| {
+                        ColumnConverters::resolve(py, detect_types, row, decltypes.as_deref())
+                    });
+                    let decode = DecodeOptions {
+                        text_factory: tf_guard.as_ref(),
+                        null_floats_as_nan: *null_floats_as_nan.lock().unwrap(),
+                        decode_large_ints: *decode_large_ints.lock().unwrap(),
+                        invalid_utf8: *invalid_utf8.lock().unwrap(),
+                        strict: bind_options.strict,
+                        converters: converters.as_ref(),
+                    };
+                    for row in &rows[range] {
+                        let out = factory.convert(py, row, &decode)?;
+                        result_list.append(out)?;
+                    }
+                    Ok(())
+                })
+                .await
+            };
+            runtime::future_into_py(
+                py,
+                dedicated_runtime.as_deref(),
+                statement_log::logged(
+                    statement_log,
+                    with_udf_errors(udf_error, future),
+                    |py, rows| rows.bind(py).len().map_or(-1, |n| n as i64),
+                ),
+            )
+            .map(|bound| bound.unbind())
+        })
+    }
+}
+
+/// Run `sql` with `sqlite3_exec` and return (rows changed, last insert rowid).
+///
+/// # Safety
+///
+/// `db` must meet the invariant described on [`RawHandleRoute::run`].
+pub(crate) unsafe fn exec_unprepared(
+    db: *mut sqlite3,
+    sql: &CStr,
+    path: &str,
+) -> PyResult<(u64, i64)> {
+    let before = sqlite3_total_changes(db);
+    let mut errmsg: *mut std::ffi::c_char = std::ptr::null_mut();
+    let rc = sqlite3_exec(db, sql.as_ptr(), None, std::ptr::null_mut(), &mut errmsg);
+    result_cache::note_statement(path, &sql.to_string_lossy());
+    if rc != SQLITE_OK {
+        let msg = if errmsg.is_null() {
+            format!("SQLite error code {rc}")
+        } else {
+            let msg = cstr_from_i8_ptr(errmsg).to_string_lossy().into_owned();
+            sqlite3_free(errmsg as *mut std::ffi::c_void);
+            msg
+        };
+        return Err(map_sqlite_error(rc, &msg, path));
+    }
+    let changes = (sqlite3_total_changes(db) - before).max(0) as u64;
+    Ok((changes, sqlite3_last_insert_rowid(db)))
+}
+
+/// Connection state needed to run a raw SQLite C API call on the connection that
+/// statements are routed to (transaction > callbacks > pool).
+struct RawHandleRoute {
+    path: String,
+    pool: Arc<Mutex<Option<SqlitePool>>>,
+    pragmas: Arc<StdMutex<Vec<(String, String)>>>,
+    pool_size: Arc<StdMutex<Option<usize>>>,
+    connection_timeout_secs: Arc<StdMutex<Option<u64>>>,
+    statement_policy: Option<Arc<StatementPolicy>>,
+    native_functions: bool,
+    wal_hook: Option<Arc<WalHook>>,
+    vfs: Option<String>,
+    event_hooks: Arc<EventHooks>,
+    transaction_state: Arc<Mutex<TransactionState>>,
+    transaction_connection: Arc<Mutex<Option<PoolConnection<sqlx::Sqlite>>>>,
+    callback_connection: Arc<Mutex<Option<PoolConnection<sqlx::Sqlite>>>>,
+    load_extension_enabled: Arc<StdMutex<bool>>,
+    user_functions: UserFunctions,
+    trace_callback: Arc<StdMutex<Option<Py<PyAny>>>>,
+    authorizer_callback: Arc<StdMutex<Option<Py<PyAny>>>>,
+    progress_handler: ProgressHandler,
+    update_hook: Arc<StdMutex<Option<Py<PyAny>>>>,
+}
+
+impl RawHandleRoute {
+    fn new(conn: &Connection) -> Self {
+        RawHandleRoute {
+            path: conn.path.clone(),
+            pool: Arc::clone(&conn.pool),
+            pragmas: Arc::clone(&conn.pragmas),
+            pool_size: Arc::clone(&conn.pool_size),
+            connection_timeout_secs: Arc::clone(&conn.connection_timeout_secs),
+            statement_policy: conn.statement_policy.clone(),
+            native_functions: conn.native_functions,
+            wal_hook: conn.wal_hook.clone(),
+            vfs: conn.vfs.clone(),
+            event_hooks: Arc::clone(&conn.event_hooks),
+            transaction_state: Arc::clone(&conn.transaction_state),
+            transaction_connection: Arc::clone(&conn.transaction_connection),
+            callback_connection: Arc::clone(&conn.callback_connection),
+            load_extension_enabled: Arc::clone(&conn.load_extension_enabled),
+            user_functions: Arc::clone(&conn.user_functions),
+            trace_callback: Arc::clone(&conn.trace_callback),
+            authorizer_callback: Arc::clone(&conn.authorizer_callback),
+            progress_handler: Arc::clone(&conn.progress_handler),
+            update_hook: Arc::clone(&conn.update_hook),
+        }
+    }
+
+    async fn in_transaction(&self) -> bool {
+        *self.transaction_state.lock().await == TransactionState::Active
+    }
+
+    /// A backup snapshot of the committed database for `method`. SQLite cannot
+    /// back up from a connection in a write transaction, so this refuses to
+    /// run inside one.
+    async fn snapshot(&self, method: &str) -> PyResult<Snapshot> {
+        if self.in_transaction().await {
+            return Err(OperationalError::new_err(format!(
+                "{method}() cannot run inside a transaction; commit first"
+            )));
+        }
+        self.run(|db| {
+            // Safety: see `RawHandleRoute::run`.
+            unsafe { Snapshot::take(db) }
+                .map_err(|e| OperationalError::new_err(format!("{method} failed: {e}")))
+        })
+        .await
+    }
+
+    /// Run `f` with the routed connection's `sqlite3*`. The connection (a pool
+    /// connection is held for the call) and its handle stay locked until `f` returns.
+    ///
+    /// This is the invariant the raw-handle helpers in this crate rely on: the
+    /// `sqlite3*` is valid and nothing else uses it until the helper returns.
+    /// Their `# Safety` sections refer here; a handle locked another way, e.g.
+    /// with `lock_handle()` on a pool connection, must give the same guarantee.
+    async fn run<T>(&self, f: impl FnOnce(*mut sqlite3) -> PyResult<T>) -> PyResult<T> {
+        if self.in_transaction().await {
+            let mut conn_guard = self.transaction_connection.lock().await;
+            let conn = conn_guard
+                .as_mut()
+                .ok_or_else(|| OperationalError::new_err("Transaction connection not available"))?;
+            return run_on_handle(conn, f).await;
+        }
+
+        if has_callbacks(
+            &self.load_extension_enabled,
+            &self.user_functions,
+            &self.trace_callback,
+            &self.authorizer_callback,
+            &self.progress_handler,
+            &self.update_hook,
+        ) {
+            ensure_callback_connection(
+                &self.path,
+                &self.pool,
+                &self.callback_connection,
+                &self.pragmas,
+                &self.pool_size,
+                &self.connection_timeout_secs,
+                &self.statement_policy,
+                self.native_functions,
+                &self.wal_hook,
+                &self.vfs,
+                &self.event_hooks,
+            )
+            .await?;
+            let mut conn_guard = self.callback_connection.lock().await;
+            let conn = conn_guard
+                .as_mut()
+                .ok_or_else(|| OperationalError::new_err("Callback connection not available"))?;
+            return run_on_handle(conn, f).await;
+        }
+
+        let pool_clone = get_or_create_pool(
+            &self.path,
+            &self.pool,
+            &self.pragmas,
+            &self.pool_size,
+            &self.connection_timeout_secs,
+            &self.statement_policy,
+            self.native_functions,
+            &self.wal_hook,
+            &self.vfs,
+            &self.event_hooks,
+        )
+        .await?;
+        let pool_size_val = *self.pool_size.lock().unwrap();
+        let timeout_val = *self.connection_timeout_secs.lock().unwrap();
+        let mut temp_conn = pool_clone
+            .acquire()
+            .await
+            .map_err(|e| pool_acquisition_error(&self.path, &e, pool_size_val, timeout_val))?;
+        run_on_handle(&mut temp_conn, f).await
+    }
+}
+
+async fn run_on_handle<T>(
+    sqlite_conn: &mut SqliteConnection,
+    f: impl FnOnce(*mut sqlite3) -> PyResult<T>,
+) -> PyResult<T> {
+    let mut handle = sqlite_conn
+        .lock_handle()
+        .await
+        .map_err(|e| OperationalError::new_err(format!("Failed to lock handle: {e}")))?;
+    f(handle.as_raw_handle().as_ptr())
+}
+
+/// Compile every statement in `sql` with `sqlite3_prepare_v2` without running it.
+///
+/// # Safety
+///
+/// `db` must meet the invariant described on [`RawHandleRoute::run`].
+unsafe fn prepare_only(db: *mut sqlite3, sql: &CStr, path: &str) -> PyResult<()> {
+    let mut remaining = sql.as_ptr();
+    while *remaining != 0 {
+        let mut stmt: *mut sqlite3_stmt = std::ptr::null_mut();
+        let mut tail: *const std::ffi::c_char = std::ptr::null();
+        let rc = sqlite3_prepare_v2(db, remaining, -1, &mut stmt, &mut tail);
+        if rc != SQLITE_OK {
+            let msg = cstr_from_i8_ptr(sqlite3_errmsg(db))
+                .to_string_lossy()
+                .into_owned();
+            return Err(map_sqlite_error(rc, &msg, path));
+        }
+        // stmt is NULL for trailing whitespace or comments
+        sqlite3_finalize(stmt);
+        if tail.is_null() || tail == remaining {
+            break;
+        }
+        remaining = tail;
+    }
+    Ok(())
+}
+
+/// Validate (`dry_run`) or execute DDL statements for `Connection.apply_ddl`,
+/// optionally inside a savepoint so a failure leaves the schema untouched.
+///
+/// # Safety
+///
+/// `db` must meet the invariant described on [`RawHandleRoute::run`].
+unsafe fn apply_ddl_statements(
+    db: *mut sqlite3,
+    statements: &[CString],
+    dry_run: bool,
+    transaction: bool,
+    path: &str,
+) -> PyResult<()> {
+    let with_index = |i: usize, e: PyErr| {
+        Python::attach(|py| {
+            let err_type = e.get_type(py);
+            PyErr::from_type(err_type, format!("Statement {i} failed: {}", e.value(py)))
+        })
+    };
+
+    if dry_run {
+        for (i, sql) in statements.iter().enumerate() {
+            prepare_only(db, sql, path).map_err(|e| with_index(i, e))?;
+        }
+        return Ok(());
+    }
+
+    let savepoint = c"SAVEPOINT rapsqlite_apply_ddl";
+    let release = c"RELEASE rapsqlite_apply_ddl";
+    let rollback = c"ROLLBACK TO rapsqlite_apply_ddl; RELEASE rapsqlite_apply_ddl";
+    if transaction {
+        exec_unprepared(db, savepoint, path)?;
+    }
+    for (i, sql) in statements.iter().enumerate() {
+        if let Err(e) = exec_unprepared(db, sql, path) {
+            if transaction {
+                let _ = exec_unprepared(db, rollback, path);
+            }
+            return Err(with_index(i, e));
+        }
+    }
+    if transaction {
+        exec_unprepared(db, release, path)?;
+    }
+    Ok(())
+}
+
+/// The dict `Connection.profile()` resolves to.
+fn profile_to_dict(py: Python<'_>, profile: &profile::Profile) -> PyResult<Py<PyAny>> {
+    let plan = PyList::empty(py);
+    for node in &profile.plan {
+        let dict = PyDict::new(py);
+        dict.set_item("id", node.id)?;
+        dict.set_item("parent", node.parent)?;
+        dict.set_item("detail", &node.detail)?;
+        dict.set_item("name", &node.name)?;
+        dict.set_item("loops", node.loops)?;
+        dict.set_item("visits", node.visits)?;
+        dict.set_item("estimated_rows", node.estimated_rows)?;
+        dict.set_item("cycles", node.cycles)?;
+        plan.append(dict)?;
+    }
+    let opcodes = PyList::empty(py);
+    for op in &profile.opcodes {
+        let dict = PyDict::new(py);
+        dict.set_item("addr", op.addr)?;
+        dict.set_item("opcode", &op.opcode)?;
+        dict.set_item("p1", op.p1)?;
+        dict.set_item("p2", op.p2)?;
+        dict.set_item("p3", op.p3)?;
+        dict.set_item("p4", &op.p4)?;
+        dict.set_item("p5", op.p5)?;
+        dict.set_item("comment", &op.comment)?;
+        dict.set_item("subprog", &op.subprog)?;
+        dict.set_item("executions", op.executions)?;
+        dict.set_item("cycles", op.cycles)?;
+        opcodes.append(dict)?;
+    }
+    let dict = PyDict::new(py);
+    dict.set_item("rows", profile.rows)?;
+    dict.set_item("elapsed", profile.elapsed.as_secs_f64())?;
+    dict.set_item("vm_steps", profile.vm_steps)?;
+    dict.set_item("fullscan_steps", profile.fullscan_steps)?;
+    dict.set_item("sorts", profile.sorts)?;
+    dict.set_item("autoindex_rows", profile.autoindex_rows)?;
+    dict.set_item("plan", plan)?;
+    dict.set_item("opcodes", opcodes)?;
+    Ok(dict.into_any().unbind())
+}
+
+/// Write the rows `sql` returns to `writer` as CSV records, for
+/// `Connection.export_csv`, preceded by the column names if `header` is set.
+/// Returns the number of rows.
+///
+/// # Safety
+///
+/// `db` must meet the invariant described on [`RawHandleRoute::run`].
+unsafe fn export_csv_rows(
+    db: *mut sqlite3,
+    sql: &CStr,
+    params: &[SqliteParam],
+    header: bool,
+    writer: &mut compression::Writer,
+    path: &str,
+) -> PyResult<u64> {
+    let error = |rc: i32| {
+        let msg = cstr_from_i8_ptr(sqlite3_errmsg(db))
+            .to_string_lossy()
+            .into_owned();
+        map_sqlite_error(rc, &msg, path)
+    };
+    let io_error = |e: std::io::Error| OperationalError::new_err(format!("export_csv failed: {e}"));
+
+    let mut stmt: *mut sqlite3_stmt = std::ptr::null_mut();
+    let rc = sqlite3_prepare_v2(db, sql.as_ptr(), -1, &mut stmt, std::ptr::null_mut());
+    if rc != SQLITE_OK {
+        return Err(error(rc));
+    }
+    if stmt.is_null() {
+        return Err(ProgrammingError::new_err("export_csv query is empty"));
+    }
+
+    let result = (|| {
+        for (i, param) in params.iter().enumerate() {
+            let index = i as c_int + 1;
+            let rc = match param {
+                SqliteParam::Null => sqlite3_bind_null(stmt, index),
+                SqliteParam::Int(v) => sqlite3_bind_int64(stmt, index, *v),
+                SqliteParam::Real(v) => sqlite3_bind_double(stmt, index, *v),
+                SqliteParam::Text(v) => sqlite3_bind_text(
+                    stmt,
+                    index,
+                    v.as_ptr() as *const c_char,
+                    v.len() as c_int,
+                    SQLITE_TRANSIENT(),
+                ),
+                SqliteParam::Blob(v) => sqlite3_bind_blob(
+                    stmt,
+                    index,
+                    v.as_ptr() as *const c_void,
+                    v.len() as c_int,
+                    SQLITE_TRANSIENT(),
+                ),
+            };
+            if rc != SQLITE_OK {
+                return Err(error(rc));
+            }
+        }
+
+        let columns = sqlite3_column_count(stmt);
+        if header {
+            let names: Vec<&[u8]> = (0..columns)
+                .map(|i| cstr_from_i8_ptr(sqlite3_column_name(stmt, i)).to_bytes())
+                .collect();
+            rapsqlite_core::csv::write_record(writer, names.into_iter().map(Some))
+                .map_err(io_error)?;
+        }
+        let mut rows = 0u64;
+        loop {
+            let rc = sqlite3_step(stmt);
+            if rc == SQLITE_DONE {
+                return Ok(rows);
+            }
+            if rc != SQLITE_ROW {
+                return Err(error(rc));
+            }
+            let fields = (0..columns).map(|i| {
+                if sqlite3_column_type(stmt, i) == SQLITE_NULL {
+                    return None;
+                }
+                let data = if sqlite3_column_type(stmt, i) == SQLITE_BLOB {
+                    sqlite3_column_blob(stmt, i) as *const u8
+                } else {
+                    sqlite3_column_text(stmt, i)
+                };
+                let len = sqlite3_column_bytes(stmt, i) as usize;
+                Some(if len == 0 {
+                    &[][..]
+                } else {
+                    std::slice::from_raw_parts(data, len)
+                })
+            });
+            rapsqlite_core::csv::write_record(writer, fields).map_err(io_error)?;
+            rows += 1;
+        }
+    })();
+    sqlite3_finalize(stmt);
+    result
+}
+
+/// Sum of per-row SHA-256 prefixes over the rows `sql` returns, for
+/// `Connection.table_checksum`. Rows are stepped one at a time; wrapping addition
+/// makes the result independent of row order while still counting duplicates.
+///
+/// # Safety
+///
+/// `db` must meet the invariant described on [`RawHandleRoute::run`].
+unsafe fn checksum_rows(db: *mut sqlite3, sql: &CStr, path: &str) -> PyResult<u128> {
+    let error = |rc: i32| {
+        let msg = cstr_from_i8_ptr(sqlite3_errmsg(db))
+            .to_string_lossy()
+            .into_owned();
+        map_sqlite_error(rc, &msg, path)
+    };
+
+    let mut stmt: *mut sqlite3_stmt = std::ptr::null_mut();
+    let rc = sqlite3_prepare_v2(db, sql.as_ptr(), -1, &mut stmt, std::ptr::null_mut());
+    if rc != SQLITE_OK {
+        return Err(error(rc));
+    }
+
+    let columns = sqlite3_column_count(stmt);
+    let mut sum: u128 = 0;
+    let rc = loop {
+        let rc = sqlite3_step(stmt);
+        if rc != SQLITE_ROW {
+            break rc;
+        }
+        let mut hasher = Sha256::new();
+        for i in 0..columns {
+            let kind = sqlite3_column_type(stmt, i);
+            hasher.update([kind as u8]);
+            match kind {
+                SQLITE_INTEGER => hasher.update(sqlite3_column_int64(stmt, i).to_le_bytes()),
+                SQLITE_FLOAT => hasher.update(sqlite3_column_double(stmt, i).to_le_bytes()),
+                SQLITE_TEXT | SQLITE_BLOB => {
+                    let data = sqlite3_column_blob(stmt, i) as *const u8;
+                    let len = sqlite3_column_bytes(stmt, i) as usize;
+                    // Length prefix keeps ("ab", "c") and ("a", "bc") apart
+                    hasher.update((len as u64).to_le_bytes());
+                    if len > 0 {
+                        hasher.update(std::slice::from_raw_parts(data, len));
+                    }
+                }
+                _ => {}
+            }
+        }
+        let digest = hasher.finalize();
+        let mut prefix = [0u8; 16];
+        prefix.copy_from_slice(&digest[..16]);
+        sum = sum.wrapping_add(u128::from_be_bytes(prefix));
+    };
+    sqlite3_finalize(stmt);
+
+    if rc != SQLITE_DONE {
+        return Err(error(rc));
+    }
+    Ok(sum)
+}
@@ -0,0 +1,153 @@
+//! SQLite constants exported as `rapsqlite.SQLITE_*` module attributes, so
+//! authorizer callbacks and the like need no hard-coded integers. The Python
+//! package also groups them into the `ResultCode`, `AuthorizerResult`,
+//! `AuthorizerAction`, `OpenFlag` and `Limit` enums.
+
+use libsqlite3_sys as ffi;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+/// `(name, value)` pairs of the listed `libsqlite3_sys` constants.
+macro_rules! constants {
+    ($($name:ident),* $(,)?) => {
+        &[$((stringify!($name), ffi::$name as i64)),*]
+    };
+}
+
+/// A group of constants: enum name, prefix dropped from the member names, and
+/// the constants.
+type ConstantGroup = (&'static str, &'static str, &'static [(&'static str, i64)]);
+
+const GROUPS: &[ConstantGroup] = &[
+    (
+        "ResultCode",
+        "SQLITE_",
+        constants!(
+            SQLITE_OK,
+            SQLITE_ERROR,
+            SQLITE_INTERNAL,
+            SQLITE_PERM,
+            SQLITE_ABORT,
+            SQLITE_BUSY,
+            SQLITE_LOCKED,
+            SQLITE_NOMEM,
+            SQLITE_READONLY,
+            SQLITE_INTERRUPT,
+            SQLITE_IOERR,
+            SQLITE_CORRUPT,
+            SQLITE_NOTFOUND,
+            SQLITE_FULL,
+            SQLITE_CANTOPEN,
+            SQLITE_PROTOCOL,
+            SQLITE_EMPTY,
+            SQLITE_SCHEMA,
+            SQLITE_TOOBIG,
+            SQLITE_CONSTRAINT,
+            SQLITE_MISMATCH,
+            SQLITE_MISUSE,
+            SQLITE_NOLFS,
+            SQLITE_AUTH,
+            SQLITE_FORMAT,
+            SQLITE_RANGE,
+            SQLITE_NOTADB,
+            SQLITE_NOTICE,
+            SQLITE_WARNING,
+            SQLITE_ROW,
+            SQLITE_DONE,
+        ),
+    ),
+    (
+        "AuthorizerResult",
+        "SQLITE_",
+        constants!(SQLITE_OK, SQLITE_DENY, SQLITE_IGNORE),
+    ),
+    (
+        "AuthorizerAction",
+        "SQLITE_",
+        constants!(
+            SQLITE_CREATE_INDEX,
+            SQLITE_CREATE_TABLE,
+            SQLITE_CREATE_TEMP_INDEX,
+            SQLITE_CREATE_TEMP_TABLE,
+            SQLITE_CREATE_TEMP_TRIGGER,
+            SQLITE_CREATE_TEMP_VIEW,
+            SQLITE_CREATE_TRIGGER,
+            SQLITE_CREATE_VIEW,
+            SQLITE_DELETE,
+            SQLITE_DROP_INDEX,
+            SQLITE_DROP_TABLE,
+            SQLITE_DROP_TEMP_INDEX,
+            SQLITE_DROP_TEMP_TABLE,
+            SQLITE_DROP_TEMP_TRIGGER,
+            SQLITE_DROP_TEMP_VIEW,
+            SQLITE_DROP_TRIGGER,
+            SQLITE_DROP_VIEW,
+            SQLITE_INSERT,
+            SQLITE_PRAGMA,
+            SQLITE_READ,
+            SQLITE_SELECT,
+            SQLITE_TRANSACTION,
+            SQLITE_UPDATE,
+            SQLITE_ATTACH,
+            SQLITE_DETACH,
+            SQLITE_ALTER_TABLE,
+            SQLITE_REINDEX,
+            SQLITE_ANALYZE,
+            SQLITE_CREATE_VTABLE,
+            SQLITE_DROP_VTABLE,
+            SQLITE_FUNCTION,
+            SQLITE_SAVEPOINT,
+            SQLITE_RECURSIVE,
+        ),
+    ),
+    (
+        "OpenFlag",
+        "SQLITE_OPEN_",
+        constants!(
+            SQLITE_OPEN_READONLY,
+            SQLITE_OPEN_READWRITE,
+            SQLITE_OPEN_CREATE,
+            SQLITE_OPEN_URI,
+            SQLITE_OPEN_MEMORY,
+            SQLITE_OPEN_NOMUTEX,
+            SQLITE_OPEN_FULLMUTEX,
+            SQLITE_OPEN_SHAREDCACHE,
+            SQLITE_OPEN_PRIVATECACHE,
+            SQLITE_OPEN_NOFOLLOW,
+            SQLITE_OPEN_EXRESCODE,
+        ),
+    ),
+    (
+        "Limit",
+        "SQLITE_LIMIT_",
+        constants!(
+            SQLITE_LIMIT_LENGTH,
+            SQLITE_LIMIT_SQL_LENGTH,
+            SQLITE_LIMIT_COLUMN,
+            SQLITE_LIMIT_EXPR_DEPTH,
+            SQLITE_LIMIT_COMPOUND_SELECT,
+            SQLITE_LIMIT_VDBE_OP,
+            SQLITE_LIMIT_FUNCTION_ARG,
+            SQLITE_LIMIT_ATTACHED,
+            SQLITE_LIMIT_LIKE_PATTERN_LENGTH,
+            SQLITE_LIMIT_VARIABLE_NUMBER,
+            SQLITE_LIMIT_TRIGGER_DEPTH,
+            SQLITE_LIMIT_WORKER_THREADS,
+        ),
+    ),
+];
+
+/// Add every constant to `m`, plus `_constant_groups`, a list of
+/// `(enum name, prefix, {name: value})` the Python package builds its enums from.
+pub(crate) fn add_constants(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    let mut groups = Vec::with_capacity(GROUPS.len());
+    for &(enum_name, prefix, constants) in GROUPS {
+        let members = PyDict::new(m.py());
+        for &(name, value) in constants {
+            m.add(name, value)?;
+            members.set_item(name, value)?;
+        }
+        groups.push((enum_name, prefix, members));
+    }
+    m.add("_constant_groups", groups)
+}
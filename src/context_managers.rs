@@ -1,21 +1,46 @@
-//! Async context-manager helper types (`ExecuteContextManager`, `TransactionContextManager`).
+//! Async context-manager helper types (`ExecuteContextManager`, `TransactionContextManager`,
+//! `OptionsContextManager`, `DeferForeignKeysContextManager`, `SavepointContextManager`).
 
 #![allow(non_local_definitions)]
 
 use pyo3::prelude::*;
-use pyo3_async_runtimes::tokio::future_into_py;
+use pyo3::types::PyDict;
+use rapsqlite_core::authorizer::StatementPolicy;
+use rapsqlite_core::cache::StatementEffect;
+use rapsqlite_core::debug::TaskAudit;
+use rapsqlite_core::group_commit::{GroupCommitOptions, GroupCommitter};
+use rapsqlite_core::wal_hook::WalHook;
 use sqlx::pool::PoolConnection;
+use sqlx::sqlite::SqliteRow;
 use sqlx::SqlitePool;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, Mutex as StdMutex};
+use std::time::Duration;
 use tokio::sync::Mutex;
 
+use crate::callbacks::{with_udf_errors, UdfErrorSlot};
+use crate::converters::declared_types;
+use crate::errors::map_group_commit_error;
+use crate::lifecycle::EventHooks;
 use crate::pool::{
     ensure_callback_connection, execute_init_hook_if_needed, get_or_create_pool, has_callbacks,
     pool_acquisition_error,
 };
-use crate::query::{bind_and_execute, bind_and_execute_on_connection};
-use crate::types::{ProgressHandler, SqliteParam, TransactionState, UserFunctions};
-use crate::{map_sqlx_error, Connection, Cursor, OperationalError};
+use crate::query::{
+    bind_and_execute, bind_and_execute_on_connection, bind_and_execute_returning_on_connection,
+    returns_rows_on_connection,
+};
+use crate::result_cache;
+use crate::runtime::{self, DedicatedRuntime};
+use crate::statement_log::PendingLog;
+use crate::types::{
+    ExecuteResult, GroupCommitSlot, ProgressHandler, SqliteParam, TransactionState, UserFunctions,
+};
+use crate::utils::{
+    audit_transaction_begin, audit_transaction_end, has_returning, quote_identifier,
+};
+use crate::{map_sqlx_error, Connection, Cursor, IntegrityError, OperationalError};
 
 /// Execute context manager returned by `Connection::execute()`.
 /// Allows `async with db.execute(...)` pattern by being both awaitable and an async context manager.
@@ -24,13 +49,21 @@ pub(crate) struct ExecuteContextManager {
     pub(crate) cursor: Py<Cursor>,
     pub(crate) query: String,
     pub(crate) param_values: Vec<SqliteParam>,
-    pub(crate) is_select: bool,
+    /// Whether the statement returns rows to fetch (it then runs lazily, on the
+    /// first fetch); None when only preparing it can tell.
+    pub(crate) returns_rows: Option<bool>,
     // Connection state needed for execution
     pub(crate) path: String,
     pub(crate) pool: Arc<Mutex<Option<SqlitePool>>>,
     pub(crate) pragmas: Arc<StdMutex<Vec<(String, String)>>>,
     pub(crate) pool_size: Arc<StdMutex<Option<usize>>>,
     pub(crate) connection_timeout_secs: Arc<StdMutex<Option<u64>>>,
+    pub(crate) statement_policy: Option<Arc<StatementPolicy>>,
+    pub(crate) native_functions: bool,
+    pub(crate) wal_hook: Option<Arc<WalHook>>,
+    pub(crate) vfs: Option<String>,
+    pub(crate) event_hooks: Arc<EventHooks>,
+    pub(crate) runtime: Option<Arc<DedicatedRuntime>>,
     pub(crate) transaction_state: Arc<Mutex<TransactionState>>,
     pub(crate) transaction_connection: Arc<Mutex<Option<PoolConnection<sqlx::Sqlite>>>>,
     pub(crate) callback_connection: Arc<Mutex<Option<PoolConnection<sqlx::Sqlite>>>>,
@@ -39,77 +72,187 @@ pub(crate) struct ExecuteContextManager {
     pub(crate) trace_callback: Arc<StdMutex<Option<Py<PyAny>>>>,
     pub(crate) authorizer_callback: Arc<StdMutex<Option<Py<PyAny>>>>,
     pub(crate) progress_handler: ProgressHandler,
+    pub(crate) update_hook: Arc<StdMutex<Option<Py<PyAny>>>>,
+    pub(crate) udf_error: UdfErrorSlot,
     pub(crate) init_hook: Arc<StdMutex<Option<Py<PyAny>>>>,
     pub(crate) init_hook_called: Arc<StdMutex<bool>>,
     pub(crate) last_rowid: Arc<Mutex<i64>>,
     pub(crate) last_changes: Arc<Mutex<u64>>,
+    pub(crate) execute_result: ExecuteResult,
+    pub(crate) group_commit_window: Option<Duration>,
+    pub(crate) group_committer: GroupCommitSlot,
+    /// Taken by the first `__aenter__`/`__await__` that runs the statement.
+    pub(crate) statement_log: StdMutex<Option<PendingLog>>,
+    /// Set by the first `__anext__`, which runs the statement.
+    pub(crate) iterating: StdMutex<bool>,
     pub(crate) connection: Py<Connection>,
 }
 
 #[pymethods]
 impl ExecuteContextManager {
     /// Async context manager entry - executes query if non-SELECT, then returns the cursor.
+    /// For non-SELECT queries the cursor's `rowcount`/`lastrowid` are filled in.
     fn __aenter__(slf: PyRef<Self>) -> PyResult<Py<PyAny>> {
-        let slf: Py<Self> = slf.into();
-        Python::attach(|py| {
-            // Extract all fields before moving into async
-            let query = slf.borrow(py).query.clone();
-            let param_values = slf.borrow(py).param_values.clone();
-            let is_select = slf.borrow(py).is_select;
-            let path = slf.borrow(py).path.clone();
-            let pool = Arc::clone(&slf.borrow(py).pool);
-            let pragmas = Arc::clone(&slf.borrow(py).pragmas);
-            let pool_size = Arc::clone(&slf.borrow(py).pool_size);
-            let connection_timeout_secs = Arc::clone(&slf.borrow(py).connection_timeout_secs);
-            let transaction_state = Arc::clone(&slf.borrow(py).transaction_state);
-            let transaction_connection = Arc::clone(&slf.borrow(py).transaction_connection);
-            let callback_connection = Arc::clone(&slf.borrow(py).callback_connection);
-            let load_extension_enabled = Arc::clone(&slf.borrow(py).load_extension_enabled);
-            let user_functions = Arc::clone(&slf.borrow(py).user_functions);
-            let trace_callback = Arc::clone(&slf.borrow(py).trace_callback);
-            let authorizer_callback = Arc::clone(&slf.borrow(py).authorizer_callback);
-            let progress_handler = Arc::clone(&slf.borrow(py).progress_handler);
-            let init_hook = Arc::clone(&slf.borrow(py).init_hook);
-            let init_hook_called = Arc::clone(&slf.borrow(py).init_hook_called);
-            let last_rowid = Arc::clone(&slf.borrow(py).last_rowid);
-            let last_changes = Arc::clone(&slf.borrow(py).last_changes);
-            let connection = slf.borrow(py).connection.clone_ref(py);
-            let cursor = slf.borrow(py).cursor.clone_ref(py);
-            // Get cursor's results Arc to mark it as executed for non-SELECT queries
-            // Note: Python::with_gil is used here for sync result caching in async context.
-            // The deprecation warning is acceptable as this is a sync operation within async.
-            #[allow(deprecated)]
-            let _cursor_results = Python::with_gil(
-                |_py| -> PyResult<Arc<StdMutex<Option<Vec<sqlx::sqlite::SqliteRow>>>>> {
-                    // We can't easily get the results Arc from Py<Cursor>
-                    // Instead, we'll handle this in fetchall() by checking if it's non-SELECT
-                    // For now, we'll pass None and handle it in fetchall()
-                    Ok(Arc::new(StdMutex::new(None))) // Placeholder - won't be used
-                },
-            )
-            .unwrap_or_else(|_| Arc::new(StdMutex::new(None)));
+        let py = slf.py();
+        let dedicated_runtime = slf.runtime.clone();
+        let udf_error = Arc::clone(&slf.udf_error);
+        let future = Self::enter(slf.into(), py);
+        runtime::future_into_py(
+            py,
+            dedicated_runtime.as_deref(),
+            with_udf_errors(udf_error, future),
+        )
+        .map(|bound| bound.unbind())
+    }
 
+    /// Async context manager exit - does nothing (cursor cleanup is automatic).
+    fn __aexit__(
+        &self,
+        _exc_type: &Bound<'_, PyAny>,
+        _exc_val: &Bound<'_, PyAny>,
+        _exc_tb: &Bound<'_, PyAny>,
+    ) -> PyResult<Py<PyAny>> {
+        Python::attach(|py| {
             let future = async move {
-                // For non-SELECT queries, execute immediately when entering context
-                if !is_select {
-                    // Check if we're currently executing init_hook FIRST (before checking transaction state)
-                    // If we're inside init_hook, we should use pool connection, not transaction connection
-                    let hook_already_called = {
-                        let guard = init_hook_called.lock().unwrap();
-                        *guard
-                    };
+                Ok(false) // Return False to not suppress exceptions
+            };
+            runtime::future_into_py(py, self.runtime.as_deref(), future).map(|bound| bound.unbind())
+        })
+    }
 
-                    // Only check for Active state, not Starting (Starting means transaction is being set up,
-                    // and init_hook may need to execute queries using pool connection)
-                    // If we're inside init_hook execution, don't use transaction connection
-                    let in_transaction = if hook_already_called {
-                        // If we're inside init_hook, don't use transaction connection even if state is Starting
-                        false
-                    } else {
-                        let g = transaction_state.lock().await;
-                        *g == TransactionState::Active
-                    };
+    /// Make ExecuteContextManager awaitable - when awaited, calls __aenter__ and returns cursor.
+    /// This allows both `await conn.execute(...)` and `async with conn.execute(...)` patterns.
+    /// Python's __await__ must return an iterator. The Future from __aenter__ has __await__ which
+    /// returns an iterator. So we call the Future's __await__ to get the iterator.
+    fn __await__(slf: PyRef<Self>) -> PyResult<Py<PyAny>> {
+        // Call __aenter__ to get the Future, then call its __await__ to get the iterator
+        let slf: Py<Self> = slf.into();
+        // Note: Python::with_gil is used here for sync operation in async context.
+        // The deprecation warning is acceptable as this is a sync operation within async.
+        #[allow(deprecated)]
+        Python::with_gil(|py| {
+            let ctx_mgr = slf.bind(py);
+            // Call __aenter__ to get the Future
+            let future = ctx_mgr.call_method0("__aenter__")?;
+            // Call the Future's __await__ to get the iterator
+            future.call_method0("__await__").map(|bound| bound.unbind())
+        })
+    }
+
+    /// Async iterator entry point, for `async for row in conn.execute(...)`.
+    fn __aiter__(slf: PyRef<Self>) -> PyRef<Self> {
+        slf
+    }
+
+    /// Async iterator next item: the first call runs the statement like
+    /// `__aenter__`, then rows are fetched from the cursor. Statements that
+    /// return no rows stop the iteration once they have run.
+    fn __anext__(slf: PyRef<Self>) -> PyResult<Py<PyAny>> {
+        let py = slf.py();
+        let dedicated_runtime = slf.runtime.clone();
+        let udf_error = Arc::clone(&slf.udf_error);
+        let next_row = slf.cursor.borrow(py).next_row(true);
+        let first = !std::mem::replace(&mut *slf.iterating.lock().unwrap(), true);
+        let enter = first.then(|| Self::enter(slf.into(), py));
+        let future = async move {
+            if let Some(enter) = enter {
+                enter.await?;
+            }
+            next_row.await
+        };
+        runtime::future_into_py(
+            py,
+            dedicated_runtime.as_deref(),
+            with_udf_errors(udf_error, future),
+        )
+        .map(|bound| bound.unbind())
+    }
+}
+
+/// Rows a statement run for its effect returned (`INSERT ... RETURNING`),
+/// with their declared types.
+type ReturnedRows = Option<(Vec<SqliteRow>, Option<Vec<Option<String>>>)>;
+
+/// Run a statement for its effect on `conn`: (changes, last rowid), and its
+/// RETURNING rows when `returning`.
+async fn run_for_effect(
+    query: &str,
+    params: &[SqliteParam],
+    conn: &mut PoolConnection<sqlx::Sqlite>,
+    path: &str,
+    returning: bool,
+    detect_types: u32,
+) -> PyResult<(u64, i64, ReturnedRows)> {
+    if !returning {
+        let result = bind_and_execute_on_connection(query, params, conn, path).await?;
+        return Ok((result.rows_affected(), result.last_insert_rowid(), None));
+    }
+    let decltypes = declared_types(detect_types, conn, query).await?;
+    let (result, rows) =
+        bind_and_execute_returning_on_connection(query, params, conn, path).await?;
+    Ok((
+        result.rows_affected(),
+        result.last_insert_rowid(),
+        Some((rows, decltypes)),
+    ))
+}
+
+impl ExecuteContextManager {
+    /// Run the statement if it returns no rows (rows are fetched lazily
+    /// through the cursor), resolving to the cursor.
+    fn enter(
+        slf: Py<Self>,
+        py: Python<'_>,
+    ) -> impl Future<Output = PyResult<Py<Cursor>>> + Send + 'static {
+        // Extract all fields before moving into async
+        let query = slf.borrow(py).query.clone();
+        let param_values = slf.borrow(py).param_values.clone();
+        let returns_rows = slf.borrow(py).returns_rows;
+        let path = slf.borrow(py).path.clone();
+        let pool = Arc::clone(&slf.borrow(py).pool);
+        let pragmas = Arc::clone(&slf.borrow(py).pragmas);
+        let pool_size = Arc::clone(&slf.borrow(py).pool_size);
+        let connection_timeout_secs = Arc::clone(&slf.borrow(py).connection_timeout_secs);
+        let statement_policy = slf.borrow(py).statement_policy.clone();
+        let native_functions = slf.borrow(py).native_functions;
+        let wal_hook = slf.borrow(py).wal_hook.clone();
+        let vfs = slf.borrow(py).vfs.clone();
+        let event_hooks = Arc::clone(&slf.borrow(py).event_hooks);
+        let transaction_state = Arc::clone(&slf.borrow(py).transaction_state);
+        let transaction_connection = Arc::clone(&slf.borrow(py).transaction_connection);
+        let callback_connection = Arc::clone(&slf.borrow(py).callback_connection);
+        let load_extension_enabled = Arc::clone(&slf.borrow(py).load_extension_enabled);
+        let user_functions = Arc::clone(&slf.borrow(py).user_functions);
+        let trace_callback = Arc::clone(&slf.borrow(py).trace_callback);
+        let authorizer_callback = Arc::clone(&slf.borrow(py).authorizer_callback);
+        let progress_handler = Arc::clone(&slf.borrow(py).progress_handler);
+        let update_hook = Arc::clone(&slf.borrow(py).update_hook);
+        let init_hook = Arc::clone(&slf.borrow(py).init_hook);
+        let init_hook_called = Arc::clone(&slf.borrow(py).init_hook_called);
+        let last_rowid = Arc::clone(&slf.borrow(py).last_rowid);
+        let last_changes = Arc::clone(&slf.borrow(py).last_changes);
+        let execute_result = Arc::clone(&slf.borrow(py).execute_result);
+        let group_commit_window = slf.borrow(py).group_commit_window;
+        let group_committer = Arc::clone(&slf.borrow(py).group_committer);
+        let statement_log = slf.borrow(py).statement_log.lock().unwrap().take();
+        let connection = slf.borrow(py).connection.clone_ref(py);
+        let cursor = slf.borrow(py).cursor.clone_ref(py);
+        let detect_types = cursor.borrow(py).detect_types;
+        async move {
+            // Check if we're currently executing init_hook FIRST (before checking transaction state)
+            // If we're inside init_hook, we should use pool connection, not transaction connection
+            let hook_already_called = {
+                let guard = init_hook_called.lock().unwrap();
+                *guard
+            };
 
+            let is_select = match returns_rows {
+                Some(returns_rows) => returns_rows,
+                None => {
+                    // Prepare the statement where it will run, once the init hook (which
+                    // may create what it refers to) has run
+                    let in_transaction = !hook_already_called
+                        && *transaction_state.lock().await == TransactionState::Active;
                     if !in_transaction {
                         get_or_create_pool(
                             &path,
@@ -117,42 +260,33 @@ impl ExecuteContextManager {
                             &pragmas,
                             &pool_size,
                             &connection_timeout_secs,
+                            &statement_policy,
+                            native_functions,
+                            &wal_hook,
+                            &vfs,
+                            &event_hooks,
                         )
                         .await?;
                     }
-
-                    // Only call init_hook if not already called (avoid re-entry during init_hook execution)
-                    // This prevents deadlocks when init_hook calls conn.execute() which triggers __aenter__
+                    let connection = Python::attach(|py| connection.clone_ref(py));
                     execute_init_hook_if_needed(&init_hook, &init_hook_called, connection).await?;
 
-                    // Re-check transaction state after init_hook (state may have changed during hook execution)
-                    // Only check for Active state, not Starting (Starting means transaction is being set up)
-                    // Also, if we're inside init_hook execution, don't use transaction connection
-                    let in_transaction_after_hook = if hook_already_called {
-                        // If we were already inside init_hook when this execute() was called,
-                        // we should use pool connection, not transaction connection
-                        false
-                    } else {
-                        // Check transaction state - only use transaction connection if state is Active
-                        let g = transaction_state.lock().await;
-                        *g == TransactionState::Active
-                    };
-
-                    let has_callbacks_flag = has_callbacks(
+                    let in_transaction = !hook_already_called
+                        && *transaction_state.lock().await == TransactionState::Active;
+                    if in_transaction {
+                        let mut conn_guard = transaction_connection.lock().await;
+                        let conn = conn_guard.as_mut().ok_or_else(|| {
+                            OperationalError::new_err("Transaction connection not available")
+                        })?;
+                        returns_rows_on_connection(&query, conn, &path).await?
+                    } else if has_callbacks(
                         &load_extension_enabled,
                         &user_functions,
                         &trace_callback,
                         &authorizer_callback,
                         &progress_handler,
-                    );
-
-                    let result = if in_transaction_after_hook {
-                        let mut conn_guard = transaction_connection.lock().await;
-                        let conn = conn_guard.as_mut().ok_or_else(|| {
-                            OperationalError::new_err("Transaction connection not available")
-                        })?;
-                        bind_and_execute_on_connection(&query, &param_values, conn, &path).await?
-                    } else if has_callbacks_flag {
+                        &update_hook,
+                    ) {
                         ensure_callback_connection(
                             &path,
                             &pool,
@@ -160,125 +294,246 @@ impl ExecuteContextManager {
                             &pragmas,
                             &pool_size,
                             &connection_timeout_secs,
+                            &statement_policy,
+                            native_functions,
+                            &wal_hook,
+                            &vfs,
+                            &event_hooks,
                         )
                         .await?;
-
                         let mut conn_guard = callback_connection.lock().await;
                         let conn = conn_guard.as_mut().ok_or_else(|| {
                             OperationalError::new_err("Callback connection not available")
                         })?;
-                        bind_and_execute_on_connection(&query, &param_values, conn, &path).await?
+                        returns_rows_on_connection(&query, conn, &path).await?
                     } else {
-                        let pool_clone = get_or_create_pool(
+                        let pool = get_or_create_pool(
                             &path,
                             &pool,
                             &pragmas,
                             &pool_size,
                             &connection_timeout_secs,
+                            &statement_policy,
+                            native_functions,
+                            &wal_hook,
+                            &vfs,
+                            &event_hooks,
                         )
                         .await?;
-                        bind_and_execute(&query, &param_values, &pool_clone, &path).await?
-                    };
+                        crate::query::returns_rows(&query, &pool, &path).await?
+                    }
+                }
+            };
 
-                    let rowid = result.last_insert_rowid();
-                    let changes = result.rows_affected();
+            // For statements that don't return rows, execute immediately when entering context
+            if !is_select {
+                // Only check for Active state, not Starting (Starting means transaction is being set up,
+                // and init_hook may need to execute queries using pool connection)
+                // If we're inside init_hook execution, don't use transaction connection
+                let in_transaction = if hook_already_called {
+                    // If we're inside init_hook, don't use transaction connection even if state is Starting
+                    false
+                } else {
+                    let g = transaction_state.lock().await;
+                    *g == TransactionState::Active
+                };
 
-                    *last_rowid.lock().await = rowid;
-                    *last_changes.lock().await = changes;
+                if !in_transaction {
+                    get_or_create_pool(
+                        &path,
+                        &pool,
+                        &pragmas,
+                        &pool_size,
+                        &connection_timeout_secs,
+                        &statement_policy,
+                        native_functions,
+                        &wal_hook,
+                        &vfs,
+                        &event_hooks,
+                    )
+                    .await?;
+                }
 
-                    // Mark cursor results as cached (empty for non-SELECT) to prevent re-execution
-                    // The fetchall() method will check if it's non-SELECT and results are None,
-                    // and return empty results without executing. This is handled in fetchall().
+                // Only call init_hook if not already called (avoid re-entry during init_hook execution)
+                // This prevents deadlocks when init_hook calls conn.execute() which triggers __aenter__
+                execute_init_hook_if_needed(&init_hook, &init_hook_called, connection).await?;
+
+                // Re-check transaction state after init_hook (state may have changed during hook execution)
+                // Only check for Active state, not Starting (Starting means transaction is being set up)
+                // Also, if we're inside init_hook execution, don't use transaction connection
+                let in_transaction_after_hook = if hook_already_called {
+                    // If we were already inside init_hook when this execute() was called,
+                    // we should use pool connection, not transaction connection
+                    false
                 } else {
-                    // For SELECT queries, ensure pool exists for lazy execution
-                    // Only check for Active state, not Starting (Starting means transaction is being set up,
-                    // and init_hook may need to execute queries using pool connection)
-                    let in_transaction = {
-                        let g = transaction_state.lock().await;
-                        *g == TransactionState::Active
-                    };
-
-                    // Check if init_hook is already being executed (to avoid deadlock)
-                    // If init_hook is already called, we're likely inside an init_hook execution
-                    // In this case, we should skip pool operations to avoid deadlock with begin()/transaction()
-                    let hook_already_called = {
-                        let guard = init_hook_called.lock().unwrap();
-                        *guard
-                    };
+                    // Check transaction state - only use transaction connection if state is Active
+                    let g = transaction_state.lock().await;
+                    *g == TransactionState::Active
+                };
+
+                let has_callbacks_flag = has_callbacks(
+                    &load_extension_enabled,
+                    &user_functions,
+                    &trace_callback,
+                    &authorizer_callback,
+                    &progress_handler,
+                    &update_hook,
+                );
+
+                // RETURNING rows are buffered for the cursor, so its fetches
+                // never run the statement again
+                let returning = has_returning(&query);
+                let group_commit = group_commit_window.filter(|_| {
+                    !returning && StatementEffect::of(&query) == StatementEffect::Write
+                });
+
+                let (changes, rowid, returned) = if in_transaction_after_hook {
+                    let mut conn_guard = transaction_connection.lock().await;
+                    let conn = conn_guard.as_mut().ok_or_else(|| {
+                        OperationalError::new_err("Transaction connection not available")
+                    })?;
+                    run_for_effect(&query, &param_values, conn, &path, returning, detect_types)
+                        .await?
+                } else if has_callbacks_flag {
+                    ensure_callback_connection(
+                        &path,
+                        &pool,
+                        &callback_connection,
+                        &pragmas,
+                        &pool_size,
+                        &connection_timeout_secs,
+                        &statement_policy,
+                        native_functions,
+                        &wal_hook,
+                        &vfs,
+                        &event_hooks,
+                    )
+                    .await?;
 
-                    // Only get/create pool if not in transaction and hook not already called
-                    // If hook is already called, we're inside init_hook execution and should
-                    // skip pool operations to avoid deadlock (begin()/transaction() will handle pool)
-                    if !in_transaction && !hook_already_called {
-                        get_or_create_pool(
-                            &path,
-                            &pool,
-                            &pragmas,
-                            &pool_size,
-                            &connection_timeout_secs,
-                        )
-                        .await?;
+                    let mut conn_guard = callback_connection.lock().await;
+                    let conn = conn_guard.as_mut().ok_or_else(|| {
+                        OperationalError::new_err("Callback connection not available")
+                    })?;
+                    run_for_effect(&query, &param_values, conn, &path, returning, detect_types)
+                        .await?
+                } else {
+                    let pool_clone = get_or_create_pool(
+                        &path,
+                        &pool,
+                        &pragmas,
+                        &pool_size,
+                        &connection_timeout_secs,
+                        &statement_policy,
+                        native_functions,
+                        &wal_hook,
+                        &vfs,
+                        &event_hooks,
+                    )
+                    .await?;
+                    if let Some(window) = group_commit {
+                        let committer = group_committer
+                            .lock()
+                            .unwrap()
+                            .get_or_insert_with(|| {
+                                GroupCommitter::spawn(
+                                    SqlitePool::clone(&pool_clone),
+                                    GroupCommitOptions {
+                                        window,
+                                        ..GroupCommitOptions::default()
+                                    },
+                                )
+                            })
+                            .clone();
+                        let result = committer.execute(&query, &param_values).await;
+                        result_cache::note_statement(&path, &query);
+                        let (changes, rowid) =
+                            result.map_err(|e| map_group_commit_error(e, &path, &query))?;
+                        (changes, rowid, None)
+                    } else if returning {
+                        let mut conn = pool_clone.acquire_for(&path).await?;
+                        run_for_effect(&query, &param_values, &mut conn, &path, true, detect_types)
+                            .await?
+                    } else {
+                        let result =
+                            bind_and_execute(&query, &param_values, &pool_clone, &path).await?;
+                        (result.rows_affected(), result.last_insert_rowid(), None)
                     }
+                };
+                if let Some((rows, decltypes)) = returned {
+                    Python::attach(|py| {
+                        cursor
+                            .borrow(py)
+                            .buffer_rows(py, &rows, decltypes.as_deref())
+                    })?;
+                }
 
-                    // Only call init_hook if not already called (avoid re-entry during init_hook execution)
-                    // This prevents deadlocks when init_hook calls conn.execute() which triggers __aenter__
-                    // Note: If hook is already called, we skip calling it again (returns early)
-                    execute_init_hook_if_needed(&init_hook, &init_hook_called, connection).await?;
-
-                    // If hook was already called and we're not in transaction, we need to ensure pool exists
-                    // for the actual query execution (hook_already_called means we're inside hook execution,
-                    // but the query still needs a connection)
-                    if !in_transaction && hook_already_called {
-                        // Pool should already exist (created by begin()/transaction()), but ensure it does
-                        get_or_create_pool(
-                            &path,
-                            &pool,
-                            &pragmas,
-                            &pool_size,
-                            &connection_timeout_secs,
-                        )
-                        .await?;
-                    }
+                *last_rowid.lock().await = rowid;
+                *last_changes.lock().await = changes;
+                *execute_result.lock().unwrap() = Some((changes, rowid));
+                if let Some(log) = statement_log {
+                    log.finish(changes as i64);
                 }
 
-                Ok(cursor)
-            };
-            future_into_py(py, future).map(|bound| bound.unbind())
-        })
-    }
+                // The cursor's fetches see the recorded result and read the rows
+                // buffered above (none without RETURNING) instead of running it again
+            } else {
+                // For SELECT queries, ensure pool exists for lazy execution
+                // Only check for Active state, not Starting (Starting means transaction is being set up,
+                // and init_hook may need to execute queries using pool connection)
+                let in_transaction = {
+                    let g = transaction_state.lock().await;
+                    *g == TransactionState::Active
+                };
+
+                // If init_hook was already called, we're likely inside an init_hook execution
+                // In this case, we should skip pool operations to avoid deadlock with begin()/transaction()
+                // Only get/create pool if not in transaction and hook not already called
+                // If hook is already called, we're inside init_hook execution and should
+                // skip pool operations to avoid deadlock (begin()/transaction() will handle pool)
+                if !in_transaction && !hook_already_called {
+                    get_or_create_pool(
+                        &path,
+                        &pool,
+                        &pragmas,
+                        &pool_size,
+                        &connection_timeout_secs,
+                        &statement_policy,
+                        native_functions,
+                        &wal_hook,
+                        &vfs,
+                        &event_hooks,
+                    )
+                    .await?;
+                }
 
-    /// Async context manager exit - does nothing (cursor cleanup is automatic).
-    fn __aexit__(
-        &self,
-        _exc_type: &Bound<'_, PyAny>,
-        _exc_val: &Bound<'_, PyAny>,
-        _exc_tb: &Bound<'_, PyAny>,
-    ) -> PyResult<Py<PyAny>> {
-        Python::attach(|py| {
-            let future = async move {
-                Ok(false) // Return False to not suppress exceptions
-            };
-            future_into_py(py, future).map(|bound| bound.unbind())
-        })
-    }
+                // Only call init_hook if not already called (avoid re-entry during init_hook execution)
+                // This prevents deadlocks when init_hook calls conn.execute() which triggers __aenter__
+                // Note: If hook is already called, we skip calling it again (returns early)
+                execute_init_hook_if_needed(&init_hook, &init_hook_called, connection).await?;
+
+                // If hook was already called and we're not in transaction, we need to ensure pool exists
+                // for the actual query execution (hook_already_called means we're inside hook execution,
+                // but the query still needs a connection)
+                if !in_transaction && hook_already_called {
+                    // Pool should already exist (created by begin()/transaction()), but ensure it does
+                    get_or_create_pool(
+                        &path,
+                        &pool,
+                        &pragmas,
+                        &pool_size,
+                        &connection_timeout_secs,
+                        &statement_policy,
+                        native_functions,
+                        &wal_hook,
+                        &vfs,
+                        &event_hooks,
+                    )
+                    .await?;
+                }
+            }
 
-    /// Make ExecuteContextManager awaitable - when awaited, calls __aenter__ and returns cursor.
-    /// This allows both `await conn.execute(...)` and `async with conn.execute(...)` patterns.
-    /// Python's __await__ must return an iterator. The Future from __aenter__ has __await__ which
-    /// returns an iterator. So we call the Future's __await__ to get the iterator.
-    fn __await__(slf: PyRef<Self>) -> PyResult<Py<PyAny>> {
-        // Call __aenter__ to get the Future, then call its __await__ to get the iterator
-        let slf: Py<Self> = slf.into();
-        // Note: Python::with_gil is used here for sync operation in async context.
-        // The deprecation warning is acceptable as this is a sync operation within async.
-        #[allow(deprecated)]
-        Python::with_gil(|py| {
-            let ctx_mgr = slf.bind(py);
-            // Call __aenter__ to get the Future
-            let future = ctx_mgr.call_method0("__aenter__")?;
-            // Call the Future's __await__ to get the iterator
-            future.call_method0("__await__").map(|bound| bound.unbind())
-        })
+            Ok(cursor)
+        }
     }
 }
 
@@ -292,12 +547,19 @@ pub(crate) struct TransactionContextManager {
     pub(crate) pragmas: Arc<StdMutex<Vec<(String, String)>>>,
     pub(crate) pool_size: Arc<StdMutex<Option<usize>>>,
     pub(crate) connection_timeout_secs: Arc<StdMutex<Option<u64>>>,
+    pub(crate) statement_policy: Option<Arc<StatementPolicy>>,
+    pub(crate) native_functions: bool,
+    pub(crate) wal_hook: Option<Arc<WalHook>>,
+    pub(crate) vfs: Option<String>,
+    pub(crate) event_hooks: Arc<EventHooks>,
+    pub(crate) runtime: Option<Arc<DedicatedRuntime>>,
     pub(crate) transaction_state: Arc<Mutex<TransactionState>>,
     pub(crate) transaction_connection: Arc<Mutex<Option<PoolConnection<sqlx::Sqlite>>>>,
     pub(crate) connection: Py<Connection>,
     pub(crate) init_hook: Arc<StdMutex<Option<Py<PyAny>>>>, // Optional initialization hook
     pub(crate) init_hook_called: Arc<StdMutex<bool>>,       // Track if init_hook has been executed
     pub(crate) timeout: Arc<StdMutex<f64>>,                 // SQLite busy_timeout in seconds
+    pub(crate) task_audit: Option<Arc<StdMutex<TaskAudit>>>, // Some when debug=True
 }
 
 #[pymethods]
@@ -310,12 +572,19 @@ impl TransactionContextManager {
             let pragmas = Arc::clone(&slf.borrow(py).pragmas);
             let pool_size = Arc::clone(&slf.borrow(py).pool_size);
             let connection_timeout_secs = Arc::clone(&slf.borrow(py).connection_timeout_secs);
+            let statement_policy = slf.borrow(py).statement_policy.clone();
+            let native_functions = slf.borrow(py).native_functions;
+            let wal_hook = slf.borrow(py).wal_hook.clone();
+            let vfs = slf.borrow(py).vfs.clone();
+            let event_hooks = Arc::clone(&slf.borrow(py).event_hooks);
+            let dedicated_runtime = slf.borrow(py).runtime.clone();
             let transaction_state = Arc::clone(&slf.borrow(py).transaction_state);
             let transaction_connection = Arc::clone(&slf.borrow(py).transaction_connection);
             let connection = slf.borrow(py).connection.clone_ref(py);
             let init_hook = Arc::clone(&slf.borrow(py).init_hook);
             let init_hook_called = Arc::clone(&slf.borrow(py).init_hook_called);
             let timeout = Arc::clone(&slf.borrow(py).timeout);
+            let audit_owner = audit_transaction_begin(&slf.borrow(py).task_audit)?;
             let future = async move {
                 // Check if transaction is already active (before doing any work)
                 {
@@ -332,6 +601,11 @@ impl TransactionContextManager {
                         &pragmas,
                         &pool_size,
                         &connection_timeout_secs,
+                        &statement_policy,
+                        native_functions,
+                        &wal_hook,
+                        &vfs,
+                        &event_hooks,
                     )
                     .await?;
 
@@ -391,6 +665,9 @@ impl TransactionContextManager {
                         let mut trans_guard = transaction_state.lock().await;
                         *trans_guard = TransactionState::Active;
                     }
+                    if let Some((audit, task)) = audit_owner {
+                        audit.lock().unwrap().transaction_begun(task);
+                    }
                     Ok(connection.into())
                 }
                 .await;
@@ -405,7 +682,8 @@ impl TransactionContextManager {
 
                 result
             };
-            future_into_py(py, future).map(|bound| bound.unbind())
+            runtime::future_into_py(py, dedicated_runtime.as_deref(), future)
+                .map(|bound| bound.unbind())
         })
     }
 
@@ -418,7 +696,10 @@ impl TransactionContextManager {
         let slf: Py<Self> = slf.into();
         let rollback = exc_type.is_some();
         Python::attach(|py| {
+            let task_audit = slf.borrow(py).task_audit.clone();
+            audit_transaction_end(&task_audit, if rollback { "roll back" } else { "commit" })?;
             let path = slf.borrow(py).path.clone();
+            let dedicated_runtime = slf.borrow(py).runtime.clone();
             let transaction_state = Arc::clone(&slf.borrow(py).transaction_state);
             let transaction_connection = Arc::clone(&slf.borrow(py).transaction_connection);
             let future = async move {
@@ -435,11 +716,258 @@ impl TransactionContextManager {
                     .execute(&mut *conn)
                     .await
                     .map_err(|e| map_sqlx_error(e, &path, query))?;
+                crate::result_cache::note_statement(&path, query);
                 drop(conn);
                 *trans_guard = TransactionState::None;
+                if let Some(audit) = &task_audit {
+                    audit.lock().unwrap().transaction_ended();
+                }
                 Ok(())
             };
-            future_into_py(py, future).map(|bound| bound.unbind())
+            runtime::future_into_py(py, dedicated_runtime.as_deref(), future)
+                .map(|bound| bound.unbind())
         })
     }
 }
+
+/// Context manager returned by `Connection::options()`. `__aenter__` sets the
+/// connection's override context variable in the calling task's context and
+/// `__aexit__` resets it, so nothing is shared with other tasks.
+#[pyclass]
+pub(crate) struct OptionsContextManager {
+    pub(crate) overrides: Py<PyDict>,
+    pub(crate) variable: Py<PyAny>,
+    /// Tokens of the `ContextVar.set()` calls not yet undone, innermost last.
+    pub(crate) tokens: StdMutex<Vec<Py<PyAny>>>,
+    pub(crate) connection: Py<Connection>,
+}
+
+#[pymethods]
+impl OptionsContextManager {
+    fn __aenter__(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
+        let variable = self.variable.bind(py);
+        let merged = PyDict::new(py);
+        let current = variable.call_method1("get", (py.None(),))?;
+        if let Ok(current) = current.cast::<PyDict>() {
+            merged.update(current.as_mapping())?;
+        }
+        merged.update(self.overrides.bind(py).as_mapping())?;
+        let token = variable.call_method1("set", (merged,))?;
+        self.tokens.lock().unwrap().push(token.unbind());
+        let connection = self.connection.clone_ref(py);
+        runtime::future_into_py(py, None, async move { Ok(connection) }).map(|bound| bound.unbind())
+    }
+
+    fn __aexit__(
+        &self,
+        py: Python<'_>,
+        _exc_type: Option<&Bound<'_, PyAny>>,
+        _exc_val: Option<&Bound<'_, PyAny>>,
+        _exc_tb: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<Py<PyAny>> {
+        let token = self.tokens.lock().unwrap().pop();
+        if let Some(token) = token {
+            self.variable.call_method1(py, "reset", (token,))?;
+        }
+        runtime::future_into_py(py, None, async { Ok(false) }).map(|bound| bound.unbind())
+    }
+}
+
+/// Context manager returned by `Connection::defer_foreign_keys()`. Defers
+/// foreign key enforcement in the enclosing transaction and checks that no
+/// violation is left when the block exits.
+#[pyclass]
+pub(crate) struct DeferForeignKeysContextManager {
+    pub(crate) path: String,
+    pub(crate) runtime: Option<Arc<DedicatedRuntime>>,
+    pub(crate) transaction_state: Arc<Mutex<TransactionState>>,
+    pub(crate) transaction_connection: Arc<Mutex<Option<PoolConnection<sqlx::Sqlite>>>>,
+    pub(crate) connection: Py<Connection>,
+}
+
+/// Describe the rows of `PRAGMA foreign_key_check`, listing the first few.
+fn describe_violations(rows: &[sqlx::sqlite::SqliteRow]) -> String {
+    use sqlx::Row;
+    const SHOWN: usize = 5;
+    let mut shown: Vec<String> = rows
+        .iter()
+        .take(SHOWN)
+        .map(|row| {
+            let table: String = row.get(0);
+            let rowid: Option<i64> = row.get(1);
+            let parent: String = row.get(2);
+            match rowid {
+                Some(rowid) => format!("{table} rowid {rowid} -> {parent}"),
+                None => format!("{table} -> {parent}"),
+            }
+        })
+        .collect();
+    if rows.len() > SHOWN {
+        shown.push(format!("and {} more", rows.len() - SHOWN));
+    }
+    format!(
+        "FOREIGN KEY constraint failed: {} violation(s) remain at the end of \
+         defer_foreign_keys(): {}",
+        rows.len(),
+        shown.join(", ")
+    )
+}
+
+#[pymethods]
+impl DeferForeignKeysContextManager {
+    fn __aenter__(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
+        let path = self.path.clone();
+        let transaction_state = Arc::clone(&self.transaction_state);
+        let transaction_connection = Arc::clone(&self.transaction_connection);
+        let connection = self.connection.clone_ref(py);
+        let future = async move {
+            let trans_guard = transaction_state.lock().await;
+            if *trans_guard != TransactionState::Active {
+                return Err(OperationalError::new_err(
+                    "defer_foreign_keys() must be used inside a transaction",
+                ));
+            }
+            let mut conn_guard = transaction_connection.lock().await;
+            let conn = conn_guard
+                .as_mut()
+                .ok_or_else(|| OperationalError::new_err("Transaction connection not available"))?;
+            let query = "PRAGMA defer_foreign_keys = ON";
+            sqlx::query(query)
+                .execute(&mut **conn)
+                .await
+                .map_err(|e| map_sqlx_error(e, &path, query))?;
+            Ok(connection)
+        };
+        runtime::future_into_py(py, self.runtime.as_deref(), future).map(|bound| bound.unbind())
+    }
+
+    fn __aexit__(
+        &self,
+        py: Python<'_>,
+        exc_type: Option<&Bound<'_, PyAny>>,
+        _exc_val: Option<&Bound<'_, PyAny>>,
+        _exc_tb: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<Py<PyAny>> {
+        let check = exc_type.is_none();
+        let path = self.path.clone();
+        let transaction_connection = Arc::clone(&self.transaction_connection);
+        let future = async move {
+            let mut conn_guard = transaction_connection.lock().await;
+            // The transaction may have been ended inside the block
+            let Some(conn) = conn_guard.as_mut() else {
+                return Ok(false);
+            };
+            if check {
+                let query = "PRAGMA foreign_key_check";
+                let violations = sqlx::query(query)
+                    .fetch_all(&mut **conn)
+                    .await
+                    .map_err(|e| map_sqlx_error(e, &path, query))?;
+                if !violations.is_empty() {
+                    return Err(IntegrityError::new_err(describe_violations(&violations)));
+                }
+            }
+            let query = "PRAGMA defer_foreign_keys = OFF";
+            sqlx::query(query)
+                .execute(&mut **conn)
+                .await
+                .map_err(|e| map_sqlx_error(e, &path, query))?;
+            Ok(false)
+        };
+        runtime::future_into_py(py, self.runtime.as_deref(), future).map(|bound| bound.unbind())
+    }
+}
+
+/// Context manager returned by `Connection::savepoint()`. Runs its block in a
+/// savepoint of the enclosing transaction, released when the block exits and
+/// rolled back to when it raises.
+#[pyclass]
+pub(crate) struct SavepointContextManager {
+    pub(crate) path: String,
+    pub(crate) runtime: Option<Arc<DedicatedRuntime>>,
+    pub(crate) transaction_state: Arc<Mutex<TransactionState>>,
+    pub(crate) transaction_connection: Arc<Mutex<Option<PoolConnection<sqlx::Sqlite>>>>,
+    pub(crate) connection: Py<Connection>,
+    pub(crate) name: String,
+}
+
+/// Name of the next savepoint created without one.
+static NEXT_SAVEPOINT: AtomicU64 = AtomicU64::new(1);
+
+impl SavepointContextManager {
+    /// A name not used by any other unnamed savepoint.
+    pub(crate) fn generated_name() -> String {
+        format!(
+            "rapsqlite_sp_{}",
+            NEXT_SAVEPOINT.fetch_add(1, Ordering::Relaxed)
+        )
+    }
+}
+
+#[pymethods]
+impl SavepointContextManager {
+    /// Name of the savepoint.
+    #[getter]
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn __aenter__(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
+        let path = self.path.clone();
+        let transaction_state = Arc::clone(&self.transaction_state);
+        let transaction_connection = Arc::clone(&self.transaction_connection);
+        let connection = self.connection.clone_ref(py);
+        let query = format!("SAVEPOINT {}", quote_identifier(&self.name));
+        let future = async move {
+            let trans_guard = transaction_state.lock().await;
+            if *trans_guard != TransactionState::Active {
+                return Err(OperationalError::new_err(
+                    "savepoint() must be used inside a transaction",
+                ));
+            }
+            let mut conn_guard = transaction_connection.lock().await;
+            let conn = conn_guard
+                .as_mut()
+                .ok_or_else(|| OperationalError::new_err("Transaction connection not available"))?;
+            sqlx::query(&query)
+                .execute(&mut **conn)
+                .await
+                .map_err(|e| map_sqlx_error(e, &path, &query))?;
+            Ok(connection)
+        };
+        runtime::future_into_py(py, self.runtime.as_deref(), future).map(|bound| bound.unbind())
+    }
+
+    fn __aexit__(
+        &self,
+        py: Python<'_>,
+        exc_type: Option<&Bound<'_, PyAny>>,
+        _exc_val: Option<&Bound<'_, PyAny>>,
+        _exc_tb: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<Py<PyAny>> {
+        let rollback = exc_type.is_some();
+        let path = self.path.clone();
+        let transaction_connection = Arc::clone(&self.transaction_connection);
+        let name = quote_identifier(&self.name);
+        let future = async move {
+            let mut conn_guard = transaction_connection.lock().await;
+            // The transaction may have been ended inside the block
+            let Some(conn) = conn_guard.as_mut() else {
+                return Ok(false);
+            };
+            // ROLLBACK TO keeps the savepoint open; RELEASE then removes it
+            let mut queries = vec![format!("RELEASE SAVEPOINT {name}")];
+            if rollback {
+                queries.insert(0, format!("ROLLBACK TO SAVEPOINT {name}"));
+            }
+            for query in &queries {
+                sqlx::query(query)
+                    .execute(&mut **conn)
+                    .await
+                    .map_err(|e| map_sqlx_error(e, &path, query))?;
+            }
+            Ok(false)
+        };
+        runtime::future_into_py(py, self.runtime.as_deref(), future).map(|bound| bound.unbind())
+    }
+}
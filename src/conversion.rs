@@ -3,15 +3,49 @@
 use pyo3::prelude::*;
 use pyo3::types::{PyBytes, PyDict, PyFloat, PyInt, PyList, PyString, PyTuple};
 use sqlx::{Column, Row};
+use std::borrow::Cow;
+use std::ops::Range;
+use std::sync::Arc;
 
 // libsqlite3-sys for raw SQLite C API access
 use libsqlite3_sys::{sqlite3_context, sqlite3_value};
 
+use crate::converters::ColumnConverters;
+use crate::pointer::{self, Pointer};
+use crate::row::RapRow;
+use crate::types::{BindOptions, InvalidUtf8Policy, LargeIntPolicy, SqliteParam, SqliteParamExt};
+use crate::utils::is_large_int_literal;
+use crate::OperationalError;
+
+/// TEXT bytes decoded according to an [`InvalidUtf8Policy`].
+enum DecodedText<'a> {
+    Str(Cow<'a, str>),
+    Bytes(&'a [u8]),
+}
+
+/// Decode TEXT bytes, applying `policy` when they are not valid UTF-8
+/// (including surrogates encoded by other SQLite clients).
+fn decode_text(
+    bytes: &[u8],
+    policy: InvalidUtf8Policy,
+) -> Result<DecodedText<'_>, std::str::Utf8Error> {
+    match std::str::from_utf8(bytes) {
+        Ok(text) => Ok(DecodedText::Str(Cow::Borrowed(text))),
+        Err(e) => match policy {
+            InvalidUtf8Policy::Error => Err(e),
+            InvalidUtf8Policy::Replace => Ok(DecodedText::Str(String::from_utf8_lossy(bytes))),
+            InvalidUtf8Policy::Bytes => Ok(DecodedText::Bytes(bytes)),
+        },
+    }
+}
+
 /// Convert a SQLite C API value (sqlite3_value*) to Python object.
 /// This is used in callback trampolines for user-defined functions.
+/// Pointer values made from `rapsqlite.Pointer` convert to the wrapped object.
 pub(crate) unsafe fn sqlite_c_value_to_py<'py>(
     py: Python<'py>,
     value: *mut sqlite3_value,
+    invalid_utf8: InvalidUtf8Policy,
 ) -> PyResult<Py<PyAny>> {
     use libsqlite3_sys::{
         sqlite3_value_blob, sqlite3_value_bytes, sqlite3_value_double, sqlite3_value_int64,
@@ -19,6 +53,10 @@ pub(crate) unsafe fn sqlite_c_value_to_py<'py>(
         SQLITE_NULL, SQLITE_TEXT,
     };
 
+    if let Some(address) = pointer::value_object(value) {
+        return Ok(pointer::object_at(py, address));
+    }
+
     let value_type = sqlite3_value_type(value);
     match value_type {
         SQLITE_NULL => Ok(py.None()),
@@ -37,12 +75,15 @@ pub(crate) unsafe fn sqlite_c_value_to_py<'py>(
                 Ok(py.None())
             } else {
                 let text_slice = std::slice::from_raw_parts(text_ptr, text_len);
-                let text_str = std::str::from_utf8(text_slice).map_err(|e| {
+                let text = decode_text(text_slice, invalid_utf8).map_err(|e| {
                     PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
                         "Invalid UTF-8 in SQLite text value: {e}"
                     ))
                 })?;
-                Ok(PyString::new(py, text_str).into())
+                Ok(match text {
+                    DecodedText::Str(text) => PyString::new(py, &text).into(),
+                    DecodedText::Bytes(bytes) => PyBytes::new(py, bytes).into(),
+                })
             }
         }
         SQLITE_BLOB => {
@@ -59,282 +100,471 @@ pub(crate) unsafe fn sqlite_c_value_to_py<'py>(
     }
 }
 
-/// Convert a Python object to SQLite C API value and set it in the context.
-/// This is used to return values from user-defined functions.
-pub(crate) unsafe fn py_to_sqlite_c_result(
-    _py: Python<'_>,
-    ctx: *mut sqlite3_context,
-    result: &Bound<'_, PyAny>,
-) -> PyResult<()> {
+/// Convert a SQLite C API value (sqlite3_value*) to a `SqliteParam` without the GIL.
+/// This is used when callbacks are dispatched to the callback thread.
+pub(crate) unsafe fn sqlite_c_value_to_param(
+    value: *mut sqlite3_value,
+    invalid_utf8: InvalidUtf8Policy,
+) -> Result<SqliteParam, String> {
+    use libsqlite3_sys::{
+        sqlite3_value_blob, sqlite3_value_bytes, sqlite3_value_double, sqlite3_value_int64,
+        sqlite3_value_text, sqlite3_value_type, SQLITE_BLOB, SQLITE_FLOAT, SQLITE_INTEGER,
+        SQLITE_TEXT,
+    };
+
+    match sqlite3_value_type(value) {
+        SQLITE_INTEGER => Ok(SqliteParam::Int(sqlite3_value_int64(value))),
+        SQLITE_FLOAT => Ok(SqliteParam::Real(sqlite3_value_double(value))),
+        SQLITE_TEXT => {
+            let text_ptr = sqlite3_value_text(value);
+            if text_ptr.is_null() {
+                return Ok(SqliteParam::Null);
+            }
+            let text_len = sqlite3_value_bytes(value) as usize;
+            let text_slice = std::slice::from_raw_parts(text_ptr, text_len);
+            match decode_text(text_slice, invalid_utf8) {
+                Ok(DecodedText::Str(text)) => Ok(SqliteParam::Text(text.into_owned())),
+                Ok(DecodedText::Bytes(bytes)) => Ok(SqliteParam::Blob(bytes.to_vec())),
+                Err(e) => Err(format!("Invalid UTF-8 in SQLite text value: {e}")),
+            }
+        }
+        SQLITE_BLOB => {
+            let blob_ptr = sqlite3_value_blob(value);
+            if blob_ptr.is_null() {
+                return Ok(SqliteParam::Null);
+            }
+            let blob_len = sqlite3_value_bytes(value) as usize;
+            let blob_slice = std::slice::from_raw_parts(blob_ptr as *const u8, blob_len);
+            Ok(SqliteParam::Blob(blob_slice.to_vec()))
+        }
+        _ => Ok(SqliteParam::Null),
+    }
+}
+
+/// A user-defined function argument converted without the GIL, for the
+/// callback thread.
+pub(crate) enum UdfArg {
+    Value(SqliteParam),
+    /// Address of the object of a `rapsqlite.Pointer` value; SQLite keeps it
+    /// alive until the function returns.
+    Object(usize),
+}
+
+impl UdfArg {
+    /// Convert `value` like [`sqlite_c_value_to_param`], keeping pointer values.
+    pub(crate) unsafe fn from_c_value(
+        value: *mut sqlite3_value,
+        invalid_utf8: InvalidUtf8Policy,
+    ) -> Result<Self, String> {
+        match pointer::value_object(value) {
+            Some(address) => Ok(UdfArg::Object(address)),
+            None => sqlite_c_value_to_param(value, invalid_utf8).map(UdfArg::Value),
+        }
+    }
+
+    /// Convert to a Python object.
+    ///
+    /// # Safety
+    ///
+    /// For `Object`, the function call the argument was passed to must not
+    /// have returned yet.
+    pub(crate) unsafe fn to_py(&self, py: Python<'_>) -> Py<PyAny> {
+        match self {
+            UdfArg::Value(param) => param.to_py(py),
+            UdfArg::Object(address) => pointer::object_at(py, *address),
+        }
+    }
+}
+
+/// A user-defined function result: an SQL value, or the object of a
+/// `rapsqlite.Pointer` returned as a pointer value.
+pub(crate) enum UdfResult {
+    Value(SqliteParam),
+    Object(Py<PyAny>),
+}
+
+/// Set a `SqliteParam` as the result of a user-defined function.
+/// SQLITE_TRANSIENT makes SQLite copy text/blob data before this returns.
+pub(crate) unsafe fn sqlite_param_c_result(ctx: *mut sqlite3_context, value: &SqliteParam) {
     use libsqlite3_sys::{
         sqlite3_result_blob, sqlite3_result_double, sqlite3_result_int64, sqlite3_result_null,
-        sqlite3_result_text,
+        sqlite3_result_text, SQLITE_TRANSIENT,
     };
 
-    if result.is_none() {
-        sqlite3_result_null(ctx);
-        return Ok(());
+    match value {
+        SqliteParam::Null => sqlite3_result_null(ctx),
+        SqliteParam::Int(v) => sqlite3_result_int64(ctx, *v),
+        SqliteParam::Real(v) => sqlite3_result_double(ctx, *v),
+        SqliteParam::Text(v) => sqlite3_result_text(
+            ctx,
+            v.as_ptr() as *const std::ffi::c_char,
+            v.len() as i32,
+            SQLITE_TRANSIENT(),
+        ),
+        SqliteParam::Blob(v) => sqlite3_result_blob(
+            ctx,
+            v.as_ptr() as *const std::ffi::c_void,
+            v.len() as i32,
+            SQLITE_TRANSIENT(),
+        ),
     }
+}
 
-    // Try to extract as integer
-    if let Ok(int_val) = result.extract::<i64>() {
-        sqlite3_result_int64(ctx, int_val);
-        return Ok(());
+/// Set a [`UdfResult`] as the result of a user-defined function.
+pub(crate) unsafe fn udf_c_result(ctx: *mut sqlite3_context, result: UdfResult) {
+    match result {
+        UdfResult::Value(value) => sqlite_param_c_result(ctx, &value),
+        UdfResult::Object(obj) => pointer::result_object(ctx, obj),
     }
+}
 
-    // Try to extract as float
-    if let Ok(float_val) = result.extract::<f64>() {
-        sqlite3_result_double(ctx, float_val);
-        return Ok(());
+/// Convert a UDF return value to a [`UdfResult`].
+/// Values that cannot be stored raise, like the same values bound as parameters;
+/// ints outside the i64 range follow the connection's `large_int_policy`.
+pub(crate) fn py_to_udf_result(
+    result: &Bound<'_, PyAny>,
+    large_int_policy: LargeIntPolicy,
+) -> PyResult<UdfResult> {
+    let options = BindOptions {
+        large_int_policy,
+        ..BindOptions::default()
+    };
+    match Pointer::unwrap(result) {
+        Some(obj) => Ok(UdfResult::Object(obj)),
+        None => SqliteParam::from_py(result, &options).map(UdfResult::Value),
     }
+}
 
-    // Try to extract as string
-    if let Ok(str_val) = result.extract::<String>() {
-        let c_str = std::ffi::CString::new(str_val).map_err(|e| {
-            PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
-                "String contains null byte: {e}"
-            ))
-        })?;
-        let ptr = c_str.as_ptr();
-        let len = c_str.as_bytes().len() as i32;
-        // SQLite will copy the string, so we need to ensure it's valid
-        // Use SQLITE_TRANSIENT to let SQLite manage the memory
-        sqlite3_result_text(ctx, ptr, len, libsqlite3_sys::SQLITE_TRANSIENT());
-        // Keep c_str alive until after the call
-        std::mem::forget(c_str);
-        return Ok(());
+/// Connection settings controlling how fetched SQLite values are decoded.
+#[derive(Clone, Copy, Default)]
+pub(crate) struct DecodeOptions<'a> {
+    /// Callable(bytes) applied to declared TEXT columns, or None for default UTF-8.
+    pub(crate) text_factory: Option<&'a Py<PyAny>>,
+    /// Return NULL in REAL columns as `float("nan")` instead of None.
+    pub(crate) null_floats_as_nan: bool,
+    /// Return TEXT holding an integer literal outside the i64 range as a Python int.
+    pub(crate) decode_large_ints: bool,
+    /// Decode by declared column type instead of probing (strict typing mode).
+    pub(crate) strict: bool,
+    /// How TEXT that is not valid UTF-8 is returned.
+    pub(crate) invalid_utf8: InvalidUtf8Policy,
+    /// Converters selected by the connection's `detect_types`, if enabled.
+    pub(crate) converters: Option<&'a ColumnConverters>,
+}
+
+impl DecodeOptions<'_> {
+    /// Result column name, without a `[type]` suffix under `PARSE_COLNAMES`.
+    fn column_name<'s>(&'s self, row: &'s sqlx::sqlite::SqliteRow, col: usize) -> &'s str {
+        self.converters
+            .and_then(|c| c.name(col))
+            .unwrap_or_else(|| row.columns()[col].name())
     }
+}
 
-    // Try to extract as bytes
-    if let Ok(bytes_val) = result.extract::<Vec<u8>>() {
-        let len = bytes_val.len() as i32;
-        let ptr = bytes_val.as_ptr();
-        sqlite3_result_blob(
-            ctx,
-            ptr as *const std::ffi::c_void,
-            len,
-            libsqlite3_sys::SQLITE_TRANSIENT(),
-        );
-        // Keep bytes_val alive
-        std::mem::forget(bytes_val);
-        return Ok(());
+/// Convert decoded TEXT to Python, honouring `decode_large_ints`.
+fn text_to_py(py: Python<'_>, val: &str, options: &DecodeOptions<'_>) -> PyResult<Py<PyAny>> {
+    if options.decode_large_ints && is_large_int_literal(val) {
+        return Ok(py.get_type::<PyInt>().call1((val,))?.unbind());
     }
+    Ok(PyString::new(py, val).into())
+}
 
-    // Try PyBytes
-    if let Ok(py_bytes) = result.cast::<PyBytes>() {
-        let bytes = py_bytes.as_bytes();
-        let len = bytes.len() as i32;
-        let ptr = bytes.as_ptr();
-        sqlite3_result_blob(
-            ctx,
-            ptr as *const std::ffi::c_void,
-            len,
-            libsqlite3_sys::SQLITE_TRANSIENT(),
-        );
-        return Ok(());
+/// SQLite storage class of a fetched value, as reported by `sqlite3_column_type`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum StorageClass {
+    Null,
+    Integer,
+    Real,
+    Text,
+    Blob,
+}
+
+impl StorageClass {
+    fn of(row: &sqlx::sqlite::SqliteRow, col: usize) -> PyResult<Self> {
+        use sqlx::{TypeInfo, ValueRef};
+
+        let value = row.try_get_raw(col).map_err(decode_error)?;
+        if value.is_null() {
+            return Ok(StorageClass::Null);
+        }
+        Ok(match value.type_info().name() {
+            "INTEGER" => StorageClass::Integer,
+            "REAL" => StorageClass::Real,
+            "TEXT" => StorageClass::Text,
+            "BLOB" => StorageClass::Blob,
+            _ => StorageClass::Null,
+        })
     }
 
-    // Try PyString
-    if let Ok(py_str) = result.cast::<PyString>() {
-        let str_val = py_str.to_str()?;
-        let c_str = std::ffi::CString::new(str_val).map_err(|e| {
-            PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
-                "String contains null byte: {e}"
-            ))
-        })?;
-        let ptr = c_str.as_ptr();
-        let len = c_str.as_bytes().len() as i32;
-        sqlite3_result_text(ctx, ptr, len, libsqlite3_sys::SQLITE_TRANSIENT());
-        std::mem::forget(c_str);
-        return Ok(());
+    fn name(self) -> &'static str {
+        match self {
+            StorageClass::Null => "NULL",
+            StorageClass::Integer => "INTEGER",
+            StorageClass::Real => "REAL",
+            StorageClass::Text => "TEXT",
+            StorageClass::Blob => "BLOB",
+        }
     }
+}
 
-    // Default: return NULL
-    sqlite3_result_null(ctx);
-    Ok(())
+fn decode_error(e: sqlx::Error) -> PyErr {
+    crate::DatabaseError::new_err(e.to_string())
+}
+
+/// Strict typing mode: reject values whose storage class does not match the
+/// declared column type. Columns without one of the four core declared types
+/// (expressions, NUMERIC, BOOLEAN, ...) accept any storage class.
+fn check_strict(column: &str, declared: &str, storage: StorageClass) -> PyResult<()> {
+    let expected = match declared {
+        "INTEGER" => StorageClass::Integer,
+        "REAL" => StorageClass::Real,
+        "TEXT" => StorageClass::Text,
+        "BLOB" => StorageClass::Blob,
+        _ => return Ok(()),
+    };
+    // Integers are valid REAL values; SQLite only stores them compactly.
+    if storage == StorageClass::Null
+        || storage == expected
+        || (expected == StorageClass::Real && storage == StorageClass::Integer)
+    {
+        return Ok(());
+    }
+    Err(crate::DatabaseError::new_err(format!(
+        "strict mode: column '{column}' declared {declared} holds a {} value",
+        storage.name()
+    )))
 }
 
 /// Convert a SQLite value from sqlx Row to Python object.
+///
+/// Each value is decoded exactly once, driven by its storage class; the declared
+/// column type (sqlite3_column_decltype, resolved once per statement by sqlx) only
+/// selects `text_factory`, `null_floats_as_nan` and strict-mode behaviour.
 pub(crate) fn sqlite_value_to_py<'py>(
     py: Python<'py>,
     row: &sqlx::sqlite::SqliteRow,
     col: usize,
-    text_factory: Option<&Py<PyAny>>,
+    options: &DecodeOptions<'_>,
 ) -> PyResult<Py<PyAny>> {
     use sqlx::{Column, Row, TypeInfo};
 
-    // Apply `text_factory` only for declared TEXT columns (aiosqlite/sqlite3 semantics).
-    if let Some(tf) = text_factory {
-        let tf_bound = tf.bind(py);
-        if !tf_bound.is_none() {
-            let declared = row.columns()[col].type_info().name().to_ascii_uppercase();
-            if declared == "TEXT" {
-                // Prefer String decoding (sqlx already decodes TEXT as UTF-8).
-                // We pass bytes to the text_factory, matching sqlite3's callable(bytes)->Any behavior.
-                if let Ok(opt_val) = row.try_get::<Option<String>, _>(col) {
-                    return Ok(match opt_val {
-                        Some(val) => {
-                            let arg = PyBytes::new(py, val.as_bytes());
-                            tf_bound.call1((arg,))?.unbind()
-                        }
-                        None => py.None(),
-                    });
-                }
-            }
+    let column = &row.columns()[col];
+    let declared = column.type_info().name();
+    let storage = StorageClass::of(row, col)?;
+    if options.strict {
+        check_strict(column.name(), declared, storage)?;
+    }
+    if storage != StorageClass::Null {
+        if let Some(converter) = options.converters.and_then(|c| c.converter(col)) {
+            return Ok(converter
+                .bind(py)
+                .call1((converter_input(py, row, col, storage)?,))?
+                .unbind());
         }
     }
 
-    // Fallback path: use column type information to reduce redundant probes.
-    // Check declared type first, then fall back to type probing for robustness.
-    let type_name = row.columns()[col].type_info().name().to_ascii_uppercase();
-
-    // Try type-specific extraction based on declared type (more efficient)
-    match type_name.as_str() {
-        "INTEGER" | "INT" => {
-            if let Ok(opt_val) = row.try_get::<Option<i64>, _>(col) {
-                return Ok(match opt_val {
-                    Some(val) => PyInt::new(py, val).into(),
-                    None => py.None(),
-                });
-            }
+    match storage {
+        StorageClass::Null => Ok(if declared == "REAL" && options.null_floats_as_nan {
+            // SQLite stores NaN as NULL, so this also makes NaN round-trip
+            PyFloat::new(py, f64::NAN).into()
+        } else {
+            py.None()
+        }),
+        StorageClass::Integer if declared == "REAL" => {
+            let val = row.try_get_unchecked::<f64, _>(col).map_err(decode_error)?;
+            Ok(PyFloat::new(py, val).into())
         }
-        "REAL" | "FLOAT" | "DOUBLE" => {
-            if let Ok(opt_val) = row.try_get::<Option<f64>, _>(col) {
-                return Ok(match opt_val {
-                    Some(val) => PyFloat::new(py, val).into(),
-                    None => py.None(),
-                });
-            }
+        StorageClass::Integer => {
+            let val = row.try_get_unchecked::<i64, _>(col).map_err(decode_error)?;
+            Ok(PyInt::new(py, val).into())
         }
-        "TEXT" | "VARCHAR" | "CHAR" => {
-            if let Ok(opt_val) = row.try_get::<Option<String>, _>(col) {
-                return Ok(match opt_val {
-                    Some(val) => PyString::new(py, &val).into(),
-                    None => py.None(),
-                });
-            }
+        StorageClass::Real => {
+            let val = row.try_get_unchecked::<f64, _>(col).map_err(decode_error)?;
+            Ok(PyFloat::new(py, val).into())
         }
-        "BLOB" => {
-            if let Ok(opt_val) = row.try_get::<Option<Vec<u8>>, _>(col) {
-                return Ok(match opt_val {
-                    Some(val) => PyBytes::new(py, &val).into(),
-                    None => py.None(),
-                });
+        StorageClass::Text => {
+            let val = row
+                .try_get_unchecked::<&[u8], _>(col)
+                .map_err(decode_error)?;
+            // Apply `text_factory` only for declared TEXT columns (aiosqlite/sqlite3 semantics).
+            // We pass bytes to the text_factory, matching sqlite3's callable(bytes)->Any behavior,
+            // so it also sees TEXT that is not valid UTF-8.
+            if declared == "TEXT" {
+                if let Some(tf) = options.text_factory.map(|tf| tf.bind(py)) {
+                    if !tf.is_none() {
+                        return Ok(tf.call1((PyBytes::new(py, val),))?.unbind());
+                    }
+                }
+            }
+            match decode_text(val, options.invalid_utf8) {
+                Ok(DecodedText::Str(text)) => text_to_py(py, &text, options),
+                Ok(DecodedText::Bytes(bytes)) => Ok(PyBytes::new(py, bytes).into()),
+                Err(e) => Err(OperationalError::new_err(format!(
+                    "Could not decode column '{}' to UTF-8: {e}",
+                    options.column_name(row, col)
+                ))),
             }
         }
-        _ => {
-            // Unknown or NULL type - fall through to type probing below
+        StorageClass::Blob => {
+            let val = row
+                .try_get_unchecked::<&[u8], _>(col)
+                .map_err(decode_error)?;
+            Ok(PyBytes::new(py, val).into())
         }
     }
+}
 
-    // Type probing fallback (for NULL, unknown types, or when declared type doesn't match)
-    // This handles SQLite's dynamic typing where any column can store any type
-    if let Ok(opt_val) = row.try_get::<Option<i64>, _>(col) {
-        return Ok(match opt_val {
-            Some(val) => PyInt::new(py, val).into(),
-            None => py.None(),
-        });
-    }
-    if let Ok(opt_val) = row.try_get::<Option<f64>, _>(col) {
-        return Ok(match opt_val {
-            Some(val) => PyFloat::new(py, val).into(),
-            None => py.None(),
-        });
-    }
-    if let Ok(opt_val) = row.try_get::<Option<String>, _>(col) {
-        return Ok(match opt_val {
-            Some(val) => PyString::new(py, &val).into(),
-            None => py.None(),
-        });
-    }
-    if let Ok(opt_val) = row.try_get::<Option<Vec<u8>>, _>(col) {
-        return Ok(match opt_val {
-            Some(val) => PyBytes::new(py, &val).into(),
-            None => py.None(),
-        });
-    }
-
-    Ok(py.None())
+/// A non-NULL value as the bytes passed to a `register_converter()` callable;
+/// numbers are passed in their text form, as sqlite3 does.
+fn converter_input<'py>(
+    py: Python<'py>,
+    row: &sqlx::sqlite::SqliteRow,
+    col: usize,
+    storage: StorageClass,
+) -> PyResult<Bound<'py, PyBytes>> {
+    Ok(match storage {
+        StorageClass::Integer => {
+            let val = row.try_get_unchecked::<i64, _>(col).map_err(decode_error)?;
+            PyBytes::new(py, val.to_string().as_bytes())
+        }
+        StorageClass::Real => {
+            let val = row.try_get_unchecked::<f64, _>(col).map_err(decode_error)?;
+            PyBytes::new(py, format!("{val:?}").as_bytes())
+        }
+        _ => {
+            let val = row
+                .try_get_unchecked::<&[u8], _>(col)
+                .map_err(decode_error)?;
+            PyBytes::new(py, val)
+        }
+    })
 }
 
 /// Convert a SQLite row to Python list.
 pub(crate) fn row_to_py_list<'py>(
     py: Python<'py>,
     row: &sqlx::sqlite::SqliteRow,
-    text_factory: Option<&Py<PyAny>>,
+    options: &DecodeOptions<'_>,
 ) -> PyResult<Bound<'py, PyList>> {
     let list = PyList::empty(py);
     for i in 0..row.len() {
-        let val = sqlite_value_to_py(py, row, i, text_factory)?;
+        let val = sqlite_value_to_py(py, row, i, options)?;
         list.append(val)?;
     }
     Ok(list)
 }
 
-/// Convert a SQLite row to Python using row_factory. factory None => list;
-/// "dict" => dict (column names as keys); "tuple" => tuple; Row class => RapRow instance; else callable(row) => result.
-pub(crate) fn row_to_py_with_factory<'py>(
-    py: Python<'py>,
-    row: &sqlx::sqlite::SqliteRow,
-    factory: Option<&Py<PyAny>>,
-    text_factory: Option<&Py<PyAny>>,
-) -> PyResult<Bound<'py, PyAny>> {
-    let default = || row_to_py_list(py, row, text_factory).map(|l| l.into_any());
-    let Some(f) = factory else {
-        return default();
-    };
-    let f = f.bind(py);
-    if f.is_none() {
-        return default();
+/// How rows are converted to Python, resolved once per fetch from `row_factory`.
+///
+/// factory None => list; "dict" => dict (column names as keys); "tuple" => tuple;
+/// Row class => RapRow instance; else callable(row) => result.
+pub(crate) enum RowFactory<'py> {
+    List,
+    Dict,
+    Tuple,
+    Row,
+    Callable(Bound<'py, PyAny>),
+}
+
+impl<'py> RowFactory<'py> {
+    /// Resolve the conversion strategy for a `row_factory` value.
+    pub(crate) fn resolve(py: Python<'py>, factory: Option<&Py<PyAny>>) -> PyResult<Self> {
+        let Some(f) = factory else {
+            return Ok(RowFactory::List);
+        };
+        let f = f.bind(py);
+        if f.is_none() {
+            return Ok(RowFactory::List);
+        }
+        if let Ok(s) = f.cast::<PyString>() {
+            return Ok(match s.to_str()? {
+                "dict" => RowFactory::Dict,
+                "tuple" => RowFactory::Tuple,
+                _ => RowFactory::List,
+            });
+        }
+        if f.is(py.get_type::<RapRow>()) {
+            return Ok(RowFactory::Row);
+        }
+        Ok(RowFactory::Callable(f.clone()))
     }
-    if let Ok(s) = f.cast::<PyString>() {
-        let name = s.to_str()?;
-        return match name {
-            "dict" => {
+
+    /// Convert a SQLite row to Python.
+    pub(crate) fn convert(
+        &self,
+        py: Python<'py>,
+        row: &sqlx::sqlite::SqliteRow,
+        options: &DecodeOptions<'_>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        match self {
+            RowFactory::List => row_to_py_list(py, row, options).map(|l| l.into_any()),
+            RowFactory::Dict => {
                 let dict = PyDict::new(py);
                 for i in 0..row.len() {
-                    let col_name = row.columns()[i].name();
-                    let val = sqlite_value_to_py(py, row, i, text_factory)?;
+                    let col_name = options.column_name(row, i);
+                    let val = sqlite_value_to_py(py, row, i, options)?;
                     dict.set_item(col_name, val)?;
                 }
                 Ok(dict.into_any())
             }
-            "tuple" => {
-                let mut vals = Vec::new();
+            RowFactory::Tuple => {
+                let mut vals = Vec::with_capacity(row.len());
                 for i in 0..row.len() {
-                    vals.push(sqlite_value_to_py(py, row, i, text_factory)?);
+                    vals.push(sqlite_value_to_py(py, row, i, options)?);
                 }
                 let tuple = PyTuple::new(py, vals)?;
                 Ok(tuple.into_any())
             }
-            _ => default(),
-        };
-    }
-
-    // Check if factory is the RapRow class (Row class from Python)
-    // Try to get RapRow class from the module and compare types
-    if let Ok(rapsqlite_mod) = py.import("rapsqlite._rapsqlite") {
-        if let Ok(raprow_class) = rapsqlite_mod.getattr("RapRow") {
-            // Check if f is the same type as RapRow class by comparing type objects
-            let f_type = f.get_type();
-            let raprow_type = raprow_class.get_type();
-            if f_type.is(raprow_type) {
-                // Create RapRow with columns and values
-                let mut columns = Vec::new();
-                let mut values = Vec::new();
+            RowFactory::Row => {
+                let mut columns = Vec::with_capacity(row.len());
+                let mut values = Vec::with_capacity(row.len());
                 for i in 0..row.len() {
-                    columns.push(row.columns()[i].name().to_string());
-                    let val = sqlite_value_to_py(py, row, i, text_factory)?;
-                    values.push(val);
+                    columns.push(options.column_name(row, i).to_string());
+                    values.push(sqlite_value_to_py(py, row, i, options)?);
                 }
-                let raprow = raprow_class.call1((columns, values))?;
-                return Ok(raprow.into_any());
+                let raprow = Bound::new(py, RapRow::new(columns, values)?)?;
+                Ok(raprow.into_any())
+            }
+            RowFactory::Callable(f) => {
+                let list = row_to_py_list(py, row, options)?;
+                f.call1((list,))
             }
         }
     }
+}
 
-    // Fallback: treat as callable
-    let list = row_to_py_list(py, row, text_factory)?;
-    let result = f.call1((list,))?;
-    Ok(result)
+/// Rows converted per GIL acquisition by `convert_in_chunks`.
+pub(crate) const CONVERSION_CHUNK_ROWS: usize = 10_000;
+
+/// Build a Python list from `len` rows with `convert`, which appends the rows
+/// in the given index range. Results of more than `CONVERSION_CHUNK_ROWS` rows
+/// are converted one chunk at a time on Tokio's blocking pool, releasing the
+/// GIL and yielding between chunks so a huge fetch does not starve the event
+/// loop or the runtime's worker threads.
+pub(crate) async fn convert_in_chunks<F>(len: usize, convert: F) -> PyResult<Py<PyAny>>
+where
+    F: Fn(Python<'_>, Range<usize>, &Bound<'_, PyList>) -> PyResult<()> + Send + Sync + 'static,
+{
+    if len <= CONVERSION_CHUNK_ROWS {
+        return Python::attach(|py| {
+            let list = PyList::empty(py);
+            convert(py, 0..len, &list)?;
+            Ok(list.into_any().unbind())
+        });
+    }
+
+    let list = Python::attach(|py| PyList::empty(py).unbind());
+    let convert = Arc::new(convert);
+    for start in (0..len).step_by(CONVERSION_CHUNK_ROWS) {
+        let end = (start + CONVERSION_CHUNK_ROWS).min(len);
+        let convert = Arc::clone(&convert);
+        let chunk_list = Python::attach(|py| list.clone_ref(py));
+        tokio::task::spawn_blocking(move || {
+            Python::attach(|py| convert(py, start..end, chunk_list.bind(py)))
+        })
+        .await
+        .map_err(|e| OperationalError::new_err(format!("Row conversion failed: {e}")))??;
+        tokio::task::yield_now().await;
+    }
+    Ok(Python::attach(|py| list.into_bound(py).into_any().unbind()))
 }
@@ -0,0 +1,194 @@
+//! sqlite3-style converters (`register_converter()` and `detect_types`).
+//!
+//! A Connection opened with `detect_types` passes fetched values through the
+//! converter registered for the column's type: the first word of the declared
+//! type (`PARSE_DECLTYPES`) or a `[type]` suffix on the column name
+//! (`PARSE_COLNAMES`, e.g. `SELECT d AS "d [date]"`). sqlx only reports a
+//! declared type it recognises, so declared types are read with
+//! `sqlite3_column_decltype` on a separately prepared copy of the statement.
+
+use libsqlite3_sys::{
+    sqlite3, sqlite3_column_count, sqlite3_column_decltype, sqlite3_finalize, sqlite3_prepare_v2,
+    sqlite3_stmt, SQLITE_OK,
+};
+use pyo3::prelude::*;
+use sqlx::sqlite::{SqliteConnection, SqliteRow};
+use sqlx::{Column, Row, SqlitePool};
+use std::collections::HashMap;
+use std::ffi::CString;
+use std::sync::{Mutex as StdMutex, OnceLock};
+
+use crate::utils::cstr_from_i8_ptr;
+use crate::OperationalError;
+
+/// Convert values by the first word of their column's declared type.
+pub(crate) const PARSE_DECLTYPES: u32 = 1;
+/// Convert values by a `[type]` suffix on their column name.
+pub(crate) const PARSE_COLNAMES: u32 = 2;
+
+fn registry() -> &'static StdMutex<HashMap<String, Py<PyAny>>> {
+    static CONVERTERS: OnceLock<StdMutex<HashMap<String, Py<PyAny>>>> = OnceLock::new();
+    CONVERTERS.get_or_init(Default::default)
+}
+
+/// Register a callable converting SQLite values of type `typename`.
+///
+/// The converter receives the value as bytes (INTEGER and REAL values in their
+/// text form) and its result is returned in place of the value; NULL is never
+/// converted. Type names are matched case-insensitively. Converters apply to
+/// Connections opened with `detect_types=PARSE_DECLTYPES` and/or
+/// `PARSE_COLNAMES`.
+///
+/// # Example
+///
+/// .. code-block:: python
+///
+///     import datetime
+///     import rapsqlite
+///
+///     rapsqlite.register_converter(
+///         "date", lambda b: datetime.date.fromisoformat(b.decode())
+///     )
+///     async with rapsqlite.connect(
+///         "example.db", detect_types=rapsqlite.PARSE_DECLTYPES
+///     ) as conn:
+///         await conn.execute("CREATE TABLE t (d DATE)")
+#[pyfunction]
+pub(crate) fn register_converter(typename: &str, converter: Py<PyAny>) {
+    registry()
+        .lock()
+        .unwrap()
+        .insert(typename.to_uppercase(), converter);
+}
+
+/// Declared types of the columns `sql` returns, when `detect_types` includes
+/// `PARSE_DECLTYPES`. A statement that fails to prepare yields no declared
+/// types; running it reports the error.
+pub(crate) async fn declared_types(
+    detect_types: u32,
+    conn: &mut SqliteConnection,
+    sql: &str,
+) -> PyResult<Option<Vec<Option<String>>>> {
+    if detect_types & PARSE_DECLTYPES == 0 {
+        return Ok(None);
+    }
+    let Ok(sql) = CString::new(sql) else {
+        return Ok(Some(Vec::new()));
+    };
+    let mut handle = conn
+        .lock_handle()
+        .await
+        .map_err(|e| OperationalError::new_err(format!("Failed to lock handle: {e}")))?;
+    // SAFETY: the handle is locked until it is dropped at the end of this function.
+    Ok(Some(unsafe {
+        column_decltypes(handle.as_raw_handle().as_ptr(), &sql)
+    }))
+}
+
+/// `declared_types` on a connection acquired from `pool` for the lookup.
+pub(crate) async fn declared_types_in_pool(
+    detect_types: u32,
+    pool: &SqlitePool,
+    sql: &str,
+    path: &str,
+) -> PyResult<Option<Vec<Option<String>>>> {
+    if detect_types & PARSE_DECLTYPES == 0 {
+        return Ok(None);
+    }
+    let mut conn = pool
+        .acquire()
+        .await
+        .map_err(|e| crate::map_sqlx_error(e, path, sql))?;
+    declared_types(detect_types, &mut conn, sql).await
+}
+
+/// # Safety
+///
+/// `db` must meet the invariant described on `RawHandleRoute::run` in
+/// `connection.rs`.
+unsafe fn column_decltypes(db: *mut sqlite3, sql: &CString) -> Vec<Option<String>> {
+    let mut stmt: *mut sqlite3_stmt = std::ptr::null_mut();
+    let rc = sqlite3_prepare_v2(db, sql.as_ptr(), -1, &mut stmt, std::ptr::null_mut());
+    if rc != SQLITE_OK || stmt.is_null() {
+        return Vec::new();
+    }
+    let decltypes = (0..sqlite3_column_count(stmt))
+        .map(|i| {
+            let decl = sqlite3_column_decltype(stmt, i);
+            (!decl.is_null()).then(|| cstr_from_i8_ptr(decl).to_string_lossy().into_owned())
+        })
+        .collect();
+    sqlite3_finalize(stmt);
+    decltypes
+}
+
+/// Converters and result column names for one statement's columns.
+pub(crate) struct ColumnConverters {
+    converters: Vec<Option<Py<PyAny>>>,
+    names: Vec<String>,
+}
+
+impl ColumnConverters {
+    /// Look up the converter for each column of `row`'s statement. Returns None
+    /// when `detect_types` is 0.
+    pub(crate) fn resolve(
+        py: Python<'_>,
+        detect_types: u32,
+        row: &SqliteRow,
+        decltypes: Option<&[Option<String>]>,
+    ) -> Option<Self> {
+        if detect_types == 0 {
+            return None;
+        }
+        let registry = registry().lock().unwrap();
+        let lookup = |typename: &str| {
+            registry
+                .get(&typename.to_uppercase())
+                .map(|c| c.clone_ref(py))
+        };
+        let mut converters = Vec::with_capacity(row.len());
+        let mut names = Vec::with_capacity(row.len());
+        for (i, column) in row.columns().iter().enumerate() {
+            let name = column.name();
+            // As in sqlite3, a column name type takes precedence over the declared type
+            let from_name = (detect_types & PARSE_COLNAMES != 0)
+                .then(|| colname_type(name))
+                .flatten();
+            let converter = match from_name {
+                Some((stripped, typename)) => {
+                    names.push(stripped.to_string());
+                    lookup(typename)
+                }
+                None => {
+                    names.push(name.to_string());
+                    None
+                }
+            };
+            let converter = converter.or_else(|| {
+                let decltype = decltypes?.get(i)?.as_deref()?;
+                let end = decltype.find([' ', '(']).unwrap_or(decltype.len());
+                lookup(&decltype[..end])
+            });
+            converters.push(converter);
+        }
+        Some(ColumnConverters { converters, names })
+    }
+
+    /// Converter for column `col`, if one is registered for its type.
+    pub(crate) fn converter(&self, col: usize) -> Option<&Py<PyAny>> {
+        self.converters.get(col)?.as_ref()
+    }
+
+    /// Name of column `col`, without a `[type]` suffix under `PARSE_COLNAMES`.
+    pub(crate) fn name(&self, col: usize) -> Option<&str> {
+        self.names.get(col).map(String::as_str)
+    }
+}
+
+/// Split `"x [type]"` into the column name (`"x"`) and the type name.
+fn colname_type(name: &str) -> Option<(&str, &str)> {
+    let open = name.find('[')?;
+    let close = open + name[open..].find(']')?;
+    let stripped = name[..open].strip_suffix(' ').unwrap_or(&name[..open]);
+    Some((stripped, &name[open + 1..close]))
+}
@@ -2,32 +2,89 @@
 
 #![allow(non_local_definitions)]
 
+use pyo3::exceptions::{PyIndexError, PyStopAsyncIteration};
 use pyo3::prelude::*;
-use pyo3::types::PyList;
-use pyo3_async_runtimes::tokio::future_into_py;
+use pyo3::types::{PyDict, PyList, PyString};
+use pyo3::{PyTraverseError, PyVisit};
+use pyo3_async_runtimes::tokio::into_future;
+use rapsqlite_core::authorizer::StatementPolicy;
+use rapsqlite_core::wal_hook::WalHook;
 use sqlx::pool::PoolConnection;
+use sqlx::sqlite::SqliteRow;
 use sqlx::SqlitePool;
-use std::sync::{Arc, Mutex as StdMutex};
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex as StdMutex, Weak};
 use tokio::sync::Mutex;
 
-use crate::conversion::row_to_py_with_factory;
-use crate::parameters::{process_named_parameters, process_positional_parameters};
+use crate::callbacks::{with_udf_errors, UdfErrorSlot};
+use crate::context_managers::ExecuteContextManager;
+use crate::conversion::{DecodeOptions, RowFactory};
+use crate::converters::{declared_types, declared_types_in_pool, ColumnConverters};
+use crate::lifecycle::EventHooks;
+use crate::parameters::{materialize_parameters, process_parameters};
 use crate::pool::{ensure_callback_connection, get_or_create_pool, has_callbacks};
-use crate::query::{
-    bind_and_execute, bind_and_execute_on_connection, bind_and_fetch_all,
-    bind_and_fetch_all_on_connection,
+use crate::query::{bind_and_fetch_all, bind_and_fetch_all_on_connection};
+use crate::runtime::{self, DedicatedRuntime};
+use crate::template::Query;
+use crate::types::{
+    BindOptions, ExecuteResult, InvalidUtf8Policy, LargeIntPolicy, ProgressHandler, SqliteParam,
+    TransactionState, UserFunctions,
 };
-use crate::types::{ProgressHandler, SqliteParam, TransactionState, UserFunctions};
-use crate::utils::is_select_query;
-use crate::{Connection, OperationalError, ProgrammingError};
+use crate::utils::returns_rows_hint;
+use crate::{Connection, OperationalError, ProgrammingError, ValueError};
+
+/// A cursor's buffered result set.
+pub(crate) type CursorResults = Arc<BufferedRows>;
+type BufferedRows = StdMutex<Option<Vec<Py<PyAny>>>>;
+
+/// The cursors a Connection handed out, so `close()` can invalidate them.
+#[derive(Default)]
+pub(crate) struct CursorRegistry(StdMutex<Vec<(Weak<AtomicBool>, Weak<BufferedRows>)>>);
+
+impl CursorRegistry {
+    /// Register a cursor's result set; the flag returned is set once it is closed.
+    pub(crate) fn track(&self, results: &CursorResults) -> Arc<AtomicBool> {
+        let closed = Arc::new(AtomicBool::new(false));
+        let mut cursors = self.0.lock().unwrap();
+        cursors.retain(|(closed, _)| closed.strong_count() > 0);
+        cursors.push((Arc::downgrade(&closed), Arc::downgrade(results)));
+        closed
+    }
+
+    /// Number of cursors still alive and not closed.
+    pub(crate) fn open_count(&self) -> usize {
+        self.0
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|(closed, _)| closed.upgrade())
+            .filter(|closed| !closed.load(Ordering::Acquire))
+            .count()
+    }
+
+    /// Close every cursor still alive, dropping its buffered rows.
+    pub(crate) fn close_all(&self) {
+        for (closed, results) in self.0.lock().unwrap().drain(..) {
+            if let Some(closed) = closed.upgrade() {
+                closed.store(true, Ordering::Release);
+            }
+            if let Some(results) = results.upgrade() {
+                results.lock().unwrap().take();
+            }
+        }
+    }
+}
 
 /// Cursor for executing queries.
-#[pyclass]
+#[pyclass(weakref)]
 pub(crate) struct Cursor {
     pub(crate) connection: Py<Connection>,
     pub(crate) query: String,
-    pub(crate) results: Arc<StdMutex<Option<Vec<Py<PyAny>>>>>,
+    pub(crate) results: CursorResults,
+    pub(crate) closed: Arc<AtomicBool>, // Set by close() and by the connection's close()
     pub(crate) current_index: Arc<StdMutex<usize>>,
+    pub(crate) arraysize: usize, // Rows fetchmany() returns when no size is given
     pub(crate) parameters: Arc<StdMutex<Option<Py<PyAny>>>>,
     // Store processed query and parameters to avoid re-processing (fixes parameterized query issue)
     pub(crate) processed_query: Option<String>,
@@ -37,8 +94,21 @@ pub(crate) struct Cursor {
     pub(crate) connection_pragmas: Arc<StdMutex<Vec<(String, String)>>>, // Reference to connection's pragmas
     pub(crate) pool_size: Arc<StdMutex<Option<usize>>>,
     pub(crate) connection_timeout_secs: Arc<StdMutex<Option<u64>>>,
+    pub(crate) statement_policy: Option<Arc<StatementPolicy>>,
+    pub(crate) native_functions: bool,
+    pub(crate) wal_hook: Option<Arc<WalHook>>,
+    pub(crate) vfs: Option<String>,
+    pub(crate) event_hooks: Arc<EventHooks>,
+    pub(crate) runtime: Option<Arc<DedicatedRuntime>>,
     pub(crate) row_factory: Arc<StdMutex<Option<Py<PyAny>>>>, // Connection's row_factory at cursor creation
     pub(crate) text_factory: Arc<StdMutex<Option<Py<PyAny>>>>, // Connection's text_factory
+    pub(crate) null_floats_as_nan: Arc<StdMutex<bool>>,       // Connection's null_floats_as_nan
+    pub(crate) large_int_policy: Arc<StdMutex<LargeIntPolicy>>, // Connection's large_int_policy
+    pub(crate) decode_large_ints: Arc<StdMutex<bool>>,        // Connection's decode_large_ints
+    pub(crate) invalid_utf8: Arc<StdMutex<InvalidUtf8Policy>>, // Connection's invalid_utf8_policy
+    pub(crate) strict: bool,                                  // Connection's strict typing mode
+    pub(crate) detect_types: u32,                             // Connection's detect_types
+    pub(crate) execute_result: ExecuteResult, // Set when the cursor's statement runs
     // Transaction and callback state for proper connection priority
     pub(crate) transaction_state: Arc<Mutex<TransactionState>>,
     pub(crate) transaction_connection: Arc<Mutex<Option<PoolConnection<sqlx::Sqlite>>>>,
@@ -48,20 +118,35 @@ pub(crate) struct Cursor {
     pub(crate) trace_callback: Arc<StdMutex<Option<Py<PyAny>>>>,
     pub(crate) authorizer_callback: Arc<StdMutex<Option<Py<PyAny>>>>,
     pub(crate) progress_handler: ProgressHandler,
+    pub(crate) update_hook: Arc<StdMutex<Option<Py<PyAny>>>>,
+    pub(crate) udf_error: UdfErrorSlot,
 }
 
 #[pymethods]
 impl Cursor {
-    /// Execute a SQL query.
-    #[pyo3(signature = (query, parameters = None))]
-    fn execute(
+    /// Execute a SQL query. `multi=True` runs every statement of a query holding
+    /// several, as for `Connection.execute()`.
+    #[pyo3(signature = (query, parameters = None, *, multi = false))]
+    fn execute<'py>(
         &mut self,
-        query: String,
-        parameters: Option<&Bound<'_, PyAny>>,
+        py: Python<'py>,
+        query: Query,
+        parameters: Option<&Bound<'py, PyAny>>,
+        multi: bool,
     ) -> PyResult<Py<PyAny>> {
+        self.check_open()?;
+        let (query, parameters) = self
+            .connection
+            .borrow(py)
+            .statement(py, query, parameters)?;
         self.query = query.clone();
 
-        // Store parameters
+        // Store parameters (a generator is collected first: they are processed again on fetch)
+        let parameters = parameters
+            .as_ref()
+            .map(materialize_parameters)
+            .transpose()?;
+        let parameters = parameters.as_ref();
         let params_for_storage = parameters.map(|params| params.clone().unbind());
 
         {
@@ -76,16 +161,101 @@ impl Cursor {
         }
 
         // Execute via Connection (no results cached yet - will fetch on first fetch call)
-        Python::attach(|py| {
-            let conn = self.connection.bind(py);
-            if let Some(params) = parameters {
-                conn.call_method1("execute", (query, params))
-                    .map(|bound| bound.unbind())
-            } else {
-                conn.call_method1("execute", (query, py.None()))
-                    .map(|bound| bound.unbind())
+        let conn = self.connection.bind(py);
+        let kwargs = PyDict::new(py);
+        kwargs.set_item("multi", multi)?;
+        let ctx_mgr = match parameters {
+            Some(params) => conn.call_method("execute", (query, params), Some(&kwargs))?,
+            None => conn.call_method("execute", (query, py.None()), Some(&kwargs))?,
+        };
+        // Share the result the statement records when it runs, for rowcount and
+        // to tell fetches whether it already ran
+        if let Ok(ctx_mgr) = ctx_mgr.cast::<ExecuteContextManager>() {
+            let ctx_mgr = ctx_mgr.borrow();
+            let executed = ctx_mgr.cursor.borrow(py);
+            self.execute_result = Arc::clone(&executed.execute_result);
+            // Rows a statement returned when it ran (`INSERT ... RETURNING`)
+            self.results = Arc::clone(&executed.results);
+        }
+        Ok(ctx_mgr.unbind())
+    }
+
+    /// Number of rows changed by the statement this cursor executed (DB-API `rowcount`).
+    ///
+    /// -1 for SELECT statements and before the statement has run.
+    #[getter(rowcount)]
+    fn rowcount(&self) -> PyResult<i64> {
+        let guard = self.execute_result.lock().unwrap();
+        Ok(guard.map_or(-1, |(changes, _)| changes as i64))
+    }
+
+    /// Alias of `rowcount`, so `(await conn.execute(...)).rows_affected` needs no
+    /// second await to `changes()`.
+    #[getter(rows_affected)]
+    fn rows_affected(&self) -> PyResult<i64> {
+        self.rowcount()
+    }
+
+    /// Row ID of the last row inserted by the statement this cursor executed.
+    ///
+    /// None for SELECT statements and before the statement has run.
+    #[getter(lastrowid)]
+    fn lastrowid(&self) -> PyResult<Option<i64>> {
+        let guard = self.execute_result.lock().unwrap();
+        Ok(guard.map(|(_, rowid)| rowid))
+    }
+
+    /// Number of rows `fetchmany()` returns when called without a size
+    /// (DB-API `arraysize`, default 1).
+    #[getter(arraysize)]
+    fn arraysize(&self) -> usize {
+        self.arraysize
+    }
+
+    /// Set the number of rows `fetchmany()` returns when called without a size.
+    #[setter(arraysize)]
+    fn set_arraysize(&mut self, value: i64) -> PyResult<()> {
+        if value < 1 {
+            return Err(ValueError::new_err("arraysize must be >= 1"));
+        }
+        self.arraysize = value as usize;
+        Ok(())
+    }
+
+    /// One-line summary: connection path, query (shortened past 60
+    /// characters) and how many buffered rows were fetched.
+    fn __str__(&self, py: Python<'_>) -> PyResult<String> {
+        let path = self.connection.borrow(py).path_repr(py)?;
+        let query = match self.query.char_indices().nth(60) {
+            Some((end, _)) => format!("{}...", &self.query[..end]),
+            None => self.query.clone(),
+        };
+        let state = if self.closed.load(Ordering::Acquire) {
+            "closed".to_string()
+        } else {
+            match self.results.lock().unwrap().as_ref() {
+                Some(rows) => format!(
+                    "rows={} position={}",
+                    rows.len(),
+                    *self.current_index.lock().unwrap()
+                ),
+                None => "pending".to_string(),
             }
-        })
+        };
+        Ok(format!(
+            "<Cursor path={path} query={} {state}>",
+            PyString::new(py, &query).repr()?
+        ))
+    }
+
+    fn __repr__(&self, py: Python<'_>) -> PyResult<String> {
+        self.__str__(py)
+    }
+
+    /// Report the connection to the garbage collector; the factories and
+    /// callbacks the cursor shares with it are reported by the connection.
+    fn __traverse__(&self, visit: PyVisit<'_>) -> Result<(), PyTraverseError> {
+        visit.call(&self.connection)
     }
 
     /// Execute a SQL query multiple times.
@@ -94,6 +264,7 @@ impl Cursor {
         query: String,
         parameters: Vec<Vec<Py<PyAny>>>,
     ) -> PyResult<Py<PyAny>> {
+        self.check_open()?;
         self.query = query.clone();
         Python::attach(|py| {
             let conn = self.connection.bind(py);
@@ -104,24 +275,59 @@ impl Cursor {
 
     /// Fetch one row.
     fn fetchone(&self) -> PyResult<Py<PyAny>> {
+        self.check_open()?;
+        if self.query.is_empty() {
+            return Err(ProgrammingError::new_err("No query executed"));
+        }
+
+        let udf_error = Arc::clone(&self.udf_error);
+        let future = self.next_row(false);
+        Python::attach(|py| {
+            runtime::future_into_py(
+                py,
+                self.runtime.as_deref(),
+                with_udf_errors(udf_error, future),
+            )
+            .map(|bound| bound.unbind())
+        })
+    }
+
+    /// Fetch all remaining rows.
+    ///
+    /// The statement runs once, on the first fetch (or when the cursor was
+    /// executed, if it returns no rows); later fetches read the rows buffered
+    /// then, from the cursor's position. With `copy=False` this resolves to an
+    /// iterator over the buffered rows instead of a new list; it advances the
+    /// cursor's position as it yields them.
+    #[pyo3(signature = (*, copy = true))]
+    fn fetchall(&self, copy: bool) -> PyResult<Py<PyAny>> {
+        self.check_open()?;
         if self.query.is_empty() {
             return Err(ProgrammingError::new_err("No query executed"));
         }
 
-        // Use same logic as fetchmany but return single element or None
         let query = self.query.clone();
         let results = Arc::clone(&self.results);
         let current_index = Arc::clone(&self.current_index);
         let parameters = Arc::clone(&self.parameters);
-        let stored_proc_query_fetchone = self.processed_query.clone();
-        let stored_proc_params_fetchone = self.processed_params.clone();
         let path = self.connection_path.clone();
         let pool = Arc::clone(&self.connection_pool);
         let pragmas = Arc::clone(&self.connection_pragmas);
         let pool_size = Arc::clone(&self.pool_size);
         let connection_timeout_secs = Arc::clone(&self.connection_timeout_secs);
+        let statement_policy = self.statement_policy.clone();
+        let native_functions = self.native_functions;
+        let wal_hook = self.wal_hook.clone();
+        let vfs = self.vfs.clone();
+        let event_hooks = Arc::clone(&self.event_hooks);
         let row_factory = Arc::clone(&self.row_factory);
         let text_factory = Arc::clone(&self.text_factory);
+        let null_floats_as_nan = Arc::clone(&self.null_floats_as_nan);
+        let large_int_policy = Arc::clone(&self.large_int_policy);
+        let decode_large_ints = Arc::clone(&self.decode_large_ints);
+        let invalid_utf8 = Arc::clone(&self.invalid_utf8);
+        let strict = self.strict;
+        let detect_types = self.detect_types;
         let transaction_state = Arc::clone(&self.transaction_state);
         let transaction_connection = Arc::clone(&self.transaction_connection);
         let callback_connection = Arc::clone(&self.callback_connection);
@@ -130,48 +336,61 @@ impl Cursor {
         let trace_callback = Arc::clone(&self.trace_callback);
         let authorizer_callback = Arc::clone(&self.authorizer_callback);
         let progress_handler = Arc::clone(&self.progress_handler);
+        let update_hook = Arc::clone(&self.update_hook);
+        let udf_error = Arc::clone(&self.udf_error);
+
+        let execute_result = Arc::clone(&self.execute_result);
+
+        // Clone processed parameters for use in async future
+        let stored_proc_query = self.processed_query.clone();
+        let stored_proc_params = self.processed_params.clone();
+        let rows = (!copy).then(|| self.buffered_rows());
 
         Python::attach(|py| {
             let future = async move {
-                // Ensure results are cached (same logic as fetchmany)
+                settle_executed(&query, &execute_result, &results);
+                // Ensure results are cached
                 let needs_fetch = {
                     let results_guard = results.lock().unwrap();
                     results_guard.is_none()
                 };
 
                 if needs_fetch {
-                    // Use stored processed parameters if available, otherwise re-process
+                    // SELECT query - fetch results
+                    // Use stored processed parameters if available (from Connection.execute()), otherwise re-process
                     let (processed_query, processed_params) =
                         if let (Some(proc_query), Some(proc_params)) =
-                            (stored_proc_query_fetchone, stored_proc_params_fetchone)
+                            (stored_proc_query, stored_proc_params)
                         {
+                            // Use stored processed parameters - these are already in the correct order
+                            // and match the ? placeholders in processed_query
+                            // The parameters were processed by process_named_parameters() which ensures
+                            // correct order matching the ? placeholders
                             (proc_query, proc_params)
                         } else {
-                            // Fallback: re-process parameters
+                            // Fallback: re-process parameters (for cursors created via cursor() method)
                             // Note: Python::with_gil is used here for sync parameter processing in async context.
                             // The deprecation warning is acceptable as this is a sync operation within async.
                             #[allow(deprecated)]
                             Python::with_gil(|py| -> PyResult<(String, Vec<SqliteParam>)> {
+                                let bind_options = BindOptions {
+                                    large_int_policy: *large_int_policy.lock().unwrap(),
+                                    strict,
+                                };
                                 let params_guard = parameters.lock().unwrap();
                                 if let Some(ref params_py) = *params_guard {
-                                    let params_bound = params_py.bind(py);
-                                    if let Ok(dict) = params_bound.cast::<pyo3::types::PyDict>() {
-                                        let (proc_query, param_values) =
-                                            process_named_parameters(&query, dict)?;
-                                        return Ok((proc_query, param_values));
-                                    }
-                                    if let Ok(list) = params_bound.cast::<PyList>() {
-                                        let param_values = process_positional_parameters(list)?;
-                                        return Ok((query.clone(), param_values));
-                                    }
-                                    let param = SqliteParam::from_py(params_bound)?;
-                                    return Ok((query.clone(), vec![param]));
+                                    return process_parameters(
+                                        query.clone(),
+                                        params_py.bind(py),
+                                        &bind_options,
+                                    );
                                 }
                                 Ok((query.clone(), Vec::new()))
                             })?
                         };
 
                     // Priority: transaction > callbacks > pool
+                    // Check transaction state - must check inside async future to get current state
                     let in_transaction = {
                         let g = transaction_state.lock().await;
                         g.is_active()
@@ -183,13 +402,19 @@ impl Cursor {
                         &trace_callback,
                         &authorizer_callback,
                         &progress_handler,
+                        &update_hook,
                     );
 
+                    let decltypes;
                     let rows = if in_transaction {
+                        // Use transaction connection - it's already acquired and holds the transaction
                         let mut conn_guard = transaction_connection.lock().await;
-                        let conn = conn_guard.as_mut().ok_or_else(|| {
-                            OperationalError::new_err("Transaction connection not available")
-                        })?;
+                        let conn = conn_guard
+                                .as_mut()
+                                .ok_or_else(|| OperationalError::new_err(
+                                    "Transaction is active but transaction_connection is None. This indicates a bug in transaction management.".to_string()
+                                ))?;
+                        decltypes = declared_types(detect_types, conn, &processed_query).await?;
                         bind_and_fetch_all_on_connection(
                             &processed_query,
                             &processed_params,
@@ -206,6 +431,11 @@ impl Cursor {
                             &pragmas,
                             &pool_size,
                             &connection_timeout_secs,
+                            &statement_policy,
+                            native_functions,
+                            &wal_hook,
+                            &vfs,
+                            &event_hooks,
                         )
                         .await?;
 
@@ -214,6 +444,7 @@ impl Cursor {
                         let conn = conn_guard.as_mut().ok_or_else(|| {
                             OperationalError::new_err("Callback connection not available")
                         })?;
+                        decltypes = declared_types(detect_types, conn, &processed_query).await?;
                         bind_and_fetch_all_on_connection(
                             &processed_query,
                             &processed_params,
@@ -228,6 +459,18 @@ impl Cursor {
                             &pragmas,
                             &pool_size,
                             &connection_timeout_secs,
+                            &statement_policy,
+                            native_functions,
+                            &wal_hook,
+                            &vfs,
+                            &event_hooks,
+                        )
+                        .await?;
+                        decltypes = declared_types_in_pool(
+                            detect_types,
+                            &pool_clone,
+                            &processed_query,
+                            &path,
                         )
                         .await?;
                         bind_and_fetch_all(&processed_query, &processed_params, &pool_clone, &path)
@@ -239,12 +482,22 @@ impl Cursor {
                     #[allow(deprecated)]
                     let cached_results = Python::with_gil(|py| -> PyResult<Vec<Py<PyAny>>> {
                         let guard = row_factory.lock().unwrap();
-                        let factory_opt = guard.as_ref();
+                        let factory = RowFactory::resolve(py, guard.as_ref())?;
                         let tf_guard = text_factory.lock().unwrap();
-                        let tf_opt = tf_guard.as_ref();
+                        let converters = rows.first().and_then(|row| {
+                            ColumnConverters::resolve(py, detect_types, row, decltypes.as_deref())
+                        });
+                        let decode = DecodeOptions {
+                            text_factory: tf_guard.as_ref(),
+                            null_floats_as_nan: *null_floats_as_nan.lock().unwrap(),
+                            decode_large_ints: *decode_large_ints.lock().unwrap(),
+                            invalid_utf8: *invalid_utf8.lock().unwrap(),
+                            strict,
+                            converters: converters.as_ref(),
+                        };
                         let mut vec = Vec::new();
                         for row in rows.iter() {
-                            let out = row_to_py_with_factory(py, row, factory_opt, tf_opt)?;
+                            let out = factory.convert(py, row, &decode)?;
                             vec.push(out.unbind());
                         }
                         Ok(vec)
@@ -254,259 +507,6 @@ impl Cursor {
                         let mut results_guard = results.lock().unwrap();
                         *results_guard = Some(cached_results);
                     }
-                    *current_index.lock().unwrap() = 0;
-                }
-
-                // Get first element or None
-                // Note: Python::with_gil is used here for sync context manager creation before async execution.
-                // The deprecation warning is acceptable as this is a sync context.
-                #[allow(deprecated)]
-                // Note: Python::with_gil is used here for sync result conversion in async context.
-                // The deprecation warning is acceptable as this is a sync operation within async.
-                #[allow(deprecated)]
-                Python::with_gil(|py| -> PyResult<Py<PyAny>> {
-                    let mut index_guard = current_index.lock().unwrap();
-                    let results_guard = results.lock().unwrap();
-
-                    let Some(ref results_vec) = *results_guard else {
-                        return Ok(py.None());
-                    };
-
-                    if *index_guard >= results_vec.len() {
-                        return Ok(py.None());
-                    }
-
-                    let row = results_vec[*index_guard].clone_ref(py);
-                    *index_guard += 1;
-
-                    Ok(row)
-                })
-            };
-            future_into_py(py, future).map(|bound| bound.unbind())
-        })
-    }
-
-    /// Fetch all rows.
-    fn fetchall(&self) -> PyResult<Py<PyAny>> {
-        if self.query.is_empty() {
-            return Err(ProgrammingError::new_err("No query executed"));
-        }
-
-        let query = self.query.clone();
-        let results = Arc::clone(&self.results);
-        let current_index = Arc::clone(&self.current_index);
-        let parameters = Arc::clone(&self.parameters);
-        let path = self.connection_path.clone();
-        let pool = Arc::clone(&self.connection_pool);
-        let pragmas = Arc::clone(&self.connection_pragmas);
-        let pool_size = Arc::clone(&self.pool_size);
-        let connection_timeout_secs = Arc::clone(&self.connection_timeout_secs);
-        let row_factory = Arc::clone(&self.row_factory);
-        let text_factory = Arc::clone(&self.text_factory);
-        let transaction_state = Arc::clone(&self.transaction_state);
-        let transaction_connection = Arc::clone(&self.transaction_connection);
-        let callback_connection = Arc::clone(&self.callback_connection);
-        let load_extension_enabled = Arc::clone(&self.load_extension_enabled);
-        let user_functions = Arc::clone(&self.user_functions);
-        let trace_callback = Arc::clone(&self.trace_callback);
-        let authorizer_callback = Arc::clone(&self.authorizer_callback);
-        let progress_handler = Arc::clone(&self.progress_handler);
-
-        // Check if this is a non-SELECT query - if so and results are None,
-        // it means the query was already executed in __aenter__ and we should
-        // just return empty results without executing again
-        let is_select = is_select_query(&query);
-        if !is_select {
-            let results_guard = results.lock().unwrap();
-            if results_guard.is_none() {
-                // Non-SELECT query already executed in __aenter__, return empty results
-                // Mark as executed to prevent re-execution
-                drop(results_guard);
-                *results.lock().unwrap() = Some(Vec::new());
-                // Return an awaitable future (empty list for non-SELECT queries)
-                return Python::attach(|py| -> PyResult<Py<PyAny>> {
-                    let future = async move {
-                        Python::attach(|py| -> PyResult<Py<PyAny>> { Ok(PyList::empty(py).into()) })
-                    };
-                    future_into_py(py, future).map(|bound| bound.unbind())
-                });
-            }
-        }
-
-        // Clone processed parameters for use in async future
-        let stored_proc_query = self.processed_query.clone();
-        let stored_proc_params = self.processed_params.clone();
-
-        Python::attach(|py| {
-            let future = async move {
-                // Ensure results are cached
-                let needs_fetch = {
-                    let results_guard = results.lock().unwrap();
-                    results_guard.is_none()
-                };
-
-                if needs_fetch {
-                    // Check if this is a non-SELECT query - if so, it was already executed in __aenter__
-                    // and we should just mark results as empty
-                    let is_select = is_select_query(&query);
-                    if !is_select {
-                        // Non-SELECT query already executed in __aenter__, mark as empty
-                        let mut results_guard = results.lock().unwrap();
-                        *results_guard = Some(Vec::new());
-                    } else {
-                        // SELECT query - fetch results
-                        // Use stored processed parameters if available (from Connection.execute()), otherwise re-process
-                        let (processed_query, processed_params) = if let (
-                            Some(proc_query),
-                            Some(proc_params),
-                        ) =
-                            (stored_proc_query, stored_proc_params)
-                        {
-                            // Use stored processed parameters - these are already in the correct order
-                            // and match the ? placeholders in processed_query
-                            // The parameters were processed by process_named_parameters() which ensures
-                            // correct order matching the ? placeholders
-                            (proc_query, proc_params)
-                        } else {
-                            // Fallback: re-process parameters (for cursors created via cursor() method)
-                            // Note: Python::with_gil is used here for sync parameter processing in async context.
-                            // The deprecation warning is acceptable as this is a sync operation within async.
-                            #[allow(deprecated)]
-                            Python::with_gil(|py| -> PyResult<(String, Vec<SqliteParam>)> {
-                                let params_guard = parameters.lock().unwrap();
-                                if let Some(ref params_py) = *params_guard {
-                                    let params_bound = params_py.bind(py);
-
-                                    // Try dict first (named parameters)
-                                    if let Ok(dict) = params_bound.cast::<pyo3::types::PyDict>() {
-                                        let (proc_query, param_values) =
-                                            process_named_parameters(&query, dict)?;
-                                        // Verify we got parameters if query contains named placeholders
-                                        if param_values.is_empty()
-                                            && (query.contains(':')
-                                                || query.contains('@')
-                                                || query.contains('$'))
-                                        {
-                                            return Err(ProgrammingError::new_err(
-                                                format!("Named parameters found in query but none extracted. Query: '{query}', Processed: '{proc_query}'")
-                                            ));
-                                        }
-                                        // Additional verification: check if processed query has ? placeholders
-                                        if !proc_query.contains('?') && query.contains(':') {
-                                            return Err(ProgrammingError::new_err(
-                                                format!("Query had named parameters but processed query has no ? placeholders. Original: '{query}', Processed: '{proc_query}'")
-                                            ));
-                                        }
-                                        return Ok((proc_query, param_values));
-                                    }
-
-                                    // Try list (positional parameters)
-                                    if let Ok(list) = params_bound.cast::<PyList>() {
-                                        let param_values = process_positional_parameters(list)?;
-                                        return Ok((query.clone(), param_values));
-                                    }
-
-                                    // Single value
-                                    let param = SqliteParam::from_py(params_bound)?;
-                                    return Ok((query.clone(), vec![param]));
-                                }
-                                Ok((query.clone(), Vec::new()))
-                            })?
-                        };
-
-                        // Priority: transaction > callbacks > pool
-                        // Check transaction state - must check inside async future to get current state
-                        let in_transaction = {
-                            let g = transaction_state.lock().await;
-                            g.is_active()
-                        };
-
-                        let has_callbacks_flag = has_callbacks(
-                            &load_extension_enabled,
-                            &user_functions,
-                            &trace_callback,
-                            &authorizer_callback,
-                            &progress_handler,
-                        );
-
-                        let rows = if in_transaction {
-                            // Use transaction connection - it's already acquired and holds the transaction
-                            let mut conn_guard = transaction_connection.lock().await;
-                            let conn = conn_guard
-                                .as_mut()
-                                .ok_or_else(|| OperationalError::new_err(
-                                    "Transaction is active but transaction_connection is None. This indicates a bug in transaction management.".to_string()
-                                ))?;
-                            bind_and_fetch_all_on_connection(
-                                &processed_query,
-                                &processed_params,
-                                conn,
-                                &path,
-                            )
-                            .await?
-                        } else if has_callbacks_flag {
-                            // Ensure callback connection exists
-                            ensure_callback_connection(
-                                &path,
-                                &pool,
-                                &callback_connection,
-                                &pragmas,
-                                &pool_size,
-                                &connection_timeout_secs,
-                            )
-                            .await?;
-
-                            // Use callback connection
-                            let mut conn_guard = callback_connection.lock().await;
-                            let conn = conn_guard.as_mut().ok_or_else(|| {
-                                OperationalError::new_err("Callback connection not available")
-                            })?;
-                            bind_and_fetch_all_on_connection(
-                                &processed_query,
-                                &processed_params,
-                                conn,
-                                &path,
-                            )
-                            .await?
-                        } else {
-                            let pool_clone = get_or_create_pool(
-                                &path,
-                                &pool,
-                                &pragmas,
-                                &pool_size,
-                                &connection_timeout_secs,
-                            )
-                            .await?;
-                            bind_and_fetch_all(
-                                &processed_query,
-                                &processed_params,
-                                &pool_clone,
-                                &path,
-                            )
-                            .await?
-                        };
-
-                        // Note: Python::with_gil is used here for sync result caching in async context.
-                        // The deprecation warning is acceptable as this is a sync operation within async.
-                        #[allow(deprecated)]
-                        let cached_results = Python::with_gil(|py| -> PyResult<Vec<Py<PyAny>>> {
-                            let guard = row_factory.lock().unwrap();
-                            let factory_opt = guard.as_ref();
-                            let tf_guard = text_factory.lock().unwrap();
-                            let tf_opt = tf_guard.as_ref();
-                            let mut vec = Vec::new();
-                            for row in rows.iter() {
-                                let out = row_to_py_with_factory(py, row, factory_opt, tf_opt)?;
-                                vec.push(out.unbind());
-                            }
-                            Ok(vec)
-                        })?;
-
-                        {
-                            let mut results_guard = results.lock().unwrap();
-                            *results_guard = Some(cached_results);
-                        }
-                    }
                 }
 
                 // Return all remaining results
@@ -517,6 +517,9 @@ impl Cursor {
                 // The deprecation warning is acceptable as this is a sync operation within async.
                 #[allow(deprecated)]
                 Python::with_gil(|py| -> PyResult<Py<PyAny>> {
+                    if let Some(rows) = rows {
+                        return Ok(Py::new(py, rows)?.into_any());
+                    }
                     let mut index_guard = current_index.lock().unwrap();
                     let results_guard = results.lock().unwrap();
 
@@ -536,17 +539,26 @@ impl Cursor {
                     Ok(result_list.into())
                 })
             };
-            future_into_py(py, future).map(|bound| bound.unbind())
+            runtime::future_into_py(
+                py,
+                self.runtime.as_deref(),
+                with_udf_errors(udf_error, future),
+            )
+            .map(|bound| bound.unbind())
         })
     }
 
     /// Fetch many rows with size-based slicing.
     /// Phase 2.2: Properly implements size parameter by fetching all results,
-    /// caching them, and returning appropriate slices.
+    /// caching them, and returning appropriate slices. Without a size, returns
+    /// `arraysize` rows.
+    #[pyo3(signature = (size = None))]
     fn fetchmany(&self, size: Option<usize>) -> PyResult<Py<PyAny>> {
+        self.check_open()?;
         if self.query.is_empty() {
             return Err(ProgrammingError::new_err("No query executed"));
         }
+        let fetch_size = size.unwrap_or(self.arraysize);
 
         let query = self.query.clone();
         let results = Arc::clone(&self.results);
@@ -559,8 +571,19 @@ impl Cursor {
         let pragmas = Arc::clone(&self.connection_pragmas);
         let pool_size = Arc::clone(&self.pool_size);
         let connection_timeout_secs = Arc::clone(&self.connection_timeout_secs);
+        let statement_policy = self.statement_policy.clone();
+        let native_functions = self.native_functions;
+        let wal_hook = self.wal_hook.clone();
+        let vfs = self.vfs.clone();
+        let event_hooks = Arc::clone(&self.event_hooks);
         let row_factory = Arc::clone(&self.row_factory);
         let text_factory = Arc::clone(&self.text_factory);
+        let null_floats_as_nan = Arc::clone(&self.null_floats_as_nan);
+        let large_int_policy = Arc::clone(&self.large_int_policy);
+        let decode_large_ints = Arc::clone(&self.decode_large_ints);
+        let invalid_utf8 = Arc::clone(&self.invalid_utf8);
+        let strict = self.strict;
+        let detect_types = self.detect_types;
         let transaction_state = Arc::clone(&self.transaction_state);
         let transaction_connection = Arc::clone(&self.transaction_connection);
         let callback_connection = Arc::clone(&self.callback_connection);
@@ -569,9 +592,13 @@ impl Cursor {
         let trace_callback = Arc::clone(&self.trace_callback);
         let authorizer_callback = Arc::clone(&self.authorizer_callback);
         let progress_handler = Arc::clone(&self.progress_handler);
+        let update_hook = Arc::clone(&self.update_hook);
+        let udf_error = Arc::clone(&self.udf_error);
+        let execute_result = Arc::clone(&self.execute_result);
 
         Python::attach(|py| {
             let future = async move {
+                settle_executed(&query, &execute_result, &results);
                 // Check if results need to be fetched
                 let needs_fetch = {
                     let results_guard = results.lock().unwrap();
@@ -591,26 +618,17 @@ impl Cursor {
                             // The deprecation warning is acceptable as this is a sync operation within async.
                             #[allow(deprecated)]
                             Python::with_gil(|py| -> PyResult<(String, Vec<SqliteParam>)> {
+                                let bind_options = BindOptions {
+                                    large_int_policy: *large_int_policy.lock().unwrap(),
+                                    strict,
+                                };
                                 let params_guard = parameters.lock().unwrap();
                                 if let Some(ref params_py) = *params_guard {
-                                    let params_bound = params_py.bind(py);
-
-                                    // Check if it's a dict (named parameters)
-                                    if let Ok(dict) = params_bound.cast::<pyo3::types::PyDict>() {
-                                        let (proc_query, param_values) =
-                                            process_named_parameters(&query, dict)?;
-                                        return Ok((proc_query, param_values));
-                                    }
-
-                                    // Check if it's a list (positional parameters)
-                                    if let Ok(list) = params_bound.cast::<PyList>() {
-                                        let param_values = process_positional_parameters(list)?;
-                                        return Ok((query.clone(), param_values));
-                                    }
-
-                                    // Single value
-                                    let param = SqliteParam::from_py(params_bound)?;
-                                    return Ok((query.clone(), vec![param]));
+                                    return process_parameters(
+                                        query.clone(),
+                                        params_py.bind(py),
+                                        &bind_options,
+                                    );
                                 }
                                 Ok((query.clone(), Vec::new()))
                             })?
@@ -628,13 +646,16 @@ impl Cursor {
                         &trace_callback,
                         &authorizer_callback,
                         &progress_handler,
+                        &update_hook,
                     );
 
+                    let decltypes;
                     let rows = if in_transaction {
                         let mut conn_guard = transaction_connection.lock().await;
                         let conn = conn_guard.as_mut().ok_or_else(|| {
                             OperationalError::new_err("Transaction connection not available")
                         })?;
+                        decltypes = declared_types(detect_types, conn, &processed_query).await?;
                         bind_and_fetch_all_on_connection(
                             &processed_query,
                             &processed_params,
@@ -651,6 +672,11 @@ impl Cursor {
                             &pragmas,
                             &pool_size,
                             &connection_timeout_secs,
+                            &statement_policy,
+                            native_functions,
+                            &wal_hook,
+                            &vfs,
+                            &event_hooks,
                         )
                         .await?;
 
@@ -659,6 +685,7 @@ impl Cursor {
                         let conn = conn_guard.as_mut().ok_or_else(|| {
                             OperationalError::new_err("Callback connection not available")
                         })?;
+                        decltypes = declared_types(detect_types, conn, &processed_query).await?;
                         bind_and_fetch_all_on_connection(
                             &processed_query,
                             &processed_params,
@@ -673,6 +700,18 @@ impl Cursor {
                             &pragmas,
                             &pool_size,
                             &connection_timeout_secs,
+                            &statement_policy,
+                            native_functions,
+                            &wal_hook,
+                            &vfs,
+                            &event_hooks,
+                        )
+                        .await?;
+                        decltypes = declared_types_in_pool(
+                            detect_types,
+                            &pool_clone,
+                            &processed_query,
+                            &path,
                         )
                         .await?;
                         bind_and_fetch_all(&processed_query, &processed_params, &pool_clone, &path)
@@ -685,12 +724,22 @@ impl Cursor {
                     #[allow(deprecated)]
                     let cached_results = Python::with_gil(|py| -> PyResult<Vec<Py<PyAny>>> {
                         let guard = row_factory.lock().unwrap();
-                        let factory_opt = guard.as_ref();
+                        let factory = RowFactory::resolve(py, guard.as_ref())?;
                         let tf_guard = text_factory.lock().unwrap();
-                        let tf_opt = tf_guard.as_ref();
+                        let converters = rows.first().and_then(|row| {
+                            ColumnConverters::resolve(py, detect_types, row, decltypes.as_deref())
+                        });
+                        let decode = DecodeOptions {
+                            text_factory: tf_guard.as_ref(),
+                            null_floats_as_nan: *null_floats_as_nan.lock().unwrap(),
+                            decode_large_ints: *decode_large_ints.lock().unwrap(),
+                            invalid_utf8: *invalid_utf8.lock().unwrap(),
+                            strict,
+                            converters: converters.as_ref(),
+                        };
                         let mut vec = Vec::new();
                         for row in rows.iter() {
-                            let out = row_to_py_with_factory(py, row, factory_opt, tf_opt)?;
+                            let out = factory.convert(py, row, &decode)?;
                             vec.push(out.unbind());
                         }
                         Ok(vec)
@@ -722,7 +771,6 @@ impl Cursor {
                     };
 
                     let start = *index_guard;
-                    let fetch_size = size.unwrap_or(1);
                     let end = std::cmp::min(start + fetch_size, results_vec.len());
 
                     // Create result slice
@@ -737,16 +785,75 @@ impl Cursor {
                     Ok(result_list.into())
                 })
             };
-            future_into_py(py, future).map(|bound| bound.unbind())
+            runtime::future_into_py(
+                py,
+                self.runtime.as_deref(),
+                with_udf_errors(udf_error, future),
+            )
+            .map(|bound| bound.unbind())
+        })
+    }
+
+    /// Move the position in the result set (DB-API optional extension).
+    ///
+    /// With `mode="relative"` (default) the position moves by `value` rows, which
+    /// may be negative; with `mode="absolute"` it moves to row `value` (0-based).
+    /// The result is buffered first if nothing has been fetched yet. Raises
+    /// IndexError, leaving the position unchanged, if the target row is outside
+    /// the result set.
+    #[pyo3(signature = (value, mode = "relative"))]
+    fn scroll(&self, value: i64, mode: &str) -> PyResult<Py<PyAny>> {
+        self.check_open()?;
+        let absolute = match mode {
+            "relative" => false,
+            "absolute" => true,
+            other => {
+                return Err(ProgrammingError::new_err(format!(
+                    "Invalid scroll mode {other:?}; expected 'relative' or 'absolute'"
+                )))
+            }
+        };
+        let buffered = self.results.lock().unwrap().is_some();
+        let load = if buffered {
+            None
+        } else {
+            Some(self.fetchmany(Some(0))?)
+        };
+        let results = Arc::clone(&self.results);
+        let current_index = Arc::clone(&self.current_index);
+
+        Python::attach(|py| {
+            let future = async move {
+                if let Some(load) = load {
+                    Python::attach(|py| into_future(load.into_bound(py)))?.await?;
+                }
+                let len = results.lock().unwrap().as_ref().map_or(0, Vec::len) as i64;
+                let mut index_guard = current_index.lock().unwrap();
+                let target = if absolute {
+                    value
+                } else {
+                    *index_guard as i64 + value
+                };
+                if !(0..len).contains(&target) {
+                    return Err(PyIndexError::new_err(format!(
+                        "scroll target {target} out of range for {len} rows"
+                    )));
+                }
+                *index_guard = target as usize;
+                Ok(())
+            };
+            runtime::future_into_py(py, self.runtime.as_deref(), future).map(|bound| bound.unbind())
         })
     }
 
     /// Async context manager entry.
     fn __aenter__(slf: PyRef<Self>) -> PyResult<Py<PyAny>> {
+        let dedicated_runtime = slf.runtime.clone();
         let slf: Py<Self> = slf.into();
         Python::attach(|py| {
             let future = async move { Ok(slf) };
-            future_into_py(py, future).map(|bound| bound.unbind())
+            runtime::future_into_py(py, dedicated_runtime.as_deref(), future)
+                .map(|bound| bound.unbind())
         })
     }
 
@@ -761,17 +868,135 @@ impl Cursor {
             let future = async move {
                 Ok(false) // Return False to not suppress exceptions
             };
-            future_into_py(py, future).map(|bound| bound.unbind())
+            runtime::future_into_py(py, self.runtime.as_deref(), future).map(|bound| bound.unbind())
         })
     }
 
-    /// Execute a script containing multiple SQL statements separated by semicolons.
+    /// Execute a script of `;`-separated SQL statements; see
+    /// `Connection.executescript()`.
     fn executescript(&self, script: String) -> PyResult<Py<PyAny>> {
+        self.check_open()?;
+        Python::attach(|py| Connection::executescript(self.connection.borrow(py), script))
+    }
+
+    /// Close the cursor, dropping its buffered rows; using it afterwards
+    /// raises ProgrammingError. Closing the connection closes its cursors.
+    fn close(&self) -> PyResult<Py<PyAny>> {
+        self.closed.store(true, Ordering::Release);
+        self.results.lock().unwrap().take();
+        Python::attach(|py| {
+            runtime::future_into_py(py, self.runtime.as_deref(), async { Ok(()) })
+                .map(|bound| bound.unbind())
+        })
+    }
+
+    /// Async iterator entry point.
+    fn __aiter__(slf: PyRef<Self>) -> PyResult<Py<Self>> {
+        Ok(slf.into())
+    }
+
+    /// Async iterator next item: awaits the next row, running the query on the
+    /// first call, and raises StopAsyncIteration past the last row.
+    fn __anext__(&self) -> PyResult<Py<PyAny>> {
+        self.check_open()?;
+        if self.query.is_empty() {
+            return Err(ProgrammingError::new_err("No query executed"));
+        }
+
+        let udf_error = Arc::clone(&self.udf_error);
+        let future = self.next_row(true);
+        Python::attach(|py| {
+            runtime::future_into_py(
+                py,
+                self.runtime.as_deref(),
+                with_udf_errors(udf_error, future),
+            )
+            .map(|bound| bound.unbind())
+        })
+    }
+}
+
+impl Cursor {
+    /// Buffer `rows`, returned when the cursor's statement ran for its effect
+    /// (`INSERT ... RETURNING`), as the cursor's result set.
+    pub(crate) fn buffer_rows(
+        &self,
+        py: Python<'_>,
+        rows: &[SqliteRow],
+        decltypes: Option<&[Option<String>]>,
+    ) -> PyResult<()> {
+        let factory_guard = self.row_factory.lock().unwrap();
+        let factory = RowFactory::resolve(py, factory_guard.as_ref())?;
+        let tf_guard = self.text_factory.lock().unwrap();
+        let converters = rows
+            .first()
+            .and_then(|row| ColumnConverters::resolve(py, self.detect_types, row, decltypes));
+        let decode = DecodeOptions {
+            text_factory: tf_guard.as_ref(),
+            null_floats_as_nan: *self.null_floats_as_nan.lock().unwrap(),
+            decode_large_ints: *self.decode_large_ints.lock().unwrap(),
+            invalid_utf8: *self.invalid_utf8.lock().unwrap(),
+            strict: self.strict,
+            converters: converters.as_ref(),
+        };
+        let rows = rows
+            .iter()
+            .map(|row| factory.convert(py, row, &decode).map(Bound::unbind))
+            .collect::<PyResult<Vec<_>>>()?;
+        *self.results.lock().unwrap() = Some(rows);
+        *self.current_index.lock().unwrap() = 0;
+        Ok(())
+    }
+
+    /// Iterator over the rows the cursor buffered, from its position.
+    fn buffered_rows(&self) -> BufferedRowIterator {
+        BufferedRowIterator {
+            results: Arc::clone(&self.results),
+            current_index: Arc::clone(&self.current_index),
+            closed: Arc::clone(&self.closed),
+        }
+    }
+
+    fn check_open(&self) -> PyResult<()> {
+        if self.closed.load(Ordering::Acquire) {
+            return Err(ProgrammingError::new_err(
+                "Cannot operate on a closed cursor",
+            ));
+        }
+        Ok(())
+    }
+
+    /// The next row, buffering the result set on first use. Past the last row
+    /// this resolves to None, or raises StopAsyncIteration when `iterating`.
+    pub(crate) fn next_row(
+        &self,
+        iterating: bool,
+    ) -> impl Future<Output = PyResult<Py<PyAny>>> + Send + 'static {
+        // Use same logic as fetchmany but return single element or None
+        let query = self.query.clone();
+        let results = Arc::clone(&self.results);
+        let current_index = Arc::clone(&self.current_index);
+        let parameters = Arc::clone(&self.parameters);
+        let stored_proc_query_fetchone = self.processed_query.clone();
+        let stored_proc_params_fetchone = self.processed_params.clone();
         let path = self.connection_path.clone();
         let pool = Arc::clone(&self.connection_pool);
         let pragmas = Arc::clone(&self.connection_pragmas);
         let pool_size = Arc::clone(&self.pool_size);
         let connection_timeout_secs = Arc::clone(&self.connection_timeout_secs);
+        let statement_policy = self.statement_policy.clone();
+        let native_functions = self.native_functions;
+        let wal_hook = self.wal_hook.clone();
+        let vfs = self.vfs.clone();
+        let event_hooks = Arc::clone(&self.event_hooks);
+        let row_factory = Arc::clone(&self.row_factory);
+        let text_factory = Arc::clone(&self.text_factory);
+        let null_floats_as_nan = Arc::clone(&self.null_floats_as_nan);
+        let large_int_policy = Arc::clone(&self.large_int_policy);
+        let decode_large_ints = Arc::clone(&self.decode_large_ints);
+        let invalid_utf8 = Arc::clone(&self.invalid_utf8);
+        let strict = self.strict;
+        let detect_types = self.detect_types;
         let transaction_state = Arc::clone(&self.transaction_state);
         let transaction_connection = Arc::clone(&self.transaction_connection);
         let callback_connection = Arc::clone(&self.callback_connection);
@@ -780,23 +1005,47 @@ impl Cursor {
         let trace_callback = Arc::clone(&self.trace_callback);
         let authorizer_callback = Arc::clone(&self.authorizer_callback);
         let progress_handler = Arc::clone(&self.progress_handler);
+        let update_hook = Arc::clone(&self.update_hook);
+        let execute_result = Arc::clone(&self.execute_result);
 
-        Python::attach(|py| {
-            let future = async move {
-                // Parse script into individual statements
-                // Simple approach: split by semicolon, but be careful about semicolons in strings
-                // For now, use a simple split - more sophisticated parsing can be added later
-                let statements: Vec<String> = script
-                    .split(';')
-                    .map(|s| s.trim().to_string())
-                    .filter(|s| !s.is_empty())
-                    .collect();
-
-                if statements.is_empty() {
-                    return Ok(());
-                }
+        async move {
+            settle_executed(&query, &execute_result, &results);
+            // Ensure results are cached (same logic as fetchmany)
+            let needs_fetch = {
+                let results_guard = results.lock().unwrap();
+                results_guard.is_none()
+            };
 
-                // Check transaction state and callback flags
+            if needs_fetch {
+                // Use stored processed parameters if available, otherwise re-process
+                let (processed_query, processed_params) =
+                    if let (Some(proc_query), Some(proc_params)) =
+                        (stored_proc_query_fetchone, stored_proc_params_fetchone)
+                    {
+                        (proc_query, proc_params)
+                    } else {
+                        // Fallback: re-process parameters
+                        // Note: Python::with_gil is used here for sync parameter processing in async context.
+                        // The deprecation warning is acceptable as this is a sync operation within async.
+                        #[allow(deprecated)]
+                        Python::with_gil(|py| -> PyResult<(String, Vec<SqliteParam>)> {
+                            let bind_options = BindOptions {
+                                large_int_policy: *large_int_policy.lock().unwrap(),
+                                strict,
+                            };
+                            let params_guard = parameters.lock().unwrap();
+                            if let Some(ref params_py) = *params_guard {
+                                return process_parameters(
+                                    query.clone(),
+                                    params_py.bind(py),
+                                    &bind_options,
+                                );
+                            }
+                            Ok((query.clone(), Vec::new()))
+                        })?
+                    };
+
+                // Priority: transaction > callbacks > pool
                 let in_transaction = {
                     let g = transaction_state.lock().await;
                     g.is_active()
@@ -808,91 +1057,189 @@ impl Cursor {
                     &trace_callback,
                     &authorizer_callback,
                     &progress_handler,
+                    &update_hook,
                 );
 
-                // Execute each statement sequentially
-                for statement in statements {
-                    if in_transaction {
-                        let mut conn_guard = transaction_connection.lock().await;
-                        let conn = conn_guard.as_mut().ok_or_else(|| {
-                            OperationalError::new_err("Transaction connection not available")
-                        })?;
-                        bind_and_execute_on_connection(&statement, &[], conn, &path).await?;
-                    } else if has_callbacks_flag {
-                        ensure_callback_connection(
-                            &path,
-                            &pool,
-                            &callback_connection,
-                            &pragmas,
-                            &pool_size,
-                            &connection_timeout_secs,
-                        )
-                        .await?;
+                let decltypes;
+                let rows = if in_transaction {
+                    let mut conn_guard = transaction_connection.lock().await;
+                    let conn = conn_guard.as_mut().ok_or_else(|| {
+                        OperationalError::new_err("Transaction connection not available")
+                    })?;
+                    decltypes = declared_types(detect_types, conn, &processed_query).await?;
+                    bind_and_fetch_all_on_connection(
+                        &processed_query,
+                        &processed_params,
+                        conn,
+                        &path,
+                    )
+                    .await?
+                } else if has_callbacks_flag {
+                    // Ensure callback connection exists
+                    ensure_callback_connection(
+                        &path,
+                        &pool,
+                        &callback_connection,
+                        &pragmas,
+                        &pool_size,
+                        &connection_timeout_secs,
+                        &statement_policy,
+                        native_functions,
+                        &wal_hook,
+                        &vfs,
+                        &event_hooks,
+                    )
+                    .await?;
+
+                    // Use callback connection
+                    let mut conn_guard = callback_connection.lock().await;
+                    let conn = conn_guard.as_mut().ok_or_else(|| {
+                        OperationalError::new_err("Callback connection not available")
+                    })?;
+                    decltypes = declared_types(detect_types, conn, &processed_query).await?;
+                    bind_and_fetch_all_on_connection(
+                        &processed_query,
+                        &processed_params,
+                        conn,
+                        &path,
+                    )
+                    .await?
+                } else {
+                    let pool_clone = get_or_create_pool(
+                        &path,
+                        &pool,
+                        &pragmas,
+                        &pool_size,
+                        &connection_timeout_secs,
+                        &statement_policy,
+                        native_functions,
+                        &wal_hook,
+                        &vfs,
+                        &event_hooks,
+                    )
+                    .await?;
+                    decltypes =
+                        declared_types_in_pool(detect_types, &pool_clone, &processed_query, &path)
+                            .await?;
+                    bind_and_fetch_all(&processed_query, &processed_params, &pool_clone, &path)
+                        .await?
+                };
 
-                        let mut conn_guard = callback_connection.lock().await;
-                        let conn = conn_guard.as_mut().ok_or_else(|| {
-                            OperationalError::new_err("Callback connection not available")
-                        })?;
-                        bind_and_execute_on_connection(&statement, &[], conn, &path).await?;
-                    } else {
-                        let pool_clone = get_or_create_pool(
-                            &path,
-                            &pool,
-                            &pragmas,
-                            &pool_size,
-                            &connection_timeout_secs,
-                        )
-                        .await?;
-                        bind_and_execute(&statement, &[], &pool_clone, &path).await?;
+                // Note: Python::with_gil is used here for sync result caching in async context.
+                // The deprecation warning is acceptable as this is a sync operation within async.
+                #[allow(deprecated)]
+                let cached_results = Python::with_gil(|py| -> PyResult<Vec<Py<PyAny>>> {
+                    let guard = row_factory.lock().unwrap();
+                    let factory = RowFactory::resolve(py, guard.as_ref())?;
+                    let tf_guard = text_factory.lock().unwrap();
+                    let converters = rows.first().and_then(|row| {
+                        ColumnConverters::resolve(py, detect_types, row, decltypes.as_deref())
+                    });
+                    let decode = DecodeOptions {
+                        text_factory: tf_guard.as_ref(),
+                        null_floats_as_nan: *null_floats_as_nan.lock().unwrap(),
+                        decode_large_ints: *decode_large_ints.lock().unwrap(),
+                        invalid_utf8: *invalid_utf8.lock().unwrap(),
+                        strict,
+                        converters: converters.as_ref(),
+                    };
+                    let mut vec = Vec::new();
+                    for row in rows.iter() {
+                        let out = factory.convert(py, row, &decode)?;
+                        vec.push(out.unbind());
                     }
-                }
+                    Ok(vec)
+                })?;
 
-                Ok(())
-            };
-            future_into_py(py, future).map(|bound| bound.unbind())
-        })
-    }
-
-    /// Async iterator entry point.
-    fn __aiter__(slf: PyRef<Self>) -> PyResult<Py<Self>> {
-        Ok(slf.into())
-    }
-
-    /// Async iterator next item.
-    fn __anext__(&self) -> PyResult<Py<PyAny>> {
-        let results = Arc::clone(&self.results);
-        let current_index = Arc::clone(&self.current_index);
-
-        Python::attach(|py| {
-            // Get the row value while holding GIL
-            let row_opt = {
-                let results_guard = results.lock().unwrap();
-                let results_opt = results_guard.as_ref();
-
-                if results_opt.is_none() {
-                    return Err(ProgrammingError::new_err(
-                        "Cursor not executed. Call execute() first.",
-                    ));
+                {
+                    let mut results_guard = results.lock().unwrap();
+                    *results_guard = Some(cached_results);
                 }
+                *current_index.lock().unwrap() = 0;
+            }
 
-                let results_vec = results_opt.unwrap();
+            // Get first element or None
+            // Note: Python::with_gil is used here for sync context manager creation before async execution.
+            // The deprecation warning is acceptable as this is a sync context.
+            #[allow(deprecated)]
+            // Note: Python::with_gil is used here for sync result conversion in async context.
+            // The deprecation warning is acceptable as this is a sync operation within async.
+            #[allow(deprecated)]
+            Python::with_gil(|py| -> PyResult<Py<PyAny>> {
                 let mut index_guard = current_index.lock().unwrap();
+                let results_guard = results.lock().unwrap();
+
+                let Some(ref results_vec) = *results_guard else {
+                    return end_of_rows(py, iterating);
+                };
 
                 if *index_guard >= results_vec.len() {
-                    // End of iteration - raise StopAsyncIteration
-                    return Err(PyErr::new::<pyo3::exceptions::PyStopAsyncIteration, _>(""));
+                    return end_of_rows(py, iterating);
                 }
 
                 let row = results_vec[*index_guard].clone_ref(py);
                 *index_guard += 1;
-                Some(row)
-            };
 
-            if let Some(row) = row_opt {
                 Ok(row)
-            } else {
-                Err(PyErr::new::<pyo3::exceptions::PyStopAsyncIteration, _>(""))
-            }
-        })
+            })
+        }
+    }
+}
+
+/// Iterator over a cursor's buffered rows, returned by `fetchall(copy=False)`.
+/// Shares the cursor's position, so rows it yields are not fetched again.
+#[pyclass]
+pub(crate) struct BufferedRowIterator {
+    results: CursorResults,
+    current_index: Arc<StdMutex<usize>>,
+    closed: Arc<AtomicBool>,
+}
+
+#[pymethods]
+impl BufferedRowIterator {
+    fn __iter__(slf: PyRef<Self>) -> PyRef<Self> {
+        slf
+    }
+
+    fn __next__(&self, py: Python<'_>) -> PyResult<Option<Py<PyAny>>> {
+        if self.closed.load(Ordering::Acquire) {
+            return Err(ProgrammingError::new_err(
+                "Cannot operate on a closed cursor",
+            ));
+        }
+        let results = self.results.lock().unwrap();
+        let mut index = self.current_index.lock().unwrap();
+        let row = results.as_ref().and_then(|rows| rows.get(*index));
+        Ok(row.map(|row| {
+            *index += 1;
+            row.clone_ref(py)
+        }))
+    }
+
+    /// Rows left to yield.
+    fn __length_hint__(&self) -> usize {
+        let results = self.results.lock().unwrap();
+        let index = *self.current_index.lock().unwrap();
+        results
+            .as_ref()
+            .map_or(0, |rows| rows.len().saturating_sub(index))
+    }
+}
+
+/// Buffer an empty result set for a statement that already ran when the
+/// cursor was executed, unless it buffered its RETURNING rows then, so
+/// fetching never runs it a second time.
+fn settle_executed(query: &str, execute_result: &ExecuteResult, results: &CursorResults) {
+    if returns_rows_hint(query) == Some(false) || execute_result.lock().unwrap().is_some() {
+        results.lock().unwrap().get_or_insert_with(Vec::new);
+    }
+}
+
+/// Result of fetching past the last row.
+fn end_of_rows(py: Python<'_>, iterating: bool) -> PyResult<Py<PyAny>> {
+    if iterating {
+        Err(PyStopAsyncIteration::new_err(()))
+    } else {
+        Ok(py.None())
     }
 }
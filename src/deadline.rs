@@ -0,0 +1,137 @@
+//! `rapsqlite.deadline()`: a time budget shared by every statement a task
+//! runs inside an `async with` block, enforced by
+//! `rapsqlite_core::deadline`'s progress handler. The deadline lives in a
+//! context variable, so it follows the task (and the tasks it creates) across
+//! Connections; `runtime` reads it whenever an operation starts.
+
+use pyo3::prelude::*;
+use pyo3::sync::PyOnceLock;
+use std::sync::Mutex as StdMutex;
+use std::time::{Duration, Instant};
+
+use crate::exceptions::DeadlineExceeded;
+use crate::{runtime, ValueError};
+
+/// `ContextVar` holding the `Budget` of the current task, or None.
+static DEADLINE: PyOnceLock<Py<PyAny>> = PyOnceLock::new();
+
+fn variable(py: Python<'_>) -> PyResult<&Bound<'_, PyAny>> {
+    DEADLINE
+        .get_or_try_init(py, || {
+            Ok::<_, PyErr>(
+                py.import("contextvars")?
+                    .getattr("ContextVar")?
+                    .call1(("rapsqlite_deadline",))?
+                    .unbind(),
+            )
+        })
+        .map(|variable| variable.bind(py))
+}
+
+/// Value of the context variable: when the budget runs out.
+#[pyclass(frozen)]
+struct Budget {
+    at: Instant,
+}
+
+/// The deadline of the current task, if it runs inside `deadline()`.
+pub(crate) fn current(py: Python<'_>) -> PyResult<Option<Instant>> {
+    let budget = variable(py)?.call_method1("get", (py.None(),))?;
+    Ok(budget.cast::<Budget>().ok().map(|budget| budget.get().at))
+}
+
+/// Raise DeadlineExceeded if the current task's deadline has passed, before
+/// starting a statement too short for the progress handler to interrupt.
+pub(crate) fn check(path: &str, query: &str) -> PyResult<()> {
+    if rapsqlite_core::deadline::expired() {
+        return Err(DeadlineExceeded::new_err(format!(
+            "Deadline exceeded before running query on database {path}\nQuery: {query}"
+        )));
+    }
+    Ok(())
+}
+
+/// Async context manager returned by `rapsqlite.deadline()`.
+#[pyclass]
+pub(crate) struct Deadline {
+    seconds: f64,
+    /// The deadline set by `__aenter__`, with the `ContextVar.set()` token
+    /// that undoes it.
+    entered: StdMutex<Vec<(Instant, Py<PyAny>)>>,
+}
+
+#[pymethods]
+impl Deadline {
+    fn __aenter__(slf: Bound<'_, Self>) -> PyResult<Py<PyAny>> {
+        let py = slf.py();
+        let mut at = Instant::now() + Duration::from_secs_f64(slf.borrow().seconds);
+        // A nested block cannot extend the budget of the one around it
+        if let Some(outer) = current(py)? {
+            at = at.min(outer);
+        }
+        let token = variable(py)?.call_method1("set", (Budget { at },))?;
+        slf.borrow()
+            .entered
+            .lock()
+            .unwrap()
+            .push((at, token.unbind()));
+        let slf = slf.unbind();
+        runtime::future_into_py(py, None, async move { Ok(slf) }).map(|bound| bound.unbind())
+    }
+
+    fn __aexit__(
+        &self,
+        py: Python<'_>,
+        _exc_type: Option<&Bound<'_, PyAny>>,
+        _exc_val: Option<&Bound<'_, PyAny>>,
+        _exc_tb: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<Py<PyAny>> {
+        let entered = self.entered.lock().unwrap().pop();
+        if let Some((_, token)) = entered {
+            variable(py)?.call_method1("reset", (token,))?;
+        }
+        runtime::future_into_py(py, None, async { Ok(false) }).map(|bound| bound.unbind())
+    }
+
+    /// Seconds left in the budget (0.0 once it is spent); the full budget
+    /// before the block is entered.
+    #[getter]
+    fn remaining(&self) -> f64 {
+        match self.entered.lock().unwrap().last() {
+            Some((at, _)) => at.saturating_duration_since(Instant::now()).as_secs_f64(),
+            None => self.seconds,
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        format!("<Deadline remaining={:.3}s>", self.remaining())
+    }
+}
+
+/// Give the statements run inside an `async with` block a total budget of
+/// `seconds`.
+///
+/// Every rapsqlite operation the task starts in the block, on any
+/// Connection, shares the budget: a statement still running when it runs out
+/// is interrupted and raises DeadlineExceeded (a QueryAborted), and so does
+/// starting a query once it is spent. Tasks created in the block inherit it,
+/// and a nested block only ever shortens it. Waiting for a pool connection
+/// is bounded by `connection_timeout`, not by the deadline.
+///
+/// # Example
+///
+/// .. code-block:: python
+///
+///     async with rapsqlite.deadline(2.0):
+///         user = await conn.fetch_one("SELECT * FROM users WHERE id = ?", [user_id])
+///         orders = await conn.fetch_all("SELECT * FROM orders WHERE user_id = ?", [user_id])
+#[pyfunction]
+pub(crate) fn deadline(seconds: f64) -> PyResult<Deadline> {
+    if !seconds.is_finite() || seconds < 0.0 {
+        return Err(ValueError::new_err("seconds must be a finite number >= 0"));
+    }
+    Ok(Deadline {
+        seconds,
+        entered: StdMutex::new(Vec::new()),
+    })
+}
@@ -0,0 +1,95 @@
+//! Encrypted backups and dumps: `Connection.backup_encrypted()`,
+//! `Connection.dump_encrypted()` (via `write_encrypted()`) and
+//! `decrypt_file()`. The file format lives in `rapsqlite_core::encryption`.
+
+use pyo3::prelude::*;
+use rapsqlite_core::encryption::{self, KEY_SIZE};
+use std::path::PathBuf;
+
+use crate::{runtime, OperationalError, ValueError};
+
+/// Check that `key` is a 32-byte symmetric key.
+pub(crate) fn parse_key(key: &[u8]) -> PyResult<[u8; KEY_SIZE]> {
+    key.try_into().map_err(|_| {
+        ValueError::new_err(format!(
+            "Invalid encryption key: expected {KEY_SIZE} bytes, got {}",
+            key.len()
+        ))
+    })
+}
+
+/// Encrypt `data` under `key` into a new file at `path`, in the format of
+/// `Connection.backup_encrypted()`.
+///
+/// # Arguments
+///
+/// * `path` - File to write (replaced if it exists).
+/// * `data` - Plaintext bytes.
+/// * `key` - 32-byte symmetric key, e.g. from `os.urandom(32)`.
+///
+/// # Errors
+///
+/// Raises ValueError for a key that is not 32 bytes and OperationalError if
+/// writing fails.
+#[pyfunction]
+pub(crate) fn write_encrypted(
+    py: Python<'_>,
+    path: PathBuf,
+    data: Vec<u8>,
+    key: &[u8],
+) -> PyResult<Py<PyAny>> {
+    let key = parse_key(key)?;
+    let future = async move {
+        tokio::task::spawn_blocking(move || {
+            let file = std::io::BufWriter::new(std::fs::File::create(&path)?);
+            encryption::encrypt(&data[..], file, &key)
+        })
+        .await
+        .map_err(|e| OperationalError::new_err(format!("write_encrypted failed: {e}")))?
+        .map_err(|e| OperationalError::new_err(format!("write_encrypted failed: {e}")))?;
+        Ok(())
+    };
+    runtime::future_into_py(py, None, future).map(|bound| bound.unbind())
+}
+
+/// Decrypt a file written by `Connection.backup_encrypted()`,
+/// `Connection.dump_encrypted()` or `write_encrypted()`.
+///
+/// Restores a backup as a database file (or a dump as an SQL script) at
+/// `dest`. The contents are authenticated: a wrong key or a modified or
+/// truncated file raises instead of producing corrupt output.
+///
+/// # Arguments
+///
+/// * `path` - Encrypted file.
+/// * `dest` - Decrypted file to write (replaced if it exists; removed again
+///   if decryption fails).
+/// * `key` - The 32-byte key the file was encrypted with.
+///
+/// # Errors
+///
+/// Raises ValueError for a key that is not 32 bytes and OperationalError for
+/// a wrong key, a corrupted file or an I/O error.
+///
+/// # Example
+///
+/// .. code-block:: python
+///
+///     await rapsqlite.decrypt_file("app.db.enc", "restored.db", key)
+#[pyfunction]
+pub(crate) fn decrypt_file(
+    py: Python<'_>,
+    path: PathBuf,
+    dest: PathBuf,
+    key: &[u8],
+) -> PyResult<Py<PyAny>> {
+    let key = parse_key(key)?;
+    let future = async move {
+        tokio::task::spawn_blocking(move || encryption::decrypt_file(&path, &dest, &key))
+            .await
+            .map_err(|e| OperationalError::new_err(format!("decrypt_file failed: {e}")))?
+            .map_err(|e| OperationalError::new_err(format!("decrypt_file failed: {e}")))?;
+        Ok(())
+    };
+    runtime::future_into_py(py, None, future).map(|bound| bound.unbind())
+}
@@ -2,7 +2,14 @@
 
 use pyo3::prelude::*;
 
-use crate::exceptions::{DatabaseError, IntegrityError, OperationalError, ProgrammingError};
+use libsqlite3_sys::{SQLITE_BUSY, SQLITE_CONSTRAINT, SQLITE_INTERRUPT, SQLITE_LOCKED};
+use rapsqlite_core::group_commit::GroupCommitError;
+use rapsqlite_core::query::FetchError;
+
+use crate::exceptions::{
+    DatabaseError, DeadlineExceeded, IntegrityError, OperationalError, ProgrammingError,
+    QueryAborted, ResultTooLarge,
+};
 
 /// Sanitize a query string to remove potentially sensitive information.
 /// Replaces common sensitive patterns with placeholders.
@@ -96,8 +103,16 @@ pub(crate) fn map_sqlx_error_with_query_visibility(
     match e {
         SqlxError::Database(db_err) => {
             let msg = db_err.message();
+            // SQLITE_INTERRUPT means the statement was aborted on purpose (progress
+            // handler returned False or sqlite3_interrupt was called), not a real failure
+            let interrupted = db_err
+                .code()
+                .and_then(|code| code.parse::<i32>().ok())
+                .is_some_and(|code| code & 0xff == SQLITE_INTERRUPT);
             // Check for specific SQLite error codes
-            if msg.contains("SQLITE_CONSTRAINT")
+            if interrupted {
+                aborted(error_msg)
+            } else if msg.contains("SQLITE_CONSTRAINT")
                 || msg.contains("UNIQUE constraint")
                 || msg.contains("NOT NULL constraint")
                 || msg.contains("FOREIGN KEY constraint")
@@ -117,3 +132,47 @@ pub(crate) fn map_sqlx_error_with_query_visibility(
         _ => DatabaseError::new_err(error_msg),
     }
 }
+
+/// Map a failed group-commit write to a Python exception. When the group's
+/// transaction failed, every statement in it raises OperationalError.
+pub(crate) fn map_group_commit_error(e: GroupCommitError, path: &str, query: &str) -> PyErr {
+    match e {
+        GroupCommitError::Statement(e) => map_sqlx_error(e, path, query),
+        other => OperationalError::new_err(format!(
+            "Failed to execute query on database {path}: {other}"
+        )),
+    }
+}
+
+/// Map a failed limited fetch to a Python exception: ResultTooLarge when the
+/// result passed `max_rows` / `max_result_bytes`.
+pub(crate) fn map_fetch_error(e: FetchError, path: &str, query: &str) -> PyErr {
+    match e {
+        FetchError::Query(e) => map_sqlx_error(e, path, query),
+        other => ResultTooLarge::new_err(format!(
+            "Failed to execute query on database {path}: {other}"
+        )),
+    }
+}
+
+/// Map a result code and message from a direct SQLite C API call to a Python exception,
+/// classified the same way as `map_sqlx_error`.
+pub(crate) fn map_sqlite_error(code: i32, message: &str, path: &str) -> PyErr {
+    let error_msg = format!("Failed to execute query on database {path}: {message}");
+    match code & 0xff {
+        SQLITE_INTERRUPT => aborted(error_msg),
+        SQLITE_CONSTRAINT => IntegrityError::new_err(error_msg),
+        SQLITE_BUSY | SQLITE_LOCKED => OperationalError::new_err(error_msg),
+        _ => DatabaseError::new_err(error_msg),
+    }
+}
+
+/// QueryAborted for an interrupted statement, or DeadlineExceeded when the
+/// `deadline()` of the task running it has passed.
+fn aborted(error_msg: String) -> PyErr {
+    if rapsqlite_core::deadline::expired() {
+        DeadlineExceeded::new_err(error_msg)
+    } else {
+        QueryAborted::new_err(error_msg)
+    }
+}
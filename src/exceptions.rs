@@ -2,6 +2,7 @@
 
 use pyo3::create_exception;
 use pyo3::exceptions::PyException;
+use pyo3::exceptions::PyUserWarning;
 use pyo3::exceptions::PyValueError;
 
 // Exception classes matching aiosqlite API (ABI3 compatible)
@@ -11,4 +12,18 @@ create_exception!(_rapsqlite, DatabaseError, Error);
 create_exception!(_rapsqlite, OperationalError, DatabaseError);
 create_exception!(_rapsqlite, ProgrammingError, DatabaseError);
 create_exception!(_rapsqlite, IntegrityError, DatabaseError);
+// Raised when a feature needs SQLite compile options this build lacks
+create_exception!(_rapsqlite, NotSupportedError, DatabaseError);
+// Raised when a query is interrupted deliberately (e.g. a progress handler returned False)
+create_exception!(_rapsqlite, QueryAborted, OperationalError);
+// Raised when a statement runs past the deadline of a rapsqlite.deadline() block
+create_exception!(_rapsqlite, DeadlineExceeded, QueryAborted);
+// Raised when a fetch returns more than max_rows rows or max_result_bytes bytes
+create_exception!(_rapsqlite, ResultTooLarge, OperationalError);
+// Raised when no pooled connection frees up in time, or max_queue tasks already wait for one
+create_exception!(_rapsqlite, PoolTimeout, OperationalError);
 create_exception!(_rapsqlite, ValueError, PyValueError);
+// Emitted in debug mode when a statement looks like an N+1 query pattern
+create_exception!(_rapsqlite, NPlusOneWarning, PyUserWarning);
+// Emitted in debug mode when tasks sharing a Connection interfere with each other
+create_exception!(_rapsqlite, TaskSafetyWarning, PyUserWarning);
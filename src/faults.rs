@@ -0,0 +1,105 @@
+//! `rapsqlite.FaultInjector`: on-demand SQLITE_BUSY, I/O errors and latency
+//! for the Connections opened with `faults=`. The wrapper VFS lives in
+//! `rapsqlite_core::faults`.
+
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use rapsqlite_core::faults::{FaultInjector as CoreInjector, FaultOp};
+use std::time::Duration;
+
+use crate::ValueError;
+
+/// Injects faults into the file operations of the Connections opened with
+/// `faults=`, so retry logic and timeout handling can be tested without racing
+/// real connections. Faults are one-shot counts or standing latencies and
+/// apply to every Connection using the injector, until `reset()`. Injectors
+/// share one set of faults; creating one clears them.
+#[pyclass(frozen)]
+pub(crate) struct FaultInjector {
+    pub(crate) injector: CoreInjector,
+}
+
+const OPS: [(&str, FaultOp); 4] = [
+    ("read", FaultOp::Read),
+    ("write", FaultOp::Write),
+    ("sync", FaultOp::Sync),
+    ("lock", FaultOp::Lock),
+];
+
+fn parse_op(op: &str) -> PyResult<FaultOp> {
+    OPS.iter()
+        .find(|(name, _)| *name == op)
+        .map(|(_, op)| *op)
+        .ok_or_else(|| {
+            ValueError::new_err(format!(
+                "op must be 'read', 'write', 'sync' or 'lock', got {op:?}"
+            ))
+        })
+}
+
+#[pymethods]
+impl FaultInjector {
+    #[new]
+    fn new() -> Self {
+        FaultInjector {
+            injector: CoreInjector::new(),
+        }
+    }
+
+    /// Make the next `count` attempts to take the write lock fail with
+    /// SQLITE_BUSY. SQLite's busy handler retries them until the busy timeout,
+    /// after which the statement raises OperationalError ("database is
+    /// locked").
+    #[pyo3(signature = (count = 1))]
+    fn busy(&self, count: u64) {
+        self.injector.fail_next(FaultOp::Lock, count);
+    }
+
+    /// Make the next `count` `op` operations ("read", "write" or "sync") on
+    /// the database, journal or WAL file fail with an I/O error; the statement
+    /// raises DatabaseError ("disk I/O error").
+    #[pyo3(signature = (op, count = 1))]
+    fn io_error(&self, op: &str, count: u64) -> PyResult<()> {
+        match parse_op(op)? {
+            FaultOp::Lock => Err(ValueError::new_err(
+                "op must be 'read', 'write' or 'sync'; use busy() for locks",
+            )),
+            op => {
+                self.injector.fail_next(op, count);
+                Ok(())
+            }
+        }
+    }
+
+    /// Delay every `op` operation ("read", "write", "sync" or "lock"; all of
+    /// them when None) by `seconds`, until `reset()` or `latency(0)`.
+    #[pyo3(signature = (seconds, op = None))]
+    fn latency(&self, seconds: f64, op: Option<&str>) -> PyResult<()> {
+        let latency = Duration::try_from_secs_f64(seconds)
+            .map_err(|_| ValueError::new_err("seconds must be a finite number >= 0"))?;
+        match op {
+            Some(op) => self.injector.set_latency(parse_op(op)?, latency),
+            None => {
+                for op in FaultOp::ALL {
+                    self.injector.set_latency(op, latency);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Clear pending faults, latencies and the `injected` counts.
+    fn reset(&self) {
+        self.injector.reset();
+    }
+
+    /// Failures injected so far: {"read", "write", "sync", "lock"} to counts.
+    #[getter]
+    fn injected<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let counts = PyDict::new(py);
+        for (name, op) in OPS {
+            counts.set_item(name, self.injector.injected(op))?;
+        }
+        Ok(counts)
+    }
+}
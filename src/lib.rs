@@ -4,16 +4,21 @@ mod connection;
 pub(crate) use connection::Connection;
 
 mod context_managers;
-pub(crate) use context_managers::{ExecuteContextManager, TransactionContextManager};
+pub(crate) use context_managers::{
+    DeferForeignKeysContextManager, ExecuteContextManager, OptionsContextManager,
+    SavepointContextManager, TransactionContextManager,
+};
 
 mod cursor;
-pub(crate) use cursor::Cursor;
+pub(crate) use cursor::{BufferedRowIterator, Cursor};
 
 use pyo3::prelude::*;
 
 mod exceptions;
 use exceptions::{
-    DatabaseError, Error, IntegrityError, OperationalError, ProgrammingError, ValueError, Warning,
+    DatabaseError, DeadlineExceeded, Error, IntegrityError, NPlusOneWarning, NotSupportedError,
+    OperationalError, PoolTimeout, ProgrammingError, QueryAborted, ResultTooLarge,
+    TaskSafetyWarning, ValueError, Warning,
 };
 
 mod types;
@@ -22,13 +27,57 @@ mod utils;
 
 mod conversion;
 
-#[macro_use]
+mod constants;
+
+mod converters;
+
+mod deadline;
+use deadline::Deadline;
+
+mod encryption;
+
+mod lifecycle;
+use lifecycle::PooledConnection;
+
 mod parameters;
 
 mod query;
 
+mod remote;
+
+mod replication;
+
+mod restore;
+
+mod result_cache;
+
+mod runtime;
+
+mod statement_log;
+
+mod stream;
+use stream::RowStream;
+
+mod template;
+use template::Sql;
+
+mod uri;
+
 mod pool;
 
+mod callbacks;
+
+mod clock;
+use clock::FakeClock;
+
+mod faults;
+use faults::FaultInjector;
+
+mod pointer;
+use pointer::Pointer;
+
+mod compression;
+
 mod errors;
 pub(crate) use errors::map_sqlx_error;
 
@@ -40,9 +89,35 @@ use row::RapRow;
 fn _rapsqlite(py: Python<'_>, m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<Connection>()?;
     m.add_class::<Cursor>()?;
+    m.add_class::<BufferedRowIterator>()?;
     m.add_class::<ExecuteContextManager>()?;
     m.add_class::<TransactionContextManager>()?;
+    m.add_class::<OptionsContextManager>()?;
+    m.add_class::<DeferForeignKeysContextManager>()?;
+    m.add_class::<SavepointContextManager>()?;
     m.add_class::<RapRow>()?;
+    m.add_class::<RowStream>()?;
+    m.add_class::<Sql>()?;
+    m.add_class::<PooledConnection>()?;
+    m.add_class::<FakeClock>()?;
+    m.add_class::<FaultInjector>()?;
+    m.add_class::<Pointer>()?;
+    m.add_class::<Deadline>()?;
+    m.add_function(wrap_pyfunction!(runtime::configure_runtime, m)?)?;
+    m.add_function(wrap_pyfunction!(converters::register_converter, m)?)?;
+    m.add_function(wrap_pyfunction!(restore::restore_to, m)?)?;
+    m.add_function(wrap_pyfunction!(encryption::write_encrypted, m)?)?;
+    m.add_function(wrap_pyfunction!(encryption::decrypt_file, m)?)?;
+    m.add_function(wrap_pyfunction!(compression::write_compressed, m)?)?;
+    m.add_function(wrap_pyfunction!(compression::read_compressed, m)?)?;
+    m.add_function(wrap_pyfunction!(remote::fetch_cached, m)?)?;
+    m.add_function(wrap_pyfunction!(template::sql, m)?)?;
+    m.add_function(wrap_pyfunction!(uri::uri_filename, m)?)?;
+    m.add_function(wrap_pyfunction!(uri::parse_uri, m)?)?;
+    m.add_function(wrap_pyfunction!(deadline::deadline, m)?)?;
+    m.add("PARSE_DECLTYPES", converters::PARSE_DECLTYPES)?;
+    m.add("PARSE_COLNAMES", converters::PARSE_COLNAMES)?;
+    constants::add_constants(m)?;
 
     // Register exception classes (required for create_exception! to be accessible from Python)
     m.add("Error", py.get_type::<Error>())?;
@@ -51,7 +126,14 @@ fn _rapsqlite(py: Python<'_>, m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add("OperationalError", py.get_type::<OperationalError>())?;
     m.add("ProgrammingError", py.get_type::<ProgrammingError>())?;
     m.add("IntegrityError", py.get_type::<IntegrityError>())?;
+    m.add("NotSupportedError", py.get_type::<NotSupportedError>())?;
+    m.add("QueryAborted", py.get_type::<QueryAborted>())?;
+    m.add("DeadlineExceeded", py.get_type::<DeadlineExceeded>())?;
+    m.add("ResultTooLarge", py.get_type::<ResultTooLarge>())?;
+    m.add("PoolTimeout", py.get_type::<PoolTimeout>())?;
     m.add("ValueError", py.get_type::<ValueError>())?;
+    m.add("NPlusOneWarning", py.get_type::<NPlusOneWarning>())?;
+    m.add("TaskSafetyWarning", py.get_type::<TaskSafetyWarning>())?;
 
     Ok(())
 }
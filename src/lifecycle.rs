@@ -0,0 +1,283 @@
+//! `Connection.set_event_hook()`: async Python hooks for the lifecycle events
+//! of pooled connections. The events are reported by
+//! `rapsqlite_core::lifecycle`; this module runs the hooks on the event loop
+//! they were registered from.
+
+use futures_util::future::BoxFuture;
+use pyo3::prelude::*;
+use pyo3::{PyTraverseError, PyVisit};
+use pyo3_async_runtimes::tokio::into_future;
+use pyo3_async_runtimes::TaskLocals;
+use rapsqlite_core::activity::Activity;
+use rapsqlite_core::lifecycle::{EventConnection, PoolEvent, PoolListener};
+use rapsqlite_core::queue::AcquireQueue;
+use std::collections::HashMap;
+use std::ffi::CString;
+use std::sync::{Arc, Mutex as StdMutex};
+
+use crate::connection::exec_unprepared;
+use crate::{runtime, ProgrammingError, ValueError};
+
+/// Event names accepted by `set_event_hook()`.
+pub(crate) const EVENTS: [&str; 4] = ["connect", "acquire", "release", "close"];
+
+fn event_name(event: PoolEvent) -> &'static str {
+    match event {
+        PoolEvent::Connect => "connect",
+        PoolEvent::Acquire => "acquire",
+        PoolEvent::Release => "release",
+    }
+}
+
+struct Hook {
+    callback: Py<PyAny>,
+    locals: TaskLocals,
+}
+
+/// The `sqlite3*` of a connection whose event hook is running.
+#[derive(Clone, Copy)]
+struct RawDb(*mut libsqlite3_sys::sqlite3);
+
+// Safety: only used while the pool keeps the connection locked for its hook.
+unsafe impl Send for RawDb {}
+
+/// The hooks of a Connection (shared by the Connections of a shared pool),
+/// installed as the listener of its pool.
+pub(crate) struct EventHooks {
+    path: String,
+    hooks: StdMutex<HashMap<&'static str, Arc<Hook>>>,
+    init_hook: StdMutex<Option<Arc<Py<PyAny>>>>, // init_hook with init_hook_scope="connection"
+    activity: Arc<Activity>,                     // statements running on the pool's connections
+    queue: Arc<AcquireQueue>,                    // tasks waiting for the pool's connections
+}
+
+impl EventHooks {
+    pub(crate) fn new(path: &str) -> Self {
+        EventHooks {
+            path: path.to_string(),
+            hooks: StdMutex::new(HashMap::new()),
+            init_hook: StdMutex::new(None),
+            activity: Arc::new(Activity::new()),
+            queue: Arc::new(AcquireQueue::new(None)),
+        }
+    }
+
+    /// The statements running on the pool's connections, for
+    /// `Connection.active_statements()`.
+    pub(crate) fn activity(&self) -> &Arc<Activity> {
+        &self.activity
+    }
+
+    /// The queue of tasks waiting for the pool's connections, for `max_queue`
+    /// and `Connection.queue_stats()`.
+    pub(crate) fn queue(&self) -> &Arc<AcquireQueue> {
+        &self.queue
+    }
+
+    /// Run `init_hook` on each new connection, before its "connect" hook.
+    /// Unlike event hooks, it runs on the event loop of the operation that
+    /// opened the connection, as the Connection's `init_hook` does.
+    pub(crate) fn set_init_hook(&self, init_hook: Py<PyAny>) {
+        *self.init_hook.lock().unwrap() = Some(Arc::new(init_hook));
+    }
+
+    /// Visit the hook callbacks, for the garbage collection of a Connection
+    /// that does not share them with others.
+    pub(crate) fn traverse(&self, visit: &PyVisit<'_>) -> Result<(), PyTraverseError> {
+        if let Ok(hooks) = self.hooks.try_lock() {
+            for hook in hooks.values() {
+                visit.call(&hook.callback)?;
+            }
+        }
+        if let Ok(init_hook) = self.init_hook.try_lock() {
+            visit.call(init_hook.as_deref())?;
+        }
+        Ok(())
+    }
+
+    fn hook(&self, event: &str) -> Option<Arc<Hook>> {
+        self.hooks.lock().unwrap().get(event).cloned()
+    }
+
+    /// Set or clear (`None`) the hook for `event`. A hook is an `async def`
+    /// function; its coroutines run on the event loop running now.
+    pub(crate) fn set(
+        &self,
+        py: Python<'_>,
+        event: &str,
+        callback: Option<Py<PyAny>>,
+    ) -> PyResult<()> {
+        let Some(event) = EVENTS.into_iter().find(|name| *name == event) else {
+            return Err(ValueError::new_err(format!(
+                "event must be one of {}, got {event:?}",
+                EVENTS.join(", ")
+            )));
+        };
+        let Some(callback) = callback else {
+            self.hooks.lock().unwrap().remove(event);
+            return Ok(());
+        };
+        let is_async = py
+            .import("inspect")?
+            .call_method1("iscoroutinefunction", (&callback,))?
+            .is_truthy()?;
+        if !is_async {
+            return Err(pyo3::exceptions::PyTypeError::new_err(
+                "event hooks must be async functions",
+            ));
+        }
+        let event_loop = py
+            .import("asyncio")?
+            .call_method0("get_running_loop")
+            .map_err(|_| {
+                ProgrammingError::new_err(
+                    "event hooks can only be set while an event loop is running",
+                )
+            })?;
+        let hook = Hook {
+            callback,
+            locals: TaskLocals::new(event_loop),
+        };
+        self.hooks.lock().unwrap().insert(event, Arc::new(hook));
+        Ok(())
+    }
+}
+
+impl PoolListener for EventHooks {
+    fn wants(&self, event: PoolEvent) -> bool {
+        (event == PoolEvent::Connect && self.init_hook.lock().unwrap().is_some())
+            || self.hooks.lock().unwrap().contains_key(event_name(event))
+    }
+
+    fn event(
+        &self,
+        event: PoolEvent,
+        conn: EventConnection,
+    ) -> BoxFuture<'static, Result<(), String>> {
+        let name = event_name(event);
+        let init_hook = match event {
+            PoolEvent::Connect => self.init_hook.lock().unwrap().clone(),
+            _ => None,
+        };
+        let hook = self.hook(name);
+        let db = Arc::new(StdMutex::new(Some(RawDb(conn.db()))));
+        let pooled = {
+            let db = Arc::clone(&db);
+            let path = self.path.clone();
+            move || PooledConnection {
+                id: conn.id,
+                db: Arc::clone(&db),
+                path: path.clone(),
+            }
+        };
+        Box::pin(async move {
+            let result = async {
+                if let Some(init_hook) = init_hook {
+                    let future = Python::attach(|py| {
+                        let coroutine = init_hook.bind(py).call1((pooled(),))?;
+                        into_future(coroutine)
+                    });
+                    future
+                        .map_err(|e| format!("Failed to call init_hook: {e}"))?
+                        .await
+                        .map_err(|e| format!("init_hook raised an exception: {e}"))?;
+                }
+                if let Some(hook) = hook {
+                    let future = Python::attach(|py| {
+                        let coroutine = hook.callback.bind(py).call1((pooled(),))?;
+                        pyo3_async_runtimes::into_future_with_locals(&hook.locals, coroutine)
+                    });
+                    let result = match future {
+                        Ok(future) => future.await.map(drop),
+                        Err(e) => Err(e),
+                    };
+                    result.map_err(|e| format!("{name} event hook failed: {e}"))?;
+                }
+                Ok(())
+            }
+            .await;
+            // The connection is unlocked once the hooks return
+            *db.lock().unwrap() = None;
+            result
+        })
+    }
+
+    fn closed(&self, id: u64) {
+        let Some(hook) = self.hook("close") else {
+            return;
+        };
+        // Nothing can run Python once the interpreter is shutting down
+        let _ = Python::try_attach(|py| {
+            let pooled = PooledConnection {
+                id,
+                db: Arc::new(StdMutex::new(None)),
+                path: self.path.clone(),
+            };
+            let callback = hook.callback.bind(py);
+            let scheduled = callback.call1((pooled,)).and_then(|coroutine| {
+                let asyncio = py.import("asyncio")?;
+                let event_loop = hook.locals.event_loop(py);
+                asyncio
+                    .call_method1("run_coroutine_threadsafe", (&coroutine, event_loop))
+                    .inspect_err(|_| {
+                        // A closed loop never runs it
+                        let _ = coroutine.call_method0("close");
+                    })
+            });
+            if let Err(e) = scheduled {
+                e.write_unraisable(py, Some(callback));
+            }
+        });
+    }
+}
+
+/// A pooled connection passed to an event hook (see
+/// `Connection.set_event_hook()`) or to an `init_hook` with
+/// `init_hook_scope="connection"`. Its statements run on that connection, so
+/// per-connection settings made here stick to it.
+#[pyclass(frozen)]
+pub(crate) struct PooledConnection {
+    id: u64,
+    db: Arc<StdMutex<Option<RawDb>>>,
+    path: String,
+}
+
+#[pymethods]
+impl PooledConnection {
+    /// Identifies the pooled connection for the life of the process.
+    #[getter]
+    fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Run `sql` (one or more statements, without parameters) on this
+    /// connection.
+    ///
+    /// # Returns
+    ///
+    /// Returns an awaitable resolving to the number of rows changed.
+    ///
+    /// # Errors
+    ///
+    /// Raises ProgrammingError once the hook has returned, and in `close`
+    /// hooks, which run after the connection closed.
+    fn execute(&self, py: Python<'_>, sql: &str) -> PyResult<Py<PyAny>> {
+        let sql = CString::new(sql)
+            .map_err(|e| ValueError::new_err(format!("Invalid SQL string: {e}")))?;
+        let changes = {
+            let db = self.db.lock().unwrap();
+            let Some(RawDb(db)) = *db else {
+                return Err(ProgrammingError::new_err(
+                    "a pooled connection can only be used while its event hook runs",
+                ));
+            };
+            // Safety: the pool keeps the connection locked until the hook returns.
+            unsafe { exec_unprepared(db, &sql, &self.path) }?.0
+        };
+        runtime::future_into_py(py, None, async move { Ok(changes) }).map(|bound| bound.unbind())
+    }
+
+    fn __repr__(&self) -> String {
+        format!("<PooledConnection id={}>", self.id)
+    }
+}
@@ -1,18 +1,94 @@
 //! SQL parameter parsing and binding helpers.
 
 use pyo3::prelude::*;
-use pyo3::types::{PyDict, PyList};
+use pyo3::types::{PyByteArray, PyBytes, PyDict, PyList, PyMapping, PySequence, PyString, PyTuple};
 
-use crate::types::SqliteParam;
+use crate::types::{BindOptions, SqliteParam, SqliteParamExt};
 
-/// Parse named parameters from SQL query and convert to positional.
-/// Returns the processed query with ? placeholders and ordered parameter values.
-pub(crate) fn process_named_parameters(
-    query: &str,
-    dict: &Bound<'_, PyDict>,
+/// How `execute()`-style parameters bind.
+enum ParameterKind {
+    Named,
+    Positional,
+    Single,
+}
+
+/// Classify parameters as sqlite3 does: a mapping (any
+/// `collections.abc.Mapping`) binds named placeholders and any other sequence
+/// or iterable (list, tuple, generator, ...) binds positionally. str, bytes
+/// and any other single value bind as the only parameter.
+fn parameter_kind(params: &Bound<'_, PyAny>) -> ParameterKind {
+    if params.is_instance_of::<PyDict>() {
+        return ParameterKind::Named;
+    }
+    if params.is_instance_of::<PyList>() || params.is_instance_of::<PyTuple>() {
+        return ParameterKind::Positional;
+    }
+    if params.is_instance_of::<PyString>()
+        || params.is_instance_of::<PyBytes>()
+        || params.is_instance_of::<PyByteArray>()
+    {
+        return ParameterKind::Single;
+    }
+    if params.cast::<PyMapping>().is_ok() {
+        return ParameterKind::Named;
+    }
+    if params.cast::<PySequence>().is_ok() || params.try_iter().is_ok() {
+        return ParameterKind::Positional;
+    }
+    ParameterKind::Single
+}
+
+/// Convert `execute()`-style parameters to the query to run and its
+/// positional values (see `parameter_kind`).
+pub(crate) fn process_parameters(
+    query: String,
+    params: &Bound<'_, PyAny>,
+    options: &BindOptions,
 ) -> PyResult<(String, Vec<SqliteParam>)> {
+    match parameter_kind(params) {
+        ParameterKind::Named => process_named_parameters(&query, params.cast()?, options),
+        ParameterKind::Positional => Ok((query, process_positional_parameters(params, options)?)),
+        ParameterKind::Single => Ok((query, vec![SqliteParam::from_py(params, options)?])),
+    }
+}
+
+/// `params` with the connection's default named parameters merged in: a
+/// mapping gets the defaults it doesn't set itself, and no parameters become
+/// the defaults. Positional and single-value parameters are returned as-is.
+pub(crate) fn merge_default_parameters<'py>(
+    params: Option<&Bound<'py, PyAny>>,
+    defaults: &Bound<'py, PyDict>,
+) -> PyResult<Option<Bound<'py, PyAny>>> {
+    let Some(params) = params else {
+        return Ok(Some(defaults.copy()?.into_any()));
+    };
+    if !matches!(parameter_kind(params), ParameterKind::Named) {
+        return Ok(Some(params.clone()));
+    }
+    let merged = defaults.copy()?;
+    merged.update(params.cast::<PyMapping>()?)?;
+    Ok(Some(merged.into_any()))
+}
+
+/// `params`, with a one-shot iterable such as a generator collected into a
+/// list so it can be stored and processed more than once.
+pub(crate) fn materialize_parameters<'py>(
+    params: &Bound<'py, PyAny>,
+) -> PyResult<Bound<'py, PyAny>> {
+    match parameter_kind(params) {
+        ParameterKind::Positional
+            if !params.is_instance_of::<PyList>() && params.cast::<PySequence>().is_err() =>
+        {
+            Ok(params.py().get_type::<PyList>().call1((params,))?)
+        }
+        _ => Ok(params.clone()),
+    }
+}
+
+/// Find the `:name`, `@name` and `$name` placeholders in `query` and replace
+/// them with `?`. Returns the rewritten query and the names in order.
+pub(crate) fn rewrite_named_placeholders(query: &str) -> (String, Vec<String>) {
     let mut processed_query = query.to_string();
-    let mut param_values = Vec::new();
 
     // Find all named parameter placeholders in order of appearance
     let mut param_placeholders: Vec<(usize, usize, String)> = Vec::new();
@@ -23,7 +99,7 @@ pub(crate) fn process_named_parameters(
         let ch = query_chars[i];
 
         // Check for :name, @name, or $name patterns
-        if (ch == ':' || ch == '@')
+        if (ch == ':' || ch == '@' || ch == '$')
             && i + 1 < query_chars.len()
             && (query_chars[i + 1].is_alphabetic() || query_chars[i + 1] == '_')
         {
@@ -41,27 +117,6 @@ pub(crate) fn process_named_parameters(
                 }
             }
 
-            if !name.is_empty() {
-                param_placeholders.push((start, i, name));
-            }
-        } else if ch == '$'
-            && i + 1 < query_chars.len()
-            && (query_chars[i + 1].is_alphabetic() || query_chars[i + 1] == '_')
-        {
-            let start = i;
-            i += 1; // Skip the $
-            let mut name = String::new();
-
-            while i < query_chars.len() {
-                let c = query_chars[i];
-                if c.is_alphanumeric() || c == '_' {
-                    name.push(c);
-                    i += 1;
-                } else {
-                    break;
-                }
-            }
-
             if !name.is_empty() {
                 param_placeholders.push((start, i, name));
             }
@@ -70,58 +125,49 @@ pub(crate) fn process_named_parameters(
         }
     }
 
-    // Replace named parameters with ? and collect values in order
-    // Process from end to start to avoid index shifting issues
-    for (start, end, name) in param_placeholders.into_iter().rev() {
-        if let Ok(Some(value)) = dict.get_item(name.as_str()) {
-            let sqlx_param = SqliteParam::from_py(&value)?;
-            param_values.push(sqlx_param);
+    // Replace named parameters with ?, from end to start to avoid index shifting issues
+    for (start, end, _) in param_placeholders.iter().rev() {
+        processed_query.replace_range(*start..*end, "?");
+    }
+    let names = param_placeholders
+        .into_iter()
+        .map(|(_, _, name)| name)
+        .collect();
+    (processed_query, names)
+}
 
-            // Replace the named parameter with ?
-            processed_query.replace_range(start..end, "?");
-        } else {
-            return Err(PyErr::new::<pyo3::exceptions::PyKeyError, _>(format!(
-                "Missing parameter: {name}"
-            )));
+/// Parse named parameters from SQL query and convert to positional.
+/// Returns the processed query with ? placeholders and ordered parameter values.
+pub(crate) fn process_named_parameters(
+    query: &str,
+    mapping: &Bound<'_, PyMapping>,
+    options: &BindOptions,
+) -> PyResult<(String, Vec<SqliteParam>)> {
+    let (processed_query, names) = rewrite_named_placeholders(query);
+    let mut param_values = Vec::with_capacity(names.len());
+    for name in names {
+        match mapping.get_item(name.as_str()) {
+            Ok(value) => param_values.push(SqliteParam::from_py(&value, options)?),
+            Err(e) if e.is_instance_of::<pyo3::exceptions::PyKeyError>(mapping.py()) => {
+                return Err(PyErr::new::<pyo3::exceptions::PyKeyError, _>(format!(
+                    "Missing parameter: {name}"
+                )));
+            }
+            Err(e) => return Err(e),
         }
     }
-
-    // Reverse to get correct order (we processed backwards)
-    param_values.reverse();
-
     Ok((processed_query, param_values))
 }
 
-/// Process positional parameters from a list/tuple.
+/// Process positional parameters from a list, tuple or other iterable.
 pub(crate) fn process_positional_parameters(
-    list: &Bound<'_, PyList>,
+    items: &Bound<'_, PyAny>,
+    options: &BindOptions,
 ) -> PyResult<Vec<SqliteParam>> {
     let mut param_values = Vec::new();
-    for item in list.iter() {
-        let param = SqliteParam::from_py(&item)?;
+    for item in items.try_iter()? {
+        let param = SqliteParam::from_py(&item?, options)?;
         param_values.push(param);
     }
     Ok(param_values)
 }
-
-/// Macro to bind a chain of parameters to a query builder.
-///
-/// Kept as a macro because sqlx binding is expressed via method-chaining; this macro
-/// generates the necessary bind chain for a fixed set of indices.
-macro_rules! bind_chain {
-    ($query:expr, $params:expr, $($idx:expr),*) => {
-        {
-            let q = sqlx::query($query);
-            $(
-                let q = match &$params[$idx] {
-                    SqliteParam::Null => q.bind(Option::<i64>::None),
-                    SqliteParam::Int(v) => q.bind(*v),
-                    SqliteParam::Real(v) => q.bind(*v),
-                    SqliteParam::Text(v) => q.bind(v.as_str()),
-                    SqliteParam::Blob(v) => q.bind(v.as_slice()),
-                };
-            )*
-            q
-        }
-    };
-}
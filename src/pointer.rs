@@ -0,0 +1,89 @@
+//! `rapsqlite.Pointer`: passes a Python object between SQL functions as a
+//! SQLite pointer value (`sqlite3_result_pointer` / `sqlite3_value_pointer`)
+//! instead of converting it to an SQL value.
+
+use libsqlite3_sys::{
+    sqlite3_context, sqlite3_result_pointer, sqlite3_value, sqlite3_value_pointer,
+};
+use pyo3::prelude::*;
+use std::ffi::{c_void, CStr};
+
+/// Pointer type of the values made from `Pointer`. A C or Rust function reads
+/// the borrowed `PyObject*` with `sqlite3_value_pointer(value, "rapsqlite.pyobject")`.
+pub(crate) const POINTER_TYPE: &CStr = c"rapsqlite.pyobject";
+
+/// Wraps a Python object returned from a user-defined function so it reaches
+/// the functions consuming the result as the same object, without being
+/// converted to an SQL value. Outside of function arguments a pointer value
+/// reads as NULL, so the object never ends up in a table or a result row.
+#[pyclass(frozen)]
+pub(crate) struct Pointer {
+    obj: Py<PyAny>,
+}
+
+#[pymethods]
+impl Pointer {
+    /// Wrap `obj` to be passed as a pointer value.
+    #[new]
+    fn new(obj: Py<PyAny>) -> Self {
+        Pointer { obj }
+    }
+
+    /// The wrapped object.
+    #[getter]
+    fn obj(&self, py: Python<'_>) -> Py<PyAny> {
+        self.obj.clone_ref(py)
+    }
+
+    fn __repr__(&self, py: Python<'_>) -> PyResult<String> {
+        Ok(format!("Pointer({})", self.obj.bind(py).repr()?))
+    }
+}
+
+impl Pointer {
+    /// The wrapped object when `value` is a `Pointer`.
+    pub(crate) fn unwrap(value: &Bound<'_, PyAny>) -> Option<Py<PyAny>> {
+        value
+            .cast::<Pointer>()
+            .ok()
+            .map(|pointer| pointer.get().obj.clone_ref(value.py()))
+    }
+}
+
+/// Address of the Python object carried by `value`, or None when `value` is
+/// not a pointer value made from `Pointer`. Does not need the GIL.
+pub(crate) unsafe fn value_object(value: *mut sqlite3_value) -> Option<usize> {
+    let object = sqlite3_value_pointer(value, POINTER_TYPE.as_ptr());
+    (!object.is_null()).then_some(object as usize)
+}
+
+/// New reference to the object at `address`, as returned by [`value_object`].
+///
+/// # Safety
+///
+/// The `sqlite3_value` the address came from must still be alive: it owns the
+/// reference that keeps the object alive.
+pub(crate) unsafe fn object_at(py: Python<'_>, address: usize) -> Py<PyAny> {
+    Py::from_borrowed_ptr(py, address as *mut pyo3::ffi::PyObject)
+}
+
+/// Set `obj` as the result of a user-defined function. SQLite owns the
+/// reference and releases it when the value is freed.
+pub(crate) unsafe fn result_object(ctx: *mut sqlite3_context, obj: Py<PyAny>) {
+    sqlite3_result_pointer(
+        ctx,
+        obj.into_ptr() as *mut c_void,
+        POINTER_TYPE.as_ptr(),
+        Some(release_object),
+    );
+}
+
+/// Destructor of pointer values; may run on any thread, so it takes the GIL.
+unsafe extern "C" fn release_object(object: *mut c_void) {
+    Python::attach(|py| {
+        drop(Py::<PyAny>::from_owned_ptr(
+            py,
+            object as *mut pyo3::ffi::PyObject,
+        ))
+    });
+}
@@ -2,37 +2,56 @@
 
 use pyo3::prelude::*;
 use pyo3_async_runtimes::tokio::into_future;
+use rapsqlite_core::authorizer::StatementPolicy;
+use rapsqlite_core::lifecycle::PoolListener;
+use rapsqlite_core::query::is_readonly;
+use rapsqlite_core::queue::{AcquireError, AcquireQueue};
+use rapsqlite_core::wal_archive::WalArchive;
+use rapsqlite_core::wal_hook::WalHook;
+use rapsqlite_core::{open_pool, Error as CoreError, PoolOptions};
 use sqlx::pool::PoolConnection;
-use sqlx::sqlite::SqlitePoolOptions;
 use sqlx::SqlitePool;
-use std::sync::{Arc, Mutex as StdMutex};
+use std::ffi::CString;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex as StdMutex, OnceLock, Weak};
 use std::time::Duration;
 use tokio::sync::Mutex;
 
+use crate::exceptions::PoolTimeout;
+use crate::lifecycle::EventHooks;
 use crate::types::{ProgressHandler, UserFunctions};
 use crate::OperationalError;
 
-/// Create a helpful error message for pool acquisition failures.
+/// Create a helpful error message for pool acquisition failures: PoolTimeout,
+/// with the queue depth and wait time, when the pool stayed saturated.
 pub(crate) fn pool_acquisition_error(
     path: &str,
-    error: &sqlx::Error,
+    error: &AcquireError,
     pool_size: Option<usize>,
     timeout: Option<u64>,
 ) -> PyErr {
-    let error_str = error.to_string();
-    let is_timeout = error_str.contains("timeout") || error_str.contains("timed out");
-
-    let mut msg = format!("Failed to acquire connection from pool at {path}: {error_str}");
+    let (depth, waited) = match error {
+        AcquireError::QueueFull { depth } => (*depth, Duration::ZERO),
+        AcquireError::Timeout { depth, waited } => (*depth, *waited),
+        AcquireError::Pool(_) => {
+            return OperationalError::new_err(format!(
+                "Failed to acquire connection from pool at {path}: {error}"
+            ))
+        }
+    };
 
-    if is_timeout {
-        msg.push_str("\n\nPossible solutions:");
-        msg.push_str("\n  - Increase pool_size (current: ");
-        msg.push_str(
-            &pool_size
-                .map(|s| s.to_string())
-                .unwrap_or_else(|| "1 (default)".to_string()),
-        );
-        msg.push(')');
+    let mut msg = format!("Failed to acquire connection from pool at {path}: {error}");
+    msg.push_str("\n\nPossible solutions:");
+    msg.push_str("\n  - Increase pool_size (current: ");
+    msg.push_str(
+        &pool_size
+            .map(|s| s.to_string())
+            .unwrap_or_else(|| "1 (default)".to_string()),
+    );
+    msg.push(')');
+    if matches!(error, AcquireError::QueueFull { .. }) {
+        msg.push_str("\n  - Increase max_queue, or retry after a backoff");
+    } else {
         msg.push_str("\n  - Increase connection_timeout (current: ");
         msg.push_str(
             &timeout
@@ -40,64 +59,305 @@ pub(crate) fn pool_acquisition_error(
                 .unwrap_or_else(|| "30s (default)".to_string()),
         );
         msg.push(')');
-        msg.push_str("\n  - Ensure connections are properly released (use async context managers)");
-        msg.push_str("\n  - Check for long-running transactions that hold connections");
     }
+    msg.push_str("\n  - Ensure connections are properly released (use async context managers)");
+    msg.push_str("\n  - Check for long-running transactions that hold connections");
+
+    Python::attach(|py| {
+        let err = PoolTimeout::new_err(msg);
+        let value = err.value(py);
+        let attributes = value
+            .setattr("queue_depth", depth)
+            .and_then(|()| value.setattr("wait_time", waited.as_secs_f64()));
+        match attributes {
+            Ok(()) => err,
+            Err(e) => e,
+        }
+    })
+}
+
+/// A Connection's pool, with the queue its tasks wait in for connections.
+/// Derefs to the `SqlitePool`; `acquire()` goes through the queue.
+#[derive(Clone)]
+pub(crate) struct QueuedPool {
+    pool: SqlitePool,
+    queue: Arc<AcquireQueue>,
+}
+
+impl QueuedPool {
+    pub(crate) fn new(pool: SqlitePool, queue: &Arc<AcquireQueue>) -> Self {
+        QueuedPool {
+            pool,
+            queue: Arc::clone(queue),
+        }
+    }
+
+    /// Acquire a connection, waiting in the queue.
+    pub(crate) async fn acquire(&self) -> Result<PoolConnection<sqlx::Sqlite>, AcquireError> {
+        self.queue.acquire(&self.pool).await
+    }
+
+    /// `acquire()` with failures mapped to Python exceptions for the
+    /// statement helpers, using the pool's own size and timeout.
+    pub(crate) async fn acquire_for(&self, path: &str) -> PyResult<PoolConnection<sqlx::Sqlite>> {
+        self.acquire().await.map_err(|e| {
+            let options = self.pool.options();
+            pool_acquisition_error(
+                path,
+                &e,
+                Some(options.get_max_connections() as usize),
+                Some(options.get_acquire_timeout().as_secs()),
+            )
+        })
+    }
+}
+
+impl std::ops::Deref for QueuedPool {
+    type Target = SqlitePool;
+
+    fn deref(&self) -> &SqlitePool {
+        &self.pool
+    }
+}
+
+/// Pool state shared by every `Connection(shared_pool=True)` on the same file
+/// with the same PRAGMAs, hardened-mode policy and clock. Each such Connection
+/// uses these slots in place of its own, so the pool is opened once and
+/// `pool_size` caps the connections of all of them together.
+pub(crate) struct SharedPool {
+    key: SharedPoolKey,
+    pub(crate) pool: Arc<Mutex<Option<SqlitePool>>>,
+    pub(crate) pool_size: Arc<StdMutex<Option<usize>>>,
+    pub(crate) connection_timeout_secs: Arc<StdMutex<Option<u64>>>,
+    pub(crate) event_hooks: Arc<EventHooks>,
+}
+
+#[derive(PartialEq)]
+struct SharedPoolKey {
+    path: PathBuf,
+    pragmas: Vec<(String, String)>,
+    policy: Option<StatementPolicy>,
+    native_functions: bool,
+    wal_archive: Option<PathBuf>,
+    vfs: Option<String>,
+}
+
+fn shared_pools() -> &'static StdMutex<Vec<Weak<SharedPool>>> {
+    static POOLS: OnceLock<StdMutex<Vec<Weak<SharedPool>>>> = OnceLock::new();
+    POOLS.get_or_init(Default::default)
+}
+
+/// The shared pool for `path` (canonicalized when the file exists) with these
+/// options, registering a new one if no live Connection uses it.
+pub(crate) fn shared_pool(
+    path: &str,
+    pragmas: &[(String, String)],
+    policy: Option<&StatementPolicy>,
+    native_functions: bool,
+    wal_archive: Option<&WalArchive>,
+    vfs: Option<&str>,
+) -> Arc<SharedPool> {
+    let key = SharedPoolKey {
+        path: std::fs::canonicalize(path).unwrap_or_else(|_| PathBuf::from(path)),
+        pragmas: pragmas.to_vec(),
+        policy: policy.cloned(),
+        native_functions,
+        wal_archive: wal_archive.map(|a| a.dir().to_path_buf()),
+        vfs: vfs.map(str::to_string),
+    };
+    let mut pools = shared_pools().lock().unwrap();
+    pools.retain(|p| p.strong_count() > 0);
+    if let Some(pool) = pools
+        .iter()
+        .filter_map(Weak::upgrade)
+        .find(|p| p.key == key)
+    {
+        return pool;
+    }
+    let pool = Arc::new(SharedPool {
+        key,
+        pool: Arc::new(Mutex::new(None)),
+        pool_size: Arc::new(StdMutex::new(None)),
+        connection_timeout_secs: Arc::new(StdMutex::new(None)),
+        event_hooks: Arc::new(EventHooks::new(path)),
+    });
+    pools.push(Arc::downgrade(&pool));
+    pool
+}
 
-    OperationalError::new_err(msg)
+/// The `WalArchive` for `dir`, shared by every Connection archiving into it so
+/// their hooks agree on what has been archived.
+pub(crate) fn wal_archive(dir: &str) -> PyResult<Arc<WalArchive>> {
+    static ARCHIVES: OnceLock<StdMutex<Vec<Weak<WalArchive>>>> = OnceLock::new();
+    let dir = std::fs::create_dir_all(dir)
+        .and_then(|_| std::fs::canonicalize(dir))
+        .map_err(|e| OperationalError::new_err(format!("Cannot use WAL archive {dir}: {e}")))?;
+    let mut archives = ARCHIVES.get_or_init(Default::default).lock().unwrap();
+    archives.retain(|a| a.strong_count() > 0);
+    if let Some(archive) = archives
+        .iter()
+        .filter_map(Weak::upgrade)
+        .find(|a| a.dir() == dir)
+    {
+        return Ok(archive);
+    }
+    let archive = Arc::new(WalArchive::open(&dir).map_err(|e| {
+        OperationalError::new_err(format!("Cannot use WAL archive {}: {e}", dir.display()))
+    })?);
+    archives.push(Arc::downgrade(&archive));
+    Ok(archive)
 }
 
 /// Helper to get or create pool and apply PRAGMAs.
+#[allow(clippy::too_many_arguments)]
 pub(crate) async fn get_or_create_pool(
     path: &str,
     pool: &Arc<Mutex<Option<SqlitePool>>>,
     pragmas: &Arc<StdMutex<Vec<(String, String)>>>,
     pool_size: &Arc<StdMutex<Option<usize>>>,
     connection_timeout_secs: &Arc<StdMutex<Option<u64>>>,
-) -> Result<SqlitePool, PyErr> {
+    statement_policy: &Option<Arc<StatementPolicy>>,
+    native_functions: bool,
+    wal_hook: &Option<Arc<WalHook>>,
+    vfs: &Option<String>,
+    event_hooks: &Arc<EventHooks>,
+) -> Result<QueuedPool, PyErr> {
     let mut pool_guard = pool.lock().await;
     if pool_guard.is_none() {
-        let max_conn = {
-            let g = pool_size.lock().unwrap();
-            (g.unwrap_or(1).max(1)) as u32
+        let options = PoolOptions {
+            max_connections: *pool_size.lock().unwrap(),
+            acquire_timeout_secs: *connection_timeout_secs.lock().unwrap(),
+            pragmas: pragmas.lock().unwrap().clone(),
+            policy: statement_policy.clone(),
+            native_functions,
+            wal_hook: wal_hook.clone(),
+            events: Some(Arc::clone(event_hooks) as Arc<dyn PoolListener>),
+            vfs: vfs.clone(),
+            read_only: false,
+            activity: Some(Arc::clone(event_hooks.activity())),
+            deadlines: true,
         };
-        let timeout_secs = {
-            let g = connection_timeout_secs.lock().unwrap();
-            *g
+        let new_pool = open_pool(path, &options)
+            .await
+            .map_err(|e| open_error(e, path))?;
+        *pool_guard = Some(new_pool);
+    }
+    // Safety: We just checked pool_guard.is_none() above and set it to Some if None.
+    // If it was already Some, we return it here. So unwrap() is safe.
+    Ok(QueuedPool::new(
+        pool_guard.as_ref().unwrap().clone(),
+        event_hooks.queue(),
+    ))
+}
+
+fn open_error(e: CoreError, path: &str) -> PyErr {
+    match e {
+        CoreError::Connect { .. } => OperationalError::new_err(e.to_string()),
+        CoreError::Query { query, source } => crate::map_sqlx_error(source, path, &query),
+        CoreError::Archive { .. } | CoreError::Backup { .. } | CoreError::Download { .. } => {
+            OperationalError::new_err(e.to_string())
+        }
+    }
+}
+
+/// The read-only pool of a `Connection(read_pool_size=...)`. Fetches whose
+/// statements only read run on it; everything else stays on the Connection's
+/// own pool, the writer. Opened on first use with the writer's settings, after
+/// the writer (which creates the file and applies the PRAGMAs that write it).
+pub(crate) struct ReadPool {
+    size: usize,
+    pool: Mutex<Option<SqlitePool>>,
+    pragmas: Arc<StdMutex<Vec<(String, String)>>>,
+    connection_timeout_secs: Arc<StdMutex<Option<u64>>>,
+    statement_policy: Option<Arc<StatementPolicy>>,
+    native_functions: bool,
+    vfs: Option<String>,
+    event_hooks: Arc<EventHooks>,
+}
+
+impl ReadPool {
+    pub(crate) fn new(
+        size: usize,
+        pragmas: &Arc<StdMutex<Vec<(String, String)>>>,
+        connection_timeout_secs: &Arc<StdMutex<Option<u64>>>,
+        statement_policy: &Option<Arc<StatementPolicy>>,
+        native_functions: bool,
+        vfs: &Option<String>,
+        event_hooks: &Arc<EventHooks>,
+    ) -> Self {
+        ReadPool {
+            size,
+            pool: Mutex::new(None),
+            pragmas: Arc::clone(pragmas),
+            connection_timeout_secs: Arc::clone(connection_timeout_secs),
+            statement_policy: statement_policy.clone(),
+            native_functions,
+            vfs: vfs.clone(),
+            event_hooks: Arc::clone(event_hooks),
+        }
+    }
+
+    async fn get_or_open(&self, path: &str) -> PyResult<QueuedPool> {
+        let mut pool_guard = self.pool.lock().await;
+        if let Some(pool) = pool_guard.as_ref() {
+            return Ok(QueuedPool::new(pool.clone(), self.event_hooks.queue()));
+        }
+        let options = PoolOptions {
+            max_connections: Some(self.size),
+            acquire_timeout_secs: *self.connection_timeout_secs.lock().unwrap(),
+            pragmas: self.pragmas.lock().unwrap().clone(),
+            policy: self.statement_policy.clone(),
+            native_functions: self.native_functions,
+            wal_hook: None,
+            events: Some(Arc::clone(&self.event_hooks) as Arc<dyn PoolListener>),
+            vfs: self.vfs.clone(),
+            read_only: true,
+            activity: Some(Arc::clone(self.event_hooks.activity())),
+            deadlines: true,
         };
-        let mut opts = SqlitePoolOptions::new().max_connections(max_conn);
-        // Set default timeout of 30 seconds if not specified
-        let timeout = timeout_secs.unwrap_or(30);
-        opts = opts.acquire_timeout(Duration::from_secs(timeout));
-        let new_pool = opts.connect(&format!("sqlite:{path}")).await.map_err(|e| {
-            OperationalError::new_err(format!("Failed to connect to database at {path}: {e}"))
-        })?;
+        let pool = open_pool(path, &options)
+            .await
+            .map_err(|e| open_error(e, path))?;
+        *pool_guard = Some(pool.clone());
+        Ok(QueuedPool::new(pool, self.event_hooks.queue()))
+    }
 
-        // Apply PRAGMAs
-        let pragmas_list = {
-            let pragmas_guard = pragmas.lock().unwrap();
-            pragmas_guard.clone()
+    /// The pool to run `query` on: this one if every statement in it only
+    /// reads, `writer` otherwise. SQL that fails to prepare goes to `writer`,
+    /// which reports the error.
+    pub(crate) async fn route(
+        &self,
+        path: &str,
+        writer: QueuedPool,
+        query: &str,
+    ) -> PyResult<QueuedPool> {
+        let Ok(sql) = CString::new(query) else {
+            return Ok(writer);
         };
+        let reader = self.get_or_open(path).await?;
+        let mut conn = reader.acquire().await.map_err(|e| {
+            pool_acquisition_error(
+                path,
+                &e,
+                Some(self.size),
+                *self.connection_timeout_secs.lock().unwrap(),
+            )
+        })?;
+        let mut handle = conn
+            .lock_handle()
+            .await
+            .map_err(|e| crate::map_sqlx_error(e, path, query))?;
+        // Safety: the handle lock gives exclusive use of the connection.
+        let readonly = unsafe { is_readonly(handle.as_raw_handle().as_ptr(), &sql) };
+        Ok(if readonly == Ok(true) { reader } else { writer })
+    }
 
-        for (name, value) in pragmas_list {
-            // Safety: PRAGMA names and values come from user input (via pragmas parameter or URI).
-            // SQLite's PRAGMA parser will reject invalid syntax, providing protection against
-            // SQL injection. PRAGMA names are identifiers (alphanumeric + underscore), and
-            // values are typically simple (strings, integers, keywords). While not perfect,
-            // SQLite's parser provides reasonable protection. For maximum security, applications
-            // should validate PRAGMA names against a whitelist.
-            let pragma_query = format!("PRAGMA {name} = {value}");
-            sqlx::query(&pragma_query)
-                .execute(&new_pool)
-                .await
-                .map_err(|e| crate::map_sqlx_error(e, path, &pragma_query))?;
+    /// Close the pool; it reopens on next use.
+    pub(crate) async fn close(&self) {
+        if let Some(pool) = self.pool.lock().await.take() {
+            pool.close().await;
         }
-
-        *pool_guard = Some(new_pool);
     }
-    // Safety: We just checked pool_guard.is_none() above and set it to Some if None.
-    // If it was already Some, we return it here. So unwrap() is safe.
-    Ok(pool_guard.as_ref().unwrap().clone())
 }
 
 /// Helper to ensure callback connection exists.
@@ -105,6 +365,7 @@ pub(crate) async fn get_or_create_pool(
 /// The connection is stored in the callback_connection mutex and should be accessed via that mutex.
 /// Note: Accessing the raw sqlite3* handle from PoolConnection requires further research
 /// into sqlx 0.8's API. This is a known limitation that needs to be resolved.
+#[allow(clippy::too_many_arguments)]
 pub(crate) async fn ensure_callback_connection(
     path: &str,
     pool: &Arc<Mutex<Option<SqlitePool>>>,
@@ -112,12 +373,28 @@ pub(crate) async fn ensure_callback_connection(
     pragmas: &Arc<StdMutex<Vec<(String, String)>>>,
     pool_size: &Arc<StdMutex<Option<usize>>>,
     connection_timeout_secs: &Arc<StdMutex<Option<u64>>>,
+    statement_policy: &Option<Arc<StatementPolicy>>,
+    native_functions: bool,
+    wal_hook: &Option<Arc<WalHook>>,
+    vfs: &Option<String>,
+    event_hooks: &Arc<EventHooks>,
 ) -> Result<(), PyErr> {
     let mut callback_guard = callback_connection.lock().await;
     if callback_guard.is_none() {
         // Get or create pool first
-        let pool_clone =
-            get_or_create_pool(path, pool, pragmas, pool_size, connection_timeout_secs).await?;
+        let pool_clone = get_or_create_pool(
+            path,
+            pool,
+            pragmas,
+            pool_size,
+            connection_timeout_secs,
+            statement_policy,
+            native_functions,
+            wal_hook,
+            vfs,
+            event_hooks,
+        )
+        .await?;
 
         // Acquire a connection from the pool
         let pool_size_val = {
@@ -208,6 +485,7 @@ pub(crate) fn has_callbacks(
     trace_callback: &Arc<StdMutex<Option<Py<PyAny>>>>,
     authorizer_callback: &Arc<StdMutex<Option<Py<PyAny>>>>,
     progress_handler: &ProgressHandler,
+    update_hook: &Arc<StdMutex<Option<Py<PyAny>>>>,
 ) -> bool {
     // Safety: StdMutex::lock() only fails if the mutex is poisoned (another thread panicked).
     // In Python's GIL context and with proper error handling, this is extremely unlikely.
@@ -217,6 +495,7 @@ pub(crate) fn has_callbacks(
     let has_trace = trace_callback.lock().unwrap().is_some();
     let has_authorizer = authorizer_callback.lock().unwrap().is_some();
     let has_progress = progress_handler.lock().unwrap().is_some();
+    let has_update_hook = update_hook.lock().unwrap().is_some();
 
-    load_ext || has_functions || has_trace || has_authorizer || has_progress
+    load_ext || has_functions || has_trace || has_authorizer || has_progress || has_update_hook
 }
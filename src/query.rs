@@ -1,746 +1,221 @@
-//! Query execution/fetch helpers built on top of sqlx.
+//! Query execution/fetch helpers: `rapsqlite_core::query` with sqlx errors mapped
+//! to Python exceptions (the database path and query are used for the message).
+//! Every statement is also reported to the result caches for the path, since
+//! even a failed `OR FAIL` write may leave changes behind, and none starts once
+//! the task's `rapsqlite.deadline()` has passed. The pool helpers wait for a
+//! connection in the Connection's queue (see `QueuedPool`).
 
 use pyo3::prelude::*;
+use rapsqlite_core::query::{self as core, FetchError, FetchLimits};
 use sqlx::pool::PoolConnection;
-use sqlx::SqlitePool;
 
+use crate::pool::QueuedPool;
 use crate::types::SqliteParam;
+use crate::ProgrammingError;
 
 /// Bind parameters to a query and execute it.
-/// This helper binds parameters dynamically to a sqlx query builder.
 pub(crate) async fn bind_and_execute(
     query: &str,
     params: &[SqliteParam],
-    pool: &SqlitePool,
+    pool: &QueuedPool,
     path: &str,
 ) -> Result<sqlx::sqlite::SqliteQueryResult, PyErr> {
-    // Build query with bound parameters
-    // sqlx uses method chaining, so we need to handle this carefully
-    // For now, we'll use a match statement for common parameter counts
-    // and fall back to building the query string with embedded values for larger counts
-
-    let result = match params.len() {
-        0 => sqlx::query(query).execute(pool).await,
-        1 => match &params[0] {
-            SqliteParam::Null => {
-                sqlx::query(query)
-                    .bind(Option::<i64>::None)
-                    .execute(pool)
-                    .await
-            }
-            SqliteParam::Int(v) => sqlx::query(query).bind(*v).execute(pool).await,
-            SqliteParam::Real(v) => sqlx::query(query).bind(*v).execute(pool).await,
-            SqliteParam::Text(v) => sqlx::query(query).bind(v.as_str()).execute(pool).await,
-            SqliteParam::Blob(v) => sqlx::query(query).bind(v.as_slice()).execute(pool).await,
-        },
-        _ => {
-            // For multiple parameters, we need to chain binds
-            // This is complex with sqlx's API, so we'll use a workaround:
-            // Build the query with parameters bound sequentially
-            // Since sqlx's bind chains are compile-time, we'll handle common cases
-            // and use a helper that builds the query properly
-
-            // For now, let's handle up to 50 parameters (which should cover most cases)
-            // using a helper that chains binds
-            bind_query_multiple(query, params, pool).await
-        }
-    };
-
-    result.map_err(|e| crate::map_sqlx_error(e, path, query))
+    crate::deadline::check(path, query)?;
+    let mut conn = pool.acquire_for(path).await?;
+    bind_and_execute_on_connection(query, params, &mut conn, path).await
 }
 
-/// Helper to bind parameters and execute on a specific connection.
-/// Similar to bind_and_execute but takes a PoolConnection instead of Pool.
+/// Bind parameters and execute on a specific connection.
 pub(crate) async fn bind_and_execute_on_connection(
     query: &str,
     params: &[SqliteParam],
     conn: &mut PoolConnection<sqlx::Sqlite>,
     path: &str,
 ) -> Result<sqlx::sqlite::SqliteQueryResult, PyErr> {
-    // Use &mut **conn to access the underlying connection that implements Executor
-    let result = match params.len() {
-        0 => sqlx::query(query).execute(&mut **conn).await,
-        1 => match &params[0] {
-            SqliteParam::Null => {
-                sqlx::query(query)
-                    .bind(Option::<i64>::None)
-                    .execute(&mut **conn)
-                    .await
-            }
-            SqliteParam::Int(v) => sqlx::query(query).bind(*v).execute(&mut **conn).await,
-            SqliteParam::Real(v) => sqlx::query(query).bind(*v).execute(&mut **conn).await,
-            SqliteParam::Text(v) => {
-                sqlx::query(query)
-                    .bind(v.as_str())
-                    .execute(&mut **conn)
-                    .await
-            }
-            SqliteParam::Blob(v) => {
-                sqlx::query(query)
-                    .bind(v.as_slice())
-                    .execute(&mut **conn)
-                    .await
-            }
-        },
-        _ => {
-            // For multiple parameters, use bind_query_multiple_on_connection
-            bind_query_multiple_on_connection(query, params, conn).await
-        }
-    };
-
+    crate::deadline::check(path, query)?;
+    let result = core::bind_and_execute_on_connection(query, params, conn).await;
+    crate::result_cache::note_statement(path, query);
     result.map_err(|e| crate::map_sqlx_error(e, path, query))
 }
 
-/// Helper to bind multiple parameters to a query and execute on a connection.
-pub(crate) async fn bind_query_multiple_on_connection(
+/// Bind parameters and execute on a specific connection, collecting the rows
+/// it returns.
+pub(crate) async fn bind_and_execute_returning_on_connection(
     query: &str,
     params: &[SqliteParam],
     conn: &mut PoolConnection<sqlx::Sqlite>,
-) -> Result<sqlx::sqlite::SqliteQueryResult, sqlx::Error> {
-    if params.is_empty() {
-        return sqlx::query(query).execute(&mut **conn).await;
-    }
+    path: &str,
+) -> Result<
+    (
+        sqlx::sqlite::SqliteQueryResult,
+        Vec<sqlx::sqlite::SqliteRow>,
+    ),
+    PyErr,
+> {
+    crate::deadline::check(path, query)?;
+    let result = core::bind_and_execute_returning_on_connection(query, params, conn).await;
+    crate::result_cache::note_statement(path, query);
+    result.map_err(|e| crate::map_sqlx_error(e, path, query))
+}
 
-    if params.len() > 50 {
-        return Err(sqlx::Error::Protocol(format!(
-            "Too many parameters ({}). Currently supporting up to 50 parameters.",
-            params.len()
-        )));
+/// Whether `query` returns rows to fetch rather than running for its effect.
+pub(crate) async fn returns_rows(query: &str, pool: &QueuedPool, path: &str) -> PyResult<bool> {
+    // No connection is needed when the text tells
+    if let Some(returns_rows) = rapsqlite_core::sql::returns_rows_hint(query) {
+        return Ok(returns_rows);
     }
-
-    // Match on parameter count and use the macro to generate the bind chain
-    let query_builder = match params.len() {
-        1 => bind_chain!(query, params, 0),
-        2 => bind_chain!(query, params, 0, 1),
-        3 => bind_chain!(query, params, 0, 1, 2),
-        4 => bind_chain!(query, params, 0, 1, 2, 3),
-        5 => bind_chain!(query, params, 0, 1, 2, 3, 4),
-        6 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5),
-        7 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6),
-        8 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6, 7),
-        9 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8),
-        10 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9),
-        11 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10),
-        12 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11),
-        13 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12),
-        14 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13),
-        15 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14),
-        16 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15),
-        17 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16),
-        18 => {
-            bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17)
-        }
-        19 => bind_chain!(
-            query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18
-        ),
-        20 => bind_chain!(
-            query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19
-        ),
-        21 => bind_chain!(
-            query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20
-        ),
-        22 => bind_chain!(
-            query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19,
-            20, 21
-        ),
-        23 => bind_chain!(
-            query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19,
-            20, 21, 22
-        ),
-        24 => bind_chain!(
-            query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19,
-            20, 21, 22, 23
-        ),
-        25 => bind_chain!(
-            query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19,
-            20, 21, 22, 23, 24
-        ),
-        26 => bind_chain!(
-            query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19,
-            20, 21, 22, 23, 24, 25
-        ),
-        27 => bind_chain!(
-            query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19,
-            20, 21, 22, 23, 24, 25, 26
-        ),
-        28 => bind_chain!(
-            query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19,
-            20, 21, 22, 23, 24, 25, 26, 27
-        ),
-        29 => bind_chain!(
-            query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19,
-            20, 21, 22, 23, 24, 25, 26, 27, 28
-        ),
-        30 => bind_chain!(
-            query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19,
-            20, 21, 22, 23, 24, 25, 26, 27, 28, 29
-        ),
-        31 => bind_chain!(
-            query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19,
-            20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30
-        ),
-        32 => bind_chain!(
-            query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19,
-            20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31
-        ),
-        33 => bind_chain!(
-            query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19,
-            20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32
-        ),
-        34 => bind_chain!(
-            query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19,
-            20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32, 33
-        ),
-        35 => bind_chain!(
-            query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19,
-            20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32, 33, 34
-        ),
-        36 => bind_chain!(
-            query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19,
-            20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32, 33, 34, 35
-        ),
-        37 => bind_chain!(
-            query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19,
-            20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32, 33, 34, 35, 36
-        ),
-        38 => bind_chain!(
-            query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19,
-            20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32, 33, 34, 35, 36, 37
-        ),
-        39 => bind_chain!(
-            query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19,
-            20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32, 33, 34, 35, 36, 37, 38
-        ),
-        40 => bind_chain!(
-            query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19,
-            20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32, 33, 34, 35, 36, 37, 38, 39
-        ),
-        41 => bind_chain!(
-            query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19,
-            20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32, 33, 34, 35, 36, 37, 38, 39, 40
-        ),
-        42 => bind_chain!(
-            query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19,
-            20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32, 33, 34, 35, 36, 37, 38, 39, 40, 41
-        ),
-        43 => bind_chain!(
-            query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19,
-            20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32, 33, 34, 35, 36, 37, 38, 39, 40, 41,
-            42
-        ),
-        44 => bind_chain!(
-            query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19,
-            20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32, 33, 34, 35, 36, 37, 38, 39, 40, 41,
-            42, 43
-        ),
-        45 => bind_chain!(
-            query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19,
-            20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32, 33, 34, 35, 36, 37, 38, 39, 40, 41,
-            42, 43, 44
-        ),
-        46 => bind_chain!(
-            query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19,
-            20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32, 33, 34, 35, 36, 37, 38, 39, 40, 41,
-            42, 43, 44, 45
-        ),
-        47 => bind_chain!(
-            query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19,
-            20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32, 33, 34, 35, 36, 37, 38, 39, 40, 41,
-            42, 43, 44, 45, 46
-        ),
-        48 => bind_chain!(
-            query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19,
-            20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32, 33, 34, 35, 36, 37, 38, 39, 40, 41,
-            42, 43, 44, 45, 46, 47
-        ),
-        49 => bind_chain!(
-            query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19,
-            20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32, 33, 34, 35, 36, 37, 38, 39, 40, 41,
-            42, 43, 44, 45, 46, 47, 48
-        ),
-        50 => bind_chain!(
-            query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19,
-            20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32, 33, 34, 35, 36, 37, 38, 39, 40, 41,
-            42, 43, 44, 45, 46, 47, 48, 49
-        ),
-        _ => unreachable!(), // Already checked above
-    };
-
-    query_builder.execute(&mut **conn).await
+    let mut conn = pool.acquire_for(path).await?;
+    returns_rows_on_connection(query, &mut conn, path).await
 }
 
-/// Helper to bind multiple parameters to a query and execute it.
-/// Handles up to 50 parameters using explicit bind chains.
-pub(crate) async fn bind_query_multiple(
+/// Whether `query` returns rows, deciding on a specific connection.
+pub(crate) async fn returns_rows_on_connection(
     query: &str,
-    params: &[SqliteParam],
-    pool: &SqlitePool,
-) -> Result<sqlx::sqlite::SqliteQueryResult, sqlx::Error> {
-    if params.is_empty() {
-        return sqlx::query(query).execute(pool).await;
-    }
-
-    if params.len() > 50 {
-        return Err(sqlx::Error::Protocol(format!(
-            "Too many parameters ({}). Currently supporting up to 50 parameters.",
-            params.len()
-        )));
-    }
-
-    // Match on parameter count and use the macro to generate the bind chain
-    let query_builder = match params.len() {
-        1 => bind_chain!(query, params, 0),
-        2 => bind_chain!(query, params, 0, 1),
-        3 => bind_chain!(query, params, 0, 1, 2),
-        4 => bind_chain!(query, params, 0, 1, 2, 3),
-        5 => bind_chain!(query, params, 0, 1, 2, 3, 4),
-        6 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5),
-        7 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6),
-        8 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6, 7),
-        9 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8),
-        10 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9),
-        11 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10),
-        12 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11),
-        13 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12),
-        14 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13),
-        15 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14),
-        16 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15),
-        17 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16),
-        18 => {
-            bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17)
-        }
-        19 => bind_chain!(
-            query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18
-        ),
-        20 => bind_chain!(
-            query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19
-        ),
-        21 => bind_chain!(
-            query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20
-        ),
-        22 => bind_chain!(
-            query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19,
-            20, 21
-        ),
-        23 => bind_chain!(
-            query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19,
-            20, 21, 22
-        ),
-        24 => bind_chain!(
-            query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19,
-            20, 21, 22, 23
-        ),
-        25 => bind_chain!(
-            query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19,
-            20, 21, 22, 23, 24
-        ),
-        26 => bind_chain!(
-            query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19,
-            20, 21, 22, 23, 24, 25
-        ),
-        27 => bind_chain!(
-            query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19,
-            20, 21, 22, 23, 24, 25, 26
-        ),
-        28 => bind_chain!(
-            query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19,
-            20, 21, 22, 23, 24, 25, 26, 27
-        ),
-        29 => bind_chain!(
-            query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19,
-            20, 21, 22, 23, 24, 25, 26, 27, 28
-        ),
-        30 => bind_chain!(
-            query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19,
-            20, 21, 22, 23, 24, 25, 26, 27, 28, 29
-        ),
-        31 => bind_chain!(
-            query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19,
-            20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30
-        ),
-        32 => bind_chain!(
-            query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19,
-            20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31
-        ),
-        33 => bind_chain!(
-            query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19,
-            20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32
-        ),
-        34 => bind_chain!(
-            query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19,
-            20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32, 33
-        ),
-        35 => bind_chain!(
-            query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19,
-            20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32, 33, 34
-        ),
-        36 => bind_chain!(
-            query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19,
-            20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32, 33, 34, 35
-        ),
-        37 => bind_chain!(
-            query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19,
-            20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32, 33, 34, 35, 36
-        ),
-        38 => bind_chain!(
-            query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19,
-            20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32, 33, 34, 35, 36, 37
-        ),
-        39 => bind_chain!(
-            query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19,
-            20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32, 33, 34, 35, 36, 37, 38
-        ),
-        40 => bind_chain!(
-            query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19,
-            20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32, 33, 34, 35, 36, 37, 38, 39
-        ),
-        41 => bind_chain!(
-            query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19,
-            20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32, 33, 34, 35, 36, 37, 38, 39, 40
-        ),
-        42 => bind_chain!(
-            query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19,
-            20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32, 33, 34, 35, 36, 37, 38, 39, 40, 41
-        ),
-        43 => bind_chain!(
-            query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19,
-            20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32, 33, 34, 35, 36, 37, 38, 39, 40, 41,
-            42
-        ),
-        44 => bind_chain!(
-            query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19,
-            20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32, 33, 34, 35, 36, 37, 38, 39, 40, 41,
-            42, 43
-        ),
-        45 => bind_chain!(
-            query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19,
-            20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32, 33, 34, 35, 36, 37, 38, 39, 40, 41,
-            42, 43, 44
-        ),
-        46 => bind_chain!(
-            query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19,
-            20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32, 33, 34, 35, 36, 37, 38, 39, 40, 41,
-            42, 43, 44, 45
-        ),
-        47 => bind_chain!(
-            query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19,
-            20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32, 33, 34, 35, 36, 37, 38, 39, 40, 41,
-            42, 43, 44, 45, 46
-        ),
-        48 => bind_chain!(
-            query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19,
-            20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32, 33, 34, 35, 36, 37, 38, 39, 40, 41,
-            42, 43, 44, 45, 46, 47
-        ),
-        49 => bind_chain!(
-            query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19,
-            20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32, 33, 34, 35, 36, 37, 38, 39, 40, 41,
-            42, 43, 44, 45, 46, 47, 48
-        ),
-        50 => bind_chain!(
-            query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19,
-            20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32, 33, 34, 35, 36, 37, 38, 39, 40, 41,
-            42, 43, 44, 45, 46, 47, 48, 49
-        ),
-        _ => unreachable!(), // Already checked above
-    };
-
-    query_builder.execute(pool).await
+    conn: &mut PoolConnection<sqlx::Sqlite>,
+    path: &str,
+) -> PyResult<bool> {
+    core::returns_rows_on_connection(query, conn)
+        .await
+        .map_err(|e| crate::map_sqlx_error(e, path, query))
 }
 
-/// Helper to bind parameters and fetch all rows.
+/// Bind parameters and fetch all rows.
 pub(crate) async fn bind_and_fetch_all(
     query: &str,
     params: &[SqliteParam],
-    pool: &SqlitePool,
+    pool: &QueuedPool,
     path: &str,
 ) -> Result<Vec<sqlx::sqlite::SqliteRow>, PyErr> {
-    if params.is_empty() {
-        return sqlx::query(query)
-            .fetch_all(pool)
-            .await
-            .map_err(|e| crate::map_sqlx_error(e, path, query));
-    }
-
-    if params.len() > 16 {
-        return Err(crate::map_sqlx_error(
-            sqlx::Error::Protocol(format!(
-                "Too many parameters ({}). Currently supporting up to 50 parameters.",
-                params.len()
-            )),
-            path,
-            query,
-        ));
-    }
-
-    let query_builder = match params.len() {
-        1 => bind_chain!(query, params, 0),
-        2 => bind_chain!(query, params, 0, 1),
-        3 => bind_chain!(query, params, 0, 1, 2),
-        4 => bind_chain!(query, params, 0, 1, 2, 3),
-        5 => bind_chain!(query, params, 0, 1, 2, 3, 4),
-        6 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5),
-        7 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6),
-        8 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6, 7),
-        9 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8),
-        10 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9),
-        11 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10),
-        12 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11),
-        13 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12),
-        14 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13),
-        15 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14),
-        16 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15),
-        _ => unreachable!(),
-    };
-
-    query_builder
-        .fetch_all(pool)
-        .await
-        .map_err(|e| crate::map_sqlx_error(e, path, query))
+    crate::deadline::check(path, query)?;
+    let mut conn = pool.acquire_for(path).await?;
+    bind_and_fetch_all_on_connection(query, params, &mut conn, path).await
 }
 
-/// Helper to bind parameters and fetch one row.
-pub(crate) async fn bind_and_fetch_one(
+/// Bind parameters and fetch all rows, raising ResultTooLarge once the result
+/// passes `limits`.
+pub(crate) async fn bind_and_fetch_all_limited(
     query: &str,
     params: &[SqliteParam],
-    pool: &SqlitePool,
+    pool: &QueuedPool,
     path: &str,
-) -> Result<sqlx::sqlite::SqliteRow, PyErr> {
-    if params.is_empty() {
-        return sqlx::query(query)
-            .fetch_one(pool)
-            .await
-            .map_err(|e| crate::map_sqlx_error(e, path, query));
-    }
+    limits: FetchLimits,
+) -> Result<Vec<sqlx::sqlite::SqliteRow>, PyErr> {
+    crate::deadline::check(path, query)?;
+    let mut conn = pool.acquire_for(path).await?;
+    bind_and_fetch_all_limited_on_connection(query, params, &mut conn, path, limits).await
+}
 
-    if params.len() > 16 {
-        return Err(crate::map_sqlx_error(
-            sqlx::Error::Protocol(format!(
-                "Too many parameters ({}). Currently supporting up to 50 parameters.",
-                params.len()
-            )),
-            path,
-            query,
-        ));
-    }
+/// At most one row: `fetch_one()`/`fetch_optional()` reject a second one.
+const SINGLE_ROW: FetchLimits = FetchLimits {
+    max_rows: Some(1),
+    max_bytes: None,
+};
 
-    let query_builder = match params.len() {
-        1 => bind_chain!(query, params, 0),
-        2 => bind_chain!(query, params, 0, 1),
-        3 => bind_chain!(query, params, 0, 1, 2),
-        4 => bind_chain!(query, params, 0, 1, 2, 3),
-        5 => bind_chain!(query, params, 0, 1, 2, 3, 4),
-        6 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5),
-        7 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6),
-        8 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6, 7),
-        9 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8),
-        10 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9),
-        11 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10),
-        12 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11),
-        13 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12),
-        14 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13),
-        15 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14),
-        16 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15),
-        _ => unreachable!(),
-    };
+fn single_row(
+    result: Result<Vec<sqlx::sqlite::SqliteRow>, FetchError>,
+    path: &str,
+    query: &str,
+) -> Result<Option<sqlx::sqlite::SqliteRow>, PyErr> {
+    match result {
+        Ok(mut rows) => Ok(rows.pop()),
+        Err(FetchError::TooManyRows { .. }) => Err(ProgrammingError::new_err(format!(
+            "Query on database {path} returned more than one row; \
+             use fetch_first() to take the first row\nQuery: {query}"
+        ))),
+        Err(e) => Err(crate::errors::map_fetch_error(e, path, query)),
+    }
+}
 
-    query_builder
-        .fetch_one(pool)
-        .await
-        .map_err(|e| crate::map_sqlx_error(e, path, query))
+/// Bind parameters and fetch zero or one row, raising ProgrammingError when
+/// the query returns more. Only the first two rows are read.
+pub(crate) async fn bind_and_fetch_single(
+    query: &str,
+    params: &[SqliteParam],
+    pool: &QueuedPool,
+    path: &str,
+) -> Result<Option<sqlx::sqlite::SqliteRow>, PyErr> {
+    crate::deadline::check(path, query)?;
+    let mut conn = pool.acquire_for(path).await?;
+    bind_and_fetch_single_on_connection(query, params, &mut conn, path).await
 }
 
-/// Helper to bind parameters and fetch optional row.
+/// Bind parameters and fetch an optional row.
 pub(crate) async fn bind_and_fetch_optional(
     query: &str,
     params: &[SqliteParam],
-    pool: &SqlitePool,
+    pool: &QueuedPool,
     path: &str,
 ) -> Result<Option<sqlx::sqlite::SqliteRow>, PyErr> {
-    if params.is_empty() {
-        return sqlx::query(query)
-            .fetch_optional(pool)
-            .await
-            .map_err(|e| crate::map_sqlx_error(e, path, query));
-    }
-
-    if params.len() > 16 {
-        return Err(crate::map_sqlx_error(
-            sqlx::Error::Protocol(format!(
-                "Too many parameters ({}). Currently supporting up to 50 parameters.",
-                params.len()
-            )),
-            path,
-            query,
-        ));
-    }
-
-    let query_builder = match params.len() {
-        1 => bind_chain!(query, params, 0),
-        2 => bind_chain!(query, params, 0, 1),
-        3 => bind_chain!(query, params, 0, 1, 2),
-        4 => bind_chain!(query, params, 0, 1, 2, 3),
-        5 => bind_chain!(query, params, 0, 1, 2, 3, 4),
-        6 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5),
-        7 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6),
-        8 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6, 7),
-        9 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8),
-        10 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9),
-        11 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10),
-        12 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11),
-        13 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12),
-        14 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13),
-        15 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14),
-        16 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15),
-        _ => unreachable!(),
-    };
-
-    query_builder
-        .fetch_optional(pool)
-        .await
-        .map_err(|e| crate::map_sqlx_error(e, path, query))
+    crate::deadline::check(path, query)?;
+    let mut conn = pool.acquire_for(path).await?;
+    bind_and_fetch_optional_on_connection(query, params, &mut conn, path).await
 }
 
-/// Helper to bind parameters and fetch all rows on a specific connection.
+/// Bind parameters and fetch all rows on a specific connection.
 pub(crate) async fn bind_and_fetch_all_on_connection(
     query: &str,
     params: &[SqliteParam],
     conn: &mut PoolConnection<sqlx::Sqlite>,
     path: &str,
 ) -> Result<Vec<sqlx::sqlite::SqliteRow>, PyErr> {
-    if params.is_empty() {
-        return sqlx::query(query)
-            .fetch_all(&mut **conn)
-            .await
-            .map_err(|e| crate::map_sqlx_error(e, path, query));
-    }
-    if params.len() > 16 {
-        return Err(crate::map_sqlx_error(
-            sqlx::Error::Protocol(format!(
-                "Too many parameters ({}). Currently supporting up to 50 parameters.",
-                params.len()
-            )),
-            path,
-            query,
-        ));
-    }
-    let query_builder = match params.len() {
-        1 => bind_chain!(query, params, 0),
-        2 => bind_chain!(query, params, 0, 1),
-        3 => bind_chain!(query, params, 0, 1, 2),
-        4 => bind_chain!(query, params, 0, 1, 2, 3),
-        5 => bind_chain!(query, params, 0, 1, 2, 3, 4),
-        6 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5),
-        7 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6),
-        8 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6, 7),
-        9 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8),
-        10 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9),
-        11 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10),
-        12 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11),
-        13 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12),
-        14 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13),
-        15 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14),
-        16 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15),
-        _ => unreachable!(),
-    };
-    query_builder
-        .fetch_all(&mut **conn)
-        .await
-        .map_err(|e| crate::map_sqlx_error(e, path, query))
+    crate::deadline::check(path, query)?;
+    let result = core::bind_and_fetch_all_on_connection(query, params, conn).await;
+    crate::result_cache::note_statement(path, query);
+    result.map_err(|e| crate::map_sqlx_error(e, path, query))
 }
 
-/// Helper to bind parameters and fetch one row on a specific connection.
-pub(crate) async fn bind_and_fetch_one_on_connection(
+/// Bind parameters and fetch all rows on a specific connection, raising
+/// ResultTooLarge once the result passes `limits`.
+pub(crate) async fn bind_and_fetch_all_limited_on_connection(
     query: &str,
     params: &[SqliteParam],
     conn: &mut PoolConnection<sqlx::Sqlite>,
     path: &str,
-) -> Result<sqlx::sqlite::SqliteRow, PyErr> {
-    if params.is_empty() {
-        return sqlx::query(query)
-            .fetch_one(&mut **conn)
-            .await
-            .map_err(|e| crate::map_sqlx_error(e, path, query));
-    }
-    if params.len() > 16 {
-        return Err(crate::map_sqlx_error(
-            sqlx::Error::Protocol(format!(
-                "Too many parameters ({}). Currently supporting up to 50 parameters.",
-                params.len()
-            )),
-            path,
-            query,
-        ));
+    limits: FetchLimits,
+) -> Result<Vec<sqlx::sqlite::SqliteRow>, PyErr> {
+    crate::deadline::check(path, query)?;
+    if limits.is_unlimited() {
+        return bind_and_fetch_all_on_connection(query, params, conn, path).await;
     }
-    let query_builder = match params.len() {
-        1 => bind_chain!(query, params, 0),
-        2 => bind_chain!(query, params, 0, 1),
-        3 => bind_chain!(query, params, 0, 1, 2),
-        4 => bind_chain!(query, params, 0, 1, 2, 3),
-        5 => bind_chain!(query, params, 0, 1, 2, 3, 4),
-        6 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5),
-        7 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6),
-        8 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6, 7),
-        9 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8),
-        10 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9),
-        11 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10),
-        12 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11),
-        13 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12),
-        14 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13),
-        15 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14),
-        16 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15),
-        _ => unreachable!(),
-    };
-    query_builder
-        .fetch_one(&mut **conn)
-        .await
-        .map_err(|e| crate::map_sqlx_error(e, path, query))
+    let result = core::bind_and_fetch_all_limited_on_connection(query, params, conn, limits).await;
+    crate::result_cache::note_statement(path, query);
+    result.map_err(|e| crate::errors::map_fetch_error(e, path, query))
+}
+
+/// `bind_and_fetch_single` on a specific connection.
+pub(crate) async fn bind_and_fetch_single_on_connection(
+    query: &str,
+    params: &[SqliteParam],
+    conn: &mut PoolConnection<sqlx::Sqlite>,
+    path: &str,
+) -> Result<Option<sqlx::sqlite::SqliteRow>, PyErr> {
+    crate::deadline::check(path, query)?;
+    let result =
+        core::bind_and_fetch_all_limited_on_connection(query, params, conn, SINGLE_ROW).await;
+    crate::result_cache::note_statement(path, query);
+    single_row(result, path, query)
 }
 
-/// Helper to bind parameters and fetch optional row on a specific connection.
+/// Bind parameters and fetch an optional row on a specific connection.
 pub(crate) async fn bind_and_fetch_optional_on_connection(
     query: &str,
     params: &[SqliteParam],
     conn: &mut PoolConnection<sqlx::Sqlite>,
     path: &str,
 ) -> Result<Option<sqlx::sqlite::SqliteRow>, PyErr> {
-    if params.is_empty() {
-        return sqlx::query(query)
-            .fetch_optional(&mut **conn)
-            .await
-            .map_err(|e| crate::map_sqlx_error(e, path, query));
-    }
-    if params.len() > 16 {
-        return Err(crate::map_sqlx_error(
-            sqlx::Error::Protocol(format!(
-                "Too many parameters ({}). Currently supporting up to 50 parameters.",
-                params.len()
-            )),
-            path,
-            query,
-        ));
-    }
-    let query_builder = match params.len() {
-        1 => bind_chain!(query, params, 0),
-        2 => bind_chain!(query, params, 0, 1),
-        3 => bind_chain!(query, params, 0, 1, 2),
-        4 => bind_chain!(query, params, 0, 1, 2, 3),
-        5 => bind_chain!(query, params, 0, 1, 2, 3, 4),
-        6 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5),
-        7 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6),
-        8 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6, 7),
-        9 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8),
-        10 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9),
-        11 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10),
-        12 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11),
-        13 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12),
-        14 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13),
-        15 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14),
-        16 => bind_chain!(query, params, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15),
-        _ => unreachable!(),
-    };
-    query_builder
-        .fetch_optional(&mut **conn)
+    crate::deadline::check(path, query)?;
+    let result = core::bind_and_fetch_optional_on_connection(query, params, conn).await;
+    crate::result_cache::note_statement(path, query);
+    result.map_err(|e| crate::map_sqlx_error(e, path, query))
+}
+
+/// Prepare a query on a specific connection without running it.
+pub(crate) async fn prepare_on_connection(
+    query: &str,
+    conn: &mut PoolConnection<sqlx::Sqlite>,
+    path: &str,
+) -> Result<(), PyErr> {
+    core::prepare_on_connection(query, conn)
         .await
         .map_err(|e| crate::map_sqlx_error(e, path, query))
 }
@@ -0,0 +1,73 @@
+//! `fetch_cached()`, behind `rapsqlite.open_http()`: a read-through cache of
+//! remote database files. Downloading and revalidation live in
+//! `rapsqlite_core::remote`.
+
+use pyo3::prelude::*;
+use rapsqlite_core::remote::{self, CacheOptions};
+use rapsqlite_core::s3::S3Options;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::{runtime, OperationalError, ValueError};
+
+/// Make an up-to-date local copy of the database at `url` in `cache_dir`.
+///
+/// A cached copy is revalidated with a conditional GET and only downloaded
+/// again when the remote file changed; if the server can't be reached, the
+/// cached copy is used as it is. Each version is stored under its own file
+/// name, so connections opened on an earlier version are never affected by a
+/// refresh.
+///
+/// # Arguments
+///
+/// * `url` - `http://`, `https://` or `s3://bucket/key` URL of the database.
+///   `s3://` requests are signed with the AWS credentials from the
+///   environment, as for `Connection.backup_to_url()`.
+/// * `cache_dir` - Directory for cached copies (created if missing).
+/// * `max_age` - Seconds during which a copy is used without revalidating it.
+///   None (default) revalidates on every call.
+/// * `region` - Bucket region for `s3://` URLs.
+/// * `endpoint_url` - S3-compatible endpoint for `s3://` URLs.
+///
+/// # Returns
+///
+/// Returns an awaitable resolving to the path of the local copy.
+///
+/// # Errors
+///
+/// Raises ValueError for a negative `max_age` and OperationalError if the URL
+/// is not supported, the download fails with nothing cached, the server
+/// answers with an error, or the response is not an SQLite database.
+#[pyfunction]
+#[pyo3(signature = (url, cache_dir, *, max_age = None, region = None, endpoint_url = None))]
+pub(crate) fn fetch_cached(
+    py: Python<'_>,
+    url: String,
+    cache_dir: PathBuf,
+    max_age: Option<f64>,
+    region: Option<String>,
+    endpoint_url: Option<String>,
+) -> PyResult<Py<PyAny>> {
+    let max_age = max_age
+        .map(|seconds| {
+            Duration::try_from_secs_f64(seconds)
+                .map_err(|_| ValueError::new_err("max_age must be a non-negative number"))
+        })
+        .transpose()?;
+    let options = CacheOptions {
+        cache_dir,
+        max_age,
+        s3: S3Options {
+            region,
+            endpoint: endpoint_url,
+            ..S3Options::default()
+        },
+    };
+    let future = async move {
+        let path = remote::fetch(&url, &options)
+            .await
+            .map_err(|e| OperationalError::new_err(e.to_string()))?;
+        Ok(path.to_string_lossy().into_owned())
+    };
+    runtime::future_into_py(py, None, future).map(|bound| bound.unbind())
+}
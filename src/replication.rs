@@ -0,0 +1,56 @@
+//! `Connection(replication_hook=...)`: WAL events for replication tools.
+//!
+//! The hook itself lives in `rapsqlite_core::wal_hook`; this module turns its
+//! events into dicts for the Python callback.
+
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use rapsqlite_core::wal_hook::{WalEvent, WalListener};
+
+/// The event as a dict with an `"event"` key naming it.
+fn event_dict<'py>(py: Python<'py>, event: &WalEvent<'_>) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new(py);
+    match *event {
+        WalEvent::FramesWritten {
+            generation,
+            frames,
+            wal_path,
+        } => {
+            dict.set_item("event", "frames_written")?;
+            dict.set_item("generation", generation)?;
+            dict.set_item("frames", frames)?;
+            dict.set_item("wal_path", wal_path.to_string_lossy())?;
+        }
+        WalEvent::CheckpointStart { generation, frames } => {
+            dict.set_item("event", "checkpoint_start")?;
+            dict.set_item("generation", generation)?;
+            dict.set_item("frames", frames)?;
+        }
+        WalEvent::CheckpointFinish {
+            generation,
+            frames,
+            checkpointed,
+            result,
+        } => {
+            dict.set_item("event", "checkpoint_finish")?;
+            dict.set_item("generation", generation)?;
+            dict.set_item("frames", frames)?;
+            dict.set_item("checkpointed", checkpointed)?;
+            dict.set_item("ok", result == libsqlite3_sys::SQLITE_OK)?;
+        }
+    }
+    Ok(dict)
+}
+
+/// A WAL listener calling `callback` with each event dict. An exception raised
+/// by the callback is reported via `sys.unraisablehook`; the commit stands.
+pub(crate) fn listener(callback: Py<PyAny>) -> Box<WalListener> {
+    Box::new(move |event| {
+        Python::attach(|py| {
+            let callback = callback.bind(py);
+            if let Err(e) = event_dict(py, event).and_then(|dict| callback.call1((dict,))) {
+                e.write_unraisable(py, Some(callback));
+            }
+        })
+    })
+}
@@ -0,0 +1,72 @@
+//! `restore_to()`: point-in-time restore from a `Connection(wal_archive=...)`
+//! archive. The archive format and replay live in `rapsqlite_core::wal_archive`.
+
+use pyo3::prelude::*;
+use std::path::PathBuf;
+
+use crate::{runtime, OperationalError, ValueError};
+
+/// Rebuild an archived database as of an earlier time.
+///
+/// Writes a new database file at `path` from the archive directory a
+/// Connection opened with `wal_archive=archive_dir` filled: the latest base
+/// snapshot taken at or before `timestamp`, with every archived commit up to
+/// `timestamp` replayed on top of it. Commits are restored whole.
+///
+/// # Arguments
+///
+/// * `path` - Database file to create; it must not exist.
+/// * `archive_dir` - The `wal_archive` directory.
+/// * `timestamp` - Point in time as Unix seconds (float) or an aware
+///   `datetime`; None (default) restores the latest archived commit.
+///
+/// # Errors
+///
+/// Raises OperationalError if `path` exists, the archive has no base snapshot
+/// at or before `timestamp`, or reading the archive fails.
+///
+/// # Example
+///
+/// .. code-block:: python
+///
+///     import time
+///     import rapsqlite
+///
+///     before = time.time()
+///     ...  # an accidental DELETE
+///     await rapsqlite.restore_to("recovered.db", "archive/", before)
+#[pyfunction]
+#[pyo3(signature = (path, archive_dir, timestamp = None))]
+pub(crate) fn restore_to(
+    py: Python<'_>,
+    path: PathBuf,
+    archive_dir: PathBuf,
+    timestamp: Option<&Bound<'_, PyAny>>,
+) -> PyResult<Py<PyAny>> {
+    let until = match timestamp {
+        None => None,
+        Some(ts) => {
+            let seconds: f64 = if ts.hasattr("timestamp")? {
+                ts.call_method0("timestamp")?.extract()?
+            } else {
+                ts.extract()?
+            };
+            if !seconds.is_finite() || seconds < 0.0 {
+                return Err(ValueError::new_err(
+                    "timestamp must be a non-negative Unix time",
+                ));
+            }
+            Some((seconds * 1e9) as u128)
+        }
+    };
+    let future = async move {
+        tokio::task::spawn_blocking(move || {
+            rapsqlite_core::wal_archive::restore_to(&archive_dir, &path, until)
+        })
+        .await
+        .map_err(|e| OperationalError::new_err(format!("restore_to failed: {e}")))?
+        .map_err(|e| OperationalError::new_err(format!("restore_to failed: {e}")))?;
+        Ok(())
+    };
+    runtime::future_into_py(py, None, future).map(|bound| bound.unbind())
+}
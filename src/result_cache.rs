@@ -0,0 +1,160 @@
+//! Query result cache for `Connection(cache="lru")`.
+//!
+//! Caches live in a per-path registry so that every write executed through
+//! `query.rs` or `sqlite3_exec` invalidates them, whichever Connection or Cursor
+//! ran it. The cache itself is `rapsqlite_core::cache::ResultCache`.
+
+use libsqlite3_sys::{sqlite3, sqlite3_get_clientdata, sqlite3_set_clientdata};
+use pyo3::prelude::*;
+use rapsqlite_core::cache::{ResultCache, StatementEffect, TableDependencies};
+use rapsqlite_core::query::FetchLimits;
+use sqlx::pool::PoolConnection;
+use sqlx::sqlite::SqliteRow;
+use std::collections::HashMap;
+use std::ffi::{c_void, CStr};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex as StdMutex, OnceLock, Weak};
+
+use crate::errors::map_fetch_error;
+use crate::query::bind_and_fetch_all_limited_on_connection;
+use crate::types::SqliteParam;
+use crate::OperationalError;
+
+pub(crate) type SharedResultCache = Arc<StdMutex<ResultCache>>;
+
+type Registry = HashMap<String, Vec<Weak<StdMutex<ResultCache>>>>;
+
+fn registry() -> &'static StdMutex<Registry> {
+    static CACHES: OnceLock<StdMutex<Registry>> = OnceLock::new();
+    CACHES.get_or_init(Default::default)
+}
+
+/// Create a cache for the database at `path` and register it for invalidation.
+pub(crate) fn register(path: &str, capacity: usize) -> SharedResultCache {
+    let cache = Arc::new(StdMutex::new(ResultCache::new(capacity)));
+    let mut caches = registry().lock().unwrap();
+    let entries = caches.entry(path.to_string()).or_default();
+    entries.retain(|c| c.strong_count() > 0);
+    entries.push(Arc::downgrade(&cache));
+    cache
+}
+
+/// Invalidate the caches for `path` after running `sql`, whether or not it
+/// succeeded.
+pub(crate) fn note_statement(path: &str, sql: &str) {
+    let mut caches = registry().lock().unwrap();
+    let Some(entries) = caches.get_mut(path) else {
+        return;
+    };
+    entries.retain(|c| c.strong_count() > 0);
+    if entries.is_empty() {
+        caches.remove(path);
+        return;
+    }
+    if StatementEffect::of(sql) == StatementEffect::Read {
+        return;
+    }
+    for cache in entries.iter().filter_map(Weak::upgrade) {
+        cache.lock().unwrap().invalidate(sql);
+    }
+}
+
+/// Clear the caches for `path` after its contents were replaced by other means
+/// than SQL, e.g. as a backup target.
+pub(crate) fn note_replaced(path: &str) {
+    let caches = registry().lock().unwrap();
+    for cache in caches
+        .get(path)
+        .into_iter()
+        .flatten()
+        .filter_map(Weak::upgrade)
+    {
+        cache.lock().unwrap().invalidate_all();
+    }
+}
+
+const CONNECTION_ID_KEY: &CStr = c"rapsqlite.result_cache_id";
+
+unsafe extern "C" fn free_connection_id(id: *mut c_void) {
+    drop(Box::from_raw(id as *mut u64));
+}
+
+/// A process-unique id for the SQLite connection `db`, stored as client data on
+/// the connection so it is never confused with a later connection that reuses
+/// the same address.
+///
+/// # Safety
+///
+/// `db` must meet the invariant described on `RawHandleRoute::run` in
+/// `connection.rs`.
+unsafe fn connection_id(db: *mut sqlite3) -> u64 {
+    static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+    let existing = sqlite3_get_clientdata(db, CONNECTION_ID_KEY.as_ptr());
+    if !existing.is_null() {
+        return *(existing as *const u64);
+    }
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    let data = Box::into_raw(Box::new(id)) as *mut c_void;
+    sqlite3_set_clientdata(
+        db,
+        CONNECTION_ID_KEY.as_ptr(),
+        data,
+        Some(free_connection_id),
+    );
+    id
+}
+
+/// `fetch_all` through `cache` on `conn`: a hit returns the stored rows, a miss
+/// fetches and stores them. Changes committed by other connections since `conn`
+/// was last used clear the cache first. `limits` applies to hits as well.
+pub(crate) async fn fetch_all_cached(
+    cache: &SharedResultCache,
+    conn: &mut PoolConnection<sqlx::Sqlite>,
+    query: &str,
+    params: &[SqliteParam],
+    path: &str,
+    limits: FetchLimits,
+) -> PyResult<Arc<Vec<SqliteRow>>> {
+    let version: i64 = sqlx::query_scalar("PRAGMA data_version")
+        .fetch_one(&mut **conn)
+        .await
+        .map_err(|e| crate::map_sqlx_error(e, path, "PRAGMA data_version"))?;
+    let id = {
+        let mut handle = conn
+            .lock_handle()
+            .await
+            .map_err(|e| OperationalError::new_err(format!("Failed to lock handle: {e}")))?;
+        unsafe { connection_id(handle.as_raw_handle().as_ptr()) }
+    };
+
+    let key = ResultCache::key(query, params);
+    let (generation, has_dependencies) = {
+        let mut guard = cache.lock().unwrap();
+        guard.observe_data_version(id, version);
+        if let Some(rows) = guard.get(&key) {
+            limits
+                .check(&rows)
+                .map_err(|e| map_fetch_error(e, path, query))?;
+            return Ok(rows);
+        }
+        (guard.generation(), guard.has_dependencies())
+    };
+
+    if !has_dependencies {
+        let dependencies = TableDependencies::load(conn)
+            .await
+            .map_err(|e| crate::map_sqlx_error(e, path, "SELECT ... FROM sqlite_master"))?;
+        cache
+            .lock()
+            .unwrap()
+            .set_dependencies(dependencies, generation);
+    }
+    let rows = Arc::new(
+        bind_and_fetch_all_limited_on_connection(query, params, conn, path, limits).await?,
+    );
+    cache
+        .lock()
+        .unwrap()
+        .insert(key, query, Arc::clone(&rows), generation);
+    Ok(rows)
+}
@@ -13,7 +13,7 @@ pub(crate) struct RapRow {
 impl RapRow {
     /// Create a new Row from column names and values.
     #[new]
-    fn new(columns: Vec<String>, values: Vec<Py<PyAny>>) -> PyResult<Self> {
+    pub(crate) fn new(columns: Vec<String>, values: Vec<Py<PyAny>>) -> PyResult<Self> {
         if columns.len() != values.len() {
             return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
                 "Columns and values must have the same length",
@@ -0,0 +1,194 @@
+//! Tokio runtime configuration.
+//!
+//! Every awaitable rapsqlite returns is driven by pyo3-async-runtimes' global
+//! Tokio runtime, which `configure_runtime()` can size before first use. A
+//! `Connection(dedicated_runtime=True)` instead runs its work on a runtime of
+//! its own, isolating it from other Connections sharing the process.
+
+use pyo3::exceptions::PyRuntimeError;
+use pyo3::prelude::*;
+use pyo3_async_runtimes::tokio as pyo3_tokio;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex as StdMutex;
+use std::time::Instant;
+use tokio::runtime::{Builder, Runtime};
+use tokio::task::{AbortHandle, JoinHandle};
+
+use crate::{OperationalError, ValueError};
+
+/// Settings from `configure_runtime()`, also used for dedicated runtimes.
+#[derive(Clone)]
+struct RuntimeConfig {
+    worker_threads: Option<usize>,
+    thread_name_prefix: Option<String>,
+    max_blocking_threads: Option<usize>,
+}
+
+static CONFIG: StdMutex<RuntimeConfig> = StdMutex::new(RuntimeConfig {
+    worker_threads: None,
+    thread_name_prefix: None,
+    max_blocking_threads: None,
+});
+
+/// Set once the global runtime has been used; it cannot be reconfigured after.
+static STARTED: AtomicBool = AtomicBool::new(false);
+
+impl RuntimeConfig {
+    fn builder(&self, default_worker_threads: Option<usize>) -> Builder {
+        let mut builder = Builder::new_multi_thread();
+        builder.enable_all();
+        if let Some(threads) = self.worker_threads.or(default_worker_threads) {
+            builder.worker_threads(threads);
+        }
+        if let Some(threads) = self.max_blocking_threads {
+            builder.max_blocking_threads(threads);
+        }
+        if let Some(prefix) = self.thread_name_prefix.clone() {
+            let next = AtomicUsize::new(0);
+            builder.thread_name_fn(move || {
+                format!("{prefix}-{}", next.fetch_add(1, Ordering::Relaxed))
+            });
+        }
+        builder
+    }
+}
+
+/// Configure the Tokio runtime that drives rapsqlite.
+///
+/// Must be called before the first Connection operation is awaited; raises
+/// RuntimeError once the runtime has started. Settings left as None keep
+/// Tokio's defaults. Dedicated runtimes (`Connection(dedicated_runtime=True)`)
+/// use the same settings, except that they default to one worker thread.
+///
+/// # Arguments
+///
+/// * `worker_threads` - Number of worker threads (default: one per CPU core).
+/// * `thread_name_prefix` - Worker threads are named `"<prefix>-<n>"`.
+/// * `max_blocking_threads` - Upper bound of the blocking thread pool
+///   (Tokio default 512).
+///
+/// # Example
+///
+/// .. code-block:: python
+///
+///     import rapsqlite
+///
+///     rapsqlite.configure_runtime(worker_threads=2, thread_name_prefix="db")
+#[pyfunction]
+#[pyo3(signature = (*, worker_threads = None, thread_name_prefix = None, max_blocking_threads = None))]
+pub(crate) fn configure_runtime(
+    worker_threads: Option<usize>,
+    thread_name_prefix: Option<String>,
+    max_blocking_threads: Option<usize>,
+) -> PyResult<()> {
+    if worker_threads == Some(0) {
+        return Err(ValueError::new_err("worker_threads must be >= 1"));
+    }
+    if max_blocking_threads == Some(0) {
+        return Err(ValueError::new_err("max_blocking_threads must be >= 1"));
+    }
+    if STARTED.load(Ordering::SeqCst) {
+        return Err(PyRuntimeError::new_err(
+            "configure_runtime() must be called before the runtime starts \
+             (before the first Connection operation)",
+        ));
+    }
+    let config = RuntimeConfig {
+        worker_threads,
+        thread_name_prefix,
+        max_blocking_threads,
+    };
+    pyo3_tokio::init(config.builder(None));
+    *CONFIG.lock().unwrap() = config;
+    Ok(())
+}
+
+/// A Tokio runtime owned by one Connection (`dedicated_runtime=True`).
+pub(crate) struct DedicatedRuntime(Option<Runtime>);
+
+impl DedicatedRuntime {
+    pub(crate) fn new() -> PyResult<Self> {
+        let config = CONFIG.lock().unwrap().clone();
+        let runtime = config.builder(Some(1)).build().map_err(|e| {
+            OperationalError::new_err(format!("Failed to start dedicated runtime: {e}"))
+        })?;
+        Ok(DedicatedRuntime(Some(runtime)))
+    }
+}
+
+impl Drop for DedicatedRuntime {
+    fn drop(&mut self) {
+        // The last reference may go away on the event loop thread; don't block it
+        if let Some(runtime) = self.0.take() {
+            runtime.shutdown_background();
+        }
+    }
+}
+
+/// Aborts the task when the awaiting future is dropped (e.g. on cancellation).
+/// As on the global runtime, the operation is dropped at the await point it is
+/// waiting on: a statement already handed to SQLite still runs to completion
+/// and a transaction stays open until committed or rolled back.
+struct AbortOnDrop<T>(JoinHandle<T>);
+
+impl<T> Drop for AbortOnDrop<T> {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+/// `pyo3_async_runtimes::tokio::future_into_py`, running `fut` on `runtime`
+/// when the Connection has a dedicated one.
+pub(crate) fn future_into_py<'py, F, T>(
+    py: Python<'py>,
+    runtime: Option<&DedicatedRuntime>,
+    fut: F,
+) -> PyResult<Bound<'py, PyAny>>
+where
+    F: Future<Output = PyResult<T>> + Send + 'static,
+    T: for<'a> IntoPyObject<'a> + Send + 'static,
+{
+    STARTED.store(true, Ordering::SeqCst);
+    let fut = with_deadline(crate::deadline::current(py)?, fut);
+    let Some(runtime) = runtime.and_then(|r| r.0.as_ref()) else {
+        return pyo3_tokio::future_into_py(py, fut);
+    };
+    // Carry the event loop over so Python awaitables (init hooks, callbacks)
+    // can still be awaited from the dedicated runtime's threads
+    let locals = pyo3_tokio::get_current_locals(py)?;
+    let mut task = AbortOnDrop(runtime.spawn(pyo3_tokio::scope(locals, fut)));
+    pyo3_tokio::future_into_py(py, async move {
+        (&mut task.0)
+            .await
+            .map_err(|e| PyRuntimeError::new_err(format!("rapsqlite task failed: {e}")))?
+    })
+}
+
+/// Run `fut` with the `rapsqlite.deadline()` of the task that started it.
+async fn with_deadline<F: Future>(deadline: Option<Instant>, fut: F) -> F::Output {
+    match deadline {
+        Some(deadline) => rapsqlite_core::deadline::scope(deadline, fut).await,
+        None => fut.await,
+    }
+}
+
+/// Spawn a background task on `runtime`, or on the global runtime, with the
+/// caller's event loop available to it as in `future_into_py`.
+pub(crate) fn spawn<F>(
+    py: Python<'_>,
+    runtime: Option<&DedicatedRuntime>,
+    fut: F,
+) -> PyResult<AbortHandle>
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    STARTED.store(true, Ordering::SeqCst);
+    let fut = with_deadline(crate::deadline::current(py)?, fut);
+    let fut = pyo3_tokio::scope(pyo3_tokio::get_current_locals(py)?, fut);
+    let task = match runtime.and_then(|r| r.0.as_ref()) {
+        Some(runtime) => runtime.spawn(fut),
+        None => pyo3_tokio::get_runtime().spawn(fut),
+    };
+    Ok(task.abort_handle())
+}
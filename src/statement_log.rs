@@ -0,0 +1,186 @@
+//! Statement logger for `Connection.set_statement_logger`.
+//!
+//! Unlike the trace callback this needs no dedicated connection: statements are
+//! reported from the Connection methods that run them, with their parameters
+//! (redacted as configured), duration and row count.
+
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList, PyString, PyTuple};
+use pyo3::{PyTraverseError, PyVisit};
+use std::collections::HashSet;
+use std::future::Future;
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::Instant;
+
+use crate::ValueError;
+
+/// Stands in for a redacted parameter value.
+const REDACTED: &str = "<redacted>";
+
+pub(crate) struct StatementLogger {
+    callback: Py<PyAny>,
+    /// Parameter names to redact in mapping parameters, without the `:`/`@`/`$` prefix.
+    names: HashSet<String>,
+    /// 0-based positions to redact in sequence parameters.
+    positions: HashSet<usize>,
+}
+
+pub(crate) type StatementLoggerSlot = Arc<StdMutex<Option<Arc<StatementLogger>>>>;
+
+fn strip_prefix(name: &str) -> &str {
+    name.trim_start_matches([':', '@', '$'])
+}
+
+impl StatementLogger {
+    /// Visit the callback, for the Connection's garbage collection.
+    pub(crate) fn traverse(&self, visit: &PyVisit<'_>) -> Result<(), PyTraverseError> {
+        visit.call(&self.callback)
+    }
+
+    /// `redact` lists parameter names (str) and positions (int).
+    pub(crate) fn new(callback: Py<PyAny>, redact: Option<&Bound<'_, PyAny>>) -> PyResult<Self> {
+        let mut logger = StatementLogger {
+            callback,
+            names: HashSet::new(),
+            positions: HashSet::new(),
+        };
+        for item in redact
+            .map(|r| r.try_iter())
+            .transpose()?
+            .into_iter()
+            .flatten()
+        {
+            let item = item?;
+            if let Ok(name) = item.extract::<String>() {
+                logger.names.insert(strip_prefix(&name).to_string());
+            } else if let Ok(position) = item.extract::<usize>() {
+                logger.positions.insert(position);
+            } else {
+                return Err(ValueError::new_err(
+                    "redact entries must be parameter names (str) or positions (int >= 0)",
+                ));
+            }
+        }
+        Ok(logger)
+    }
+
+    /// A copy of `params` with the configured names/positions replaced.
+    fn redact<'py>(&self, params: &Bound<'py, PyAny>) -> PyResult<Bound<'py, PyAny>> {
+        let py = params.py();
+        let redacted = || PyString::new(py, REDACTED).into_any();
+        if let Ok(dict) = params.cast::<PyDict>() {
+            let copy = PyDict::new(py);
+            for (key, value) in dict.iter() {
+                let hidden = key
+                    .extract::<String>()
+                    .is_ok_and(|k| self.names.contains(strip_prefix(&k)));
+                copy.set_item(&key, if hidden { redacted() } else { value })?;
+            }
+            return Ok(copy.into_any());
+        }
+        let is_tuple = params.is_instance_of::<PyTuple>();
+        if is_tuple || params.is_instance_of::<PyList>() {
+            let mut items = Vec::new();
+            for (i, value) in params.try_iter()?.enumerate() {
+                items.push(if self.positions.contains(&i) {
+                    redacted()
+                } else {
+                    value?
+                });
+            }
+            return if is_tuple {
+                PyTuple::new(py, items).map(|t| t.into_any())
+            } else {
+                PyList::new(py, items).map(|l| l.into_any())
+            };
+        }
+        // A single value is parameter 0
+        if self.positions.contains(&0) {
+            Ok(redacted())
+        } else {
+            Ok(params.clone())
+        }
+    }
+}
+
+/// A statement about to run, reported by [`PendingLog::finish`] once it has.
+pub(crate) struct PendingLog {
+    logger: Arc<StatementLogger>,
+    query: String,
+    params: Py<PyAny>,
+    started: Instant,
+}
+
+/// Start logging a statement if a logger is set. `params` is the parameter
+/// object the caller passed (a mapping, a sequence, a single value or None).
+pub(crate) fn start(
+    slot: &StatementLoggerSlot,
+    query: &str,
+    params: Option<&Bound<'_, PyAny>>,
+) -> PyResult<Option<PendingLog>> {
+    let Some(logger) = slot.lock().unwrap().clone() else {
+        return Ok(None);
+    };
+    let params = match params {
+        Some(p) if !p.is_none() => logger.redact(p)?.unbind(),
+        _ => Python::attach(|py| py.None()),
+    };
+    Ok(Some(PendingLog::new(logger, query, params)))
+}
+
+/// [`start`] for `execute_many`: the logger receives the list of parameter lists.
+pub(crate) fn start_many(
+    slot: &StatementLoggerSlot,
+    query: &str,
+    params: &[Vec<Py<PyAny>>],
+) -> PyResult<Option<PendingLog>> {
+    let Some(logger) = slot.lock().unwrap().clone() else {
+        return Ok(None);
+    };
+    let params = Python::attach(|py| -> PyResult<Py<PyAny>> {
+        let rows = params
+            .iter()
+            .map(|row| logger.redact(PyList::new(py, row)?.as_any()))
+            .collect::<PyResult<Vec<_>>>()?;
+        Ok(PyList::new(py, rows)?.into_any().unbind())
+    })?;
+    Ok(Some(PendingLog::new(logger, query, params)))
+}
+
+impl PendingLog {
+    fn new(logger: Arc<StatementLogger>, query: &str, params: Py<PyAny>) -> Self {
+        PendingLog {
+            logger,
+            query: query.to_string(),
+            params,
+            started: Instant::now(),
+        }
+    }
+
+    /// Call the logger with `(sql, params, duration, rows)`. An exception raised by
+    /// the logger is reported via `sys.unraisablehook` and does not fail the statement.
+    pub(crate) fn finish(self, rows: i64) {
+        let duration = self.started.elapsed().as_secs_f64();
+        Python::attach(|py| {
+            let callback = self.logger.callback.bind(py);
+            if let Err(e) = callback.call1((self.query, self.params, duration, rows)) {
+                e.write_unraisable(py, Some(callback));
+            }
+        })
+    }
+}
+
+/// Await `future` and, if it succeeds, report it to `log` with the row count
+/// `rows` derives from its result.
+pub(crate) async fn logged<T>(
+    log: Option<PendingLog>,
+    future: impl Future<Output = PyResult<T>>,
+    rows: impl FnOnce(Python<'_>, &T) -> i64,
+) -> PyResult<T> {
+    let result = future.await?;
+    if let Some(log) = log {
+        let count = Python::attach(|py| rows(py, &result));
+        log.finish(count);
+    }
+    Ok(result)
+}
@@ -0,0 +1,150 @@
+//! `RowStream`: async iterator returned by `Connection.stream()` and
+//! `Connection.fetch_stream()`.
+//!
+//! A producer task reads the result set on a dedicated pool connection and
+//! sends rows into a bounded channel, so at most `prefetch` rows wait in Rust
+//! for the Python consumer; when the channel is full the query simply pauses.
+//! `fetch_stream()` streams take the rows out a chunk at a time.
+
+use pyo3::exceptions::PyStopAsyncIteration;
+use pyo3::prelude::*;
+use pyo3::types::PyList;
+use sqlx::sqlite::SqliteRow;
+use std::sync::{Arc, Mutex as StdMutex, OnceLock};
+use tokio::sync::{mpsc, Mutex};
+use tokio::task::AbortHandle;
+
+use crate::conversion::{DecodeOptions, RowFactory};
+use crate::converters::ColumnConverters;
+use crate::runtime::{self, DedicatedRuntime};
+use crate::types::InvalidUtf8Policy;
+
+pub(crate) type RowReceiver = mpsc::Receiver<PyResult<SqliteRow>>;
+
+/// Async iterator over the rows of a query, fetched ahead in a bounded buffer.
+#[pyclass]
+pub(crate) struct RowStream {
+    pub(crate) receiver: Arc<Mutex<RowReceiver>>,
+    pub(crate) producer: AbortHandle,
+    pub(crate) runtime: Option<Arc<DedicatedRuntime>>,
+    pub(crate) row_factory: Arc<StdMutex<Option<Py<PyAny>>>>,
+    pub(crate) text_factory: Arc<StdMutex<Option<Py<PyAny>>>>,
+    pub(crate) null_floats_as_nan: Arc<StdMutex<bool>>,
+    pub(crate) decode_large_ints: Arc<StdMutex<bool>>,
+    pub(crate) invalid_utf8: Arc<StdMutex<InvalidUtf8Policy>>,
+    pub(crate) strict: bool,
+    pub(crate) detect_types: u32,
+    /// Declared column types, set by the producer before it sends the first row.
+    pub(crate) decltypes: Arc<StdMutex<Option<Vec<Option<String>>>>>,
+    pub(crate) converters: Arc<OnceLock<Option<ColumnConverters>>>,
+    /// Yield lists of up to this many rows instead of single rows.
+    pub(crate) chunk_size: Option<usize>,
+}
+
+impl Drop for RowStream {
+    fn drop(&mut self) {
+        // An abandoned stream must not keep its pool connection checked out
+        self.producer.abort();
+    }
+}
+
+#[pymethods]
+impl RowStream {
+    fn __aiter__(slf: PyRef<Self>) -> PyRef<Self> {
+        slf
+    }
+
+    /// Next row, formatted according to the connection's `row_factory`, or
+    /// for `fetch_stream()` the next list of rows.
+    fn __anext__(&self) -> PyResult<Py<PyAny>> {
+        let receiver = Arc::clone(&self.receiver);
+        let row_factory = Arc::clone(&self.row_factory);
+        let text_factory = Arc::clone(&self.text_factory);
+        let null_floats_as_nan = Arc::clone(&self.null_floats_as_nan);
+        let decode_large_ints = Arc::clone(&self.decode_large_ints);
+        let invalid_utf8 = Arc::clone(&self.invalid_utf8);
+        let strict = self.strict;
+        let detect_types = self.detect_types;
+        let decltypes = Arc::clone(&self.decltypes);
+        let converters = Arc::clone(&self.converters);
+        let chunk_size = self.chunk_size;
+
+        Python::attach(|py| {
+            let future = async move {
+                let mut receiver = receiver.lock().await;
+                let Some(row) = receiver.recv().await else {
+                    return Err(PyStopAsyncIteration::new_err(()));
+                };
+                let mut rows = vec![row?];
+                while rows.len() < chunk_size.unwrap_or(1) {
+                    match receiver.recv().await {
+                        Some(row) => rows.push(row?),
+                        None => break,
+                    }
+                }
+                drop(receiver);
+                Python::attach(|py| -> PyResult<Py<PyAny>> {
+                    let guard = row_factory.lock().unwrap();
+                    let factory = RowFactory::resolve(py, guard.as_ref())?;
+                    let tf_guard = text_factory.lock().unwrap();
+                    let converters = converters.get_or_init(|| {
+                        let decltypes = decltypes.lock().unwrap();
+                        ColumnConverters::resolve(py, detect_types, &rows[0], decltypes.as_deref())
+                    });
+                    let decode = DecodeOptions {
+                        text_factory: tf_guard.as_ref(),
+                        null_floats_as_nan: *null_floats_as_nan.lock().unwrap(),
+                        decode_large_ints: *decode_large_ints.lock().unwrap(),
+                        invalid_utf8: *invalid_utf8.lock().unwrap(),
+                        strict,
+                        converters: converters.as_ref(),
+                    };
+                    if chunk_size.is_none() {
+                        return Ok(factory.convert(py, &rows[0], &decode)?.unbind());
+                    }
+                    let chunk = PyList::empty(py);
+                    for row in &rows {
+                        chunk.append(factory.convert(py, row, &decode)?)?;
+                    }
+                    Ok(chunk.into_any().unbind())
+                })
+            };
+            runtime::future_into_py(py, self.runtime.as_deref(), future).map(|bound| bound.unbind())
+        })
+    }
+
+    /// Stop the query and release its connection. Rows already buffered are
+    /// discarded; iterating afterwards ends immediately.
+    fn aclose(&self) -> PyResult<Py<PyAny>> {
+        self.producer.abort();
+        let receiver = Arc::clone(&self.receiver);
+        Python::attach(|py| {
+            let future = async move {
+                let mut receiver = receiver.lock().await;
+                receiver.close();
+                while receiver.recv().await.is_some() {}
+                Ok(())
+            };
+            runtime::future_into_py(py, self.runtime.as_deref(), future).map(|bound| bound.unbind())
+        })
+    }
+
+    fn __aenter__(slf: PyRef<Self>) -> PyResult<Py<PyAny>> {
+        let dedicated_runtime = slf.runtime.clone();
+        let slf: Py<Self> = slf.into();
+        Python::attach(|py| {
+            let future = async move { Ok(slf) };
+            runtime::future_into_py(py, dedicated_runtime.as_deref(), future)
+                .map(|bound| bound.unbind())
+        })
+    }
+
+    fn __aexit__(
+        &self,
+        _exc_type: &Bound<'_, PyAny>,
+        _exc_val: &Bound<'_, PyAny>,
+        _exc_tb: &Bound<'_, PyAny>,
+    ) -> PyResult<Py<PyAny>> {
+        self.aclose()
+    }
+}
@@ -0,0 +1,261 @@
+//! `rapsqlite.sql()`: composable SQL templates. A template is parsed by
+//! `rapsqlite_core::sql::parse_template` and rendered to SQL with `?`
+//! placeholders plus positional values when it is executed, so bound values
+//! never become SQL text and identifiers are always quoted.
+
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyFrozenSet, PyList, PySet, PyString, PyTuple};
+use rapsqlite_core::sql::{parse_template, TemplatePart};
+
+use crate::utils::quote_identifier;
+use crate::ValueError;
+
+enum Piece {
+    Text(String),
+    Value(String),
+    Identifier(String),
+    Fragment(Py<Sql>),
+}
+
+/// A SQL template with bound parameters, created by `rapsqlite.sql()`.
+///
+/// `:name` (or `@name` / `$name`) placeholders take values: a list, tuple or
+/// set expands to a parenthesized list of placeholders for `IN`, and another
+/// `Sql` object is inlined with its own parameters. `{name}` placeholders take
+/// an identifier (or a list of identifiers), which is quoted. Objects are
+/// immutable: `bind()`, `join()` and `+` return new ones.
+#[pyclass(frozen)]
+pub(crate) struct Sql {
+    pieces: Vec<Piece>,
+    params: Py<PyDict>,
+}
+
+impl Sql {
+    /// Append this template's SQL to `sql` and its values to `values`.
+    /// Placeholders are looked up in the template's own parameters, then in
+    /// `inherited` (those of the enclosing template or the connection's
+    /// defaults).
+    fn render_into(
+        &self,
+        py: Python<'_>,
+        inherited: Option<&Bound<'_, PyDict>>,
+        sql: &mut String,
+        values: &Bound<'_, PyList>,
+    ) -> PyResult<()> {
+        let params = self.params.bind(py);
+        let scope = match inherited {
+            Some(inherited) if !inherited.is_empty() => {
+                let scope = inherited.copy()?;
+                scope.update(params.as_mapping())?;
+                scope
+            }
+            _ => params.clone(),
+        };
+        let lookup = |name: &str| {
+            scope.get_item(name)?.ok_or_else(|| {
+                PyErr::new::<pyo3::exceptions::PyKeyError, _>(format!("Missing parameter: {name}"))
+            })
+        };
+        for piece in &self.pieces {
+            match piece {
+                Piece::Text(text) => sql.push_str(text),
+                Piece::Fragment(fragment) => {
+                    fragment.get().render_into(py, Some(&scope), sql, values)?
+                }
+                Piece::Value(name) => {
+                    let value = lookup(name)?;
+                    if let Ok(fragment) = value.cast::<Sql>() {
+                        fragment.get().render_into(py, Some(&scope), sql, values)?;
+                    } else if value.is_instance_of::<PyList>()
+                        || value.is_instance_of::<PyTuple>()
+                        || value.is_instance_of::<PySet>()
+                        || value.is_instance_of::<PyFrozenSet>()
+                    {
+                        let mut n = 0;
+                        for item in value.try_iter()? {
+                            values.append(item?)?;
+                            n += 1;
+                        }
+                        sql.push('(');
+                        sql.push_str(&vec!["?"; n].join(", "));
+                        sql.push(')');
+                    } else {
+                        values.append(value)?;
+                        sql.push('?');
+                    }
+                }
+                Piece::Identifier(name) => {
+                    let value = lookup(name)?;
+                    let names: Vec<String> = if value.is_instance_of::<PyString>() {
+                        vec![value.extract()?]
+                    } else {
+                        value.extract().map_err(|_| {
+                            pyo3::exceptions::PyTypeError::new_err(format!(
+                                "Identifier parameter {{{name}}} must be a str or a list of str"
+                            ))
+                        })?
+                    };
+                    if names.is_empty() {
+                        return Err(ValueError::new_err(format!(
+                            "Identifier parameter {{{name}}} must not be empty"
+                        )));
+                    }
+                    let quoted: Vec<String> = names.iter().map(|n| quote_identifier(n)).collect();
+                    sql.push_str(&quoted.join(", "));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Render to SQL text and positional values, with `defaults` (the
+    /// connection's default parameters) filling placeholders left unbound.
+    pub(crate) fn render_with<'py>(
+        &self,
+        py: Python<'py>,
+        defaults: Option<&Bound<'py, PyDict>>,
+    ) -> PyResult<(String, Bound<'py, PyList>)> {
+        let mut sql = String::new();
+        let values = PyList::empty(py);
+        self.render_into(py, defaults, &mut sql, &values)?;
+        Ok((sql, values))
+    }
+
+    fn template_text(&self) -> String {
+        self.pieces
+            .iter()
+            .map(|piece| match piece {
+                Piece::Text(text) => text.clone(),
+                Piece::Value(name) => format!(":{name}"),
+                Piece::Identifier(name) => format!("{{{name}}}"),
+                Piece::Fragment(fragment) => fragment.get().template_text(),
+            })
+            .collect()
+    }
+
+    fn composed(py: Python<'_>, fragments: Vec<Piece>) -> Sql {
+        Sql {
+            pieces: fragments,
+            params: PyDict::new(py).unbind(),
+        }
+    }
+}
+
+#[pymethods]
+impl Sql {
+    /// Return a copy with `params` bound (added to, or replacing, those bound
+    /// before).
+    #[pyo3(signature = (**params))]
+    fn bind(&self, py: Python<'_>, params: Option<&Bound<'_, PyDict>>) -> PyResult<Sql> {
+        let merged = self.params.bind(py).copy()?;
+        if let Some(params) = params {
+            merged.update(params.as_mapping())?;
+        }
+        Ok(Sql {
+            pieces: self
+                .pieces
+                .iter()
+                .map(|piece| piece.clone_ref(py))
+                .collect(),
+            params: merged.unbind(),
+        })
+    }
+
+    /// Join `fragments` with this template as the separator, e.g.
+    /// `sql(" AND ").join(conditions)`.
+    fn join(slf: &Bound<'_, Self>, fragments: &Bound<'_, PyAny>) -> PyResult<Sql> {
+        let py = slf.py();
+        let mut pieces = Vec::new();
+        for (i, fragment) in fragments.try_iter()?.enumerate() {
+            let fragment = fragment?;
+            let fragment = fragment.cast::<Sql>().map_err(|_| {
+                pyo3::exceptions::PyTypeError::new_err("join() expects rapsqlite.sql() objects")
+            })?;
+            if i > 0 {
+                pieces.push(Piece::Fragment(slf.clone().unbind()));
+            }
+            pieces.push(Piece::Fragment(fragment.clone().unbind()));
+        }
+        Ok(Sql::composed(py, pieces))
+    }
+
+    /// Render to `(sql, values)`: SQL text with `?` placeholders and the list
+    /// of values to bind to them.
+    fn render<'py>(&self, py: Python<'py>) -> PyResult<(String, Bound<'py, PyList>)> {
+        self.render_with(py, None)
+    }
+
+    fn __add__(slf: &Bound<'_, Self>, other: &Bound<'_, PyAny>) -> PyResult<Option<Sql>> {
+        let Ok(other) = other.cast::<Sql>() else {
+            return Ok(None);
+        };
+        Ok(Some(Sql::composed(
+            slf.py(),
+            vec![
+                Piece::Fragment(slf.clone().unbind()),
+                Piece::Fragment(other.clone().unbind()),
+            ],
+        )))
+    }
+
+    fn __repr__(&self) -> String {
+        format!("sql({:?})", self.template_text())
+    }
+}
+
+impl Piece {
+    fn clone_ref(&self, py: Python<'_>) -> Piece {
+        match self {
+            Piece::Text(text) => Piece::Text(text.clone()),
+            Piece::Value(name) => Piece::Value(name.clone()),
+            Piece::Identifier(name) => Piece::Identifier(name.clone()),
+            Piece::Fragment(fragment) => Piece::Fragment(fragment.clone_ref(py)),
+        }
+    }
+}
+
+/// Create a composable SQL template (see `Sql`).
+///
+/// # Example
+///
+/// ```python
+/// query = rapsqlite.sql("SELECT {cols} FROM {table} WHERE id IN :ids").bind(
+///     cols=["id", "name"], table="users", ids=[1, 2, 3]
+/// )
+/// rows = await conn.fetch_all(query)
+/// ```
+///
+/// # Errors
+///
+/// Raises ValueError for positional `?` placeholders and malformed `{name}`
+/// placeholders.
+#[pyfunction]
+pub(crate) fn sql(py: Python<'_>, template: &str) -> PyResult<Sql> {
+    let pieces = parse_template(template)
+        .map_err(ValueError::new_err)?
+        .into_iter()
+        .map(|part| match part {
+            TemplatePart::Text(text) => Piece::Text(text),
+            TemplatePart::Value(name) => Piece::Value(name),
+            TemplatePart::Identifier(name) => Piece::Identifier(name),
+        })
+        .collect();
+    Ok(Sql::composed(py, pieces))
+}
+
+/// A `query` argument: SQL text or a `rapsqlite.sql()` template.
+pub(crate) enum Query {
+    Text(String),
+    Template(Py<Sql>),
+}
+
+impl<'a, 'py> FromPyObject<'a, 'py> for Query {
+    type Error = PyErr;
+
+    fn extract(obj: Borrowed<'a, 'py, PyAny>) -> PyResult<Self> {
+        if let Ok(template) = obj.cast::<Sql>() {
+            return Ok(Query::Template(template.to_owned().unbind()));
+        }
+        Ok(Query::Text(obj.extract()?))
+    }
+}
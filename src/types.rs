@@ -1,13 +1,25 @@
 //! Shared internal types used across modules.
 
+use pyo3::exceptions::PyOverflowError;
 use pyo3::prelude::*;
-use pyo3::types::{PyBytes, PyFloat, PyInt, PyString};
+use pyo3::types::{PyByteArray, PyBytes, PyDict, PyFloat, PyInt, PyString};
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex as StdMutex};
 
+use crate::ValueError;
+
+use rapsqlite_core::group_commit::GroupCommitter;
+pub(crate) use rapsqlite_core::SqliteParam;
+
 // Type aliases for complex types to reduce clippy warnings
-pub(crate) type UserFunctions = Arc<StdMutex<HashMap<String, (i32, Py<PyAny>)>>>;
+/// Registered UDFs keyed by (lowercased name, nargs), so each arity of a name is a
+/// separate overload like in SQLite (nargs = -1 is the variadic fallback).
+pub(crate) type UserFunctions = Arc<StdMutex<HashMap<(String, i32), Arc<Py<PyAny>>>>>;
 pub(crate) type ProgressHandler = Arc<StdMutex<Option<(i32, Py<PyAny>)>>>;
+/// (rows affected, last insert rowid) of the statement a cursor executed, if any.
+pub(crate) type ExecuteResult = Arc<StdMutex<Option<(u64, i64)>>>;
+/// Writer task for `group_commit_window`, spawned on first use.
+pub(crate) type GroupCommitSlot = Arc<StdMutex<Option<GroupCommitter>>>;
 
 /// Transaction state tracking.
 #[derive(Clone, PartialEq)]
@@ -25,29 +37,113 @@ impl TransactionState {
     }
 }
 
-/// Convert a Python value to a SQLite-compatible value for binding.
-/// Returns a boxed value that can be used with sqlx query binding.
-#[derive(Clone)]
-pub(crate) enum SqliteParam {
-    Null,
-    Int(i64),
-    Real(f64),
-    Text(String),
-    Blob(Vec<u8>),
+/// How Python ints outside the signed 64-bit range are bound.
+#[derive(Clone, Copy, Default, PartialEq)]
+pub(crate) enum LargeIntPolicy {
+    /// Raise OverflowError (default).
+    #[default]
+    Error,
+    /// Bind the decimal representation as TEXT.
+    Text,
+    /// Bind the big-endian two's complement bytes as a BLOB.
+    Blob,
+}
+
+impl LargeIntPolicy {
+    pub(crate) fn parse(value: &str) -> Option<Self> {
+        match value {
+            "error" => Some(LargeIntPolicy::Error),
+            "text" => Some(LargeIntPolicy::Text),
+            "blob" => Some(LargeIntPolicy::Blob),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            LargeIntPolicy::Error => "error",
+            LargeIntPolicy::Text => "text",
+            LargeIntPolicy::Blob => "blob",
+        }
+    }
+}
+
+/// How TEXT values that are not valid UTF-8 are returned, both from fetches
+/// and as user-defined function arguments.
+#[derive(Clone, Copy, Default, PartialEq)]
+pub(crate) enum InvalidUtf8Policy {
+    /// Raise an error.
+    Error,
+    /// Decode with U+FFFD replacement characters.
+    Replace,
+    /// Return the raw bytes (default).
+    #[default]
+    Bytes,
+}
+
+impl InvalidUtf8Policy {
+    pub(crate) fn parse(value: &str) -> Option<Self> {
+        match value {
+            "error" => Some(InvalidUtf8Policy::Error),
+            "replace" => Some(InvalidUtf8Policy::Replace),
+            "bytes" => Some(InvalidUtf8Policy::Bytes),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            InvalidUtf8Policy::Error => "error",
+            InvalidUtf8Policy::Replace => "replace",
+            InvalidUtf8Policy::Bytes => "bytes",
+        }
+    }
+}
+
+/// Connection settings controlling how Python values are bound as parameters.
+#[derive(Clone, Copy, Default)]
+pub(crate) struct BindOptions {
+    pub(crate) large_int_policy: LargeIntPolicy,
+    /// Refuse conversions that could lose information (strict typing mode).
+    pub(crate) strict: bool,
+}
+
+/// Conversions between Python values and [`SqliteParam`]. An extension trait
+/// because the parameter type lives in `rapsqlite_core`, which has no Python
+/// dependency.
+pub(crate) trait SqliteParamExt: Sized {
+    /// Convert a Python value to a SQLite-compatible value for binding.
+    fn from_py(value: &Bound<'_, PyAny>, options: &BindOptions) -> PyResult<Self>;
+    /// Strict typing mode: accept only values SQLite stores without loss.
+    fn from_py_strict(value: &Bound<'_, PyAny>, options: &BindOptions) -> PyResult<Self>;
+    /// Bind a Python int outside the i64 range according to `policy`.
+    fn from_large_int(py_int: &Bound<'_, PyInt>, policy: LargeIntPolicy) -> PyResult<Self>;
+    /// Convert back to a Python object.
+    fn to_py(&self, py: Python<'_>) -> Py<PyAny>;
 }
 
-impl SqliteParam {
-    pub(crate) fn from_py(value: &Bound<'_, PyAny>) -> PyResult<Self> {
+impl SqliteParamExt for SqliteParam {
+    fn from_py(value: &Bound<'_, PyAny>, options: &BindOptions) -> PyResult<Self> {
         // Check for None first
         if value.is_none() {
             return Ok(SqliteParam::Null);
         }
 
+        if options.strict {
+            return Self::from_py_strict(value, options);
+        }
+
         // Try to extract as i64 (integer)
         if let Ok(int_val) = value.extract::<i64>() {
             return Ok(SqliteParam::Int(int_val));
         }
 
+        // Python ints that did not fit in i64 (checked before f64, which would
+        // silently round them)
+        if let Ok(py_int) = value.cast::<PyInt>() {
+            return Self::from_large_int(py_int, options.large_int_policy);
+        }
+
         // Try to extract as f64 (float)
         if let Ok(float_val) = value.extract::<f64>() {
             return Ok(SqliteParam::Real(float_val));
@@ -73,16 +169,6 @@ impl SqliteParam {
             return Ok(SqliteParam::Blob(py_bytes.as_bytes().to_vec()));
         }
 
-        // Try to extract as int (Python int)
-        if let Ok(py_int) = value.cast::<PyInt>() {
-            if let Ok(int_val) = py_int.extract::<i64>() {
-                return Ok(SqliteParam::Int(int_val));
-            }
-            // For very large Python ints, convert to string
-            // SQLite can handle large integers as text, but we'll keep as int if possible
-            return Ok(SqliteParam::Text(py_int.to_string()));
-        }
-
         // Try to extract as float
         if let Ok(py_float) = value.cast::<PyFloat>() {
             if let Ok(float_val) = py_float.extract::<f64>() {
@@ -100,4 +186,65 @@ impl SqliteParam {
             value.get_type().name()?
         )))
     }
+
+    fn from_py_strict(value: &Bound<'_, PyAny>, options: &BindOptions) -> PyResult<Self> {
+        if let Ok(py_int) = value.cast::<PyInt>() {
+            return match py_int.extract::<i64>() {
+                Ok(int_val) => Ok(SqliteParam::Int(int_val)),
+                Err(_) => Self::from_large_int(py_int, options.large_int_policy),
+            };
+        }
+        if let Ok(py_float) = value.cast::<PyFloat>() {
+            let float_val = py_float.value();
+            if float_val.is_nan() {
+                return Err(ValueError::new_err(
+                    "Cannot bind NaN in strict mode: SQLite stores NaN as NULL",
+                ));
+            }
+            return Ok(SqliteParam::Real(float_val));
+        }
+        if let Ok(py_str) = value.cast::<PyString>() {
+            return Ok(SqliteParam::Text(py_str.to_str()?.to_string()));
+        }
+        if let Ok(py_bytes) = value.cast::<PyBytes>() {
+            return Ok(SqliteParam::Blob(py_bytes.as_bytes().to_vec()));
+        }
+        if let Ok(py_bytearray) = value.cast::<PyByteArray>() {
+            return Ok(SqliteParam::Blob(py_bytearray.to_vec()));
+        }
+        Err(PyErr::new::<pyo3::exceptions::PyTypeError, _>(format!(
+            "Unsupported parameter type in strict mode: {}. Convert it to int, float, \
+             str, bytes, or None explicitly.",
+            value.get_type().name()?
+        )))
+    }
+
+    fn from_large_int(py_int: &Bound<'_, PyInt>, policy: LargeIntPolicy) -> PyResult<Self> {
+        match policy {
+            LargeIntPolicy::Error => Err(PyOverflowError::new_err(format!(
+                "Python int too large to bind as SQLite INTEGER (64-bit): {py_int}. \
+                 Set large_int_policy to \"text\" or \"blob\" to store it."
+            ))),
+            LargeIntPolicy::Text => Ok(SqliteParam::Text(py_int.to_string())),
+            LargeIntPolicy::Blob => {
+                // Minimal signed length: one extra bit for the sign
+                let bit_length: usize = py_int.call_method0("bit_length")?.extract()?;
+                let kwargs = PyDict::new(py_int.py());
+                kwargs.set_item("signed", true)?;
+                let bytes =
+                    py_int.call_method("to_bytes", (bit_length / 8 + 1, "big"), Some(&kwargs))?;
+                Ok(SqliteParam::Blob(bytes.extract()?))
+            }
+        }
+    }
+
+    fn to_py(&self, py: Python<'_>) -> Py<PyAny> {
+        match self {
+            SqliteParam::Null => py.None(),
+            SqliteParam::Int(v) => PyInt::new(py, *v).into_any().unbind(),
+            SqliteParam::Real(v) => PyFloat::new(py, *v).into_any().unbind(),
+            SqliteParam::Text(v) => PyString::new(py, v).into_any().unbind(),
+            SqliteParam::Blob(v) => PyBytes::new(py, v).into_any().unbind(),
+        }
+    }
 }
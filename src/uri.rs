@@ -0,0 +1,117 @@
+//! `rapsqlite.uri_filename()` and `rapsqlite.parse_uri()`: build and take
+//! apart SQLite `file:` URIs, escaping what SQLite would otherwise read as
+//! URI syntax.
+
+use pyo3::prelude::*;
+use pyo3::types::{PyBool, PyDict};
+use std::path::PathBuf;
+
+use crate::utils::parse_connection_string;
+use crate::ValueError;
+
+/// Percent-encode `text`, keeping ASCII letters and digits, the unreserved
+/// characters `-._~` and the bytes in `keep`.
+fn escape(text: &str, keep: &[u8]) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for &byte in text.as_bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) || keep.contains(&byte) {
+            escaped.push(byte as char);
+        } else {
+            escaped.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    escaped
+}
+
+/// Decode the `%XX` escapes of a URI component, as SQLite does; a `%` not
+/// followed by two hex digits is kept as is.
+fn unescape(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let byte = bytes
+            .get(i + 1..i + 3)
+            .filter(|_| bytes[i] == b'%')
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match byte {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Text of a query parameter value: booleans as 1/0, anything else as `str()`.
+fn param_value(value: &Bound<'_, PyAny>) -> PyResult<String> {
+    if let Ok(flag) = value.cast::<PyBool>() {
+        return Ok(if flag.is_true() { "1" } else { "0" }.to_string());
+    }
+    if value.is_none() {
+        return Err(ValueError::new_err("URI parameter values cannot be None"));
+    }
+    Ok(value.str()?.to_string())
+}
+
+/// The `file:` URI for the database at `path` with query parameters `params`,
+/// e.g. `uri_filename("/data/app.db", mode="ro", immutable=True)` gives
+/// `file:///data/app.db?mode=ro&immutable=1`.
+///
+/// `?`, `#`, `%`, spaces and non-ASCII characters of the path and the
+/// parameters are percent-encoded; boolean values become 1 or 0.
+#[pyfunction]
+#[pyo3(signature = (path, **params))]
+pub(crate) fn uri_filename(path: PathBuf, params: Option<&Bound<'_, PyDict>>) -> PyResult<String> {
+    let path = path
+        .to_str()
+        .ok_or_else(|| ValueError::new_err("path is not valid UTF-8"))?;
+    let mut path = path.to_string();
+    if cfg!(windows) {
+        path = path.replace('\\', "/");
+        // Drive letters: file:///C:/data/app.db
+        if path.as_bytes().get(1) == Some(&b':') && path.as_bytes()[0].is_ascii_alphabetic() {
+            path.insert(0, '/');
+        }
+    }
+
+    let mut uri = String::from("file:");
+    if path.starts_with('/') {
+        // An empty authority, so a path starting with // is not read as a host
+        uri.push_str("//");
+    }
+    uri.push_str(&escape(&path, b"/:"));
+
+    let mut separator = '?';
+    for (key, value) in params.into_iter().flat_map(|params| params.iter()) {
+        let key = key.extract::<String>()?;
+        uri.push(separator);
+        uri.push_str(&escape(&key, b""));
+        uri.push('=');
+        uri.push_str(&escape(&param_value(&value)?, b"/:"));
+        separator = '&';
+    }
+    Ok(uri)
+}
+
+/// Split a database name into its path and query parameters, decoding
+/// percent-escapes: the reverse of `uri_filename()`. Names that are not
+/// `file:` URIs are returned unchanged with no parameters.
+#[pyfunction]
+pub(crate) fn parse_uri<'py>(py: Python<'py>, uri: &str) -> PyResult<(String, Bound<'py, PyDict>)> {
+    let (path, params) = parse_connection_string(uri)?;
+    if !uri.starts_with("file:") {
+        return Ok((path, PyDict::new(py)));
+    }
+    let dict = PyDict::new(py);
+    for (key, value) in params {
+        dict.set_item(unescape(&key), unescape(&value))?;
+    }
+    Ok((unescape(&path), dict))
+}
@@ -1,52 +1,18 @@
 //! Miscellaneous internal helpers (query/path/utilities).
 
 use pyo3::prelude::*;
+use rapsqlite_core::debug::{NPlusOneDetector, TaskAudit, TaskRef};
 use std::collections::HashMap;
-use std::ffi::CStr;
+use std::ffi::{CStr, CString};
 use std::sync::{Arc, Mutex as StdMutex};
 
-/// Detect if a query is a SELECT query (for determining execution strategy).
-pub(crate) fn is_select_query(query: &str) -> bool {
-    let trimmed = query.trim().to_uppercase();
-    trimmed.starts_with("SELECT") || trimmed.starts_with("WITH")
-}
+use crate::exceptions::{NPlusOneWarning, TaskSafetyWarning};
+use crate::types::SqliteParam;
 
-/// Normalize a SQL query by removing extra whitespace and standardizing formatting.
-/// This helps improve prepared statement cache hit rates by ensuring queries with
-/// different whitespace are treated as identical.
-///
-/// **Prepared Statement Caching (Phase 2.13):**
-/// sqlx (the underlying database library) automatically caches prepared statements
-/// per connection. When the same query is executed multiple times on the same
-/// connection, sqlx reuses the prepared statement, providing significant performance
-/// benefits. This normalization function ensures that queries with only whitespace
-/// differences are treated as identical, maximizing cache hit rates.
-///
-/// The prepared statement cache is managed entirely by sqlx and does not require
-/// explicit configuration. Each connection in the pool maintains its own cache,
-/// and statements are automatically prepared on first use and reused for subsequent
-/// executions of the same query.
-pub(crate) fn normalize_query(query: &str) -> String {
-    // Remove leading/trailing whitespace
-    let trimmed = query.trim();
-    // Replace multiple whitespace characters with single space
-    let normalized: String = trimmed
-        .chars()
-        .fold((String::new(), false), |(acc, was_space), ch| {
-            let is_space = ch.is_whitespace();
-            if is_space && was_space {
-                // Skip multiple consecutive spaces
-                (acc, true)
-            } else if is_space {
-                // Replace any whitespace with single space
-                (acc + " ", true)
-            } else {
-                (acc + &ch.to_string(), false)
-            }
-        })
-        .0;
-    normalized
-}
+pub(crate) use rapsqlite_core::sql::{
+    has_returning, hidden_column_kind, is_large_int_literal, normalize_query, parse_column_clauses,
+    quote_identifier, returns_rows_hint, second_statement, split_statements,
+};
 
 /// Track query usage in the cache for analytics and optimization.
 /// This helps identify frequently used queries that benefit from prepared statement caching.
@@ -59,6 +25,177 @@ pub(crate) fn track_query_usage(query_cache: &Arc<StdMutex<HashMap<String, u64>>
     *cache.entry(normalized).or_insert(0) += 1;
 }
 
+/// The current asyncio task. Outside a running loop there is no task; such
+/// calls share id 0.
+pub(crate) fn current_task(py: Python<'_>) -> PyResult<TaskRef> {
+    let task = py
+        .import("asyncio")?
+        .call_method0("current_task")
+        .ok()
+        .filter(|task| !task.is_none());
+    Ok(match task {
+        Some(task) => TaskRef {
+            id: task.as_ptr() as u64,
+            name: task.call_method0("get_name")?.extract()?,
+        },
+        None => TaskRef {
+            id: 0,
+            name: "<no task>".to_string(),
+        },
+    })
+}
+
+/// Record a statement with the connection's N+1 detector (debug mode), scoped to
+/// the current asyncio task, and emit `NPlusOneWarning` when it reports.
+pub(crate) fn check_n_plus_one(
+    detector: &Option<Arc<StdMutex<NPlusOneDetector>>>,
+    query: &str,
+    params: &[SqliteParam],
+) -> PyResult<()> {
+    let Some(detector) = detector else {
+        return Ok(());
+    };
+    Python::attach(|py| {
+        let scope = current_task(py)?.id;
+        let Some(report) = detector.lock().unwrap().record(scope, query, params) else {
+            return Ok(());
+        };
+        let message = format!(
+            "Possible N+1 query: {:?} ran {} times with {} different parameter values \
+             in one task; fetch the rows in a single query (e.g. WHERE ... IN (...) or a JOIN)",
+            report.query, report.count, report.distinct
+        );
+        let message = CString::new(message).unwrap_or_default();
+        PyErr::warn(py, &py.get_type::<NPlusOneWarning>(), &message, 1)
+    })
+}
+
+/// Emit a `TaskSafetyWarning` pointing at the caller's code.
+fn warn_task_safety(py: Python<'_>, message: String) -> PyResult<()> {
+    let message = CString::new(message).unwrap_or_default();
+    PyErr::warn(py, &py.get_type::<TaskSafetyWarning>(), &message, 1)
+}
+
+/// Task-safety audit (debug mode): the current task is about to run a
+/// statement. Warns if another task changed a setting this task set, if the
+/// statement joins a transaction another task began, or if it will run under a
+/// trace callback, authorizer, progress handler or update hook another task
+/// installed.
+pub(crate) fn audit_statement(audit: &Option<Arc<StdMutex<TaskAudit>>>) -> PyResult<()> {
+    let Some(audit) = audit else {
+        return Ok(());
+    };
+    Python::attach(|py| {
+        let task = current_task(py)?;
+        let (changed, owner) = audit.lock().unwrap().statement(&task);
+        for (setting, by) in changed {
+            warn_task_safety(
+                py,
+                format!(
+                    "Task '{}' set {setting} on this Connection, but task '{}' changed it \
+                     since; tasks sharing a Connection share its settings (use \
+                     conn.options() for per-task row_factory/text_factory)",
+                    task.name, by.name
+                ),
+            )?;
+        }
+        if let Some(owner) = owner {
+            warn_task_safety(
+                py,
+                format!(
+                    "Task '{}' ran a statement inside the transaction begun by task '{}'; \
+                     it will be committed or rolled back with that transaction",
+                    task.name, owner.name
+                ),
+            )?;
+        }
+        let foreign = audit.lock().unwrap().foreign_callbacks(&task);
+        for (callback, by) in foreign {
+            warn_task_safety(
+                py,
+                format!(
+                    "Task '{}' ran a statement while the {callback} installed by task '{}' \
+                     is set; tasks sharing a Connection share its callback connection, so \
+                     that callback also runs for this task's statements",
+                    task.name, by.name
+                ),
+            )?;
+        }
+        Ok(())
+    })
+}
+
+/// Task-safety audit (debug mode): the current task installed (`installed`) or
+/// cleared `callback` on the callback connection.
+pub(crate) fn audit_callback(
+    audit: &Option<Arc<StdMutex<TaskAudit>>>,
+    callback: &str,
+    installed: bool,
+) -> PyResult<()> {
+    let Some(audit) = audit else {
+        return Ok(());
+    };
+    Python::attach(|py| {
+        let task = current_task(py)?;
+        audit
+            .lock()
+            .unwrap()
+            .callback_changed(&task, callback, installed);
+        Ok(())
+    })
+}
+
+/// Task-safety audit (debug mode): the current task changed `setting`.
+pub(crate) fn audit_setting(
+    audit: &Option<Arc<StdMutex<TaskAudit>>>,
+    setting: &str,
+) -> PyResult<()> {
+    let Some(audit) = audit else {
+        return Ok(());
+    };
+    Python::attach(|py| {
+        let task = current_task(py)?;
+        audit.lock().unwrap().setting_changed(&task, setting);
+        Ok(())
+    })
+}
+
+/// Task-safety audit (debug mode): the audit and the current task, to record
+/// as the transaction's owner once its BEGIN succeeds.
+pub(crate) fn audit_transaction_begin(
+    audit: &Option<Arc<StdMutex<TaskAudit>>>,
+) -> PyResult<Option<(Arc<StdMutex<TaskAudit>>, TaskRef)>> {
+    let Some(audit) = audit else {
+        return Ok(None);
+    };
+    Python::attach(|py| Ok(Some((Arc::clone(audit), current_task(py)?))))
+}
+
+/// Task-safety audit (debug mode): the current task is about to `action`
+/// ("commit" or "roll back") the open transaction. Warns if another task began
+/// it.
+pub(crate) fn audit_transaction_end(
+    audit: &Option<Arc<StdMutex<TaskAudit>>>,
+    action: &str,
+) -> PyResult<()> {
+    let Some(audit) = audit else {
+        return Ok(());
+    };
+    Python::attach(|py| {
+        let task = current_task(py)?;
+        let Some(owner) = audit.lock().unwrap().transaction_ending(&task) else {
+            return Ok(());
+        };
+        warn_task_safety(
+            py,
+            format!(
+                "Task '{}' is about to {action} the transaction begun by task '{}'",
+                task.name, owner.name
+            ),
+        )
+    })
+}
+
 /// Validate a file path for security and correctness.
 ///
 /// Checks for:
@@ -104,6 +241,38 @@ pub(crate) fn validate_path(path: &str) -> PyResult<()> {
     Ok(())
 }
 
+/// Render a Python value as the SQLite literal of a `PRAGMA name = value`.
+///
+/// Booleans become 1/0 (not `True`, which integer PRAGMAs reject), ints and
+/// finite floats their numeric form, strings a single-quoted literal and None
+/// NULL. Other types raise TypeError.
+pub(crate) fn pragma_literal(value: &Bound<'_, PyAny>) -> PyResult<String> {
+    use pyo3::types::{PyBool, PyFloat, PyInt, PyString};
+
+    if value.is_none() {
+        Ok("NULL".to_string())
+    } else if let Ok(flag) = value.cast::<PyBool>() {
+        Ok(if flag.is_true() { "1" } else { "0" }.to_string())
+    } else if value.is_instance_of::<PyInt>() {
+        Ok(value.extract::<i64>()?.to_string())
+    } else if value.is_instance_of::<PyFloat>() {
+        let number = value.extract::<f64>()?;
+        if !number.is_finite() {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "PRAGMA value must be finite, got {number}"
+            )));
+        }
+        Ok(format!("{number:?}"))
+    } else if let Ok(text) = value.cast::<PyString>() {
+        Ok(format!("'{}'", text.to_str()?.replace('\'', "''")))
+    } else {
+        Err(PyErr::new::<pyo3::exceptions::PyTypeError, _>(format!(
+            "PRAGMA value must be bool, int, float, str or None, not {}",
+            value.get_type().name()?
+        )))
+    }
+}
+
 /// Parse SQLite connection string (URI format: file:path?param=value&param2=value2).
 /// Returns (database_path, vec of (param_name, param_value)).
 pub(crate) fn parse_connection_string(uri: &str) -> PyResult<(String, Vec<(String, String)>)> {
@@ -218,28 +387,6 @@ pub(crate) unsafe fn cstr_from_i8_ptr(ptr: *const i8) -> &'static CStr {
 mod tests {
     use super::*;
 
-    #[test]
-    fn test_is_select_query_basic() {
-        assert!(is_select_query("SELECT 1"));
-        assert!(is_select_query(" select 1 "));
-        assert!(is_select_query("\n\tSELECT 1"));
-        assert!(is_select_query("WITH cte AS (SELECT 1) SELECT * FROM cte"));
-
-        assert!(!is_select_query("INSERT INTO t VALUES (1)"));
-        assert!(!is_select_query("UPDATE t SET x = 1"));
-        assert!(!is_select_query("DELETE FROM t"));
-        assert!(!is_select_query("PRAGMA foreign_keys = ON"));
-    }
-
-    #[test]
-    fn test_normalize_query_whitespace() {
-        assert_eq!(normalize_query("  SELECT   1  "), "SELECT 1");
-        assert_eq!(normalize_query("SELECT\t1"), "SELECT 1");
-        assert_eq!(normalize_query("SELECT\n1"), "SELECT 1");
-        assert_eq!(normalize_query("SELECT\r\n1"), "SELECT 1");
-        assert_eq!(normalize_query("SELECT  1   FROM   t"), "SELECT 1 FROM t");
-    }
-
     #[test]
     fn test_parse_connection_string_memory() {
         let (path, params) = parse_connection_string(":memory:").unwrap();
@@ -18,14 +18,16 @@ if sys.platform == "win32":
 
 
 def cleanup_db(test_db: str) -> None:
-    """Helper to clean up database file.
+    """Helper to clean up database file and its journal/WAL files.
 
     Args:
         test_db: Path to database file to clean up
     """
-    if os.path.exists(test_db):
+    for path in (test_db, test_db + "-journal", test_db + "-wal", test_db + "-shm"):
+        if not os.path.exists(path):
+            continue
         try:
-            os.unlink(test_db)
+            os.unlink(path)
         except (PermissionError, OSError):
             # On Windows, database files may still be locked by SQLite
             # This is a cleanup issue, not a test failure
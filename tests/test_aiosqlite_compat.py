@@ -14,7 +14,13 @@ import sys
 import tempfile
 from pathlib import Path
 
-from rapsqlite import Connection, connect, OperationalError
+from rapsqlite import (
+    Connection,
+    DatabaseError,
+    OperationalError,
+    ProgrammingError,
+    connect,
+)
 
 
 def cleanup_db(test_db: str) -> None:
@@ -187,9 +193,7 @@ async def test_close_twice(test_db):
 
 @pytest.mark.asyncio
 async def test_connection_await(test_db):
-    """Test connection creation (rapsqlite doesn't require await for connect)."""
-    # In rapsqlite, connect() returns Connection directly, not awaitable
-    # But Connection() also works
+    """Test awaiting connect() as in aiosqlite; Connection() works unawaited."""
     db = Connection(test_db)
     assert isinstance(db, Connection)
 
@@ -198,6 +202,15 @@ async def test_connection_await(test_db):
 
     await db.close()
 
+    db = await connect(test_db)
+    assert isinstance(db, Connection)
+    assert await db.fetch_all("SELECT 1, 2") == [[1, 2]]
+    await db.close()
+
+    # Awaiting opens the database, so errors surface at connect time
+    with pytest.raises(OperationalError):
+        await connect("/something/that/shouldnt/exist/test.db")
+
 
 @pytest.mark.asyncio
 async def test_connection_properties(test_db):
@@ -438,6 +451,21 @@ async def test_create_function_multiple_args(test_db):
         assert result[0] == "HELLO"
 
 
+@pytest.mark.asyncio
+async def test_create_function_deterministic(test_db):
+    """deterministic=True allows a function in index expressions."""
+    async with connect(test_db) as db:
+        await db.execute("CREATE TABLE t (name TEXT)")
+        await db.create_function("fold", 1, str.casefold)
+        with pytest.raises(DatabaseError, match="non-deterministic"):
+            await db.execute("CREATE INDEX t_fold ON t (fold(name))")
+
+        await db.create_function("fold", 1, str.casefold, deterministic=True)
+        await db.execute("CREATE INDEX t_fold ON t (fold(name))")
+        await db.execute("INSERT INTO t VALUES ('Straße')")
+        rows = await db.fetch_all("SELECT name FROM t WHERE fold(name) = 'strasse'")
+        assert rows == [["Straße"]]
+
 @pytest.mark.asyncio
 async def test_create_function_type_conversions(test_db):
     """Test custom functions with different return types."""
@@ -685,6 +713,48 @@ async def test_set_trace_callback_remove(test_db):
         assert len(traced_statements) == initial_count
 
 
+def test_sqlite_constants():
+    """Test that SQLite constants are exported as attributes and enums."""
+    import sqlite3
+
+    import rapsqlite
+
+    for name in ("SQLITE_OK", "SQLITE_DENY", "SQLITE_IGNORE", "SQLITE_READ"):
+        assert getattr(rapsqlite, name) == getattr(sqlite3, name)
+        assert name in rapsqlite.__all__
+    assert rapsqlite.SQLITE_BUSY == 5
+    assert rapsqlite.SQLITE_LIMIT_VARIABLE_NUMBER == 9
+    assert rapsqlite.SQLITE_OPEN_READWRITE == 0x2
+
+    assert rapsqlite.AuthorizerResult.DENY == sqlite3.SQLITE_DENY
+    assert rapsqlite.AuthorizerAction(sqlite3.SQLITE_INSERT).name == "INSERT"
+    assert rapsqlite.ResultCode.BUSY == rapsqlite.SQLITE_BUSY
+    assert rapsqlite.Limit.LENGTH == rapsqlite.SQLITE_LIMIT_LENGTH
+    flags = rapsqlite.OpenFlag.READWRITE | rapsqlite.OpenFlag.CREATE
+    assert flags == rapsqlite.SQLITE_OPEN_READWRITE | rapsqlite.SQLITE_OPEN_CREATE
+
+
+@pytest.mark.asyncio
+async def test_authorizer_with_constants(test_db):
+    """Test an authorizer written with the exported constants."""
+    from rapsqlite import AuthorizerAction, SQLITE_DENY, SQLITE_OK
+
+    async with connect(test_db) as db:
+        await db.execute("CREATE TABLE t (x INTEGER)")
+
+        def authorizer(action, arg1, arg2, arg3, arg4):
+            if action == AuthorizerAction.DELETE:
+                return SQLITE_DENY
+            return SQLITE_OK
+
+        await db.set_authorizer(authorizer)
+        await db.execute("INSERT INTO t VALUES (1)")
+        with pytest.raises(DatabaseError, match="not authorized"):
+            await db.execute("DELETE FROM t")
+        await db.set_authorizer(None)
+        assert await db.fetch_all("SELECT x FROM t") == [[1]]
+
+
 @pytest.mark.asyncio
 async def test_set_authorizer_comprehensive(test_db):
     """Test authorizer with various operations."""
@@ -1358,11 +1428,32 @@ async def test_cursor_return_self(test_db):
     pass
 
 
-@pytest.mark.skip(reason="Connection internal state tracking differs in rapsqlite")
 @pytest.mark.asyncio
 async def test_cursor_on_closed_connection(test_db):
-    """Test cursor behavior on closed connection."""
-    pass
+    """Test cursors are closed with their connection."""
+    db = Connection(test_db)
+    cursor = await db.execute("SELECT 1, 2")
+    manual = db.cursor()
+    await manual.execute("SELECT 3")
+    assert await manual.fetchone() == [3]
+    await db.close()
+
+    for call in (
+        lambda: cursor.execute("SELECT 1, 2"),
+        lambda: cursor.fetchall(),
+        lambda: manual.fetchone(),
+    ):
+        with pytest.raises(ProgrammingError, match="closed cursor"):
+            await call()
+
+    # The connection reopens on use; new cursors work, closed ones stay closed
+    fresh = db.cursor()
+    await fresh.execute("SELECT 4")
+    assert await fresh.fetchall() == [[4]]
+    await fresh.close()
+    with pytest.raises(ProgrammingError, match="closed cursor"):
+        await fresh.fetchone()
+    await db.close()
 
 
 @pytest.mark.skip(reason="Connection internal state tracking differs in rapsqlite")
@@ -1605,6 +1696,66 @@ async def test_cursor_fetchmany_size_based(test_db):
             assert len(rows5) == 0
 
 
+
+@pytest.mark.asyncio
+async def test_cursor_arraysize(test_db):
+    """Test that fetchmany() without a size returns arraysize rows."""
+    async with connect(test_db) as db:
+        await db.execute("CREATE TABLE t (value INTEGER)")
+        await db.execute_many("INSERT INTO t VALUES (?)", [[i] for i in range(7)])
+
+        cursor = db.cursor()
+        assert cursor.arraysize == 1
+        await cursor.execute("SELECT value FROM t ORDER BY value")
+        assert await cursor.fetchmany() == [[0]]
+
+        cursor.arraysize = 3
+        assert cursor.arraysize == 3
+        assert await cursor.fetchmany() == [[1], [2], [3]]
+        assert await cursor.fetchone() == [4]
+        assert await cursor.fetchmany(1) == [[5]]
+        assert await cursor.fetchmany() == [[6]]
+        assert await cursor.fetchmany() == []
+
+        # A new query starts from the first row, keeping arraysize
+        await cursor.execute("SELECT value FROM t ORDER BY value")
+        assert await cursor.fetchmany() == [[0], [1], [2]]
+
+        with pytest.raises(ValueError):
+            cursor.arraysize = 0
+        assert cursor.arraysize == 3
+
+
+@pytest.mark.asyncio
+async def test_cursor_fetchall_runs_once_and_copy_false(test_db):
+    """Test fetchall() reads the buffered rows; copy=False iterates them in place."""
+    async with connect(test_db) as db:
+        calls = []
+        await db.create_function("tick", 1, lambda x: calls.append(x) or x)
+        await db.execute("CREATE TABLE t (value INTEGER)")
+        await db.execute_many("INSERT INTO t VALUES (?)", [[i] for i in range(4)])
+
+        cursor = await db.execute("SELECT tick(value) FROM t ORDER BY value")
+        assert await cursor.fetchone() == [0]
+        assert await cursor.fetchall() == [[1], [2], [3]]
+        assert await cursor.fetchall() == []
+        assert len(calls) == 4  # the statement ran once
+
+        cursor = db.cursor()
+        await cursor.execute("SELECT tick(value) FROM t ORDER BY value")
+        assert await cursor.fetchone() == [0]
+        rows = await cursor.fetchall(copy=False)
+        assert next(rows) == [1]
+        # The iterator shares the cursor's position
+        assert await cursor.fetchone() == [2]
+        assert list(rows) == [[3]]
+        assert await cursor.fetchone() is None
+        assert len(calls) == 8
+
+        await cursor.close()
+        with pytest.raises(ProgrammingError, match="closed cursor"):
+            next(rows)
+
 @pytest.mark.asyncio
 async def test_set_pragma(test_db):
     """Test PRAGMA settings via set_pragma method (Phase 2.3)."""
@@ -1626,6 +1777,19 @@ async def test_set_pragma(test_db):
         )  # NORMAL = 1 (per SQLite: 0=OFF, 1=NORMAL, 2=FULL, 3=EXTRA)
 
 
+@pytest.mark.asyncio
+async def test_set_pragma_returns_effective_value(test_db):
+    """set_pragma renders bools as 1/0 and resolves to the value SQLite reports."""
+    async with connect(test_db) as db:
+        assert await db.set_pragma("foreign_keys", True) == 1
+        assert await db.fetch_all("PRAGMA foreign_keys") == [[1]]
+        assert await db.set_pragma("foreign_keys", False) == 0
+        assert await db.set_pragma("journal_mode", "WAL") == "wal"
+        assert await db.set_pragma("user_version", 7) == 7
+        with pytest.raises(TypeError):
+            await db.set_pragma("user_version", [7])
+
+
 @pytest.mark.asyncio
 async def test_pragma_constructor_parameter(test_db):
     """Test PRAGMA settings via constructor parameter (Phase 2.3)."""
@@ -1661,6 +1825,41 @@ async def test_connection_string_uri_parsing(test_db):
         assert rows[0][0] == "test"
 
 
+
+def test_uri_filename_and_parse_uri():
+    """Test building escaped file: URIs and parsing them back."""
+    import pathlib
+
+    from rapsqlite import parse_uri, uri_filename
+
+    uri = uri_filename("/data/my db?#%.sqlite", mode="ro", immutable=True, vfs="a&b")
+    assert uri == (
+        "file:///data/my%20db%3F%23%25.sqlite?mode=ro&immutable=1&vfs=a%26b"
+    )
+    assert parse_uri(uri) == (
+        "/data/my db?#%.sqlite",
+        {"mode": "ro", "immutable": "1", "vfs": "a&b"},
+    )
+    assert uri_filename(pathlib.PurePosixPath("rel/app.db")) == "file:rel/app.db"
+    assert uri_filename("café.db", cache=False) == "file:caf%C3%A9.db?cache=0"
+    assert parse_uri("plain.db") == ("plain.db", {})
+    assert parse_uri(":memory:") == (":memory:", {})
+
+
+@pytest.mark.asyncio
+async def test_connect_with_uri_filename(tmp_path):
+    """Test that a URI built by uri_filename() opens the file it names."""
+    from rapsqlite import uri_filename
+
+    path = tmp_path / "odd name?#%.db"
+    path.touch()
+    async with connect(uri_filename(path)) as db:
+        await db.execute("CREATE TABLE t (x INTEGER)")
+        await db.execute("INSERT INTO t VALUES (1)")
+    assert path.stat().st_size > 0
+    async with connect(uri_filename(path)) as db:
+        assert await db.fetch_all("SELECT x FROM t") == [[1]]
+
 @pytest.mark.asyncio
 async def test_parameter_missing_error(test_db):
     """Test error handling for missing parameters."""
@@ -1709,6 +1908,94 @@ async def test_mixed_named_parameters(test_db):
         assert rows[0][2] == 3.14
 
 
+@pytest.mark.asyncio
+async def test_default_named_parameters(test_db):
+    """set_default_params() fills named parameters the call doesn't pass."""
+    async with connect(test_db) as db:
+        await db.execute(
+            "CREATE TABLE orders (id INTEGER PRIMARY KEY, tenant_id INTEGER, item TEXT)"
+        )
+        await db.execute_many(
+            "INSERT INTO orders (tenant_id, item) VALUES (?, ?)",
+            [[1, "a"], [1, "b"], [2, "c"]],
+        )
+        db.set_default_params({"tenant_id": 1})
+        query = "SELECT item FROM orders WHERE tenant_id = :tenant_id ORDER BY id"
+
+        assert await db.fetch_all(query) == [["a"], ["b"]]
+        assert await db.fetch_one(query + " LIMIT 1") == ["a"]
+        assert await db.fetch_first(query) == ["a"]
+        # Values passed with the call win; other names still come from the defaults
+        assert await db.fetch_all(query, {"tenant_id": 2}) == [["c"]]
+        by_item = "SELECT id FROM orders WHERE tenant_id = :tenant_id AND item = :item"
+        assert await db.fetch_optional(by_item, {"item": "b"}) == [2]
+        assert await db.fetch_optional(by_item, {"item": "c"}) is None
+        await db.execute("INSERT INTO orders (tenant_id, item) VALUES (:tenant_id, 'd')")
+        cursor = db.cursor()
+        await cursor.execute(query)
+        assert await cursor.fetchall() == [["a"], ["b"], ["d"]]
+        # Positional parameters are not affected
+        assert await db.fetch_all("SELECT item FROM orders WHERE tenant_id = ?", [2]) == [
+            ["c"]
+        ]
+
+        db.set_default_params(None)
+        with pytest.raises(OperationalError):
+            await db.fetch_all(query)
+        with pytest.raises(TypeError):
+            db.set_default_params([1])
+
+
+@pytest.mark.asyncio
+async def test_sequence_and_mapping_parameters(test_db):
+    """Any Sequence/iterable binds positionally and any Mapping by name (sqlite3)."""
+    import collections.abc
+    import types
+
+    class Params(collections.abc.Mapping):
+        def __init__(self, values):
+            self.values = values
+
+        def __getitem__(self, key):
+            return self.values[key]
+
+        def __iter__(self):
+            return iter(self.values)
+
+        def __len__(self):
+            return len(self.values)
+
+    async with connect(test_db) as db:
+        await db.execute("CREATE TABLE t (a TEXT, b INTEGER)")
+        await db.execute("INSERT INTO t VALUES (?, ?)", ("x", 1))
+        await db.execute("INSERT INTO t VALUES (?, ?)", (v for v in ["y", 2]))
+        await db.execute("INSERT INTO t VALUES (:a, :b)", Params({"a": "z", "b": 3}))
+        await db.execute(
+            "INSERT INTO t VALUES (:a, :b)", types.MappingProxyType({"a": "w", "b": 4})
+        )
+        assert await db.fetch_all("SELECT a, b FROM t ORDER BY b") == [
+            ["x", 1],
+            ["y", 2],
+            ["z", 3],
+            ["w", 4],
+        ]
+        assert await db.fetch_all("SELECT a FROM t WHERE b = ?", (2,)) == [["y"]]
+        assert await db.fetch_all("SELECT a FROM t WHERE b IN (?, ?)", range(3, 5)) == [
+            ["z"],
+            ["w"],
+        ]
+        # str and bytes are single values, not sequences of characters
+        assert await db.fetch_all("SELECT ?", "abc") == [["abc"]]
+        assert await db.fetch_all("SELECT count(*) FROM t", ()) == [[4]]
+        with pytest.raises(KeyError):
+            await db.fetch_all("SELECT :missing", Params({}))
+
+        # A generator passed to a cursor is collected, so fetching can re-bind it
+        async with db.cursor() as cursor:
+            await cursor.execute("SELECT a FROM t WHERE b > ?", (v for v in [3]))
+            assert await cursor.fetchall() == [["w"]]
+
+
 @pytest.mark.asyncio
 async def test_cursor_parameterized_queries(test_db):
     """Test parameterized queries with cursor methods."""
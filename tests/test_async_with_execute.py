@@ -169,6 +169,29 @@ async def test_cursor_from_execute_fetchmany():
         cleanup_db(test_db)
 
 
+@pytest.mark.asyncio
+async def test_async_for_over_cursor_and_execute(test_db):
+    """Test async iteration over a cursor and over conn.execute(...)."""
+    async with connect(test_db) as conn:
+        await conn.execute("CREATE TABLE test (id INTEGER PRIMARY KEY, value INTEGER)")
+        for i in range(5):
+            await conn.execute("INSERT INTO test (value) VALUES (?)", [i])
+
+        async with conn.execute("SELECT value FROM test ORDER BY id") as cursor:
+            assert await cursor.fetchone() == [0]
+            # Iteration continues from the cursor's position
+            assert [row async for row in cursor] == [[1], [2], [3], [4]]
+            assert [row async for row in cursor] == []
+
+        query = "SELECT value FROM test WHERE value > ?"
+        assert [row async for row in conn.execute(query, [2])] == [[3], [4]]
+
+        # A statement returning no rows runs once and yields nothing
+        async for _ in conn.execute("INSERT INTO test (value) VALUES (5)"):
+            raise AssertionError("INSERT yielded a row")
+        assert await conn.fetch_one("SELECT COUNT(*) FROM test") == [6]
+
+
 @pytest.mark.asyncio
 async def test_async_with_execute_parameterized():
     """Test async with db.execute() with parameterized queries."""
@@ -315,3 +338,92 @@ async def test_execute_delete_returns_cursor():
             assert rows[0][1] == "world"
     finally:
         cleanup_db(test_db)
+
+
+@pytest.mark.asyncio
+async def test_execute_detects_statement_kind_by_preparing():
+    """Statements the leading keyword doesn't classify are prepared to tell."""
+    with tempfile.NamedTemporaryFile(suffix=".db", delete=False) as f:
+        test_db = f.name
+
+    try:
+        async with connect(test_db) as conn:
+            await conn.execute("CREATE TABLE test (id INTEGER PRIMARY KEY, value TEXT)")
+
+            # CTE-led DML runs when awaited, without a fetch
+            cursor = await conn.execute(
+                "WITH v(x) AS (VALUES ('a'), ('b')) INSERT INTO test (value) "
+                "SELECT x FROM v"
+            )
+            assert cursor.rowcount == 2
+            assert await cursor.fetchall() == []
+            assert await conn.fetch_all("SELECT value FROM test ORDER BY id") == [
+                ["a"],
+                ["b"],
+            ]
+
+            # VALUES, row-returning PRAGMAs and EXPLAIN return their rows
+            async with conn.execute("VALUES (1, 'x'), (2, 'y')") as cursor:
+                assert await cursor.fetchall() == [[1, "x"], [2, "y"]]
+                assert cursor.rowcount == -1
+            async with conn.execute("PRAGMA table_info(test)") as cursor:
+                assert [row[1] for row in await cursor.fetchall()] == ["id", "value"]
+            async with conn.execute("EXPLAIN DELETE FROM test") as cursor:
+                assert len(await cursor.fetchall()) > 0
+            assert len(await conn.fetch_all("SELECT * FROM test")) == 2
+
+            # PRAGMAs changing a setting still run when awaited
+            await conn.execute("PRAGMA user_version = 7")
+            cursor = await conn.execute("PRAGMA user_version")
+            assert await cursor.fetchall() == [[7]]
+
+            # The same holds for statements run through a cursor
+            cursor = conn.cursor()
+            await cursor.execute(
+                "WITH v(x) AS (VALUES ('c')) INSERT INTO test (value) SELECT x FROM v"
+            )
+            assert cursor.rowcount == 1
+            assert await cursor.fetchall() == []
+            await cursor.execute("VALUES (3)")
+            assert await cursor.fetchall() == [[3]]
+            assert len(await conn.fetch_all("SELECT * FROM test")) == 3
+    finally:
+        cleanup_db(test_db)
+
+
+@pytest.mark.asyncio
+async def test_execute_runs_dml_once(test_db):
+    """DML runs exactly once, however its cursor is fetched from afterwards."""
+    async with connect(test_db) as conn:
+        await conn.execute("CREATE TABLE test (id INTEGER PRIMARY KEY, value INTEGER)")
+
+        cursor = await conn.execute("INSERT INTO test (value) VALUES (1)")
+        assert await cursor.fetchone() is None
+        assert await cursor.fetchmany(5) == []
+        assert await cursor.fetchall() == []
+        async with conn.execute("INSERT INTO test (value) VALUES (2)") as cursor:
+            assert await cursor.fetchone() is None
+        assert await conn.fetch_one("SELECT COUNT(*) FROM test") == [2]
+
+        # RETURNING rows are kept from the run, in every fetch style
+        cursor = await conn.execute(
+            "INSERT INTO test (value) VALUES (?), (?) RETURNING id, value", [3, 4]
+        )
+        assert cursor.rowcount == 2
+        assert cursor.lastrowid == 4
+        assert await cursor.fetchone() == [3, 3]
+        assert await cursor.fetchall() == [[4, 4]]
+        assert await cursor.fetchall() == []
+        rows = [
+            row async for row in conn.execute("DELETE FROM test WHERE id > 3 RETURNING id")
+        ]
+        assert rows == [[4]]
+        async with conn.transaction():
+            cursor = conn.cursor()
+            await cursor.execute("UPDATE test SET value = value * 10 RETURNING value")
+            assert await cursor.fetchmany(5) == [[10], [20], [30]]
+        assert await conn.fetch_all("SELECT value FROM test ORDER BY id") == [
+            [10],
+            [20],
+            [30],
+        ]
@@ -292,3 +292,28 @@ async def test_backup_target_with_existing_data(test_db, target_db):
         rows = await verify.fetch_all("SELECT * FROM t")
         assert len(rows) == 1
         assert rows[0][1] == "new"
+
+
+@pytest.mark.asyncio
+async def test_backup_to_file_path(test_db, tmp_path):
+    """A str or os.PathLike target is a database file, created if missing."""
+    target = tmp_path / "copy.db"
+    steps = []
+    async with connect(test_db) as src:
+        await src.execute("CREATE TABLE t (id INTEGER PRIMARY KEY, v BLOB)")
+        for _ in range(20):
+            await src.execute("INSERT INTO t (v) VALUES (zeroblob(4096))")
+        await src.backup(
+            target, pages=8, progress=lambda *step: steps.append(step), sleep=0
+        )
+        assert len(steps) > 1
+
+        # Backing up again overwrites the file
+        await src.execute("DELETE FROM t")
+        await src.backup(str(target))
+
+        with pytest.raises(OperationalError, match="Cannot back up to"):
+            await src.backup(str(tmp_path / "missing" / "copy.db"))
+
+    with sqlite3.connect(target) as verify:
+        assert verify.execute("SELECT count(*) FROM t").fetchone() == (0,)
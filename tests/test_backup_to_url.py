@@ -0,0 +1,216 @@
+"""Tests for Connection.backup_to_url() against an in-process S3 stand-in."""
+
+import hashlib
+import os
+import re
+import threading
+from http.server import BaseHTTPRequestHandler, ThreadingHTTPServer
+from urllib.parse import parse_qs, urlsplit
+
+import pytest
+
+from rapsqlite import Connection, OperationalError, decrypt_file, open_http
+
+
+class FakeS3(BaseHTTPRequestHandler):
+    """Just enough of the S3 object API: GET, PUT, multipart upload and abort."""
+
+    objects = {}
+    uploads = {}
+    requests = []
+    deny = False
+
+    def log_message(self, *args):
+        pass
+
+    def _reply(self, status, body=b"", headers=()):
+        self.send_response(status)
+        for name, value in headers:
+            self.send_header(name, value)
+        self.send_header("Content-Length", str(len(body)))
+        self.end_headers()
+        self.wfile.write(body)
+
+    def _request(self):
+        url = urlsplit(self.path)
+        query = parse_qs(url.query, keep_blank_values=True)
+        body = self.rfile.read(int(self.headers.get("Content-Length", 0)))
+        self.requests.append((self.command, url.path, sorted(query)))
+        auth = self.headers.get("Authorization", "")
+        assert auth.startswith("AWS4-HMAC-SHA256 Credential=test-key/")
+        assert self.headers["x-amz-content-sha256"] == hashlib.sha256(body).hexdigest()
+        if self.deny:
+            error = b"<Error><Code>AccessDenied</Code><Message>No</Message></Error>"
+            self._reply(403, error)
+            return None
+        return url.path, query, body
+
+    def do_GET(self):
+        request = self._request()
+        if request is None:
+            return
+        body = self.objects.get(request[0])
+        if body is None:
+            error = b"<Error><Code>NoSuchKey</Code><Message>No</Message></Error>"
+            self._reply(404, error)
+        else:
+            self._reply(200, body, headers=[("ETag", '"%d"' % len(body))])
+
+    def do_PUT(self):
+        request = self._request()
+        if request is None:
+            return
+        path, query, body = request
+        if "uploadId" in query:
+            etag = '"%s"' % hashlib.md5(body).hexdigest()
+            self.uploads[query["uploadId"][0]][int(query["partNumber"][0])] = body
+            self._reply(200, headers=[("ETag", etag)])
+        else:
+            self.objects[path] = body
+            self._reply(200)
+
+    def do_POST(self):
+        request = self._request()
+        if request is None:
+            return
+        path, query, body = request
+        if "uploads" in query:
+            upload_id = "upload-%d" % len(self.uploads)
+            self.uploads[upload_id] = {}
+            xml = (
+                "<InitiateMultipartUploadResult><UploadId>%s</UploadId>"
+                "</InitiateMultipartUploadResult>" % upload_id
+            )
+            self._reply(200, xml.encode())
+        else:
+            parts = self.uploads.pop(query["uploadId"][0])
+            numbers = [int(n) for n in re.findall(rb"<PartNumber>(\d+)<", body)]
+            self.objects[path] = b"".join(parts[n] for n in numbers)
+            self._reply(200, b"<CompleteMultipartUploadResult/>")
+
+    def do_DELETE(self):
+        request = self._request()
+        if request is not None:
+            self.uploads.pop(parse_qs(urlsplit(self.path).query)["uploadId"][0])
+            self._reply(204)
+
+
+@pytest.fixture
+def s3_endpoint():
+    keys = ("AWS_ACCESS_KEY_ID", "AWS_SECRET_ACCESS_KEY")
+    saved = {key: os.environ.get(key) for key in keys}
+    os.environ["AWS_ACCESS_KEY_ID"] = "test-key"
+    os.environ["AWS_SECRET_ACCESS_KEY"] = "test-secret"
+    FakeS3.objects, FakeS3.uploads, FakeS3.requests = {}, {}, []
+    FakeS3.deny = False
+    server = ThreadingHTTPServer(("127.0.0.1", 0), FakeS3)
+    thread = threading.Thread(target=server.serve_forever, daemon=True)
+    thread.start()
+    try:
+        yield "http://127.0.0.1:%d" % server.server_port
+    finally:
+        server.shutdown()
+        for key, value in saved.items():
+            if value is None:
+                os.environ.pop(key, None)
+            else:
+                os.environ[key] = value
+
+
+async def _restore(data, tmp_path):
+    path = str(tmp_path / "restored.db")
+    with open(path, "wb") as f:
+        f.write(data)
+    async with Connection(path) as conn:
+        return await conn.fetch_one("SELECT count(*), sum(length(b)) FROM t")
+
+
+@pytest.mark.asyncio
+async def test_backup_to_url_single_put(test_db, s3_endpoint, tmp_path):
+    async with Connection(test_db) as conn:
+        await conn.execute("CREATE TABLE t (b BLOB)")
+        await conn.execute_many(
+            "INSERT INTO t VALUES (randomblob(?))", [[100]] * 10
+        )
+        size = await conn.backup_to_url(
+            "s3://backups/app/db 1.sqlite", endpoint_url=s3_endpoint
+        )
+    data = FakeS3.objects["/backups/app/db%201.sqlite"]
+    assert size == len(data)
+    assert [r[0] for r in FakeS3.requests] == ["PUT"]
+    assert await _restore(data, tmp_path) == [10, 1000]
+
+
+@pytest.mark.asyncio
+async def test_backup_to_url_multipart(test_db, s3_endpoint, tmp_path):
+    async with Connection(test_db) as conn:
+        await conn.execute("CREATE TABLE t (b BLOB)")
+        await conn.execute_many(
+            "INSERT INTO t VALUES (randomblob(?))", [[1024 * 1024]] * 12
+        )
+        size = await conn.backup_to_url(
+            "s3://backups/app.db",
+            endpoint_url=s3_endpoint,
+            part_size=5 * 1024 * 1024,
+            concurrency=2,
+        )
+    data = FakeS3.objects["/backups/app.db"]
+    assert size == len(data) > 10 * 1024 * 1024
+    parts = [r for r in FakeS3.requests if r[0] == "PUT"]
+    assert len(parts) == 3
+    assert FakeS3.uploads == {}
+    assert await _restore(data, tmp_path) == [12, 12 * 1024 * 1024]
+
+
+@pytest.mark.asyncio
+async def test_backup_to_url_encrypted(test_db, s3_endpoint, tmp_path):
+    key = os.urandom(32)
+    async with Connection(test_db) as conn:
+        await conn.execute("CREATE TABLE t (b BLOB)")
+        await conn.execute_many(
+            "INSERT INTO t VALUES (randomblob(?))", [[100]] * 10
+        )
+        await conn.backup_to_url(
+            "s3://backups/app.db.enc", endpoint_url=s3_endpoint, encryption_key=key
+        )
+    data = FakeS3.objects["/backups/app.db.enc"]
+    assert not data.startswith(b"SQLite format 3")
+    sealed = tmp_path / "app.db.enc"
+    sealed.write_bytes(data)
+    plain = tmp_path / "plain.db"
+    await decrypt_file(str(sealed), str(plain), key)
+    assert await _restore(plain.read_bytes(), tmp_path) == [10, 1000]
+
+
+@pytest.mark.asyncio
+async def test_open_http_reads_backup_from_s3(test_db, s3_endpoint, tmp_path):
+    async with Connection(test_db) as conn:
+        await conn.execute("CREATE TABLE t (b BLOB)")
+        await conn.execute_many("INSERT INTO t VALUES (randomblob(?))", [[100]] * 3)
+        await conn.backup_to_url("s3://backups/app.db", endpoint_url=s3_endpoint)
+    cache = str(tmp_path / "cache")
+    replica = await open_http("s3://backups/app.db", cache, endpoint_url=s3_endpoint)
+    async with replica:
+        assert await replica.fetch_one("SELECT count(*) FROM t") == [3]
+    with pytest.raises(OperationalError, match="NoSuchKey"):
+        await open_http("s3://backups/missing.db", cache, endpoint_url=s3_endpoint)
+
+
+@pytest.mark.asyncio
+async def test_backup_to_url_errors(test_db, s3_endpoint):
+    async with Connection(test_db) as conn:
+        await conn.execute("CREATE TABLE t (b BLOB)")
+        with pytest.raises(ValueError):
+            await conn.backup_to_url("s3://b/k", part_size=1024)
+        with pytest.raises(ValueError):
+            await conn.backup_to_url("s3://b/k", concurrency=0)
+        with pytest.raises(ValueError):
+            await conn.backup_to_url("s3://b/k", encryption_key=b"short")
+        with pytest.raises(OperationalError, match="s3://bucket/key"):
+            await conn.backup_to_url("https://b/k", endpoint_url=s3_endpoint)
+        async with conn.transaction():
+            with pytest.raises(OperationalError, match="transaction"):
+                await conn.backup_to_url("s3://b/k", endpoint_url=s3_endpoint)
+        FakeS3.deny = True
+        with pytest.raises(OperationalError, match="AccessDenied"):
+            await conn.backup_to_url("s3://b/k", endpoint_url=s3_endpoint)
@@ -0,0 +1,90 @@
+"""Tests for Connection.bulk_load (deferred index maintenance)."""
+
+import pytest
+
+from rapsqlite import Connection, IntegrityError, OperationalError
+
+
+async def _setup(conn):
+    await conn.execute("CREATE TABLE users (id INTEGER PRIMARY KEY)")
+    await conn.execute(
+        "CREATE TABLE events (id INTEGER PRIMARY KEY, "
+        "user_id REFERENCES users(id), kind TEXT UNIQUE)"
+    )
+    await conn.execute("CREATE INDEX idx_events_user ON events (user_id)")
+    await conn.execute("CREATE INDEX idx_events_kind ON events (lower(kind))")
+    await conn.execute("INSERT INTO users VALUES (1)")
+
+
+async def _index_sql(conn):
+    rows = await conn.fetch_all(
+        "SELECT name, sql FROM sqlite_master WHERE type = 'index' ORDER BY name"
+    )
+    return [tuple(row) for row in rows]
+
+
+@pytest.mark.asyncio
+async def test_bulk_load_drops_and_rebuilds_indexes(test_db):
+    async with Connection(test_db, pragmas={"foreign_keys": "ON"}) as conn:
+        await _setup(conn)
+        before = await _index_sql(conn)
+
+        async with conn.bulk_load("events") as loader:
+            assert loader is conn
+            # Only the constraint's automatic index remains during the load
+            during = await _index_sql(conn)
+            assert [name for name, _ in during] == ["sqlite_autoindex_events_1"]
+            assert await conn.fetch_all("PRAGMA foreign_keys") == [[0]]
+            await conn.execute_many(
+                "INSERT INTO events (user_id, kind) VALUES (?, ?)",
+                [[1, f"kind-{i}"] for i in range(100)],
+            )
+
+        assert await _index_sql(conn) == before
+        assert await conn.fetch_all("SELECT count(*) FROM events") == [[100]]
+        assert await conn.fetch_all("PRAGMA foreign_keys") == [[1]]
+        assert not await conn.in_transaction()
+
+
+@pytest.mark.asyncio
+async def test_bulk_load_rolls_back_on_error(test_db):
+    async with Connection(test_db) as conn:
+        await _setup(conn)
+        before = await _index_sql(conn)
+
+        with pytest.raises(RuntimeError):
+            async with conn.bulk_load("events"):
+                await conn.execute("INSERT INTO events (user_id) VALUES (1)")
+                raise RuntimeError("load failed")
+
+        assert await _index_sql(conn) == before
+        assert await conn.fetch_all("SELECT count(*) FROM events") == [[0]]
+
+
+@pytest.mark.asyncio
+async def test_bulk_load_verifies_foreign_keys(test_db):
+    async with Connection(test_db, pragmas={"foreign_keys": "ON"}) as conn:
+        await _setup(conn)
+        before = await _index_sql(conn)
+
+        with pytest.raises(IntegrityError, match="foreign key violation"):
+            async with conn.bulk_load("events"):
+                await conn.execute("INSERT INTO events (user_id) VALUES (1)")
+                await conn.execute("INSERT INTO events (user_id) VALUES (42)")
+
+        assert await _index_sql(conn) == before
+        assert await conn.fetch_all("SELECT count(*) FROM events") == [[0]]
+        assert await conn.fetch_all("PRAGMA foreign_keys") == [[1]]
+
+
+@pytest.mark.asyncio
+async def test_bulk_load_rejects_missing_table_and_open_transaction(test_db):
+    async with Connection(test_db) as conn:
+        await _setup(conn)
+        with pytest.raises(OperationalError, match="no such table"):
+            async with conn.bulk_load("missing"):
+                pass
+        async with conn.transaction():
+            with pytest.raises(OperationalError, match="inside a transaction"):
+                async with conn.bulk_load("events"):
+                    pass
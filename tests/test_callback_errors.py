@@ -16,11 +16,33 @@ async def test_create_function_exception_handled(test_db):
         # Create function that raises exception
         await db.create_function("failing_func", 1, failing_func)
 
-        # Calling the function should return a SQLite error, not crash
+        # Calling the function should re-raise the original exception, not crash
         # Need to fetch results to trigger the error
-        # The error is converted to DatabaseError (which is correct)
-        with pytest.raises(rapsqlite.DatabaseError, match="Python function error"):
+        # The SQLite error is chained as the cause
+        with pytest.raises(ValueError, match="Test error") as exc_info:
             await db.fetch_all("SELECT failing_func(1)")
+        assert isinstance(exc_info.value.__cause__, rapsqlite.DatabaseError)
+        assert "Python function error" in str(exc_info.value.__cause__)
+
+        # The connection keeps working after the failure
+        assert await db.fetch_all("SELECT 1") == [[1]]
+
+
+@pytest.mark.asyncio
+async def test_create_function_unconvertible_result_raises(test_db):
+    """Results SQLite cannot store raise instead of becoming NULL."""
+    async with rapsqlite.connect(test_db) as db:
+        await db.create_function("big", 0, lambda: 2**70)
+        await db.create_function("opaque", 0, lambda: object())
+
+        for mode in ("inline", "thread"):
+            db.callback_mode = mode
+            with pytest.raises(OverflowError) as exc_info:
+                await db.fetch_all("SELECT big(), typeof(big())")
+            assert "Python function error" in str(exc_info.value.__cause__)
+
+            with pytest.raises(TypeError, match="object"):
+                await db.fetch_all("SELECT opaque()")
 
 
 @pytest.mark.asyncio
@@ -9,7 +9,7 @@ import tempfile
 import os
 import sys
 
-from rapsqlite import connect, DatabaseError, OperationalError
+from rapsqlite import connect, DatabaseError, OperationalError, Pointer, QueryAborted
 
 
 def cleanup_db(test_db: str) -> None:
@@ -60,6 +60,124 @@ async def test_create_function_many_arguments(test_db):
         assert result[0] == 21
 
 
+@pytest.mark.asyncio
+async def test_create_function_variadic(test_db):
+    """Test nargs=-1 functions receive every argument, however many."""
+    async with connect(test_db) as db:
+
+        def count_args(*args):
+            return len(args)
+
+        await db.create_function("count_args", -1, count_args)
+        assert (await db.fetch_one("SELECT count_args()"))[0] == 0
+        assert (await db.fetch_one("SELECT count_args(NULL)"))[0] == 1
+
+        values = ", ".join(str(i) for i in range(40))
+        result = await db.fetch_one(f"SELECT count_args({values})")
+        assert result[0] == 40
+
+        await db.create_function("join_all", -1, lambda *args: "-".join(map(str, args)))
+        result = await db.fetch_one("SELECT join_all('a', 1, 2.5)")
+        assert result[0] == "a-1-2.5"
+
+
+@pytest.mark.asyncio
+async def test_create_function_variadic_modes(test_db):
+    """Test nargs=-1 functions in thread mode and as `async def`."""
+    values = ", ".join(str(i) for i in range(127))
+    for mode in ("inline", "thread"):
+        async with connect(test_db) as db:
+            db.callback_mode = mode
+
+            async def async_count(*args):
+                return len(args)
+
+            await db.create_function("total", -1, lambda *args: sum(args))
+            await db.create_function("async_count", -1, async_count)
+
+            assert await db.fetch_one("SELECT total()") == [0]
+            assert await db.fetch_one(f"SELECT total({values})") == [sum(range(127))]
+            assert await db.fetch_one("SELECT async_count(1, 'a', NULL, x'00')") == [4]
+            # SQLite caps a call at 127 arguments
+            with pytest.raises(DatabaseError, match="too many arguments"):
+                await db.fetch_one(f"SELECT total({values}, 127)")
+
+
+@pytest.mark.asyncio
+async def test_create_function_overloads_by_arity(test_db):
+    """Test registering one name with several arities, like SQLite overloads."""
+    async with connect(test_db) as db:
+        await db.create_function("pick", 1, lambda a: "one")
+        await db.create_function("pick", 2, lambda a, b: "two")
+        await db.create_function("PICK", -1, lambda *args: f"any:{len(args)}")
+
+        assert (await db.fetch_one("SELECT pick(1)"))[0] == "one"
+        assert (await db.fetch_one("SELECT pick(1, 2)"))[0] == "two"
+        assert (await db.fetch_one("SELECT pick(1, 2, 3)"))[0] == "any:3"
+
+        # Removing one overload leaves the others registered. As in SQLite, the
+        # removed arity is not redirected to the variadic overload.
+        await db.create_function("pick", 2, None)
+        with pytest.raises(DatabaseError):
+            await db.fetch_one("SELECT pick(1, 2)")
+        assert (await db.fetch_one("SELECT pick(1)"))[0] == "one"
+        assert (await db.fetch_one("SELECT pick(1, 2, 3)"))[0] == "any:3"
+
+        await db.create_function("Pick", 1, None)
+        await db.create_function("pick", -1, None)
+        with pytest.raises(DatabaseError):
+            await db.fetch_one("SELECT pick(1)")
+
+
+@pytest.mark.asyncio
+async def test_create_function_async(test_db):
+    """Test async def UDFs awaited on the running event loop."""
+    import asyncio
+
+    async with connect(test_db) as db:
+        cache = {"a": 1, "b": 2}
+
+        async def lookup(key):
+            await asyncio.sleep(0.01)
+            return cache.get(key)
+
+        await db.create_function("lookup", 1, lookup)
+        await db.execute("CREATE TABLE keys (k TEXT)")
+        await db.execute("INSERT INTO keys VALUES ('a'), ('b'), ('c')")
+
+        rows = await db.fetch_all("SELECT k, lookup(k) FROM keys ORDER BY k")
+        assert rows == [["a", 1], ["b", 2], ["c", None]]
+
+        # Works in thread callback mode as well
+        db.callback_mode = "thread"
+        row = await db.fetch_one("SELECT lookup('b')")
+        assert row[0] == 2
+
+
+@pytest.mark.asyncio
+async def test_create_function_async_timeout_and_error(test_db):
+    """Test async UDF timeouts and exceptions fail the calling statement."""
+    import asyncio
+
+    async with connect(test_db) as db:
+        db.callback_timeout = 0.1
+
+        async def slow(x):
+            await asyncio.sleep(1)
+            return x
+
+        async def broken(x):
+            raise RuntimeError("lookup failed")
+
+        await db.create_function("slow", 1, slow)
+        await db.create_function("broken", 1, broken)
+
+        with pytest.raises(DatabaseError, match="timed out"):
+            await db.fetch_one("SELECT slow(1)")
+        with pytest.raises(RuntimeError, match="lookup failed"):
+            await db.fetch_one("SELECT broken(1)")
+
+
 @pytest.mark.asyncio
 async def test_create_function_with_state(test_db):
     """Test functions that maintain state (closure variables)."""
@@ -179,10 +297,43 @@ async def test_create_function_exception_types(test_db):
 
         await db.create_function("error_func", 1, raise_value_error)
 
-        with pytest.raises(DatabaseError) as exc_info:
+        with pytest.raises(ValueError, match="Custom error message") as exc_info:
             await db.fetch_one("SELECT error_func(1)")
-        assert "Python function error" in str(exc_info.value)
-        assert "ValueError" in str(exc_info.value)
+        cause = exc_info.value.__cause__
+        assert isinstance(cause, DatabaseError)
+        assert "Python function error" in str(cause)
+        assert "ValueError" in str(cause)
+
+
+@pytest.mark.asyncio
+async def test_create_function_exception_propagates_from_all_paths(test_db):
+    """Test UDF exceptions are re-raised from execute, cursors and thread mode."""
+    async with connect(test_db) as db:
+
+        class LookupFailed(Exception):
+            pass
+
+        def fail(x):
+            raise LookupFailed(f"no value for {x}")
+
+        await db.create_function("fail", 1, fail)
+        await db.execute("CREATE TABLE test (id INTEGER, v INTEGER)")
+
+        with pytest.raises(LookupFailed, match="no value for 1"):
+            await db.execute("INSERT INTO test VALUES (1, fail(1))")
+
+        cursor = db.cursor()
+        await cursor.execute("SELECT fail(2)")
+        with pytest.raises(LookupFailed, match="no value for 2"):
+            await cursor.fetchall()
+
+        db.callback_mode = "thread"
+        with pytest.raises(LookupFailed, match="no value for 3"):
+            await db.fetch_all("SELECT fail(3)")
+
+        # Unrelated errors are not replaced by a stale UDF exception
+        with pytest.raises(DatabaseError):
+            await db.fetch_all("SELECT * FROM missing_table")
 
 
 @pytest.mark.asyncio
@@ -560,6 +711,21 @@ async def test_progress_handler_abort_operation(test_db):
         assert call_count[0] >= 1
 
 
+@pytest.mark.asyncio
+async def test_progress_handler_abort_raises_query_aborted(test_db):
+    """Test that an abort from the progress handler raises QueryAborted."""
+    async with connect(test_db) as db:
+        await db.execute("CREATE TABLE test (id INTEGER PRIMARY KEY)")
+
+        await db.set_progress_handler(1, lambda: False)
+
+        with pytest.raises(QueryAborted, match="interrupted"):
+            await db.fetch_all("SELECT * FROM test")
+
+        # QueryAborted is still an OperationalError for existing handlers
+        assert issubclass(QueryAborted, OperationalError)
+
+
 @pytest.mark.asyncio
 async def test_progress_handler_exception_handling(test_db):
     """Test progress handler that raises exceptions."""
@@ -586,6 +752,101 @@ async def test_progress_handler_exception_handling(test_db):
         assert call_count[0] >= 0  # May be 0 if operation is too fast
 
 
+@pytest.mark.asyncio
+async def test_callback_mode_thread_runs_callbacks(test_db):
+    """Test that UDFs, progress and trace callbacks work in thread callback mode."""
+    async with connect(test_db) as db:
+        assert db.callback_mode == "inline"
+        db.callback_mode = "thread"
+        assert db.callback_mode == "thread"
+
+        traced = []
+        progress_calls = [0]
+
+        def progress():
+            progress_calls[0] += 1
+            return True
+
+        await db.create_function("concat3", 3, lambda a, b, c: f"{a}{b}{c}")
+        await db.set_trace_callback(traced.append)
+        await db.set_progress_handler(1, progress)
+
+        row = await db.fetch_one("SELECT concat3('a', 1, 2.5)")
+        assert row[0] == "a12.5"
+        assert progress_calls[0] > 0
+
+        await db.set_trace_callback(None)
+        await db.set_progress_handler(1, None)
+        assert any("concat3" in sql for sql in traced)
+
+
+@pytest.mark.asyncio
+async def test_pointer_passes_objects_between_functions(test_db):
+    """Test that a Pointer result reaches other UDFs as the same object."""
+    payload = {"rows": [1, 2, 3]}
+
+    for mode in ("inline", "thread"):
+        async with connect(test_db) as db:
+            db.callback_mode = mode
+            seen = []
+
+            def describe(obj):
+                seen.append(obj)
+                return len(obj["rows"])
+
+            await db.create_function("payload", 0, lambda: Pointer(payload))
+            await db.create_function("describe", 1, describe)
+
+            assert await db.fetch_one("SELECT describe(payload())") == [3]
+            assert seen[0] is payload
+            # Outside of function arguments a pointer value reads as NULL
+            assert await db.fetch_one("SELECT payload(), typeof(payload())") == [
+                None,
+                "null",
+            ]
+
+    pointer = Pointer(payload)
+    assert pointer.obj is payload
+    assert repr(pointer) == f"Pointer({payload!r})"
+
+
+@pytest.mark.asyncio
+async def test_callback_mode_thread_timeout(test_db):
+    """Test that a slow UDF fails its statement instead of blocking in thread mode."""
+    import time
+
+    async with connect(test_db) as db:
+        db.callback_mode = "thread"
+        db.callback_timeout = 0.1
+        assert db.callback_timeout == 0.1
+
+        def slow(x):
+            time.sleep(0.5)
+            return x
+
+        await db.create_function("slow", 1, slow)
+
+        with pytest.raises(DatabaseError, match="timed out"):
+            await db.fetch_one("SELECT slow(1)")
+
+        # Switching back to inline mode calls the function directly again
+        db.callback_mode = "inline"
+        row = await db.fetch_one("SELECT slow(2)")
+        assert row[0] == 2
+
+
+@pytest.mark.asyncio
+async def test_callback_mode_invalid_values(test_db):
+    """Test validation of callback_mode and callback_timeout."""
+    async with connect(test_db) as db:
+        with pytest.raises(ValueError):
+            db.callback_mode = "process"
+        with pytest.raises(ValueError):
+            db.callback_timeout = 0
+        assert db.callback_mode == "inline"
+        assert db.callback_timeout == 5.0
+
+
 # ============================================================================
 # iterdump robust tests
 # ============================================================================
@@ -879,6 +1140,68 @@ async def test_callbacks_clear_and_reuse(test_db):
         assert result[0] == 15
 
 
+@pytest.mark.asyncio
+async def test_replaced_hook_callbacks_are_released(test_db):
+    """Test replacing or clearing trace/authorizer/progress callbacks frees the old ones."""
+    import gc
+    import weakref
+
+    class Callback:
+        def __call__(self, *args):
+            return 0
+
+    async with connect(test_db) as db:
+        setters = {
+            "trace": db.set_trace_callback,
+            "authorizer": db.set_authorizer,
+            "progress": lambda cb: db.set_progress_handler(100, cb),
+        }
+        for name, setter in setters.items():
+            first = Callback()
+            first_ref = weakref.ref(first)
+            await setter(first)
+            await db.fetch_all("SELECT 1")
+
+            # Replacing the callback releases the previous one
+            second = Callback()
+            second_ref = weakref.ref(second)
+            await setter(second)
+            del first
+            await db.fetch_all("SELECT 1")
+            gc.collect()
+            assert first_ref() is None, f"{name} callback leaked after replacement"
+
+            # Clearing the callback releases the current one
+            await setter(None)
+            del second
+            await db.fetch_all("SELECT 1")
+            gc.collect()
+            assert second_ref() is None, f"{name} callback leaked after clearing"
+
+
+@pytest.mark.asyncio
+async def test_cleared_callbacks_do_not_follow_connection_into_pool(test_db):
+    """Test handlers are removed from the callback connection before it is pooled.
+
+    With the default pool size of 1, the released callback connection is the one
+    that runs the next statement.
+    """
+    async with connect(test_db) as db:
+        await db.execute("CREATE TABLE test (id INTEGER PRIMARY KEY)")
+
+        await db.set_authorizer(lambda *args: 1)  # SQLITE_DENY
+        with pytest.raises(DatabaseError):
+            await db.fetch_all("SELECT * FROM test")
+        await db.set_authorizer(None)
+        assert await db.fetch_all("SELECT * FROM test") == []
+
+        await db.set_progress_handler(1, lambda: False)
+        with pytest.raises(QueryAborted):
+            await db.fetch_all("SELECT * FROM test")
+        await db.set_progress_handler(1, None)
+        assert await db.fetch_all("SELECT * FROM test") == []
+
+
 @pytest.mark.asyncio
 async def test_callbacks_with_cursor(test_db):
     """Test callbacks work with cursor operations."""
@@ -0,0 +1,140 @@
+"""Tests for compressed dumps, CSV exports and imports."""
+
+import csv
+import gzip
+import io
+
+import pytest
+
+from rapsqlite import Connection, DatabaseError, OperationalError, read_compressed
+
+
+async def _populate(conn):
+    await conn.execute("CREATE TABLE t (id INTEGER PRIMARY KEY, name TEXT, score REAL)")
+    await conn.execute_many(
+        "INSERT INTO t (name, score) VALUES (?, ?)",
+        [["row %d" % i, i / 4] for i in range(1000)],
+    )
+    await conn.execute(
+        "INSERT INTO t (name, score) VALUES (?, NULL)", ['quote "this", please\nok']
+    )
+
+
+async def _open(path):
+    path.touch()
+    return Connection(str(path))
+
+
+async def _check_dump(test_db, tmp_path, compression, text):
+    tmp_path.mkdir()
+    dump = tmp_path / "dump.sql"
+    async with Connection(test_db) as conn:
+        size = await conn.dump(str(dump), compression=compression)
+    assert size == dump.stat().st_size
+    if compression is None:
+        assert dump.read_text() == text
+    else:
+        assert size < len(text) / 4
+    assert (await read_compressed(str(dump))).decode() == text
+    if compression == "gzip":
+        assert gzip.decompress(dump.read_bytes()).decode() == text
+
+    async with await _open(tmp_path / "restored.db") as conn:
+        await conn.import_dump(str(dump), compression=compression)
+        assert await conn.fetch_one("SELECT count(*), sum(score) FROM t") == [
+            1001,
+            sum(i / 4 for i in range(1000)),
+        ]
+        assert not await conn.in_transaction()
+
+
+async def _check_csv(conn, path, compression):
+    count = await conn.export_csv(
+        str(path),
+        "SELECT id, name, score FROM t WHERE id > ?",
+        [990],
+        compression=compression,
+    )
+    assert count == 11
+
+    text = (await read_compressed(str(path))).decode()
+    records = list(csv.reader(io.StringIO(text, newline="")))
+    assert records[0] == ["id", "name", "score"]
+    assert records[1] == ["991", "row 990", "247.5"]
+    assert records[-1] == ["1001", 'quote "this", please\nok', ""]
+
+    await conn.execute("DROP TABLE IF EXISTS copy")
+    await conn.execute("CREATE TABLE copy (id INTEGER PRIMARY KEY, name TEXT, score REAL)")
+    assert await conn.import_csv(str(path), "copy") == 11
+    assert await conn.fetch_all("SELECT * FROM copy WHERE id < 993") == [
+        [991, "row 990", 247.5],
+        [992, "row 991", 247.75],
+    ]
+
+
+@pytest.mark.asyncio
+async def test_dump_and_import_dump(test_db, tmp_path):
+    async with Connection(test_db) as conn:
+        await _populate(conn)
+        text = "\n".join(await conn.iterdump()) + "\n"
+    for compression in (None, "gzip", "zstd"):
+        await _check_dump(test_db, tmp_path / str(compression), compression, text)
+
+
+@pytest.mark.asyncio
+async def test_export_and_import_csv(test_db, tmp_path):
+    async with Connection(test_db) as conn:
+        await _populate(conn)
+        for compression in (None, "gzip", "zstd"):
+            await _check_csv(conn, tmp_path / ("t.csv.%s" % compression), compression)
+
+
+@pytest.mark.asyncio
+async def test_export_csv_without_header_or_rows(test_db, tmp_path):
+    path = tmp_path / "empty.csv"
+    async with Connection(test_db) as conn:
+        await _populate(conn)
+        assert await conn.export_csv(str(path), "SELECT name AS n FROM t WHERE 0") == 0
+        assert path.read_bytes() == b"n\r\n"
+        assert (
+            await conn.export_csv(
+                str(path), "SELECT id, name FROM t WHERE id = :id", {"id": 2}, header=False
+            )
+            == 1
+        )
+        assert path.read_bytes() == b"2,row 1\r\n"
+
+        await conn.execute("CREATE TABLE copy (id INTEGER, name TEXT)")
+        assert await conn.import_csv(str(path), "copy", header=False) == 1
+        assert await conn.fetch_all("SELECT * FROM copy") == [[2, "row 1"]]
+
+
+@pytest.mark.asyncio
+async def test_compression_errors(test_db, tmp_path):
+    path = str(tmp_path / "out")
+    async with Connection(test_db) as conn:
+        await _populate(conn)
+        with pytest.raises(ValueError, match="compression"):
+            await conn.dump(path, compression="lz4")
+        with pytest.raises(ValueError, match="compression"):
+            await conn.export_csv(path, "SELECT 1", compression="bz2")
+        with pytest.raises(DatabaseError, match="no such table"):
+            await conn.export_csv(path, "SELECT * FROM missing")
+        assert not (tmp_path / "out").exists()
+
+        await conn.dump(path, compression="gzip")
+        with pytest.raises(OperationalError, match="not a zstd file"):
+            await conn.import_dump(path, compression="zstd")
+
+        bad = tmp_path / "bad.csv"
+        bad.write_text("id,name\n1,a\n2\n")
+        await conn.execute("CREATE TABLE copy (id INTEGER, name TEXT)")
+        with pytest.raises(ValueError, match="record 3"):
+            await conn.import_csv(str(bad), "copy")
+
+        # A failing import leaves the table unchanged
+        await conn.execute("CREATE TABLE keyed (id INTEGER PRIMARY KEY, name TEXT)")
+        bad.write_text("1,a\n2,b\n1,c\n")
+        with pytest.raises(DatabaseError):
+            await conn.import_csv(str(bad), "keyed", header=False)
+        assert await conn.fetch_one("SELECT count(*) FROM keyed") == [0]
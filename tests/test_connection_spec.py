@@ -0,0 +1,55 @@
+"""Tests for pickling connections and ConnectionSpec."""
+
+import asyncio
+import multiprocessing
+import pickle
+from concurrent.futures import ProcessPoolExecutor
+
+import pytest
+
+import rapsqlite
+
+
+def _count_rows(spec):
+    async def run():
+        async with spec.connect() as conn:
+            return (await conn.fetch_one("SELECT COUNT(*) FROM t"))[0], conn.timeout
+
+    return asyncio.run(run())
+
+
+@pytest.mark.asyncio
+async def test_pickling_connection_raises_with_guidance(test_db):
+    async with rapsqlite.connect(test_db) as conn:
+        with pytest.raises(TypeError, match="ConnectionSpec"):
+            pickle.dumps(conn)
+
+
+@pytest.mark.asyncio
+async def test_spec_round_trips_and_connects(test_db):
+    spec = rapsqlite.ConnectionSpec(test_db, {"timeout": 12.5, "strict": True})
+    copy = pickle.loads(pickle.dumps(spec))
+    assert copy == spec
+    assert copy.path == test_db
+    assert copy.options == {"timeout": 12.5, "strict": True}
+
+    async with copy.connect() as conn:
+        assert conn.timeout == 12.5
+        assert conn.strict is True
+
+
+def test_spec_rejects_unknown_options(test_db):
+    with pytest.raises(TypeError, match="bogus"):
+        rapsqlite.ConnectionSpec(test_db, {"bogus": 1})
+
+
+@pytest.mark.asyncio
+async def test_spec_in_worker_process(test_db):
+    async with rapsqlite.connect(test_db) as conn:
+        await conn.execute("CREATE TABLE t (x INTEGER)")
+        await conn.execute("INSERT INTO t VALUES (1), (2)")
+
+    spec = rapsqlite.ConnectionSpec(test_db, {"timeout": 3.0})
+    context = multiprocessing.get_context("spawn")
+    with ProcessPoolExecutor(max_workers=1, mp_context=context) as pool:
+        assert pool.submit(_count_rows, spec).result(timeout=60) == (2, 3.0)
@@ -0,0 +1,89 @@
+"""Tests for register_converter() and detect_types (sqlite3 converter parity)."""
+
+import datetime
+
+import pytest
+
+import rapsqlite
+from rapsqlite import Connection, PARSE_COLNAMES, PARSE_DECLTYPES, connect
+
+
+class Point:
+    def __init__(self, x, y):
+        self.x, self.y = x, y
+
+    def __eq__(self, other):
+        return (self.x, self.y) == (other.x, other.y)
+
+
+def convert_point(data):
+    x, y = map(float, data.split(b";"))
+    return Point(x, y)
+
+
+rapsqlite.register_converter("point", convert_point)
+rapsqlite.register_converter(
+    "DATE", lambda b: datetime.date.fromisoformat(b.decode())
+)
+rapsqlite.register_converter("counter", lambda b: ("counter", b))
+
+
+async def _create(conn):
+    await conn.execute("CREATE TABLE t (p point, d date, n counter(8), plain TEXT)")
+    await conn.execute(
+        "INSERT INTO t VALUES ('1.5;2', '2024-02-29', 42, '3;4')"
+    )
+    await conn.execute("INSERT INTO t VALUES (NULL, NULL, NULL, NULL)")
+
+
+@pytest.mark.asyncio
+async def test_parse_decltypes(test_db):
+    async with Connection(test_db, detect_types=PARSE_DECLTYPES) as conn:
+        assert conn.detect_types == PARSE_DECLTYPES
+        await _create(conn)
+        rows = await conn.fetch_all("SELECT * FROM t ORDER BY rowid")
+        assert rows == [
+            [Point(1.5, 2.0), datetime.date(2024, 2, 29), ("counter", b"42"), "3;4"],
+            [None, None, None, None],
+        ]
+        assert await conn.fetch_one("SELECT d FROM t WHERE p IS NOT NULL") == [
+            datetime.date(2024, 2, 29)
+        ]
+        cursor = await conn.execute("SELECT p FROM t ORDER BY rowid")
+        assert await cursor.fetchone() == [Point(1.5, 2.0)]
+        async with conn.stream("SELECT n FROM t WHERE n IS NOT NULL") as rows:
+            assert [row async for row in rows] == [[("counter", b"42")]]
+        async with conn.transaction():
+            assert await conn.fetch_optional("SELECT p FROM t LIMIT 1") == [
+                Point(1.5, 2.0)
+            ]
+
+
+@pytest.mark.asyncio
+async def test_parse_colnames(test_db):
+    async with Connection(test_db) as conn:
+        await _create(conn)
+    async with connect(test_db, detect_types=PARSE_COLNAMES) as conn:
+        conn.row_factory = "dict"
+        # Declared types are ignored without PARSE_DECLTYPES
+        row = await conn.fetch_one(
+            'SELECT plain AS "plain [point]", d FROM t WHERE p IS NOT NULL'
+        )
+        assert row == {"plain": Point(3.0, 4.0), "d": "2024-02-29"}
+    async with Connection(
+        test_db, detect_types=PARSE_DECLTYPES | PARSE_COLNAMES
+    ) as conn:
+        # A column name type takes precedence over the declared type
+        rows = await conn.fetch_all(
+            'SELECT p AS "p [counter]", d FROM t WHERE p IS NOT NULL'
+        )
+        assert rows == [[("counter", b"1.5;2"), datetime.date(2024, 2, 29)]]
+
+
+@pytest.mark.asyncio
+async def test_converters_off_by_default(test_db):
+    async with Connection(test_db) as conn:
+        await _create(conn)
+        assert await conn.fetch_one(
+            'SELECT p AS "p [point]" FROM t WHERE p IS NOT NULL'
+        ) == ["1.5;2"]
@@ -0,0 +1,98 @@
+"""Tests for rapsqlite.diff_data (two-database table diff)."""
+
+import os
+import tempfile
+
+import pytest
+
+import rapsqlite
+from rapsqlite import Connection
+
+
+@pytest.fixture
+def db_pair():
+    """Two temporary database files."""
+    paths = []
+    for _ in range(2):
+        with tempfile.NamedTemporaryFile(suffix=".db", delete=False) as f:
+            paths.append(f.name)
+    try:
+        yield paths
+    finally:
+        for path in paths:
+            if os.path.exists(path):
+                os.unlink(path)
+
+
+async def collect(*args, **kwargs):
+    return [change async for change in rapsqlite.diff_data(*args, **kwargs)]
+
+
+@pytest.mark.asyncio
+async def test_diff_data_single_key(db_pair):
+    async with Connection(db_pair[0]) as a, Connection(db_pair[1]) as b:
+        for conn in (a, b):
+            await conn.execute("CREATE TABLE t (id INTEGER PRIMARY KEY, v)")
+        await a.execute_many(
+            "INSERT INTO t VALUES (?, ?)",
+            [(i, f"v{i}") for i in range(1, 11)],
+        )
+        await b.execute_many(
+            "INSERT INTO t VALUES (?, ?)",
+            [(i, f"v{i}") for i in range(3, 13)],
+        )
+        await b.execute("UPDATE t SET v = 'changed' WHERE id = 5")
+        # Same value, different storage class
+        await b.execute("UPDATE t SET v = 7 WHERE id = 7")
+        await a.execute("UPDATE t SET v = '7' WHERE id = 7")
+
+        # A small batch forces several pages on each side
+        changes = await collect(a, b, "t", "id", batch_size=3)
+        assert changes == [
+            ("deleted", 1),
+            ("deleted", 2),
+            ("updated", 5),
+            ("updated", 7),
+            ("inserted", 11),
+            ("inserted", 12),
+        ]
+        assert await collect(a, a, "t", "id") == []
+
+
+@pytest.mark.asyncio
+async def test_diff_data_composite_key(db_pair):
+    async with Connection(db_pair[0]) as a, Connection(db_pair[1]) as b:
+        for conn in (a, b):
+            await conn.execute(
+                "CREATE TABLE m (tenant TEXT, id INTEGER, v, PRIMARY KEY (tenant, id))"
+            )
+        rows = [("x", 1, 1), ("x", 2, 2), ("y", 1, 3)]
+        await a.execute_many("INSERT INTO m VALUES (?, ?, ?)", rows)
+        await b.execute_many("INSERT INTO m VALUES (?, ?, ?)", rows[1:])
+        await b.execute("INSERT INTO m VALUES ('y', 2, 4)")
+        await b.execute("UPDATE m SET v = NULL WHERE tenant = 'x' AND id = 2")
+
+        changes = await collect(a, b, "m", ["tenant", "id"], batch_size=1)
+        assert changes == [
+            ("deleted", ("x", 1)),
+            ("updated", ("x", 2)),
+            ("inserted", ("y", 2)),
+        ]
+
+
+@pytest.mark.asyncio
+async def test_diff_data_errors(db_pair):
+    async with Connection(db_pair[0]) as a, Connection(db_pair[1]) as b:
+        await a.execute("CREATE TABLE t (id INTEGER, v TEXT)")
+        await b.execute("CREATE TABLE t (id INTEGER, w TEXT)")
+
+        with pytest.raises(ValueError, match="differ"):
+            await collect(a, b, "t", "id")
+        with pytest.raises(ValueError, match="does not exist"):
+            await collect(a, b, "missing", "id")
+        with pytest.raises(ValueError, match="key_cols"):
+            await collect(a, b, "t", [])
+
+        await a.execute("INSERT INTO t VALUES (NULL, 'x')")
+        with pytest.raises(ValueError, match="NULL key"):
+            await collect(a, a, "t", "id")
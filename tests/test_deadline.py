@@ -0,0 +1,78 @@
+"""Tests for rapsqlite.deadline() (a time budget shared by a task's statements)."""
+
+import asyncio
+import time
+
+import pytest
+
+import rapsqlite
+from rapsqlite import Connection, DeadlineExceeded, QueryAborted
+
+LONG_COUNT = (
+    "WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n "
+    "WHERE i < 1000000000) SELECT count(*) FROM n"
+)
+SHORT_COUNT = LONG_COUNT.replace("1000000000", "100000")
+
+
+@pytest.mark.asyncio
+async def test_deadline_interrupts_long_query(test_db):
+    async with Connection(test_db) as conn:
+        started = time.monotonic()
+        with pytest.raises(DeadlineExceeded):
+            async with rapsqlite.deadline(0.1):
+                await conn.fetch_all(LONG_COUNT)
+        assert time.monotonic() - started < 5
+        assert issubclass(DeadlineExceeded, QueryAborted)
+
+        # Outside the block nothing is interrupted
+        assert await conn.fetch_one(SHORT_COUNT) == [100000]
+
+
+@pytest.mark.asyncio
+async def test_deadline_budget_is_shared(test_db):
+    async with Connection(test_db) as conn:
+        await conn.execute("CREATE TABLE t (x INTEGER)")
+        async with rapsqlite.deadline(0.2) as budget:
+            await conn.execute("INSERT INTO t VALUES (1)")
+            assert 0 < budget.remaining <= 0.2
+            await asyncio.sleep(0.25)
+            assert budget.remaining == 0.0
+            # Spent: even a short query is not started
+            with pytest.raises(DeadlineExceeded, match="Deadline exceeded"):
+                await conn.fetch_all("SELECT * FROM t")
+
+            # A nested block cannot extend it
+            async with rapsqlite.deadline(10) as inner:
+                assert inner.remaining == 0.0
+        assert await conn.fetch_all("SELECT * FROM t") == [[1]]
+
+
+@pytest.mark.asyncio
+async def test_deadline_in_transaction(test_db):
+    async with Connection(test_db) as conn:
+        await conn.execute("CREATE TABLE t (x INTEGER)")
+        async with conn.transaction():
+            await conn.execute("INSERT INTO t VALUES (1)")
+            with pytest.raises(DeadlineExceeded):
+                async with rapsqlite.deadline(0.1):
+                    await conn.fetch_all(LONG_COUNT)
+            # The transaction's connection is usable again after the block
+            assert await conn.fetch_one(SHORT_COUNT) == [100000]
+        assert await conn.fetch_all("SELECT * FROM t") == [[1]]
+
+
+@pytest.mark.asyncio
+async def test_deadline_follows_the_task(test_db):
+    async with Connection(test_db) as conn:
+
+        async def slow():
+            await conn.fetch_all(LONG_COUNT)
+
+        async with rapsqlite.deadline(0.1):
+            task = asyncio.create_task(slow())
+        with pytest.raises(DeadlineExceeded):
+            await task
+
+        with pytest.raises(ValueError):
+            rapsqlite.deadline(-1)
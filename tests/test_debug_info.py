@@ -0,0 +1,61 @@
+"""Tests for Connection/Cursor repr() and Connection.debug_info()."""
+
+import pytest
+
+from rapsqlite import Connection
+
+
+@pytest.mark.asyncio
+async def test_connection_repr(test_db):
+    conn = Connection(test_db)
+    assert repr(conn) == f"<Connection path={test_db!r} pool=closed transaction=none>"
+    async with conn:
+        conn.row_factory = "dict"
+        async with conn.transaction():
+            text = str(conn)
+            assert "transaction=active" in text
+            assert "row_factory='dict'" in text
+            assert "pool=1/" in text
+
+
+@pytest.mark.asyncio
+async def test_cursor_repr(test_db):
+    async with Connection(test_db) as conn:
+        await conn.execute("CREATE TABLE t (x INTEGER)")
+        await conn.execute("INSERT INTO t VALUES (1), (2)")
+        cursor = await conn.execute("SELECT x FROM t")
+        assert repr(cursor).endswith("query='SELECT x FROM t' pending>")
+        await cursor.fetchone()
+        assert repr(cursor).endswith("rows=2 position=1>")
+        await cursor.close()
+        assert repr(cursor).endswith(" closed>")
+
+
+@pytest.mark.asyncio
+async def test_debug_info(test_db):
+    async with Connection(test_db) as conn:
+        await conn.execute("CREATE TABLE t (x INTEGER)")
+        cursor = await conn.execute("SELECT 1")
+        info = conn.debug_info()
+        assert info["path"] == test_db
+        assert info["pool_open"] is True
+        assert info["pool_size"] >= 1
+        assert info["transaction"] == "none"
+        assert info["row_factory"] is None
+        assert info["open_cursors"] == 1
+        assert info["shared_pool"] is False
+        assert info["queue"]["acquired"] >= 1
+        await cursor.close()
+        assert conn.debug_info()["open_cursors"] == 0
+
+
+@pytest.mark.asyncio
+async def test_redact_path(test_db):
+    async with Connection(test_db) as conn:
+        cursor = await conn.execute("SELECT 1")
+        conn.redact_path = True
+        assert conn.redact_path is True
+        assert test_db not in repr(conn)
+        assert test_db not in repr(cursor)
+        assert "path=<redacted>" in repr(cursor)
+        assert conn.debug_info()["path"] == "<redacted>"
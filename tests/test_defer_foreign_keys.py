@@ -0,0 +1,53 @@
+"""Tests for Connection.defer_foreign_keys()."""
+
+import pytest
+
+from rapsqlite import Connection, IntegrityError, OperationalError
+
+
+async def _create_schema(conn):
+    await conn.execute("PRAGMA foreign_keys = ON")
+    await conn.execute("CREATE TABLE parent (id INTEGER PRIMARY KEY)")
+    await conn.execute(
+        "CREATE TABLE child (id INTEGER PRIMARY KEY,"
+        " parent_id INTEGER REFERENCES parent(id))"
+    )
+    await conn.execute("INSERT INTO parent (id) VALUES (1)")
+    await conn.execute("INSERT INTO child (id, parent_id) VALUES (10, 1)")
+
+
+@pytest.mark.asyncio
+async def test_defer_foreign_keys_allows_reparenting(test_db):
+    async with Connection(test_db) as conn:
+        await _create_schema(conn)
+        async with conn.transaction():
+            async with conn.defer_foreign_keys():
+                # Would fail immediately without deferral
+                await conn.execute("UPDATE parent SET id = 2 WHERE id = 1")
+                await conn.execute("UPDATE child SET parent_id = 2 WHERE parent_id = 1")
+            # Enforcement is immediate again after the block
+            with pytest.raises(IntegrityError):
+                await conn.execute("INSERT INTO child (id, parent_id) VALUES (11, 99)")
+        rows = await conn.fetch_all("SELECT id, parent_id FROM child")
+        assert rows == [[10, 2]]
+
+
+@pytest.mark.asyncio
+async def test_defer_foreign_keys_reports_remaining_violations(test_db):
+    async with Connection(test_db) as conn:
+        await _create_schema(conn)
+        with pytest.raises(IntegrityError, match="child rowid 10 -> parent"):
+            async with conn.transaction():
+                async with conn.defer_foreign_keys():
+                    await conn.execute("DELETE FROM parent WHERE id = 1")
+        # The enclosing transaction rolled back
+        assert await conn.fetch_all("SELECT id FROM parent") == [[1]]
+
+
+@pytest.mark.asyncio
+async def test_defer_foreign_keys_requires_transaction(test_db):
+    async with Connection(test_db) as conn:
+        await _create_schema(conn)
+        with pytest.raises(OperationalError, match="inside a transaction"):
+            async with conn.defer_foreign_keys():
+                pass
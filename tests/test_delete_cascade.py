@@ -0,0 +1,65 @@
+"""Tests for Connection.delete_cascade (children-first deletes)."""
+
+import pytest
+
+from rapsqlite import Connection, OperationalError
+
+
+async def _setup(conn):
+    await conn.execute("CREATE TABLE users (id INTEGER PRIMARY KEY)")
+    await conn.execute(
+        "CREATE TABLE posts (id INTEGER PRIMARY KEY, user_id REFERENCES users)"
+    )
+    await conn.execute(
+        "CREATE TABLE comments (id INTEGER PRIMARY KEY, "
+        "post_id INTEGER REFERENCES posts(id), "
+        "author_id INTEGER REFERENCES users(id) ON DELETE SET NULL)"
+    )
+    await conn.execute("INSERT INTO users (id) VALUES (1), (2)")
+    await conn.execute("INSERT INTO posts (id, user_id) VALUES (10, 1), (11, 1), (20, 2)")
+    await conn.execute(
+        "INSERT INTO comments (id, post_id, author_id) VALUES "
+        "(100, 10, 2), (101, 11, 2), (200, 20, 1)"
+    )
+
+
+@pytest.mark.asyncio
+async def test_delete_cascade_deletes_children_first(test_db):
+    async with Connection(test_db) as conn:
+        await conn.set_pragma("foreign_keys", 1)
+        await _setup(conn)
+
+        deleted = await conn.delete_cascade("users", "id = ?", [1])
+
+        assert deleted == {"comments": 2, "posts": 2, "users": 1}
+        assert list(deleted) == ["comments", "posts", "users"]
+        assert await conn.fetch_all("SELECT id FROM users") == [[2]]
+        assert await conn.fetch_all("SELECT id FROM posts") == [[20]]
+        # Referenced through ON DELETE SET NULL only, so kept (and nulled)
+        assert await conn.fetch_all("SELECT id, author_id FROM comments") == [
+            [200, None]
+        ]
+        assert not await conn.in_transaction()
+
+
+@pytest.mark.asyncio
+async def test_delete_cascade_uses_enclosing_transaction(test_db):
+    async with Connection(test_db) as conn:
+        await _setup(conn)
+        async with conn.transaction():
+            deleted = await conn.delete_cascade("users", "id = :id", {"id": 2})
+            assert deleted == {"comments": 1, "posts": 1, "users": 1}
+            await conn.execute("INSERT INTO users (id) VALUES (3)")
+        assert await conn.fetch_all("SELECT id FROM users ORDER BY id") == [[1], [3]]
+
+
+@pytest.mark.asyncio
+async def test_delete_cascade_rejects_cycles(test_db):
+    async with Connection(test_db) as conn:
+        await conn.execute(
+            "CREATE TABLE nodes (id INTEGER PRIMARY KEY, parent_id REFERENCES nodes)"
+        )
+        await conn.execute("INSERT INTO nodes (id, parent_id) VALUES (1, NULL)")
+        with pytest.raises(OperationalError, match="cyclic"):
+            await conn.delete_cascade("nodes", "id = 1")
+        assert await conn.fetch_all("SELECT id FROM nodes") == [[1]]
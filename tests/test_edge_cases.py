@@ -15,7 +15,9 @@ from rapsqlite import (
     connect,
     OperationalError,
     DatabaseError,
+    IntegrityError,
     ProgrammingError,
+    sql,
 )
 
 
@@ -204,7 +206,7 @@ async def test_empty_parameter_list(test_db):
 @pytest.mark.edge_case
 @pytest.mark.asyncio
 async def test_large_parameter_list(test_db):
-    """Test parameter list at the limit (16 parameters)."""
+    """Test a parameter list of 16 parameters."""
     async with connect(test_db) as db:
         # Create table with 16 columns
         await db.execute("""
@@ -227,6 +229,23 @@ async def test_large_parameter_list(test_db):
         assert rows[0] == params
 
 
+@pytest.mark.edge_case
+@pytest.mark.asyncio
+async def test_wide_parameter_lists(test_db):
+    """Statements bind as many parameters as SQLite allows, not just 16."""
+    async with connect(test_db) as db:
+        columns = [f"c{i}" for i in range(300)]
+        await db.execute(f"CREATE TABLE t ({', '.join(columns)})")
+        placeholders = ", ".join("?" * 300)
+        await db.execute(f"INSERT INTO t VALUES ({placeholders})", list(range(300)))
+
+        query = f"SELECT c299 FROM t WHERE c0 IN ({', '.join('?' * 1000)})"
+        keys = list(range(1000))
+        assert await db.fetch_all(query, keys) == [[299]]
+        assert await db.fetch_one(query, keys) == [299]
+        assert await db.fetch_optional(query, keys) == [299]
+
+
 @pytest.mark.edge_case
 @pytest.mark.asyncio
 async def test_too_many_parameters(test_db):
@@ -375,6 +394,208 @@ async def test_very_large_integer(test_db):
         assert rows[0][0] == large_int
 
 
+@pytest.mark.edge_case
+@pytest.mark.asyncio
+async def test_integer_beyond_64_bits_raises_overflow_by_default(test_db):
+    """Test ints outside the i64 range are rejected instead of silently converted."""
+    async with connect(test_db) as db:
+        await db.execute("CREATE TABLE t (id INTEGER PRIMARY KEY, value)")
+        assert db.large_int_policy == "error"
+
+        for value in (2**63, -(2**63) - 1, 10**30):
+            with pytest.raises(OverflowError, match="large_int_policy"):
+                await db.execute("INSERT INTO t (value) VALUES (?)", [value])
+        with pytest.raises(OverflowError):
+            await db.fetch_all("SELECT ? + 0", [2**64])
+        with pytest.raises(OverflowError):
+            await db.execute_many("INSERT INTO t (value) VALUES (?)", [[1], [2**64]])
+
+        rows = await db.fetch_all("SELECT value FROM t")
+        assert rows == []
+
+
+@pytest.mark.edge_case
+@pytest.mark.asyncio
+async def test_large_int_policy_text_round_trip(test_db):
+    """Test large_int_policy="text" with decode_large_ints reads values back as int."""
+    async with connect(test_db) as db:
+        await db.execute("CREATE TABLE t (id INTEGER PRIMARY KEY, value TEXT)")
+        db.large_int_policy = "text"
+        values = [2**64, -(2**70), 12345678901234567890123]
+        for value in values:
+            await db.execute("INSERT INTO t (value) VALUES (:v)", {"v": value})
+        await db.execute("INSERT INTO t (value) VALUES (?)", ["not a number"])
+
+        rows = await db.fetch_all("SELECT value FROM t ORDER BY id")
+        assert [row[0] for row in rows] == [str(v) for v in values] + ["not a number"]
+
+        # Comparisons stay exact because the full decimal text is stored
+        row = await db.fetch_one("SELECT COUNT(*) FROM t WHERE value = ?", [2**64])
+        assert row[0] == 1
+
+        db.decode_large_ints = True
+        rows = await db.fetch_all("SELECT value FROM t ORDER BY id")
+        assert [row[0] for row in rows] == values + ["not a number"]
+        # In-range values and non-integer text are not affected
+        row = await db.fetch_one("SELECT '42', '1e30'")
+        assert row == ["42", "1e30"]
+
+
+@pytest.mark.edge_case
+@pytest.mark.asyncio
+async def test_large_int_policy_blob(test_db):
+    """Test large_int_policy="blob" stores two's complement big-endian bytes."""
+    async with connect(test_db) as db:
+        await db.execute("CREATE TABLE t (id INTEGER PRIMARY KEY, value BLOB)")
+        db.large_int_policy = "blob"
+        values = [2**63, -(2**63) - 1, 2**100]
+        for value in values:
+            await db.execute("INSERT INTO t (value) VALUES (?)", [value])
+        # In-range ints are still bound as INTEGER
+        await db.execute("INSERT INTO t (value) VALUES (?)", [7])
+
+        rows = await db.fetch_all("SELECT value FROM t ORDER BY id")
+        decoded = [int.from_bytes(row[0], "big", signed=True) for row in rows[:3]]
+        assert decoded == values
+        assert rows[3][0] == 7
+
+        with pytest.raises(ValueError):
+            db.large_int_policy = "float"
+        assert db.large_int_policy == "blob"
+
+
+@pytest.mark.edge_case
+@pytest.mark.asyncio
+async def test_large_int_policy_applies_to_udf_results(test_db):
+    """Test ints returned by UDFs follow the connection's large_int_policy."""
+    async with connect(test_db) as db:
+        await db.create_function("big", 0, lambda: 2**70)
+        with pytest.raises(OverflowError, match="large_int_policy"):
+            await db.fetch_all("SELECT big()")
+
+        db.large_int_policy = "text"
+        assert await db.fetch_all("SELECT big(), typeof(big())") == [[str(2**70), "text"]]
+
+        db.large_int_policy = "blob"
+        row = await db.fetch_one("SELECT big()")
+        assert int.from_bytes(row[0], "big", signed=True) == 2**70
+
+
+@pytest.mark.edge_case
+@pytest.mark.asyncio
+async def test_writes_while_iterating_see_snapshot(test_db):
+    """Test writing to a table while walking a cursor over it.
+
+    Results are materialized on the first fetch, so the iteration must not see
+    rows written during it (SQLite's own semantics here are undefined).
+    """
+    async with connect(test_db) as db:
+        await db.execute("CREATE TABLE t (id INTEGER PRIMARY KEY)")
+        await db.execute("INSERT INTO t (id) VALUES (1), (2), (3)")
+
+        cursor = await db.execute("SELECT id FROM t ORDER BY id")
+        seen = []
+        while (row := await cursor.fetchone()) is not None:
+            seen.append(row[0])
+            await db.execute("INSERT INTO t (id) VALUES (?)", [row[0] + 100])
+        assert seen == [1, 2, 3]
+
+        async with db.transaction():
+            cursor = await db.execute("SELECT id FROM t ORDER BY id")
+            seen = []
+            while (row := await cursor.fetchone()) is not None:
+                seen.append(row[0])
+                await db.execute("UPDATE t SET id = id + 1000 WHERE id = ?", [row[0]])
+            assert seen == [1, 2, 3, 101, 102, 103]
+
+
+@pytest.mark.edge_case
+@pytest.mark.asyncio
+async def test_decoding_follows_storage_class(test_db):
+    """Test values decode by their storage class, not by what they look like."""
+    async with connect(test_db) as db:
+        await db.execute(
+            "CREATE TABLE t (id INTEGER PRIMARY KEY, a, n INTEGER, s TEXT)"
+        )
+        await db.execute("INSERT INTO t (a, n, s) VALUES ('007', 'abc', '42')")
+        await db.execute("INSERT INTO t (a, n, s) VALUES (7, 3, 4.5)")
+        await db.execute("INSERT INTO t (a, n, s) VALUES (1.25, NULL, x'00ff')")
+        await db.execute("INSERT INTO t (a, n, s) VALUES (x'01', 2.5, NULL)")
+
+        rows = await db.fetch_all("SELECT a, n, s FROM t ORDER BY id")
+        assert rows[0] == ["007", "abc", "42"]
+        assert rows[1] == [7, 3, "4.5"]
+        assert rows[2] == [1.25, None, b"\x00\xff"]
+        assert rows[3] == [b"\x01", 2.5, None]
+        assert type(rows[1][0]) is int
+        assert type(rows[2][0]) is float
+
+
+@pytest.mark.edge_case
+@pytest.mark.asyncio
+async def test_strict_mode_rejects_lossy_parameters(test_db):
+    """Test strict=True refuses NaN and types without a SQLite equivalent."""
+    from decimal import Decimal
+
+    async with connect(test_db, strict=True) as db:
+        assert db.strict is True
+        await db.execute("CREATE TABLE t (value)")
+        with pytest.raises(ValueError):
+            await db.execute("INSERT INTO t (value) VALUES (?)", [float("nan")])
+        with pytest.raises(TypeError):
+            await db.execute("INSERT INTO t (value) VALUES (?)", [Decimal("1.5")])
+        with pytest.raises(TypeError):
+            await db.execute("INSERT INTO t (value) VALUES (?)", [[1, 2]])
+        # Native types still bind
+        await db.execute(
+            "INSERT INTO t (value) VALUES (?), (?), (?), (?), (?)",
+            [1, 2.5, "x", b"y", None],
+        )
+        rows = await db.fetch_all("SELECT value FROM t ORDER BY rowid")
+        assert [row[0] for row in rows] == [1, 2.5, "x", b"y", None]
+
+
+@pytest.mark.edge_case
+@pytest.mark.asyncio
+async def test_strict_mode_decodes_by_declared_type(test_db):
+    """Test strict=True decodes by declared type and raises on mismatches."""
+    async with connect(test_db) as db:
+        assert db.strict is False
+        await db.execute("CREATE TABLE t (i INTEGER, r REAL, s TEXT)")
+        await db.execute("INSERT INTO t VALUES (1, 2, '003')")
+        await db.execute("INSERT INTO t VALUES ('abc', 1.5, 'x')")
+
+    async with connect(test_db, strict=True) as db:
+        row = await db.fetch_one("SELECT i, r, s FROM t WHERE rowid = 1")
+        assert row == [1, 2.0, "003"]
+        assert isinstance(row[1], float)
+        # Expressions have no declared type and decode by storage class
+        assert await db.fetch_one("SELECT 1 + 1, 'a' || 'b'") == [2, "ab"]
+        with pytest.raises(DatabaseError, match="declared INTEGER"):
+            await db.fetch_all("SELECT i FROM t")
+
+
+@pytest.mark.edge_case
+@pytest.mark.asyncio
+async def test_create_table_strict(test_db):
+    """Test create_table emits STRICT tables when requested."""
+    async with connect(test_db, strict=True) as db:
+        await db.create_table("plain", {"id": "INTEGER PRIMARY KEY"}, strict=False)
+        await db.create_table(
+            "typed", {"id": "INTEGER PRIMARY KEY", "name": "TEXT NOT NULL"}
+        )
+        await db.create_table("typed", {"id": "INTEGER"}, if_not_exists=True)
+        rows = await db.fetch_all(
+            "SELECT name, sql FROM sqlite_master WHERE type = 'table' ORDER BY name"
+        )
+        sql = {row[0]: row[1] for row in rows}
+        assert not sql["plain"].endswith("STRICT")
+        assert sql["typed"].endswith("STRICT")
+
+        with pytest.raises(DatabaseError):
+            await db.execute("INSERT INTO typed (name) VALUES (?)", [b"\x00"])
+
+
 @pytest.mark.edge_case
 @pytest.mark.asyncio
 async def test_nan_float(test_db):
@@ -409,6 +630,45 @@ async def test_infinity_float(test_db):
         assert math.isinf(rows[1][0])
 
 
+@pytest.mark.edge_case
+@pytest.mark.asyncio
+async def test_null_floats_as_nan(test_db):
+    """Test null_floats_as_nan fetches NULL REAL values (and stored NaN) as NaN."""
+    import math
+
+    async with connect(test_db) as db:
+        assert db.null_floats_as_nan is False
+        await db.execute(
+            "CREATE TABLE t (id INTEGER PRIMARY KEY, value REAL, label TEXT)"
+        )
+        await db.execute("INSERT INTO t (value) VALUES (?)", [float("nan")])
+        await db.execute("INSERT INTO t (value) VALUES (?)", [None])
+        await db.execute("INSERT INTO t (value) VALUES (?)", [float("-inf")])
+        await db.execute("INSERT INTO t (value) VALUES (?)", [1.5])
+
+        db.null_floats_as_nan = True
+        assert db.null_floats_as_nan is True
+        rows = await db.fetch_all("SELECT value, label FROM t ORDER BY id")
+        assert math.isnan(rows[0][0])
+        assert math.isnan(rows[1][0])
+        assert rows[2][0] == float("-inf")
+        assert rows[3][0] == 1.5
+        # Non-REAL columns keep returning None for NULL
+        assert all(row[1] is None for row in rows)
+
+        # Applies to every fetch path and row factory
+        db.row_factory = "dict"
+        row = await db.fetch_one("SELECT value FROM t WHERE id = 2")
+        assert math.isnan(row["value"])
+        cursor = db.cursor()
+        await cursor.execute("SELECT value FROM t WHERE id = 2")
+        assert math.isnan((await cursor.fetchone())["value"])
+
+        db.null_floats_as_nan = False
+        row = await db.fetch_optional("SELECT value FROM t WHERE id = 2")
+        assert row["value"] is None
+
+
 @pytest.mark.edge_case
 @pytest.mark.asyncio
 async def test_empty_blob(test_db):
@@ -460,3 +720,42 @@ async def test_null_handling(test_db):
         assert rows[1][1] is None
         assert rows[2][0] is None
         assert rows[2][1] == 42
+
+
+@pytest.mark.edge_case
+@pytest.mark.asyncio
+async def test_create_index_expressions_and_partial(test_db):
+    """Test create_index quotes columns, inlines sql() expressions and drop_index."""
+    async with connect(test_db) as db:
+        await db.create_table(
+            "user accounts",
+            {"id": "INTEGER PRIMARY KEY", "email": "TEXT", "deleted": "INTEGER"},
+        )
+        name = await db.create_index(
+            "user accounts",
+            [sql("lower({col})").bind(col="email")],
+            unique=True,
+            where="deleted = 0",
+        )
+        assert name == "idx_user accounts_lower__email"
+        assert await db.create_index("user accounts", "deleted", name="by_deleted")
+        # if_not_exists=True by default
+        await db.create_index("user accounts", ["deleted"], name="by_deleted")
+
+        await db.execute(
+            'INSERT INTO "user accounts" (email, deleted) VALUES '
+            "('A@x.io', 0), ('a@x.io', 1)"
+        )
+        with pytest.raises(IntegrityError):
+            await db.execute(
+                "INSERT INTO \"user accounts\" (email, deleted) VALUES ('a@X.io', 0)"
+            )
+        with pytest.raises(ValueError):
+            await db.create_index("user accounts", [sql("email = :v").bind(v=1)])
+
+        await db.drop_index(name)
+        await db.drop_index(name)
+        rows = await db.fetch_all(
+            "SELECT name FROM sqlite_master WHERE type = 'index' ORDER BY name"
+        )
+        assert rows == [["by_deleted"]]
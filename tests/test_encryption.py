@@ -0,0 +1,91 @@
+"""Tests for encrypted backups and dumps."""
+
+import os
+
+import pytest
+
+from rapsqlite import Connection, OperationalError, decrypt_file
+
+
+async def _populate(conn):
+    await conn.execute("CREATE TABLE t (id INTEGER PRIMARY KEY, name TEXT)")
+    await conn.execute_many(
+        "INSERT INTO t (name) VALUES (?)", [["row %d" % i] for i in range(500)]
+    )
+
+
+@pytest.mark.asyncio
+async def test_backup_encrypted_roundtrip(test_db, tmp_path):
+    key = os.urandom(32)
+    sealed = str(tmp_path / "app.db.enc")
+    async with Connection(test_db) as conn:
+        await _populate(conn)
+        await conn.backup_encrypted(sealed, key)
+    with open(sealed, "rb") as f:
+        data = f.read()
+    assert data.startswith(b"RSQLENC1")
+    assert b"SQLite format 3" not in data and b"row 42" not in data
+
+    restored = str(tmp_path / "restored.db")
+    await decrypt_file(sealed, restored, key)
+    async with Connection(restored) as conn:
+        assert await conn.fetch_one("SELECT count(*) FROM t") == [500]
+
+
+@pytest.mark.asyncio
+async def test_dump_encrypted_roundtrip(test_db, tmp_path):
+    key = os.urandom(32)
+    sealed = str(tmp_path / "dump.sql.enc")
+    async with Connection(test_db) as conn:
+        await _populate(conn)
+        await conn.dump_encrypted(sealed, key)
+        lines = await conn.iterdump()
+
+    script = str(tmp_path / "dump.sql")
+    await decrypt_file(sealed, script, key)
+    with open(script) as f:
+        sql = f.read()
+    assert sql == "\n".join(lines) + "\n"
+    restored = tmp_path / "restored.db"
+    restored.touch()
+    async with Connection(str(restored)) as conn:
+        cursor = conn.cursor()
+        await cursor.executescript(sql)
+        assert await conn.fetch_one("SELECT name FROM t WHERE id = 500") == ["row 499"]
+
+
+@pytest.mark.asyncio
+async def test_decrypt_rejects_wrong_key_and_tampering(test_db, tmp_path):
+    key = os.urandom(32)
+    sealed = tmp_path / "app.db.enc"
+    restored = tmp_path / "restored.db"
+    async with Connection(test_db) as conn:
+        await _populate(conn)
+        await conn.backup_encrypted(str(sealed), key)
+
+    with pytest.raises(OperationalError, match="wrong key or corrupted"):
+        await decrypt_file(str(sealed), str(restored), os.urandom(32))
+    assert not restored.exists()
+
+    data = bytearray(sealed.read_bytes())
+    data[len(data) // 2] ^= 1
+    sealed.write_bytes(bytes(data))
+    with pytest.raises(OperationalError, match="wrong key or corrupted"):
+        await decrypt_file(str(sealed), str(restored), key)
+    assert not restored.exists()
+
+
+@pytest.mark.asyncio
+async def test_encryption_errors(test_db, tmp_path):
+    sealed = str(tmp_path / "app.db.enc")
+    async with Connection(test_db) as conn:
+        await _populate(conn)
+        with pytest.raises(ValueError, match="32 bytes"):
+            await conn.backup_encrypted(sealed, b"too short")
+        with pytest.raises(ValueError, match="32 bytes"):
+            await conn.dump_encrypted(sealed, b"x" * 33)
+        async with conn.transaction():
+            with pytest.raises(OperationalError, match="transaction"):
+                await conn.backup_encrypted(sealed, os.urandom(32))
+    with pytest.raises(OperationalError, match="rapsqlite encrypted file"):
+        await decrypt_file(test_db, str(tmp_path / "out.db"), os.urandom(32))
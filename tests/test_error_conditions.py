@@ -159,6 +159,39 @@ async def test_invalid_parameter_type(test_db):
             await db.execute("INSERT INTO t (name) VALUES (?)", [{"invalid": "type"}])
 
 
+@pytest.mark.edge_case
+@pytest.mark.asyncio
+async def test_multiple_statements_need_multi(test_db):
+    """execute() refuses a query holding several statements unless multi=True."""
+    async with connect(test_db) as db:
+        await db.execute("CREATE TABLE t (id INTEGER PRIMARY KEY, name TEXT)")
+
+        with pytest.raises(ProgrammingError, match="multi=True"):
+            await db.execute("INSERT INTO t (name) VALUES ('a'); DROP TABLE t")
+        cursor = db.cursor()
+        with pytest.raises(ProgrammingError, match="multi=True"):
+            await cursor.execute("SELECT 1; SELECT 2")
+        assert await db.fetch_all("SELECT * FROM t") == []
+
+        # Trailing semicolons, comments and trigger bodies are one statement
+        await db.execute("INSERT INTO t (name) VALUES ('a'); -- done")
+        await db.execute(
+            "CREATE TRIGGER tr AFTER DELETE ON t BEGIN "
+            "INSERT INTO t (name) VALUES ('deleted'); SELECT 1; END;"
+        )
+
+        await db.execute(
+            "INSERT INTO t (name) VALUES (?); UPDATE t SET name = ? WHERE name = 'a'",
+            ["b", "c"],
+            multi=True,
+        )
+        rows = await db.fetch_all("SELECT name FROM t ORDER BY id")
+        assert rows == [["c"], ["b"]]
+        await cursor.execute("DELETE FROM t WHERE name = 'b'; DROP TRIGGER tr", multi=True)
+        rows = await db.fetch_all("SELECT name FROM t ORDER BY id")
+        assert rows == [["c"], ["deleted"]]
+
+
 @pytest.mark.edge_case
 @pytest.mark.asyncio
 async def test_rollback_without_transaction(test_db):
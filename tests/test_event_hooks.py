@@ -0,0 +1,90 @@
+"""Tests for Connection.set_event_hook() lifecycle hooks."""
+
+import asyncio
+
+import pytest
+
+from rapsqlite import Connection, OperationalError, ProgrammingError
+
+
+@pytest.mark.asyncio
+async def test_event_hooks_report_pool_events(test_db):
+    events = []
+
+    def recorder(name):
+        async def hook(conn):
+            events.append((name, conn.id))
+
+        return hook
+
+    conn = Connection(test_db)
+    for name in ("connect", "acquire", "release", "close"):
+        conn.set_event_hook(name, recorder(name))
+    await conn.execute("CREATE TABLE t (id INTEGER PRIMARY KEY)")
+    await conn.fetch_all("SELECT * FROM t")
+    await conn.close()
+    # The close hook is scheduled on the loop by the thread closing the connection
+    for _ in range(100):
+        if events and events[-1][0] == "close":
+            break
+        await asyncio.sleep(0.01)
+
+    names = [name for name, _ in events]
+    assert names[0] == "connect"
+    assert names[-1] == "close"
+    assert "release" in names
+    assert names.count("acquire") >= 2
+    assert len({conn_id for _, conn_id in events}) == 1
+
+
+@pytest.mark.asyncio
+async def test_connect_hook_configures_each_connection(test_db):
+    async def on_connect(conn):
+        assert await conn.execute("PRAGMA user_version = 7") == 0
+
+    async with Connection(test_db) as conn:
+        conn.set_event_hook("connect", on_connect)
+        rows = await conn.fetch_all("PRAGMA user_version")
+        assert rows == [[7]]
+
+
+@pytest.mark.asyncio
+async def test_pooled_connection_unusable_after_hook(test_db):
+    kept = []
+
+    async def on_connect(conn):
+        kept.append(conn)
+
+    async with Connection(test_db) as conn:
+        conn.set_event_hook("connect", on_connect)
+        await conn.execute("SELECT 1")
+        with pytest.raises(ProgrammingError):
+            await kept[0].execute("SELECT 1")
+
+
+@pytest.mark.asyncio
+async def test_failing_connect_hook_discards_connection(test_db):
+    async def on_connect(conn):
+        raise RuntimeError("no thanks")
+
+    async with Connection(test_db) as conn:
+        conn.connection_timeout = 1
+        conn.set_event_hook("connect", on_connect)
+        # Opening a connection is retried until the timeout
+        with pytest.raises(OperationalError, match="timed out"):
+            await conn.execute("SELECT 1")
+        conn.set_event_hook("connect", None)
+        await conn.execute("SELECT 1")
+
+
+@pytest.mark.asyncio
+async def test_set_event_hook_validation(test_db):
+    async with Connection(test_db) as conn:
+        with pytest.raises(ValueError):
+            conn.set_event_hook("checkout", None)
+
+        def not_async(conn):
+            pass
+
+        with pytest.raises(TypeError):
+            conn.set_event_hook("connect", not_async)
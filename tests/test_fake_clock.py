@@ -0,0 +1,70 @@
+"""Tests for rapsqlite.FakeClock (Connection(clock=...))."""
+
+import datetime
+
+import pytest
+
+from rapsqlite import Connection, ConnectionSpec, FakeClock, connect
+
+
+@pytest.mark.asyncio
+async def test_fake_clock_drives_sql_now(test_db):
+    clock = FakeClock(datetime.datetime(2024, 2, 28, 23, 59, 30))
+    async with Connection(test_db, clock=clock) as conn:
+        rows = await conn.fetch_all(
+            "SELECT CURRENT_TIMESTAMP, datetime('now'), unixepoch()"
+        )
+        assert rows == [["2024-02-28 23:59:30", "2024-02-28 23:59:30", 1709164770]]
+
+        clock.advance(60)
+        assert await conn.fetch_all("SELECT date('now')") == [["2024-02-29"]]
+
+        clock.set(0)
+        assert clock.now == 0.0
+        assert await conn.fetch_all("SELECT datetime('now')") == [
+            ["1970-01-01 00:00:00"]
+        ]
+
+        # Defaults are evaluated with the clock too
+        await conn.execute(
+            "CREATE TABLE events (id INTEGER PRIMARY KEY, "
+            "at TEXT DEFAULT CURRENT_TIMESTAMP)"
+        )
+        await conn.execute("INSERT INTO events DEFAULT VALUES")
+        assert await conn.fetch_all("SELECT at FROM events") == [
+            ["1970-01-01 00:00:00"]
+        ]
+
+
+@pytest.mark.asyncio
+async def test_fake_clock_aware_datetime_and_real_clock(test_db):
+    tz = datetime.timezone(datetime.timedelta(hours=2))
+    clock = FakeClock(datetime.datetime(1970, 1, 2, 2, 0, tzinfo=tz))
+    assert clock.now == 86400.0
+    async with Connection(test_db, clock=clock) as conn:
+        assert await conn.fetch_all("SELECT datetime('now')") == [
+            ["1970-01-02 00:00:00"]
+        ]
+        # Connections without a clock keep the system time
+        async with Connection(test_db) as other:
+            rows = await other.fetch_all("SELECT unixepoch() > 86400")
+            assert rows == [[1]]
+
+
+@pytest.mark.asyncio
+async def test_connect_accepts_clock(test_db):
+    clock = FakeClock(0)
+    async with connect(test_db, clock=clock) as conn:
+        assert await conn.fetch_all("SELECT unixepoch()") == [[0]]
+    async with ConnectionSpec(test_db, {"clock": clock}).connect() as conn:
+        assert await conn.fetch_all("SELECT unixepoch()") == [[0]]
+
+
+@pytest.mark.asyncio
+async def test_connection_keeps_its_clock(test_db):
+    async with Connection(test_db, clock=FakeClock(0)) as conn:
+        # The first clock is only referenced by the Connection now; new clocks
+        # must not take over its VFS
+        others = [FakeClock(86400) for _ in range(3)]
+        assert await conn.fetch_all("SELECT unixepoch()") == [[0]]
+        del others
@@ -0,0 +1,101 @@
+"""Tests for rapsqlite.FaultInjector (Connection(faults=...))."""
+
+import time
+
+import pytest
+
+from rapsqlite import (
+    Connection,
+    DatabaseError,
+    FakeClock,
+    FaultInjector,
+    OperationalError,
+    connect,
+)
+
+
+@pytest.mark.asyncio
+async def test_busy_is_retried_until_busy_timeout(test_db):
+    faults = FaultInjector()
+    async with Connection(
+        test_db, faults=faults, pragmas={"busy_timeout": 200}
+    ) as conn:
+        await conn.execute("CREATE TABLE t (x)")
+
+        # Retried by SQLite's busy handler
+        faults.busy(3)
+        await conn.execute("INSERT INTO t VALUES (1)")
+        assert faults.injected["lock"] == 3
+
+        faults.busy(10**9)
+        with pytest.raises(OperationalError, match="locked"):
+            await conn.execute("INSERT INTO t VALUES (2)")
+
+        faults.reset()
+        assert faults.injected == {"read": 0, "write": 0, "sync": 0, "lock": 0}
+        await conn.execute("INSERT INTO t VALUES (3)")
+        assert await conn.fetch_all("SELECT x FROM t") == [[1], [3]]
+
+
+@pytest.mark.asyncio
+async def test_io_errors_fail_the_statement(test_db):
+    faults = FaultInjector()
+    async with Connection(test_db, faults=faults) as conn:
+        await conn.execute("CREATE TABLE t (x)")
+        faults.io_error("write")
+        with pytest.raises(DatabaseError, match="disk I/O error"):
+            await conn.execute("INSERT INTO t VALUES (1)")
+        assert faults.injected["write"] == 1
+        # Only the next write failed
+        await conn.execute("INSERT INTO t VALUES (2)")
+        assert await conn.fetch_all("SELECT x FROM t") == [[2]]
+
+        with pytest.raises(ValueError):
+            faults.io_error("lock")
+        with pytest.raises(ValueError):
+            faults.latency(0.1, "seek")
+
+
+@pytest.mark.asyncio
+async def test_latency_and_clock(test_db):
+    faults = FaultInjector()
+    clock = FakeClock(0)
+    async with Connection(test_db, faults=faults, clock=clock) as conn:
+        await conn.execute("CREATE TABLE t (x)")
+        faults.latency(0.2, "write")
+        start = time.monotonic()
+        await conn.execute("INSERT INTO t VALUES (1)")
+        assert time.monotonic() - start >= 0.2
+        faults.latency(0)
+        # The clock still drives 'now' underneath the injector
+        assert await conn.fetch_all("SELECT unixepoch()") == [[0]]
+
+
+@pytest.mark.asyncio
+async def test_new_injector_clears_shared_faults(test_db):
+    faults = FaultInjector()
+    async with Connection(test_db, faults=faults) as conn:
+        await conn.execute("CREATE TABLE t (x)")
+        faults.io_error("write", 10)
+        with pytest.raises(DatabaseError):
+            await conn.execute("INSERT INTO t VALUES (1)")
+
+        # Injectors share one VFS and one set of faults
+        other = FaultInjector()
+        assert faults.injected["write"] == 0
+        await conn.execute("INSERT INTO t VALUES (2)")
+        other.io_error("write")
+        with pytest.raises(DatabaseError):
+            await conn.execute("INSERT INTO t VALUES (3)")
+        assert faults.injected["write"] == 1
+
+
+@pytest.mark.asyncio
+async def test_connect_accepts_faults(test_db):
+    faults = FaultInjector()
+    async with connect(test_db, faults=faults) as conn:
+        await conn.execute("CREATE TABLE t (x)")
+        faults.io_error("write")
+        with pytest.raises(DatabaseError, match="disk I/O error"):
+            await conn.execute("INSERT INTO t VALUES (1)")
+    assert faults.injected["write"] == 1
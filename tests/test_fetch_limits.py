@@ -0,0 +1,97 @@
+"""Tests for fetch_all result guardrails (max_rows= / max_result_bytes=)."""
+
+import pytest
+
+import rapsqlite
+from rapsqlite import Connection, OperationalError, ResultTooLarge, connect
+
+
+async def _setup(conn, count=20):
+    await conn.execute("CREATE TABLE items (id INTEGER PRIMARY KEY, name TEXT)")
+    await conn.execute_many(
+        "INSERT INTO items (id, name) VALUES (?, ?)",
+        [[i, "abcd"] for i in range(count)],
+    )
+
+
+@pytest.mark.asyncio
+async def test_max_rows_per_call(test_db):
+    async with Connection(test_db) as conn:
+        await _setup(conn)
+        rows = await conn.fetch_all("SELECT * FROM items", max_rows=20)
+        assert len(rows) == 20
+        with pytest.raises(ResultTooLarge, match="max_rows=19"):
+            await conn.fetch_all("SELECT * FROM items", max_rows=19)
+        assert issubclass(ResultTooLarge, OperationalError)
+
+
+@pytest.mark.asyncio
+async def test_max_result_bytes_per_call(test_db):
+    async with Connection(test_db) as conn:
+        await _setup(conn)
+        # 12 bytes per row: an INTEGER and 4 bytes of TEXT
+        rows = await conn.fetch_all("SELECT * FROM items", max_result_bytes=240)
+        assert len(rows) == 20
+        with pytest.raises(ResultTooLarge, match="max_result_bytes=239"):
+            await conn.fetch_all("SELECT * FROM items", max_result_bytes=239)
+
+
+@pytest.mark.asyncio
+async def test_connection_defaults_and_override(test_db):
+    async with Connection(test_db, max_rows=10) as conn:
+        await _setup(conn)
+        assert len(await conn.fetch_all("SELECT * FROM items LIMIT 10")) == 10
+        with pytest.raises(ResultTooLarge):
+            await conn.fetch_all("SELECT * FROM items")
+        rows = await conn.fetch_all("SELECT * FROM items", max_rows=100)
+        assert len(rows) == 20
+
+
+@pytest.mark.asyncio
+async def test_unbounded_query_is_abandoned():
+    async with Connection(":memory:", max_rows=1000) as conn:
+        with pytest.raises(ResultTooLarge):
+            await conn.fetch_all(
+                "WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n) "
+                "SELECT i FROM n"
+            )
+
+
+@pytest.mark.asyncio
+async def test_limits_in_transaction(test_db):
+    async with Connection(test_db) as conn:
+        await _setup(conn)
+        async with conn.transaction():
+            with pytest.raises(ResultTooLarge):
+                await conn.fetch_all("SELECT * FROM items", max_rows=5)
+            # The transaction is still usable
+            assert len(await conn.fetch_all("SELECT * FROM items")) == 20
+
+
+@pytest.mark.asyncio
+async def test_limits_apply_to_cached_results(test_db):
+    async with Connection(test_db, cache="lru") as conn:
+        await _setup(conn)
+        assert len(await conn.fetch_all("SELECT * FROM items")) == 20
+        with pytest.raises(ResultTooLarge):
+            await conn.fetch_all("SELECT * FROM items", max_rows=5)
+
+        assert len(await conn.cached_fetch_all("SELECT * FROM items", ttl=60)) == 20
+        with pytest.raises(ResultTooLarge):
+            await conn.cached_fetch_all("SELECT * FROM items", ttl=60, max_rows=5)
+
+
+@pytest.mark.asyncio
+async def test_connect_and_named_connection_defaults(test_db):
+    async with connect(test_db, max_result_bytes=100) as conn:
+        await _setup(conn)
+        with pytest.raises(ResultTooLarge):
+            await conn.fetch_all("SELECT * FROM items")
+
+    rapsqlite.register("limited", test_db, max_rows=3)
+    try:
+        async with connect("name://limited") as conn:
+            with pytest.raises(ResultTooLarge):
+                await conn.fetch_all("SELECT * FROM items")
+    finally:
+        rapsqlite.unregister("limited")
@@ -0,0 +1,106 @@
+"""Tests for rapsqlite.FileLock / with_write_lease (cross-process advisory locks)."""
+
+import asyncio
+import os
+import sqlite3
+import subprocess
+import sys
+import time
+
+import pytest
+
+from rapsqlite import FileLock, OperationalError, with_write_lease
+
+
+@pytest.mark.asyncio
+async def test_lock_excludes_other_holders(test_db):
+    first = with_write_lease(test_db)
+    async with first:
+        assert first.locked
+        with pytest.raises(OperationalError, match="Timed out"):
+            async with FileLock(test_db, "write", timeout=0.2):
+                pass
+        # Different names are independent
+        async with FileLock(test_db, "other", timeout=0):
+            pass
+    assert not first.locked
+
+    async with FileLock(test_db, "write", timeout=0):
+        pass
+
+
+@pytest.mark.asyncio
+async def test_waiter_acquires_after_release(test_db):
+    order = []
+
+    async def worker(tag):
+        async with FileLock(test_db, "migrate", poll_interval=0.01):
+            order.append(f"{tag}-in")
+            await asyncio.sleep(0.05)
+            order.append(f"{tag}-out")
+
+    await asyncio.gather(worker("a"), worker("b"))
+    assert order in (
+        ["a-in", "a-out", "b-in", "b-out"],
+        ["b-in", "b-out", "a-in", "a-out"],
+    )
+
+
+@pytest.mark.asyncio
+async def test_expired_lease_is_taken_over(test_db):
+    async with FileLock(test_db, "vacuum", timeout=0):
+        pass
+    # A holder that crashed leaves a lease behind that has since expired
+    conn = sqlite3.connect(test_db)
+    conn.execute(
+        "INSERT INTO _rapsqlite_locks VALUES ('vacuum', 'dead-worker', ?)",
+        (time.time() - 1,),
+    )
+    conn.commit()
+    conn.close()
+
+    async with FileLock(test_db, "vacuum", timeout=0) as lock:
+        conn = sqlite3.connect(test_db)
+        owner = conn.execute("SELECT owner FROM _rapsqlite_locks").fetchone()[0]
+        conn.close()
+        assert owner == lock.owner
+
+
+@pytest.mark.asyncio
+async def test_lock_held_by_another_process(test_db):
+    script = (
+        "import asyncio, sys\n"
+        "from rapsqlite import FileLock\n"
+        "async def main():\n"
+        "    async with FileLock(sys.argv[1], 'write', timeout=0):\n"
+        "        print('held', flush=True)\n"
+        "        sys.stdin.readline()\n"
+        "    print('released', flush=True)\n"
+        "asyncio.run(main())\n"
+    )
+    child = subprocess.Popen(
+        [sys.executable, "-c", script, test_db],
+        stdin=subprocess.PIPE,
+        stdout=subprocess.PIPE,
+        text=True,
+        # Import rapsqlite from wherever this process found it
+        env={**os.environ, "PYTHONPATH": os.pathsep.join(sys.path)},
+    )
+    try:
+        assert child.stdout.readline().strip() == "held"
+        with pytest.raises(OperationalError):
+            await FileLock(test_db, "write", timeout=0).acquire()
+        child.stdin.write("\n")
+        child.stdin.flush()
+        assert child.stdout.readline().strip() == "released"
+        async with FileLock(test_db, "write", timeout=5):
+            pass
+    finally:
+        child.kill()
+
+
+def test_invalid_options(test_db):
+    with pytest.raises(ValueError):
+        FileLock(test_db, lease=0)
+    with pytest.raises(ValueError):
+        FileLock(test_db, timeout=-1)
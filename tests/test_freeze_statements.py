@@ -0,0 +1,75 @@
+"""Tests for Connection.freeze_statements() (statement allowlist mode)."""
+
+import pytest
+
+from rapsqlite import Connection, ProgrammingError
+
+
+async def _warm_up(conn):
+    await conn.execute("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT)")
+    await conn.execute("INSERT INTO users (name) VALUES (?)", ["alice"])
+    await conn.fetch_all("SELECT name FROM users WHERE id = :id", {"id": 1})
+
+
+@pytest.mark.asyncio
+async def test_freeze_after_warm_up(test_db):
+    async with Connection(test_db) as conn:
+        await _warm_up(conn)
+        conn.freeze_statements()
+
+        await conn.execute("INSERT INTO users (name) VALUES (?)", ["bob"])
+        # Whitespace differences are ignored
+        rows = await conn.fetch_all(
+            "SELECT name\n  FROM users WHERE id = :id", {"id": 2}
+        )
+        assert rows == [["bob"]]
+
+        with pytest.raises(ProgrammingError, match="freeze_statements"):
+            await conn.execute("DROP TABLE users")
+        with pytest.raises(ProgrammingError):
+            await conn.fetch_one("SELECT name FROM users WHERE id = 1")
+        with pytest.raises(ProgrammingError):
+            await conn.execute_many("DELETE FROM users WHERE id = ?", [[1]])
+        with pytest.raises(ProgrammingError):
+            await conn.execute_raw("DELETE FROM users")
+        rows = await conn.fetch_all("SELECT name FROM users WHERE id = :id", {"id": 1})
+        assert rows == [["alice"]]
+
+
+@pytest.mark.asyncio
+async def test_freeze_with_explicit_list(test_db):
+    async with Connection(test_db) as conn:
+        await _warm_up(conn)
+        conn.freeze_statements(["SELECT count(*) FROM users"])
+
+        assert await conn.fetch_one("SELECT count(*) FROM users") == [1]
+        with pytest.raises(ProgrammingError):
+            await conn.execute("INSERT INTO users (name) VALUES (?)", ["bob"])
+
+
+@pytest.mark.asyncio
+async def test_freeze_covers_cursors(test_db):
+    async with Connection(test_db) as conn:
+        await _warm_up(conn)
+        conn.freeze_statements()
+
+        cursor = conn.cursor()
+        with pytest.raises(ProgrammingError):
+            await cursor.execute("DELETE FROM users")
+        with pytest.raises(ProgrammingError):
+            await cursor.executescript("DELETE FROM users; DROP TABLE users;")
+        rows = await conn.fetch_all("SELECT name FROM users WHERE id = :id", {"id": 1})
+        assert rows == [["alice"]]
+
+
+@pytest.mark.asyncio
+async def test_internal_statements_unaffected(test_db):
+    async with Connection(test_db) as conn:
+        await _warm_up(conn)
+        conn.freeze_statements()
+
+        assert "users" in await conn.get_tables()
+        async with conn.transaction():
+            await conn.execute("INSERT INTO users (name) VALUES (?)", ["carol"])
+        rows = await conn.fetch_all("SELECT name FROM users WHERE id = :id", {"id": 2})
+        assert rows == [["carol"]]
@@ -0,0 +1,81 @@
+"""Tests for weak references and garbage collection of Connection/Cursor."""
+
+import asyncio
+import gc
+import weakref
+
+import pytest
+
+import rapsqlite
+
+
+async def _collect():
+    # References released on rapsqlite's worker threads are only dropped the
+    # next time the extension is entered, so touch it before collecting
+    for _ in range(3):
+        await asyncio.sleep(0.02)
+        rapsqlite.connect(":memory:")
+        gc.collect()
+
+
+@pytest.mark.asyncio
+async def test_weakref_to_connection_and_cursor(test_db):
+    async with rapsqlite.connect(test_db) as conn:
+        cursor = conn.cursor()
+        conn_ref = weakref.ref(conn)
+        cursor_ref = weakref.ref(cursor)
+        assert conn_ref() is conn
+        assert cursor_ref() is cursor
+
+    del conn, cursor
+    await _collect()
+    assert conn_ref() is None
+    assert cursor_ref() is None
+
+
+async def _udf_closure_cycle(path, close):
+    conn = rapsqlite.connect(path)
+    await conn.create_function("conn_id", 0, lambda: id(conn))
+    assert (await conn.fetch_one("SELECT conn_id()"))[0] == id(conn)
+    cursor = await conn.execute("SELECT conn_id()")
+    await cursor.fetchall()
+    if close:
+        await conn.close()
+    return weakref.ref(conn), weakref.ref(cursor)
+
+
+@pytest.mark.asyncio
+async def test_udf_closure_cycle_is_collected(test_db):
+    refs = await _udf_closure_cycle(test_db, close=True)
+    await _collect()
+    assert [ref() for ref in refs] == [None, None]
+
+
+@pytest.mark.asyncio
+async def test_unclosed_udf_closure_cycle_is_collected(test_db):
+    # The dedicated UDF connection goes back to the pool when collected
+    refs = await _udf_closure_cycle(test_db, close=False)
+    await _collect()
+    assert [ref() for ref in refs] == [None, None]
+
+
+@pytest.mark.asyncio
+async def test_callback_closure_cycles_are_collected(test_db):
+    conn = rapsqlite.connect(test_db)
+    statements = []
+    await conn.set_trace_callback(lambda sql: statements.append((conn, sql)))
+    await conn.set_progress_handler(100, lambda: conn is None)
+    await conn.set_update_hook(lambda *event: statements.append((conn, event)))
+    conn.row_factory = lambda row: (conn, row)
+
+    async def on_connect(pooled):
+        assert conn is not None
+
+    conn.set_event_hook("connect", on_connect)
+    assert (await conn.fetch_all("SELECT 1"))[0][1] == [1]
+    assert statements
+
+    ref = weakref.ref(conn)
+    del conn, statements
+    await _collect()
+    assert ref() is None
@@ -0,0 +1,71 @@
+"""Tests for group commit mode: Connection(group_commit_window=...)."""
+
+import asyncio
+
+import pytest
+
+from rapsqlite import (
+    Connection,
+    IntegrityError,
+    ValueError as RapValueError,
+    connect,
+)
+
+
+def test_group_commit_window_validation(test_db):
+    for window in (0, -0.5, float("nan"), float("inf")):
+        with pytest.raises((ValueError, RapValueError)):
+            Connection(test_db, group_commit_window=window)
+    Connection(test_db, group_commit_window=0.002)
+
+
+@pytest.mark.asyncio
+async def test_concurrent_writes_are_committed(test_db):
+    async with Connection(test_db, group_commit_window=0.01) as conn:
+        await conn.execute("CREATE TABLE t (id INTEGER PRIMARY KEY, v)")
+
+        async def insert(v):
+            cursor = await conn.execute("INSERT INTO t (v) VALUES (?)", [v])
+            return cursor.rowcount, cursor.lastrowid
+
+        results = await asyncio.gather(*(insert(v) for v in range(50)))
+        assert all(rowcount == 1 for rowcount, _ in results)
+        assert sorted(rowid for _, rowid in results) == list(range(1, 51))
+
+        # Visible to other connections once execute() has returned
+        async with Connection(test_db) as other:
+            assert await other.fetch_all("SELECT count(*) FROM t") == [[50]]
+
+        await conn.execute("UPDATE t SET v = v + 1 WHERE v < 10")
+        assert await conn.fetch_all("SELECT sum(v) FROM t") == [[sum(range(50)) + 10]]
+
+
+@pytest.mark.asyncio
+async def test_failing_statement_does_not_affect_its_group(test_db):
+    async with Connection(test_db, group_commit_window=0.05) as conn:
+        await conn.execute("CREATE TABLE t (v UNIQUE)")
+        await conn.execute("INSERT INTO t VALUES (0)")
+
+        results = await asyncio.gather(
+            conn.execute("INSERT INTO t VALUES (1)"),
+            conn.execute("INSERT INTO t VALUES (0)"),
+            conn.execute("INSERT INTO t VALUES (2)"),
+            return_exceptions=True,
+        )
+        assert [r.rowcount for r in (results[0], results[2])] == [1, 1]
+        assert isinstance(results[1], IntegrityError)
+        rows = await conn.fetch_all("SELECT v FROM t ORDER BY v")
+        assert rows == [[0], [1], [2]]
+
+
+@pytest.mark.asyncio
+async def test_transactions_bypass_group_commit(test_db):
+    async with connect(test_db, group_commit_window=0.01) as conn:
+        await conn.execute("CREATE TABLE t (v)")
+        async with conn.transaction():
+            await conn.execute("INSERT INTO t VALUES (1)")
+            assert await conn.fetch_all("SELECT v FROM t") == [[1]]
+        await conn.begin()
+        await conn.execute("INSERT INTO t VALUES (2)")
+        await conn.rollback()
+        assert await conn.fetch_all("SELECT v FROM t") == [[1]]
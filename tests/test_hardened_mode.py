@@ -0,0 +1,95 @@
+"""Tests for hardened mode (authorizer tripwire for semi-trusted SQL)."""
+
+import pytest
+
+from rapsqlite import Connection, DatabaseError, connect
+
+SQLITE_DENY = 1
+SQLITE_OK = 0
+SQLITE_INSERT = 18
+
+
+async def _create_schema(path):
+    async with Connection(path) as conn:
+        await conn.execute("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT)")
+
+
+@pytest.mark.asyncio
+async def test_denied_statements(test_db):
+    await _create_schema(test_db)
+    async with Connection(test_db, hardened=True) as conn:
+        for sql in [
+            "CREATE TABLE other (x)",
+            "DROP TABLE users",
+            "ALTER TABLE users ADD COLUMN email TEXT",
+            "CREATE INDEX idx_name ON users (name)",
+            "ATTACH DATABASE ':memory:' AS side",
+            "PRAGMA writable_schema = ON",
+            "PRAGMA journal_mode = DELETE",
+        ]:
+            with pytest.raises(DatabaseError, match="not authorized"):
+                await conn.execute(sql)
+        assert await conn.get_tables() == ["users"]
+
+
+@pytest.mark.asyncio
+async def test_ordinary_statements_allowed(test_db):
+    await _create_schema(test_db)
+    async with Connection(
+        test_db, hardened=True, pragmas={"foreign_keys": "ON"}
+    ) as conn:
+        await conn.execute("INSERT INTO users (name) VALUES (?)", ["ann"])
+        await conn.execute("UPDATE users SET name = ? WHERE id = 1", ["bob"])
+        assert await conn.fetch_all("SELECT name FROM users") == [["bob"]]
+        # PRAGMA reads, introspection and TEMP objects are fine
+        assert await conn.fetch_all("PRAGMA foreign_keys") == [[1]]
+        assert len(await conn.get_table_info("users")) == 2
+        await conn.execute("CREATE TEMP TABLE scratch (x)")
+        async with conn.transaction():
+            await conn.execute("DELETE FROM users")
+
+
+@pytest.mark.asyncio
+async def test_allowlist(test_db):
+    await _create_schema(test_db)
+    async with connect(
+        test_db,
+        hardened=True,
+        hardened_allow=["schema", "pragma:user_version"],
+    ) as conn:
+        await conn.execute("CREATE TABLE other (x)")
+        await conn.execute("PRAGMA user_version = 5")
+        assert await conn.fetch_all("PRAGMA user_version") == [[5]]
+        with pytest.raises(DatabaseError, match="not authorized"):
+            await conn.execute("ATTACH DATABASE ':memory:' AS side")
+
+
+@pytest.mark.asyncio
+async def test_policy_survives_python_authorizer(test_db):
+    await _create_schema(test_db)
+    async with Connection(test_db, hardened=True) as conn:
+        calls = []
+
+        def authorizer(action, arg1, arg2, arg3, arg4):
+            calls.append(action)
+            return SQLITE_DENY if action == SQLITE_INSERT else SQLITE_OK
+
+        await conn.set_authorizer(authorizer)
+        with pytest.raises(DatabaseError, match="not authorized"):
+            await conn.execute("INSERT INTO users (name) VALUES ('x')")
+        with pytest.raises(DatabaseError, match="not authorized"):
+            await conn.execute("DROP TABLE users")
+        assert calls
+
+        # Clearing the Python authorizer keeps hardened mode in force
+        await conn.set_authorizer(None)
+        await conn.execute("INSERT INTO users (name) VALUES ('x')")
+        with pytest.raises(DatabaseError, match="not authorized"):
+            await conn.execute("DROP TABLE users")
+
+
+def test_invalid_allowlist(test_db):
+    with pytest.raises(ValueError, match="allowlist"):
+        Connection(test_db, hardened=True, hardened_allow=["everything"])
+    with pytest.raises(ValueError, match="hardened=True"):
+        Connection(test_db, hardened_allow=["schema"])
@@ -990,3 +990,55 @@ async def test_init_hook_with_complex_schema(tmp_path):
 
     posts = await conn.fetch_all("SELECT * FROM posts")
     assert len(posts) == 1
+
+
+@pytest.mark.asyncio
+async def test_init_hook_per_connection_scope(tmp_path):
+    """Test init_hook_scope="connection" runs the hook on each new connection."""
+    db_path = tmp_path / "test.db"
+    # Create empty file - SQLite needs file to exist
+    db_path.touch()
+    calls = []
+
+    async def init_hook(conn):
+        calls.append(conn.id)
+        await conn.execute("CREATE TEMP TABLE scratch (value INTEGER)")
+        await conn.execute("INSERT INTO scratch (value) VALUES (42)")
+
+    conn = rapsqlite.Connection(
+        str(db_path), init_hook=init_hook, init_hook_scope="connection"
+    )
+    try:
+        # Temporary tables only exist on the connection that created them
+        assert await conn.fetch_all("SELECT value FROM scratch") == [[42]]
+        assert await conn.fetch_all("SELECT value FROM scratch") == [[42]]
+        assert len(calls) == 1
+    finally:
+        await conn.close()
+
+    with pytest.raises(ValueError):
+        rapsqlite.Connection(str(db_path), init_hook=init_hook, init_hook_scope="pool")
+
+
+@pytest.mark.asyncio
+async def test_connect_forwards_init_hook_scope(tmp_path):
+    """connect() passes init_hook and init_hook_scope on to Connection."""
+    db_path = tmp_path / "test.db"
+    db_path.touch()
+    calls = []
+
+    async def init_hook(conn):
+        calls.append(conn.id)
+        await conn.execute("CREATE TEMP TABLE scratch (value INTEGER)")
+
+    async with rapsqlite.connect(
+        str(db_path), init_hook=init_hook, init_hook_scope="connection"
+    ) as conn:
+        assert await conn.fetch_all("SELECT count(*) FROM scratch") == [[0]]
+        assert len(calls) == 1
+
+    with pytest.raises(ValueError):
+        rapsqlite.connect(str(db_path), init_hook=init_hook, init_hook_scope="pool")
+    # Misspelled arguments are not silently ignored
+    with pytest.raises(TypeError, match="init_hook_scop"):
+        rapsqlite.connect(str(db_path), init_hook_scop="connection")
@@ -0,0 +1,65 @@
+"""Tests for Connection.load_fixtures (declarative test data)."""
+
+import pytest
+
+from rapsqlite import Connection, IntegrityError
+
+
+async def _setup(conn):
+    await conn.execute(
+        "CREATE TABLE users (id INTEGER PRIMARY KEY AUTOINCREMENT, name TEXT, "
+        "active INTEGER DEFAULT 1)"
+    )
+    await conn.execute(
+        "CREATE TABLE posts (id INTEGER PRIMARY KEY, "
+        "user_id INTEGER NOT NULL REFERENCES users(id), title TEXT)"
+    )
+
+
+@pytest.mark.asyncio
+async def test_load_fixtures_orders_parents_first(test_db):
+    async with Connection(test_db) as conn:
+        await _setup(conn)
+        loaded = await conn.load_fixtures(
+            {
+                # Children listed first still load after their parents
+                "posts": [{"user_id": 1, "title": "hello"}],
+                "users": [{"id": 1, "name": "alice"}, {"name": "bob", "active": 0}],
+            }
+        )
+        assert list(loaded.items()) == [("users", 2), ("posts", 1)]
+        assert await conn.fetch_all("SELECT id, name, active FROM users") == [
+            [1, "alice", 1],
+            [2, "bob", 0],
+        ]
+        assert await conn.fetch_all("SELECT user_id, title FROM posts") == [
+            [1, "hello"]
+        ]
+
+
+@pytest.mark.asyncio
+async def test_load_fixtures_truncate(test_db):
+    async with Connection(test_db) as conn:
+        await _setup(conn)
+        await conn.load_fixtures({"users": [{"name": "old"}], "posts": []})
+        await conn.execute("INSERT INTO posts (user_id, title) VALUES (1, 'x')")
+
+        await conn.load_fixtures(
+            {"users": [{"name": "new"}], "posts": []}, truncate=True
+        )
+
+        # AUTOINCREMENT counters are reset too
+        assert await conn.fetch_all("SELECT id, name FROM users") == [[1, "new"]]
+        assert await conn.fetch_all("SELECT * FROM posts") == []
+
+
+@pytest.mark.asyncio
+async def test_load_fixtures_rolls_back_on_violation(test_db):
+    async with Connection(test_db) as conn:
+        await _setup(conn)
+        with pytest.raises(IntegrityError):
+            await conn.load_fixtures(
+                {"users": [{"id": 1}], "posts": [{"user_id": 2, "title": "orphan"}]}
+            )
+        assert await conn.fetch_all("SELECT * FROM users") == []
+        assert not await conn.in_transaction()
@@ -0,0 +1,65 @@
+"""Tests for debug-mode N+1 query detection (Connection(debug=True))."""
+
+import asyncio
+import warnings
+
+import pytest
+
+from rapsqlite import Connection, NPlusOneWarning
+
+
+async def _setup(conn):
+    await conn.execute("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT)")
+    await conn.execute_many(
+        "INSERT INTO users (id, name) VALUES (?, ?)",
+        [[i, f"user{i}"] for i in range(20)],
+    )
+
+
+@pytest.mark.asyncio
+async def test_n_plus_one_warns_once_with_counts(test_db):
+    async with Connection(test_db, debug=True, n_plus_one_threshold=5) as conn:
+        await _setup(conn)
+        with warnings.catch_warnings(record=True) as caught:
+            warnings.simplefilter("always")
+            for i in range(12):
+                await conn.fetch_one("SELECT name FROM users WHERE id = ?", [i])
+        found = [w for w in caught if issubclass(w.category, NPlusOneWarning)]
+        assert len(found) == 1
+        message = str(found[0].message)
+        assert "SELECT name FROM users WHERE id = ?" in message
+        assert "5 times with 5 different parameter values" in message
+
+
+@pytest.mark.asyncio
+async def test_no_warning_for_repeats_other_tasks_or_without_debug(test_db):
+    async with Connection(test_db, debug=True, n_plus_one_threshold=5) as conn:
+        await _setup(conn)
+
+        async def lookup(i):
+            await conn.fetch_all("SELECT name FROM users WHERE id = ?", [i])
+
+        with warnings.catch_warnings():
+            warnings.simplefilter("error", NPlusOneWarning)
+            # Same parameter every time
+            for _ in range(10):
+                await conn.fetch_one("SELECT name FROM users WHERE id = ?", [1])
+            # One lookup per task
+            await asyncio.gather(*(asyncio.create_task(lookup(i)) for i in range(10)))
+
+    async with Connection(test_db) as conn:
+        with warnings.catch_warnings():
+            warnings.simplefilter("error", NPlusOneWarning)
+            for i in range(20):
+                await conn.fetch_optional("SELECT name FROM users WHERE id = ?", [i])
+
+
+@pytest.mark.asyncio
+async def test_n_plus_one_warning_as_error(test_db):
+    async with Connection(test_db, debug=True, n_plus_one_threshold=3) as conn:
+        await _setup(conn)
+        with warnings.catch_warnings():
+            warnings.simplefilter("error", NPlusOneWarning)
+            with pytest.raises(NPlusOneWarning):
+                for i in range(3):
+                    await conn.execute("UPDATE users SET name = 'x' WHERE id = ?", [i])
@@ -0,0 +1,81 @@
+"""Tests for the named connection registry (register / connect("name://..."))."""
+
+import pytest
+
+import rapsqlite
+
+
+@pytest.fixture
+def registry():
+    """Give each test a clean registry."""
+    saved = dict(rapsqlite._registry)
+    rapsqlite._registry.clear()
+    try:
+        yield
+    finally:
+        rapsqlite._registry.clear()
+        rapsqlite._registry.update(saved)
+
+
+@pytest.mark.asyncio
+async def test_connect_by_name(registry, test_db):
+    rapsqlite.register("app", test_db, timeout=12.5, strict=True)
+
+    async with rapsqlite.connect("name://app") as db:
+        assert db.timeout == 12.5
+        assert db.strict is True
+        await db.execute("CREATE TABLE t (id INTEGER)")
+        await db.execute("INSERT INTO t VALUES (1)")
+
+    # Same file, explicit arguments override registered options
+    async with rapsqlite.connect("name://app", timeout=2.0) as db:
+        assert db.timeout == 2.0
+        assert db.strict is True
+        assert await db.fetch_all("SELECT id FROM t") == [[1]]
+
+
+@pytest.mark.asyncio
+async def test_explicit_default_overrides_registered(registry, test_db):
+    rapsqlite.register("app", test_db, timeout=12.5, strict=True)
+
+    # Passing the default value still counts as passing it
+    async with rapsqlite.connect("name://app", timeout=5.0, strict=False) as db:
+        assert db.timeout == 5.0
+        assert db.strict is False
+
+
+@pytest.mark.asyncio
+async def test_registered_read_pool(registry, test_db):
+    rapsqlite.register("split", test_db, read_pool_size=2, write_pool_size=3)
+
+    async with rapsqlite.connect("name://split") as db:
+        assert db.pool_size == 3
+    async with rapsqlite.ConnectionSpec(
+        test_db, {"read_pool_size": 2, "write_pool_size": 3}
+    ).connect() as db:
+        assert db.pool_size == 3
+
+
+@pytest.mark.asyncio
+async def test_registered_pragmas_apply(registry, test_db):
+    rapsqlite.register("wal", test_db, pragmas={"journal_mode": "WAL"})
+
+    async with rapsqlite.connect("name://wal") as db:
+        row = await db.fetch_one("PRAGMA journal_mode")
+        assert row[0].lower() == "wal"
+
+
+def test_register_validation(registry, test_db):
+    with pytest.raises(ValueError):
+        rapsqlite.register("", test_db)
+    with pytest.raises(ValueError):
+        rapsqlite.register("a://b", test_db)
+    with pytest.raises(TypeError, match="pool_size"):
+        rapsqlite.register("app", test_db, pool_size=4)
+
+    rapsqlite.register("app", test_db)
+    rapsqlite.unregister("app")
+    with pytest.raises(ValueError, match="'app'"):
+        rapsqlite.unregister("app")
+    with pytest.raises(ValueError, match="No connection registered"):
+        rapsqlite.connect("name://app")
@@ -0,0 +1,66 @@
+"""Tests for the Rust-native SQL functions (Connection(native_functions=True))."""
+
+import hashlib
+import re
+import uuid
+
+import pytest
+
+from rapsqlite import Connection, DatabaseError, connect
+
+
+@pytest.mark.asyncio
+async def test_native_functions(test_db):
+    async with connect(test_db, native_functions=True) as conn:
+        await conn.execute("CREATE TABLE t (s TEXT, b BLOB)")
+        await conn.execute("INSERT INTO t VALUES (?, ?)", ["héllo", b"\x00\xff"])
+        row = await conn.fetch_one("SELECT sha256(s), md5(s), sha256(b), md5(b) FROM t")
+        assert row == [
+            hashlib.sha256("héllo".encode()).hexdigest(),
+            hashlib.md5("héllo".encode()).hexdigest(),
+            hashlib.sha256(b"\x00\xff").hexdigest(),
+            hashlib.md5(b"\x00\xff").hexdigest(),
+        ]
+        assert await conn.fetch_one("SELECT md5(42), sha256(NULL)") == [
+            hashlib.md5(b"42").hexdigest(),
+            None,
+        ]
+
+        (value,) = await conn.fetch_one("SELECT uuid4()")
+        assert uuid.UUID(value).version == 4
+        rows = await conn.fetch_all(
+            "WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 100) "
+            "SELECT uuid4() FROM n"
+        )
+        assert len({row[0] for row in rows}) == 100
+
+        assert await conn.fetch_one(
+            r"SELECT regexp_replace('2024-02-29', '(\d+)-(\d+)-(\d+)', '$3/$2/$1')"
+        ) == ["29/02/2024"]
+        assert await conn.fetch_one("SELECT regexp_replace(NULL, 'a', 'b')") == [None]
+        with pytest.raises(DatabaseError, match="invalid pattern"):
+            await conn.fetch_one("SELECT regexp_replace('a', '(', 'b')")
+
+
+@pytest.mark.asyncio
+async def test_native_functions_with_callbacks_and_transactions(test_db):
+    async with Connection(test_db, native_functions=True) as conn:
+        async with conn.transaction():
+            (value,) = await conn.fetch_one("SELECT uuid4()")
+            assert re.fullmatch(
+                r"[0-9a-f]{8}-[0-9a-f]{4}-4[0-9a-f]{3}-[89ab][0-9a-f]{3}-[0-9a-f]{12}",
+                value,
+            )
+        # Python UDFs route statements to the callback connection, which has them too
+        await conn.create_function("double", 1, lambda x: x * 2)
+        assert await conn.fetch_one("SELECT double(2), md5('')") == [
+            4,
+            hashlib.md5(b"").hexdigest(),
+        ]
+
+
+@pytest.mark.asyncio
+async def test_native_functions_off_by_default(test_db):
+    async with Connection(test_db) as conn:
+        with pytest.raises(DatabaseError, match="no such function"):
+            await conn.fetch_one("SELECT uuid4()")
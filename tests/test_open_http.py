@@ -0,0 +1,135 @@
+"""Tests for rapsqlite.open_http(), the read-through cache of remote databases."""
+
+import hashlib
+import os
+import sqlite3
+import threading
+from http.server import BaseHTTPRequestHandler, ThreadingHTTPServer
+
+import pytest
+
+from rapsqlite import DatabaseError, OperationalError, fetch_cached, open_http
+
+
+class FileServer(BaseHTTPRequestHandler):
+    """Serves FileServer.files by path, with ETags and If-None-Match."""
+
+    files = {}
+    requests = []
+
+    def log_message(self, *args):
+        pass
+
+    def do_GET(self):
+        body = self.files.get(self.path)
+        self.requests.append((self.path, self.headers.get("If-None-Match")))
+        if body is None:
+            self.send_response(404)
+            self.send_header("Content-Length", "0")
+            self.end_headers()
+            return
+        etag = '"%s"' % hashlib.sha256(body).hexdigest()[:16]
+        if self.headers.get("If-None-Match") == etag:
+            self.send_response(304)
+            self.end_headers()
+            return
+        self.send_response(200)
+        self.send_header("ETag", etag)
+        self.send_header("Content-Length", str(len(body)))
+        self.end_headers()
+        self.wfile.write(body)
+
+
+def _serve():
+    httpd = ThreadingHTTPServer(("127.0.0.1", 0), FileServer)
+    threading.Thread(target=httpd.serve_forever, daemon=True).start()
+    return httpd, "http://127.0.0.1:%d" % httpd.server_port
+
+
+def _stop(httpd):
+    httpd.shutdown()
+    httpd.server_close()
+
+
+@pytest.fixture
+def server():
+    FileServer.files, FileServer.requests = {}, []
+    httpd, url = _serve()
+    try:
+        yield url
+    finally:
+        _stop(httpd)
+
+
+def _database(tmp_path, name, rows):
+    path = str(tmp_path / name)
+    with sqlite3.connect(path) as db:
+        db.execute("CREATE TABLE cities (name TEXT)")
+        db.executemany("INSERT INTO cities VALUES (?)", [[r] for r in rows])
+    with open(path, "rb") as f:
+        return f.read()
+
+
+@pytest.mark.asyncio
+async def test_open_http_downloads_and_revalidates(server, tmp_path):
+    cache = str(tmp_path / "cache")
+    FileServer.files["/cities.db"] = _database(tmp_path, "v1.db", ["Oslo", "Lima"])
+
+    conn = await open_http(server + "/cities.db", cache)
+    async with conn:
+        assert await conn.fetch_all("SELECT name FROM cities ORDER BY name") == [
+            ["Lima"],
+            ["Oslo"],
+        ]
+        with pytest.raises(DatabaseError, match="readonly"):
+            await conn.execute("INSERT INTO cities VALUES ('Rome')")
+    assert FileServer.requests == [("/cities.db", None)]
+
+    # Unchanged: a conditional GET, answered with 304
+    first = await fetch_cached(server + "/cities.db", cache)
+    assert FileServer.requests[-1][1] is not None
+    assert await fetch_cached(server + "/cities.db", cache) == first
+
+    # Changed: downloaded again under a new name, the old version removed
+    FileServer.files["/cities.db"] = _database(tmp_path, "v2.db", ["Rome"])
+    second = await fetch_cached(server + "/cities.db", cache)
+    assert second != first
+    assert not os.path.exists(first)
+    conn = await open_http(server + "/cities.db", cache)
+    async with conn:
+        assert await conn.fetch_all("SELECT name FROM cities") == [["Rome"]]
+
+
+@pytest.mark.asyncio
+async def test_open_http_max_age_and_offline(server, tmp_path):
+    cache = str(tmp_path / "cache")
+    url = server + "/cities.db"
+    FileServer.files["/cities.db"] = _database(tmp_path, "v1.db", ["Oslo"])
+    path = await fetch_cached(url, cache, max_age=60)
+    assert await fetch_cached(url, cache, max_age=60) == path
+    assert len(FileServer.requests) == 1
+
+    # An unreachable server falls back to the cached copy
+    httpd, other = _serve()
+    cached = await fetch_cached(other + "/cities.db", cache)
+    _stop(httpd)
+    assert await fetch_cached(other + "/cities.db", cache) == cached
+    with pytest.raises(OperationalError, match="Download of"):
+        await fetch_cached(other + "/cities.db", cache + "-empty")
+
+    FileServer.files.clear()
+    with pytest.raises(OperationalError, match="404"):
+        await fetch_cached(url, cache + "-empty")
+
+
+@pytest.mark.asyncio
+async def test_open_http_errors(server, tmp_path):
+    cache = str(tmp_path / "cache")
+    FileServer.files["/page.html"] = b"<html>not a database</html>"
+    with pytest.raises(OperationalError, match="not an SQLite database"):
+        await fetch_cached(server + "/page.html", cache)
+    assert [f for f in os.listdir(cache) if not f.endswith(".meta")] == []
+    with pytest.raises(OperationalError, match="http://, https:// or s3://"):
+        await fetch_cached("ftp://example.com/db", cache)
+    with pytest.raises(ValueError):
+        await fetch_cached(server + "/page.html", cache, max_age=-1)
@@ -510,3 +510,65 @@ async def test_pool_config_mixed_operations_under_load(test_db):
 
         rows = await db.fetch_all("SELECT COUNT(*) FROM t")
         assert rows[0][0] >= 13  # Original 3 + at least 10 new
+
+
+@pytest.mark.asyncio
+async def test_auto_analyze_collects_missing_statistics(test_db):
+    """auto_analyze=True analyzes tables without statistics when the pool opens."""
+    async with connect(test_db) as db:
+        await db.execute("CREATE TABLE t (id INTEGER PRIMARY KEY, k TEXT)")
+        await db.execute("CREATE INDEX idx_t_k ON t (k)")
+        for i in range(50):
+            await db.execute("INSERT INTO t (k) VALUES (?)", [f"k{i % 5}"])
+        tables = await db.fetch_all(
+            "SELECT name FROM sqlite_master WHERE name = 'sqlite_stat1'"
+        )
+        assert tables == []
+
+    async with connect(test_db, auto_analyze=True) as db:
+        stats = await db.fetch_all("SELECT tbl, idx FROM sqlite_stat1")
+        assert ["t", "idx_t_k"] in [list(row) for row in stats]
+        limit = await db.fetch_one("PRAGMA analysis_limit")
+        assert limit[0] == 400
+
+
+@pytest.mark.asyncio
+async def test_read_pool_runs_reads_on_read_only_connections(test_db):
+    """read_pool_size sends queries that only read to a second, read-only pool."""
+    connected = []
+
+    async def on_connect(conn):
+        connected.append(conn.id)
+
+    async with connect(
+        test_db, pragmas={"journal_mode": "WAL"}, read_pool_size=2
+    ) as db:
+        db.set_event_hook("connect", on_connect)
+        assert db.pool_size == 1
+        await db.execute("CREATE TABLE t (v)")
+        # A read-only connection would refuse the INSERT
+        assert await db.fetch_all("INSERT INTO t VALUES (1) RETURNING v") == [[1]]
+        assert await db.fetch_all("SELECT v FROM t") == [[1]]
+        assert await db.fetch_one("SELECT count(*) FROM t") == [1]
+        # The writer and the readers
+        assert len(set(connected)) > 1
+
+        # Inside a transaction, reads see its uncommitted writes
+        async with db.transaction():
+            await db.execute("INSERT INTO t VALUES (2)")
+            assert await db.fetch_one("SELECT count(*) FROM t") == [2]
+
+        with pytest.raises(rapsqlite.DatabaseError, match="no such table"):
+            await db.fetch_all("SELECT * FROM missing")
+
+
+@pytest.mark.asyncio
+async def test_read_pool_size_validation(test_db):
+    with pytest.raises(ValueError, match="requires read_pool_size"):
+        connect(test_db, write_pool_size=2)
+    with pytest.raises(ValueError, match="must be >= 1"):
+        connect(test_db, read_pool_size=0)
+    with pytest.raises(ValueError, match="not supported for in-memory"):
+        connect(":memory:", read_pool_size=2)
+    async with connect(test_db, read_pool_size=2, write_pool_size=3) as db:
+        assert db.pool_size == 3
@@ -1,5 +1,8 @@
 """Tests for pool exhaustion scenarios and error handling."""
 
+import asyncio
+import time
+
 import pytest
 import rapsqlite
 
@@ -143,3 +146,79 @@ async def test_pool_exhaustion_with_callback_connection(test_db):
 
         # Operations should work again
         await db.execute("INSERT INTO t DEFAULT VALUES")
+
+
+@pytest.mark.asyncio
+async def test_active_statements(test_db):
+    """active_statements() lists the statements running on pooled connections."""
+    async with rapsqlite.connect(test_db) as db:
+        await db.execute("CREATE TABLE t (id INTEGER PRIMARY KEY)")
+        assert db.active_statements() == []
+
+        # A function sees the statement calling it
+        seen = []
+
+        def peek():
+            seen.extend(db.active_statements())
+            return 0
+
+        await db.create_function("peek", 0, peek)
+        await db.fetch_all("SELECT peek() FROM (SELECT 1)")
+        assert [s["sql"] for s in seen] == ["SELECT peek() FROM (SELECT 1)"]
+        assert seen[0]["connection"] > 0
+        assert seen[0]["elapsed"] >= 0
+        assert abs(seen[0]["started"] - time.time()) < 60
+
+        # A trace callback replaces SQLite's trace hook; statements are still listed
+        traced = []
+        await db.set_trace_callback(lambda sql: traced.append(sql))
+        seen.clear()
+        await db.fetch_all("SELECT peek()")
+        await db.set_trace_callback(None)
+        assert traced == ["SELECT peek()"]
+        assert [s["sql"] for s in seen] == ["SELECT peek()"]
+
+        seen.clear()
+        await db.fetch_all("SELECT peek() + 1")
+        assert [s["sql"] for s in seen] == ["SELECT peek() + 1"]
+        assert db.active_statements() == []
+
+
+@pytest.mark.asyncio
+async def test_pool_timeout_reports_queue(test_db):
+    """PoolTimeout carries the queue depth and wait; max_queue turns tasks away."""
+    async with rapsqlite.connect(test_db, max_queue=1) as db:
+        db.pool_size = 1
+        db.connection_timeout = 1
+        assert db.max_queue == 1
+
+        # A stream holds the only connection until it is closed
+        counter = (
+            "WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n) "
+            "SELECT i FROM n"
+        )
+        rows = db.stream(counter)
+        assert await rows.__anext__() == [1]
+
+        waiter = asyncio.ensure_future(db.fetch_all("SELECT 1"))
+        await asyncio.sleep(0.2)
+        assert db.queue_stats()["waiting"] == 1
+        with pytest.raises(rapsqlite.PoolTimeout) as rejected:
+            await db.fetch_all("SELECT 2")
+        assert rejected.value.queue_depth == 1
+        assert rejected.value.wait_time == 0.0
+        assert "max_queue" in str(rejected.value)
+
+        with pytest.raises(rapsqlite.OperationalError) as timed_out:
+            await waiter
+        assert isinstance(timed_out.value, rapsqlite.PoolTimeout)
+        assert timed_out.value.queue_depth == 1
+        assert timed_out.value.wait_time >= 0.9
+
+        await rows.aclose()
+        assert await db.fetch_all("SELECT 3") == [[3]]
+        stats = db.queue_stats()
+        assert stats["waiting"] == 0
+        assert (stats["timed_out"], stats["rejected"]) == (1, 1)
+        assert stats["acquired"] >= 2
+        assert stats["max_wait"] >= 0
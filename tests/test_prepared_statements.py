@@ -10,7 +10,7 @@ import os
 import sys
 import time
 
-from rapsqlite import connect
+from rapsqlite import DatabaseError, connect
 
 
 def cleanup_db(test_db: str) -> None:
@@ -300,3 +300,46 @@ async def test_repeated_vs_unique_queries_performance():
             print("  Both benefit from sqlx's prepared statement caching")
     finally:
         cleanup_db(test_db)
+
+
+@pytest.mark.asyncio
+async def test_preheat_prepares_without_executing():
+    """preheat() prepares statements on every pooled connection without running them."""
+    with tempfile.NamedTemporaryFile(suffix=".db", delete=False) as f:
+        test_db = f.name
+
+    try:
+        async with connect(test_db) as conn:
+            conn.pool_size = 3
+            await conn.execute("CREATE TABLE test (id INTEGER PRIMARY KEY, value TEXT)")
+            workload = [
+                "INSERT INTO test (value) VALUES (:value)",
+                "SELECT * FROM test WHERE value = ?",
+            ]
+            assert await conn.preheat(workload) == 3
+            assert await conn.fetch_all("SELECT * FROM test") == []
+
+            await conn.execute(workload[0], {"value": "a"})
+            rows = await conn.fetch_all(workload[1], ["a"])
+            assert len(rows) == 1
+
+            # The transaction's connection is prepared too
+            async with conn.transaction():
+                assert await conn.preheat(workload) == 3
+    finally:
+        cleanup_db(test_db)
+
+
+@pytest.mark.asyncio
+async def test_preheat_invalid_statement_raises():
+    """preheat() raises for a statement that does not compile."""
+    with tempfile.NamedTemporaryFile(suffix=".db", delete=False) as f:
+        test_db = f.name
+
+    try:
+        async with connect(test_db) as conn:
+            with pytest.raises(DatabaseError, match="no such table"):
+                await conn.preheat(["SELECT * FROM missing"])
+            assert await conn.preheat([]) == 1
+    finally:
+        cleanup_db(test_db)
@@ -0,0 +1,114 @@
+"""Tests for query plan helpers (explain_query_plan / assert_index_used / profile)."""
+
+import pytest
+
+import rapsqlite
+
+
+@pytest.mark.asyncio
+async def test_explain_query_plan(test_db):
+    async with rapsqlite.connect(test_db) as db:
+        await db.execute("CREATE TABLE users (id INTEGER PRIMARY KEY, email TEXT)")
+        plan = await db.explain_query_plan("SELECT * FROM users WHERE email = ?", ["a"])
+        assert plan == ["SCAN users"]
+
+
+@pytest.mark.asyncio
+async def test_assert_index_used(test_db):
+    async with rapsqlite.connect(test_db) as db:
+        await db.execute("CREATE TABLE users (id INTEGER PRIMARY KEY, email TEXT)")
+        query = "SELECT id FROM users WHERE email = ?"
+
+        with pytest.raises(AssertionError, match="full table scan"):
+            await db.assert_index_used(query, ["a@example.com"])
+
+        await db.execute("CREATE INDEX idx_users_email ON users(email)")
+        plan = await db.assert_index_used(
+            query, ["a@example.com"], index_name="idx_users_email"
+        )
+        assert any("idx_users_email" in detail for detail in plan)
+
+        # Rowid lookups and SELECTs without FROM are not full scans
+        await db.assert_index_used("SELECT * FROM users WHERE id = ?", [1])
+        await db.assert_index_used("SELECT 1")
+
+        with pytest.raises(AssertionError, match="does not use index 'idx_other'"):
+            await db.assert_index_used(query, ["a"], index_name="idx_other")
+
+        # The query itself is never executed
+        await db.assert_index_used("DELETE FROM users WHERE email = 'x'")
+        await db.execute("INSERT INTO users (email) VALUES ('x')")
+        await db.assert_index_used("DELETE FROM users WHERE email = 'x'")
+        assert await db.fetch_all("SELECT email FROM users") == [["x"]]
+
+
+@pytest.mark.asyncio
+async def test_profile(test_db):
+    async with rapsqlite.connect(test_db) as db:
+        await db.execute("CREATE TABLE t (id INTEGER PRIMARY KEY, v INTEGER)")
+        await db.execute_many(
+            "INSERT INTO t (v) VALUES (?)", [[i % 10] for i in range(100)]
+        )
+
+        profile = await db.profile("SELECT * FROM t WHERE v = ?", [3])
+        assert profile["rows"] == 10
+        assert profile["fullscan_steps"] == 99
+        assert profile["elapsed"] >= 0
+
+        # Plan nodes line up with explain_query_plan()
+        details = [node["detail"] for node in profile["plan"]]
+        assert details == await db.explain_query_plan("SELECT * FROM t WHERE v = ?")
+        (scan,) = profile["plan"]
+        assert scan["name"] == "t"
+        assert (scan["loops"], scan["visits"]) == (1, 100)
+
+        # One entry per EXPLAIN row, in address order
+        opcodes = profile["opcodes"]
+        assert [op["addr"] for op in opcodes] == list(range(len(opcodes)))
+        executions = {op["opcode"]: op["executions"] for op in opcodes}
+        assert executions["Next"] == 100
+        assert executions["ResultRow"] == 10
+
+        await db.execute("CREATE INDEX idx_t_v ON t(v)")
+        profile = await db.profile("SELECT * FROM t WHERE v = ?", [3])
+        assert profile["fullscan_steps"] == 0
+        (search,) = profile["plan"]
+        assert search["name"] == "idx_t_v"
+        assert search["visits"] == 10
+
+
+@pytest.mark.asyncio
+async def test_profile_runs_writes(test_db):
+    async with rapsqlite.connect(test_db) as db:
+        await db.execute("CREATE TABLE t (x)")
+        profile = await db.profile("INSERT INTO t VALUES (?)", [1])
+        assert profile["rows"] == 0
+        assert await db.fetch_all("SELECT x FROM t") == [[1]]
+
+        with pytest.raises(rapsqlite.DatabaseError, match="no such table"):
+            await db.profile("SELECT * FROM missing")
+
+
+@pytest.mark.asyncio
+async def test_is_readonly_query(test_db):
+    async with rapsqlite.connect(test_db) as db:
+        await db.execute("CREATE TABLE t (x)")
+        assert await db.is_readonly_query("SELECT * FROM t WHERE x = ?")
+        assert await db.is_readonly_query("EXPLAIN DELETE FROM t")
+        assert not await db.is_readonly_query("INSERT INTO t VALUES (1) RETURNING x")
+        assert not await db.is_readonly_query("SELECT 1; DELETE FROM t")
+        assert await db.fetch_all("SELECT x FROM t") == []
+
+        with pytest.raises(rapsqlite.DatabaseError, match="no such table"):
+            await db.is_readonly_query("SELECT * FROM missing")
+
+
+@pytest.mark.asyncio
+async def test_profile_not_supported_error_is_database_error(test_db):
+    # Raised by profile() in builds without the scan status compile options
+    assert issubclass(rapsqlite.NotSupportedError, rapsqlite.DatabaseError)
+    async with rapsqlite.connect(test_db) as db:
+        try:
+            await db.profile("SELECT 1")
+        except rapsqlite.NotSupportedError as e:
+            assert "LIBSQLITE3_FLAGS" in str(e)
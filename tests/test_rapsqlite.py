@@ -7,6 +7,7 @@ import sys
 
 from rapsqlite import (
     Connection,
+    ProgrammingError,
     connect,
 )
 
@@ -364,6 +365,35 @@ async def test_fetch_optional():
         cleanup_db(test_db)
 
 
+@pytest.mark.asyncio
+async def test_fetch_first_and_multiple_rows():
+    """fetch_one/fetch_optional reject several rows; fetch_first takes the first."""
+    with tempfile.NamedTemporaryFile(suffix=".db", delete=False) as f:
+        test_db = f.name
+
+    try:
+        async with Connection(test_db) as conn:
+            await conn.execute("CREATE TABLE test (id INTEGER PRIMARY KEY, value INTEGER)")
+            assert await conn.fetch_first("SELECT value FROM test") is None
+            await conn.execute_many(
+                "INSERT INTO test (value) VALUES (?)", [[1], [2], [3]]
+            )
+
+            for method in (conn.fetch_one, conn.fetch_optional):
+                with pytest.raises(ProgrammingError, match="fetch_first"):
+                    await method("SELECT value FROM test")
+            assert await conn.fetch_first("SELECT value FROM test ORDER BY id") == [1]
+            assert await conn.fetch_first(
+                "SELECT value FROM test WHERE value > ? ORDER BY id", [1]
+            ) == [2]
+            async with conn.transaction():
+                with pytest.raises(ProgrammingError):
+                    await conn.fetch_one("SELECT value FROM test")
+                assert await conn.fetch_first("SELECT max(value) FROM test") == [3]
+    finally:
+        cleanup_db(test_db)
+
+
 @pytest.mark.asyncio
 async def test_last_insert_rowid():
     """Test last_insert_rowid method."""
@@ -400,6 +430,212 @@ async def test_changes():
         cleanup_db(test_db)
 
 
+
+@pytest.mark.asyncio
+async def test_execute_result_attributes():
+    """Test the cursor returned by execute carries rowcount and lastrowid."""
+    with tempfile.NamedTemporaryFile(suffix=".db", delete=False) as f:
+        test_db = f.name
+
+    try:
+        async with Connection(test_db) as conn:
+            await conn.execute(
+                "CREATE TABLE test (id INTEGER PRIMARY KEY, value INTEGER)"
+            )
+            result = await conn.execute(
+                "INSERT INTO test (value) VALUES (?), (?)", [1, 2]
+            )
+            assert result.rows_affected == 2
+            assert result.rowcount == 2
+            assert result.lastrowid == 2
+
+            result = await conn.execute("UPDATE test SET value = 0 WHERE id = 1")
+            assert (result.rows_affected, result.lastrowid) == (1, 2)
+
+            async with conn.execute("DELETE FROM test") as cursor:
+                assert cursor.rowcount == 2
+
+            result = await conn.execute("SELECT * FROM test")
+            assert result.rowcount == -1
+            assert result.lastrowid is None
+            assert await result.fetchall() == []
+    finally:
+        cleanup_db(test_db)
+
+
+@pytest.mark.asyncio
+async def test_count():
+    """Test count returns a query's row count without fetching rows."""
+    with tempfile.NamedTemporaryFile(suffix=".db", delete=False) as f:
+        test_db = f.name
+
+    try:
+        async with Connection(test_db) as conn:
+            await conn.execute("CREATE TABLE test (id INTEGER PRIMARY KEY, tag TEXT)")
+            await conn.execute_many(
+                "INSERT INTO test (tag) VALUES (?)",
+                [["a"], ["b"], ["a"], ["a"], ["ORDER BY"]],
+            )
+
+            assert await conn.count("SELECT * FROM test") == 5
+            assert await conn.count("SELECT * FROM test WHERE tag = ?", ["a"]) == 3
+            assert (
+                await conn.count(
+                    "SELECT * FROM test WHERE tag = :tag ORDER BY id DESC;",
+                    {"tag": "a"},
+                )
+                == 3
+            )
+            assert await conn.count("SELECT * FROM test ORDER BY id LIMIT 2") == 2
+            assert await conn.count("SELECT DISTINCT tag FROM test") == 3
+            assert (
+                await conn.count("SELECT * FROM test WHERE tag != 'ORDER BY' -- all")
+                == 4
+            )
+            # ORDER BY holding a parameter is kept so parameter positions line up
+            assert (
+                await conn.count(
+                    "SELECT * FROM test WHERE id > ? ORDER BY tag = ?", [1, "a"]
+                )
+                == 4
+            )
+    finally:
+        cleanup_db(test_db)
+
+
+@pytest.mark.asyncio
+async def test_execute_raw():
+    """Test execute_raw runs unprepared multi-statement SQL and returns changes."""
+    from rapsqlite import IntegrityError
+
+    with tempfile.NamedTemporaryFile(suffix=".db", delete=False) as f:
+        test_db = f.name
+
+    try:
+        async with Connection(test_db) as conn:
+            changed = await conn.execute_raw(
+                "CREATE TABLE test (id INTEGER PRIMARY KEY, value INTEGER UNIQUE);"
+                "INSERT INTO test (value) VALUES (1);"
+                "INSERT INTO test (value) VALUES (2), (3);"
+            )
+            assert changed == 3
+            assert await conn.changes() == 3
+            assert await conn.last_insert_rowid() == 3
+            assert await conn.execute_raw("UPDATE test SET value = value + 10") == 3
+
+            # Inside a transaction the transaction connection is used
+            await conn.begin()
+            assert await conn.execute_raw("DELETE FROM test WHERE id = 1") == 1
+            await conn.rollback()
+            rows = await conn.fetch_all("SELECT value FROM test ORDER BY id")
+            assert rows == [[11], [12], [13]]
+
+            with pytest.raises(IntegrityError):
+                await conn.execute_raw("INSERT INTO test (value) VALUES (11)")
+            with pytest.raises(Exception):
+                await conn.execute_raw("INVALID SQL STATEMENT")
+    finally:
+        cleanup_db(test_db)
+
+
+@pytest.mark.asyncio
+async def test_executescript():
+    """Test executescript splits like sqlite3_exec and honors transactions."""
+    from rapsqlite import IntegrityError, ProgrammingError
+
+    with tempfile.NamedTemporaryFile(suffix=".db", delete=False) as f:
+        test_db = f.name
+
+    try:
+        async with Connection(test_db) as conn:
+            await conn.executescript(
+                """
+                CREATE TABLE test (id INTEGER PRIMARY KEY, value TEXT UNIQUE);
+                CREATE TABLE log (value TEXT);
+                CREATE TRIGGER test_log AFTER INSERT ON test BEGIN
+                    INSERT INTO log VALUES (new.value);
+                END;
+                INSERT INTO test (value) VALUES ('a;b');
+                """
+            )
+            assert await conn.last_insert_rowid() == 1
+            assert await conn.fetch_all("SELECT value FROM log") == [["a;b"]]
+
+            # A script's own transaction commits as one unit
+            await conn.executescript(
+                "BEGIN; INSERT INTO test (value) VALUES ('c'); COMMIT;"
+            )
+            # ...and is rolled back when it fails or is left open
+            with pytest.raises(IntegrityError):
+                await conn.executescript(
+                    "BEGIN; INSERT INTO test (value) VALUES ('d');"
+                    "INSERT INTO test (value) VALUES ('c'); COMMIT;"
+                )
+            with pytest.raises(ProgrammingError):
+                await conn.executescript("BEGIN; INSERT INTO test (value) VALUES ('e');")
+            rows = await conn.fetch_all("SELECT value FROM test ORDER BY id")
+            assert rows == [["a;b"], ["c"]]
+            assert not await conn.in_transaction()
+
+            # Inside begin() the script joins the transaction
+            await conn.begin()
+            cursor = conn.cursor()
+            await cursor.executescript("DELETE FROM log; DELETE FROM test;")
+            assert await conn.fetch_all("SELECT * FROM test") == []
+            await conn.rollback()
+            assert len(await conn.fetch_all("SELECT * FROM test")) == 2
+    finally:
+        cleanup_db(test_db)
+
+
+@pytest.mark.asyncio
+async def test_table_checksum():
+    """Test table_checksum ignores row order but detects data changes."""
+    from rapsqlite import DatabaseError
+
+    paths = []
+    for _ in range(2):
+        with tempfile.NamedTemporaryFile(suffix=".db", delete=False) as f:
+            paths.append(f.name)
+
+    try:
+        async with Connection(paths[0]) as a, Connection(paths[1]) as b:
+            rows = [(1, "ann", 1.5, b"x"), (2, "bob", None, b""), (3, "cy", 2.0, None)]
+            for conn, ordered in ((a, rows), (b, rows[::-1])):
+                await conn.execute(
+                    "CREATE TABLE t (id INTEGER, name TEXT, score REAL, data BLOB)"
+                )
+                await conn.execute_many("INSERT INTO t VALUES (?, ?, ?, ?)", ordered)
+
+            checksum = await a.table_checksum("t")
+            assert len(checksum) == 32
+            assert checksum == await b.table_checksum("t")
+
+            await b.execute("UPDATE t SET score = 9.5 WHERE id = 1")
+            assert await a.table_checksum("t") != await b.table_checksum("t")
+            columns = ["id", "name"]
+            assert await a.table_checksum(
+                "t", columns=columns
+            ) == await b.table_checksum("t", columns=columns)
+
+            # Storage class is part of the hash, and duplicate rows are counted
+            for conn, value in ((a, 1), (b, "1")):
+                await conn.execute("CREATE TABLE d (x)")
+                await conn.execute("INSERT INTO d VALUES (?)", [value])
+            assert await a.table_checksum("d") != await b.table_checksum("d")
+            once = await a.table_checksum("d")
+            await a.execute("INSERT INTO d VALUES (1)")
+            assert await a.table_checksum("d") != once
+
+            with pytest.raises(DatabaseError):
+                await a.table_checksum("missing")
+            with pytest.raises(ValueError):
+                await a.table_checksum("t", columns=[])
+    finally:
+        for path in paths:
+            cleanup_db(path)
+
+
 # Cursor tests
 @pytest.mark.asyncio
 async def test_cursor_execute():
@@ -493,6 +729,42 @@ async def test_cursor_fetchall():
             cleanup_db(test_db)
 
 
+@pytest.mark.asyncio
+async def test_cursor_scroll():
+    """Test cursor scroll in relative and absolute mode."""
+    with tempfile.NamedTemporaryFile(suffix=".db", delete=False) as f:
+        test_db = f.name
+
+    try:
+        async with Connection(test_db) as conn:
+            await conn.execute("CREATE TABLE test (id INTEGER PRIMARY KEY)")
+            for _ in range(5):
+                await conn.execute("INSERT INTO test DEFAULT VALUES")
+
+            cursor = conn.cursor()
+            await cursor.execute("SELECT id FROM test ORDER BY id")
+            # Scrolling before the first fetch buffers the result
+            await cursor.scroll(2)
+            assert await cursor.fetchone() == [3]
+            await cursor.scroll(-2)
+            assert await cursor.fetchone() == [2]
+            await cursor.scroll(4, mode="absolute")
+            assert await cursor.fetchall() == [[5]]
+            await cursor.scroll(0, "absolute")
+            assert await cursor.fetchmany(2) == [[1], [2]]
+
+            with pytest.raises(IndexError):
+                await cursor.scroll(5, mode="absolute")
+            with pytest.raises(IndexError):
+                await cursor.scroll(-3)
+            # A failed scroll leaves the position unchanged
+            assert await cursor.fetchone() == [3]
+            with pytest.raises(ProgrammingError):
+                await cursor.scroll(1, mode="backwards")
+    finally:
+        cleanup_db(test_db)
+
+
 # Context manager tests
 @pytest.mark.asyncio
 async def test_connection_context_manager():
@@ -0,0 +1,52 @@
+"""Tests for WAL replication events (Connection(replication_hook=...))."""
+
+import os
+
+import pytest
+
+from rapsqlite import Connection, connect
+
+
+@pytest.mark.asyncio
+async def test_replication_events(test_db):
+    events = []
+    async with connect(test_db, replication_hook=events.append) as conn:
+        assert await conn.fetch_one("PRAGMA journal_mode") == ["wal"]
+        await conn.execute("CREATE TABLE t (x BLOB)")
+        await conn.execute("INSERT INTO t VALUES (randomblob(100))")
+        written = [e for e in events if e["event"] == "frames_written"]
+        assert len(written) == 2
+        assert written[1]["frames"] > written[0]["frames"]
+        assert written[0]["generation"] == written[1]["generation"]
+        assert written[0]["wal_path"].endswith("-wal")
+        assert os.path.exists(written[0]["wal_path"])
+
+        # Enough frames for the hook to checkpoint, after which the WAL restarts
+        for _ in range(150):
+            await conn.execute("INSERT INTO t VALUES (randomblob(30000))")
+        kinds = [e["event"] for e in events]
+        start = kinds.index("checkpoint_start")
+        assert kinds[start + 1] == "checkpoint_finish"
+        finish = events[start + 1]
+        assert finish["ok"] is True
+        assert finish["checkpointed"] == finish["frames"] >= 1000
+        written = [e for e in events if e["event"] == "frames_written"]
+        assert len({e["generation"] for e in written}) > 1
+
+
+@pytest.mark.asyncio
+async def test_replication_hook_errors_do_not_fail_commits(test_db):
+    def hook(event):
+        raise RuntimeError("replica down")
+
+    async with Connection(test_db, replication_hook=hook) as conn:
+        await conn.execute("CREATE TABLE t (x INTEGER)")
+        await conn.execute("INSERT INTO t VALUES (1)")
+        assert await conn.fetch_one("SELECT x FROM t") == [1]
+
+
+def test_replication_hook_validation(test_db):
+    with pytest.raises(ValueError):
+        Connection(":memory:", replication_hook=print)
+    with pytest.raises(ValueError):
+        Connection(test_db, replication_hook=print, shared_pool=True)
@@ -0,0 +1,136 @@
+"""Tests for fetch_all result caching: Connection(cache="lru") and cached_fetch_all."""
+
+import sqlite3
+
+import pytest
+
+from rapsqlite import Connection, ValueError as RapValueError
+
+
+def test_cache_option_validation(test_db):
+    with pytest.raises((ValueError, RapValueError)):
+        Connection(test_db, cache="fifo")
+    assert Connection(test_db).cache_info() is None
+    info = Connection(test_db, cache="lru", cache_size=10).cache_info()
+    assert info == {"hits": 0, "misses": 0, "maxsize": 10, "currsize": 0}
+
+
+@pytest.mark.asyncio
+async def test_cache_hits_and_table_invalidation(test_db):
+    async with Connection(test_db, cache="lru") as conn:
+        await conn.execute("CREATE TABLE a (x)")
+        await conn.execute("CREATE TABLE b (x)")
+        await conn.execute("CREATE VIEW v AS SELECT x * 10 AS x FROM a")
+        await conn.execute("INSERT INTO a VALUES (1)")
+        await conn.execute("INSERT INTO b VALUES (2)")
+
+        assert await conn.fetch_all("SELECT x FROM v") == [[10]]
+        assert await conn.fetch_all("SELECT x FROM b WHERE x = ?", [2]) == [[2]]
+        assert await conn.fetch_all("SELECT  x  FROM v") == [[10]]
+        assert conn.cache_info()["hits"] == 1
+
+        # Writing `a` invalidates the view over it, not `b`
+        await conn.execute("INSERT INTO a VALUES (2)")
+        assert await conn.fetch_all("SELECT x FROM v") == [[10], [20]]
+        assert await conn.fetch_all("SELECT x FROM b WHERE x = ?", [2]) == [[2]]
+        info = conn.cache_info()
+        assert (info["hits"], info["misses"], info["currsize"]) == (2, 3, 2)
+
+        # Different parameters are different entries
+        assert await conn.fetch_all("SELECT x FROM b WHERE x = ?", [3]) == []
+
+        # Cached rows are converted on every hit, so row_factory applies
+        conn.row_factory = "dict"
+        assert await conn.fetch_all("SELECT x FROM v") == [{"x": 10}, {"x": 20}]
+
+        conn.cache_clear()
+        assert conn.cache_info() == {
+            "hits": 0,
+            "misses": 0,
+            "maxsize": 128,
+            "currsize": 0,
+        }
+
+
+@pytest.mark.asyncio
+async def test_cache_follows_triggers_and_foreign_keys(test_db):
+    async with Connection(
+        test_db, cache="lru", pragmas={"foreign_keys": "ON"}
+    ) as conn:
+        await conn.execute("CREATE TABLE parent (id INTEGER PRIMARY KEY)")
+        await conn.execute(
+            "CREATE TABLE child (pid REFERENCES parent(id) ON DELETE CASCADE)"
+        )
+        await conn.execute("CREATE TABLE log (msg)")
+        await conn.execute(
+            "CREATE TRIGGER child_log AFTER DELETE ON child "
+            "BEGIN INSERT INTO log VALUES ('deleted'); END"
+        )
+        await conn.execute("INSERT INTO parent VALUES (1)")
+        await conn.execute("INSERT INTO child VALUES (1)")
+
+        assert await conn.fetch_all("SELECT count(*) FROM child") == [[1]]
+        assert await conn.fetch_all("SELECT count(*) FROM log") == [[0]]
+
+        await conn.execute("DELETE FROM parent WHERE id = 1")
+        assert await conn.fetch_all("SELECT count(*) FROM child") == [[0]]
+        assert await conn.fetch_all("SELECT count(*) FROM log") == [[1]]
+
+
+@pytest.mark.asyncio
+async def test_cache_sees_external_commits(test_db):
+    async with Connection(test_db, cache="lru") as conn:
+        await conn.execute("CREATE TABLE t (x)")
+        await conn.execute("INSERT INTO t VALUES (1)")
+        assert await conn.fetch_all("SELECT x FROM t") == [[1]]
+        assert await conn.fetch_all("SELECT x FROM t") == [[1]]
+
+        other = sqlite3.connect(test_db)
+        other.execute("INSERT INTO t VALUES (2)")
+        other.commit()
+        other.close()
+
+        assert await conn.fetch_all("SELECT x FROM t") == [[1], [2]]
+
+
+@pytest.mark.asyncio
+async def test_cache_bypassed_in_transaction(test_db):
+    async with Connection(test_db, cache="lru") as conn:
+        await conn.execute("CREATE TABLE t (x)")
+        async with conn.transaction():
+            await conn.execute("INSERT INTO t VALUES (1)")
+            assert await conn.fetch_all("SELECT x FROM t") == [[1]]
+            assert await conn.fetch_all("SELECT x FROM t") == [[1]]
+        assert conn.cache_info()["misses"] == 0
+        assert await conn.fetch_all("SELECT x FROM t") == [[1]]
+
+
+@pytest.mark.asyncio
+async def test_cached_fetch_all_ttl(test_db):
+    async with Connection(test_db) as conn:
+        await conn.execute("CREATE TABLE t (x)")
+        await conn.execute("INSERT INTO t VALUES (1)")
+        assert await conn.cached_fetch_all("SELECT x FROM t", ttl=60) == [[1]]
+
+        # Not invalidated by writes: the memoized result is served until it expires
+        await conn.execute("INSERT INTO t VALUES (2)")
+        assert await conn.cached_fetch_all("SELECT x FROM t", ttl=60) == [[1]]
+        assert await conn.fetch_all("SELECT x FROM t") == [[1], [2]]
+
+        assert await conn.cached_fetch_all("SELECT x FROM t", ttl=0) == [[1], [2]]
+        assert await conn.cached_fetch_all("SELECT x FROM t WHERE x = ?", [2]) == [
+            [2]
+        ]
+        info = conn.ttl_cache_info()
+        assert (info["hits"], info["misses"]) == (1, 3)
+        assert info["hit_rate"] == 0.25
+
+        conn.ttl_cache_clear()
+        assert conn.ttl_cache_info() == {
+            "hits": 0,
+            "misses": 0,
+            "hit_rate": 0.0,
+            "currsize": 0,
+        }
+        with pytest.raises((ValueError, RapValueError)):
+            await conn.cached_fetch_all("SELECT x FROM t", ttl=-1)
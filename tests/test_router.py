@@ -0,0 +1,80 @@
+"""Tests for rapsqlite.Router (per-tenant database routing)."""
+
+import os
+import shutil
+import tempfile
+
+import pytest
+
+from rapsqlite import Router
+
+
+@pytest.fixture
+def base_dir():
+    path = tempfile.mkdtemp()
+    try:
+        yield path
+    finally:
+        shutil.rmtree(path, ignore_errors=True)
+
+
+@pytest.mark.asyncio
+async def test_tenants_get_separate_files(base_dir):
+    async with Router(base_dir) as router:
+        for tenant in ("acme", "globex"):
+            async with router.for_tenant(tenant) as conn:
+                await conn.execute("CREATE TABLE t (name TEXT)")
+                await conn.execute("INSERT INTO t VALUES (?)", [tenant])
+
+        async with router.for_tenant("acme") as conn:
+            assert await conn.fetch_all("SELECT name FROM t") == [["acme"]]
+        assert router.open_count == 2
+    assert router.open_count == 0
+    assert sorted(os.listdir(base_dir)) == ["acme.db", "globex.db"]
+
+
+@pytest.mark.asyncio
+async def test_naming_and_path_checks(base_dir):
+    router = Router(base_dir, naming=lambda t: f"shard-{t % 2}/{t}.sqlite")
+    assert router.path_for(7) == os.path.join(base_dir, "shard-1", "7.sqlite")
+    async with router.for_tenant(7) as conn:
+        await conn.execute("CREATE TABLE t (x INTEGER)")
+    assert os.path.exists(os.path.join(base_dir, "shard-1", "7.sqlite"))
+    await router.close()
+
+    router = Router(base_dir, naming="{tenant}/db.sqlite")
+    with pytest.raises(ValueError):
+        router.for_tenant("..")
+    with pytest.raises(ValueError):
+        router.for_tenant("../../etc")
+
+
+@pytest.mark.asyncio
+async def test_lru_eviction_skips_busy_connections(base_dir):
+    async with Router(base_dir, max_open=2) as router:
+        async with router.for_tenant("a") as busy:
+            for tenant in ("b", "c", "d"):
+                async with router.for_tenant(tenant) as conn:
+                    await conn.execute("CREATE TABLE t (x INTEGER)")
+            # "a" is in use, so only idle tenants were evicted
+            assert router.open_count == 2
+            await busy.execute("CREATE TABLE t (x INTEGER)")
+        assert router.open_count == 2
+
+        # Evicted tenants reopen transparently
+        async with router.for_tenant("b") as conn:
+            assert await conn.fetch_all("SELECT * FROM t") == []
+
+
+@pytest.mark.asyncio
+async def test_read_pool_options(base_dir):
+    async with Router(base_dir, read_pool_size=2, write_pool_size=3) as router:
+        async with router.for_tenant("acme") as conn:
+            assert conn.pool_size == 3
+
+
+def test_invalid_arguments(base_dir):
+    with pytest.raises(ValueError):
+        Router(base_dir, max_open=0)
+    with pytest.raises(TypeError):
+        Router(base_dir, no_such_option=True)
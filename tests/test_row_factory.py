@@ -1,5 +1,6 @@
 """Robust tests for Connection.row_factory and Cursor row_factory behavior."""
 
+import asyncio
 import os
 import sys
 import tempfile
@@ -168,6 +169,27 @@ async def test_row_factory_fetch_all_multiple_rows(test_db):
         assert rows == ["a1.0", "b2.0", "c3.0"]
 
 
+@pytest.mark.asyncio
+async def test_row_factory_fetch_all_large_result(test_db):
+    """Large results are converted in chunks; order and factories are preserved."""
+    query = (
+        "WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n "
+        "WHERE i < 25001) SELECT i, 'r' || i AS name FROM n"
+    )
+    async with connect(test_db) as db:
+        rows = await db.fetch_all(query)
+        assert len(rows) == 25001
+        assert rows[0] == [1, "r1"] and rows[10000] == [10001, "r10001"]
+        assert [r[0] for r in rows] == list(range(1, 25002))
+
+        db.row_factory = "dict"
+        rows = await db.fetch_all(query)
+        assert rows[-1] == {"i": 25001, "name": "r25001"}
+
+        db.row_factory = lambda r: r[0]
+        assert await db.fetch_all(query) == list(range(1, 25002))
+
+
 # ---- NULLs and duplicate column names ----
 
 
@@ -467,6 +489,30 @@ async def test_row_factory_rapsqlite_row_mixed_access(test_db):
         assert len(row) == 3
 
 
+@pytest.mark.asyncio
+async def test_row_factory_class_is_called_like_callable(test_db):
+    """A class other than rapsqlite.Row is called with the row list like any callable."""
+
+    class Point:
+        def __init__(self, values):
+            self.values = values
+
+    async with connect(test_db) as db:
+        await _ensure_table(db)
+        await db.execute("INSERT INTO t (a, b) VALUES ('x', 1.5)")
+        await db.execute("INSERT INTO t (a, b) VALUES ('y', 2.5)")
+
+        db.row_factory = Point
+        rows = await db.fetch_all("SELECT * FROM t ORDER BY id")
+        assert [type(r) for r in rows] == [Point, Point]
+        assert [r.values for r in rows] == [[1, "x", 1.5], [2, "y", 2.5]]
+
+        # Row results stay per-row even when many rows share one factory
+        db.row_factory = Row
+        rows = await db.fetch_all("SELECT * FROM t ORDER BY id")
+        assert [r["a"] for r in rows] == ["x", "y"]
+
+
 @pytest.mark.asyncio
 async def test_row_factory_blob(test_db):
     """BLOB columns work with dict/tuple/list factories."""
@@ -485,3 +531,76 @@ async def test_row_factory_blob(test_db):
         db.row_factory = None
         rows = await db.fetch_all("SELECT * FROM bin")
         assert rows[0][1] == b"\x00\x01\x02"
+
+
+# ---- options() overrides ----
+
+
+@pytest.mark.asyncio
+async def test_options_overrides_only_inside_block(test_db):
+    """options() applies inside the block and leaves the connection setting alone."""
+    async with connect(test_db) as db:
+        await _ensure_table(db)
+        await db.execute("INSERT INTO t (a, b) VALUES ('x', 1.5)")
+        db.row_factory = "tuple"
+
+        async with db.options(row_factory="dict") as same:
+            assert same is db
+            assert db.row_factory == "dict"
+            assert await db.fetch_one("SELECT a, b FROM t") == {"a": "x", "b": 1.5}
+            cursor = db.cursor()
+            await cursor.execute("SELECT a FROM t")
+            async with db.options(text_factory=lambda b: b.decode().upper()):
+                assert await db.fetch_all("SELECT a FROM t") == [{"a": "X"}]
+            assert await db.fetch_optional("SELECT a FROM t") == {"a": "x"}
+
+        assert db.row_factory == "tuple"
+        assert db.text_factory is None
+        assert await db.fetch_one("SELECT a, b FROM t") == ("x", 1.5)
+        # A cursor created inside the block keeps its row format
+        assert await cursor.fetchone() == {"a": "x"}
+
+        with pytest.raises(TypeError):
+            db.options(isolation_level=None)
+
+
+@pytest.mark.asyncio
+async def test_options_restored_after_exception(test_db):
+    """Leaving the block with an exception also drops the override."""
+    async with connect(test_db) as db:
+        await _ensure_table(db)
+        await db.execute("INSERT INTO t (a, b) VALUES ('x', 1.5)")
+        with pytest.raises(DatabaseError):
+            async with db.options(row_factory="dict"):
+                await db.fetch_all("SELECT * FROM missing")
+        assert db.row_factory is None
+        assert await db.fetch_one("SELECT a FROM t") == ["x"]
+
+
+@pytest.mark.asyncio
+async def test_options_are_per_task(test_db):
+    """Concurrent tasks sharing one Connection each see their own override."""
+    async with connect(test_db) as db:
+        await _ensure_table(db)
+        await db.execute("INSERT INTO t (a, b) VALUES ('x', 1.5)")
+        started = asyncio.Event()
+        release = asyncio.Event()
+
+        async def as_dict():
+            async with db.options(row_factory="dict"):
+                started.set()
+                await release.wait()
+                return await db.fetch_one("SELECT a FROM t")
+
+        async def as_tuple():
+            await started.wait()
+            async with db.options(row_factory="tuple"):
+                release.set()
+                return await db.fetch_one("SELECT a FROM t")
+
+        async def plain():
+            await started.wait()
+            return await db.fetch_one("SELECT a FROM t")
+
+        results = await asyncio.gather(as_dict(), as_tuple(), plain())
+        assert results == [{"a": "x"}, ("x",), ["x"]]
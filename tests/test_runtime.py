@@ -0,0 +1,106 @@
+"""Tests for Tokio runtime configuration (configure_runtime / dedicated_runtime)."""
+
+import asyncio
+import time
+
+import pytest
+
+import rapsqlite
+from rapsqlite import Connection, connect
+
+
+@pytest.mark.asyncio
+async def test_dedicated_runtime(test_db):
+    async with Connection(test_db, dedicated_runtime=True) as conn:
+        await conn.execute("CREATE TABLE t (x INTEGER)")
+        await conn.execute("INSERT INTO t VALUES (1)")
+        async with conn.transaction():
+            await conn.execute("INSERT INTO t VALUES (2)")
+        cursor = await conn.execute("SELECT x FROM t ORDER BY x")
+        assert await cursor.fetchall() == [[1], [2]]
+        results = await asyncio.gather(
+            *(conn.fetch_one("SELECT count(*) FROM t") for _ in range(10))
+        )
+        assert results == [[2]] * 10
+
+
+@pytest.mark.asyncio
+async def test_dedicated_runtime_awaits_python_hooks(test_db):
+    hook_calls = []
+
+    async def init_hook(conn):
+        hook_calls.append(conn)
+        await conn.execute("CREATE TABLE t (x INTEGER)")
+
+    async with Connection(
+        test_db, dedicated_runtime=True, init_hook=init_hook
+    ) as conn:
+        await conn.execute("INSERT INTO t VALUES (1)")
+        assert await conn.fetch_all("SELECT x FROM t") == [[1]]
+    assert len(hook_calls) == 1
+
+
+@pytest.mark.asyncio
+async def test_dedicated_runtime_cancellation(test_db):
+    started = asyncio.Event()
+    loop = asyncio.get_running_loop()
+
+    def slow(i):
+        loop.call_soon_threadsafe(started.set)
+        time.sleep(0.01)
+        return i
+
+    async with Connection(test_db, dedicated_runtime=True) as conn:
+        await conn.execute("CREATE TABLE t (x INTEGER)")
+        await conn.create_function("slow", 1, slow)
+        insert = asyncio.ensure_future(
+            conn.execute(
+                "WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n "
+                "WHERE i < 100) INSERT INTO t SELECT slow(i) FROM n"
+            )
+        )
+        await started.wait()
+        insert.cancel()
+        with pytest.raises(asyncio.CancelledError):
+            await insert
+
+        # The cancelled statement either committed in full or not at all, and
+        # released its write lock: another connection can write
+        async with connect(test_db, pragmas={"busy_timeout": 5000}) as other:
+            await other.execute("INSERT INTO t VALUES (0)")
+            (count,) = await other.fetch_one("SELECT count(*) FROM t")
+        assert count in (1, 101)
+
+        # Cancelling inside a transaction leaves it open for rollback()
+        await conn.begin()
+        await conn.execute("INSERT INTO t VALUES (-1)")
+        select = asyncio.ensure_future(
+            conn.fetch_all(
+                "WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n "
+                "WHERE i < 100) SELECT slow(i) FROM n"
+            )
+        )
+        started.clear()
+        await started.wait()
+        select.cancel()
+        with pytest.raises(asyncio.CancelledError):
+            await select
+        assert await conn.in_transaction()
+        await conn.rollback()
+        assert not await conn.in_transaction()
+        assert await conn.fetch_all("SELECT count(*) FROM t WHERE x = -1") == [[0]]
+
+
+@pytest.mark.asyncio
+async def test_configure_runtime_after_start():
+    async with Connection(":memory:") as conn:
+        await conn.fetch_one("SELECT 1")
+    with pytest.raises(RuntimeError, match="configure_runtime"):
+        rapsqlite.configure_runtime(worker_threads=2)
+
+
+def test_configure_runtime_validates_arguments():
+    with pytest.raises(ValueError):
+        rapsqlite.configure_runtime(worker_threads=0)
+    with pytest.raises(ValueError):
+        rapsqlite.configure_runtime(max_blocking_threads=0)
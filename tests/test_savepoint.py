@@ -0,0 +1,53 @@
+"""Tests for Connection.savepoint()."""
+
+import pytest
+
+from rapsqlite import Connection, OperationalError
+
+
+@pytest.mark.asyncio
+async def test_savepoint_rolls_back_only_its_block(test_db):
+    async with Connection(test_db) as conn:
+        await conn.execute("CREATE TABLE t (x INTEGER)")
+        async with conn.transaction():
+            await conn.execute("INSERT INTO t VALUES (1)")
+            with pytest.raises(RuntimeError):
+                async with conn.savepoint():
+                    await conn.execute("INSERT INTO t VALUES (2)")
+                    raise RuntimeError("undo")
+            async with conn.savepoint("kept") as entered:
+                assert entered is conn
+                await conn.execute("INSERT INTO t VALUES (3)")
+        assert await conn.fetch_all("SELECT x FROM t ORDER BY x") == [[1], [3]]
+
+
+@pytest.mark.asyncio
+async def test_savepoints_nest(test_db):
+    async with Connection(test_db) as conn:
+        await conn.execute("CREATE TABLE t (x INTEGER)")
+        async with conn.transaction():
+            async with conn.savepoint() as outer:
+                await conn.execute("INSERT INTO t VALUES (1)")
+                with pytest.raises(ValueError):
+                    async with conn.savepoint():
+                        await conn.execute("INSERT INTO t VALUES (2)")
+                        raise ValueError("inner")
+                await conn.execute("INSERT INTO t VALUES (3)")
+            assert outer is conn
+        assert await conn.fetch_all("SELECT x FROM t ORDER BY x") == [[1], [3]]
+
+
+@pytest.mark.asyncio
+async def test_savepoint_names(test_db):
+    async with Connection(test_db) as conn:
+        assert conn.savepoint("my sp").name == "my sp"
+        first, second = conn.savepoint(), conn.savepoint()
+        assert first.name != second.name
+
+
+@pytest.mark.asyncio
+async def test_savepoint_requires_transaction(test_db):
+    async with Connection(test_db) as conn:
+        with pytest.raises(OperationalError, match="inside a transaction"):
+            async with conn.savepoint():
+                pass
@@ -1109,6 +1109,61 @@ async def test_get_table_xinfo_vs_table_info(test_db):
             assert xinfo_col["hidden"] == 0  # Normal columns
 
 
+@pytest.mark.asyncio
+async def test_get_table_xinfo_generated_columns(test_db):
+    """get_table_xinfo reports the kind of generated columns."""
+    async with Connection(test_db) as conn:
+        await conn.execute(
+            "CREATE TABLE t (a INTEGER, "
+            "b INTEGER GENERATED ALWAYS AS (a * 2) VIRTUAL, "
+            "c TEXT GENERATED ALWAYS AS (a || 'x') STORED)"
+        )
+
+        xinfo = await conn.get_table_xinfo("t")
+        kinds = {col["name"]: (col["hidden"], col["kind"]) for col in xinfo}
+        assert kinds == {
+            "a": (0, "normal"),
+            "b": (2, "virtual"),
+            "c": (3, "stored"),
+        }
+
+
+@pytest.mark.asyncio
+async def test_get_table_info_generated_and_default_expressions(test_db):
+    """get_table_info keeps generated columns and DEFAULT expressions as written."""
+    async with Connection(test_db) as conn:
+        await conn.execute(
+            "CREATE TABLE t ("
+            "id INTEGER PRIMARY KEY, "
+            "created TEXT DEFAULT (datetime('now')), "
+            "qty INTEGER NOT NULL DEFAULT -1, "
+            "label TEXT DEFAULT 'a,b', "
+            "double_qty INTEGER GENERATED ALWAYS AS (qty * 2) VIRTUAL, "
+            '"Upper Label" TEXT AS (upper(label)) STORED)'
+        )
+
+        info = {col["name"]: col for col in await conn.get_table_info("t")}
+        assert list(info) == [
+            "id",
+            "created",
+            "qty",
+            "label",
+            "double_qty",
+            "Upper Label",
+        ]
+
+        assert info["created"]["default_expr"] == "(datetime('now'))"
+        assert info["qty"]["default_expr"] == "-1"
+        assert info["label"]["default_expr"] == "'a,b'"
+        assert info["id"]["default_expr"] is None
+
+        assert info["id"]["generated"] is None
+        assert info["double_qty"]["generated"] == "virtual"
+        assert info["double_qty"]["generated_expr"] == "qty * 2"
+        assert info["Upper Label"]["generated"] == "stored"
+        assert info["Upper Label"]["generated_expr"] == "upper(label)"
+
+
 @pytest.mark.asyncio
 async def test_get_table_xinfo_nonexistent_table(test_db):
     """Test get_table_xinfo with non-existent table."""
@@ -1484,3 +1539,191 @@ async def test_get_table_xinfo_column_order(test_db):
         assert xinfo[1]["name"] == "first"
         assert xinfo[2]["name"] == "second"
         assert xinfo[3]["name"] == "third"
+
+
+@pytest.mark.asyncio
+async def test_apply_ddl_dry_run(test_db):
+    """Test apply_ddl(dry_run=True) validates statements without running them."""
+    from rapsqlite import DatabaseError
+
+    async with Connection(test_db) as conn:
+        await conn.execute("CREATE TABLE users (id INTEGER PRIMARY KEY, email TEXT)")
+        validated = await conn.apply_ddl(
+            [
+                "CREATE TABLE posts (id INTEGER PRIMARY KEY, user_id INTEGER)",
+                "CREATE INDEX idx_users_email ON users(email)",
+            ],
+            dry_run=True,
+        )
+        assert validated == 2
+        assert await conn.get_tables() == ["users"]
+        assert await conn.get_indexes(table_name="users") == []
+
+        with pytest.raises(DatabaseError, match="Statement 1 failed"):
+            await conn.apply_ddl(
+                ["CREATE TABLE ok (id INTEGER)", "CREATE TABLE bad ("],
+                dry_run=True,
+            )
+
+
+@pytest.mark.asyncio
+async def test_apply_ddl_is_atomic(test_db):
+    """Test apply_ddl applies all statements or none of them."""
+    from rapsqlite import DatabaseError
+
+    async with Connection(test_db) as conn:
+        applied = await conn.apply_ddl(
+            [
+                "CREATE TABLE users (id INTEGER PRIMARY KEY, email TEXT)",
+                "CREATE UNIQUE INDEX idx_users_email ON users(email)",
+            ]
+        )
+        assert applied == 2
+        assert await conn.get_tables() == ["users"]
+
+        with pytest.raises(DatabaseError, match="Statement 1 failed"):
+            await conn.apply_ddl(
+                ["CREATE TABLE posts (id INTEGER)", "CREATE INDEX idx ON missing(x)"]
+            )
+        assert await conn.get_tables() == ["users"]
+
+        # Inside an explicit transaction only the batch is rolled back
+        await conn.begin()
+        await conn.execute("CREATE TABLE kept (id INTEGER)")
+        with pytest.raises(DatabaseError):
+            await conn.apply_ddl(["CREATE TABLE gone (id INTEGER)", "NOT SQL"])
+        await conn.commit()
+        assert sorted(await conn.get_tables()) == ["kept", "users"]
+
+        # transaction=False keeps the statements that ran before the failure
+        with pytest.raises(DatabaseError):
+            await conn.apply_ddl(
+                ["CREATE TABLE partial (id INTEGER)", "NOT SQL"], transaction=False
+            )
+        assert "partial" in await conn.get_tables()
+
+
+@pytest.mark.asyncio
+async def test_get_dependencies(test_db):
+    """get_dependencies links views, triggers, indexes and FKs to what they use."""
+    async with Connection(test_db) as conn:
+        await conn.execute("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT)")
+        await conn.execute(
+            "CREATE TABLE posts (id INTEGER PRIMARY KEY, "
+            "author INTEGER REFERENCES Users(id), title TEXT UNIQUE)"
+        )
+        await conn.execute("CREATE TABLE audit (msg TEXT)")
+        await conn.execute("CREATE INDEX idx_posts_author ON posts(author)")
+        await conn.execute(
+            'CREATE VIEW "user posts" AS SELECT u.name, p.title '
+            "FROM users u JOIN [posts] p ON p.author = u.id"
+        )
+        await conn.execute(
+            "CREATE VIEW recent AS SELECT * FROM \"user posts\" -- not audit\n"
+            "WHERE title <> 'users'"
+        )
+        await conn.execute(
+            "CREATE TRIGGER log_post AFTER INSERT ON posts "
+            "BEGIN INSERT INTO audit VALUES (new.title); END"
+        )
+
+        graph = await conn.get_dependencies()
+
+        assert graph == {
+            "users": {"type": "table", "depends_on": []},
+            "posts": {"type": "table", "depends_on": ["users"]},
+            "audit": {"type": "table", "depends_on": []},
+            "idx_posts_author": {"type": "index", "depends_on": ["posts"]},
+            "user posts": {"type": "view", "depends_on": ["posts", "users"]},
+            "recent": {"type": "view", "depends_on": ["user posts"]},
+            "log_post": {"type": "trigger", "depends_on": ["audit", "posts"]},
+        }
+
+
+async def _alter_table_schema(conn):
+    await conn.execute(
+        "CREATE TABLE users (\n"
+        "  id INTEGER PRIMARY KEY, -- the key\n"
+        "  mail TEXT NOT NULL,\n"
+        "  age TEXT,\n"
+        "  legacy INTEGER DEFAULT 0,\n"
+        "  CHECK (length(mail) > 3)\n"
+        ")"
+    )
+    await conn.execute(
+        "CREATE TABLE posts (id INTEGER PRIMARY KEY, user_id REFERENCES users(id))"
+    )
+    await conn.execute("CREATE TABLE audit (mail TEXT)")
+    await conn.execute("CREATE INDEX idx_users_mail ON users (mail)")
+    await conn.execute(
+        "CREATE TRIGGER log_user AFTER INSERT ON users "
+        "BEGIN INSERT INTO audit VALUES (new.mail); END"
+    )
+    await conn.execute("CREATE VIEW adults AS SELECT id FROM users WHERE age >= 18")
+    await conn.execute("INSERT INTO users (id, mail, age) VALUES (1, 'a@x.io', '42')")
+    await conn.execute("INSERT INTO posts (id, user_id) VALUES (1, 1)")
+
+
+@pytest.mark.asyncio
+async def test_alter_table_rebuilds_table(test_db):
+    """alter_table adds, drops, renames and retypes, keeping the other schema."""
+    async with Connection(test_db) as conn:
+        await _alter_table_schema(conn)
+
+        await conn.alter_table(
+            "users",
+            add=["created TEXT DEFAULT 'now'"],
+            drop=["legacy"],
+            rename={"mail": "email"},
+            retype={"age": "INTEGER"},
+        )
+
+        info = await conn.get_table_info("users")
+        assert {col["name"]: col["type"] for col in info} == {
+            "id": "INTEGER",
+            "email": "TEXT",
+            "age": "INTEGER",
+            "created": "TEXT",
+        }
+        assert await conn.fetch_all("SELECT id, email, age, created FROM users") == [
+            [1, "a@x.io", 42, "now"]
+        ]
+        # Indexes, triggers, views and constraints survive
+        indexes = await conn.get_indexes("users")
+        assert [index["name"] for index in indexes] == ["idx_users_mail"]
+        await conn.execute("INSERT INTO users (id, email) VALUES (2, 'b@x.io')")
+        assert await conn.fetch_all("SELECT mail FROM audit") == [
+            ["a@x.io"],
+            ["b@x.io"],
+        ]
+        assert await conn.fetch_all("SELECT id FROM adults") == [[1]]
+        with pytest.raises(Exception):
+            await conn.execute("INSERT INTO users (id, email) VALUES (3, 'x')")
+        # Foreign keys referencing the table still apply
+        with pytest.raises(Exception):
+            await conn.execute("INSERT INTO posts (id, user_id) VALUES (2, 99)")
+
+
+@pytest.mark.asyncio
+async def test_alter_table_rolls_back_on_error(test_db):
+    """A failing alter_table leaves the table as it was."""
+    from rapsqlite import DatabaseError, OperationalError
+
+    async with Connection(test_db) as conn:
+        await _alter_table_schema(conn)
+
+        # The index on mail can't be recreated without the column
+        with pytest.raises(DatabaseError):
+            await conn.alter_table("users", drop=["mail"], rename={"age": "years"})
+        columns = [col["name"] for col in await conn.get_table_info("users")]
+        assert columns == ["id", "mail", "age", "legacy"]
+        leftovers = await conn.fetch_all(
+            "SELECT name FROM sqlite_master WHERE name LIKE '_rapsqlite%'"
+        )
+        assert leftovers == []
+
+        with pytest.raises(OperationalError, match="no such column"):
+            await conn.alter_table("users", retype={"missing": "TEXT"})
+        async with conn.transaction():
+            with pytest.raises(OperationalError, match="inside a transaction"):
+                await conn.alter_table("users", drop=["legacy"])
@@ -0,0 +1,50 @@
+"""Tests for Connection(shared_pool=True) (process-wide pool sharing)."""
+
+import pytest
+
+from rapsqlite import Connection, connect
+
+
+@pytest.mark.asyncio
+async def test_connections_share_one_pool(test_db):
+    first = Connection(test_db, shared_pool=True)
+    second = connect(test_db, shared_pool=True)
+    first.pool_size = 1
+    assert second.pool_size == 1
+
+    # With a single shared OS connection, a TEMP table created through one
+    # Connection is visible through the other
+    await first.execute("CREATE TEMP TABLE scratch (x INTEGER)")
+    await first.execute("INSERT INTO scratch VALUES (1)")
+    assert await second.fetch_all("SELECT x FROM scratch") == [[1]]
+
+    # Closing one Connection leaves the pool open for the other
+    await first.close()
+    assert await second.fetch_all("SELECT x FROM scratch") == [[1]]
+    await second.close()
+
+
+@pytest.mark.asyncio
+async def test_pools_not_shared_by_default_or_across_options(test_db):
+    shared = Connection(test_db, shared_pool=True)
+    own = Connection(test_db)
+    other_pragmas = Connection(
+        test_db, shared_pool=True, pragmas={"foreign_keys": True}
+    )
+    shared.pool_size = 3
+    assert own.pool_size is None
+    assert other_pragmas.pool_size is None
+
+    await shared.execute("CREATE TEMP TABLE scratch (x INTEGER)")
+    for conn in (own, other_pragmas):
+        rows = await conn.fetch_all(
+            "SELECT name FROM sqlite_temp_master WHERE name = 'scratch'"
+        )
+        assert rows == []
+    for conn in (shared, own, other_pragmas):
+        await conn.close()
+
+
+def test_shared_pool_rejects_memory():
+    with pytest.raises(ValueError):
+        Connection(":memory:", shared_pool=True)
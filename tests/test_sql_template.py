@@ -0,0 +1,93 @@
+"""Tests for rapsqlite.sql() composable SQL templates."""
+
+import pytest
+
+from rapsqlite import Connection, sql
+
+HOSTILE = "'; DROP TABLE users; --"
+
+
+async def _open(path):
+    path.touch()
+    conn = Connection(str(path))
+    await conn.execute(
+        "CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT, role TEXT)"
+    )
+    await conn.execute_many(
+        "INSERT INTO users (name, role) VALUES (?, ?)",
+        [["ann", "admin"], ["bob", "user"], ["cy", "user"], [HOSTILE, "x"]],
+    )
+    return conn
+
+
+def test_render_expands_lists_and_quotes_identifiers():
+    query = sql("SELECT {cols} FROM {table} WHERE id IN :ids AND name = :name").bind(
+        cols=["id", 'odd"name'], table="users", ids=(1, 2, 3), name="ann"
+    )
+    assert query.render() == (
+        'SELECT "id", "odd""name" FROM "users" WHERE id IN (?, ?, ?) AND name = ?',
+        [1, 2, 3, "ann"],
+    )
+    # Placeholders in literals and comments are left alone
+    assert sql("SELECT ':x' -- :y").render() == ("SELECT ':x' -- :y", [])
+    assert "sql(" in repr(query)
+
+    with pytest.raises(ValueError):
+        sql("SELECT * FROM t WHERE id = ?")
+    with pytest.raises(KeyError):
+        sql("SELECT :missing").render()
+    with pytest.raises(TypeError):
+        sql("SELECT * FROM {t}").bind(t=1).render()
+
+
+def test_composition_keeps_each_fragments_parameters():
+    conditions = [
+        sql("role = :v").bind(v="user"),
+        sql("name <> :v").bind(v="cy"),
+    ]
+    where = sql(" AND ").join(conditions)
+    query = sql("SELECT name FROM users WHERE :where").bind(where=where)
+    assert query.render() == (
+        "SELECT name FROM users WHERE role = ? AND name <> ?",
+        ["user", "cy"],
+    )
+    # Unbound placeholders of a fragment are filled from the enclosing template
+    query = sql("SELECT name FROM users WHERE ") + sql("role = :role")
+    assert query.bind(role="admin").render() == (
+        "SELECT name FROM users WHERE role = ?",
+        ["admin"],
+    )
+    assert sql("SELECT 1").join([]).render() == ("", [])
+
+
+@pytest.mark.asyncio
+async def test_execute_and_fetch_templates(tmp_path):
+    conn = await _open(tmp_path / "t.db")
+    try:
+        names = sql("SELECT name FROM {table} WHERE id IN :ids ORDER BY id")
+        rows = await conn.fetch_all(names.bind(table="users", ids=[1, 3]))
+        assert rows == [["ann"], ["cy"]]
+        assert await conn.fetch_all(names.bind(table="users", ids=[])) == []
+
+        # Values never become SQL text
+        by_name = sql("SELECT id FROM users WHERE name = :n")
+        assert await conn.fetch_one(by_name.bind(n=HOSTILE)) == [4]
+
+        await conn.execute(
+            sql("UPDATE users SET role = :role WHERE name IN :names").bind(
+                role="admin", names={"bob"}
+            )
+        )
+        cursor = conn.cursor()
+        by_role = sql("SELECT name FROM users WHERE role = :role ORDER BY id")
+        await cursor.execute(by_role.bind(role="admin"))
+        assert await cursor.fetchall() == [["ann"], ["bob"]]
+
+        # Connection default parameters fill unbound placeholders
+        conn.set_default_params({"role": "user"})
+        assert await conn.fetch_all(by_role) == [["cy"]]
+
+        with pytest.raises(TypeError):
+            await conn.fetch_all(sql("SELECT 1"), [1])
+    finally:
+        await conn.close()
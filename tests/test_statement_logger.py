@@ -0,0 +1,75 @@
+"""Tests for Connection.set_statement_logger (parameter logging with redaction)."""
+
+import pytest
+
+from rapsqlite import Connection, ValueError as RapValueError
+
+
+@pytest.mark.asyncio
+async def test_logger_receives_statements(test_db):
+    entries = []
+    async with Connection(test_db) as conn:
+        conn.set_statement_logger(lambda *entry: entries.append(entry))
+        await conn.execute("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT)")
+        await conn.execute_many(
+            "INSERT INTO users (name) VALUES (?)", [["ann"], ["bob"], ["cy"]]
+        )
+        await conn.execute("UPDATE users SET name = upper(name) WHERE id > ?", [1])
+        await conn.fetch_all("SELECT * FROM users")
+        await conn.fetch_one("SELECT name FROM users WHERE id = :id", {"id": 1})
+        await conn.fetch_optional("SELECT name FROM users WHERE id = ?", [99])
+
+        conn.set_statement_logger(None)
+        await conn.fetch_all("SELECT * FROM users")
+
+    assert [(sql, params, rows) for sql, params, _, rows in entries] == [
+        ("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT)", None, 0),
+        ("INSERT INTO users (name) VALUES (?)", [["ann"], ["bob"], ["cy"]], 3),
+        ("UPDATE users SET name = upper(name) WHERE id > ?", [1], 2),
+        ("SELECT * FROM users", None, 3),
+        ("SELECT name FROM users WHERE id = ?", {"id": 1}, 1),
+        ("SELECT name FROM users WHERE id = ?", [99], 0),
+    ]
+    assert all(isinstance(entry[2], float) and entry[2] >= 0 for entry in entries)
+
+
+@pytest.mark.asyncio
+async def test_logger_redacts_by_name_and_position(test_db):
+    entries = []
+    async with Connection(test_db) as conn:
+        await conn.execute("CREATE TABLE accounts (email TEXT, password TEXT)")
+        conn.set_statement_logger(
+            lambda sql, params, duration, rows: entries.append(params),
+            redact=[":password", 1],
+        )
+        await conn.execute(
+            "INSERT INTO accounts VALUES (:email, :password)",
+            {"email": "a@example.com", "password": "hunter2"},
+        )
+        await conn.execute(
+            "INSERT INTO accounts VALUES (?, ?)", ["b@example.com", "swordfish"]
+        )
+        await conn.execute_many(
+            "INSERT INTO accounts VALUES (?, ?)", [["c@example.com", "letmein"]]
+        )
+        await conn.fetch_all("SELECT * FROM accounts WHERE password = ?", ["hunter2"])
+
+    assert entries == [
+        {"email": "a@example.com", "password": "<redacted>"},
+        ["b@example.com", "<redacted>"],
+        [["c@example.com", "<redacted>"]],
+        ["hunter2"],
+    ]
+
+
+@pytest.mark.asyncio
+async def test_logger_errors_do_not_fail_statements(test_db):
+    def broken(*entry):
+        raise RuntimeError("logger failed")
+
+    async with Connection(test_db) as conn:
+        conn.set_statement_logger(broken)
+        await conn.execute("CREATE TABLE t (x)")
+        assert await conn.fetch_all("SELECT count(*) FROM t") == [[0]]
+        with pytest.raises((ValueError, RapValueError)):
+            conn.set_statement_logger(print, redact=[1.5])
@@ -0,0 +1,99 @@
+"""Tests for Connection.stream() and fetch_stream() (bounded prefetch of query results)."""
+
+import pytest
+
+from rapsqlite import Connection, DatabaseError, ProgrammingError
+
+COUNTER = (
+    "WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n) "
+    "SELECT i FROM n"
+)
+
+
+@pytest.mark.asyncio
+async def test_stream_rows(test_db):
+    async with Connection(test_db) as conn:
+        await conn.execute("CREATE TABLE t (id INTEGER, name TEXT)")
+        await conn.execute_many(
+            "INSERT INTO t VALUES (?, ?)", [[i, f"n{i}"] for i in range(50)]
+        )
+        async with conn.stream(
+            "SELECT * FROM t WHERE id >= ? ORDER BY id", [45], prefetch=4
+        ) as rows:
+            assert [row async for row in rows] == [[i, f"n{i}"] for i in range(45, 50)]
+
+        conn.row_factory = "dict"
+        rows = conn.stream("SELECT * FROM t WHERE id = :id", {"id": 7})
+        assert [row async for row in rows] == [{"id": 7, "name": "n7"}]
+
+
+@pytest.mark.asyncio
+async def test_unbounded_query_with_small_prefetch(test_db):
+    # The query never ends; backpressure keeps it from running ahead
+    async with Connection(test_db) as conn:
+        async with conn.stream(COUNTER, prefetch=2) as rows:
+            seen = []
+            async for row in rows:
+                seen.append(row[0])
+                if len(seen) == 5:
+                    break
+        assert seen == [1, 2, 3, 4, 5]
+        # Closing the stream released its pool connection
+        assert await conn.fetch_one("SELECT 1") == [1]
+
+
+@pytest.mark.asyncio
+async def test_aclose_and_errors(test_db):
+    async with Connection(test_db) as conn:
+        rows = conn.stream(COUNTER)
+        assert await rows.__anext__() == [1]
+        await rows.aclose()
+        assert [row async for row in rows] == []
+
+        with pytest.raises(DatabaseError):
+            async for _ in conn.stream("SELECT * FROM missing_table"):
+                pass
+
+        await conn.execute("CREATE TABLE t (x INTEGER)")
+        async with conn.transaction():
+            with pytest.raises(ProgrammingError, match="transaction"):
+                async for _ in conn.stream("SELECT * FROM t"):
+                    pass
+
+        with pytest.raises(ValueError):
+            conn.stream("SELECT 1", prefetch=0)
+
+
+@pytest.mark.asyncio
+async def test_fetch_stream_chunks(test_db):
+    async with Connection(test_db) as conn:
+        await conn.execute("CREATE TABLE t (id INTEGER, name TEXT)")
+        await conn.execute_many(
+            "INSERT INTO t VALUES (?, ?)", [[i, f"n{i}"] for i in range(10)]
+        )
+        async with conn.fetch_stream(
+            "SELECT * FROM t WHERE id >= ? ORDER BY id", [3], chunk_size=3
+        ) as chunks:
+            assert [chunk async for chunk in chunks] == [
+                [[3, "n3"], [4, "n4"], [5, "n5"]],
+                [[6, "n6"], [7, "n7"], [8, "n8"]],
+                [[9, "n9"]],
+            ]
+        assert [c async for c in conn.fetch_stream("SELECT * FROM t WHERE 0")] == []
+
+        conn.row_factory = "dict"
+        chunks = conn.fetch_stream("SELECT id FROM t WHERE id < 2 ORDER BY id")
+        assert [c async for c in chunks] == [[{"id": 0}, {"id": 1}]]
+
+        # Only a chunk at a time is read from an unbounded query
+        async with conn.fetch_stream(COUNTER, chunk_size=100) as chunks:
+            first = await chunks.__anext__()
+            second = await chunks.__anext__()
+        assert [row["i"] for row in first + second] == list(range(1, 201))
+
+        async with conn.transaction():
+            with pytest.raises(ProgrammingError, match="fetch_stream"):
+                async for _ in conn.fetch_stream("SELECT * FROM t"):
+                    pass
+        with pytest.raises(ValueError):
+            conn.fetch_stream("SELECT 1", chunk_size=0)
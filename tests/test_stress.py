@@ -125,6 +125,53 @@ async def test_memory_leak_detection(test_db):
     assert growth_ratio < 2.0, f"Possible memory leak: {growth_ratio}x object growth"
 
 
+def _current_rss_bytes():
+    """Current resident set size from /proc (Linux only), or None."""
+    try:
+        with open("/proc/self/statm") as f:
+            resident_pages = int(f.read().split()[1])
+    except (OSError, ValueError, IndexError):
+        return None
+    import os
+
+    return resident_pages * os.sysconf("SC_PAGE_SIZE")
+
+
+@pytest.mark.stress
+@pytest.mark.slow
+@pytest.mark.asyncio
+async def test_udf_text_blob_results_do_not_leak(test_db):
+    """Test RSS stays stable when UDFs return many large text/blob results."""
+    if _current_rss_bytes() is None:
+        pytest.skip("RSS measurement requires /proc/self/statm")
+
+    text_payload = "x" * 64 * 1024
+    blob_payload = b"\x01" * 64 * 1024
+
+    async with connect(test_db) as db:
+        await db.create_function("big_text", 0, lambda: text_payload)
+        await db.create_function("big_blob", 0, lambda: blob_payload)
+
+        async def run(iterations):
+            for _ in range(iterations):
+                row = await db.fetch_one(
+                    "SELECT length(big_text()), length(big_blob())"
+                )
+                assert row == [len(text_payload), len(blob_payload)]
+
+        # Warm up allocators and statement caches before measuring
+        await run(200)
+        gc.collect()
+        before = _current_rss_bytes()
+
+        # 2000 iterations * 128 KiB would leak ~250 MiB if results were not freed
+        await run(2000)
+        gc.collect()
+        growth = _current_rss_bytes() - before
+
+    assert growth < 64 * 1024 * 1024, f"RSS grew by {growth / 1024 / 1024:.1f} MiB"
+
+
 @pytest.mark.stress
 @pytest.mark.slow
 @pytest.mark.asyncio
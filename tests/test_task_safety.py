@@ -0,0 +1,158 @@
+"""Tests for the debug-mode task-safety audit (Connection(debug=True))."""
+
+import asyncio
+import warnings
+
+import pytest
+
+from rapsqlite import Connection, TaskSafetyWarning
+
+
+def _task_safety_messages(caught):
+    return [str(w.message) for w in caught if issubclass(w.category, TaskSafetyWarning)]
+
+
+@pytest.mark.asyncio
+async def test_commit_of_another_tasks_transaction_warns(test_db):
+    async with Connection(test_db, debug=True) as conn:
+        await conn.execute("CREATE TABLE t (id INTEGER PRIMARY KEY)")
+        began = asyncio.Event()
+        done = asyncio.Event()
+
+        async def owner():
+            await conn.begin()
+            began.set()
+            await done.wait()
+
+        async def intruder():
+            await began.wait()
+            await conn.execute("INSERT INTO t (id) VALUES (1)")
+            await conn.commit()
+            done.set()
+
+        with warnings.catch_warnings(record=True) as caught:
+            warnings.simplefilter("always")
+            await asyncio.gather(
+                asyncio.create_task(owner(), name="owner"),
+                asyncio.create_task(intruder(), name="intruder"),
+            )
+        messages = _task_safety_messages(caught)
+        assert len(messages) == 2
+        assert "'intruder' ran a statement inside the transaction begun by task 'owner'" in (
+            messages[0]
+        )
+        assert "'intruder' is about to commit the transaction begun by task 'owner'" in (
+            messages[1]
+        )
+
+
+@pytest.mark.asyncio
+async def test_setting_changed_by_another_task_warns(test_db):
+    async with Connection(test_db, debug=True) as conn:
+        await conn.execute("CREATE TABLE t (id INTEGER PRIMARY KEY)")
+        changed = asyncio.Event()
+
+        async def reader():
+            conn.row_factory = "dict"
+            await conn.fetch_all("SELECT * FROM t")
+            await changed.wait()
+            await conn.fetch_all("SELECT * FROM t")
+            await conn.fetch_all("SELECT * FROM t")
+
+        async def writer():
+            conn.row_factory = "tuple"
+            await conn.fetch_all("SELECT * FROM t")
+            changed.set()
+
+        with warnings.catch_warnings(record=True) as caught:
+            warnings.simplefilter("always")
+            await asyncio.gather(
+                asyncio.create_task(reader(), name="reader"),
+                asyncio.create_task(writer(), name="writer"),
+            )
+        messages = _task_safety_messages(caught)
+        assert len(messages) == 1
+        assert "'reader' set row_factory" in messages[0]
+        assert "task 'writer' changed it" in messages[0]
+
+
+@pytest.mark.asyncio
+async def test_statement_under_another_tasks_callback_warns(test_db):
+    async with Connection(test_db, debug=True) as conn:
+        await conn.execute("CREATE TABLE t (id INTEGER PRIMARY KEY)")
+        traced = []
+        installed = asyncio.Event()
+        done = asyncio.Event()
+
+        async def tracer():
+            await conn.set_trace_callback(traced.append)
+            await conn.fetch_all("SELECT * FROM t")
+            installed.set()
+            await done.wait()
+            await conn.set_trace_callback(None)
+
+        async def other():
+            await installed.wait()
+            await conn.fetch_all("SELECT id FROM t")
+            await conn.fetch_all("SELECT id FROM t")
+            done.set()
+
+        with warnings.catch_warnings(record=True) as caught:
+            warnings.simplefilter("always")
+            await asyncio.gather(
+                asyncio.create_task(tracer(), name="tracer"),
+                asyncio.create_task(other(), name="other"),
+            )
+        messages = _task_safety_messages(caught)
+        assert len(messages) == 1
+        assert "'other' ran a statement while the trace_callback installed by task 'tracer'" in (
+            messages[0]
+        )
+        assert "SELECT id FROM t" in traced
+
+        # Turned into an error, the warning stops the statement before it runs
+        await conn.set_authorizer(lambda *args: 0)
+        traced.clear()
+        with warnings.catch_warnings():
+            warnings.simplefilter("error", TaskSafetyWarning)
+
+            async def intruder():
+                await conn.fetch_all("SELECT id FROM t")
+
+            with pytest.raises(TaskSafetyWarning, match="authorizer"):
+                await asyncio.create_task(intruder())
+        await conn.set_authorizer(None)
+
+
+@pytest.mark.asyncio
+async def test_no_warning_for_own_transaction_or_without_debug(test_db):
+    async with Connection(test_db, debug=True) as conn:
+        await conn.execute("CREATE TABLE t (id INTEGER PRIMARY KEY)")
+        with warnings.catch_warnings(record=True) as caught:
+            warnings.simplefilter("always")
+            conn.row_factory = "dict"
+            async with conn.transaction():
+                await conn.execute("INSERT INTO t (id) VALUES (1)")
+            await conn.begin()
+            await conn.execute("INSERT INTO t (id) VALUES (2)")
+            await conn.rollback()
+            # Sequential tasks that each set what they use
+            for factory in ("tuple", "dict"):
+
+                async def use(factory=factory):
+                    conn.row_factory = factory
+                    await conn.fetch_all("SELECT * FROM t")
+
+                await asyncio.create_task(use())
+        assert _task_safety_messages(caught) == []
+
+    async with Connection(test_db) as conn:
+        with warnings.catch_warnings(record=True) as caught:
+            warnings.simplefilter("always")
+
+            async def owner():
+                await conn.begin()
+
+            await asyncio.create_task(owner())
+            await conn.commit()
+        assert _task_safety_messages(caught) == []
@@ -0,0 +1,47 @@
+"""Tests for Connection.begin_test_snapshot() / rollback_test_snapshot()."""
+
+import pytest
+
+from rapsqlite import Connection, OperationalError
+
+
+@pytest.mark.asyncio
+async def test_rollback_test_snapshot_resets_database(test_db):
+    async with Connection(test_db, cache="lru") as conn:
+        await conn.execute("CREATE TABLE t (id INTEGER PRIMARY KEY, name TEXT)")
+        await conn.execute("INSERT INTO t (name) VALUES ('seed')")
+        await conn.begin_test_snapshot()
+
+        for test_run in range(2):
+            # A "test" changing data and schema
+            await conn.execute("INSERT INTO t (name) VALUES ('test')")
+            await conn.execute("CREATE TABLE scratch (x)")
+            assert len(await conn.fetch_all("SELECT * FROM t")) == 2
+
+            await conn.rollback_test_snapshot()
+
+            # The cached result of the query above is not served
+            assert await conn.fetch_all("SELECT * FROM t") == [[1, "seed"]]
+            tables = await conn.get_tables()
+            assert tables == ["t"]
+
+        # Other connections see the restored contents
+        async with Connection(test_db) as other:
+            assert await other.fetch_all("SELECT name FROM t") == [["seed"]]
+
+
+@pytest.mark.asyncio
+async def test_test_snapshot_errors(test_db):
+    async with Connection(test_db) as conn:
+        await conn.execute("CREATE TABLE t (id INTEGER PRIMARY KEY)")
+        with pytest.raises(OperationalError, match="begin_test_snapshot"):
+            await conn.rollback_test_snapshot()
+        await conn.begin()
+        with pytest.raises(OperationalError, match="inside a transaction"):
+            await conn.begin_test_snapshot()
+        await conn.rollback()
+        await conn.begin_test_snapshot()
+        await conn.begin()
+        with pytest.raises(OperationalError, match="inside a transaction"):
+            await conn.rollback_test_snapshot()
+        await conn.rollback()
@@ -32,3 +32,32 @@ async def test_text_factory_none_uses_default_utf8(test_db):
         db.text_factory = None
         row = await db.fetch_one("SELECT v FROM t")
         assert row[0] == "hello"
+
+
+@pytest.mark.asyncio
+async def test_invalid_utf8_policy(test_db):
+    async with rapsqlite.connect(test_db) as db:
+        await db.execute("CREATE TABLE t (v TEXT)")
+        await db.execute("INSERT INTO t (v) VALUES (CAST(X'61FF62' AS TEXT))")
+        await db.create_function("echo", 1, lambda v: repr(v))
+
+        assert db.invalid_utf8_policy == "bytes"
+        assert await db.fetch_one("SELECT v FROM t") == [b"a\xffb"]
+        assert await db.fetch_one("SELECT echo(v) FROM t") == ["b'a\\xffb'"]
+
+        db.invalid_utf8_policy = "replace"
+        assert await db.fetch_one("SELECT v FROM t") == ["a�b"]
+        assert await db.fetch_one("SELECT echo(v) FROM t") == ["'a�b'"]
+
+        db.invalid_utf8_policy = "error"
+        with pytest.raises(rapsqlite.OperationalError, match="UTF-8"):
+            await db.fetch_one("SELECT v FROM t")
+        with pytest.raises(rapsqlite.DatabaseError, match="UTF-8"):
+            await db.fetch_one("SELECT echo(v) FROM t")
+
+        # text_factory always receives the raw bytes
+        db.text_factory = lambda raw: raw
+        assert await db.fetch_one("SELECT v FROM t") == [b"a\xffb"]
+
+        with pytest.raises(ValueError):
+            db.invalid_utf8_policy = "ignore"
@@ -0,0 +1,63 @@
+"""Tests for Connection.set_update_hook()."""
+
+import asyncio
+
+import pytest
+
+import rapsqlite
+from rapsqlite import connect
+
+
+@pytest.mark.asyncio
+async def test_update_hook_reports_row_changes(test_db):
+    async with connect(test_db) as db:
+        await db.execute("CREATE TABLE items (id INTEGER PRIMARY KEY, name TEXT)")
+        events = []
+        await db.set_update_hook(lambda *event: events.append(event))
+
+        await db.execute("INSERT INTO items (id, name) VALUES (1, 'a'), (2, 'b')")
+        await db.execute("UPDATE items SET name = 'c' WHERE id = 2")
+        await db.begin()
+        await db.execute("DELETE FROM items WHERE id = 1")
+        await db.commit()
+
+        assert events == [
+            (rapsqlite.SQLITE_INSERT, "main", "items", 1),
+            (rapsqlite.SQLITE_INSERT, "main", "items", 2),
+            (rapsqlite.SQLITE_UPDATE, "main", "items", 2),
+            (rapsqlite.SQLITE_DELETE, "main", "items", 1),
+        ]
+
+        # Cleared hooks see nothing more
+        await db.set_update_hook(None)
+        await db.execute("INSERT INTO items (id, name) VALUES (3, 'd')")
+        assert len(events) == 4
+
+
+@pytest.mark.asyncio
+async def test_update_hook_errors_do_not_fail_statements(test_db):
+    async with connect(test_db) as db:
+        await db.execute("CREATE TABLE items (id INTEGER PRIMARY KEY)")
+
+        def broken(*event):
+            raise RuntimeError("hook failed")
+
+        await db.set_update_hook(broken)
+        await db.execute("INSERT INTO items (id) VALUES (1)")
+        assert (await db.fetch_one("SELECT COUNT(*) FROM items"))[0] == 1
+
+
+@pytest.mark.asyncio
+async def test_update_hook_in_thread_mode(test_db):
+    async with connect(test_db) as db:
+        await db.execute("CREATE TABLE items (id INTEGER PRIMARY KEY)")
+        db.callback_mode = "thread"
+        events = []
+        await db.set_update_hook(lambda *event: events.append(event))
+        await db.execute("INSERT INTO items (id) VALUES (7)")
+        # Thread mode queues the call without waiting for it
+        for _ in range(50):
+            if events:
+                break
+            await asyncio.sleep(0.01)
+        assert events == [(rapsqlite.SQLITE_INSERT, "main", "items", 7)]
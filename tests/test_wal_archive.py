@@ -0,0 +1,70 @@
+"""Tests for WAL archiving (Connection(wal_archive=...)) and restore_to()."""
+
+import datetime
+import os
+import time
+
+import pytest
+
+import rapsqlite
+from rapsqlite import Connection, OperationalError, connect
+
+
+@pytest.mark.asyncio
+async def test_restore_to_point_in_time(test_db, tmp_path):
+    archive = str(tmp_path / "archive")
+    async with connect(test_db, wal_archive=archive) as conn:
+        assert await conn.fetch_one("PRAGMA journal_mode") == ["wal"]
+        await conn.execute("CREATE TABLE t (id INTEGER PRIMARY KEY, v TEXT)")
+        await conn.execute_many(
+            "INSERT INTO t (v) VALUES (?)", [[f"row{i}"] for i in range(100)]
+        )
+        before_delete = time.time()
+        time.sleep(0.01)
+        await conn.execute("DELETE FROM t WHERE id > 10")
+
+    restored = str(tmp_path / "restored.db")
+    await rapsqlite.restore_to(restored, archive, before_delete)
+    async with Connection(restored) as conn:
+        assert await conn.fetch_one("SELECT count(*) FROM t") == [100]
+
+    latest = str(tmp_path / "latest.db")
+    await rapsqlite.restore_to(
+        latest, archive, datetime.datetime.now(datetime.timezone.utc)
+    )
+    async with Connection(latest) as conn:
+        assert await conn.fetch_one("SELECT count(*) FROM t") == [10]
+
+    with pytest.raises(OperationalError, match="exists"):
+        await rapsqlite.restore_to(latest, archive)
+    with pytest.raises(OperationalError, match="no base snapshot"):
+        await rapsqlite.restore_to(str(tmp_path / "early.db"), archive, 0)
+
+
+@pytest.mark.asyncio
+async def test_archive_continues_across_reopen(test_db, tmp_path):
+    archive = str(tmp_path / "archive")
+    async with Connection(test_db, wal_archive=archive) as conn:
+        await conn.execute("CREATE TABLE t (x INTEGER)")
+        await conn.execute("INSERT INTO t VALUES (1)")
+    async with Connection(test_db, wal_archive=archive) as conn:
+        await conn.execute("INSERT INTO t VALUES (2)")
+        async with conn.transaction():
+            await conn.execute("INSERT INTO t VALUES (3)")
+
+    restored = str(tmp_path / "restored.db")
+    await rapsqlite.restore_to(restored, archive)
+    async with Connection(restored) as conn:
+        assert await conn.fetch_all("SELECT x FROM t ORDER BY x") == [[1], [2], [3]]
+    assert any(name.startswith("base-") for name in os.listdir(archive))
+
+
+def test_wal_archive_validation(tmp_path):
+    with pytest.raises(ValueError):
+        Connection(":memory:", wal_archive=str(tmp_path))
+    with pytest.raises(ValueError):
+        Connection(
+            str(tmp_path / "x.db"),
+            wal_archive=str(tmp_path),
+            pragmas={"journal_mode": "DELETE"},
+        )